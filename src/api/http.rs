@@ -0,0 +1,680 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::ApiError;
+
+/// Largest body accepted in an HTTP request.
+pub const MAX_BODY_LENGTH: usize = 1024 * 1024;
+
+/// Largest request line or header line accepted in an HTTP request.
+const MAX_LINE_LENGTH: usize = 8 * 1024;
+
+/// Largest number of headers accepted in an HTTP request.
+const MAX_HEADERS: usize = 100;
+
+/// Time a client has to send its whole request, however slowly it sends it, and to read the response.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest number of connections served at the same time by a server.
+pub const MAX_CONNECTIONS: usize = 64;
+
+/// HTTP request structure
+/// It represents an HTTP/1.1 request read from a connection: its method, its target and the headers and body sent with it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    method: String,
+    target: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+/// HTTP request implementation.
+impl HttpRequest {
+    ///
+    /// Read an HTTP request from a reader. The body is read only if its length is given by a Content-Length header.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A BufRead that represents the connection the request is read from.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::api::HttpRequest;
+    /// let mut reader: &[u8] = b"POST /rpc HTTP/1.1\r\nContent-Length: 2\r\n\r\n{}";
+    /// let request = HttpRequest::read_from(&mut reader).unwrap();
+    /// # assert_eq!(request.get_method(), "POST");
+    /// # assert_eq!(request.get_path(), "/rpc");
+    /// # assert_eq!(request.get_body(), b"{}")
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An IoError is returned if the request can not be read, a MalformedRequestError if it is not
+    /// a valid HTTP request and a PayloadTooLargeError if its body is longer than MAX_BODY_LENGTH.
+    ///
+    pub fn read_from<R: BufRead>(
+        reader: &mut R,
+    ) -> Result<HttpRequest, ApiError> {
+        let request_line = read_line(reader)?;
+        let mut parts = request_line.split_whitespace();
+        let (method, target) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(method), Some(target), Some(version), None) if version.starts_with("HTTP/1.") =>
+                (method.to_string(), target.to_string()),
+            _ => return Err(malformed(&format!("invalid request line '{}'", request_line))),
+        };
+
+        let mut headers = Vec::new();
+        loop {
+            let line = read_line(reader)?;
+            if line.is_empty() {
+                break;
+            }
+            if headers.len() == MAX_HEADERS {
+                return Err(malformed("too many headers"));
+            }
+            match line.split_once(':') {
+                Some((name, value)) => headers.push((name.trim().to_string(), value.trim().to_string())),
+                None => return Err(malformed(&format!("invalid header '{}'", line))),
+            }
+        }
+
+        let mut request = HttpRequest {
+            method,
+            target,
+            headers,
+            body: Vec::new(),
+        };
+        if request.get_header("Transfer-Encoding").is_some() {
+            return Err(malformed("chunked bodies are not supported, a Content-Length header is required"));
+        }
+        let length = match request.get_header("Content-Length") {
+            Some(length) => length
+                .parse::<usize>()
+                .map_err(|_| malformed(&format!("invalid Content-Length '{}'", length)))?,
+            None => 0,
+        };
+        if length > MAX_BODY_LENGTH {
+            return Err(ApiError::PayloadTooLargeError { length, limit: MAX_BODY_LENGTH });
+        }
+        request.body = vec![0; length];
+        reader.read_exact(&mut request.body)?;
+        Ok(request)
+    }
+
+    ///
+    /// Return the method of the request, e.g. "POST".
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::HttpRequest;
+    /// # let request = HttpRequest::read_from(&mut &b"GET /entities HTTP/1.1\r\n\r\n"[..]).unwrap();
+    /// let method : &str = request.get_method();
+    /// # assert_eq!(method, "GET")
+    /// ```
+    ///
+    pub fn get_method(
+        self: &HttpRequest,
+    ) -> &str {
+        &self.method
+    }
+
+    ///
+    /// Return the path of the target of the request, without its query.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::HttpRequest;
+    /// # let request = HttpRequest::read_from(&mut &b"GET /entities?state=Failed HTTP/1.1\r\n\r\n"[..]).unwrap();
+    /// let path : &str = request.get_path();
+    /// # assert_eq!(path, "/entities")
+    /// ```
+    ///
+    pub fn get_path(
+        self: &HttpRequest,
+    ) -> &str {
+        match self.target.split_once('?') {
+            Some((path, _)) => path,
+            None => &self.target,
+        }
+    }
+
+    ///
+    /// Return the query of the target of the request, without the leading '?', if any.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::HttpRequest;
+    /// # let request = HttpRequest::read_from(&mut &b"GET /entities?state=Failed HTTP/1.1\r\n\r\n"[..]).unwrap();
+    /// let query : Option<&str> = request.get_query();
+    /// # assert_eq!(query, Some("state=Failed"))
+    /// ```
+    ///
+    pub fn get_query(
+        self: &HttpRequest,
+    ) -> Option<&str> {
+        self.target.split_once('?').map(|(_, query)| query)
+    }
+
+    ///
+    /// Return the decoded names and values of the parameters of the query, in order.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::HttpRequest;
+    /// # let request = HttpRequest::read_from(&mut &b"GET /entities?state=Failed&note=a%20b+c HTTP/1.1\r\n\r\n"[..]).unwrap();
+    /// let parameters : Vec<(String, String)> = request.get_query_parameters().unwrap();
+    /// # assert_eq!(parameters[0], ("state".to_string(), "Failed".to_string()));
+    /// # assert_eq!(parameters[1], ("note".to_string(), "a b c".to_string()))
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A MalformedRequestError is returned if a name or a value is not correctly percent-encoded.
+    ///
+    pub fn get_query_parameters(
+        self: &HttpRequest,
+    ) -> Result<Vec<(String, String)>, ApiError> {
+        self.get_query()
+            .unwrap_or("")
+            .split('&')
+            .filter(|parameter| !parameter.is_empty())
+            .map(|parameter| {
+                let (name, value) = parameter.split_once('=').unwrap_or((parameter, ""));
+                Ok((decode_query_component(name)?, decode_query_component(value)?))
+            })
+            .collect()
+    }
+
+    ///
+    /// Return the decoded segments of the path, without the empty ones.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::HttpRequest;
+    /// # let request = HttpRequest::read_from(&mut &b"GET /entities/entity%20001/transactions HTTP/1.1\r\n\r\n"[..]).unwrap();
+    /// let segments : Vec<String> = request.get_path_segments().unwrap();
+    /// # assert_eq!(segments, vec!["entities", "entity 001", "transactions"])
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A MalformedRequestError is returned if a segment is not correctly percent-encoded.
+    ///
+    pub fn get_path_segments(
+        self: &HttpRequest,
+    ) -> Result<Vec<String>, ApiError> {
+        self.get_path()
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(percent_decode)
+            .collect()
+    }
+
+    ///
+    /// Return the value of the first header with the given name, which is compared ignoring the case.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A str that represents the name of the header.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::HttpRequest;
+    /// # let request = HttpRequest::read_from(&mut &b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n"[..]).unwrap();
+    /// let host : Option<&str> = request.get_header("host");
+    /// # assert_eq!(host, Some("localhost"))
+    /// ```
+    ///
+    pub fn get_header(
+        self: &HttpRequest,
+        name: &str,
+    ) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    ///
+    /// Return the body of the request.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::HttpRequest;
+    /// # let request = HttpRequest::read_from(&mut &b"GET / HTTP/1.1\r\n\r\n"[..]).unwrap();
+    /// let body : &[u8] = request.get_body();
+    /// # assert!(body.is_empty())
+    /// ```
+    ///
+    pub fn get_body(
+        self: &HttpRequest,
+    ) -> &[u8] {
+        &self.body
+    }
+}
+
+/// HTTP response structure
+/// It represents an HTTP/1.1 response: a status code and an optional JSON body.
+/// The connection is closed once the response is written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+/// HTTP response implementation.
+impl HttpResponse {
+    ///
+    /// Return a new HttpResponse with the given status code and JSON body.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - A u16 integer that represents the status code of the response.
+    /// * `body` - A Value that represents the JSON body of the response.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::api::HttpResponse;
+    /// let response = HttpResponse::json(200, &serde_json::json!({ "balance": "100" }));
+    /// # assert_eq!(response.get_status(), 200);
+    /// # assert_eq!(response.get_body(), b"{\"balance\":\"100\"}")
+    /// ```
+    ///
+    pub fn json(
+        status: u16,
+        body: &serde_json::Value,
+    ) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: body.to_string().into_bytes(),
+        }
+    }
+
+    ///
+    /// Return a new HttpResponse with the given status code and no body.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - A u16 integer that represents the status code of the response.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::api::HttpResponse;
+    /// let response = HttpResponse::empty(204);
+    /// # assert!(response.get_body().is_empty())
+    /// ```
+    ///
+    pub fn empty(
+        status: u16,
+    ) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    ///
+    /// Return the response with a header added.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A str that represents the name of the header.
+    /// * `value` - A str that represents the value of the header.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::api::HttpResponse;
+    /// let response = HttpResponse::empty(405).with_header("Allow", "POST");
+    /// # assert_eq!(response.get_header("allow"), Some("POST"))
+    /// ```
+    ///
+    pub fn with_header(
+        mut self: HttpResponse,
+        name: &str,
+        value: &str,
+    ) -> HttpResponse {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    ///
+    /// Return the status code of the response.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::HttpResponse;
+    /// # let response = HttpResponse::empty(204);
+    /// let status : u16 = response.get_status();
+    /// # assert_eq!(status, 204)
+    /// ```
+    ///
+    pub fn get_status(
+        self: &HttpResponse,
+    ) -> u16 {
+        self.status
+    }
+
+    ///
+    /// Return the value of the first header with the given name, which is compared ignoring the case.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A str that represents the name of the header.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::HttpResponse;
+    /// # let response = HttpResponse::json(200, &serde_json::json!({}));
+    /// let content_type : Option<&str> = response.get_header("Content-Type");
+    /// # assert_eq!(content_type, Some("application/json"))
+    /// ```
+    ///
+    pub fn get_header(
+        self: &HttpResponse,
+        name: &str,
+    ) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    ///
+    /// Return the body of the response.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::HttpResponse;
+    /// # let response = HttpResponse::json(200, &serde_json::json!([]));
+    /// let body : &[u8] = response.get_body();
+    /// # assert_eq!(body, b"[]")
+    /// ```
+    ///
+    pub fn get_body(
+        self: &HttpResponse,
+    ) -> &[u8] {
+        &self.body
+    }
+
+    ///
+    /// Write the response to a writer, asking the client to close the connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - A Write that represents the connection the response is written to.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::HttpResponse;
+    /// let mut buffer: Vec<u8> = Vec::new();
+    /// HttpResponse::empty(204).write_to(&mut buffer).unwrap();
+    /// # assert!(buffer.starts_with(b"HTTP/1.1 204 No Content\r\n"))
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An IoError is returned if the response can not be written.
+    ///
+    pub fn write_to<W: Write>(
+        self: &HttpResponse,
+        writer: &mut W,
+    ) -> Result<(), ApiError> {
+        let mut head = format!("HTTP/1.1 {} {}\r\n", self.status, reason_phrase(self.status));
+        for (name, value) in &self.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str(&format!("Content-Length: {}\r\nConnection: close\r\n\r\n", self.body.len()));
+        writer.write_all(head.as_bytes())?;
+        writer.write_all(&self.body)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// HTTP server structure
+/// It listens on a TCP port and answers every request with the response returned by its handler, which is
+/// also given the requests that could not be read. Every connection is served by its own thread and carries
+/// a single request, which must be received within REQUEST_TIMEOUT. At most MAX_CONNECTIONS connections are served
+/// at the same time: the other ones are answered with a 503 status at once. The server stops when it is shut down or dropped.
+pub struct HttpServer {
+    address: SocketAddr,
+    stopped: Arc<AtomicBool>,
+}
+
+/// HTTP server implementation.
+impl HttpServer {
+    ///
+    /// Return a new HttpServer listening on the given address and answering the requests with the given handler.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - A str that represents the address to listen on, e.g. "127.0.0.1:0" for any free port.
+    /// * `handler` - A function that returns the response to a request, or to the error met reading it.
+    ///
+    /// # Errors
+    ///
+    /// Return an IoError if the server can not listen on the address.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::api::{HttpResponse, HttpServer};
+    /// let server = HttpServer::start("127.0.0.1:0", |_| HttpResponse::empty(204)).unwrap();
+    /// # assert!(server.get_address().starts_with("127.0.0.1:"))
+    /// ```
+    ///
+    pub fn start<H>(
+        address: &str,
+        handler: H,
+    ) -> Result<HttpServer, ApiError>
+        where H: Fn(Result<HttpRequest, ApiError>) -> HttpResponse + Send + Sync + 'static {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let stopped = Arc::new(AtomicBool::new(false));
+        let accepting_stopped = Arc::clone(&stopped);
+        let handler = Arc::new(handler);
+        let connections = Arc::new(AtomicUsize::new(0));
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if accepting_stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    if connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                        connections.fetch_sub(1, Ordering::SeqCst);
+                        let _ = refuse_client(stream);
+                        continue;
+                    }
+                    let handler = Arc::clone(&handler);
+                    let connections = Arc::clone(&connections);
+                    thread::spawn(move || {
+                        let _ = serve_client(&*handler, stream);
+                        connections.fetch_sub(1, Ordering::SeqCst);
+                    });
+                }
+            }
+        });
+        Ok(HttpServer {
+            address,
+            stopped,
+        })
+    }
+
+    ///
+    /// Return the address the server listens on.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::{HttpResponse, HttpServer};
+    /// # let server = HttpServer::start("127.0.0.1:0", |_| HttpResponse::empty(204)).unwrap();
+    /// let address : String = server.get_address();
+    /// # assert!(address.starts_with("127.0.0.1:"))
+    /// ```
+    ///
+    pub fn get_address(
+        self: &HttpServer,
+    ) -> String {
+        self.address.to_string()
+    }
+
+    ///
+    /// Stop the server: it stops accepting connections. The requests being served are completed.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::{HttpResponse, HttpServer};
+    /// let server = HttpServer::start("127.0.0.1:0", |_| HttpResponse::empty(204)).unwrap();
+    /// server.shutdown();
+    /// ```
+    ///
+    pub fn shutdown(
+        self: &HttpServer,
+    ) {
+        if self.stopped.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        // Wake up the thread blocked on accepting connections, so that it sees the server is stopped
+        let _ = TcpStream::connect(self.address);
+    }
+}
+
+/// A server stops when it is dropped.
+impl Drop for HttpServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Read the request of a client and write the response of the handler.
+fn serve_client<H>(
+    handler: &H,
+    stream: TcpStream,
+) -> Result<(), ApiError>
+    where H: Fn(Result<HttpRequest, ApiError>) -> HttpResponse {
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut reader = BufReader::new(DeadlineReader {
+        stream: stream.try_clone()?,
+        deadline: Instant::now() + REQUEST_TIMEOUT,
+    });
+    handler(HttpRequest::read_from(&mut reader)).write_to(&mut &stream)
+}
+
+/// Answer a client beyond the connections a server serves at the same time, without reading its request.
+fn refuse_client(
+    stream: TcpStream,
+) -> Result<(), ApiError> {
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    HttpResponse::empty(503).write_to(&mut &stream)?;
+    // Discard what the client already sent, so that closing the connection does not reset it before the response is read
+    stream.shutdown(Shutdown::Write)?;
+    stream.set_nonblocking(true)?;
+    let mut buffer = [0; 1024];
+    while let Ok(length) = (&stream).read(&mut buffer) {
+        if length == 0 {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Reader of a connection failing once the deadline of the request is passed,
+/// so that a client sending its request a byte at a time can not hold the connection.
+struct DeadlineReader {
+    stream: TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "the request was not received in time"));
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buffer)
+    }
+}
+
+/// Decode a percent-encoded component of a query, where '+' stands for a space.
+fn decode_query_component(
+    component: &str,
+) -> Result<String, ApiError> {
+    percent_decode(&component.replace('+', " "))
+}
+
+/// Decode a percent-encoded string.
+fn percent_decode(
+    value: &str,
+) -> Result<String, ApiError> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let byte = bytes
+                .get(index + 1..index + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| malformed(&format!("invalid percent-encoding in '{}'", value)))?;
+            decoded.push(byte);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| malformed(&format!("'{}' is not valid UTF-8 once decoded", value)))
+}
+
+/// Read a line terminated by CRLF or LF, without its terminator.
+fn read_line<R: BufRead>(
+    reader: &mut R,
+) -> Result<String, ApiError> {
+    let mut line = Vec::new();
+    reader.take(MAX_LINE_LENGTH as u64 + 1).read_until(b'\n', &mut line)?;
+    if line.last() != Some(&b'\n') {
+        return Err(match line.len() {
+            0 => malformed("the connection was closed before the end of the request"),
+            length if length > MAX_LINE_LENGTH => malformed("line too long"),
+            _ => malformed("the connection was closed in the middle of a line"),
+        });
+    }
+    line.pop();
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    String::from_utf8(line).map_err(|_| malformed("the request is not valid UTF-8"))
+}
+
+/// Return a MalformedRequestError with the given message.
+fn malformed(
+    message: &str,
+) -> ApiError {
+    ApiError::MalformedRequestError { message: message.to_string() }
+}
+
+/// Return the reason phrase of the status codes used by the servers.
+fn reason_phrase(
+    status: u16,
+) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        422 => "Unprocessable Entity",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "",
+    }
+}
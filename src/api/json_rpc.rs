@@ -0,0 +1,503 @@
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+
+use crate::api::ServerOptions;
+use crate::cli::{run_command_with_audit, Command, TransferSignature};
+use crate::error::{CliError, WebhookError};
+use crate::model::{Amount, Entity};
+use crate::storage::Storage;
+use crate::webhook::WebhookDispatcher;
+
+/// Version of the JSON-RPC protocol spoken by the server.
+pub const JSON_RPC_VERSION: &str = "2.0";
+
+/// Error code of a request body that is not valid JSON.
+pub const PARSE_ERROR: i64 = -32700;
+/// Error code of a request that is not a valid JSON-RPC request object.
+pub const INVALID_REQUEST: i64 = -32600;
+/// Error code of a request naming an unknown method.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// Error code of a request whose params do not match the method.
+pub const INVALID_PARAMS: i64 = -32602;
+/// Error code of a failure of the server itself, e.g. of its storage.
+pub const INTERNAL_ERROR: i64 = -32603;
+/// Error code of a request naming an entity that does not exist.
+pub const ENTITY_NOT_FOUND: i64 = 1001;
+/// Error code of a request creating an entity that already exists.
+pub const DUPLICATE_ENTITY: i64 = 1002;
+/// Error code of a request naming a transaction that does not exist.
+pub const TRANSACTION_NOT_FOUND: i64 = 1003;
+/// Error code of a wallet or entity operation that failed, e.g. a withdrawal beyond the overdraft policy.
+pub const ENTITY_OPERATION_FAILED: i64 = 1004;
+/// Error code of a transaction that can not be created, e.g. because of a negative amount.
+pub const TRANSACTION_OPERATION_FAILED: i64 = 1005;
+/// Error code of a transfer that failed or was refused.
+pub const TRANSFER_FAILED: i64 = 1006;
+/// Error code of a change of a webhook endpoint that was refused, e.g. because it is not signed by the entity.
+pub const WEBHOOK_OPERATION_FAILED: i64 = 1007;
+
+/// JSON-RPC error object structure
+/// It represents the error member of a JSON-RPC response. The data member carries the serde representation
+/// of the error of the library, so that its fields, e.g. the current and removed amounts of a wallet error, are kept.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcErrorObject {
+    code: i64,
+    message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+}
+
+/// JSON-RPC error object implementation.
+impl RpcErrorObject {
+    ///
+    /// Return a new RpcErrorObject.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - An i64 integer that represents the error code, e.g. METHOD_NOT_FOUND.
+    /// * `message` - A String that represents the description of the error.
+    /// * `data` - An optional Value that represents the details of the error.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::api::{RpcErrorObject, METHOD_NOT_FOUND};
+    /// let error = RpcErrorObject::new(
+    ///     METHOD_NOT_FOUND,
+    ///     "Method not found: wallet_burn".to_string(),
+    ///     None
+    /// );
+    /// # assert_eq!(error.get_code(), METHOD_NOT_FOUND)
+    /// ```
+    ///
+    pub fn new(
+        code: i64,
+        message: String,
+        data: Option<Value>,
+    ) -> RpcErrorObject {
+        RpcErrorObject {
+            code,
+            message,
+            data,
+        }
+    }
+
+    ///
+    /// Return the RpcErrorObject of an error of a command: the code depends on the kind of error,
+    /// the message is its description and the data is its serde representation.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - A CliError that represents the error of the command.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::api::{RpcErrorObject, ENTITY_NOT_FOUND};
+    /// use banana_coin::error::CliError;
+    /// let error = RpcErrorObject::from_cli_error(&CliError::EntityNotFoundError { entity_id: "entity_001".to_string() });
+    /// # assert_eq!(error.get_code(), ENTITY_NOT_FOUND);
+    /// # assert_eq!(error.get_data().unwrap()["EntityNotFoundError"]["entity_id"], "entity_001")
+    /// ```
+    ///
+    pub fn from_cli_error(
+        error: &CliError,
+    ) -> RpcErrorObject {
+        let code = match error {
+            CliError::UsageError { .. } | CliError::ReservedEntityIdError { .. } => INVALID_PARAMS,
+            CliError::DuplicateEntityError { .. } => DUPLICATE_ENTITY,
+            CliError::EntityNotFoundError { .. } => ENTITY_NOT_FOUND,
+            CliError::TransactionNotFoundError { .. } => TRANSACTION_NOT_FOUND,
+            CliError::CryptoOperationError { .. } => INVALID_PARAMS,
+            CliError::EntityOperationError { .. } | CliError::PublicKeyReplacementError { .. } => ENTITY_OPERATION_FAILED,
+            CliError::TransactionOperationError { .. } => TRANSACTION_OPERATION_FAILED,
+            CliError::TransferOperationError { .. } => TRANSFER_FAILED,
+            CliError::StorageOperationError { .. } | CliError::AuditOperationError { .. } => INTERNAL_ERROR,
+        };
+        RpcErrorObject::new(code, error.to_string(), serde_json::to_value(error).ok())
+    }
+
+    ///
+    /// Return the RpcErrorObject of an error of a webhook method: INVALID_PARAMS for an invalid url,
+    /// WEBHOOK_OPERATION_FAILED otherwise. The message is its description and the data is its serde representation.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - A WebhookError that represents the error of the method.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::api::{RpcErrorObject, WEBHOOK_OPERATION_FAILED};
+    /// use banana_coin::error::WebhookError;
+    /// let error = RpcErrorObject::from_webhook_error(&WebhookError::EndpointNotFoundError { entity_id: "entity_001".to_string() });
+    /// # assert_eq!(error.get_code(), WEBHOOK_OPERATION_FAILED);
+    /// # assert_eq!(error.get_data().unwrap()["EndpointNotFoundError"]["entity_id"], "entity_001")
+    /// ```
+    ///
+    pub fn from_webhook_error(
+        error: &WebhookError,
+    ) -> RpcErrorObject {
+        let code = match error {
+            WebhookError::InvalidUrlError { .. } => INVALID_PARAMS,
+            _ => WEBHOOK_OPERATION_FAILED,
+        };
+        RpcErrorObject::new(code, error.to_string(), serde_json::to_value(error).ok())
+    }
+
+    ///
+    /// Return the error code.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::{RpcErrorObject, PARSE_ERROR};
+    /// # let error = RpcErrorObject::new(PARSE_ERROR, "Parse error".to_string(), None);
+    /// let code : i64 = error.get_code();
+    /// # assert_eq!(code, PARSE_ERROR)
+    /// ```
+    ///
+    pub fn get_code(
+        self: &RpcErrorObject,
+    ) -> i64 {
+        self.code
+    }
+
+    ///
+    /// Return the description of the error.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::{RpcErrorObject, PARSE_ERROR};
+    /// # let error = RpcErrorObject::new(PARSE_ERROR, "Parse error".to_string(), None);
+    /// let message : &str = error.get_message();
+    /// # assert_eq!(message, "Parse error")
+    /// ```
+    ///
+    pub fn get_message(
+        self: &RpcErrorObject,
+    ) -> &str {
+        &self.message
+    }
+
+    ///
+    /// Return the details of the error, if any.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::{RpcErrorObject, PARSE_ERROR};
+    /// # let error = RpcErrorObject::new(PARSE_ERROR, "Parse error".to_string(), None);
+    /// let data : Option<&serde_json::Value> = error.get_data();
+    /// # assert!(data.is_none())
+    /// ```
+    ///
+    pub fn get_data(
+        self: &RpcErrorObject,
+    ) -> Option<&Value> {
+        self.data.as_ref()
+    }
+}
+
+/// Params of the entity_create method.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EntityCreateParams {
+    entity_id: String,
+    name: String,
+    #[serde(default)]
+    public_key: Option<String>,
+}
+
+/// Params of the wallet_getBalance method.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WalletGetBalanceParams {
+    entity_id: String,
+}
+
+/// Params of the transfer_submit method.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TransferSubmitParams {
+    from_entity_id: String,
+    to_entity_id: String,
+    amount: Amount,
+    #[serde(default)]
+    transaction_id: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    nonce: Option<u64>,
+    signature: String,
+}
+
+/// Params of the tx_get method.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TxGetParams {
+    transaction_id: String,
+}
+
+/// Params of the tx_history method.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TxHistoryParams {
+    #[serde(default)]
+    entity_id: Option<String>,
+}
+
+/// Params of the webhook_register method.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WebhookRegisterParams {
+    entity_id: String,
+    url: String,
+    secret: String,
+    signature: String,
+}
+
+/// Params of the webhook_unregister method.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WebhookUnregisterParams {
+    entity_id: String,
+    signature: String,
+}
+
+///
+/// Handle the body of a JSON-RPC 2.0 request against a storage and return the body of the response,
+/// or None if the request is made of notifications only.
+/// A batch request, i.e. an array of requests, is answered with an array of responses.
+///
+/// The methods take their params by name:
+/// * `entity_create` - `entity_id`, `name` and an optional `public_key`, hex encoded, which the transfers
+///   of the entity are checked against; returns the new entity, whose wallet is empty. The clients can not
+///   create coins: they are deposited by the operator only, with the command-line tool.
+/// * `wallet_getBalance` - `entity_id`; returns the `entity_id` and the `balance`.
+/// * `transfer_submit` - `from_entity_id`, `to_entity_id`, `amount`, `transaction_id`, an optional `description`,
+///   an optional `nonce`, the next one of the sender by default, and the `signature` of the transaction by the sender,
+///   hex encoded; returns the completed transaction.
+/// * `tx_get` - `transaction_id`; returns the transaction.
+/// * `tx_history` - an optional `entity_id`; returns the transactions, of the entity if given.
+/// * `webhook_register` - `entity_id`, the `url` of the endpoint notified of the transactions of the entity, the `secret`
+///   the notifications are signed with and the `signature` of the registration by the entity, hex encoded,
+///   see 'sign_registration'; returns the `entity_id` and the `url`. It replaces the previous endpoint of the entity.
+/// * `webhook_unregister` - `entity_id` and the `signature` of the removal by the entity; returns the `entity_id`.
+///
+/// The webhook methods are found only if the options have a webhook dispatcher.
+///
+/// # Arguments
+///
+/// * `storage` - A Storage that represents the data the methods work against.
+/// * `body` - A slice of bytes that represents the body of the HTTP request.
+/// * `timestamp` - A u64 integer that represents the timestamp of the state changes of new transactions.
+/// * `options` - A ServerOptions that represents the event bus the changes of the methods are published on
+///   once they are stored and the audit log, if any, they are appended to once stored.
+///   A method whose changes can not be audited fails, although they are stored.
+///
+/// # Example
+/// ```
+/// use banana_coin::api::{handle_json_rpc, ServerOptions};
+/// use banana_coin::event::EventBus;
+/// use banana_coin::storage::MemoryStorage;
+/// let mut storage = MemoryStorage::new();
+/// let event_bus = EventBus::new();
+/// let receiver = event_bus.subscribe_channel();
+/// let options = ServerOptions::new().with_event_bus(event_bus);
+/// let body = br#"{"jsonrpc": "2.0", "method": "entity_create", "params": {"entity_id": "entity_001", "name": "john"}, "id": 1}"#;
+/// let response = handle_json_rpc(&mut storage, body, 1, &options).unwrap();
+/// # assert_eq!(response["id"], 1);
+/// # assert_eq!(response["result"]["name"], "john");
+/// # assert_eq!(receiver.try_recv().unwrap().get_name(), "EntityCreated")
+/// ```
+///
+pub fn handle_json_rpc<S: Storage>(
+    storage: &mut S,
+    body: &[u8],
+    timestamp: u64,
+    options: &ServerOptions,
+) -> Option<Value> {
+    let request: Value = match serde_json::from_slice(body) {
+        Ok(request) => request,
+        Err(error) => {
+            let error = RpcErrorObject::new(PARSE_ERROR, format!("Parse error: {}", error), None);
+            return Some(error_response(Value::Null, error));
+        }
+    };
+    match request {
+        Value::Array(requests) if requests.is_empty() => {
+            let error = RpcErrorObject::new(INVALID_REQUEST, "Invalid request: empty batch".to_string(), None);
+            Some(error_response(Value::Null, error))
+        }
+        Value::Array(requests) => {
+            let responses: Vec<Value> = requests
+                .into_iter()
+                .filter_map(|request| handle_request(storage, request, timestamp, options))
+                .collect();
+            if responses.is_empty() {
+                None
+            } else {
+                Some(Value::Array(responses))
+            }
+        }
+        request => handle_request(storage, request, timestamp, options),
+    }
+}
+
+/// Handle a single JSON-RPC request, returning its response unless it is a notification.
+fn handle_request<S: Storage>(
+    storage: &mut S,
+    request: Value,
+    timestamp: u64,
+    options: &ServerOptions,
+) -> Option<Value> {
+    let mut request = match request {
+        Value::Object(request) => request,
+        _ => {
+            let error = RpcErrorObject::new(INVALID_REQUEST, "Invalid request: not an object".to_string(), None);
+            return Some(error_response(Value::Null, error));
+        }
+    };
+    // A request without an id is a notification, which is never answered
+    let id = request.remove("id");
+    let response_id = match &id {
+        Some(id @ (Value::String(_) | Value::Number(_) | Value::Null)) => id.clone(),
+        Some(_) => {
+            let error = RpcErrorObject::new(INVALID_REQUEST, "Invalid request: the id must be a string, a number or null".to_string(), None);
+            return Some(error_response(Value::Null, error));
+        }
+        None => Value::Null,
+    };
+    let result = check_request(&request).and_then(|(method, params)| call_method(storage, method, params, timestamp, options));
+    id.map(|_| match result {
+        Ok(result) => json!({ "jsonrpc": JSON_RPC_VERSION, "result": result, "id": response_id }),
+        Err(error) => error_response(response_id, error),
+    })
+}
+
+/// Return the method and the params of a request object, or an INVALID_REQUEST error.
+fn check_request(
+    request: &Map<String, Value>,
+) -> Result<(&str, Value), RpcErrorObject> {
+    if request.get("jsonrpc").and_then(Value::as_str) != Some(JSON_RPC_VERSION) {
+        return Err(RpcErrorObject::new(INVALID_REQUEST, "Invalid request: the jsonrpc member must be \"2.0\"".to_string(), None));
+    }
+    let method = request
+        .get("method")
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcErrorObject::new(INVALID_REQUEST, "Invalid request: the method must be a string".to_string(), None))?;
+    let params = match request.get("params") {
+        None => Value::Object(Map::new()),
+        Some(params @ Value::Object(_)) => params.clone(),
+        Some(Value::Array(_)) => {
+            return Err(RpcErrorObject::new(INVALID_PARAMS, "Invalid params: the params must be passed by name".to_string(), None));
+        }
+        Some(_) => {
+            return Err(RpcErrorObject::new(INVALID_REQUEST, "Invalid request: the params must be an object".to_string(), None));
+        }
+    };
+    Ok((method, params))
+}
+
+/// Run the command of a method and return its output.
+fn call_method<S: Storage>(
+    storage: &mut S,
+    method: &str,
+    params: Value,
+    timestamp: u64,
+    options: &ServerOptions,
+) -> Result<Value, RpcErrorObject> {
+    if let ("webhook_register" | "webhook_unregister", Some(webhook_dispatcher)) = (method, options.get_webhook_dispatcher()) {
+        return call_webhook_method(storage, method, params, webhook_dispatcher);
+    }
+    let command = match method {
+        "entity_create" => {
+            let params: EntityCreateParams = parse_params(params)?;
+            Command::EntityCreate {
+                entity_id: params.entity_id,
+                name: params.name,
+                balance: Amount::default(),
+                public_key: params.public_key,
+            }
+        }
+        "wallet_getBalance" => {
+            let params: WalletGetBalanceParams = parse_params(params)?;
+            Command::WalletBalance { entity_id: params.entity_id }
+        }
+        "transfer_submit" => {
+            let params: TransferSubmitParams = parse_params(params)?;
+            Command::Transfer {
+                from_entity_id: params.from_entity_id,
+                to_entity_id: params.to_entity_id,
+                amount: params.amount,
+                transaction_id: params.transaction_id,
+                description: params.description,
+                nonce: params.nonce,
+                signature: TransferSignature::Signature(params.signature),
+            }
+        }
+        "tx_get" => {
+            let params: TxGetParams = parse_params(params)?;
+            Command::TransactionShow { transaction_id: params.transaction_id }
+        }
+        "tx_history" => {
+            let params: TxHistoryParams = parse_params(params)?;
+            Command::TransactionHistory { entity_id: params.entity_id }
+        }
+        method => return Err(RpcErrorObject::new(METHOD_NOT_FOUND, format!("Method not found: {}", method), None)),
+    };
+    run_command_with_audit(storage, &command, timestamp, &options.get_event_bus(), options.get_audit_subscriber())
+        .map(|output| output.to_json())
+        .map_err(|error| RpcErrorObject::from_cli_error(&error))
+}
+
+/// Change the endpoint of an entity as a webhook method asks and return its output.
+fn call_webhook_method<S: Storage>(
+    storage: &S,
+    method: &str,
+    params: Value,
+    webhook_dispatcher: &WebhookDispatcher,
+) -> Result<Value, RpcErrorObject> {
+    if method == "webhook_register" {
+        let params: WebhookRegisterParams = parse_params(params)?;
+        let entity = load_entity(storage, &params.entity_id)?;
+        webhook_dispatcher
+            .register_signed_endpoint(&entity, params.url.clone(), params.secret, &params.signature)
+            .map_err(|error| RpcErrorObject::from_webhook_error(&error))?;
+        Ok(json!({ "entity_id": params.entity_id, "url": params.url }))
+    } else {
+        let params: WebhookUnregisterParams = parse_params(params)?;
+        let entity = load_entity(storage, &params.entity_id)?;
+        webhook_dispatcher
+            .unregister_signed_endpoint(&entity, &params.signature)
+            .map_err(|error| RpcErrorObject::from_webhook_error(&error))?;
+        Ok(json!({ "entity_id": params.entity_id }))
+    }
+}
+
+/// Return the stored entity identified by the given id, or the error of the command-line tool for a missing entity.
+fn load_entity<S: Storage>(
+    storage: &S,
+    entity_id: &str,
+) -> Result<Entity, RpcErrorObject> {
+    let error = match storage.load_entity(entity_id) {
+        Ok(Some(entity)) => return Ok(entity),
+        Ok(None) => CliError::EntityNotFoundError { entity_id: entity_id.to_string() },
+        Err(error) => CliError::StorageOperationError { error },
+    };
+    Err(RpcErrorObject::from_cli_error(&error))
+}
+
+/// Deserialize the params of a method, or return an INVALID_PARAMS error.
+fn parse_params<P: DeserializeOwned>(
+    params: Value,
+) -> Result<P, RpcErrorObject> {
+    serde_json::from_value(params)
+        .map_err(|error| RpcErrorObject::new(INVALID_PARAMS, format!("Invalid params: {}", error), None))
+}
+
+/// Return the response to a request that failed.
+fn error_response(
+    id: Value,
+    error: RpcErrorObject,
+) -> Value {
+    json!({ "jsonrpc": JSON_RPC_VERSION, "error": error, "id": id })
+}
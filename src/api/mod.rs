@@ -0,0 +1,23 @@
+/// Importing http module
+pub mod http;
+pub use http::*;
+
+/// Importing server options module
+pub mod server_options;
+pub use server_options::*;
+
+/// Importing JSON-RPC module
+pub mod json_rpc;
+pub use json_rpc::*;
+
+/// Importing JSON-RPC server module
+pub mod rpc_server;
+pub use rpc_server::*;
+
+/// Importing REST API module
+pub mod rest_api;
+pub use rest_api::*;
+
+/// Importing REST server module
+pub mod rest_server;
+pub use rest_server::*;
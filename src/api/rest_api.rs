@@ -0,0 +1,489 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::api::{HttpRequest, HttpResponse};
+use crate::audit::AuditSubscriber;
+use crate::cli::{run_command, run_command_with_audit, Command, TransferSignature};
+use crate::error::{ApiError, CliError, StorageError};
+use crate::event::EventBus;
+use crate::model::{Amount, Transaction};
+use crate::storage::{IdempotencyRecord, Storage};
+
+/// Number of transactions in a page when the request does not give a limit.
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+
+/// Largest number of transactions in a page.
+pub const MAX_PAGE_SIZE: usize = 500;
+
+/// Header carrying the idempotency key of a POST request.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Header set on a response that is replayed for an idempotency key already used.
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "Idempotent-Replayed";
+
+/// Number of seconds during which an idempotency key is remembered, when the API is not given another duration.
+pub const DEFAULT_IDEMPOTENCY_KEY_TTL: u64 = 24 * 60 * 60;
+
+/// Largest idempotency key accepted.
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
+/// Body of a POST /transfers request. The transaction is signed by the sender elsewhere, so that its id is given
+/// and, if it is not the next one of the sender, the nonce it was signed with.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TransferBody {
+    from_entity_id: String,
+    to_entity_id: String,
+    amount: Amount,
+    #[serde(default)]
+    transaction_id: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    nonce: Option<u64>,
+    signature: String,
+}
+
+/// REST API structure
+/// It answers resource-oriented HTTP requests against a storage:
+/// * `GET /entities/{id}` - returns the entity.
+/// * `GET /entities/{id}/transactions` - returns a page of the transactions of the entity, in the order of the log.
+///   The query may filter them by `state`, e.g. "Completed", and by creation timestamp with `from` and `to`, both
+///   included. A page holds at most `limit` transactions and the `next_cursor` of the response, if not null,
+///   is passed back as `cursor` to get the next page. The cursor is opaque to the clients: it is the id of the
+///   first transaction of the next page, so that a page does not depend on the position of the transactions.
+/// * `POST /transfers` - runs a transfer, see 'TransferBody', and returns the transaction. The request requires an
+///   Idempotency-Key header: a request repeating the key and the body is answered with the response of the first one.
+///   The keys and their responses are saved in the storage, so that they survive a restart, and are removed once
+///   they are older than the idempotency key TTL.
+///
+/// The changes of the transfers are appended to the audit log of the API, if given,
+/// and published on the event bus of the API, if given, once they are stored.
+/// Entities and transactions keep their serde representation. An error is answered with its serde representation
+/// under `error` and its description under `message`.
+#[derive(Debug, Clone)]
+pub struct RestApi {
+    /// Number of seconds during which the idempotency key of a transfer is remembered.
+    idempotency_key_ttl: u64,
+    /// Bus the changes of the transfers are published on.
+    event_bus: EventBus,
+    /// Audit log the changes of the transfers are appended to once they are stored, if any.
+    audit_subscriber: Option<AuditSubscriber>,
+}
+
+/// Default REST API: the idempotency keys are remembered for DEFAULT_IDEMPOTENCY_KEY_TTL seconds.
+impl Default for RestApi {
+    fn default() -> RestApi {
+        RestApi::new()
+    }
+}
+
+/// REST API implementation.
+impl RestApi {
+    ///
+    /// Return a new RestApi remembering the idempotency keys for DEFAULT_IDEMPOTENCY_KEY_TTL seconds.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::api::{RestApi, DEFAULT_IDEMPOTENCY_KEY_TTL};
+    /// let api = RestApi::new();
+    /// # assert_eq!(api.get_idempotency_key_ttl(), DEFAULT_IDEMPOTENCY_KEY_TTL)
+    /// ```
+    ///
+    pub fn new() -> RestApi {
+        RestApi {
+            idempotency_key_ttl: DEFAULT_IDEMPOTENCY_KEY_TTL,
+            event_bus: EventBus::new(),
+            audit_subscriber: None,
+        }
+    }
+
+    ///
+    /// Return the RestApi remembering the idempotency keys for the given number of seconds.
+    ///
+    /// # Arguments
+    ///
+    /// * `idempotency_key_ttl` - A u64 integer that represents the number of seconds during which a key is remembered.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::api::RestApi;
+    /// let api = RestApi::new().with_idempotency_key_ttl(3600);
+    /// # assert_eq!(api.get_idempotency_key_ttl(), 3600)
+    /// ```
+    ///
+    pub fn with_idempotency_key_ttl(
+        mut self: RestApi,
+        idempotency_key_ttl: u64,
+    ) -> RestApi {
+        self.idempotency_key_ttl = idempotency_key_ttl;
+        self
+    }
+
+    ///
+    /// Return the number of seconds during which the idempotency key of a transfer is remembered.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::RestApi;
+    /// # let api = RestApi::new().with_idempotency_key_ttl(3600);
+    /// let idempotency_key_ttl : u64 = api.get_idempotency_key_ttl();
+    /// # assert_eq!(idempotency_key_ttl, 3600)
+    /// ```
+    ///
+    pub fn get_idempotency_key_ttl(
+        self: &RestApi,
+    ) -> u64 {
+        self.idempotency_key_ttl
+    }
+
+    ///
+    /// Return the RestApi publishing the changes of the transfers on the given event bus.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_bus` - An EventBus that represents the bus the changes are published on.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::api::RestApi;
+    /// use banana_coin::event::EventBus;
+    /// let event_bus = EventBus::new();
+    /// let _receiver = event_bus.subscribe_channel();
+    /// let api = RestApi::new().with_event_bus(event_bus);
+    /// # assert_eq!(api.get_event_bus().get_subscriber_count(), 1)
+    /// ```
+    ///
+    pub fn with_event_bus(
+        mut self: RestApi,
+        event_bus: EventBus,
+    ) -> RestApi {
+        self.event_bus = event_bus;
+        self
+    }
+
+    ///
+    /// Return the event bus the changes of the transfers are published on.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::RestApi;
+    /// # use banana_coin::event::EventBus;
+    /// # let api = RestApi::new();
+    /// let event_bus : EventBus = api.get_event_bus();
+    /// # assert_eq!(event_bus.get_subscriber_count(), 0)
+    /// ```
+    ///
+    pub fn get_event_bus(
+        self: &RestApi,
+    ) -> EventBus {
+        self.event_bus.clone()
+    }
+
+    ///
+    /// Return the RestApi appending the changes of the transfers to the audit log of the given subscriber once they are stored.
+    /// A transfer whose changes can not be audited fails without storing them.
+    ///
+    /// # Arguments
+    ///
+    /// * `audit_subscriber` - An AuditSubscriber that represents the audit log the changes are appended to.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::api::RestApi;
+    /// use banana_coin::audit::{AuditLog, AuditSubscriber};
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_rest_api_audit_{}.jsonl", std::process::id()));
+    /// let audit_subscriber = AuditSubscriber::new(AuditLog::open(&path).unwrap(), "rest".to_string());
+    /// let api = RestApi::new().with_audit_subscriber(audit_subscriber);
+    /// # assert!(api.get_audit_subscriber().is_some());
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn with_audit_subscriber(
+        mut self: RestApi,
+        audit_subscriber: AuditSubscriber,
+    ) -> RestApi {
+        self.audit_subscriber = Some(audit_subscriber);
+        self
+    }
+
+    ///
+    /// Return the subscriber of the audit log the changes of the transfers are appended to, if any.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::RestApi;
+    /// # use banana_coin::audit::AuditSubscriber;
+    /// # let api = RestApi::new();
+    /// let audit_subscriber : Option<AuditSubscriber> = api.get_audit_subscriber();
+    /// # assert!(audit_subscriber.is_none())
+    /// ```
+    ///
+    pub fn get_audit_subscriber(
+        self: &RestApi,
+    ) -> Option<AuditSubscriber> {
+        self.audit_subscriber.clone()
+    }
+
+    ///
+    /// Answer an HTTP request against a storage.
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - A Storage that represents the data the resources are read from and written to.
+    /// * `request` - An HttpRequest that represents the request to answer.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the state changes of new transactions.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::api::{HttpRequest, RestApi};
+    /// use banana_coin::model::{Amount, Entity, Wallet};
+    /// use banana_coin::storage::{MemoryStorage, Storage};
+    /// let mut storage = MemoryStorage::new();
+    /// # storage.save_entity(&Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)))).unwrap();
+    /// let request = HttpRequest::read_from(&mut &b"GET /entities/entity_001 HTTP/1.1\r\n\r\n"[..]).unwrap();
+    /// let response = RestApi::new().handle(&mut storage, &request, 1);
+    /// # assert_eq!(response.get_status(), 200);
+    /// # let entity: serde_json::Value = serde_json::from_slice(response.get_body()).unwrap();
+    /// # assert_eq!(entity["name"], "john")
+    /// ```
+    ///
+    pub fn handle<S: Storage>(
+        self: &RestApi,
+        storage: &mut S,
+        request: &HttpRequest,
+        timestamp: u64,
+    ) -> HttpResponse {
+        let segments = match request.get_path_segments() {
+            Ok(segments) => segments,
+            Err(error) => return rest_error_response(&error),
+        };
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+        match (request.get_method(), segments.as_slice()) {
+            ("GET", ["entities", entity_id]) =>
+                run_command(storage, &Command::EntityShow { entity_id: entity_id.to_string() }, timestamp)
+                    .map(|output| HttpResponse::json(200, &output.to_json()))
+                    .unwrap_or_else(|error| cli_error_response(&error)),
+            ("GET", ["entities", entity_id, "transactions"]) =>
+                list_transactions(storage, entity_id, request),
+            ("POST", ["transfers"]) =>
+                self.submit_transfer(storage, request, timestamp),
+            (_, ["entities", _]) | (_, ["entities", _, "transactions"]) =>
+                method_not_allowed(request, "GET"),
+            (_, ["transfers"]) =>
+                method_not_allowed(request, "POST"),
+            (method, _) => rest_error_response(&ApiError::RouteNotFoundError {
+                method: method.to_string(),
+                path: request.get_path().to_string(),
+            }),
+        }
+    }
+
+    /// Run the transfer of a POST /transfers request, unless its idempotency key was already used.
+    fn submit_transfer<S: Storage>(
+        self: &RestApi,
+        storage: &mut S,
+        request: &HttpRequest,
+        timestamp: u64,
+    ) -> HttpResponse {
+        let idempotency_key = match request.get_header(IDEMPOTENCY_KEY_HEADER) {
+            Some(key) if !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LENGTH => key.to_string(),
+            Some(key) if !key.is_empty() => return rest_error_response(&ApiError::InvalidParameterError {
+                parameter: IDEMPOTENCY_KEY_HEADER.to_string(),
+                value: key.to_string(),
+            }),
+            _ => return rest_error_response(&ApiError::MissingIdempotencyKeyError),
+        };
+        let body: Value = match serde_json::from_slice(request.get_body()) {
+            Ok(body) => body,
+            Err(error) => return rest_error_response(&ApiError::InvalidBodyError { message: error.to_string() }),
+        };
+        // The expired keys are forgotten first, so that the stored keys stay within the TTL
+        let expired_before = timestamp.saturating_sub(self.idempotency_key_ttl);
+        let record = storage
+            .remove_idempotency_records_before(expired_before)
+            .and_then(|_| storage.load_idempotency_record(&idempotency_key));
+        match record {
+            Ok(Some(record)) if *record.get_request() != body =>
+                return rest_error_response(&ApiError::IdempotencyKeyReuseError { idempotency_key }),
+            Ok(Some(record)) =>
+                return HttpResponse::json(record.get_status(), record.get_response())
+                    .with_header(IDEMPOTENT_REPLAYED_HEADER, "true"),
+            Ok(None) => {}
+            Err(error) => return cli_error_response(&CliError::StorageOperationError { error }),
+        }
+        let transfer: TransferBody = match serde_json::from_value(body.clone()) {
+            Ok(transfer) => transfer,
+            Err(error) => return rest_error_response(&ApiError::InvalidBodyError { message: error.to_string() }),
+        };
+        let command = Command::Transfer {
+            from_entity_id: transfer.from_entity_id,
+            to_entity_id: transfer.to_entity_id,
+            amount: transfer.amount,
+            transaction_id: transfer.transaction_id,
+            description: transfer.description,
+            nonce: transfer.nonce,
+            signature: TransferSignature::Signature(transfer.signature),
+        };
+        let (status, response) = run_command_with_audit(storage, &command, timestamp, &self.event_bus, self.audit_subscriber.as_ref())
+            .map(|output| (201, output.to_json()))
+            .unwrap_or_else(|error| cli_error_body(&error));
+        // A failure of the server may not happen again, so the request can be retried with the same key
+        if status < 500 {
+            let record = IdempotencyRecord::new(idempotency_key, body, status, response.clone(), timestamp);
+            if let Err(error) = storage.save_idempotency_record(&record) {
+                return cli_error_response(&CliError::StorageOperationError { error });
+            }
+        }
+        HttpResponse::json(status, &response)
+    }
+}
+
+/// Answer a GET /entities/{id}/transactions request with a page of the transactions of the entity.
+fn list_transactions<S: Storage>(
+    storage: &S,
+    entity_id: &str,
+    request: &HttpRequest,
+) -> HttpResponse {
+    let parameters = match request.get_query_parameters() {
+        Ok(parameters) => parameters,
+        Err(error) => return rest_error_response(&error),
+    };
+    let mut state = None;
+    let mut from = u64::MIN;
+    let mut to = u64::MAX;
+    let mut cursor = None;
+    let mut limit = DEFAULT_PAGE_SIZE;
+    for (parameter, value) in &parameters {
+        let valid = match parameter.as_str() {
+            "state" => {
+                state = Some(value.as_str());
+                ["Created", "OnProcess", "Completed", "Failed", "Blocked"].contains(&value.as_str())
+            }
+            "from" => value.parse().map(|value| from = value).is_ok(),
+            "to" => value.parse().map(|value| to = value).is_ok(),
+            "cursor" => {
+                cursor = Some(value.as_str());
+                !value.is_empty()
+            }
+            "limit" => value.parse().map(|value| limit = value).is_ok() && (1..=MAX_PAGE_SIZE).contains(&limit),
+            _ => false,
+        };
+        if !valid {
+            return rest_error_response(&ApiError::InvalidParameterError {
+                parameter: parameter.clone(),
+                value: value.clone(),
+            });
+        }
+    }
+
+    let transactions = match find_transactions(storage, entity_id) {
+        Ok(transactions) => transactions,
+        Err(error) => return cli_error_response(&error),
+    };
+    // The page starts at the transaction identified by the cursor, which must be a transaction of the entity
+    let start = match cursor {
+        Some(cursor) => match transactions.iter().position(|transaction| transaction.get_id() == cursor) {
+            Some(position) => position,
+            None => return rest_error_response(&ApiError::InvalidParameterError {
+                parameter: "cursor".to_string(),
+                value: cursor.to_string(),
+            }),
+        },
+        None => 0,
+    };
+    let mut transactions: Vec<Transaction> = transactions
+        .into_iter()
+        .skip(start)
+        .filter(|transaction| state.is_none_or(|state| transaction.get_current_state().get_name() == state))
+        .filter(|transaction| (from..=to).contains(&transaction.get_creation_timestamp()))
+        .take(limit + 1)
+        .collect();
+    // The transaction beyond the limit is not returned, its id is where the next page starts
+    let next_cursor = if transactions.len() > limit {
+        transactions.pop().map(|transaction| transaction.get_id())
+    } else {
+        None
+    };
+    HttpResponse::json(200, &json!({ "transactions": transactions, "next_cursor": next_cursor }))
+}
+
+/// Return the transactions of an entity, in the order of the log.
+fn find_transactions<S: Storage>(
+    storage: &S,
+    entity_id: &str,
+) -> Result<Vec<Transaction>, CliError> {
+    if storage.load_entity(entity_id)?.is_none() {
+        return Err(CliError::EntityNotFoundError { entity_id: entity_id.to_string() });
+    }
+    Ok(storage.list_transactions_by_entity(entity_id)?)
+}
+
+/// Return the response to a request whose method is not accepted by the resource.
+fn method_not_allowed(
+    request: &HttpRequest,
+    allowed: &str,
+) -> HttpResponse {
+    rest_error_response(&ApiError::MethodNotAllowedError {
+        method: request.get_method().to_string(),
+        path: request.get_path().to_string(),
+    }).with_header("Allow", allowed)
+}
+
+///
+/// Return the response to a request that can not be answered by the API, e.g. because its body is too large.
+///
+/// # Arguments
+///
+/// * `error` - An ApiError that represents why the request can not be answered.
+///
+/// # Example
+/// ```
+/// use banana_coin::api::rest_error_response;
+/// use banana_coin::error::ApiError;
+/// let response = rest_error_response(&ApiError::PayloadTooLargeError { length: 2048, limit: 1024 });
+/// # assert_eq!(response.get_status(), 413)
+/// ```
+///
+pub fn rest_error_response(
+    error: &ApiError,
+) -> HttpResponse {
+    let status = match error {
+        ApiError::IoError { .. } => 500,
+        ApiError::PayloadTooLargeError { .. } => 413,
+        ApiError::RouteNotFoundError { .. } => 404,
+        ApiError::MethodNotAllowedError { .. } => 405,
+        ApiError::IdempotencyKeyReuseError { .. } => 422,
+        ApiError::MalformedRequestError { .. }
+        | ApiError::InvalidParameterError { .. }
+        | ApiError::InvalidBodyError { .. }
+        | ApiError::MissingIdempotencyKeyError => 400,
+    };
+    HttpResponse::json(status, &json!({ "error": error, "message": error.to_string() }))
+}
+
+/// Return the response to a request whose command failed.
+fn cli_error_response(
+    error: &CliError,
+) -> HttpResponse {
+    let (status, body) = cli_error_body(error);
+    HttpResponse::json(status, &body)
+}
+
+/// Return the status and the body of the response to a request whose command failed.
+fn cli_error_body(
+    error: &CliError,
+) -> (u16, Value) {
+    let status = match error {
+        CliError::UsageError { .. } | CliError::ReservedEntityIdError { .. } | CliError::CryptoOperationError { .. } => 400,
+        CliError::EntityNotFoundError { .. } | CliError::TransactionNotFoundError { .. } => 404,
+        CliError::DuplicateEntityError { .. } | CliError::PublicKeyReplacementError { .. } => 409,
+        CliError::StorageOperationError { error: StorageError::DuplicateTransactionError { .. } } => 409,
+        CliError::EntityOperationError { .. }
+        | CliError::TransactionOperationError { .. }
+        | CliError::TransferOperationError { .. } => 422,
+        CliError::StorageOperationError { .. } | CliError::AuditOperationError { .. } => 500,
+    };
+    (status, json!({ "error": error, "message": error.to_string() }))
+}
+
@@ -0,0 +1,109 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::api::{rest_error_response, HttpServer, RestApi, ServerOptions};
+use crate::error::ApiError;
+use crate::storage::Storage;
+use crate::util::now;
+
+/// REST server structure
+/// It serves the resource-oriented HTTP requests of a 'RestApi', see its documentation for the resources.
+/// Connections are served as described by 'HttpServer' and carry a single request; the requests are run one at a time
+/// against the storage of the server. Once stored, their changes are appended to the audit log of the options of the server,
+/// if any, and published on the event bus of the options.
+/// The idempotency keys of the transfers are saved in the storage.
+/// The server stops when it is shut down or dropped.
+pub struct RestServer {
+    server: HttpServer,
+}
+
+/// REST server implementation.
+impl RestServer {
+    ///
+    /// Return a new RestServer listening on the given address and working against a storage,
+    /// which may be shared with other servers.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - A str that represents the address to listen on, e.g. "127.0.0.1:0" for any free port.
+    /// * `storage` - A shared Storage that represents the data the resources are read from and written to.
+    /// * `options` - A ServerOptions that represents the event bus the changes of the transfers are published on
+    ///   and the audit log, if any, they are appended to.
+    ///
+    /// # Errors
+    ///
+    /// Return an IoError if the server can not listen on the address.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use banana_coin::api::{RestServer, RpcServer, ServerOptions};
+    /// use banana_coin::event::EventBus;
+    /// use banana_coin::storage::MemoryStorage;
+    /// let storage = Arc::new(Mutex::new(MemoryStorage::new()));
+    /// let options = ServerOptions::new().with_event_bus(EventBus::new());
+    /// let rpc_server = RpcServer::start("127.0.0.1:0", Arc::clone(&storage), options.clone()).unwrap();
+    /// let rest_server = RestServer::start("127.0.0.1:0", Arc::clone(&storage), options).unwrap();
+    /// # assert_ne!(rpc_server.get_address(), rest_server.get_address())
+    /// ```
+    ///
+    pub fn start<S: Storage + Send + 'static>(
+        address: &str,
+        storage: Arc<Mutex<S>>,
+        options: ServerOptions,
+    ) -> Result<RestServer, ApiError> {
+        let api = RestApi::new().with_event_bus(options.get_event_bus());
+        let api = match options.get_audit_subscriber() {
+            Some(audit_subscriber) => api.with_audit_subscriber(audit_subscriber.clone()),
+            None => api,
+        };
+        let server = HttpServer::start(address, move |request| match request {
+            Ok(request) => {
+                let mut storage = storage.lock().unwrap_or_else(PoisonError::into_inner);
+                api.handle(&mut *storage, &request, now())
+            }
+            Err(error) => rest_error_response(&error),
+        })?;
+        Ok(RestServer {
+            server,
+        })
+    }
+
+    ///
+    /// Return the address the server listens on.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::RestServer;
+    /// # use banana_coin::storage::MemoryStorage;
+    /// # use banana_coin::api::ServerOptions;
+    /// # use std::sync::{Arc, Mutex};
+    /// # let server = RestServer::start("127.0.0.1:0", Arc::new(Mutex::new(MemoryStorage::new())), ServerOptions::new()).unwrap();
+    /// let address : String = server.get_address();
+    /// # assert!(address.starts_with("127.0.0.1:"))
+    /// ```
+    ///
+    pub fn get_address(
+        self: &RestServer,
+    ) -> String {
+        self.server.get_address()
+    }
+
+    ///
+    /// Stop the server: it stops accepting connections. The requests being served are completed.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::RestServer;
+    /// # use banana_coin::storage::MemoryStorage;
+    /// # use banana_coin::api::ServerOptions;
+    /// # use std::sync::{Arc, Mutex};
+    /// let server = RestServer::start("127.0.0.1:0", Arc::new(Mutex::new(MemoryStorage::new())), ServerOptions::new()).unwrap();
+    /// server.shutdown();
+    /// ```
+    ///
+    pub fn shutdown(
+        self: &RestServer,
+    ) {
+        self.server.shutdown()
+    }
+}
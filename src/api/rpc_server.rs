@@ -0,0 +1,119 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use serde_json::json;
+
+use crate::api::{handle_json_rpc, HttpResponse, HttpServer, RpcErrorObject, ServerOptions, INVALID_REQUEST, JSON_RPC_VERSION};
+use crate::error::ApiError;
+use crate::storage::Storage;
+use crate::util::now;
+
+/// JSON-RPC server structure
+/// It serves JSON-RPC 2.0 requests sent with HTTP POST to any path, see 'handle_json_rpc' for the methods.
+/// Connections are served as described by 'HttpServer' and carry a single request; the requests are run one at a time
+/// against the storage of the server. Once stored, their changes are appended to the audit log of the options of the server,
+/// if any, and published on the event bus of the options.
+/// The server stops when it is shut down or dropped.
+pub struct RpcServer {
+    server: HttpServer,
+}
+
+/// JSON-RPC server implementation.
+impl RpcServer {
+    ///
+    /// Return a new RpcServer listening on the given address and working against a storage,
+    /// which may be shared with other servers.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - A str that represents the address to listen on, e.g. "127.0.0.1:0" for any free port.
+    /// * `storage` - A shared Storage that represents the data the methods work against.
+    /// * `options` - A ServerOptions that represents the event bus the changes of the methods are published on
+    ///   and the audit log, if any, they are appended to.
+    ///
+    /// # Errors
+    ///
+    /// Return an IoError if the server can not listen on the address.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use banana_coin::api::{RpcServer, ServerOptions};
+    /// use banana_coin::audit::{AuditLog, AuditSubscriber};
+    /// use banana_coin::event::EventBus;
+    /// use banana_coin::storage::MemoryStorage;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_rpc_server_audit_{}.jsonl", std::process::id()));
+    /// let storage = Arc::new(Mutex::new(MemoryStorage::new()));
+    /// let audit_subscriber = AuditSubscriber::new(AuditLog::open(&path).unwrap(), "rpc".to_string());
+    /// let options = ServerOptions::new()
+    ///     .with_event_bus(EventBus::new())
+    ///     .with_audit_subscriber(audit_subscriber);
+    /// let server = RpcServer::start("127.0.0.1:0", storage, options).unwrap();
+    /// # assert!(server.get_address().starts_with("127.0.0.1:"));
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn start<S: Storage + Send + 'static>(
+        address: &str,
+        storage: Arc<Mutex<S>>,
+        options: ServerOptions,
+    ) -> Result<RpcServer, ApiError> {
+        let server = HttpServer::start(address, move |request| match request {
+            Ok(request) if request.get_method() != "POST" =>
+                HttpResponse::empty(405).with_header("Allow", "POST"),
+            Ok(request) => {
+                let mut storage = storage.lock().unwrap_or_else(PoisonError::into_inner);
+                match handle_json_rpc(&mut *storage, request.get_body(), now(), &options) {
+                    Some(body) => HttpResponse::json(200, &body),
+                    None => HttpResponse::empty(204),
+                }
+            }
+            Err(ApiError::PayloadTooLargeError { .. }) => HttpResponse::empty(413),
+            Err(error) => {
+                let error = RpcErrorObject::new(INVALID_REQUEST, error.to_string(), None);
+                HttpResponse::json(400, &json!({ "jsonrpc": JSON_RPC_VERSION, "error": error, "id": null }))
+            }
+        })?;
+        Ok(RpcServer {
+            server,
+        })
+    }
+
+    ///
+    /// Return the address the server listens on.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::RpcServer;
+    /// # use banana_coin::storage::MemoryStorage;
+    /// # use banana_coin::api::ServerOptions;
+    /// # use std::sync::{Arc, Mutex};
+    /// # let server = RpcServer::start("127.0.0.1:0", Arc::new(Mutex::new(MemoryStorage::new())), ServerOptions::new()).unwrap();
+    /// let address : String = server.get_address();
+    /// # assert!(address.starts_with("127.0.0.1:"))
+    /// ```
+    ///
+    pub fn get_address(
+        self: &RpcServer,
+    ) -> String {
+        self.server.get_address()
+    }
+
+    ///
+    /// Stop the server: it stops accepting connections. The requests being served are completed.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::RpcServer;
+    /// # use banana_coin::storage::MemoryStorage;
+    /// # use banana_coin::api::ServerOptions;
+    /// # use std::sync::{Arc, Mutex};
+    /// let server = RpcServer::start("127.0.0.1:0", Arc::new(Mutex::new(MemoryStorage::new())), ServerOptions::new()).unwrap();
+    /// server.shutdown();
+    /// ```
+    ///
+    pub fn shutdown(
+        self: &RpcServer,
+    ) {
+        self.server.shutdown()
+    }
+}
@@ -0,0 +1,175 @@
+use crate::audit::AuditSubscriber;
+use crate::event::EventBus;
+use crate::webhook::WebhookDispatcher;
+
+/// Server options structure
+/// It represents where the servers report the changes of the requests they run: the event bus the changes are
+/// published on once stored, the webhook dispatcher, if any, notifying the entities of their transactions and whose
+/// endpoints the entities register through the servers, and the audit log, if any, the changes are appended to
+/// once stored. By default, the changes are published on a bus without subscribers, are not notified
+/// and are not audited.
+#[derive(Debug, Clone, Default)]
+pub struct ServerOptions {
+    /// Bus the changes of the requests are published on.
+    event_bus: EventBus,
+    /// Audit log the changes of the requests are appended to once they are stored, if any.
+    audit_subscriber: Option<AuditSubscriber>,
+    /// Dispatcher subscribed to the event bus, if any.
+    webhook_dispatcher: Option<WebhookDispatcher>,
+}
+
+/// Server options implementation.
+impl ServerOptions {
+    ///
+    /// Return new ServerOptions publishing the changes on a bus without subscribers, without webhooks and not auditing them.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::api::ServerOptions;
+    /// let options = ServerOptions::new();
+    /// # assert!(options.get_audit_subscriber().is_none())
+    /// ```
+    ///
+    pub fn new() -> ServerOptions {
+        ServerOptions {
+            event_bus: EventBus::new(),
+            audit_subscriber: None,
+            webhook_dispatcher: None,
+        }
+    }
+
+    ///
+    /// Return the ServerOptions publishing the changes on the given event bus, e.g. to deliver them to webhooks.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_bus` - An EventBus that represents the bus the changes are published on.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::api::ServerOptions;
+    /// use banana_coin::event::EventBus;
+    /// let event_bus = EventBus::new();
+    /// let _receiver = event_bus.subscribe_channel();
+    /// let options = ServerOptions::new().with_event_bus(event_bus);
+    /// # assert_eq!(options.get_event_bus().get_subscriber_count(), 1)
+    /// ```
+    ///
+    pub fn with_event_bus(
+        mut self: ServerOptions,
+        event_bus: EventBus,
+    ) -> ServerOptions {
+        // The dispatcher, if any, keeps being notified of the changes
+        if let Some(webhook_dispatcher) = &self.webhook_dispatcher {
+            event_bus.subscribe(webhook_dispatcher.clone());
+        }
+        self.event_bus = event_bus;
+        self
+    }
+
+    ///
+    /// Return the event bus the changes are published on.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::ServerOptions;
+    /// # use banana_coin::event::EventBus;
+    /// # let options = ServerOptions::new();
+    /// let event_bus : EventBus = options.get_event_bus();
+    /// # assert_eq!(event_bus.get_subscriber_count(), 0)
+    /// ```
+    ///
+    pub fn get_event_bus(
+        self: &ServerOptions,
+    ) -> EventBus {
+        self.event_bus.clone()
+    }
+
+    ///
+    /// Return the ServerOptions appending the changes to the audit log of the given subscriber once they are stored.
+    /// A request whose changes can not be audited fails without storing them.
+    ///
+    /// # Arguments
+    ///
+    /// * `audit_subscriber` - An AuditSubscriber that represents the audit log the changes are appended to.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::api::ServerOptions;
+    /// use banana_coin::audit::{AuditLog, AuditSubscriber};
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_server_options_audit_{}.jsonl", std::process::id()));
+    /// let audit_subscriber = AuditSubscriber::new(AuditLog::open(&path).unwrap(), "server".to_string());
+    /// let options = ServerOptions::new().with_audit_subscriber(audit_subscriber);
+    /// # assert!(options.get_audit_subscriber().is_some());
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn with_audit_subscriber(
+        mut self: ServerOptions,
+        audit_subscriber: AuditSubscriber,
+    ) -> ServerOptions {
+        self.audit_subscriber = Some(audit_subscriber);
+        self
+    }
+
+    ///
+    /// Return the audit log the changes are appended to once they are stored, if any.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::ServerOptions;
+    /// # let options = ServerOptions::new();
+    /// let audit_subscriber = options.get_audit_subscriber();
+    /// # assert!(audit_subscriber.is_none())
+    /// ```
+    ///
+    pub fn get_audit_subscriber(
+        self: &ServerOptions,
+    ) -> Option<&AuditSubscriber> {
+        self.audit_subscriber.as_ref()
+    }
+
+    ///
+    /// Return the ServerOptions notifying the entities of their transactions through the given webhook dispatcher,
+    /// which is subscribed to the event bus. The entities register their endpoints through the servers.
+    /// The dispatcher only queues the deliveries: they are sent when it is processed, e.g. by its worker.
+    ///
+    /// # Arguments
+    ///
+    /// * `webhook_dispatcher` - A WebhookDispatcher that represents the dispatcher the changes are notified through.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::api::ServerOptions;
+    /// use banana_coin::webhook::{RetryPolicy, WebhookDispatcher};
+    /// let options = ServerOptions::new().with_webhook_dispatcher(WebhookDispatcher::new(RetryPolicy::default()));
+    /// # assert!(options.get_webhook_dispatcher().is_some());
+    /// # assert_eq!(options.get_event_bus().get_subscriber_count(), 1)
+    /// ```
+    ///
+    pub fn with_webhook_dispatcher(
+        mut self: ServerOptions,
+        webhook_dispatcher: WebhookDispatcher,
+    ) -> ServerOptions {
+        self.event_bus.subscribe(webhook_dispatcher.clone());
+        self.webhook_dispatcher = Some(webhook_dispatcher);
+        self
+    }
+
+    ///
+    /// Return the webhook dispatcher the changes are notified through, if any.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::api::ServerOptions;
+    /// # let options = ServerOptions::new();
+    /// let webhook_dispatcher = options.get_webhook_dispatcher();
+    /// # assert!(webhook_dispatcher.is_none())
+    /// ```
+    ///
+    pub fn get_webhook_dispatcher(
+        self: &ServerOptions,
+    ) -> Option<&WebhookDispatcher> {
+        self.webhook_dispatcher.as_ref()
+    }
+}
@@ -0,0 +1,447 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::slice;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::audit::{AuditRecord, GENESIS_HASH};
+use crate::error::AuditError;
+use crate::event::{DomainEvent, Subscriber};
+use crate::util::now;
+
+/// Audit log structure
+/// It records every change of the balances and of the settings of the entities in an append-only file, one JSON record per line, so that the log can be
+/// inspected with standard tools. Each record is chained to the previous one through its hash, see 'AuditRecord':
+/// a record changed, removed or reordered afterwards is found by 'AuditLog::verify'.
+/// Removing the last records leaves a valid chain, so the hash of the last record should be kept elsewhere too.
+/// The file is locked while the log is open, so that two processes can not append to the same chain.
+#[derive(Debug)]
+pub struct AuditLog {
+    /// Path of the audit log file.
+    path: PathBuf,
+    /// Audit log file, opened in append mode and locked until the log is dropped.
+    file: File,
+    /// Last record of the log, None if the log is empty.
+    last_record: Option<AuditRecord>,
+}
+
+/// Audit log implementation.
+impl AuditLog {
+    ///
+    /// Open the audit log file at the given path, creating it if it does not exist, after locking it and verifying its records.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A Path that represents the location of the audit log file.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::audit::AuditLog;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_open_{}.jsonl", std::process::id()));
+    /// let audit_log = AuditLog::open(&path).unwrap();
+    /// # assert_eq!(audit_log.get_record_count(), 0);
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An IoError is returned if the file can not be opened, a LockedLogError if it is already open, in this process
+    /// or in another one, and a TamperedRecordError if the log has been tampered with, so that no record is chained to a tampered one.
+    ///
+    pub fn open(
+        path: &Path,
+    ) -> Result<AuditLog, AuditError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        // The lock is released when the file is closed, even if the process is killed
+        match file.try_lock() {
+            Ok(_) => {}
+            Err(TryLockError::WouldBlock) => return Err(AuditError::LockedLogError { path: path.display().to_string() }),
+            Err(TryLockError::Error(error)) => return Err(AuditError::from(error)),
+        }
+        let last_record = verify_records(BufReader::new(&file))?;
+        Ok(AuditLog {
+            path: path.to_path_buf(),
+            file,
+            last_record,
+        })
+    }
+
+    ///
+    /// Walk the audit log file at the given path and return its last record, None if the log is empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A Path that represents the location of the audit log file.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::audit::AuditLog;
+    /// # use banana_coin::event::DomainEvent;
+    /// # use banana_coin::model::Amount;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_verify_{}.jsonl", std::process::id()));
+    /// # let mut audit_log = AuditLog::open(&path).unwrap();
+    /// # let event = DomainEvent::CoinsAdded { entity_id: "entity_001".to_string(), asset: "BNC".to_string(), amount: Amount::from_integer(10), balance: Amount::from_integer(110), transaction_id: None, timestamp: 1 };
+    /// # audit_log.append("admin", &event).unwrap();
+    /// let last_record = AuditLog::verify(&path).unwrap();
+    /// # assert_eq!(last_record.unwrap().get_sequence(), 1);
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An IoError is returned if the file can not be read and a TamperedRecordError pointing at the first line
+    /// that is not a valid record, is out of sequence, does not match its hash or is not chained to the previous record.
+    /// A SerializationError is returned if the hash of a record can not be computed.
+    ///
+    pub fn verify(
+        path: &Path,
+    ) -> Result<Option<AuditRecord>, AuditError> {
+        verify_records(BufReader::new(File::open(path)?))
+    }
+
+    ///
+    /// Return the path of the audit log file.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::AuditLog;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_path_{}.jsonl", std::process::id()));
+    /// let audit_log = AuditLog::open(&path).unwrap();
+    /// assert_eq!(audit_log.get_path(), path.as_path());
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_path(
+        self: &AuditLog,
+    ) -> &Path {
+        &self.path
+    }
+
+    ///
+    /// Return the number of records of the log.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::AuditLog;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_count_{}.jsonl", std::process::id()));
+    /// # let audit_log = AuditLog::open(&path).unwrap();
+    /// let record_count : u64 = audit_log.get_record_count();
+    /// # assert_eq!(record_count, 0);
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_record_count(
+        self: &AuditLog,
+    ) -> u64 {
+        self.last_record.as_ref().map_or(0, AuditRecord::get_sequence)
+    }
+
+    ///
+    /// Return the hash of the last record of the log, or GENESIS_HASH if the log is empty.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::{AuditLog, GENESIS_HASH};
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_last_hash_{}.jsonl", std::process::id()));
+    /// # let audit_log = AuditLog::open(&path).unwrap();
+    /// let last_hash : &str = audit_log.get_last_hash();
+    /// # assert_eq!(last_hash, GENESIS_HASH);
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_last_hash(
+        self: &AuditLog,
+    ) -> &str {
+        self.last_record.as_ref().map_or(GENESIS_HASH, AuditRecord::get_hash)
+    }
+
+    ///
+    /// Append the records of the balances or of the setting changed by an event and return them. The initial balances of a new
+    /// entity are recorded at the current time, while the other changes are recorded at the timestamp of their event.
+    /// Events not changing any entity are not recorded.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - A str that represents the name of whoever made the change.
+    /// * `event` - A DomainEvent that represents the change.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::AuditLog;
+    /// # use banana_coin::event::DomainEvent;
+    /// # use banana_coin::model::Amount;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_append_{}.jsonl", std::process::id()));
+    /// # let mut audit_log = AuditLog::open(&path).unwrap();
+    /// let event = DomainEvent::CoinsRemoved {
+    ///     entity_id: "entity_001".to_string(),
+    ///     asset: "BNC".to_string(),
+    ///     amount: Amount::from_integer(10),
+    ///     balance: Amount::from_integer(90),
+    ///     transaction_id: Some("id_0001".to_string()),
+    ///     timestamp: 1,
+    /// };
+    /// let records = audit_log.append("admin", &event).unwrap();
+    /// # assert_eq!(records[0].get_before(), Some(Amount::from_integer(100)));
+    /// # assert_eq!(audit_log.get_last_hash(), records[0].get_hash());
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An InvalidEventError is returned if the balance before the change is out of range,
+    /// a SerializationError if the records can not be serialized and an IoError if the records can not be written.
+    ///
+    pub fn append(
+        self: &mut AuditLog,
+        actor: &str,
+        event: &DomainEvent,
+    ) -> Result<Vec<AuditRecord>, AuditError> {
+        self.append_all(actor, slice::from_ref(event))
+    }
+
+    ///
+    /// Append the records of the balances changed by the events of an operation and return them, see 'append'.
+    /// The records are written at once: none of them is appended if one of the events can not be recorded.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - A str that represents the name of whoever made the changes.
+    /// * `events` - A slice of DomainEvent that represents the changes, in order.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::AuditLog;
+    /// # use banana_coin::event::DomainEvent;
+    /// # use banana_coin::model::Amount;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_append_all_{}.jsonl", std::process::id()));
+    /// # let mut audit_log = AuditLog::open(&path).unwrap();
+    /// let removed = DomainEvent::CoinsRemoved {
+    ///     entity_id: "entity_001".to_string(),
+    ///     asset: "BNC".to_string(),
+    ///     amount: Amount::from_integer(10),
+    ///     balance: Amount::from_integer(90),
+    ///     transaction_id: Some("id_0001".to_string()),
+    ///     timestamp: 1,
+    /// };
+    /// let added = DomainEvent::CoinsAdded {
+    ///     entity_id: "entity_002".to_string(),
+    ///     asset: "BNC".to_string(),
+    ///     amount: Amount::from_integer(10),
+    ///     balance: Amount::from_integer(10),
+    ///     transaction_id: Some("id_0001".to_string()),
+    ///     timestamp: 1,
+    /// };
+    /// let records = audit_log.append_all("admin", &[removed, added]).unwrap();
+    /// # assert_eq!(records.len(), 2);
+    /// # assert_eq!(records[1].get_previous_hash(), records[0].get_hash());
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An InvalidEventError is returned if the balance before a change is out of range,
+    /// a SerializationError if the records can not be serialized and an IoError if the records can not be written.
+    ///
+    pub fn append_all(
+        self: &mut AuditLog,
+        actor: &str,
+        events: &[DomainEvent],
+    ) -> Result<Vec<AuditRecord>, AuditError> {
+        let mut records = Vec::new();
+        let mut last_record = self.last_record.clone();
+        let timestamp = now();
+        for event in events {
+            for record in AuditRecord::from_event(actor, event, timestamp)? {
+                let record = match &last_record {
+                    Some(previous) => record.link(previous.get_sequence(), previous.get_hash())?,
+                    None => record.link(0, GENESIS_HASH)?,
+                };
+                last_record = Some(record.clone());
+                records.push(record);
+            }
+        }
+        if records.is_empty() {
+            return Ok(records);
+        }
+        let mut lines = String::new();
+        for record in &records {
+            lines.push_str(&serde_json::to_string(record)?);
+            lines.push('\n');
+        }
+        // The records of the events are written at once, and become the tail of the chain only once written
+        self.file.write_all(lines.as_bytes())?;
+        self.file.flush()?;
+        self.last_record = last_record;
+        Ok(records)
+    }
+}
+
+/// Audit subscriber structure
+/// It appends the changes of the entities published on an event bus to an audit log, on behalf of an actor.
+/// Since the subscribers can not fail, the errors of the audit log are kept and can be checked once the events are published.
+/// An operation that must report the failure of the audit of its stored changes appends them with 'record' instead.
+#[derive(Debug, Clone)]
+pub struct AuditSubscriber {
+    audit_log: Arc<Mutex<AuditLog>>,
+    actor: String,
+    errors: Arc<Mutex<Vec<AuditError>>>,
+}
+
+/// Audit subscriber implementation.
+impl AuditSubscriber {
+    ///
+    /// Return a new AuditSubscriber appending the changes made by the given actor to an audit log.
+    ///
+    /// # Arguments
+    ///
+    /// * `audit_log` - An AuditLog that represents the log the changes are appended to.
+    /// * `actor` - A String that represents the name of whoever makes the changes.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::audit::{AuditLog, AuditSubscriber};
+    /// use banana_coin::event::EventBus;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_subscriber_{}.jsonl", std::process::id()));
+    /// let event_bus = EventBus::new();
+    /// let subscriber = AuditSubscriber::new(AuditLog::open(&path).unwrap(), "admin".to_string());
+    /// event_bus.subscribe(subscriber.clone());
+    /// # assert_eq!(event_bus.get_subscriber_count(), 1);
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn new(
+        audit_log: AuditLog,
+        actor: String,
+    ) -> AuditSubscriber {
+        AuditSubscriber {
+            audit_log: Arc::new(Mutex::new(audit_log)),
+            actor,
+            errors: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    ///
+    /// Return the number of records of the audit log.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::{AuditLog, AuditSubscriber};
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_subscriber_count_{}.jsonl", std::process::id()));
+    /// # let subscriber = AuditSubscriber::new(AuditLog::open(&path).unwrap(), "admin".to_string());
+    /// let record_count : u64 = subscriber.get_record_count();
+    /// # assert_eq!(record_count, 0);
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_record_count(
+        self: &AuditSubscriber,
+    ) -> u64 {
+        self.audit_log.lock().unwrap_or_else(PoisonError::into_inner).get_record_count()
+    }
+
+    ///
+    /// Return the errors raised while appending the published events, in the order they happened.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::{AuditLog, AuditSubscriber};
+    /// # use banana_coin::error::AuditError;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_subscriber_errors_{}.jsonl", std::process::id()));
+    /// # let subscriber = AuditSubscriber::new(AuditLog::open(&path).unwrap(), "admin".to_string());
+    /// let errors : Vec<AuditError> = subscriber.get_errors();
+    /// # assert!(errors.is_empty());
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_errors(
+        self: &AuditSubscriber,
+    ) -> Vec<AuditError> {
+        self.errors.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    ///
+    /// Append the changes of an operation to the audit log at once, on behalf of the actor of the subscriber,
+    /// and return the records, see 'AuditLog::append_all'. Unlike the published events, the errors are returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `events` - A slice of DomainEvent that represents the changes, in order.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::{AuditLog, AuditSubscriber};
+    /// # use banana_coin::event::DomainEvent;
+    /// # use banana_coin::model::Amount;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_subscriber_record_{}.jsonl", std::process::id()));
+    /// # let subscriber = AuditSubscriber::new(AuditLog::open(&path).unwrap(), "admin".to_string());
+    /// let event = DomainEvent::CoinsAdded {
+    ///     entity_id: "entity_001".to_string(),
+    ///     asset: "BNC".to_string(),
+    ///     amount: Amount::from_integer(10),
+    ///     balance: Amount::from_integer(110),
+    ///     transaction_id: None,
+    ///     timestamp: 1,
+    /// };
+    /// let records = subscriber.record(&[event]).unwrap();
+    /// # assert_eq!(records[0].get_actor(), "admin");
+    /// # assert_eq!(subscriber.get_record_count(), 1);
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An InvalidEventError is returned if the balance before a change is out of range,
+    /// a SerializationError if the records can not be serialized and an IoError if the records can not be written.
+    ///
+    pub fn record(
+        self: &AuditSubscriber,
+        events: &[DomainEvent],
+    ) -> Result<Vec<AuditRecord>, AuditError> {
+        self.audit_log.lock().unwrap_or_else(PoisonError::into_inner).append_all(&self.actor, events)
+    }
+}
+
+impl Subscriber for AuditSubscriber {
+    fn notify(
+        &self,
+        event: &DomainEvent,
+    ) {
+        if let Err(error) = self.record(slice::from_ref(event)) {
+            self.errors.lock().unwrap_or_else(PoisonError::into_inner).push(error);
+        }
+    }
+}
+
+/// Walk the lines of an audit log and return its last record, or the first line breaking the chain of hashes.
+fn verify_records<R: BufRead>(
+    reader: R,
+) -> Result<Option<AuditRecord>, AuditError> {
+    let mut last_record: Option<AuditRecord> = None;
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let tampered = |message: String| AuditError::TamperedRecordError { line: index + 1, message };
+        let record: AuditRecord = serde_json::from_str(&line)
+            .map_err(|error| tampered(format!("it is not a valid record: {}", error)))?;
+        let (previous_sequence, previous_hash) = match &last_record {
+            Some(previous) => (previous.get_sequence(), previous.get_hash()),
+            None => (0, GENESIS_HASH),
+        };
+        if record.get_sequence() != previous_sequence + 1 {
+            return Err(tampered(format!(
+                "its sequence is {} instead of {}, records were removed or reordered",
+                record.get_sequence(), previous_sequence + 1)));
+        }
+        if record.get_hash() != record.compute_hash()? {
+            return Err(tampered("its hash does not match its content".to_string()));
+        }
+        if record.get_previous_hash() != previous_hash {
+            return Err(tampered("it is not chained to the hash of the previous record, one of the two was changed".to_string()));
+        }
+        last_record = Some(record);
+    }
+    Ok(last_record)
+}
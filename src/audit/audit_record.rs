@@ -0,0 +1,480 @@
+use serde::{Deserialize, Serialize};
+
+use crate::chain::sha256;
+use crate::error::AuditError;
+use crate::event::DomainEvent;
+use crate::event::DomainEvent::*;
+use crate::model::Amount;
+
+/// Hash linked by the first record of an audit log, which has no previous record.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Audit record structure
+/// It represents a change of an entity: who made it, when, and either the balance of an asset before and after the change
+/// or, for the change of a setting of the entity such as its public key, the value of the setting before and after.
+/// Every record carries the hash of the previous record of the log and its own hash, computed over all its other fields,
+/// so that changing, removing or reordering a record breaks the chain of hashes.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditRecord {
+    /// Position of the record in the log, starting from 1.
+    sequence: u64,
+    timestamp: u64,
+    actor: String,
+    entity_id: String,
+    /// Name of the event that changed the entity, e.g. "CoinsAdded".
+    operation: String,
+    /// Asset whose balance changed, None for the change of a setting.
+    asset: Option<String>,
+    before: Option<Amount>,
+    after: Option<Amount>,
+    transaction_id: Option<String>,
+    /// Setting that changed, e.g. "public_key", None for the change of a balance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    setting: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    previous_value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    previous_hash: String,
+    hash: String,
+}
+
+/// Audit record implementation.
+impl AuditRecord {
+    /// Return the unlinked records of the changes of an event: one for each asset whose balance changed, or one for the setting
+    /// that changed. Events not changing entities have none, the initial balances of a new entity are recorded at the given timestamp.
+    pub(crate) fn from_event(
+        actor: &str,
+        event: &DomainEvent,
+        created_at: u64,
+    ) -> Result<Vec<AuditRecord>, AuditError> {
+        let record = |entity_id: &str, operation: &str, asset: &str, before: Amount, after: Amount, transaction_id: Option<String>, timestamp: u64| AuditRecord {
+            sequence: 0,
+            timestamp,
+            actor: actor.to_string(),
+            entity_id: entity_id.to_string(),
+            operation: operation.to_string(),
+            asset: Some(asset.to_string()),
+            before: Some(before),
+            after: Some(after),
+            transaction_id,
+            setting: None,
+            previous_value: None,
+            value: None,
+            previous_hash: String::new(),
+            hash: String::new(),
+        };
+        let setting_record = |entity_id: &str, setting: &str, previous_value: Option<String>, value: String, timestamp: u64| AuditRecord {
+            sequence: 0,
+            timestamp,
+            actor: actor.to_string(),
+            entity_id: entity_id.to_string(),
+            operation: event.get_name().to_string(),
+            asset: None,
+            before: None,
+            after: None,
+            transaction_id: None,
+            setting: Some(setting.to_string()),
+            previous_value,
+            value: Some(value),
+            previous_hash: String::new(),
+            hash: String::new(),
+        };
+        let invalid = || AuditError::InvalidEventError { message: format!("the balance before the {} event is out of range", event.get_name()) };
+        match event {
+            EntityCreated { entity_id, balances, .. } => Ok(balances
+                .iter()
+                .map(|(asset, balance)| record(entity_id, event.get_name(), asset, Amount::zero(), *balance, None, created_at))
+                .collect()),
+            CoinsAdded { entity_id, asset, amount, balance, transaction_id, timestamp } => {
+                let before = balance.checked_sub(*amount).ok_or_else(invalid)?;
+                Ok(vec![record(entity_id, event.get_name(), asset, before, *balance, transaction_id.clone(), *timestamp)])
+            }
+            CoinsRemoved { entity_id, asset, amount, balance, transaction_id, timestamp } => {
+                let before = balance.checked_add(*amount).ok_or_else(invalid)?;
+                Ok(vec![record(entity_id, event.get_name(), asset, before, *balance, transaction_id.clone(), *timestamp)])
+            }
+            PublicKeySet { entity_id, public_key, timestamp } =>
+                Ok(vec![setting_record(entity_id, "public_key", None, public_key.clone(), *timestamp)]),
+            OverdraftPolicyChanged { entity_id, previous_policy, overdraft_policy, timestamp, .. } =>
+                Ok(vec![setting_record(entity_id, "overdraft_policy", Some(previous_policy.to_string()), overdraft_policy.to_string(), *timestamp)]),
+            TransactionStateChanged { .. } | BlockAppended { .. } => Ok(Vec::new()),
+        }
+    }
+
+    /// Link the record to the previous record of the log, given its sequence and hash, and compute its hash.
+    pub(crate) fn link(
+        mut self: AuditRecord,
+        previous_sequence: u64,
+        previous_hash: &str,
+    ) -> Result<AuditRecord, AuditError> {
+        self.sequence = previous_sequence + 1;
+        self.previous_hash = previous_hash.to_string();
+        self.hash = self.compute_hash()?;
+        Ok(self)
+    }
+
+    ///
+    /// Return the position of the record in the log, starting from 1.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::collections::BTreeMap;
+    /// # use banana_coin::audit::AuditLog;
+    /// # use banana_coin::event::DomainEvent;
+    /// # use banana_coin::model::Amount;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_sequence_{}.jsonl", std::process::id()));
+    /// # let mut audit_log = AuditLog::open(&path).unwrap();
+    /// # let event = DomainEvent::EntityCreated { entity_id: "entity_001".to_string(), name: "john".to_string(), balances: BTreeMap::from([("BNC".to_string(), Amount::from_integer(100))]) };
+    /// # let record = audit_log.append("admin", &event).unwrap().remove(0);
+    /// let sequence : u64 = record.get_sequence();
+    /// # assert_eq!(sequence, 1);
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_sequence(
+        self: &AuditRecord,
+    ) -> u64 {
+        self.sequence
+    }
+
+    ///
+    /// Return the timestamp of the change.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::AuditLog;
+    /// # use banana_coin::event::DomainEvent;
+    /// # use banana_coin::model::Amount;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_timestamp_{}.jsonl", std::process::id()));
+    /// # let mut audit_log = AuditLog::open(&path).unwrap();
+    /// # let event = DomainEvent::CoinsAdded { entity_id: "entity_001".to_string(), asset: "BNC".to_string(), amount: Amount::from_integer(10), balance: Amount::from_integer(110), transaction_id: None, timestamp: 1 };
+    /// # let record = audit_log.append("admin", &event).unwrap().remove(0);
+    /// let timestamp : u64 = record.get_timestamp();
+    /// # assert_eq!(timestamp, 1);
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_timestamp(
+        self: &AuditRecord,
+    ) -> u64 {
+        self.timestamp
+    }
+
+    ///
+    /// Return the name of whoever made the change.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::AuditLog;
+    /// # use banana_coin::event::DomainEvent;
+    /// # use banana_coin::model::Amount;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_actor_{}.jsonl", std::process::id()));
+    /// # let mut audit_log = AuditLog::open(&path).unwrap();
+    /// # let event = DomainEvent::CoinsAdded { entity_id: "entity_001".to_string(), asset: "BNC".to_string(), amount: Amount::from_integer(10), balance: Amount::from_integer(110), transaction_id: None, timestamp: 1 };
+    /// # let record = audit_log.append("admin", &event).unwrap().remove(0);
+    /// let actor : &str = record.get_actor();
+    /// # assert_eq!(actor, "admin");
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_actor(
+        self: &AuditRecord,
+    ) -> &str {
+        &self.actor
+    }
+
+    ///
+    /// Return the id of the entity that changed.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::AuditLog;
+    /// # use banana_coin::event::DomainEvent;
+    /// # use banana_coin::model::Amount;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_entity_{}.jsonl", std::process::id()));
+    /// # let mut audit_log = AuditLog::open(&path).unwrap();
+    /// # let event = DomainEvent::CoinsAdded { entity_id: "entity_001".to_string(), asset: "BNC".to_string(), amount: Amount::from_integer(10), balance: Amount::from_integer(110), transaction_id: None, timestamp: 1 };
+    /// # let record = audit_log.append("admin", &event).unwrap().remove(0);
+    /// let entity_id : &str = record.get_entity_id();
+    /// # assert_eq!(entity_id, "entity_001");
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_entity_id(
+        self: &AuditRecord,
+    ) -> &str {
+        &self.entity_id
+    }
+
+    ///
+    /// Return the name of the event that changed the entity: "EntityCreated", "CoinsAdded", "CoinsRemoved",
+    /// "PublicKeySet" or "OverdraftPolicyChanged".
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::AuditLog;
+    /// # use banana_coin::event::DomainEvent;
+    /// # use banana_coin::model::Amount;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_operation_{}.jsonl", std::process::id()));
+    /// # let mut audit_log = AuditLog::open(&path).unwrap();
+    /// # let event = DomainEvent::CoinsRemoved { entity_id: "entity_001".to_string(), asset: "BNC".to_string(), amount: Amount::from_integer(10), balance: Amount::from_integer(90), transaction_id: None, timestamp: 1 };
+    /// # let record = audit_log.append("admin", &event).unwrap().remove(0);
+    /// let operation : &str = record.get_operation();
+    /// # assert_eq!(operation, "CoinsRemoved");
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_operation(
+        self: &AuditRecord,
+    ) -> &str {
+        &self.operation
+    }
+
+    ///
+    /// Return the asset whose balance changed, or None for the change of a setting.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::AuditLog;
+    /// # use banana_coin::event::DomainEvent;
+    /// # use banana_coin::model::Amount;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_asset_{}.jsonl", std::process::id()));
+    /// # let mut audit_log = AuditLog::open(&path).unwrap();
+    /// # let event = DomainEvent::CoinsAdded { entity_id: "entity_001".to_string(), asset: "BNC".to_string(), amount: Amount::from_integer(10), balance: Amount::from_integer(110), transaction_id: None, timestamp: 1 };
+    /// # let record = audit_log.append("admin", &event).unwrap().remove(0);
+    /// let asset : Option<&str> = record.get_asset();
+    /// # assert_eq!(asset, Some("BNC"));
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_asset(
+        self: &AuditRecord,
+    ) -> Option<&str> {
+        self.asset.as_deref()
+    }
+
+    ///
+    /// Return the balance before the change, or None for the change of a setting.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::AuditLog;
+    /// # use banana_coin::event::DomainEvent;
+    /// # use banana_coin::model::Amount;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_before_{}.jsonl", std::process::id()));
+    /// # let mut audit_log = AuditLog::open(&path).unwrap();
+    /// # let event = DomainEvent::CoinsAdded { entity_id: "entity_001".to_string(), asset: "BNC".to_string(), amount: Amount::from_integer(10), balance: Amount::from_integer(110), transaction_id: None, timestamp: 1 };
+    /// # let record = audit_log.append("admin", &event).unwrap().remove(0);
+    /// let before : Option<Amount> = record.get_before();
+    /// # assert_eq!(before, Some(Amount::from_integer(100)));
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_before(
+        self: &AuditRecord,
+    ) -> Option<Amount> {
+        self.before
+    }
+
+    ///
+    /// Return the balance after the change, or None for the change of a setting.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::AuditLog;
+    /// # use banana_coin::event::DomainEvent;
+    /// # use banana_coin::model::Amount;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_after_{}.jsonl", std::process::id()));
+    /// # let mut audit_log = AuditLog::open(&path).unwrap();
+    /// # let event = DomainEvent::CoinsAdded { entity_id: "entity_001".to_string(), asset: "BNC".to_string(), amount: Amount::from_integer(10), balance: Amount::from_integer(110), transaction_id: None, timestamp: 1 };
+    /// # let record = audit_log.append("admin", &event).unwrap().remove(0);
+    /// let after : Option<Amount> = record.get_after();
+    /// # assert_eq!(after, Some(Amount::from_integer(110)));
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_after(
+        self: &AuditRecord,
+    ) -> Option<Amount> {
+        self.after
+    }
+
+    ///
+    /// Return the setting of the entity that changed, "public_key" or "overdraft_policy", or None for the change of a balance.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::AuditLog;
+    /// # use banana_coin::event::DomainEvent;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_setting_{}.jsonl", std::process::id()));
+    /// # let mut audit_log = AuditLog::open(&path).unwrap();
+    /// # let event = DomainEvent::PublicKeySet { entity_id: "entity_001".to_string(), public_key: "ab".repeat(32), timestamp: 1 };
+    /// # let record = audit_log.append("admin", &event).unwrap().remove(0);
+    /// let setting : Option<&str> = record.get_setting();
+    /// # assert_eq!(setting, Some("public_key"));
+    /// # assert!(record.get_asset().is_none());
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_setting(
+        self: &AuditRecord,
+    ) -> Option<&str> {
+        self.setting.as_deref()
+    }
+
+    ///
+    /// Return the value of the setting before the change, or None if it had none or for the change of a balance.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::AuditLog;
+    /// # use banana_coin::event::DomainEvent;
+    /// # use banana_coin::model::OverdraftPolicy;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_previous_value_{}.jsonl", std::process::id()));
+    /// # let mut audit_log = AuditLog::open(&path).unwrap();
+    /// # let event = DomainEvent::OverdraftPolicyChanged { entity_id: "entity_001".to_string(), previous_policy: OverdraftPolicy::NoOverdraft, overdraft_policy: OverdraftPolicy::Unlimited, changed_by: "admin".to_string(), timestamp: 1 };
+    /// # let record = audit_log.append("admin", &event).unwrap().remove(0);
+    /// let previous_value : Option<&str> = record.get_previous_value();
+    /// # assert_eq!(previous_value, Some("no overdraft"));
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_previous_value(
+        self: &AuditRecord,
+    ) -> Option<&str> {
+        self.previous_value.as_deref()
+    }
+
+    ///
+    /// Return the value of the setting after the change, or None for the change of a balance.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::AuditLog;
+    /// # use banana_coin::event::DomainEvent;
+    /// # use banana_coin::model::OverdraftPolicy;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_value_{}.jsonl", std::process::id()));
+    /// # let mut audit_log = AuditLog::open(&path).unwrap();
+    /// # let event = DomainEvent::OverdraftPolicyChanged { entity_id: "entity_001".to_string(), previous_policy: OverdraftPolicy::NoOverdraft, overdraft_policy: OverdraftPolicy::Unlimited, changed_by: "admin".to_string(), timestamp: 1 };
+    /// # let record = audit_log.append("admin", &event).unwrap().remove(0);
+    /// let value : Option<&str> = record.get_value();
+    /// # assert_eq!(value, Some("unlimited overdraft"));
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_value(
+        self: &AuditRecord,
+    ) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    ///
+    /// Return the id of the transaction that changed the balance, if any.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::AuditLog;
+    /// # use banana_coin::event::DomainEvent;
+    /// # use banana_coin::model::Amount;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_transaction_{}.jsonl", std::process::id()));
+    /// # let mut audit_log = AuditLog::open(&path).unwrap();
+    /// # let event = DomainEvent::CoinsAdded { entity_id: "entity_001".to_string(), asset: "BNC".to_string(), amount: Amount::from_integer(10), balance: Amount::from_integer(110), transaction_id: Some("id_0001".to_string()), timestamp: 1 };
+    /// # let record = audit_log.append("admin", &event).unwrap().remove(0);
+    /// let transaction_id : Option<&str> = record.get_transaction_id();
+    /// # assert_eq!(transaction_id, Some("id_0001"));
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_transaction_id(
+        self: &AuditRecord,
+    ) -> Option<&str> {
+        self.transaction_id.as_deref()
+    }
+
+    ///
+    /// Return the hash of the previous record of the log, or GENESIS_HASH for the first record.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::{AuditLog, GENESIS_HASH};
+    /// # use banana_coin::event::DomainEvent;
+    /// # use banana_coin::model::Amount;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_previous_{}.jsonl", std::process::id()));
+    /// # let mut audit_log = AuditLog::open(&path).unwrap();
+    /// # let event = DomainEvent::CoinsAdded { entity_id: "entity_001".to_string(), asset: "BNC".to_string(), amount: Amount::from_integer(10), balance: Amount::from_integer(110), transaction_id: None, timestamp: 1 };
+    /// # let record = audit_log.append("admin", &event).unwrap().remove(0);
+    /// let previous_hash : &str = record.get_previous_hash();
+    /// # assert_eq!(previous_hash, GENESIS_HASH);
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_previous_hash(
+        self: &AuditRecord,
+    ) -> &str {
+        &self.previous_hash
+    }
+
+    ///
+    /// Return the hash of the record, as it was written to the log.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::AuditLog;
+    /// # use banana_coin::event::DomainEvent;
+    /// # use banana_coin::model::Amount;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_hash_{}.jsonl", std::process::id()));
+    /// # let mut audit_log = AuditLog::open(&path).unwrap();
+    /// # let event = DomainEvent::CoinsAdded { entity_id: "entity_001".to_string(), asset: "BNC".to_string(), amount: Amount::from_integer(10), balance: Amount::from_integer(110), transaction_id: None, timestamp: 1 };
+    /// # let record = audit_log.append("admin", &event).unwrap().remove(0);
+    /// let hash : &str = record.get_hash();
+    /// # assert_eq!(hash, record.compute_hash().unwrap());
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_hash(
+        self: &AuditRecord,
+    ) -> &str {
+        &self.hash
+    }
+
+    ///
+    /// Return the hash of the content of the record: the SHA-256 digest, hex encoded, of the JSON array of all
+    /// its fields but the hash itself, the setting fields being left out for the change of a balance. It differs from the hash written to the log if the record was altered.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::audit::AuditLog;
+    /// # use banana_coin::event::DomainEvent;
+    /// # use banana_coin::model::Amount;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_audit_compute_{}.jsonl", std::process::id()));
+    /// # let mut audit_log = AuditLog::open(&path).unwrap();
+    /// # let event = DomainEvent::CoinsAdded { entity_id: "entity_001".to_string(), asset: "BNC".to_string(), amount: Amount::from_integer(10), balance: Amount::from_integer(110), transaction_id: None, timestamp: 1 };
+    /// # let record = audit_log.append("admin", &event).unwrap().remove(0);
+    /// let hash : String = record.compute_hash().unwrap();
+    /// # assert_eq!(hash.len(), 64);
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A SerializationError is returned if the content of the record can not be serialized.
+    ///
+    pub fn compute_hash(
+        self: &AuditRecord,
+    ) -> Result<String, AuditError> {
+        // The setting fields are hashed only for the change of a setting, so that the hashes of the balance records stay the same
+        let content = match self.setting {
+            Some(_) => serde_json::to_vec(&(
+                self.sequence, self.timestamp, &self.actor, &self.entity_id, &self.operation, &self.asset, self.before, self.after,
+                &self.transaction_id, &self.setting, &self.previous_value, &self.value, &self.previous_hash,
+            )),
+            None => serde_json::to_vec(&(
+                self.sequence, self.timestamp, &self.actor, &self.entity_id, &self.operation, &self.asset, self.before, self.after,
+                &self.transaction_id, &self.previous_hash,
+            )),
+        };
+        Ok(sha256(&content?))
+    }
+}
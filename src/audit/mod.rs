@@ -0,0 +1,7 @@
+/// Importing audit record module
+pub mod audit_record;
+pub use audit_record::*;
+
+/// Importing audit log module
+pub mod audit_log;
+pub use audit_log::*;
@@ -0,0 +1,88 @@
+extern crate banana_coin;
+
+use std::path::PathBuf;
+use std::process;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use banana_coin::api::{RestServer, RpcServer, ServerOptions};
+use banana_coin::audit::{AuditLog, AuditSubscriber};
+use banana_coin::cli::{default_audit_log, DEFAULT_DATA_FILE};
+use banana_coin::storage::JsonLinesStorage;
+use banana_coin::webhook::{RetryPolicy, WebhookDispatcher};
+
+/// Default address the JSON-RPC server listens on.
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8545";
+
+/// Time between two processings of the webhook deliveries.
+const WEBHOOK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Usage of the JSON-RPC server.
+const USAGE: &str = "Usage: banana_coin_rpc [--data-file <path>] [--audit-log <path>] [--actor <name>] [--address <host:port>] [--rest-address <host:port>]";
+
+fn main() {
+    let mut data_file = PathBuf::from(DEFAULT_DATA_FILE);
+    let mut audit_log = None;
+    let mut actor = None;
+    let mut address = DEFAULT_ADDRESS.to_string();
+    let mut rest_address = None;
+    let mut arguments = std::env::args().skip(1);
+    while let Some(argument) = arguments.next() {
+        match (argument.as_str(), arguments.next()) {
+            ("--data-file", Some(value)) => data_file = PathBuf::from(value),
+            ("--audit-log", Some(value)) => audit_log = Some(PathBuf::from(value)),
+            ("--actor", Some(value)) => actor = Some(value),
+            ("--address", Some(value)) => address = value,
+            ("--rest-address", Some(value)) => rest_address = Some(value),
+            _ => {
+                eprintln!("{}", USAGE);
+                process::exit(2)
+            }
+        }
+    }
+
+    // The changes of the entities are audited once they are stored, on behalf of the given actor, or else of the user running the servers.
+    // The data file stays locked by its storage while the servers run, so that no other process changes it meanwhile
+    let audit_log = audit_log.unwrap_or_else(|| default_audit_log(&data_file));
+    let actor = actor
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_else(|| "unknown".to_string());
+    let servers = AuditLog::open(&audit_log)
+        .map_err(|error| error.to_string())
+        .and_then(|audit_log| JsonLinesStorage::open(&data_file)
+            .map(|storage| (AuditSubscriber::new(audit_log, actor), storage))
+            .map_err(|error| error.to_string()))
+        .and_then(|(audit_subscriber, storage)| {
+            // Both servers work against the same storage, one request at a time, and share the bus and the audit log.
+            // The entities register their webhook endpoints through the JSON-RPC server and the deliveries are sent by a worker
+            let storage = Arc::new(Mutex::new(storage));
+            let webhook_dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+            let options = ServerOptions::new()
+                .with_audit_subscriber(audit_subscriber)
+                .with_webhook_dispatcher(webhook_dispatcher.clone());
+            let rpc_server = RpcServer::start(&address, Arc::clone(&storage), options.clone())
+                .map_err(|error| error.to_string())?;
+            let rest_server = match &rest_address {
+                Some(rest_address) => Some(RestServer::start(rest_address, storage, options)
+                    .map_err(|error| error.to_string())?),
+                None => None,
+            };
+            Ok((rpc_server, rest_server, webhook_dispatcher.start_worker(WEBHOOK_INTERVAL)))
+        });
+    match servers {
+        Ok((rpc_server, rest_server, _webhook_worker)) => {
+            println!("Serving JSON-RPC requests on http://{}", rpc_server.get_address());
+            if let Some(rest_server) = &rest_server {
+                println!("Serving REST requests on http://{}", rest_server.get_address());
+            }
+            loop {
+                thread::park();
+            }
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1)
+        }
+    }
+}
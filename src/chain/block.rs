@@ -0,0 +1,390 @@
+use serde::{Deserialize, Serialize};
+
+use crate::chain::{MerkleProof, MerkleTree, sha256};
+use crate::model::Transaction;
+
+/// Hash the first block of a chain refers to as its previous block.
+pub const GENESIS_PREVIOUS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Prefix of the hashed bytes of a block header, so that a header hash can not collide with other kinds of data.
+const HEADER_DOMAIN: &[u8] = b"banana_coin/block/v1";
+
+/// Block header structure
+/// It represents the data of a block that is hashed: its position in the chain, the hash of the previous block,
+/// the Merkle root of its transactions, its timestamp and the proof of work, i.e. the difficulty and the nonce
+/// that makes the hash of the header start with that many zero bits.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockHeader {
+    height: u64,
+    previous_hash: String,
+    merkle_root: String,
+    timestamp: u64,
+    #[serde(default)]
+    difficulty: u32,
+    #[serde(default)]
+    nonce: u64,
+}
+
+/// Block header implementation.
+impl BlockHeader {
+    ///
+    /// Return a new BlockHeader.
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - A u64 integer that represents the position of the block in the chain, starting from 0.
+    /// * `previous_hash` - A String that represents the hash of the previous block, hex encoded.
+    /// * `merkle_root` - A String that represents the Merkle root of the transactions of the block, hex encoded.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the block.
+    /// * `difficulty` - A u32 integer that represents the number of leading zero bits the hash of the header must have.
+    /// * `nonce` - A u64 integer that represents the value changed by the miner to find a valid hash.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::chain::{BlockHeader, GENESIS_PREVIOUS_HASH};
+    /// let header = BlockHeader::new(
+    ///     0,
+    ///     GENESIS_PREVIOUS_HASH.to_string(),
+    ///     "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string(),
+    ///     1,
+    ///     0,
+    ///     0
+    /// );
+    /// # assert_eq!(header.get_height(), 0)
+    /// ```
+    ///
+    pub fn new(
+        height: u64,
+        previous_hash: String,
+        merkle_root: String,
+        timestamp: u64,
+        difficulty: u32,
+        nonce: u64,
+    ) -> BlockHeader {
+        BlockHeader {
+            height,
+            previous_hash,
+            merkle_root,
+            timestamp,
+            difficulty,
+            nonce,
+        }
+    }
+
+    ///
+    /// Return the height of the block.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{BlockHeader, GENESIS_PREVIOUS_HASH};
+    /// # let header = BlockHeader::new(3, GENESIS_PREVIOUS_HASH.to_string(), "".to_string(), 1, 0, 0);
+    /// let height : u64 = header.get_height();
+    /// # assert_eq!(height, 3)
+    /// ```
+    ///
+    pub fn get_height(
+        self: &BlockHeader,
+    ) -> u64 {
+        self.height
+    }
+
+    ///
+    /// Return the hash of the previous block, hex encoded.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{BlockHeader, GENESIS_PREVIOUS_HASH};
+    /// # let header = BlockHeader::new(0, GENESIS_PREVIOUS_HASH.to_string(), "".to_string(), 1, 0, 0);
+    /// let previous_hash : &str = header.get_previous_hash();
+    /// # assert_eq!(previous_hash, GENESIS_PREVIOUS_HASH)
+    /// ```
+    ///
+    pub fn get_previous_hash(
+        self: &BlockHeader,
+    ) -> &str {
+        &self.previous_hash
+    }
+
+    ///
+    /// Return the Merkle root of the transactions of the block, hex encoded.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{BlockHeader, GENESIS_PREVIOUS_HASH};
+    /// # let header = BlockHeader::new(0, GENESIS_PREVIOUS_HASH.to_string(), "abcd".to_string(), 1, 0, 0);
+    /// let merkle_root : &str = header.get_merkle_root();
+    /// # assert_eq!(merkle_root, "abcd")
+    /// ```
+    ///
+    pub fn get_merkle_root(
+        self: &BlockHeader,
+    ) -> &str {
+        &self.merkle_root
+    }
+
+    ///
+    /// Return the timestamp of the block.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{BlockHeader, GENESIS_PREVIOUS_HASH};
+    /// # let header = BlockHeader::new(0, GENESIS_PREVIOUS_HASH.to_string(), "".to_string(), 7, 0, 0);
+    /// let timestamp : u64 = header.get_timestamp();
+    /// # assert_eq!(timestamp, 7)
+    /// ```
+    ///
+    pub fn get_timestamp(
+        self: &BlockHeader,
+    ) -> u64 {
+        self.timestamp
+    }
+
+    ///
+    /// Return the number of leading zero bits the hash of the header must have.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{BlockHeader, GENESIS_PREVIOUS_HASH};
+    /// # let header = BlockHeader::new(0, GENESIS_PREVIOUS_HASH.to_string(), "".to_string(), 7, 3, 0);
+    /// let difficulty : u32 = header.get_difficulty();
+    /// # assert_eq!(difficulty, 3)
+    /// ```
+    ///
+    pub fn get_difficulty(
+        self: &BlockHeader,
+    ) -> u32 {
+        self.difficulty
+    }
+
+    ///
+    /// Return the nonce of the header.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{BlockHeader, GENESIS_PREVIOUS_HASH};
+    /// # let header = BlockHeader::new(0, GENESIS_PREVIOUS_HASH.to_string(), "".to_string(), 7, 3, 42);
+    /// let nonce : u64 = header.get_nonce();
+    /// # assert_eq!(nonce, 42)
+    /// ```
+    ///
+    pub fn get_nonce(
+        self: &BlockHeader,
+    ) -> u64 {
+        self.nonce
+    }
+
+    ///
+    /// Return true if the hash of the header has at least as many leading zero bits as the difficulty.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{BlockHeader, GENESIS_PREVIOUS_HASH};
+    /// let header = BlockHeader::new(0, GENESIS_PREVIOUS_HASH.to_string(), "".to_string(), 7, 0, 0);
+    /// # assert!(header.meets_difficulty())
+    /// ```
+    ///
+    pub fn meets_difficulty(
+        self: &BlockHeader,
+    ) -> bool {
+        let hash = hex::decode(self.get_hash()).unwrap_or_default();
+        let mut zero_bits: u32 = 0;
+        for byte in hash.iter() {
+            zero_bits += byte.leading_zeros();
+            if *byte != 0 {
+                break;
+            }
+        }
+        zero_bits >= self.difficulty
+    }
+
+    ///
+    /// Return the hash of the header, which is the hash of the block, hex encoded.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{BlockHeader, GENESIS_PREVIOUS_HASH};
+    /// # let header = BlockHeader::new(0, GENESIS_PREVIOUS_HASH.to_string(), "".to_string(), 7, 0, 0);
+    /// let hash : String = header.get_hash();
+    /// # assert_eq!(hash.len(), 64)
+    /// ```
+    ///
+    pub fn get_hash(
+        self: &BlockHeader,
+    ) -> String {
+        let mut bytes = HEADER_DOMAIN.to_vec();
+        bytes.extend_from_slice(&self.height.to_be_bytes());
+        for field in [&self.previous_hash, &self.merkle_root].iter() {
+            bytes.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(field.as_bytes());
+        }
+        bytes.extend_from_slice(&self.timestamp.to_be_bytes());
+        bytes.extend_from_slice(&self.difficulty.to_be_bytes());
+        bytes.extend_from_slice(&self.nonce.to_be_bytes());
+        sha256(&bytes)
+    }
+}
+
+/// Block structure
+/// It represents a group of transactions, linked to the previous block of the chain through its header.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub struct Block {
+    header: BlockHeader,
+    transactions: Vec<Transaction>,
+}
+
+/// Block implementation.
+impl Block {
+    ///
+    /// Return a new Block, whose header contains the Merkle root of the given transactions.
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - A u64 integer that represents the position of the block in the chain, starting from 0.
+    /// * `previous_hash` - A String that represents the hash of the previous block, hex encoded.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the block.
+    /// * `difficulty` - A u32 integer that represents the number of leading zero bits the hash of the block must have.
+    /// * `transactions` - A Vec<Transaction> that represents the transactions of the block.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::chain::{Block, GENESIS_PREVIOUS_HASH};
+    /// let block = Block::new(
+    ///     0,
+    ///     GENESIS_PREVIOUS_HASH.to_string(),
+    ///     1,
+    ///     0,
+    ///     Vec::new()
+    /// );
+    /// # assert_eq!(block.get_header().get_merkle_root(), block.compute_merkle_root())
+    /// ```
+    ///
+    pub fn new(
+        height: u64,
+        previous_hash: String,
+        timestamp: u64,
+        difficulty: u32,
+        transactions: Vec<Transaction>,
+    ) -> Block {
+        let merkle_root = MerkleTree::new(&transactions).get_root();
+        Block {
+            header: BlockHeader::new(height, previous_hash, merkle_root, timestamp, difficulty, 0),
+            transactions,
+        }
+    }
+
+    ///
+    /// Return the header of the block.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{Block, BlockHeader, GENESIS_PREVIOUS_HASH};
+    /// # let block = Block::new(0, GENESIS_PREVIOUS_HASH.to_string(), 1, 0, Vec::new());
+    /// let header : &BlockHeader = block.get_header();
+    /// # assert_eq!(header.get_timestamp(), 1)
+    /// ```
+    ///
+    pub fn get_header(
+        self: &Block,
+    ) -> &BlockHeader {
+        &self.header
+    }
+
+    ///
+    /// Set the nonce of the header of the block, which changes the hash of the block.
+    ///
+    /// # Arguments
+    ///
+    /// * `nonce` - A u64 integer that represents the new nonce.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{Block, GENESIS_PREVIOUS_HASH};
+    /// let mut block = Block::new(0, GENESIS_PREVIOUS_HASH.to_string(), 1, 0, Vec::new());
+    /// block.set_nonce(42);
+    /// # assert_eq!(block.get_header().get_nonce(), 42)
+    /// ```
+    ///
+    pub fn set_nonce(
+        self: &mut Block,
+        nonce: u64,
+    ) {
+        self.header.nonce = nonce;
+    }
+
+    ///
+    /// Return a copy of the transactions of the block.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{Block, GENESIS_PREVIOUS_HASH};
+    /// # use banana_coin::model::Transaction;
+    /// # let block = Block::new(0, GENESIS_PREVIOUS_HASH.to_string(), 1, 0, Vec::new());
+    /// let transactions : Vec<Transaction> = block.get_transactions();
+    /// # assert!(transactions.is_empty())
+    /// ```
+    ///
+    pub fn get_transactions(
+        self: &Block,
+    ) -> Vec<Transaction> {
+        self.transactions.clone()
+    }
+
+    ///
+    /// Return the hash of the block, i.e. the hash of its header, hex encoded.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{Block, GENESIS_PREVIOUS_HASH};
+    /// # let block = Block::new(0, GENESIS_PREVIOUS_HASH.to_string(), 1, 0, Vec::new());
+    /// let hash : String = block.get_hash();
+    /// # assert_eq!(hash, block.get_header().get_hash())
+    /// ```
+    ///
+    pub fn get_hash(
+        self: &Block,
+    ) -> String {
+        self.header.get_hash()
+    }
+
+    ///
+    /// Return the Merkle root of the transactions of the block, computed from the transactions themselves.
+    /// It differs from the one in the header if the transactions have been tampered with.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{Block, GENESIS_PREVIOUS_HASH};
+    /// # let block = Block::new(0, GENESIS_PREVIOUS_HASH.to_string(), 1, 0, Vec::new());
+    /// let merkle_root : String = block.compute_merkle_root();
+    /// ```
+    ///
+    pub fn compute_merkle_root(
+        self: &Block,
+    ) -> String {
+        MerkleTree::new(&self.transactions).get_root()
+    }
+
+    ///
+    /// Return the inclusion proof of the transaction with the given id, if the transaction is in the block.
+    /// The proof can be verified against the Merkle root of the header without the other transactions.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_id` - A str that represents the id of the transaction.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{Block, GENESIS_PREVIOUS_HASH};
+    /// # let block = Block::new(0, GENESIS_PREVIOUS_HASH.to_string(), 1, 0, Vec::new());
+    /// let proof = block.get_merkle_proof("tx_0001");
+    /// # assert!(proof.is_none())
+    /// ```
+    ///
+    pub fn get_merkle_proof(
+        self: &Block,
+        transaction_id: &str,
+    ) -> Option<MerkleProof> {
+        MerkleTree::new(&self.transactions).get_proof(transaction_id)
+    }
+}
@@ -0,0 +1,824 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::TryFrom;
+use std::iter;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chain::{Block, ConsensusRules, create_coinbase_transaction, GENESIS_PREVIOUS_HASH, mine_block};
+use crate::error::ChainError;
+use crate::ledger::ISSUANCE_ACCOUNT_ID;
+use crate::model::{Amount, Entity, Transaction, DEFAULT_ASSET};
+
+/// Blockchain structure
+/// It represents an append-only list of blocks, each one linked to the previous one through its hash.
+/// Changing a transaction or a header of a past block breaks the links and is detected by the validation.
+/// Every block must also follow the consensus rules of the chain: difficulty, proof of work and block reward,
+/// and its transfers are replayed against the balances of the previous blocks, so that no entity spends more than it holds.
+/// Including a transfer in a block executes it: its amount and fee move whatever its state, which is not signed
+/// and so can not decide whether the transfer pays out.
+/// The state reached at the last block is kept, so that a new block is checked without replaying the chain;
+/// a deserialized chain replays its blocks the first time the state is needed.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Default)]
+pub struct Blockchain {
+    blocks: Vec<Block>,
+    #[serde(default)]
+    rules: ConsensusRules,
+    #[serde(skip)]
+    state: OnceLock<ChainState>,
+}
+
+/// State of the chain obtained by replaying its blocks: the balance of every entity in every asset,
+/// the next nonce of every sender and the ids of the transactions already in the chain.
+#[derive(Debug, Clone, Default)]
+struct ChainState {
+    balances: BTreeMap<(String, String), Amount>,
+    nonces: BTreeMap<String, u64>,
+    transaction_ids: BTreeSet<String>,
+}
+
+/// Changes made to a ChainState by transactions that are being checked. The state is read through the changes
+/// and is not copied: the changes are applied to it only once every transaction has been checked.
+struct StateChanges<'a> {
+    state: &'a ChainState,
+    changes: ChainState,
+}
+
+/// Pending state structure
+/// It represents the changes made to the state of a chain, at its last block, by transactions waiting for the next block.
+/// Every transaction is checked against the state of the chain and the transactions added before it,
+/// without replaying the chain. It is valid only as long as no block is appended to the chain it was created from.
+#[derive(Debug, Clone)]
+pub struct PendingState {
+    height: u64,
+    changes: ChainState,
+}
+
+/// Blockchain implementation.
+impl Blockchain {
+    ///
+    /// Return a new empty Blockchain that follows the default consensus rules.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::chain::Blockchain;
+    /// let blockchain = Blockchain::new();
+    /// # assert_eq!(blockchain.get_height(), 0)
+    /// ```
+    ///
+    pub fn new() -> Blockchain {
+        Blockchain::with_rules(ConsensusRules::default())
+    }
+
+    ///
+    /// Return a new empty Blockchain that follows the given consensus rules.
+    ///
+    /// # Arguments
+    ///
+    /// * `rules` - A ConsensusRules that represents the rules every block of the chain must follow.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::chain::{Blockchain, ConsensusRules};
+    /// use banana_coin::model::Amount;
+    /// let blockchain = Blockchain::with_rules(ConsensusRules::new(4, 16, 10, 5, Amount::from_integer(50)));
+    /// # assert_eq!(blockchain.get_next_difficulty(), 4)
+    /// ```
+    ///
+    pub fn with_rules(
+        rules: ConsensusRules,
+    ) -> Blockchain {
+        Blockchain {
+            blocks: Vec::new(),
+            rules,
+            state: OnceLock::from(ChainState::default()),
+        }
+    }
+
+    ///
+    /// Return a new Blockchain that follows the given consensus rules, made of the given blocks.
+    /// Every block is checked as if it was appended to the chain one after the other.
+    ///
+    /// # Arguments
+    ///
+    /// * `rules` - A ConsensusRules that represents the rules every block of the chain must follow.
+    /// * `blocks` - A Vec<Block> that represents the blocks of the chain, from the first one.
+    ///
+    /// # Errors
+    ///
+    /// Return the ChainError of the first block that can not be appended to the chain.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{Blockchain, ConsensusRules};
+    /// # let mut blockchain = Blockchain::new();
+    /// # let block = blockchain.create_block(Vec::new(), 1);
+    /// # blockchain.append_block(block).unwrap();
+    /// let copy = Blockchain::from_blocks(ConsensusRules::default(), blockchain.get_blocks()).unwrap();
+    /// # assert_eq!(copy.get_tip_hash(), blockchain.get_tip_hash())
+    /// ```
+    ///
+    pub fn from_blocks(
+        rules: ConsensusRules,
+        blocks: Vec<Block>,
+    ) -> Result<Blockchain, ChainError> {
+        let mut blockchain = Blockchain::with_rules(rules);
+        for block in blocks {
+            blockchain.append_block(block)?;
+        }
+        Ok(blockchain)
+    }
+    ///
+    /// Return a copy of the consensus rules of the chain.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{Blockchain, ConsensusRules};
+    /// # let blockchain = Blockchain::new();
+    /// let rules : ConsensusRules = blockchain.get_rules();
+    /// # assert_eq!(rules, ConsensusRules::default())
+    /// ```
+    ///
+    pub fn get_rules(
+        self: &Blockchain,
+    ) -> ConsensusRules {
+        self.rules.clone()
+    }
+
+    ///
+    /// Return the difficulty the next block of the chain must have.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::Blockchain;
+    /// # let blockchain = Blockchain::new();
+    /// let difficulty : u32 = blockchain.get_next_difficulty();
+    /// # assert_eq!(difficulty, 0)
+    /// ```
+    ///
+    pub fn get_next_difficulty(
+        self: &Blockchain,
+    ) -> u32 {
+        self.rules.get_next_difficulty(&self.blocks)
+    }
+
+    ///
+    /// Return a copy of the blocks of the chain, from the first one.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{Block, Blockchain};
+    /// # let blockchain = Blockchain::new();
+    /// let blocks : Vec<Block> = blockchain.get_blocks();
+    /// # assert!(blocks.is_empty())
+    /// ```
+    ///
+    pub fn get_blocks(
+        self: &Blockchain,
+    ) -> Vec<Block> {
+        self.blocks.clone()
+    }
+
+    ///
+    /// Return the block with the given height, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - A u64 integer that represents the height of the block.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::Blockchain;
+    /// # let blockchain = Blockchain::new();
+    /// let block = blockchain.get_block(0);
+    /// # assert!(block.is_none())
+    /// ```
+    ///
+    pub fn get_block(
+        self: &Blockchain,
+        height: u64,
+    ) -> Option<&Block> {
+        usize::try_from(height).ok().and_then(|index| self.blocks.get(index))
+    }
+
+    ///
+    /// Return the number of blocks of the chain, which is also the height of the next block.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::Blockchain;
+    /// # let blockchain = Blockchain::new();
+    /// let height : u64 = blockchain.get_height();
+    /// # assert_eq!(height, 0)
+    /// ```
+    ///
+    pub fn get_height(
+        self: &Blockchain,
+    ) -> u64 {
+        self.blocks.len() as u64
+    }
+
+    ///
+    /// Return the hash of the last block of the chain, or the genesis previous hash if the chain is empty.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{Blockchain, GENESIS_PREVIOUS_HASH};
+    /// # let blockchain = Blockchain::new();
+    /// let tip_hash : String = blockchain.get_tip_hash();
+    /// # assert_eq!(tip_hash, GENESIS_PREVIOUS_HASH)
+    /// ```
+    ///
+    pub fn get_tip_hash(
+        self: &Blockchain,
+    ) -> String {
+        match self.blocks.last() {
+            Some(block) => block.get_hash(),
+            None => GENESIS_PREVIOUS_HASH.to_string(),
+        }
+    }
+
+    ///
+    /// Return a new block containing the given transactions, linked to the last block of the chain
+    /// and with the difficulty required by the chain. The block is neither mined nor appended to the chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `transactions` - A Vec<Transaction> that represents the transactions of the block.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the block.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::Blockchain;
+    /// let mut blockchain = Blockchain::new();
+    /// let block = blockchain.create_block(Vec::new(), 1);
+    /// blockchain.append_block(block).unwrap();
+    /// # assert_eq!(blockchain.get_height(), 1)
+    /// ```
+    ///
+    pub fn create_block(
+        self: &Blockchain,
+        transactions: Vec<Transaction>,
+        timestamp: u64,
+    ) -> Block {
+        Block::new(self.get_height(), self.get_tip_hash(), timestamp, self.get_next_difficulty(), transactions)
+    }
+
+    ///
+    /// Mine a new block containing a coinbase transaction followed by the given transactions,
+    /// append it to the chain and credit the block reward and the fees of the transactions of the block
+    /// to the wallet of the miner.
+    ///
+    /// # Arguments
+    ///
+    /// * `miner` - An Entity that represents the miner of the block.
+    /// * `transactions` - A Vec<Transaction> that represents the transactions of the block, after the coinbase.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the block.
+    ///
+    /// # Errors
+    ///
+    /// Return a ChainError if the block can not be appended to the chain or if the reward can not be credited
+    /// to the miner. In both cases neither the chain nor the miner are changed.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::Blockchain;
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
+    /// let mut blockchain = Blockchain::new();
+    /// let mut miner = Entity::new("id_0001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// let block = blockchain.mine_block(&mut miner, Vec::new(), 1).unwrap();
+    /// # assert_eq!(blockchain.get_tip_hash(), block.get_hash());
+    /// # assert_eq!(miner.get_wallet().get_balance(), Amount::from_integer(50))
+    /// ```
+    ///
+    pub fn mine_block(
+        self: &mut Blockchain,
+        miner: &mut Entity,
+        transactions: Vec<Transaction>,
+        timestamp: u64,
+    ) -> Result<Block, ChainError> {
+        let height = self.get_height();
+        let block_reward = self.rules.get_block_reward();
+        let coinbase = create_coinbase_transaction(height, miner.get_id().to_string(), block_reward, timestamp)
+            .map_err(|error| ChainError::InvalidTransactionError {
+                height,
+                transaction_id: format!("coinbase_{:06}", height),
+                error,
+            })?;
+        let fees = transactions.iter()
+            .map(|transaction| (transaction.get_asset(), transaction.get_fee().unwrap_or_default()));
+        let mut rewarded_miner = miner.clone();
+        for (asset, coins) in iter::once((DEFAULT_ASSET.to_string(), block_reward)).chain(fees).filter(|(_, coins)| !coins.is_zero()) {
+            if let Err(error) = rewarded_miner.add_asset_coins(&asset, coins) {
+                return Err(ChainError::MinerOperationError { entity_id: miner.get_id().to_string(), error });
+            }
+        }
+        let mut block_transactions = vec![coinbase];
+        block_transactions.extend(transactions);
+        let mut block = self.create_block(block_transactions, timestamp);
+        mine_block(&mut block)?;
+        self.append_block(block.clone())?;
+        *miner = rewarded_miner;
+        Ok(block)
+    }
+
+    ///
+    /// Append a block to the chain, after checking that it is linked to the last block of the chain
+    /// and that its content matches its header.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - A Block that represents the block to append.
+    ///
+    /// # Errors
+    ///
+    /// Return a ChainError if the block has a wrong height, refers to a different previous block,
+    /// has a timestamp lower than the previous block, has a wrong difficulty or a hash that does not meet it,
+    /// has a Merkle root that does not match its transactions, contains a transaction that is not valid
+    /// or mints coins other than the block reward through its first transaction.
+    /// A transfer must also be new to the chain, signed with the registered key of its sender, carry the next nonce
+    /// of the sender and spend only coins the sender holds. The chain is left unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{Block, Blockchain, GENESIS_PREVIOUS_HASH};
+    /// let mut blockchain = Blockchain::new();
+    /// let block = Block::new(1, GENESIS_PREVIOUS_HASH.to_string(), 1, 0, Vec::new());
+    /// let result = blockchain.append_block(block);
+    /// # assert!(result.is_err())
+    /// ```
+    ///
+    pub fn append_block(
+        self: &mut Blockchain,
+        block: Block,
+    ) -> Result<(), ChainError> {
+        let mut changes = StateChanges::new(self.get_state()?);
+        self.check_block(&self.blocks, &mut changes, &block)?;
+        let changes = changes.changes;
+        if let Some(state) = self.state.get_mut() {
+            state.apply(changes);
+        }
+        self.blocks.push(block);
+        Ok(())
+    }
+
+    ///
+    /// Check that the given transactions, in the given order, could be included in the next block of the chain:
+    /// each one must be valid, new to the chain, signed with the registered key of its sender, carry the next nonce
+    /// of the sender and spend only coins the sender holds. None of them can mint coins.
+    ///
+    /// # Arguments
+    ///
+    /// * `transactions` - A slice of Transaction that represents the transactions following the last block of the chain.
+    ///
+    /// # Errors
+    ///
+    /// Return the ChainError of the chain if it is not valid, or the ChainError of the first transaction
+    /// that can not follow the chain and the transactions before it, with the height of the next block.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::Blockchain;
+    /// # use banana_coin::model::{Amount, Transaction};
+    /// # use banana_coin::model::TransactionState::*;
+    /// let blockchain = Blockchain::new();
+    /// let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(),
+    ///     Amount::from_integer(100), "".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// let result = blockchain.check_transactions(&[transaction]);
+    /// # assert!(result.is_err())
+    /// ```
+    ///
+    pub fn check_transactions(
+        self: &Blockchain,
+        transactions: &[Transaction],
+    ) -> Result<(), ChainError> {
+        let mut pending_state = self.new_pending_state();
+        for transaction in transactions.iter() {
+            self.check_pending_transaction(&mut pending_state, transaction)?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Return a new PendingState at the last block of the chain, with no pending transactions.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{Blockchain, PendingState};
+    /// let blockchain = Blockchain::new();
+    /// let pending_state : PendingState = blockchain.new_pending_state();
+    /// ```
+    ///
+    pub fn new_pending_state(
+        self: &Blockchain,
+    ) -> PendingState {
+        PendingState {
+            height: self.get_height(),
+            changes: ChainState::default(),
+        }
+    }
+
+    ///
+    /// Check that the given transaction could follow the last block of the chain and the transactions
+    /// already added to the pending state, then add it to the pending state. It must be valid, new to the chain,
+    /// signed with the registered key of its sender, carry the next nonce of the sender and spend only coins
+    /// the sender holds. It can not mint coins. The chain is not replayed.
+    ///
+    /// # Arguments
+    ///
+    /// * `pending_state` - A PendingState created from the chain since its last block was appended.
+    /// * `transaction` - A Transaction that represents the transaction following the pending ones.
+    ///
+    /// # Errors
+    ///
+    /// Return the ChainError of the chain if it is not valid, or the ChainError of the transaction
+    /// with the height of the next block. The pending state is left unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::Blockchain;
+    /// # use banana_coin::model::{Amount, Transaction};
+    /// # use banana_coin::model::TransactionState::*;
+    /// let blockchain = Blockchain::new();
+    /// let mut pending_state = blockchain.new_pending_state();
+    /// let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(),
+    ///     Amount::from_integer(100), "".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// let result = blockchain.check_pending_transaction(&mut pending_state, &transaction);
+    /// # assert!(result.is_err())
+    /// ```
+    ///
+    pub fn check_pending_transaction(
+        self: &Blockchain,
+        pending_state: &mut PendingState,
+        transaction: &Transaction,
+    ) -> Result<(), ChainError> {
+        Blockchain::check_pending_form(pending_state.height, transaction)?;
+        self.rules.check_signature(pending_state.height, transaction)?;
+        self.apply_pending_transaction(pending_state, transaction)
+    }
+
+    /// Add a transaction whose signature has been checked with the rules of the chain to the pending state,
+    /// if it is valid, does not mint coins, is new to the chain, carries the next nonce of its sender
+    /// and spends only coins the sender holds. The pending state is left unchanged on error.
+    pub(crate) fn add_pending_transaction(
+        self: &Blockchain,
+        pending_state: &mut PendingState,
+        transaction: &Transaction,
+    ) -> Result<(), ChainError> {
+        Blockchain::check_pending_form(pending_state.height, transaction)?;
+        self.apply_pending_transaction(pending_state, transaction)
+    }
+
+    /// Check that a pending transaction is valid and does not mint coins.
+    fn check_pending_form(
+        height: u64,
+        transaction: &Transaction,
+    ) -> Result<(), ChainError> {
+        if let Err(error) = transaction.validate() {
+            return Err(ChainError::InvalidTransactionError { height, transaction_id: transaction.get_id(), error });
+        }
+        if transaction.get_from_entity_id() == ISSUANCE_ACCOUNT_ID {
+            return Err(ChainError::MisplacedCoinbaseError { height, transaction_id: transaction.get_id() });
+        }
+        Ok(())
+    }
+
+    /// Check a pending transaction against the state of the chain and the pending state, then add it to the pending state.
+    fn apply_pending_transaction(
+        self: &Blockchain,
+        pending_state: &mut PendingState,
+        transaction: &Transaction,
+    ) -> Result<(), ChainError> {
+        let mut changes = StateChanges::with_changes(self.get_state()?, std::mem::take(&mut pending_state.changes));
+        let result = Blockchain::check_transfer(&mut changes, pending_state.height, ISSUANCE_ACCOUNT_ID, transaction);
+        pending_state.changes = changes.changes;
+        result
+    }
+
+    ///
+    /// Check the whole chain, block by block, from the first one.
+    ///
+    /// # Errors
+    ///
+    /// Return the ChainError of the first block that is not linked to its previous block
+    /// or whose content does not match its header.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::Blockchain;
+    /// let blockchain = Blockchain::new();
+    /// let result = blockchain.validate();
+    /// # assert!(result.is_ok())
+    /// ```
+    ///
+    pub fn validate(
+        self: &Blockchain,
+    ) -> Result<(), ChainError> {
+        self.replay().map(|_| ())
+    }
+
+    ///
+    /// Return the balance in the default asset of every entity that appears in a transaction of the chain,
+    /// obtained by replaying the transactions of the chain from zero balances.
+    /// Every transaction moves its coins, whatever its state. The fees of a block are credited to the miner of the block.
+    ///
+    /// # Errors
+    ///
+    /// Return a ChainError if the chain is not valid or if a balance overflows.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::Amount;
+    /// # use std::collections::BTreeMap;
+    /// # use banana_coin::chain::Blockchain;
+    /// let blockchain = Blockchain::new();
+    /// let balances : BTreeMap<String, Amount> = blockchain.get_balances().unwrap();
+    /// # assert!(balances.is_empty())
+    /// ```
+    ///
+    pub fn get_balances(
+        self: &Blockchain,
+    ) -> Result<BTreeMap<String, Amount>, ChainError> {
+        self.get_asset_balances(DEFAULT_ASSET)
+    }
+
+    ///
+    /// Return the balance in the given asset of every entity that appears in a transaction
+    /// of the chain moving that asset, obtained by replaying those transactions from zero balances.
+    /// Every transaction moves its coins, whatever its state. The fees of a block are credited to the miner of the block,
+    /// in the asset of the transaction paying them.
+    ///
+    /// # Arguments
+    ///
+    /// * `asset` - A str that represents the code of the asset.
+    ///
+    /// # Errors
+    ///
+    /// Return a ChainError if the chain is not valid or if a balance overflows.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::Amount;
+    /// # use std::collections::BTreeMap;
+    /// # use banana_coin::chain::Blockchain;
+    /// let blockchain = Blockchain::new();
+    /// let balances : BTreeMap<String, Amount> = blockchain.get_asset_balances("GOLD").unwrap();
+    /// # assert!(balances.is_empty())
+    /// ```
+    ///
+    pub fn get_asset_balances(
+        self: &Blockchain,
+        asset: &str,
+    ) -> Result<BTreeMap<String, Amount>, ChainError> {
+        let balances = self.get_state()?.balances.iter()
+            .filter(|((_, balance_asset), _)| balance_asset == asset)
+            .map(|((entity_id, _), balance)| (entity_id.clone(), *balance))
+            .collect();
+        Ok(balances)
+    }
+
+    /// Return the state at the last block of the chain, replaying the chain if it is not known yet.
+    fn get_state(
+        self: &Blockchain,
+    ) -> Result<&ChainState, ChainError> {
+        if let Some(state) = self.state.get() {
+            return Ok(state);
+        }
+        let state = self.replay()?;
+        Ok(self.state.get_or_init(|| state))
+    }
+
+    /// Check every block of the chain from the first one and return the state obtained by replaying them.
+    fn replay(
+        self: &Blockchain,
+    ) -> Result<ChainState, ChainError> {
+        let mut state = ChainState::default();
+        for (height, block) in self.blocks.iter().enumerate() {
+            let mut changes = StateChanges::new(&state);
+            self.check_block(&self.blocks[..height], &mut changes, block)?;
+            let changes = changes.changes;
+            state.apply(changes);
+        }
+        Ok(state)
+    }
+
+    /// Check that a block follows the given blocks, that it respects the consensus rules,
+    /// that its content matches its header and that its transfers can be replayed on the state of the given blocks,
+    /// recording their changes. The changes must be dropped if the block is not valid.
+    fn check_block(
+        self: &Blockchain,
+        previous_blocks: &[Block],
+        changes: &mut StateChanges,
+        block: &Block,
+    ) -> Result<(), ChainError> {
+        let previous_block = previous_blocks.last();
+        let expected_height = previous_blocks.len() as u64;
+        let header = block.get_header();
+        let height = header.get_height();
+        if height != expected_height {
+            return Err(ChainError::InvalidHeightError { expected_height, found_height: height });
+        }
+        let expected_hash = match previous_block {
+            Some(previous_block) => previous_block.get_hash(),
+            None => GENESIS_PREVIOUS_HASH.to_string(),
+        };
+        if header.get_previous_hash() != expected_hash {
+            return Err(ChainError::InvalidPreviousHashError {
+                height,
+                expected_hash,
+                found_hash: header.get_previous_hash().to_string(),
+            });
+        }
+        if let Some(previous_block) = previous_block {
+            let previous_timestamp = previous_block.get_header().get_timestamp();
+            if header.get_timestamp() < previous_timestamp {
+                return Err(ChainError::DecreasingTimestampError {
+                    height,
+                    previous_timestamp,
+                    timestamp: header.get_timestamp(),
+                });
+            }
+        }
+        let expected_difficulty = self.rules.get_next_difficulty(previous_blocks);
+        if header.get_difficulty() != expected_difficulty {
+            return Err(ChainError::InvalidDifficultyError {
+                height,
+                expected_difficulty,
+                found_difficulty: header.get_difficulty(),
+            });
+        }
+        if !header.meets_difficulty() {
+            return Err(ChainError::InvalidProofOfWorkError {
+                height,
+                difficulty: header.get_difficulty(),
+                hash: header.get_hash(),
+            });
+        }
+        let merkle_root = block.compute_merkle_root();
+        if header.get_merkle_root() != merkle_root {
+            return Err(ChainError::InvalidMerkleRootError {
+                height,
+                expected_root: merkle_root,
+                found_root: header.get_merkle_root().to_string(),
+            });
+        }
+        for (index, transaction) in block.get_transactions().iter().enumerate() {
+            if let Err(error) = transaction.validate() {
+                return Err(ChainError::InvalidTransactionError {
+                    height,
+                    transaction_id: transaction.get_id(),
+                    error,
+                });
+            }
+            if transaction.get_from_entity_id() != ISSUANCE_ACCOUNT_ID {
+                continue;
+            }
+            if index != 0 {
+                return Err(ChainError::MisplacedCoinbaseError { height, transaction_id: transaction.get_id() });
+            }
+            if transaction.get_asset() != DEFAULT_ASSET {
+                return Err(ChainError::InvalidRewardAssetError {
+                    height,
+                    expected_asset: DEFAULT_ASSET.to_string(),
+                    found_asset: transaction.get_asset(),
+                });
+            }
+            let block_reward = self.rules.get_block_reward();
+            if transaction.get_amount() != block_reward {
+                return Err(ChainError::InvalidBlockRewardError {
+                    height,
+                    expected_reward: block_reward,
+                    found_reward: transaction.get_amount(),
+                });
+            }
+        }
+        // The fees go to the miner of the block, or back to the issuance account if the block has no coinbase
+        let transactions = block.get_transactions();
+        let fee_collector_id = match transactions.first() {
+            Some(coinbase) if coinbase.get_from_entity_id() == ISSUANCE_ACCOUNT_ID => coinbase.get_to_entity_id(),
+            _ => ISSUANCE_ACCOUNT_ID.to_string(),
+        };
+        for transaction in transactions.iter() {
+            if transaction.get_from_entity_id() != ISSUANCE_ACCOUNT_ID {
+                self.rules.check_signature(height, transaction)?;
+            }
+            Blockchain::check_transfer(changes, height, &fee_collector_id, transaction)?;
+        }
+        Ok(())
+    }
+
+    /// Check that a transaction is new, that it carries the next nonce of its sender and that the sender holds
+    /// the coins it spends, then record its amount and fee in the changes, whatever its state.
+    /// The signature is checked by the caller; the coinbase is checked by 'check_block' and only moves the block reward.
+    /// The changes are left untouched if the transaction can not be applied.
+    fn check_transfer(
+        changes: &mut StateChanges,
+        height: u64,
+        fee_collector_id: &str,
+        transaction: &Transaction,
+    ) -> Result<(), ChainError> {
+        let transaction_id = transaction.get_id();
+        if changes.contains_transaction_id(&transaction_id) {
+            return Err(ChainError::DuplicateTransactionIdError { height, transaction_id });
+        }
+        let from_entity_id = transaction.get_from_entity_id();
+        let is_coinbase = from_entity_id == ISSUANCE_ACCOUNT_ID;
+        let nonce = changes.get_nonce(&from_entity_id);
+        if !is_coinbase && transaction.get_nonce() != nonce {
+            return Err(ChainError::InvalidNonceError {
+                height,
+                transaction_id,
+                expected_nonce: nonce,
+                found_nonce: transaction.get_nonce(),
+            });
+        }
+        // The state is not signed, so that anyone relaying the transfer could change it: it is ignored
+        let asset = transaction.get_asset();
+        let amount = transaction.get_amount();
+        let fee = transaction.get_fee().unwrap_or_default();
+        let to_entity_id = transaction.get_to_entity_id();
+        let mut balances = BTreeMap::new();
+        let mut moves = vec![(from_entity_id.as_str(), amount, true), (to_entity_id.as_str(), amount, false)];
+        if !fee.is_zero() {
+            moves.push((from_entity_id.as_str(), fee, true));
+            moves.push((fee_collector_id, fee, false));
+        }
+        for (entity_id, coins, debit) in moves {
+            let balance = balances.entry(entity_id).or_insert_with(|| changes.get_balance(entity_id, &asset));
+            let new_balance = if debit { balance.checked_sub(coins) } else { balance.checked_add(coins) };
+            *balance = match new_balance {
+                Some(new_balance) => new_balance,
+                None => return Err(ChainError::BalanceOverflowError { height, transaction_id, entity_id: entity_id.to_string() }),
+            };
+        }
+        if !is_coinbase && balances[from_entity_id.as_str()].is_negative() {
+            return Err(ChainError::InsufficientBalanceError { height, transaction_id, entity_id: from_entity_id });
+        }
+        if !is_coinbase {
+            changes.changes.nonces.insert(from_entity_id.clone(), nonce + 1);
+        }
+        for (entity_id, balance) in balances {
+            changes.changes.balances.insert((entity_id.to_string(), asset.clone()), balance);
+        }
+        changes.changes.transaction_ids.insert(transaction_id);
+        Ok(())
+    }
+}
+
+/// Chain state implementation.
+impl ChainState {
+    /// Apply the changes made to the state by the transactions of a block.
+    fn apply(
+        self: &mut ChainState,
+        changes: ChainState,
+    ) {
+        self.balances.extend(changes.balances);
+        self.nonces.extend(changes.nonces);
+        self.transaction_ids.extend(changes.transaction_ids);
+    }
+}
+
+/// State changes implementation.
+impl<'a> StateChanges<'a> {
+    /// Return empty changes to the given state.
+    fn new(
+        state: &'a ChainState,
+    ) -> StateChanges<'a> {
+        StateChanges::with_changes(state, ChainState::default())
+    }
+
+    /// Return the given changes to the given state.
+    fn with_changes(
+        state: &'a ChainState,
+        changes: ChainState,
+    ) -> StateChanges<'a> {
+        StateChanges { state, changes }
+    }
+
+    /// Return the balance of an entity in an asset, zero if the entity never held the asset.
+    fn get_balance(
+        self: &StateChanges<'a>,
+        entity_id: &str,
+        asset: &str,
+    ) -> Amount {
+        let key = (entity_id.to_string(), asset.to_string());
+        self.changes.balances.get(&key).or_else(|| self.state.balances.get(&key)).copied().unwrap_or_default()
+    }
+
+    /// Return the next nonce of a sender.
+    fn get_nonce(
+        self: &StateChanges<'a>,
+        entity_id: &str,
+    ) -> u64 {
+        self.changes.nonces.get(entity_id).or_else(|| self.state.nonces.get(entity_id)).copied().unwrap_or_default()
+    }
+
+    /// Return whether a transaction with the given id is already in the chain or in the changes.
+    fn contains_transaction_id(
+        self: &StateChanges<'a>,
+        transaction_id: &str,
+    ) -> bool {
+        self.changes.transaction_ids.contains(transaction_id) || self.state.transaction_ids.contains(transaction_id)
+    }
+}
@@ -0,0 +1,331 @@
+use std::cmp;
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chain::Block;
+use crate::error::ChainError;
+use crate::model::{Amount, Transaction};
+
+/// Block reward of the default consensus rules.
+pub const DEFAULT_BLOCK_REWARD: Amount = Amount::from_integer(50);
+
+/// Highest possible difficulty: every bit of a SHA-256 hash set to zero.
+pub const MAX_DIFFICULTY: u32 = 256;
+
+/// Consensus rules structure
+/// It represents the rules every node of the network agrees on: how hard it is to mine a block,
+/// how the difficulty follows the pace of the blocks and how many coins a miner earns for a block.
+/// The difficulty is the number of leading zero bits the hash of a block header must have.
+/// The rules also hold the public keys of the entities allowed to send coins: every transfer of a block
+/// must be signed by its sender with the registered key.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsensusRules {
+    initial_difficulty: u32,
+    max_difficulty: u32,
+    target_block_interval: u64,
+    retarget_window: u64,
+    block_reward: Amount,
+    #[serde(default)]
+    public_keys: BTreeMap<String, String>,
+}
+
+/// Default consensus rules: difficulty fixed to zero, so that blocks are mined at the first nonce.
+impl Default for ConsensusRules {
+    fn default() -> ConsensusRules {
+        ConsensusRules::new(0, 0, 1, 2, DEFAULT_BLOCK_REWARD)
+    }
+}
+
+/// Consensus rules implementation.
+impl ConsensusRules {
+    ///
+    /// Return new ConsensusRules.
+    ///
+    /// # Arguments
+    ///
+    /// * `initial_difficulty` - A u32 integer that represents the difficulty of the first block.
+    /// * `max_difficulty` - A u32 integer that represents the highest difficulty the retarget can reach, at most 256.
+    /// * `target_block_interval` - A u64 integer that represents the expected time between two blocks.
+    /// * `retarget_window` - A u64 integer that represents every how many blocks the difficulty is adjusted, at least 2.
+    /// * `block_reward` - An Amount that represents the coins of the default asset credited to the miner of a block.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::chain::ConsensusRules;
+    /// use banana_coin::model::Amount;
+    /// let rules = ConsensusRules::new(
+    ///     4,
+    ///     16,
+    ///     10,
+    ///     5,
+    ///     Amount::from_integer(50)
+    /// );
+    /// # assert_eq!(rules.get_initial_difficulty(), 4)
+    /// ```
+    ///
+    pub fn new(
+        initial_difficulty: u32,
+        max_difficulty: u32,
+        target_block_interval: u64,
+        retarget_window: u64,
+        block_reward: Amount,
+    ) -> ConsensusRules {
+        let max_difficulty = cmp::min(max_difficulty, MAX_DIFFICULTY);
+        ConsensusRules {
+            initial_difficulty: cmp::min(initial_difficulty, max_difficulty),
+            max_difficulty,
+            target_block_interval,
+            retarget_window: cmp::max(retarget_window, 2),
+            block_reward,
+            public_keys: BTreeMap::new(),
+        }
+    }
+
+    ///
+    /// Return the difficulty of the first block.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::ConsensusRules;
+    /// # use banana_coin::model::Amount;
+    /// # let rules = ConsensusRules::new(4, 16, 10, 5, Amount::from_integer(50));
+    /// let initial_difficulty : u32 = rules.get_initial_difficulty();
+    /// # assert_eq!(initial_difficulty, 4)
+    /// ```
+    ///
+    pub fn get_initial_difficulty(
+        self: &ConsensusRules,
+    ) -> u32 {
+        self.initial_difficulty
+    }
+
+    ///
+    /// Return the highest difficulty the retarget can reach.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::ConsensusRules;
+    /// # use banana_coin::model::Amount;
+    /// # let rules = ConsensusRules::new(4, 16, 10, 5, Amount::from_integer(50));
+    /// let max_difficulty : u32 = rules.get_max_difficulty();
+    /// # assert_eq!(max_difficulty, 16)
+    /// ```
+    ///
+    pub fn get_max_difficulty(
+        self: &ConsensusRules,
+    ) -> u32 {
+        self.max_difficulty
+    }
+
+    ///
+    /// Return the expected time between two blocks.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::ConsensusRules;
+    /// # use banana_coin::model::Amount;
+    /// # let rules = ConsensusRules::new(4, 16, 10, 5, Amount::from_integer(50));
+    /// let target_block_interval : u64 = rules.get_target_block_interval();
+    /// # assert_eq!(target_block_interval, 10)
+    /// ```
+    ///
+    pub fn get_target_block_interval(
+        self: &ConsensusRules,
+    ) -> u64 {
+        self.target_block_interval
+    }
+
+    ///
+    /// Return every how many blocks the difficulty is adjusted.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::ConsensusRules;
+    /// # use banana_coin::model::Amount;
+    /// # let rules = ConsensusRules::new(4, 16, 10, 5, Amount::from_integer(50));
+    /// let retarget_window : u64 = rules.get_retarget_window();
+    /// # assert_eq!(retarget_window, 5)
+    /// ```
+    ///
+    pub fn get_retarget_window(
+        self: &ConsensusRules,
+    ) -> u64 {
+        self.retarget_window
+    }
+
+    ///
+    /// Return the coins credited to the miner of a block.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::ConsensusRules;
+    /// # use banana_coin::model::Amount;
+    /// # let rules = ConsensusRules::new(4, 16, 10, 5, Amount::from_integer(50));
+    /// let block_reward : Amount = rules.get_block_reward();
+    /// # assert_eq!(block_reward, Amount::from_integer(50))
+    /// ```
+    ///
+    pub fn get_block_reward(
+        self: &ConsensusRules,
+    ) -> Amount {
+        self.block_reward
+    }
+
+    ///
+    /// Register the public key of an entity.
+    /// Only the transfers signed with the registered key of their sender are accepted in a block.
+    /// A registered key can not be replaced, since the blocks already signed with it would no longer be valid.
+    /// Registering the same key again has no effect.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A String that represents the id of the entity.
+    /// * `public_key` - A String that represents the public key of the entity, hex encoded.
+    ///
+    /// # Errors
+    ///
+    /// Return a PublicKeyReplacementError if the entity already has a different registered key.
+    /// The rules are left unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::ConsensusRules;
+    /// # use banana_coin::crypto::KeyPair;
+    /// let key_pair = KeyPair::generate();
+    /// let mut rules = ConsensusRules::default();
+    /// rules.register_public_key("id_0001".to_string(), key_pair.get_public_key()).unwrap();
+    /// # assert_eq!(rules.get_public_key("id_0001"), Some(key_pair.get_public_key()));
+    /// # assert!(rules.register_public_key("id_0001".to_string(), KeyPair::generate().get_public_key()).is_err())
+    /// ```
+    ///
+    pub fn register_public_key(
+        self: &mut ConsensusRules,
+        entity_id: String,
+        public_key: String,
+    ) -> Result<(), ChainError> {
+        match self.public_keys.get(&entity_id) {
+            Some(registered_key) if *registered_key != public_key => Err(ChainError::PublicKeyReplacementError { entity_id }),
+            Some(_) => Ok(()),
+            None => {
+                self.public_keys.insert(entity_id, public_key);
+                Ok(())
+            }
+        }
+    }
+
+    ///
+    /// Return the registered public key of an entity, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A str that represents the id of the entity.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::ConsensusRules;
+    /// # let rules = ConsensusRules::default();
+    /// let public_key : Option<String> = rules.get_public_key("id_0001");
+    /// # assert!(public_key.is_none())
+    /// ```
+    ///
+    pub fn get_public_key(
+        self: &ConsensusRules,
+        entity_id: &str,
+    ) -> Option<String> {
+        self.public_keys.get(entity_id).cloned()
+    }
+
+    ///
+    /// Check that a transfer is signed with the registered key of its sender. It does not depend on the state
+    /// of the chain, so that it can be checked before the transfer is checked against the chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - A u64 integer that represents the height of the block including the transfer.
+    /// * `transaction` - A Transaction that represents the transfer.
+    ///
+    /// # Errors
+    ///
+    /// Return an UnknownSenderError if the sender has no registered key,
+    /// an InvalidSignatureError if the transfer is not signed with it.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::ConsensusRules;
+    /// # use banana_coin::crypto::KeyPair;
+    /// # use banana_coin::model::{Amount, Transaction};
+    /// # use banana_coin::model::TransactionState::*;
+    /// let key_pair = KeyPair::generate();
+    /// let mut rules = ConsensusRules::default();
+    /// rules.register_public_key("entity_001".to_string(), key_pair.get_public_key()).unwrap();
+    /// let mut transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(),
+    ///     Amount::from_integer(100), "".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// transaction.sign(&key_pair);
+    /// let result = rules.check_signature(0, &transaction);
+    /// # assert!(result.is_ok())
+    /// ```
+    ///
+    pub fn check_signature(
+        self: &ConsensusRules,
+        height: u64,
+        transaction: &Transaction,
+    ) -> Result<(), ChainError> {
+        let transaction_id = transaction.get_id();
+        let from_entity_id = transaction.get_from_entity_id();
+        let public_key = match self.public_keys.get(&from_entity_id) {
+            Some(public_key) => public_key,
+            None => return Err(ChainError::UnknownSenderError { height, transaction_id, entity_id: from_entity_id }),
+        };
+        if let Err(error) = transaction.verify_signature(public_key) {
+            return Err(ChainError::InvalidSignatureError { height, transaction_id, error });
+        }
+        Ok(())
+    }
+
+    ///
+    /// Return the difficulty the block following the given blocks must have.
+    /// The difficulty changes only when the number of blocks is a multiple of the retarget window:
+    /// it goes up by one if the last window of blocks took less than half of the expected time,
+    /// it goes down by one if it took more than twice the expected time.
+    ///
+    /// # Arguments
+    ///
+    /// * `blocks` - A slice of Block that represents the chain, from the first block.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::ConsensusRules;
+    /// # use banana_coin::model::Amount;
+    /// # let rules = ConsensusRules::new(4, 16, 10, 5, Amount::from_integer(50));
+    /// let difficulty : u32 = rules.get_next_difficulty(&[]);
+    /// # assert_eq!(difficulty, 4)
+    /// ```
+    ///
+    pub fn get_next_difficulty(
+        self: &ConsensusRules,
+        blocks: &[Block],
+    ) -> u32 {
+        let last_block = match blocks.last() {
+            Some(last_block) => last_block,
+            None => return self.initial_difficulty,
+        };
+        let difficulty = last_block.get_header().get_difficulty();
+        let window = self.retarget_window as usize;
+        if !blocks.len().is_multiple_of(window) {
+            return difficulty;
+        }
+        let first_block = &blocks[blocks.len() - window];
+        let elapsed = last_block.get_header().get_timestamp()
+            .saturating_sub(first_block.get_header().get_timestamp());
+        let expected = self.target_block_interval.saturating_mul(self.retarget_window - 1);
+        if elapsed.saturating_mul(2) < expected {
+            cmp::min(difficulty.saturating_add(1), self.max_difficulty)
+        } else if elapsed > expected.saturating_mul(2) {
+            difficulty.saturating_sub(1)
+        } else {
+            difficulty
+        }
+    }
+}
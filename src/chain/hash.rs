@@ -0,0 +1,67 @@
+use sha2::{Digest, Sha256};
+
+use crate::model::Transaction;
+
+///
+/// Return the SHA-256 digest of the given bytes, hex encoded.
+///
+/// # Arguments
+///
+/// * `bytes` - A slice of bytes that represents the data to hash.
+///
+/// # Example
+/// ```
+/// use banana_coin::chain::sha256;
+/// let hash : String = sha256(b"abc");
+/// # assert_eq!(hash, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+/// ```
+///
+pub fn sha256(
+    bytes: &[u8],
+) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+///
+/// Return the hash of a transaction: the SHA-256 digest of its signing bytes followed by its signature, hex encoded.
+/// The states of the transaction do not contribute to the hash, so that it does not change while the transaction
+/// is processed, and neither does the encoding of its JSON representation.
+///
+/// # Arguments
+///
+/// * `transaction` - A Transaction that represents the transaction to hash.
+///
+/// # Example
+/// ```
+/// # use banana_coin::model::{Amount, Transaction};
+/// # use banana_coin::model::TransactionState::*;
+/// use banana_coin::chain::hash_transaction;
+/// # let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(),
+/// #     Amount::from_integer(100), "".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+/// let hash : String = hash_transaction(&transaction);
+/// # assert_eq!(hash.len(), 64);
+/// # let mut processed = transaction.clone();
+/// # processed.transition_to(OnProcess {timestamp: 2}).unwrap();
+/// # assert_eq!(hash_transaction(&processed), hash)
+/// ```
+///
+pub fn hash_transaction(
+    transaction: &Transaction,
+) -> String {
+    hex::encode(transaction_digest(transaction))
+}
+
+/// Return the SHA-256 digest of the signing bytes of a transaction followed by its signature.
+pub(crate) fn transaction_digest(
+    transaction: &Transaction,
+) -> Vec<u8> {
+    let mut bytes = transaction.get_signing_bytes();
+    match transaction.get_signature() {
+        Some(signature) => {
+            bytes.push(1);
+            bytes.extend_from_slice(signature.as_bytes());
+        }
+        None => bytes.push(0),
+    }
+    Sha256::digest(&bytes).to_vec()
+}
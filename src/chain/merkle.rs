@@ -0,0 +1,453 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::chain::{hash_transaction, sha256, transaction_digest};
+use crate::error::ChainError;
+use crate::model::Transaction;
+
+/// Prefix of the bytes hashed into a leaf node, so that a leaf can not be taken for an inner node.
+const LEAF_PREFIX: u8 = 0x00;
+
+/// Prefix of the bytes hashed into an inner node, so that an inner node can not be taken for a leaf.
+const NODE_PREFIX: u8 = 0x01;
+
+///
+/// Return the Merkle root of a list of hex encoded hashes.
+/// Each leaf node hashes a leaf prefixed by 0x00 and each inner node hashes the concatenation of two consecutive nodes
+/// prefixed by 0x01. The last node of a level with an odd number of nodes is carried up to the next level as it is,
+/// so that no two lists of leaves share a root. The root of an empty list is the hash of no bytes.
+///
+/// # Arguments
+///
+/// * `leaves` - A slice of String that represents the hex encoded hashes of the leaves.
+///
+/// # Example
+/// ```
+/// use banana_coin::chain::{compute_merkle_root, sha256};
+/// let leaf = sha256(b"leaf");
+/// let root : String = compute_merkle_root(&[leaf.clone()]).unwrap();
+/// # assert_ne!(root, leaf);
+/// # assert!(compute_merkle_root(&["not hex".to_string()]).is_err())
+/// ```
+///
+/// # Errors
+///
+/// An InvalidHashError is returned if one of the leaves is not hex encoded.
+///
+pub fn compute_merkle_root(
+    leaves: &[String],
+) -> Result<String, ChainError> {
+    let leaves = leaves.iter().map(|leaf| decode_hash(leaf)).collect::<Result<Vec<Vec<u8>>, ChainError>>()?;
+    Ok(match build_levels(&leaves).last().and_then(|level| level.first()) {
+        Some(root) => hex::encode(root),
+        None => sha256(&[]),
+    })
+}
+
+/// Return the bytes of a hex encoded hash, or an InvalidHashError.
+fn decode_hash(
+    hash: &str,
+) -> Result<Vec<u8>, ChainError> {
+    hex::decode(hash).map_err(|_| ChainError::InvalidHashError { hash: hash.to_string() })
+}
+
+/// Return the leaf node of a leaf: the hash of its bytes prefixed by LEAF_PREFIX.
+fn hash_leaf(
+    leaf: &[u8],
+) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(leaf);
+    hasher.finalize().to_vec()
+}
+
+/// Return the inner node of two nodes: the hash of their concatenation prefixed by NODE_PREFIX.
+fn hash_pair(
+    left: &[u8],
+    right: &[u8],
+) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().to_vec()
+}
+
+/// Return every level of the tree built over the given leaves, from the leaf nodes up to the root.
+fn build_levels(
+    leaves: &[Vec<u8>],
+) -> Vec<Vec<Vec<u8>>> {
+    if leaves.is_empty() {
+        return Vec::new();
+    }
+    let mut levels = vec![leaves.iter().map(|leaf| hash_leaf(leaf)).collect::<Vec<Vec<u8>>>()];
+    while levels[levels.len() - 1].len() > 1 {
+        let level = levels[levels.len() - 1]
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_pair(left, right),
+                _ => pair[0].clone(),
+            })
+            .collect();
+        levels.push(level);
+    }
+    levels
+}
+
+/// Merkle side enum
+/// It represents on which side of the running hash a sibling hash of a proof is concatenated.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MerkleSide {
+    Left,
+    Right,
+}
+
+/// Merkle proof step structure
+/// It represents a sibling hash met while walking from a leaf up to the root.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MerkleProofStep {
+    hash: String,
+    side: MerkleSide,
+}
+
+/// Merkle proof step implementation.
+impl MerkleProofStep {
+    ///
+    /// Return a new MerkleProofStep.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - A String that represents the sibling hash, hex encoded.
+    /// * `side` - A MerkleSide that represents the side of the sibling hash.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::chain::{MerkleProofStep, MerkleSide};
+    /// let step = MerkleProofStep::new("abcd".to_string(), MerkleSide::Left);
+    /// # assert_eq!(step.get_side(), MerkleSide::Left)
+    /// ```
+    ///
+    pub fn new(
+        hash: String,
+        side: MerkleSide,
+    ) -> MerkleProofStep {
+        MerkleProofStep {
+            hash,
+            side,
+        }
+    }
+
+    ///
+    /// Return the sibling hash, hex encoded.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{MerkleProofStep, MerkleSide};
+    /// # let step = MerkleProofStep::new("abcd".to_string(), MerkleSide::Left);
+    /// let hash : &str = step.get_hash();
+    /// # assert_eq!(hash, "abcd")
+    /// ```
+    ///
+    pub fn get_hash(
+        self: &MerkleProofStep,
+    ) -> &str {
+        &self.hash
+    }
+
+    ///
+    /// Return the side of the sibling hash.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{MerkleProofStep, MerkleSide};
+    /// # let step = MerkleProofStep::new("abcd".to_string(), MerkleSide::Right);
+    /// let side : MerkleSide = step.get_side();
+    /// # assert_eq!(side, MerkleSide::Right)
+    /// ```
+    ///
+    pub fn get_side(
+        self: &MerkleProofStep,
+    ) -> MerkleSide {
+        self.side
+    }
+}
+
+/// Merkle proof structure
+/// It represents the proof that a transaction is part of a batch: the hash of the transaction
+/// and the sibling hashes needed to rebuild the Merkle root of the batch.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MerkleProof {
+    transaction_id: String,
+    leaf_hash: String,
+    steps: Vec<MerkleProofStep>,
+}
+
+/// Merkle proof implementation.
+impl MerkleProof {
+    ///
+    /// Return a new MerkleProof.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_id` - A String that represents the id of the proven transaction.
+    /// * `leaf_hash` - A String that represents the hash of the proven transaction, hex encoded.
+    /// * `steps` - A Vec<MerkleProofStep> that represents the sibling hashes, from the leaf up to the root.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::chain::{MerkleProof, sha256};
+    /// let leaf_hash = sha256(b"leaf");
+    /// let proof = MerkleProof::new("tx_0001".to_string(), leaf_hash.clone(), Vec::new());
+    /// # assert_eq!(proof.get_leaf_hash(), leaf_hash)
+    /// ```
+    ///
+    pub fn new(
+        transaction_id: String,
+        leaf_hash: String,
+        steps: Vec<MerkleProofStep>,
+    ) -> MerkleProof {
+        MerkleProof {
+            transaction_id,
+            leaf_hash,
+            steps,
+        }
+    }
+
+    ///
+    /// Return the id of the proven transaction.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::MerkleProof;
+    /// # let proof = MerkleProof::new("tx_0001".to_string(), "".to_string(), Vec::new());
+    /// let transaction_id : &str = proof.get_transaction_id();
+    /// # assert_eq!(transaction_id, "tx_0001")
+    /// ```
+    ///
+    pub fn get_transaction_id(
+        self: &MerkleProof,
+    ) -> &str {
+        &self.transaction_id
+    }
+
+    ///
+    /// Return the hash of the proven transaction, hex encoded.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::MerkleProof;
+    /// # let proof = MerkleProof::new("tx_0001".to_string(), "abcd".to_string(), Vec::new());
+    /// let leaf_hash : &str = proof.get_leaf_hash();
+    /// # assert_eq!(leaf_hash, "abcd")
+    /// ```
+    ///
+    pub fn get_leaf_hash(
+        self: &MerkleProof,
+    ) -> &str {
+        &self.leaf_hash
+    }
+
+    ///
+    /// Return a copy of the sibling hashes of the proof, from the leaf up to the root.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{MerkleProof, MerkleProofStep};
+    /// # let proof = MerkleProof::new("tx_0001".to_string(), "abcd".to_string(), Vec::new());
+    /// let steps : Vec<MerkleProofStep> = proof.get_steps();
+    /// # assert!(steps.is_empty())
+    /// ```
+    ///
+    pub fn get_steps(
+        self: &MerkleProof,
+    ) -> Vec<MerkleProofStep> {
+        self.steps.clone()
+    }
+
+    ///
+    /// Return the Merkle root obtained by hashing the leaf node of the leaf hash with the sibling hashes of the proof.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{compute_merkle_root, MerkleProof, sha256};
+    /// # let proof = MerkleProof::new("tx_0001".to_string(), sha256(b"leaf"), Vec::new());
+    /// let root : String = proof.compute_root().unwrap();
+    /// # assert_eq!(root, compute_merkle_root(&[sha256(b"leaf")]).unwrap())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An InvalidHashError is returned if the leaf hash or one of the sibling hashes is not hex encoded.
+    ///
+    pub fn compute_root(
+        self: &MerkleProof,
+    ) -> Result<String, ChainError> {
+        let mut node = hash_leaf(&decode_hash(&self.leaf_hash)?);
+        for step in self.steps.iter() {
+            let sibling = decode_hash(&step.hash)?;
+            node = match step.side {
+                MerkleSide::Left => hash_pair(&sibling, &node),
+                MerkleSide::Right => hash_pair(&node, &sibling),
+            };
+        }
+        Ok(hex::encode(node))
+    }
+
+    ///
+    /// Return true if the proof leads to the given Merkle root. A proof with a hash that is not hex encoded leads to no root.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - A str that represents the trusted Merkle root, hex encoded.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{MerkleProof, sha256};
+    /// # let proof = MerkleProof::new("tx_0001".to_string(), sha256(b"leaf"), Vec::new());
+    /// # let root = proof.compute_root().unwrap();
+    /// let included : bool = proof.verify(&root);
+    /// # assert!(included);
+    /// # assert!(!MerkleProof::new("tx_0001".to_string(), "not hex".to_string(), Vec::new()).verify(&root))
+    /// ```
+    ///
+    pub fn verify(
+        self: &MerkleProof,
+        root: &str,
+    ) -> bool {
+        match self.compute_root() {
+            Ok(computed_root) => computed_root == root,
+            Err(_) => false,
+        }
+    }
+
+    ///
+    /// Return true if the proof is about the given transaction and leads to the given Merkle root.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - A Transaction that represents the transaction claimed to be in the batch.
+    /// * `root` - A str that represents the trusted Merkle root, hex encoded.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Transaction};
+    /// # use banana_coin::model::TransactionState::*;
+    /// use banana_coin::chain::MerkleTree;
+    /// # let transaction = Transaction::new("tx_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(),
+    /// #     Amount::from_integer(100), "".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// let tree = MerkleTree::new(&[transaction.clone()]);
+    /// let proof = tree.get_proof("tx_0001").unwrap();
+    /// # assert!(proof.verify_transaction(&transaction, &tree.get_root()))
+    /// ```
+    ///
+    pub fn verify_transaction(
+        self: &MerkleProof,
+        transaction: &Transaction,
+        root: &str,
+    ) -> bool {
+        transaction.get_id() == self.transaction_id
+            && hash_transaction(transaction) == self.leaf_hash
+            && self.verify(root)
+    }
+}
+
+/// Merkle tree structure
+/// It represents a Merkle tree built over the hashes of a batch of transactions, whose leaves keep the order of the batch.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    transaction_ids: Vec<String>,
+    /// Hashes of the transactions, hex encoded.
+    leaf_hashes: Vec<String>,
+    /// Nodes of every level, hex encoded, from the leaf nodes up to the root.
+    levels: Vec<Vec<String>>,
+}
+
+/// Merkle tree implementation.
+impl MerkleTree {
+    ///
+    /// Return a new MerkleTree built over the given transactions.
+    ///
+    /// # Arguments
+    ///
+    /// * `transactions` - A slice of Transaction that represents the batch of transactions.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::chain::{MerkleTree, sha256};
+    /// let tree = MerkleTree::new(&[]);
+    /// # assert_eq!(tree.get_root(), sha256(&[]))
+    /// ```
+    ///
+    pub fn new(
+        transactions: &[Transaction],
+    ) -> MerkleTree {
+        let leaves: Vec<Vec<u8>> = transactions.iter().map(transaction_digest).collect();
+        MerkleTree {
+            transaction_ids: transactions.iter().map(|transaction| transaction.get_id()).collect(),
+            leaf_hashes: leaves.iter().map(hex::encode).collect(),
+            levels: build_levels(&leaves)
+                .iter()
+                .map(|level| level.iter().map(hex::encode).collect())
+                .collect(),
+        }
+    }
+
+    ///
+    /// Return the Merkle root of the tree, hex encoded.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::MerkleTree;
+    /// # let tree = MerkleTree::new(&[]);
+    /// let root : String = tree.get_root();
+    /// # assert_eq!(root.len(), 64)
+    /// ```
+    ///
+    pub fn get_root(
+        self: &MerkleTree,
+    ) -> String {
+        match self.levels.last().and_then(|level| level.first()) {
+            Some(root) => root.clone(),
+            None => sha256(&[]),
+        }
+    }
+
+    ///
+    /// Return the inclusion proof of the transaction with the given id, if the transaction is in the tree.
+    /// If the batch contains the same id more than once, the proof refers to the first one.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_id` - A str that represents the id of the transaction.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::MerkleTree;
+    /// # let tree = MerkleTree::new(&[]);
+    /// let proof = tree.get_proof("tx_0001");
+    /// # assert!(proof.is_none())
+    /// ```
+    ///
+    pub fn get_proof(
+        self: &MerkleTree,
+        transaction_id: &str,
+    ) -> Option<MerkleProof> {
+        let mut index = self.transaction_ids.iter().position(|id| id == transaction_id)?;
+        let leaf_hash = self.leaf_hashes[index].clone();
+        let mut steps = Vec::new();
+        for level in self.levels.iter().take(self.levels.len() - 1) {
+            // The last node of a level with an odd number of nodes has no sibling: it is carried up as it is
+            if index % 2 == 1 {
+                steps.push(MerkleProofStep::new(level[index - 1].clone(), MerkleSide::Left));
+            } else if let Some(sibling) = level.get(index + 1) {
+                steps.push(MerkleProofStep::new(sibling.clone(), MerkleSide::Right));
+            }
+            index /= 2;
+        }
+        Some(MerkleProof::new(transaction_id.to_string(), leaf_hash, steps))
+    }
+}
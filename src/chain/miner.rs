@@ -0,0 +1,85 @@
+use crate::chain::Block;
+use crate::error::{ChainError, TransactionError};
+use crate::ledger::ISSUANCE_ACCOUNT_ID;
+use crate::model::{Amount, Transaction, TransactionState};
+
+///
+/// Return the coinbase transaction of a block: a completed transaction from the issuance account
+/// that credits the block reward to the miner.
+///
+/// # Arguments
+///
+/// * `height` - A u64 integer that represents the height of the mined block.
+/// * `miner_entity_id` - A String that represents the id of the entity that mines the block.
+/// * `block_reward` - An Amount that represents the coins credited to the miner.
+/// * `timestamp` - A u64 integer that represents the timestamp of the mined block.
+///
+/// # Errors
+///
+/// Return the TransactionError of Transaction::new, which can not happen for the state history of a coinbase transaction.
+///
+/// # Example
+/// ```
+/// use banana_coin::chain::create_coinbase_transaction;
+/// use banana_coin::ledger::ISSUANCE_ACCOUNT_ID;
+/// use banana_coin::model::Amount;
+/// let transaction = create_coinbase_transaction(0, "entity_001".to_string(), Amount::from_integer(50), 1).unwrap();
+/// # assert_eq!(transaction.get_id(), "coinbase_000000");
+/// # assert_eq!(transaction.get_from_entity_id(), ISSUANCE_ACCOUNT_ID);
+/// # assert_eq!(transaction.get_amount(), Amount::from_integer(50))
+/// ```
+///
+pub fn create_coinbase_transaction(
+    height: u64,
+    miner_entity_id: String,
+    block_reward: Amount,
+    timestamp: u64,
+) -> Result<Transaction, TransactionError> {
+    let state_history = vec![
+        TransactionState::Created { timestamp },
+        TransactionState::OnProcess { timestamp },
+        TransactionState::Completed { timestamp },
+    ];
+    Transaction::new(
+        format!("coinbase_{:06}", height),
+        ISSUANCE_ACCOUNT_ID.to_string(),
+        miner_entity_id,
+        block_reward,
+        format!("Block reward for height {}", height),
+        TransactionState::Completed { timestamp },
+        state_history,
+    )
+}
+
+///
+/// Search the nonce that makes the hash of the block meet its difficulty, starting from zero,
+/// and set it in the header of the block.
+///
+/// # Arguments
+///
+/// * `block` - A Block that represents the block to mine.
+///
+/// # Errors
+///
+/// Return a NonceExhaustedError if no nonce meets the difficulty of the block.
+///
+/// # Example
+/// ```
+/// use banana_coin::chain::{Block, GENESIS_PREVIOUS_HASH, mine_block};
+/// let mut block = Block::new(0, GENESIS_PREVIOUS_HASH.to_string(), 1, 8, Vec::new());
+/// mine_block(&mut block).unwrap();
+/// # assert!(block.get_header().meets_difficulty());
+/// # assert!(block.get_hash().starts_with("00"))
+/// ```
+///
+pub fn mine_block(
+    block: &mut Block,
+) -> Result<(), ChainError> {
+    for nonce in 0..=u64::MAX {
+        block.set_nonce(nonce);
+        if block.get_header().meets_difficulty() {
+            return Ok(());
+        }
+    }
+    Err(ChainError::NonceExhaustedError { height: block.get_header().get_height() })
+}
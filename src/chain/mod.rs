@@ -0,0 +1,23 @@
+/// Importing hash module
+pub mod hash;
+pub use hash::*;
+
+/// Importing merkle module
+pub mod merkle;
+pub use merkle::*;
+
+/// Importing block module
+pub mod block;
+pub use block::*;
+
+/// Importing blockchain module
+pub mod blockchain;
+pub use blockchain::*;
+
+/// Importing consensus module
+pub mod consensus;
+pub use consensus::*;
+
+/// Importing miner module
+pub mod miner;
+pub use miner::*;
@@ -0,0 +1,289 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::CliError;
+use crate::model::{Amount, OverdraftPolicy};
+
+/// Default path of the data file used by the command-line tool.
+pub const DEFAULT_DATA_FILE: &str = "banana_coin.jsonl";
+
+/// Extension of the default audit log, which is next to the data file.
+const AUDIT_LOG_EXTENSION: &str = "audit.jsonl";
+
+/// Usage of the command-line tool.
+pub const USAGE: &str = "Usage: banana_coin [--data-file <path>] [--audit-log <path>] [--actor <name>] [--json] <command>
+
+The changes of the entities are audited in the audit log, by default the data file with the extension audit.jsonl.
+Transfers must be signed by the sender, whose public key is set once, at creation or with entity key:
+either signed here with its secret key or signed elsewhere, given the id of the transaction and, if it is not
+the next one of the sender, the nonce the transaction was signed with.
+
+Commands:
+    entity create <entity id> <name> [--balance <coins>] [--public-key <key>]
+    entity show <entity id>
+    entity list
+    entity key <entity id> <public key>
+    wallet balance <entity id>
+    wallet deposit <entity id> <coins> [--id <transaction id>] [--description <text>]
+    wallet withdraw <entity id> <coins> [--id <transaction id>] [--description <text>]
+    wallet overdraft <entity id> none|unlimited|<floor> --changed-by <name>
+    transfer <from entity id> <to entity id> <coins> --secret-key <key> [--id <transaction id>] [--description <text>]
+    transfer <from entity id> <to entity id> <coins> --signature <signature> --id <transaction id> [--nonce <nonce>] [--description <text>]
+    key generate
+    tx show <transaction id>
+    tx history [<entity id>]
+    audit verify <audit log path>";
+
+/// Command enumeration
+/// It represents a subcommand of the command-line tool with its arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    EntityCreate { entity_id: String, name: String, balance: Amount, public_key: Option<String> },
+    EntityShow { entity_id: String },
+    EntityList,
+    EntityKey { entity_id: String, public_key: String },
+    WalletBalance { entity_id: String },
+    WalletDeposit { entity_id: String, amount: Amount, transaction_id: Option<String>, description: Option<String> },
+    WalletWithdraw { entity_id: String, amount: Amount, transaction_id: Option<String>, description: Option<String> },
+    WalletOverdraft { entity_id: String, overdraft_policy: OverdraftPolicy, changed_by: String },
+    Transfer {
+        from_entity_id: String,
+        to_entity_id: String,
+        amount: Amount,
+        transaction_id: Option<String>,
+        description: Option<String>,
+        nonce: Option<u64>,
+        signature: TransferSignature,
+    },
+    KeyGenerate,
+    TransactionShow { transaction_id: String },
+    TransactionHistory { entity_id: Option<String> },
+    AuditVerify { audit_log: PathBuf },
+}
+
+/// Transfer signature enumeration
+/// It represents how a transfer is signed: with the secret key of the sender, hex encoded,
+/// or with a signature of the transaction made elsewhere, hex encoded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransferSignature {
+    SecretKey(String),
+    Signature(String),
+}
+
+///
+/// Return the path of the audit log of a data file when no other audit log is given: the data file
+/// with the extension audit.jsonl, so that every data file has its own audit log.
+///
+/// # Arguments
+///
+/// * `data_file` - A Path that represents the location of the data file.
+///
+/// # Example
+/// ```
+/// use std::path::{Path, PathBuf};
+/// use banana_coin::cli::default_audit_log;
+/// let audit_log = default_audit_log(Path::new("data/banana_coin.jsonl"));
+/// # assert_eq!(audit_log, PathBuf::from("data/banana_coin.audit.jsonl"))
+/// ```
+///
+pub fn default_audit_log(
+    data_file: &Path,
+) -> PathBuf {
+    data_file.with_extension(AUDIT_LOG_EXTENSION)
+}
+
+/// Invocation structure
+/// It represents a parsed command line: the global options and the command to run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invocation {
+    /// Path of the data file the command works against.
+    pub data_file: PathBuf,
+    /// Path of the audit log the changes of the entities are appended to once they are stored.
+    pub audit_log: PathBuf,
+    /// Name of whoever runs the command, recorded in the audit log.
+    pub actor: Option<String>,
+    /// Whether the output is printed as JSON instead of human-readable tables.
+    pub json: bool,
+    /// Command to run.
+    pub command: Command,
+}
+
+/// Invocation implementation.
+impl Invocation {
+    ///
+    /// Parse the arguments of the command line, without the name of the program.
+    ///
+    /// # Arguments
+    ///
+    /// * `arguments` - A slice of String that represents the arguments of the command line.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::cli::{Command, Invocation};
+    /// let arguments : Vec<String> = vec!["--json", "wallet", "balance", "entity_001"]
+    ///     .into_iter()
+    ///     .map(String::from)
+    ///     .collect();
+    /// let invocation = Invocation::parse(&arguments).unwrap();
+    /// # assert!(invocation.json);
+    /// # assert_eq!(invocation.command, Command::WalletBalance { entity_id: "entity_001".to_string() })
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A UsageError is returned if the arguments do not match any command.
+    ///
+    pub fn parse(
+        arguments: &[String],
+    ) -> Result<Invocation, CliError> {
+        let mut data_file = PathBuf::from(DEFAULT_DATA_FILE);
+        let mut audit_log = None;
+        let mut actor = None;
+        let mut json = false;
+        let mut positionals = Vec::new();
+        let mut options = Options::default();
+
+        let mut iterator = arguments.iter();
+        while let Some(argument) = iterator.next() {
+            match argument.as_str() {
+                "--data-file" => data_file = PathBuf::from(next_value(&mut iterator, argument)?),
+                "--audit-log" => audit_log = Some(PathBuf::from(next_value(&mut iterator, argument)?)),
+                "--actor" => actor = Some(next_value(&mut iterator, argument)?),
+                "--json" => json = true,
+                "--balance" => options.balance = Some(parse_number(&next_value(&mut iterator, argument)?, argument)?),
+                "--id" => options.transaction_id = Some(next_value(&mut iterator, argument)?),
+                "--description" => options.description = Some(next_value(&mut iterator, argument)?),
+                "--changed-by" => options.changed_by = Some(next_value(&mut iterator, argument)?),
+                "--public-key" => options.public_key = Some(next_value(&mut iterator, argument)?),
+                "--nonce" => options.nonce = Some(parse_number(&next_value(&mut iterator, argument)?, argument)?),
+                "--secret-key" => options.secret_key = Some(next_value(&mut iterator, argument)?),
+                "--signature" => options.signature = Some(next_value(&mut iterator, argument)?),
+                "--help" | "-h" => return Err(usage_error("")),
+                option if option.starts_with("--") => return Err(usage_error(&format!("Unknown option {}", option))),
+                positional => positionals.push(positional),
+            }
+        }
+
+        let command = match positionals.as_slice() {
+            ["entity", "create", entity_id, name] => Command::EntityCreate {
+                entity_id: entity_id.to_string(),
+                name: name.to_string(),
+                balance: options.balance.unwrap_or_default(),
+                public_key: options.public_key,
+            },
+            ["entity", "show", entity_id] => Command::EntityShow { entity_id: entity_id.to_string() },
+            ["entity", "list"] => Command::EntityList,
+            ["entity", "key", entity_id, public_key] => Command::EntityKey {
+                entity_id: entity_id.to_string(),
+                public_key: public_key.to_string(),
+            },
+            ["wallet", "balance", entity_id] => Command::WalletBalance { entity_id: entity_id.to_string() },
+            ["wallet", "deposit", entity_id, amount] => Command::WalletDeposit {
+                entity_id: entity_id.to_string(),
+                amount: parse_number(amount, "<coins>")?,
+                transaction_id: options.transaction_id,
+                description: options.description,
+            },
+            ["wallet", "withdraw", entity_id, amount] => Command::WalletWithdraw {
+                entity_id: entity_id.to_string(),
+                amount: parse_number(amount, "<coins>")?,
+                transaction_id: options.transaction_id,
+                description: options.description,
+            },
+            ["wallet", "overdraft", entity_id, overdraft_policy] => Command::WalletOverdraft {
+                entity_id: entity_id.to_string(),
+                overdraft_policy: parse_overdraft_policy(overdraft_policy)?,
+                changed_by: options.changed_by.ok_or_else(|| usage_error("Missing --changed-by for wallet overdraft"))?,
+            },
+            ["transfer", from_entity_id, to_entity_id, amount] => {
+                let signature = match (options.secret_key, options.signature) {
+                    (Some(secret_key), None) => TransferSignature::SecretKey(secret_key),
+                    (None, Some(signature)) => TransferSignature::Signature(signature),
+                    (Some(_), Some(_)) => return Err(usage_error("Only one of --secret-key and --signature for transfer")),
+                    (None, None) => return Err(usage_error("Missing --secret-key or --signature for transfer")),
+                };
+                Command::Transfer {
+                    from_entity_id: from_entity_id.to_string(),
+                    to_entity_id: to_entity_id.to_string(),
+                    amount: parse_number(amount, "<coins>")?,
+                    transaction_id: options.transaction_id,
+                    description: options.description,
+                    nonce: options.nonce,
+                    signature,
+                }
+            }
+            ["key", "generate"] => Command::KeyGenerate,
+            ["tx", "show", transaction_id] => Command::TransactionShow { transaction_id: transaction_id.to_string() },
+            ["tx", "history"] => Command::TransactionHistory { entity_id: None },
+            ["tx", "history", entity_id] => Command::TransactionHistory { entity_id: Some(entity_id.to_string()) },
+            ["audit", "verify", audit_log] => Command::AuditVerify { audit_log: PathBuf::from(audit_log) },
+            [] => return Err(usage_error("")),
+            _ => return Err(usage_error(&format!("Unknown command: {}", positionals.join(" ")))),
+        };
+
+        Ok(Invocation {
+            audit_log: audit_log.unwrap_or_else(|| default_audit_log(&data_file)),
+            data_file,
+            actor,
+            json,
+            command,
+        })
+    }
+}
+
+/// Options of the commands, collected before the command itself is known.
+#[derive(Default)]
+struct Options {
+    balance: Option<Amount>,
+    transaction_id: Option<String>,
+    description: Option<String>,
+    changed_by: Option<String>,
+    public_key: Option<String>,
+    nonce: Option<u64>,
+    secret_key: Option<String>,
+    signature: Option<String>,
+}
+
+/// Return the value following an option, or a UsageError if it is missing.
+fn next_value<'a, I>(
+    iterator: &mut I,
+    option: &str,
+) -> Result<String, CliError>
+    where I: Iterator<Item = &'a String> {
+    iterator
+        .next()
+        .cloned()
+        .ok_or_else(|| usage_error(&format!("Missing value for {}", option)))
+}
+
+/// Parse a number, or return a UsageError naming the argument.
+fn parse_number<N>(
+    value: &str,
+    argument: &str,
+) -> Result<N, CliError>
+    where N: std::str::FromStr {
+    value
+        .parse()
+        .map_err(|_| usage_error(&format!("Invalid number {} for {}", value, argument)))
+}
+
+/// Parse an overdraft policy: "none", "unlimited" or the floor of an overdraft limit, or return a UsageError.
+fn parse_overdraft_policy(
+    value: &str,
+) -> Result<OverdraftPolicy, CliError> {
+    match value {
+        "none" => Ok(OverdraftPolicy::NoOverdraft),
+        "unlimited" => Ok(OverdraftPolicy::Unlimited),
+        floor => Ok(OverdraftPolicy::Limit { floor: parse_number(floor, "<floor>")? }),
+    }
+}
+
+/// Return a UsageError made of the given message followed by the usage of the tool.
+fn usage_error(
+    message: &str,
+) -> CliError {
+    let message = match message {
+        "" => USAGE.to_string(),
+        message => format!("{}\n\n{}", message, USAGE),
+    };
+    CliError::UsageError { message }
+}
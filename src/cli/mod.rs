@@ -0,0 +1,11 @@
+/// Importing command module
+pub mod command;
+pub use command::*;
+
+/// Importing command output module
+pub mod output;
+pub use output::*;
+
+/// Importing command runner module
+pub mod runner;
+pub use runner::*;
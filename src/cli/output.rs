@@ -0,0 +1,157 @@
+use serde_json::{json, Value};
+
+use crate::model::{Amount, Entity, Transaction};
+
+/// Command output enumeration
+/// It represents the result of a command, printed either as a human-readable table or as JSON.
+#[derive(Debug, Clone)]
+pub enum CommandOutput {
+    Entity(Entity),
+    Entities(Vec<Entity>),
+    Balance { entity_id: String, balance: Amount },
+    Transaction(Transaction),
+    Transactions(Vec<Transaction>),
+    AuditLog { record_count: u64, last_hash: String },
+    KeyPair { public_key: String, secret_key: String },
+}
+
+/// Command output implementation.
+impl CommandOutput {
+    ///
+    /// Return the text to print for the output.
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - A boolean that represents if the output is rendered as JSON instead of a table.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::cli::CommandOutput;
+    /// use banana_coin::model::Amount;
+    /// let output = CommandOutput::Balance { entity_id: "entity_001".to_string(), balance: Amount::from_integer(100) };
+    /// # assert_eq!(output.render(false), "ENTITY ID   BALANCE\nentity_001  100");
+    /// # assert_eq!(output.render(true), "{\n  \"balance\": \"100\",\n  \"entity_id\": \"entity_001\"\n}");
+    /// ```
+    ///
+    pub fn render(
+        self: &CommandOutput,
+        json: bool,
+    ) -> String {
+        if json {
+            return serde_json::to_string_pretty(&self.to_json()).unwrap_or_default();
+        }
+        match self {
+            CommandOutput::Entity(entity) => render_entities(std::slice::from_ref(entity)),
+            CommandOutput::Entities(entities) => render_entities(entities),
+            CommandOutput::Balance { entity_id, balance } =>
+                render_table(&["ENTITY ID", "BALANCE"], vec![vec![entity_id.clone(), balance.to_string()]]),
+            CommandOutput::Transaction(transaction) => {
+                let history: Vec<String> = transaction
+                    .get_state_history()
+                    .iter()
+                    .map(|state| format!("{}@{}", state.get_name(), state.get_timestamp()))
+                    .collect();
+                render_table(&["FIELD", "VALUE"], vec![
+                    vec!["id".to_string(), transaction.get_id()],
+                    vec!["from".to_string(), transaction.get_from_entity_id()],
+                    vec!["to".to_string(), transaction.get_to_entity_id()],
+                    vec!["amount".to_string(), transaction.get_amount().to_string()],
+                    vec!["state".to_string(), transaction.get_current_state().get_name().to_string()],
+                    vec!["history".to_string(), history.join(" -> ")],
+                    vec!["description".to_string(), transaction.get_description()],
+                ])
+            }
+            CommandOutput::Transactions(transactions) => render_table(
+                &["ID", "FROM", "TO", "AMOUNT", "STATE", "TIMESTAMP"],
+                transactions
+                    .iter()
+                    .map(|transaction| vec![
+                        transaction.get_id(),
+                        transaction.get_from_entity_id(),
+                        transaction.get_to_entity_id(),
+                        transaction.get_amount().to_string(),
+                        transaction.get_current_state().get_name().to_string(),
+                        transaction.get_current_state().get_timestamp().to_string(),
+                    ])
+                    .collect(),
+            ),
+            CommandOutput::AuditLog { record_count, last_hash } => render_table(&["FIELD", "VALUE"], vec![
+                vec!["records".to_string(), record_count.to_string()],
+                vec!["last hash".to_string(), last_hash.clone()],
+            ]),
+            CommandOutput::KeyPair { public_key, secret_key } => render_table(&["FIELD", "VALUE"], vec![
+                vec!["public key".to_string(), public_key.clone()],
+                vec!["secret key".to_string(), secret_key.clone()],
+            ]),
+        }
+    }
+    ///
+    /// Return the JSON value of the output, made of the serde representations of the entities and transactions.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::cli::CommandOutput;
+    /// use banana_coin::model::Amount;
+    /// let output = CommandOutput::Balance { entity_id: "entity_001".to_string(), balance: Amount::from_integer(100) };
+    /// let value : serde_json::Value = output.to_json();
+    /// # assert_eq!(value["balance"], "100")
+    /// ```
+    ///
+    pub fn to_json(
+        self: &CommandOutput,
+    ) -> Value {
+        match self {
+            CommandOutput::Entity(entity) => json!(entity),
+            CommandOutput::Entities(entities) => json!(entities),
+            CommandOutput::Balance { entity_id, balance } => json!({ "entity_id": entity_id, "balance": balance }),
+            CommandOutput::Transaction(transaction) => json!(transaction),
+            CommandOutput::Transactions(transactions) => json!(transactions),
+            CommandOutput::AuditLog { record_count, last_hash } => json!({ "record_count": record_count, "last_hash": last_hash }),
+            CommandOutput::KeyPair { public_key, secret_key } => json!({ "public_key": public_key, "secret_key": secret_key }),
+        }
+    }
+}
+
+/// Render a table of entities.
+fn render_entities(
+    entities: &[Entity],
+) -> String {
+    render_table(
+        &["ID", "NAME", "BALANCE", "OVERDRAFT"],
+        entities
+            .iter()
+            .map(|entity| vec![
+                entity.get_id().to_string(),
+                entity.get_name().to_string(),
+                entity.get_wallet().get_balance().to_string(),
+                entity.get_wallet().get_overdraft_policy().to_string(),
+            ])
+            .collect(),
+    )
+}
+
+/// Render a table whose columns are aligned on their widest cell.
+fn render_table(
+    header: &[&str],
+    rows: Vec<Vec<String>>,
+) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|cell| cell.chars().count()).collect();
+    for row in &rows {
+        for (index, cell) in row.iter().enumerate() {
+            widths[index] = widths[index].max(cell.chars().count());
+        }
+    }
+    let header: Vec<String> = header.iter().map(|cell| cell.to_string()).collect();
+    std::iter::once(&header)
+        .chain(rows.iter())
+        .map(|row| {
+            let cells: Vec<String> = row
+                .iter()
+                .enumerate()
+                .map(|(index, cell)| format!("{:width$}", cell, width = widths[index]))
+                .collect();
+            cells.join("  ").trim_end().to_string()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
@@ -0,0 +1,423 @@
+use rand_core::{OsRng, RngCore};
+
+use crate::audit::{AuditLog, AuditRecord, AuditSubscriber, GENESIS_HASH};
+use crate::cli::{Command, CommandOutput, TransferSignature};
+use crate::crypto::{check_public_key, KeyPair};
+use crate::error::{CliError, StorageError};
+use crate::event::{balance_changes, entity_created, overdraft_policy_changed, public_key_set, transaction_changes, DomainEvent, EventBus};
+use crate::ledger::{ESCROW_ACCOUNT_ID, ISSUANCE_ACCOUNT_ID};
+use crate::model::{Amount, Entity, Transaction, TransactionState, Wallet};
+use crate::model::TransactionState::*;
+use crate::storage::Storage;
+use crate::transfer::TransferExecutor;
+
+///
+/// Run a command of the command-line tool against a storage.
+/// Deposits and withdrawals are recorded as transactions from or to the issuance account.
+/// Failed deposits, withdrawals and transfers are recorded too, while the wallets are left untouched.
+/// Transfers must be signed by their sender, whose public key must have been set. A transfer signed elsewhere
+/// must be given the id of its transaction, since the signature covers it.
+///
+/// # Arguments
+///
+/// * `storage` - A Storage that represents the data the command works against.
+/// * `command` - A Command that represents the command to run.
+/// * `timestamp` - A u64 integer that represents the timestamp of the state changes of new transactions.
+///
+/// # Example
+/// ```
+/// use banana_coin::cli::{run_command, Command};
+/// use banana_coin::model::Amount;
+/// use banana_coin::storage::MemoryStorage;
+/// let mut storage = MemoryStorage::new();
+/// let command = Command::EntityCreate {
+///     entity_id: "entity_001".to_string(),
+///     name: "john".to_string(),
+///     balance: Amount::from_integer(100),
+///     public_key: None,
+/// };
+/// let output = run_command(&mut storage, &command, 1).unwrap();
+/// println!("{}", output.render(false));
+/// ```
+///
+/// # Errors
+///
+/// A CliError is returned if the command can not be run or if the operation it runs fails.
+///
+pub fn run_command<S: Storage>(
+    storage: &mut S,
+    command: &Command,
+    timestamp: u64,
+) -> Result<CommandOutput, CliError> {
+    run_command_with_events(storage, command, timestamp, &EventBus::new())
+}
+
+///
+/// Run a command of the command-line tool against a storage, see 'run_command', and publish its changes
+/// on an event bus once they are stored: the new entities, the public keys and overdraft policies set,
+/// the changed balances and the states of the new transactions.
+///
+/// # Arguments
+///
+/// * `storage` - A Storage that represents the data the command works against.
+/// * `command` - A Command that represents the command to run.
+/// * `timestamp` - A u64 integer that represents the timestamp of the state changes of new transactions.
+/// * `event_bus` - An EventBus that represents the bus the changes are published on.
+///
+/// # Example
+/// ```
+/// use banana_coin::cli::{run_command_with_events, Command};
+/// use banana_coin::event::EventBus;
+/// use banana_coin::model::Amount;
+/// use banana_coin::storage::MemoryStorage;
+/// let mut storage = MemoryStorage::new();
+/// let event_bus = EventBus::new();
+/// let receiver = event_bus.subscribe_channel();
+/// let command = Command::EntityCreate {
+///     entity_id: "entity_001".to_string(),
+///     name: "john".to_string(),
+///     balance: Amount::from_integer(100),
+///     public_key: None,
+/// };
+/// run_command_with_events(&mut storage, &command, 1, &event_bus).unwrap();
+/// # assert_eq!(receiver.try_recv().unwrap().get_name(), "EntityCreated")
+/// ```
+///
+/// # Errors
+///
+/// A CliError is returned if the command can not be run or if the operation it runs fails.
+///
+pub fn run_command_with_events<S: Storage>(
+    storage: &mut S,
+    command: &Command,
+    timestamp: u64,
+    event_bus: &EventBus,
+) -> Result<CommandOutput, CliError> {
+    run_command_with_audit(storage, command, timestamp, event_bus, None)
+}
+
+///
+/// Run a command of the command-line tool against a storage and publish its changes on an event bus,
+/// see 'run_command_with_events', and append them to an audit log, if given, once they are stored:
+/// a change that can not be stored is not audited, so that every audit record matches a stored change.
+///
+/// # Arguments
+///
+/// * `storage` - A Storage that represents the data the command works against.
+/// * `command` - A Command that represents the command to run.
+/// * `timestamp` - A u64 integer that represents the timestamp of the state changes of new transactions.
+/// * `event_bus` - An EventBus that represents the bus the changes are published on.
+/// * `audit_subscriber` - An optional AuditSubscriber that represents the audit log the changes are appended to
+///   and the actor they are recorded on behalf of. It should not be subscribed to the event bus too.
+///
+/// # Example
+/// ```
+/// use banana_coin::audit::{AuditLog, AuditSubscriber};
+/// use banana_coin::cli::{run_command_with_audit, Command};
+/// use banana_coin::event::EventBus;
+/// use banana_coin::model::Amount;
+/// use banana_coin::storage::MemoryStorage;
+/// # let path = std::env::temp_dir().join(format!("banana_coin_doc_run_command_with_audit_{}.jsonl", std::process::id()));
+/// let mut storage = MemoryStorage::new();
+/// let audit_subscriber = AuditSubscriber::new(AuditLog::open(&path).unwrap(), "admin".to_string());
+/// let command = Command::EntityCreate {
+///     entity_id: "entity_001".to_string(),
+///     name: "john".to_string(),
+///     balance: Amount::from_integer(100),
+///     public_key: None,
+/// };
+/// run_command_with_audit(&mut storage, &command, 1, &EventBus::new(), Some(&audit_subscriber)).unwrap();
+/// # assert_eq!(audit_subscriber.get_record_count(), 1);
+/// # std::fs::remove_file(&path).unwrap()
+/// ```
+///
+/// # Errors
+///
+/// A CliError is returned if the command can not be run, if the operation it runs fails
+/// or if its changes can not be audited, in which case they are stored nonetheless.
+///
+pub fn run_command_with_audit<S: Storage>(
+    storage: &mut S,
+    command: &Command,
+    timestamp: u64,
+    event_bus: &EventBus,
+    audit_subscriber: Option<&AuditSubscriber>,
+) -> Result<CommandOutput, CliError> {
+    match command {
+        Command::EntityCreate { entity_id, name, balance, public_key } => {
+            if entity_id == ISSUANCE_ACCOUNT_ID || entity_id == ESCROW_ACCOUNT_ID {
+                return Err(CliError::ReservedEntityIdError { entity_id: entity_id.clone() });
+            }
+            if storage.load_entity(entity_id)?.is_some() {
+                return Err(CliError::DuplicateEntityError { entity_id: entity_id.clone() });
+            }
+            let mut entity = Entity::new(entity_id.clone(), name.clone(), Wallet::new(*balance));
+            if let Some(public_key) = public_key {
+                check_public_key(public_key)?;
+                entity.set_public_key(public_key.clone());
+            }
+            let mut events = vec![entity_created(&entity)];
+            events.extend(public_key_set(&entity, timestamp));
+            storage.save_entity(&entity)?;
+            report(event_bus, audit_subscriber, events)?;
+            Ok(CommandOutput::Entity(entity))
+        }
+        Command::EntityShow { entity_id } =>
+            Ok(CommandOutput::Entity(find_entity(storage, entity_id)?)),
+        Command::EntityList =>
+            Ok(CommandOutput::Entities(storage.list_entities()?)),
+        Command::EntityKey { entity_id, public_key } => {
+            let mut entity = find_entity(storage, entity_id)?;
+            // Replacing the key would let whoever runs the tool sign transfers on behalf of the entity
+            if entity.get_public_key().is_some() {
+                return Err(CliError::PublicKeyReplacementError { entity_id: entity_id.clone() });
+            }
+            check_public_key(public_key)?;
+            entity.set_public_key(public_key.clone());
+            storage.save_entity(&entity)?;
+            report(event_bus, audit_subscriber, public_key_set(&entity, timestamp).into_iter().collect())?;
+            Ok(CommandOutput::Entity(entity))
+        }
+        Command::WalletBalance { entity_id } => {
+            let entity = find_entity(storage, entity_id)?;
+            Ok(CommandOutput::Balance {
+                entity_id: entity_id.clone(),
+                balance: entity.get_wallet().get_balance(),
+            })
+        }
+        Command::WalletDeposit { entity_id, amount, transaction_id, description } => {
+            let mut entity = find_entity(storage, entity_id)?;
+            let mut transaction = new_transaction(
+                storage,
+                transaction_id,
+                ISSUANCE_ACCOUNT_ID,
+                entity_id,
+                *amount,
+                description.clone().unwrap_or_else(|| format!("Depositing {} coins to entity identified by '{}'.", amount, entity_id)),
+                timestamp,
+            )?;
+            transition(&mut transaction, OnProcess { timestamp })?;
+            let wallet = entity.get_wallet();
+            let result = entity.add_coins(*amount);
+            settle(storage, event_bus, audit_subscriber, transaction, &[(wallet, &entity)], timestamp, result
+                .map_err(|error| CliError::EntityOperationError { entity_id: entity_id.clone(), error }))
+        }
+        Command::WalletWithdraw { entity_id, amount, transaction_id, description } => {
+            let mut entity = find_entity(storage, entity_id)?;
+            let mut transaction = new_transaction(
+                storage,
+                transaction_id,
+                entity_id,
+                ISSUANCE_ACCOUNT_ID,
+                *amount,
+                description.clone().unwrap_or_else(|| format!("Withdrawing {} coins from entity identified by '{}'.", amount, entity_id)),
+                timestamp,
+            )?;
+            transition(&mut transaction, OnProcess { timestamp })?;
+            let wallet = entity.get_wallet();
+            let result = entity.remove_coins(*amount, timestamp);
+            settle(storage, event_bus, audit_subscriber, transaction, &[(wallet, &entity)], timestamp, result
+                .map_err(|error| CliError::EntityOperationError { entity_id: entity_id.clone(), error }))
+        }
+        Command::WalletOverdraft { entity_id, overdraft_policy, changed_by } => {
+            let mut entity = find_entity(storage, entity_id)?;
+            let previous_policy = entity.get_wallet().get_overdraft_policy();
+            entity
+                .set_overdraft_policy(*overdraft_policy, changed_by.clone(), timestamp)
+                .map_err(|error| CliError::EntityOperationError { entity_id: entity_id.clone(), error })?;
+            storage.save_entity(&entity)?;
+            report(event_bus, audit_subscriber, vec![overdraft_policy_changed(&entity, previous_policy, changed_by, timestamp)])?;
+            Ok(CommandOutput::Entity(entity))
+        }
+        Command::Transfer { from_entity_id, to_entity_id, amount, transaction_id, description, nonce, signature } => {
+            if let (TransferSignature::Signature(_), None) = (signature, transaction_id) {
+                return Err(CliError::UsageError { message: "A transfer signed elsewhere needs the id of its transaction".to_string() });
+            }
+            let mut from_entity = find_entity(storage, from_entity_id)?;
+            let mut to_entity = find_entity(storage, to_entity_id)?;
+            let mut transaction = new_transaction(
+                storage,
+                transaction_id,
+                from_entity_id,
+                to_entity_id,
+                *amount,
+                description.clone().unwrap_or_else(|| format!(
+                    "Moving {} coins from entity identified by '{}' to entity identified by '{}'.",
+                    amount, from_entity_id, to_entity_id)),
+                timestamp,
+            )?;
+            transaction.set_nonce(nonce.unwrap_or_else(|| from_entity.get_nonce()));
+            match signature {
+                TransferSignature::SecretKey(secret_key) => transaction.sign(&KeyPair::from_secret_key(secret_key)?),
+                TransferSignature::Signature(signature) => transaction.set_signature(signature.clone()),
+            }
+            let (from_wallet, to_wallet) = (from_entity.get_wallet(), to_entity.get_wallet());
+            let result = TransferExecutor::new()
+                .with_required_signature(true)
+                .execute(&mut transaction, &mut from_entity, &mut to_entity, timestamp);
+            match result {
+                Ok(_) => {
+                    let mut events = balance_changes(
+                        from_entity_id, &from_wallet, &from_entity.get_wallet(), Some(transaction.get_id()), timestamp);
+                    events.extend(balance_changes(
+                        to_entity_id, &to_wallet, &to_entity.get_wallet(), Some(transaction.get_id()), timestamp));
+                    let events = transaction_changes(&transaction, 1, events);
+                    storage.append_transaction_with_entities(&transaction, &[from_entity, to_entity])?;
+                    report(event_bus, audit_subscriber, events)?;
+                    Ok(CommandOutput::Transaction(transaction))
+                }
+                Err(error) => {
+                    if transaction.get_current_state().is_final() {
+                        // A failed transaction still consumes the nonce of the sender
+                        let events = transaction_changes(&transaction, 1, Vec::new());
+                        storage.append_transaction_with_entities(&transaction, &[from_entity])?;
+                        report(event_bus, audit_subscriber, events)?;
+                    }
+                    Err(CliError::TransferOperationError { error: Box::new(error) })
+                }
+            }
+        }
+        Command::TransactionShow { transaction_id } => {
+            match storage.load_transaction(transaction_id)? {
+                Some(transaction) => Ok(CommandOutput::Transaction(transaction)),
+                None => Err(CliError::TransactionNotFoundError { transaction_id: transaction_id.clone() }),
+            }
+        }
+        Command::TransactionHistory { entity_id } => {
+            let transactions = match entity_id {
+                Some(entity_id) => {
+                    find_entity(storage, entity_id)?;
+                    storage.list_transactions_by_entity(entity_id)?
+                }
+                None => storage.list_transactions()?,
+            };
+            Ok(CommandOutput::Transactions(transactions))
+        }
+        Command::KeyGenerate => {
+            let key_pair = KeyPair::generate();
+            Ok(CommandOutput::KeyPair {
+                public_key: key_pair.get_public_key(),
+                secret_key: key_pair.get_secret_key(),
+            })
+        }
+        Command::AuditVerify { audit_log } => {
+            let last_record = AuditLog::verify(audit_log)?;
+            Ok(CommandOutput::AuditLog {
+                record_count: last_record.as_ref().map_or(0, AuditRecord::get_sequence),
+                last_hash: last_record.as_ref().map_or(GENESIS_HASH, AuditRecord::get_hash).to_string(),
+            })
+        }
+    }
+}
+
+/// Return the stored entity identified by the given id, or an EntityNotFoundError.
+fn find_entity<S: Storage>(
+    storage: &S,
+    entity_id: &str,
+) -> Result<Entity, CliError> {
+    storage
+        .load_entity(entity_id)?
+        .ok_or_else(|| CliError::EntityNotFoundError { entity_id: entity_id.to_string() })
+}
+
+/// Return a new transaction in the Created state, whose id is the given one, if it is free, or a new random one.
+/// A random id does not depend on the stored transactions, so that two processes sharing the data can not pick the same one.
+fn new_transaction<S: Storage>(
+    storage: &S,
+    transaction_id: &Option<String>,
+    from_entity_id: &str,
+    to_entity_id: &str,
+    amount: Amount,
+    description: String,
+    timestamp: u64,
+) -> Result<Transaction, CliError> {
+    let transaction_id = match transaction_id {
+        Some(transaction_id) => {
+            if storage.load_transaction(transaction_id)?.is_some() {
+                return Err(CliError::StorageOperationError {
+                    error: StorageError::DuplicateTransactionError { transaction_id: transaction_id.clone() },
+                });
+            }
+            transaction_id.clone()
+        }
+        None => loop {
+            let mut random = [0u8; 16];
+            OsRng.fill_bytes(&mut random);
+            let transaction_id = format!("tx_{}", hex::encode(random));
+            if storage.load_transaction(&transaction_id)?.is_none() {
+                break transaction_id;
+            }
+        },
+    };
+    Transaction::new(
+        transaction_id.clone(),
+        from_entity_id.to_string(),
+        to_entity_id.to_string(),
+        amount,
+        description,
+        Created { timestamp },
+        vec![Created { timestamp }],
+    ).map_err(|error| CliError::TransactionOperationError { transaction_id, error })
+}
+
+/// Move the transaction to a new state, wrapping the error into a CliError.
+fn transition(
+    transaction: &mut Transaction,
+    state: TransactionState,
+) -> Result<(), CliError> {
+    transaction
+        .transition_to(state)
+        .map_err(|error| CliError::TransactionOperationError { transaction_id: transaction.get_id(), error })
+}
+
+/// Complete or fail a transaction in the OnProcess state depending on the result of its wallet operations,
+/// then store it along with the changed entities, given with their wallets before the operations,
+/// which are stored only if the operations succeeded, in a single storage operation.
+/// The changes are audited and published on the event bus once stored.
+fn settle<S: Storage>(
+    storage: &mut S,
+    event_bus: &EventBus,
+    audit_subscriber: Option<&AuditSubscriber>,
+    mut transaction: Transaction,
+    entities: &[(Wallet, &Entity)],
+    timestamp: u64,
+    result: Result<(), CliError>,
+) -> Result<CommandOutput, CliError> {
+    match result {
+        Ok(_) => {
+            transition(&mut transaction, Completed { timestamp })?;
+            let mut events = Vec::new();
+            for (wallet, entity) in entities {
+                events.extend(balance_changes(
+                    entity.get_id(), wallet, &entity.get_wallet(), Some(transaction.get_id()), timestamp));
+            }
+            let events = transaction_changes(&transaction, 1, events);
+            let entities: Vec<Entity> = entities.iter().map(|(_, entity)| (*entity).clone()).collect();
+            storage.append_transaction_with_entities(&transaction, &entities)?;
+            report(event_bus, audit_subscriber, events)?;
+            Ok(CommandOutput::Transaction(transaction))
+        }
+        Err(error) => {
+            transition(&mut transaction, Failed { timestamp })?;
+            let events = transaction_changes(&transaction, 1, Vec::new());
+            storage.append_transaction(&transaction)?;
+            report(event_bus, audit_subscriber, events)?;
+            Err(error)
+        }
+    }
+}
+
+/// Append the stored changes of a command to the audit log, if any, and publish them on the event bus.
+/// The changes are published even if they can not be audited, since they are stored anyway.
+fn report(
+    event_bus: &EventBus,
+    audit_subscriber: Option<&AuditSubscriber>,
+    events: Vec<DomainEvent>,
+) -> Result<(), CliError> {
+    let result = match audit_subscriber {
+        Some(audit_subscriber) => audit_subscriber.record(&events).map(|_| ()).map_err(CliError::from),
+        None => Ok(()),
+    };
+    event_bus.publish_all(events);
+    result
+}
@@ -0,0 +1,200 @@
+use std::convert::TryInto;
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand_core::OsRng;
+
+use crate::error::CryptoError;
+
+/// Key pair structure
+/// It represents an Ed25519 key pair. Keys and signatures are exchanged as lowercase hexadecimal strings.
+#[derive(Debug, Clone)]
+pub struct KeyPair {
+    signing_key: SigningKey,
+}
+
+/// Key pair implementation.
+impl KeyPair {
+    ///
+    /// Return a new random KeyPair. The randomness is provided by the operating system.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::crypto::KeyPair;
+    /// let key_pair = KeyPair::generate();
+    /// # assert_eq!(key_pair.get_public_key().len(), 64)
+    /// ```
+    ///
+    pub fn generate() -> KeyPair {
+        KeyPair {
+            signing_key: SigningKey::generate(&mut OsRng),
+        }
+    }
+
+    ///
+    /// Return the KeyPair of the given secret key.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_key` - A str that represents the 32 bytes of the secret key, hex encoded.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::crypto::KeyPair;
+    /// let key_pair = KeyPair::from_secret_key(
+    ///     "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60"
+    /// ).unwrap();
+    /// # assert_eq!(key_pair.get_public_key(), "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a")
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An InvalidKeyError is returned if the secret key is not made of 32 hex encoded bytes.
+    ///
+    pub fn from_secret_key(
+        secret_key: &str,
+    ) -> Result<KeyPair, CryptoError> {
+        let bytes = decode_array::<32>(secret_key)
+            .map_err(|message| CryptoError::InvalidKeyError { message })?;
+        Ok(KeyPair {
+            signing_key: SigningKey::from_bytes(&bytes),
+        })
+    }
+
+    ///
+    /// Return the secret key, hex encoded. It must be kept private.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::crypto::KeyPair;
+    /// let key_pair = KeyPair::generate();
+    /// let secret_key : String = key_pair.get_secret_key();
+    /// # assert_eq!(KeyPair::from_secret_key(&secret_key).unwrap().get_public_key(), key_pair.get_public_key())
+    /// ```
+    ///
+    pub fn get_secret_key(
+        self: &KeyPair,
+    ) -> String {
+        hex::encode(self.signing_key.to_bytes())
+    }
+
+    ///
+    /// Return the public key, hex encoded.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::crypto::KeyPair;
+    /// let key_pair = KeyPair::generate();
+    /// let public_key : String = key_pair.get_public_key();
+    /// ```
+    ///
+    pub fn get_public_key(
+        self: &KeyPair,
+    ) -> String {
+        hex::encode(self.signing_key.verifying_key().to_bytes())
+    }
+
+    ///
+    /// Sign a message and return the signature, hex encoded.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - A slice of bytes that represents the message to sign.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::crypto::{verify_signature, KeyPair};
+    /// let key_pair = KeyPair::generate();
+    /// let signature : String = key_pair.sign(b"message");
+    /// # assert!(verify_signature(&key_pair.get_public_key(), b"message", &signature).is_ok())
+    /// ```
+    ///
+    pub fn sign(
+        self: &KeyPair,
+        message: &[u8],
+    ) -> String {
+        hex::encode(self.signing_key.sign(message).to_bytes())
+    }
+}
+
+///
+/// Verify the signature of a message against a public key.
+///
+/// # Arguments
+///
+/// * `public_key` - A str that represents the public key, hex encoded.
+/// * `message` - A slice of bytes that represents the signed message.
+/// * `signature` - A str that represents the signature, hex encoded.
+///
+/// # Example
+/// ```
+/// use banana_coin::crypto::{verify_signature, KeyPair};
+/// let key_pair = KeyPair::generate();
+/// let signature = key_pair.sign(b"message");
+/// let result = verify_signature(&key_pair.get_public_key(), b"message", &signature);
+/// # assert!(result.is_ok());
+/// # assert!(verify_signature(&key_pair.get_public_key(), b"other message", &signature).is_err())
+/// ```
+///
+/// # Errors
+///
+/// An InvalidKeyError or an InvalidSignatureError is returned if the key or the signature can not be decoded,
+/// a SignatureMismatchError if the signature does not match the message and the public key.
+///
+pub fn verify_signature(
+    public_key: &str,
+    message: &[u8],
+    signature: &str,
+) -> Result<(), CryptoError> {
+    let verifying_key = decode_public_key(public_key)?;
+    let signature_bytes = decode_array::<64>(signature)
+        .map_err(|message| CryptoError::InvalidSignatureError { message })?;
+    verifying_key
+        .verify(message, &Signature::from_bytes(&signature_bytes))
+        .map_err(|_| CryptoError::SignatureMismatchError)
+}
+
+///
+/// Check that a public key can be used to verify signatures.
+///
+/// # Arguments
+///
+/// * `public_key` - A str that represents the public key, hex encoded.
+///
+/// # Example
+/// ```
+/// use banana_coin::crypto::{check_public_key, KeyPair};
+/// let result = check_public_key(&KeyPair::generate().get_public_key());
+/// # assert!(result.is_ok());
+/// # assert!(check_public_key("d75a98").is_err())
+/// ```
+///
+/// # Errors
+///
+/// An InvalidKeyError is returned if the public key is not a valid Ed25519 public key made of 32 hex encoded bytes.
+///
+pub fn check_public_key(
+    public_key: &str,
+) -> Result<(), CryptoError> {
+    decode_public_key(public_key).map(|_| ())
+}
+
+/// Decode a hex encoded public key, or return an InvalidKeyError.
+fn decode_public_key(
+    public_key: &str,
+) -> Result<VerifyingKey, CryptoError> {
+    let public_key_bytes = decode_array::<32>(public_key)
+        .map_err(|message| CryptoError::InvalidKeyError { message })?;
+    VerifyingKey::from_bytes(&public_key_bytes)
+        .map_err(|error| CryptoError::InvalidKeyError { message: error.to_string() })
+}
+
+/// Decode a hex string made of exactly N bytes.
+fn decode_array<const N: usize>(
+    value: &str,
+) -> Result<[u8; N], String> {
+    let bytes = hex::decode(value).map_err(|error| error.to_string())?;
+    let length = bytes.len();
+    bytes
+        .try_into()
+        .map_err(|_| format!("expected {} bytes, found {}", N, length))
+}
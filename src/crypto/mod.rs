@@ -0,0 +1,3 @@
+/// Importing key pair module
+pub mod key_pair;
+pub use key_pair::*;
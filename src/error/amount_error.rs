@@ -0,0 +1,36 @@
+use std::{error, fmt};
+use std::fmt::{Error, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AmountError::*;
+use crate::model::Amount;
+
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub enum AmountError {
+    InvalidFormatError { text: String },
+    TooManyDecimalsError { decimals: u8, max_decimals: u8 },
+    OutOfRangeError { text: String },
+    PrecisionLossError { amount: Amount, decimals: u8 },
+    AssetDecimalsConflictError { asset: String, decimals: u8, registered_decimals: u8 },
+}
+
+impl error::Error for AmountError {}
+
+impl fmt::Display for AmountError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            InvalidFormatError { text } =>
+                write!(f, "The text {:?} is not a valid amount: it must look like 12 or -12.345", text),
+            TooManyDecimalsError { decimals, max_decimals } =>
+                write!(f, "An amount can not have {} decimal places: the maximum is {}", decimals, max_decimals),
+            OutOfRangeError { text } =>
+                write!(f, "The amount {} is out of the range of the amounts", text),
+            PrecisionLossError { amount, decimals } =>
+                write!(f, "The amount {} can not be represented with {} decimal places without rounding", amount, decimals),
+            AssetDecimalsConflictError { asset, decimals, registered_decimals } =>
+                write!(f, "The asset {} can not have {} decimal places: it is registered with {}", asset, decimals, registered_decimals),
+        }
+    }
+}
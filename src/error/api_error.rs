@@ -0,0 +1,53 @@
+use std::{error, fmt, io};
+use std::fmt::{Error, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ApiError::*;
+
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub enum ApiError {
+    IoError { message: String },
+    MalformedRequestError { message: String },
+    PayloadTooLargeError { length: usize, limit: usize },
+    RouteNotFoundError { method: String, path: String },
+    MethodNotAllowedError { method: String, path: String },
+    InvalidParameterError { parameter: String, value: String },
+    InvalidBodyError { message: String },
+    MissingIdempotencyKeyError,
+    IdempotencyKeyReuseError { idempotency_key: String },
+}
+
+impl error::Error for ApiError {}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            IoError { message } =>
+                write!(f, "A network operation of the server failed: {}", message),
+            MalformedRequestError { message } =>
+                write!(f, "The HTTP request is malformed: {}", message),
+            PayloadTooLargeError { length, limit } =>
+                write!(f, "The body of the HTTP request is {} bytes long but at most {} bytes are accepted", length, limit),
+            RouteNotFoundError { method, path } =>
+                write!(f, "No resource answers {} {}", method, path),
+            MethodNotAllowedError { method, path } =>
+                write!(f, "The resource {} does not accept the method {}", path, method),
+            InvalidParameterError { parameter, value } =>
+                write!(f, "The value '{}' of the parameter {} is not valid", value, parameter),
+            InvalidBodyError { message } =>
+                write!(f, "The body of the HTTP request is not valid: {}", message),
+            MissingIdempotencyKeyError =>
+                write!(f, "The request requires an Idempotency-Key header"),
+            IdempotencyKeyReuseError { idempotency_key } =>
+                write!(f, "The idempotency key {} was already used by a request with a different body", idempotency_key),
+        }
+    }
+}
+
+impl From<io::Error> for ApiError {
+    fn from(error: io::Error) -> Self {
+        IoError { message: error.to_string() }
+    }
+}
@@ -0,0 +1,47 @@
+use std::{error, fmt, io};
+use std::fmt::{Error, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AuditError::*;
+
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub enum AuditError {
+    IoError { message: String },
+    SerializationError { message: String },
+    TamperedRecordError { line: usize, message: String },
+    InvalidEventError { message: String },
+    LockedLogError { path: String },
+}
+
+impl error::Error for AuditError {}
+
+impl fmt::Display for AuditError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            IoError { message } =>
+                write!(f, "An I/O operation of the audit log failed: {}", message),
+            SerializationError { message } =>
+                write!(f, "A record of the audit log could not be serialized: {}", message),
+            TamperedRecordError { line, message } =>
+                write!(f, "The record at line {} of the audit log has been tampered with: {}", line, message),
+            InvalidEventError { message } =>
+                write!(f, "The event can not be recorded in the audit log: {}", message),
+            LockedLogError { path } =>
+                write!(f, "The audit log {} is already open in another process", path),
+        }
+    }
+}
+
+impl From<io::Error> for AuditError {
+    fn from(error: io::Error) -> Self {
+        IoError { message: error.to_string() }
+    }
+}
+
+impl From<serde_json::Error> for AuditError {
+    fn from(error: serde_json::Error) -> Self {
+        SerializationError { message: error.to_string() }
+    }
+}
@@ -0,0 +1,81 @@
+use std::{error, fmt};
+use std::fmt::{Error, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ChainError::*, CryptoError, EntityError, TransactionError};
+use crate::model::Amount;
+
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub enum ChainError {
+    InvalidHeightError { expected_height: u64, found_height: u64 },
+    InvalidPreviousHashError { height: u64, expected_hash: String, found_hash: String },
+    InvalidMerkleRootError { height: u64, expected_root: String, found_root: String },
+    DecreasingTimestampError { height: u64, previous_timestamp: u64, timestamp: u64 },
+    InvalidTransactionError { height: u64, transaction_id: String, error: TransactionError },
+    BalanceOverflowError { height: u64, transaction_id: String, entity_id: String },
+    InsufficientBalanceError { height: u64, transaction_id: String, entity_id: String },
+    DuplicateTransactionIdError { height: u64, transaction_id: String },
+    InvalidNonceError { height: u64, transaction_id: String, expected_nonce: u64, found_nonce: u64 },
+    UnknownSenderError { height: u64, transaction_id: String, entity_id: String },
+    InvalidSignatureError { height: u64, transaction_id: String, error: CryptoError },
+    InvalidDifficultyError { height: u64, expected_difficulty: u32, found_difficulty: u32 },
+    InvalidProofOfWorkError { height: u64, difficulty: u32, hash: String },
+    MisplacedCoinbaseError { height: u64, transaction_id: String },
+    InvalidBlockRewardError { height: u64, expected_reward: Amount, found_reward: Amount },
+    InvalidRewardAssetError { height: u64, expected_asset: String, found_asset: String },
+    NonceExhaustedError { height: u64 },
+    MinerOperationError { entity_id: String, error: EntityError },
+    PublicKeyReplacementError { entity_id: String },
+    InvalidHashError { hash: String },
+}
+
+impl error::Error for ChainError {}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            InvalidHeightError { expected_height, found_height } =>
+                write!(f, "The block should have height {} but it has height {}", expected_height, found_height),
+            InvalidPreviousHashError { height, expected_hash, found_hash } =>
+                write!(f, "The block at height {} should refer to the previous block {} but it refers to {}", height, expected_hash, found_hash),
+            InvalidMerkleRootError { height, expected_root, found_root } =>
+                write!(f, "The transactions of the block at height {} have Merkle root {} but the header contains {}", height, expected_root, found_root),
+            DecreasingTimestampError { height, previous_timestamp, timestamp } =>
+                write!(f, "The block at height {} has timestamp {} which is lower than the timestamp {} of the previous block", height, timestamp, previous_timestamp),
+            InvalidTransactionError { height, transaction_id, error } =>
+                write!(f, "The transaction {} of the block at height {} is not valid -> {}", transaction_id, height, error),
+            BalanceOverflowError { height, transaction_id, entity_id } =>
+                write!(f, "Replaying the transaction {} of the block at height {} overflows the balance of the entity {}", transaction_id, height, entity_id),
+            InsufficientBalanceError { height, transaction_id, entity_id } =>
+                write!(f, "Replaying the transaction {} of the block at height {} spends more coins than the entity {} holds", transaction_id, height, entity_id),
+            DuplicateTransactionIdError { height, transaction_id } =>
+                write!(f, "The transaction {} of the block at height {} is already in the chain", transaction_id, height),
+            InvalidNonceError { height, transaction_id, expected_nonce, found_nonce } =>
+                write!(f, "The transaction {} of the block at height {} should have nonce {} but it has nonce {}", transaction_id, height, expected_nonce, found_nonce),
+            UnknownSenderError { height, transaction_id, entity_id } =>
+                write!(f, "The transaction {} of the block at height {} is sent by the entity {} which has no registered public key", transaction_id, height, entity_id),
+            InvalidSignatureError { height, transaction_id, error } =>
+                write!(f, "The signature of the transaction {} of the block at height {} is not valid -> {}", transaction_id, height, error),
+            InvalidDifficultyError { height, expected_difficulty, found_difficulty } =>
+                write!(f, "The block at height {} should have difficulty {} but it has difficulty {}", height, expected_difficulty, found_difficulty),
+            InvalidProofOfWorkError { height, difficulty, hash } =>
+                write!(f, "The hash {} of the block at height {} does not meet the difficulty {}", hash, height, difficulty),
+            MisplacedCoinbaseError { height, transaction_id } =>
+                write!(f, "The transaction {} of the block at height {} mints coins but it is not the first transaction of the block", transaction_id, height),
+            InvalidBlockRewardError { height, expected_reward, found_reward } =>
+                write!(f, "The block at height {} should reward its miner with {} coins but it rewards {} coins", height, expected_reward, found_reward),
+            InvalidRewardAssetError { height, expected_asset, found_asset } =>
+                write!(f, "The block at height {} should reward its miner in asset {} but it rewards asset {}", height, expected_asset, found_asset),
+            NonceExhaustedError { height } =>
+                write!(f, "No nonce meets the difficulty of the block at height {}", height),
+            MinerOperationError { entity_id, error } =>
+                write!(f, "Error while crediting the block reward to the miner {} -> {}", entity_id, error),
+            PublicKeyReplacementError { entity_id } =>
+                write!(f, "The entity {} already has a different registered public key, which the blocks of the chain may have been checked with", entity_id),
+            InvalidHashError { hash } =>
+                write!(f, "The hash {} is not a hex encoded hash", hash),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::{error, fmt};
+use std::fmt::{Error, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AuditError, CliError::*, CryptoError, EntityError, StorageError, TransactionError, TransferError};
+
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub enum CliError {
+    UsageError { message: String },
+    DuplicateEntityError { entity_id: String },
+    ReservedEntityIdError { entity_id: String },
+    EntityNotFoundError { entity_id: String },
+    PublicKeyReplacementError { entity_id: String },
+    TransactionNotFoundError { transaction_id: String },
+    EntityOperationError { entity_id: String, error: EntityError },
+    TransactionOperationError { transaction_id: String, error: TransactionError },
+    TransferOperationError { error: Box<TransferError> },
+    CryptoOperationError { error: CryptoError },
+    StorageOperationError { error: StorageError },
+    AuditOperationError { error: AuditError },
+}
+
+impl error::Error for CliError {}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            UsageError { message } =>
+                write!(f, "{}", message),
+            DuplicateEntityError { entity_id } =>
+                write!(f, "An entity identified by {} already exists", entity_id),
+            ReservedEntityIdError { entity_id } =>
+                write!(f, "The id {} is reserved for an account of the ledger", entity_id),
+            EntityNotFoundError { entity_id } =>
+                write!(f, "No entity identified by {} exists", entity_id),
+            PublicKeyReplacementError { entity_id } =>
+                write!(f, "The entity {} already has a public key, which can not be replaced", entity_id),
+            TransactionNotFoundError { transaction_id } =>
+                write!(f, "No transaction identified by {} exists", transaction_id),
+            EntityOperationError { entity_id, error } =>
+                write!(f, "An operation on the entity {} generated the following error -> {}", entity_id, error),
+            TransactionOperationError { transaction_id, error } =>
+                write!(f, "An operation on the transaction {} generated the following error -> {}", transaction_id, error),
+            TransferOperationError { error } =>
+                write!(f, "A transfer operation generated the following error -> {}", error),
+            CryptoOperationError { error } =>
+                write!(f, "A cryptographic operation generated the following error -> {}", error),
+            StorageOperationError { error } =>
+                write!(f, "A storage operation generated the following error -> {}", error),
+            AuditOperationError { error } =>
+                write!(f, "An audit log operation generated the following error -> {}", error),
+        }
+    }
+}
+
+impl From<StorageError> for CliError {
+    fn from(error: StorageError) -> Self {
+        StorageOperationError { error }
+    }
+}
+
+impl From<CryptoError> for CliError {
+    fn from(error: CryptoError) -> Self {
+        CryptoOperationError { error }
+    }
+}
+
+impl From<AuditError> for CliError {
+    fn from(error: AuditError) -> Self {
+        AuditOperationError { error }
+    }
+}
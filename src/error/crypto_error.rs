@@ -0,0 +1,32 @@
+use std::{error, fmt};
+use std::fmt::{Error, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::CryptoError::*;
+
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub enum CryptoError {
+    InvalidKeyError { message: String },
+    InvalidSignatureError { message: String },
+    MissingSignatureError,
+    SignatureMismatchError,
+}
+
+impl error::Error for CryptoError {}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            InvalidKeyError { message } =>
+                write!(f, "The key is not a valid Ed25519 key: {}", message),
+            InvalidSignatureError { message } =>
+                write!(f, "The signature is not a valid Ed25519 signature: {}", message),
+            MissingSignatureError =>
+                write!(f, "The data is not signed."),
+            SignatureMismatchError =>
+                write!(f, "The signature does not match the data and the public key."),
+        }
+    }
+}
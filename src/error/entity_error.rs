@@ -8,7 +8,8 @@ use crate::error::{EntityError::*, WalletError};
 #[derive(Serialize, Deserialize)]
 #[derive(Debug, Clone)]
 pub enum EntityError {
-    WalletOperationError {error : WalletError},
+    WalletOperationError {error : Box<WalletError>},
+    NonceOverflowError { nonce: u64 },
 }
 
 impl error::Error for EntityError {}
@@ -18,6 +19,8 @@ impl fmt::Display for EntityError {
         match self {
             WalletOperationError {error} =>
                 write!(f, "A wallet operation generated the following error -> {}", error),
+            NonceOverflowError { nonce } =>
+                write!(f, "The nonce {} of the entity can not be incremented without overflowing", nonce),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,48 @@
+use std::{error, fmt};
+use std::fmt::{Error, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{CryptoError, EscrowError::*, TransactionError};
+use crate::model::{ReleaseCondition, TransactionState};
+
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub enum EscrowError {
+    SettledEscrowError { transaction_id: String, current_state: TransactionState },
+    UnauthorizedApprovalError { transaction_id: String, entity_id: String, condition: ReleaseCondition },
+    DuplicateApprovalError { transaction_id: String, entity_id: String },
+    ReleaseConditionNotMetError { transaction_id: String, condition: ReleaseCondition },
+    UnauthorizedRefundError { transaction_id: String, entity_id: String },
+    MissingPublicKeyError { entity_id: String },
+    SignatureVerificationError { transaction_id: String, entity_id: String, error: CryptoError },
+    InvalidTransactionError { transaction_id: String, error: Box<TransactionError> },
+    OpenEscrowStateError { transaction_id: String, current_state: TransactionState },
+}
+
+impl error::Error for EscrowError {}
+
+impl fmt::Display for EscrowError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            SettledEscrowError { transaction_id, current_state } =>
+                write!(f, "The escrow of the transaction {} has already been released or refunded: the current state is {:?}", transaction_id, current_state),
+            UnauthorizedApprovalError { transaction_id, entity_id, condition } =>
+                write!(f, "The entity {} can not approve the release of the escrow of the transaction {}: the release condition is {}", entity_id, transaction_id, condition),
+            DuplicateApprovalError { transaction_id, entity_id } =>
+                write!(f, "The entity {} has already approved the release of the escrow of the transaction {}", entity_id, transaction_id),
+            ReleaseConditionNotMetError { transaction_id, condition } =>
+                write!(f, "The release condition of the escrow of the transaction {} is not met yet: the release condition is {}", transaction_id, condition),
+            UnauthorizedRefundError { transaction_id, entity_id } =>
+                write!(f, "The entity {} can not refund the escrow of the transaction {}", entity_id, transaction_id),
+            MissingPublicKeyError { entity_id } =>
+                write!(f, "The entity {} has no registered public key to sign its approval with", entity_id),
+            SignatureVerificationError { transaction_id, entity_id, error } =>
+                write!(f, "The approval of the escrow of the transaction {} by the entity {} is not signed with its registered key -> {}", transaction_id, entity_id, error),
+            InvalidTransactionError { transaction_id, error } =>
+                write!(f, "The transaction {} of the escrow is not valid -> {}", transaction_id, error),
+            OpenEscrowStateError { transaction_id, current_state } =>
+                write!(f, "The transaction {} of an open escrow must be Blocked: the current state is {:?}", transaction_id, current_state),
+        }
+    }
+}
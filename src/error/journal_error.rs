@@ -0,0 +1,36 @@
+use std::{error, fmt};
+use std::fmt::{Error, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::JournalError::*;
+use crate::model::Amount;
+
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub enum JournalError {
+    ZeroAmountPostingError { transaction_id: String },
+    SameEntityPostingError { transaction_id: String, entity_id: String },
+    NegativeAmountPostingError { transaction_id: String, amount: Amount },
+    UnbalancedJournalError { asset: String, total: Amount },
+    BalanceOverflowError { asset: String },
+}
+
+impl error::Error for JournalError {}
+
+impl fmt::Display for JournalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            ZeroAmountPostingError { transaction_id } =>
+                write!(f, "The journal does not allow to record a zero/null amount of coins: the transaction is {}", transaction_id),
+            SameEntityPostingError { transaction_id, entity_id } =>
+                write!(f, "The debit and the credit of the transaction {} refer to the same entity {}", transaction_id, entity_id),
+            NegativeAmountPostingError { transaction_id, amount } =>
+                write!(f, "The journal does not allow to record a negative amount of coins: the transaction {} moves {}", transaction_id, amount),
+            UnbalancedJournalError { asset, total } =>
+                write!(f, "The journal is not balanced: the sum of all the postings of asset {} is {}", asset, total),
+            BalanceOverflowError { asset } =>
+                write!(f, "The sum of the postings of asset {} is out of range", asset),
+        }
+    }
+}
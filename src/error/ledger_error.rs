@@ -0,0 +1,66 @@
+use std::{error, fmt};
+use std::fmt::{Error, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AmountError, EntityError, EscrowError, JournalError, LedgerError::*, StorageError, TransactionError, TransferError};
+use crate::model::Amount;
+
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub enum LedgerError {
+    DuplicateEntityError { entity_id: String },
+    EntityNotFoundError { entity_id: String },
+    DuplicateTransactionError { transaction_id: String },
+    TransferOperationError { error: Box<TransferError> },
+    ReservedEntityIdError { entity_id: String },
+    JournalOperationError { error: JournalError },
+    BalanceMismatchError { entity_id: String, asset: String, wallet_balance: Amount, derived_balance: Amount },
+    EscrowNotFoundError { transaction_id: String },
+    EscrowOperationError { error: EscrowError },
+    AssetDefinitionError { error: AmountError },
+    AssetPrecisionError { entity_id: String, asset: String, amount: Amount, decimals: u8 },
+    InvalidTransactionError { transaction_id: String, error: TransactionError },
+    StorageOperationError { error: StorageError },
+    EscrowsAlreadyOpenError { count: usize },
+    EscrowAccountOperationError { error: EntityError },
+}
+
+impl error::Error for LedgerError {}
+
+impl fmt::Display for LedgerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            DuplicateEntityError { entity_id } =>
+                write!(f, "An entity identified by {} is already registered in the ledger", entity_id),
+            EntityNotFoundError { entity_id } =>
+                write!(f, "No entity identified by {} is registered in the ledger", entity_id),
+            DuplicateTransactionError { transaction_id } =>
+                write!(f, "A transaction identified by {} is already recorded in the ledger", transaction_id),
+            TransferOperationError { error } =>
+                write!(f, "A transfer operation generated the following error -> {}", error),
+            ReservedEntityIdError { entity_id } =>
+                write!(f, "The id {} is reserved and can not be used by an entity", entity_id),
+            JournalOperationError { error } =>
+                write!(f, "A journal operation generated the following error -> {}", error),
+            BalanceMismatchError { entity_id, asset, wallet_balance, derived_balance } =>
+                write!(f, "The wallet of the entity {} has a balance of {} in asset {} but its postings sum up to {}", entity_id, wallet_balance, asset, derived_balance),
+            EscrowNotFoundError { transaction_id } =>
+                write!(f, "No escrow of the transaction {} is open in the ledger", transaction_id),
+            EscrowOperationError { error } =>
+                write!(f, "An escrow operation generated the following error -> {}", error),
+            AssetDefinitionError { error } =>
+                write!(f, "The asset can not be defined in the ledger -> {}", error),
+            AssetPrecisionError { entity_id, asset, amount, decimals } =>
+                write!(f, "The wallet of the entity {} has a balance of {} in asset {}, which has {} decimal places", entity_id, amount, asset, decimals),
+            InvalidTransactionError { transaction_id, error } =>
+                write!(f, "The transaction {} is not valid -> {}", transaction_id, error),
+            StorageOperationError { error } =>
+                write!(f, "A storage operation generated the following error -> {}", error),
+            EscrowsAlreadyOpenError { count } =>
+                write!(f, "The escrows can not be loaded in a ledger that has already {} open escrows", count),
+            EscrowAccountOperationError { error } =>
+                write!(f, "An operation on the escrow account generated the following error -> {}", error),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use std::{error, fmt};
+use std::fmt::{Error, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{MempoolError::*, TransactionError};
+use crate::model::{Amount, TransactionState};
+
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub enum MempoolError {
+    DuplicateTransactionError { transaction_id: String },
+    TransactionNotFoundError { transaction_id: String },
+    InvalidTransactionStateError { transaction_id: String, current_state: TransactionState },
+    EntityMismatchError { expected_entity_id: String, actual_entity_id: String },
+    InsufficientBalanceError { entity_id: String, asset: String, required_amount: Amount },
+    TransactionOperationError { transaction_id: String, error: TransactionError },
+    ReplayedNonceError { entity_id: String, expected_nonce: u64, found_nonce: u64 },
+    OutOfOrderNonceError { entity_id: String, expected_nonce: u64, found_nonce: u64 },
+    AmountOverflowError { entity_id: String, asset: String },
+}
+
+impl error::Error for MempoolError {}
+
+impl fmt::Display for MempoolError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            DuplicateTransactionError { transaction_id } =>
+                write!(f, "A transaction identified by {} is already in the mempool", transaction_id),
+            TransactionNotFoundError { transaction_id } =>
+                write!(f, "No transaction identified by {} is in the mempool", transaction_id),
+            InvalidTransactionStateError { transaction_id, current_state } =>
+                write!(f, "The transaction {} can not be handled in its current state {:?}", transaction_id, current_state),
+            EntityMismatchError { expected_entity_id, actual_entity_id } =>
+                write!(f, "The transaction refers to the entity {} but the entity {} has been provided", expected_entity_id, actual_entity_id),
+            InsufficientBalanceError { entity_id, asset, required_amount } =>
+                write!(f, "The available balance of the entity {} is too low to spend the {} coins of asset {} of its pending transactions and of this one", entity_id, required_amount, asset),
+            TransactionOperationError { transaction_id, error } =>
+                write!(f, "An operation on the transaction {} generated the following error -> {}", transaction_id, error),
+            ReplayedNonceError { entity_id, expected_nonce, found_nonce } =>
+                write!(f, "The nonce {} of the entity {} has already been used: the next transaction must carry the nonce {}", found_nonce, entity_id, expected_nonce),
+            OutOfOrderNonceError { entity_id, expected_nonce, found_nonce } =>
+                write!(f, "The nonce {} is ahead of the entity {}: the next transaction must carry the nonce {}", found_nonce, entity_id, expected_nonce),
+            AmountOverflowError { entity_id, asset } =>
+                write!(f, "The coins of asset {} pending for or available to the entity {} are out of range", asset, entity_id),
+        }
+    }
+}
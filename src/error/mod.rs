@@ -4,4 +4,64 @@ pub use wallet_error::*;
 
 /// Importing entity error module
 pub mod entity_error;
-pub use entity_error::*;
\ No newline at end of file
+pub use entity_error::*;
+
+/// Importing transfer error module
+pub mod transfer_error;
+pub use transfer_error::*;
+
+/// Importing transaction error module
+pub mod transaction_error;
+pub use transaction_error::*;
+
+/// Importing ledger error module
+pub mod ledger_error;
+pub use ledger_error::*;
+
+/// Importing journal error module
+pub mod journal_error;
+pub use journal_error::*;
+
+/// Importing storage error module
+pub mod storage_error;
+pub use storage_error::*;
+
+/// Importing cli error module
+pub mod cli_error;
+pub use cli_error::*;
+
+/// Importing crypto error module
+pub mod crypto_error;
+pub use crypto_error::*;
+
+/// Importing chain error module
+pub mod chain_error;
+pub use chain_error::*;
+
+/// Importing network error module
+pub mod network_error;
+pub use network_error::*;
+
+/// Importing mempool error module
+pub mod mempool_error;
+pub use mempool_error::*;
+
+/// Importing amount error module
+pub mod amount_error;
+pub use amount_error::*;
+
+/// Importing escrow error module
+pub mod escrow_error;
+pub use escrow_error::*;
+
+/// Importing api error module
+pub mod api_error;
+pub use api_error::*;
+
+/// Importing webhook error module
+pub mod webhook_error;
+pub use webhook_error::*;
+
+/// Importing audit error module
+pub mod audit_error;
+pub use audit_error::*;
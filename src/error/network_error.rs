@@ -0,0 +1,72 @@
+use std::{error, fmt, io};
+use std::fmt::{Error, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ChainError, NetworkError::*, TransactionError};
+use crate::model::TransactionState;
+
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub enum NetworkError {
+    IoError { message: String },
+    SerializationError { message: String },
+    IncompatibleVersionError { expected_version: u32, found_version: u32 },
+    UnexpectedMessageError { message: String },
+    DuplicateTransactionError { transaction_id: String },
+    InvalidTransactionError { transaction_id: String, error: TransactionError },
+    ChainOperationError { error: Box<ChainError> },
+    RejectedTransactionError { transaction_id: String, error: Box<ChainError> },
+    ProcessedTransactionError { transaction_id: String, state: TransactionState },
+    MessageTooLongError { max_length: usize },
+    TooManyPeersError { max_peers: usize },
+}
+
+impl error::Error for NetworkError {}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            IoError { message } =>
+                write!(f, "A network operation failed: {}", message),
+            SerializationError { message } =>
+                write!(f, "A message could not be serialized or deserialized: {}", message),
+            IncompatibleVersionError { expected_version, found_version } =>
+                write!(f, "The peer speaks the protocol version {} but version {} is required", found_version, expected_version),
+            UnexpectedMessageError { message } =>
+                write!(f, "The peer sent an unexpected message: {}", message),
+            DuplicateTransactionError { transaction_id } =>
+                write!(f, "A transaction identified by {} is already known by the node", transaction_id),
+            InvalidTransactionError { transaction_id, error } =>
+                write!(f, "The transaction {} is not valid -> {}", transaction_id, error),
+            ChainOperationError { error } =>
+                write!(f, "Error while operating on the blockchain -> {}", error),
+            RejectedTransactionError { transaction_id, error } =>
+                write!(f, "The transaction {} can not follow the chain of the node -> {}", transaction_id, error),
+            ProcessedTransactionError { transaction_id, state } =>
+                write!(f, "The transaction {} is in state {:?} but only created transactions can be submitted, since the block including a transaction executes it", transaction_id, state),
+            MessageTooLongError { max_length } =>
+                write!(f, "The peer sent a message longer than {} bytes", max_length),
+            TooManyPeersError { max_peers } =>
+                write!(f, "The node is already connected to {} peers", max_peers),
+        }
+    }
+}
+
+impl From<io::Error> for NetworkError {
+    fn from(error: io::Error) -> Self {
+        IoError { message: error.to_string() }
+    }
+}
+
+impl From<serde_json::Error> for NetworkError {
+    fn from(error: serde_json::Error) -> Self {
+        SerializationError { message: error.to_string() }
+    }
+}
+
+impl From<ChainError> for NetworkError {
+    fn from(error: ChainError) -> Self {
+        ChainOperationError { error: Box::new(error) }
+    }
+}
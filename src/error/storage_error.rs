@@ -0,0 +1,57 @@
+use std::{error, fmt, io};
+use std::fmt::{Error, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::StorageError::*;
+
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub enum StorageError {
+    IoError { message: String },
+    SerializationError { message: String },
+    CorruptedRecordError { line: usize, message: String },
+    DatabaseError { message: String },
+    DuplicateTransactionError { transaction_id: String },
+    LockedStorageError { path: String },
+}
+
+impl error::Error for StorageError {}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            IoError { message } =>
+                write!(f, "An I/O operation of the storage failed: {}", message),
+            SerializationError { message } =>
+                write!(f, "A record of the storage could not be serialized or deserialized: {}", message),
+            CorruptedRecordError { line, message } =>
+                write!(f, "The record at line {} of the storage is corrupted: {}", line, message),
+            DatabaseError { message } =>
+                write!(f, "A database operation of the storage failed: {}", message),
+            DuplicateTransactionError { transaction_id } =>
+                write!(f, "A transaction identified by {} is already stored", transaction_id),
+            LockedStorageError { path } =>
+                write!(f, "The storage file {} is already open in another process", path),
+        }
+    }
+}
+
+impl From<io::Error> for StorageError {
+    fn from(error: io::Error) -> Self {
+        IoError { message: error.to_string() }
+    }
+}
+
+impl From<serde_json::Error> for StorageError {
+    fn from(error: serde_json::Error) -> Self {
+        SerializationError { message: error.to_string() }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for StorageError {
+    fn from(error: rusqlite::Error) -> Self {
+        DatabaseError { message: error.to_string() }
+    }
+}
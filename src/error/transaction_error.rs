@@ -0,0 +1,48 @@
+use std::{error, fmt};
+use std::fmt::{Error, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::TransactionError::*;
+use crate::model::{Amount, TransactionState};
+
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub enum TransactionError {
+    EmptyStateHistoryError,
+    InvalidInitialStateError { state: TransactionState },
+    IllegalTransitionError { from_state: TransactionState, to_state: TransactionState },
+    DecreasingTimestampError { from_state: TransactionState, to_state: TransactionState },
+    CurrentStateMismatchError { current_state: TransactionState, last_state: TransactionState },
+    AmountOverflowError { amount: Amount, fee: Amount },
+    NegativeAmountError { amount: Amount },
+    EmptyAssetError,
+    AssetPrecisionError { asset: String, amount: Amount, decimals: u8 },
+}
+
+impl error::Error for TransactionError {}
+
+impl fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            EmptyStateHistoryError =>
+                write!(f, "The state history of a transaction can not be empty."),
+            InvalidInitialStateError { state } =>
+                write!(f, "A transaction must start in the created state: the first state is {:?}", state),
+            IllegalTransitionError { from_state, to_state } =>
+                write!(f, "A transaction can not move from the state {:?} to the state {:?}", from_state, to_state),
+            DecreasingTimestampError { from_state, to_state } =>
+                write!(f, "The timestamp of a transaction can not decrease: moving from the state {:?} to the state {:?}", from_state, to_state),
+            CurrentStateMismatchError { current_state, last_state } =>
+                write!(f, "The current state {:?} does not match the last state {:?} of the history", current_state, last_state),
+            AmountOverflowError { amount, fee } =>
+                write!(f, "The amount {} plus the fee {} of the transaction overflows", amount, fee),
+            NegativeAmountError { amount } =>
+                write!(f, "The amount and the fee of a transaction can not be negative: found {}", amount),
+            EmptyAssetError =>
+                write!(f, "The asset code of a transaction can not be empty."),
+            AssetPrecisionError { asset, amount, decimals } =>
+                write!(f, "The amount {} has more decimal places than the {} of the asset {}", amount, decimals, asset),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use std::{error, fmt};
+use std::fmt::{Error, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{CryptoError, EntityError, EscrowError, TransactionError, TransferError::*};
+use crate::model::TransactionState;
+
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub enum TransferError {
+    EntityMismatchError { expected_entity_id: String, actual_entity_id: String },
+    SelfTransferError { entity_id: String },
+    InvalidTransactionStateError { current_state: TransactionState },
+    EntityOperationError { entity_id: String, error: EntityError },
+    TransactionOperationError { transaction_id: String, error: TransactionError },
+    MissingPublicKeyError { entity_id: String },
+    SignatureVerificationError { transaction_id: String, error: CryptoError },
+    ReplayedNonceError { entity_id: String, expected_nonce: u64, found_nonce: u64 },
+    OutOfOrderNonceError { entity_id: String, expected_nonce: u64, found_nonce: u64 },
+    MissingFeeCollectorError { transaction_id: String },
+    EscrowOperationError { transaction_id: String, error: EscrowError },
+}
+
+impl error::Error for TransferError {}
+
+impl fmt::Display for TransferError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            EntityMismatchError { expected_entity_id, actual_entity_id } =>
+                write!(f, "The transaction refers to the entity {} but the entity {} has been provided", expected_entity_id, actual_entity_id),
+            SelfTransferError { entity_id } =>
+                write!(f, "The entity {} can not transfer coins to itself", entity_id),
+            InvalidTransactionStateError { current_state } =>
+                write!(f, "Only a transaction in the created or on process state can be executed: the current state is {:?}", current_state),
+            EntityOperationError { entity_id, error } =>
+                write!(f, "An operation on the entity {} generated the following error -> {}", entity_id, error),
+            TransactionOperationError { transaction_id, error } =>
+                write!(f, "An operation on the transaction {} generated the following error -> {}", transaction_id, error),
+            MissingPublicKeyError { entity_id } =>
+                write!(f, "The entity {} has no public key, so its transactions can not be verified", entity_id),
+            SignatureVerificationError { transaction_id, error } =>
+                write!(f, "The signature of the transaction {} can not be verified -> {}", transaction_id, error),
+            ReplayedNonceError { entity_id, expected_nonce, found_nonce } =>
+                write!(f, "The nonce {} of the entity {} has already been used: the next transaction must carry the nonce {}", found_nonce, entity_id, expected_nonce),
+            OutOfOrderNonceError { entity_id, expected_nonce, found_nonce } =>
+                write!(f, "The nonce {} is ahead of the entity {}: the next transaction must carry the nonce {}", found_nonce, entity_id, expected_nonce),
+            MissingFeeCollectorError { transaction_id } =>
+                write!(f, "The transaction {} has a fee but no entity collects the fees", transaction_id),
+            EscrowOperationError { transaction_id, error } =>
+                write!(f, "An operation on the escrow of the transaction {} generated the following error -> {}", transaction_id, error),
+        }
+    }
+}
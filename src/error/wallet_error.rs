@@ -4,17 +4,28 @@ use std::fmt::{Error, Formatter};
 use serde::{Deserialize, Serialize};
 
 use crate::error::WalletError::*;
+use crate::model::Amount;
 
 #[derive(Serialize, Deserialize)]
 #[derive(Debug, Clone)]
 pub enum WalletError {
-    AddCoinError,
+    AddCoinError { added_amount: Amount },
     AddZeroCoinError,
-    AddCoinOverflowError { current_amount: i64, added_amount: u32 },
-    RemoveCoinError,
+    AddCoinOverflowError { asset: String, current_amount: Amount, added_amount: Amount },
+    RemoveCoinError { removed_amount: Amount },
     RemoveZeroCoinError,
-    RemoveCoinOverflowError { current_amount: i64, removed_amount: u32 },
-    RemoveCoinNegativeBalanceError { current_amount: i64, removed_amount: u32, negative_balance_allowed: bool },
+    RemoveCoinOverflowError { asset: String, current_amount: Amount, removed_amount: Amount },
+    RemoveCoinNegativeBalanceError { asset: String, current_amount: Amount, held_amount: Amount, removed_amount: Amount },
+    PositiveOverdraftFloorError { floor: Amount },
+    EmptyAssetError,
+    InvalidHoldAmountError { hold_id: String, amount: Amount },
+    DuplicateHoldError { hold_id: String },
+    HoldNotFoundError { hold_id: String },
+    HoldNegativeBalanceError { hold_id: String, asset: String, available_amount: Amount, held_amount: Amount },
+    HoldAssetMismatchError { hold_id: String, hold_asset: String, asset: String },
+    CaptureExceedsHoldError { hold_id: String, held_amount: Amount, captured_amount: Amount },
+    HeldAmountOverflowError { asset: String },
+    AssetPrecisionError { asset: String, amount: Amount, decimals: u8 },
 }
 
 impl error::Error for WalletError {}
@@ -22,20 +33,40 @@ impl error::Error for WalletError {}
 impl fmt::Display for WalletError {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         match self {
-            AddCoinError =>
-                write!(f, "Impossible to add coin to the wallet. This error should never happen."),
+            AddCoinError { added_amount } =>
+                write!(f, "The wallet does not allow to add a negative amount of coins: the added amount is {}", added_amount),
             AddZeroCoinError =>
                 write!(f, "The wallet does not allow to add a zero/null amount of coins."),
-            AddCoinOverflowError { current_amount, added_amount } =>
-                write!(f, "An overflow has been detected: the current amount is {} and the added amount is {}", current_amount, added_amount),
-            RemoveCoinError =>
-                write!(f, "Impossible to remove coin from the wallet. This error should never happen."),
+            AddCoinOverflowError { asset, current_amount, added_amount } =>
+                write!(f, "An overflow has been detected on asset {}: the current amount is {} and the added amount is {}", asset, current_amount, added_amount),
+            RemoveCoinError { removed_amount } =>
+                write!(f, "The wallet does not allow to remove a negative amount of coins: the removed amount is {}", removed_amount),
             RemoveZeroCoinError =>
                 write!(f, "The wallet does not allow to remove a zero/null amount of coins."),
-            RemoveCoinOverflowError { current_amount, removed_amount } =>
-                write!(f, "An overflow has been detected: the current amount is {} and the removed amount is {}", current_amount, removed_amount),
-            RemoveCoinNegativeBalanceError { current_amount, removed_amount, negative_balance_allowed } =>
-                write!(f, "Removing coins from this wallet will return a negative balance: the current amount is {} and the removed amount is {} and negative balance flag is {}", current_amount, removed_amount, negative_balance_allowed)
+            RemoveCoinOverflowError { asset, current_amount, removed_amount } =>
+                write!(f, "An overflow has been detected on asset {}: the current amount is {} and the removed amount is {}", asset, current_amount, removed_amount),
+            RemoveCoinNegativeBalanceError { asset, current_amount, held_amount, removed_amount } =>
+                write!(f, "Removing coins of asset {} from this wallet will exceed its overdraft policy: the current amount is {} of which {} held and the removed amount is {}", asset, current_amount, held_amount, removed_amount),
+            PositiveOverdraftFloorError { floor } =>
+                write!(f, "The floor of an overdraft limit can not be positive: the floor is {}", floor),
+            EmptyAssetError =>
+                write!(f, "The wallet does not allow an empty asset code."),
+            InvalidHoldAmountError { hold_id, amount } =>
+                write!(f, "The hold {} must be on a positive amount of coins: the amount is {}", hold_id, amount),
+            DuplicateHoldError { hold_id } =>
+                write!(f, "The wallet already has a hold identified by '{}'.", hold_id),
+            HoldNotFoundError { hold_id } =>
+                write!(f, "The wallet has no hold identified by '{}', it may be expired.", hold_id),
+            HoldNegativeBalanceError { hold_id, asset, available_amount, held_amount } =>
+                write!(f, "Placing the hold {} on coins of asset {} will exceed the overdraft policy of the wallet: the available amount is {} and the held amount is {}", hold_id, asset, available_amount, held_amount),
+            HoldAssetMismatchError { hold_id, hold_asset, asset } =>
+                write!(f, "The hold {} is on asset {}, not on asset {}", hold_id, hold_asset, asset),
+            CaptureExceedsHoldError { hold_id, held_amount, captured_amount } =>
+                write!(f, "The captured amount exceeds the hold {}: the held amount is {} and the captured amount is {}", hold_id, held_amount, captured_amount),
+            HeldAmountOverflowError { asset } =>
+                write!(f, "The coins of asset {} held by the wallet, or the coins left available, are out of range", asset),
+            AssetPrecisionError { asset, amount, decimals } =>
+                write!(f, "The amount {} has more decimal places than the {} of the asset {}", amount, decimals, asset),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,50 @@
+use std::{error, fmt, io};
+use std::fmt::{Error, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{CryptoError, WebhookError::*};
+
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub enum WebhookError {
+    InvalidUrlError { url: String, message: String },
+    EndpointNotFoundError { entity_id: String },
+    DeadLetterNotFoundError { delivery_id: String },
+    IoError { message: String },
+    MalformedResponseError { message: String },
+    UnexpectedStatusError { status: u16 },
+    MissingPublicKeyError { entity_id: String },
+    SignatureVerificationError { entity_id: String, error: CryptoError },
+}
+
+impl error::Error for WebhookError {}
+
+impl fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            InvalidUrlError { url, message } =>
+                write!(f, "The webhook url {} is not valid: {}", url, message),
+            EndpointNotFoundError { entity_id } =>
+                write!(f, "No webhook endpoint is registered for the entity {}", entity_id),
+            DeadLetterNotFoundError { delivery_id } =>
+                write!(f, "No dead-lettered webhook delivery is identified by {}", delivery_id),
+            IoError { message } =>
+                write!(f, "The webhook could not be delivered: {}", message),
+            MalformedResponseError { message } =>
+                write!(f, "The webhook endpoint sent a malformed response: {}", message),
+            UnexpectedStatusError { status } =>
+                write!(f, "The webhook endpoint answered with the status {} instead of a success", status),
+            MissingPublicKeyError { entity_id } =>
+                write!(f, "The entity {} has no public key to sign the changes of its webhook endpoint with", entity_id),
+            SignatureVerificationError { entity_id, error } =>
+                write!(f, "The change of the webhook endpoint of the entity {} is not signed with its registered key -> {}", entity_id, error),
+        }
+    }
+}
+
+impl From<io::Error> for WebhookError {
+    fn from(error: io::Error) -> Self {
+        IoError { message: error.to_string() }
+    }
+}
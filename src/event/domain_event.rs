@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chain::Block;
+use crate::event::DomainEvent::*;
+use crate::model::{Amount, Entity, OverdraftPolicy, Transaction, TransactionState, Wallet};
+
+/// Domain event enumeration
+/// It represents a change of the entities, of their wallets, of the transactions or of a chain, published once the change is done.
+/// Besides their balances, the changes of the entities are the setting of their public key and the changes of their overdraft policy.
+/// The events of an operation are published in the order the changes happen: a transfer moves to the OnProcess state,
+/// its coins are removed and added, then it reaches its last state.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub enum DomainEvent {
+    EntityCreated { entity_id: String, name: String, balances: BTreeMap<String, Amount> },
+    CoinsAdded { entity_id: String, asset: String, amount: Amount, balance: Amount, transaction_id: Option<String>, timestamp: u64 },
+    CoinsRemoved { entity_id: String, asset: String, amount: Amount, balance: Amount, transaction_id: Option<String>, timestamp: u64 },
+    PublicKeySet { entity_id: String, public_key: String, timestamp: u64 },
+    OverdraftPolicyChanged { entity_id: String, previous_policy: OverdraftPolicy, overdraft_policy: OverdraftPolicy, changed_by: String, timestamp: u64 },
+    TransactionStateChanged { transaction: Transaction, previous_state: Option<TransactionState> },
+    BlockAppended { height: u64, hash: String, transactions: Vec<Transaction>, timestamp: u64 },
+}
+
+/// Domain event implementation.
+impl DomainEvent {
+    ///
+    /// Return the name of the event.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::event::DomainEvent;
+    /// use banana_coin::model::Amount;
+    /// let event = DomainEvent::CoinsAdded {
+    ///     entity_id: "entity_001".to_string(),
+    ///     asset: "BNC".to_string(),
+    ///     amount: Amount::from_integer(10),
+    ///     balance: Amount::from_integer(110),
+    ///     transaction_id: None,
+    ///     timestamp: 1,
+    /// };
+    /// let name : &str = event.get_name();
+    /// # assert_eq!(name, "CoinsAdded")
+    /// ```
+    ///
+    pub fn get_name(
+        self: &DomainEvent,
+    ) -> &'static str {
+        match self {
+            EntityCreated { .. } => "EntityCreated",
+            CoinsAdded { .. } => "CoinsAdded",
+            CoinsRemoved { .. } => "CoinsRemoved",
+            PublicKeySet { .. } => "PublicKeySet",
+            OverdraftPolicyChanged { .. } => "OverdraftPolicyChanged",
+            TransactionStateChanged { .. } => "TransactionStateChanged",
+            BlockAppended { .. } => "BlockAppended",
+        }
+    }
+
+    ///
+    /// Return the ids of the entities the event is about: the sender and the receiver of a transaction,
+    /// or the senders and the receivers of the transactions of a block, without duplicates.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::event::DomainEvent;
+    /// # use banana_coin::model::Amount;
+    /// # let event = DomainEvent::CoinsAdded { entity_id: "entity_001".to_string(), asset: "BNC".to_string(), amount: Amount::from_integer(10), balance: Amount::from_integer(110), transaction_id: None, timestamp: 1 };
+    /// let entity_ids : Vec<String> = event.get_entity_ids();
+    /// # assert_eq!(entity_ids, vec!["entity_001"])
+    /// ```
+    ///
+    pub fn get_entity_ids(
+        self: &DomainEvent,
+    ) -> Vec<String> {
+        match self {
+            EntityCreated { entity_id, .. } | CoinsAdded { entity_id, .. } | CoinsRemoved { entity_id, .. }
+            | PublicKeySet { entity_id, .. } | OverdraftPolicyChanged { entity_id, .. } =>
+                vec![entity_id.clone()],
+            TransactionStateChanged { transaction, .. } =>
+                vec![transaction.get_from_entity_id(), transaction.get_to_entity_id()],
+            BlockAppended { transactions, .. } => {
+                let mut entity_ids: Vec<String> = transactions
+                    .iter()
+                    .flat_map(|transaction| vec![transaction.get_from_entity_id(), transaction.get_to_entity_id()])
+                    .collect();
+                entity_ids.sort();
+                entity_ids.dedup();
+                entity_ids
+            }
+        }
+    }
+}
+
+/// Return the event of a block appended to a chain.
+pub(crate) fn block_appended(
+    block: &Block,
+) -> DomainEvent {
+    BlockAppended {
+        height: block.get_header().get_height(),
+        hash: block.get_hash(),
+        transactions: block.get_transactions(),
+        timestamp: block.get_header().get_timestamp(),
+    }
+}
+
+/// Return the event of the creation of an entity.
+pub(crate) fn entity_created(
+    entity: &Entity,
+) -> DomainEvent {
+    EntityCreated {
+        entity_id: entity.get_id().to_string(),
+        name: entity.get_name().to_string(),
+        balances: entity.get_wallet().get_balances(),
+    }
+}
+
+/// Return the event of the setting of the public key of an entity, if it has one.
+pub(crate) fn public_key_set(
+    entity: &Entity,
+    timestamp: u64,
+) -> Option<DomainEvent> {
+    entity.get_public_key().map(|public_key| PublicKeySet {
+        entity_id: entity.get_id().to_string(),
+        public_key: public_key.to_string(),
+        timestamp,
+    })
+}
+
+/// Return the event of the change of the overdraft policy of an entity, given its policy before the change.
+pub(crate) fn overdraft_policy_changed(
+    entity: &Entity,
+    previous_policy: OverdraftPolicy,
+    changed_by: &str,
+    timestamp: u64,
+) -> DomainEvent {
+    OverdraftPolicyChanged {
+        entity_id: entity.get_id().to_string(),
+        previous_policy,
+        overdraft_policy: entity.get_wallet().get_overdraft_policy(),
+        changed_by: changed_by.to_string(),
+        timestamp,
+    }
+}
+
+/// Return the events of the balances of a wallet that changed, one for each asset.
+pub(crate) fn balance_changes(
+    entity_id: &str,
+    before: &Wallet,
+    after: &Wallet,
+    transaction_id: Option<String>,
+    timestamp: u64,
+) -> Vec<DomainEvent> {
+    let before = before.get_balances();
+    let after = after.get_balances();
+    let mut assets: Vec<&String> = before.keys().chain(after.keys()).collect();
+    assets.sort();
+    assets.dedup();
+    assets
+        .into_iter()
+        .filter_map(|asset| {
+            let old_balance = before.get(asset).copied().unwrap_or_default();
+            let balance = after.get(asset).copied().unwrap_or_default();
+            let difference = balance.checked_sub(old_balance)?;
+            let (entity_id, asset, transaction_id) = (entity_id.to_string(), asset.clone(), transaction_id.clone());
+            if difference.is_zero() {
+                None
+            } else if difference.is_negative() {
+                let amount = difference.checked_neg()?;
+                Some(CoinsRemoved { entity_id, asset, amount, balance, transaction_id, timestamp })
+            } else {
+                Some(CoinsAdded { entity_id, asset, amount: difference, balance, transaction_id, timestamp })
+            }
+        })
+        .collect()
+}
+
+/// Return the events of an operation on a transaction: the states reached since the given position of its history,
+/// with the changes of the balances happening before the last one.
+pub(crate) fn transaction_changes(
+    transaction: &Transaction,
+    seen_states: usize,
+    balance_changes: Vec<DomainEvent>,
+) -> Vec<DomainEvent> {
+    let history = transaction.get_state_history();
+    let mut events: Vec<DomainEvent> = (seen_states..history.len())
+        .map(|position| TransactionStateChanged {
+            transaction: transaction.at_state(position),
+            previous_state: position.checked_sub(1).map(|previous| history[previous].clone()),
+        })
+        .collect();
+    let last_state = events.pop();
+    events.extend(balance_changes);
+    events.extend(last_state);
+    events
+}
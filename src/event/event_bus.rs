@@ -0,0 +1,148 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::sync::mpsc::{self, Receiver};
+
+use crate::event::{ChannelSubscriber, DomainEvent, Subscriber};
+
+/// Event bus structure
+/// It publishes the domain events to its subscribers, in the order they subscribed.
+/// The copies of an event bus share their subscribers, so that a bus can be handed to every component publishing events.
+#[derive(Clone, Default)]
+pub struct EventBus {
+    subscribers: Arc<Mutex<Vec<Arc<dyn Subscriber>>>>,
+}
+
+/// Event bus implementation.
+impl EventBus {
+    ///
+    /// Return a new EventBus without subscribers.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::event::EventBus;
+    /// let event_bus = EventBus::new();
+    /// # assert_eq!(event_bus.get_subscriber_count(), 0)
+    /// ```
+    ///
+    pub fn new() -> EventBus {
+        EventBus {
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    ///
+    /// Subscribe to the events published from now on, which are handled synchronously by the subscriber.
+    ///
+    /// # Arguments
+    ///
+    /// * `subscriber` - A Subscriber that represents the handler of the events.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use banana_coin::event::{DomainEvent, EventBus};
+    /// let event_bus = EventBus::new();
+    /// let names = Arc::new(Mutex::new(Vec::new()));
+    /// let subscriber_names = Arc::clone(&names);
+    /// event_bus.subscribe(move |event: &DomainEvent| subscriber_names.lock().unwrap().push(event.get_name()));
+    /// # assert_eq!(event_bus.get_subscriber_count(), 1)
+    /// ```
+    ///
+    pub fn subscribe<S: Subscriber + 'static>(
+        self: &EventBus,
+        subscriber: S,
+    ) {
+        self.lock().push(Arc::new(subscriber));
+    }
+
+    ///
+    /// Subscribe to the events published from now on through a channel and return its receiver,
+    /// so that the events are handled by the thread owning it.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use banana_coin::event::{DomainEvent, EventBus};
+    /// let event_bus = EventBus::new();
+    /// let receiver = event_bus.subscribe_channel();
+    /// event_bus.publish(&DomainEvent::EntityCreated { entity_id: "entity_001".to_string(), name: "john".to_string(), balances: BTreeMap::new() });
+    /// # assert_eq!(receiver.recv().unwrap().get_name(), "EntityCreated")
+    /// ```
+    ///
+    pub fn subscribe_channel(
+        self: &EventBus,
+    ) -> Receiver<DomainEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribe(ChannelSubscriber::new(sender));
+        receiver
+    }
+
+    ///
+    /// Notify every subscriber of an event.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - A DomainEvent that represents the event to publish.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::collections::BTreeMap;
+    /// # use banana_coin::event::{DomainEvent, EventBus};
+    /// # let event_bus = EventBus::new();
+    /// event_bus.publish(&DomainEvent::EntityCreated { entity_id: "entity_001".to_string(), name: "john".to_string(), balances: BTreeMap::new() });
+    /// ```
+    ///
+    pub fn publish(
+        self: &EventBus,
+        event: &DomainEvent,
+    ) {
+        // The subscribers are notified without holding the lock, so that they can subscribe or publish in turn
+        let subscribers = self.lock().clone();
+        for subscriber in subscribers {
+            subscriber.notify(event);
+        }
+    }
+
+    ///
+    /// Return the number of subscribers of the event bus.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::event::EventBus;
+    /// # let event_bus = EventBus::new();
+    /// let subscriber_count : usize = event_bus.get_subscriber_count();
+    /// # assert_eq!(subscriber_count, 0)
+    /// ```
+    ///
+    pub fn get_subscriber_count(
+        self: &EventBus,
+    ) -> usize {
+        self.lock().len()
+    }
+
+    /// Publish the events of an operation, in order.
+    pub(crate) fn publish_all(
+        self: &EventBus,
+        events: Vec<DomainEvent>,
+    ) {
+        for event in &events {
+            self.publish(event);
+        }
+    }
+
+    /// Lock the subscribers, which stay usable even if a subscriber panicked while they were locked.
+    fn lock(
+        self: &EventBus,
+    ) -> MutexGuard<'_, Vec<Arc<dyn Subscriber>>> {
+        self.subscribers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventBus")
+            .field("subscribers", &self.get_subscriber_count())
+            .finish()
+    }
+}
@@ -0,0 +1,11 @@
+/// Importing domain event module
+pub mod domain_event;
+pub use domain_event::*;
+
+/// Importing subscriber module
+pub mod subscriber;
+pub use subscriber::*;
+
+/// Importing event bus module
+pub mod event_bus;
+pub use event_bus::*;
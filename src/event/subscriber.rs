@@ -0,0 +1,77 @@
+use std::sync::mpsc::Sender;
+
+use crate::event::DomainEvent;
+
+/// Subscriber trait
+/// It is notified of the events published on the event buses it is subscribed to.
+/// The subscribers are notified synchronously, by the thread publishing the event, so they should not block:
+/// a slow subscriber can forward the events to a channel, see 'ChannelSubscriber'.
+/// Any function taking a &DomainEvent is a subscriber.
+pub trait Subscriber: Send + Sync {
+    ///
+    /// Handle an event.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - A DomainEvent that represents the published event.
+    ///
+    fn notify(
+        &self,
+        event: &DomainEvent,
+    );
+}
+
+/// Any function taking an event is a subscriber.
+impl<F> Subscriber for F
+    where F: Fn(&DomainEvent) + Send + Sync {
+    fn notify(
+        &self,
+        event: &DomainEvent,
+    ) {
+        self(event)
+    }
+}
+
+/// Channel subscriber structure
+/// It forwards a copy of every event to a channel, so that the events are handled by the thread owning the receiver.
+/// The events are dropped once the receiver is dropped.
+#[derive(Debug, Clone)]
+pub struct ChannelSubscriber {
+    sender: Sender<DomainEvent>,
+}
+
+/// Channel subscriber implementation.
+impl ChannelSubscriber {
+    ///
+    /// Return a new ChannelSubscriber forwarding the events to the given sender.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - A Sender that represents the channel the events are forwarded to.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::mpsc;
+    /// use banana_coin::event::ChannelSubscriber;
+    /// let (sender, receiver) = mpsc::channel();
+    /// let subscriber = ChannelSubscriber::new(sender);
+    /// ```
+    ///
+    pub fn new(
+        sender: Sender<DomainEvent>,
+    ) -> ChannelSubscriber {
+        ChannelSubscriber {
+            sender,
+        }
+    }
+}
+
+impl Subscriber for ChannelSubscriber {
+    fn notify(
+        &self,
+        event: &DomainEvent,
+    ) {
+        // A dropped receiver is not interested in the events anymore
+        let _ = self.sender.send(event.clone());
+    }
+}
@@ -0,0 +1,1259 @@
+use std::collections::BTreeMap;
+
+use crate::error::LedgerError;
+use crate::event::{balance_changes, entity_created, transaction_changes, DomainEvent, EventBus};
+use crate::ledger::{ESCROW_ACCOUNT_ID, ISSUANCE_ACCOUNT_ID, Journal};
+use crate::model::{Amount, AssetDefinition, AssetRegistry, Entity, Escrow, Posting, ReleaseCondition, Transaction, TransactionState, Wallet};
+use crate::storage::Storage;
+use crate::transfer::TransferExecutor;
+
+/// Ledger structure
+/// It owns the registered entities, identified by their ids, the append-only log of the executed transactions
+/// and the open escrows, whose transactions are Blocked until they are released or refunded.
+/// The coins of the open escrows are held in the wallet of the escrow account, which is not a registered entity.
+/// Every balance change is recorded in a double-entry journal, so that the wallet balances can be reconciled,
+/// and published on the event bus of the ledger along with the new entities and the changes of state of the transactions.
+/// The balances of the wallets and the amounts of the transactions fit the decimal places of the asset registry of the ledger.
+#[derive(Debug, Clone)]
+pub struct Ledger {
+    /// Registered entities, indexed by their ids.
+    entities: BTreeMap<String, Entity>,
+    /// Executed transactions, in order of execution.
+    transactions: Vec<Transaction>,
+    /// Postings of all the balance changes.
+    journal: Journal,
+    /// Executor used to move the coins between the entities.
+    executor: TransferExecutor,
+    /// Id of the registered entity credited with the fees of the transactions, if any.
+    fee_collector_id: Option<String>,
+    /// Open escrows, indexed by the ids of their transactions.
+    escrows: BTreeMap<String, Escrow>,
+    /// Escrow account holding the coins of the open escrows.
+    escrow_account: Entity,
+    /// Event bus the changes are published on.
+    event_bus: EventBus,
+    /// Definitions of the assets the balances and the transactions are checked against.
+    assets: AssetRegistry,
+}
+
+/// Ledger implementation.
+impl Ledger {
+    ///
+    /// Return a new empty Ledger.
+    /// If the executor has an event bus, the ledger takes it over as its own event bus: the executor stops publishing
+    /// and the changes of the transfers are published once, by the ledger.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - A TransferExecutor that represents the executor used to run the transfers.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::ledger::Ledger;
+    /// use banana_coin::transfer::TransferExecutor;
+    /// let ledger = Ledger::new(
+    ///     TransferExecutor::new()
+    /// );
+    /// # assert!(ledger.get_entities().is_empty());
+    /// # assert!(ledger.get_transactions().is_empty())
+    /// ```
+    ///
+    pub fn new(
+        mut executor: TransferExecutor,
+    ) -> Ledger {
+        let event_bus = executor.take_event_bus().unwrap_or_default();
+        Ledger {
+            entities: BTreeMap::new(),
+            transactions: Vec::new(),
+            journal: Journal::new(),
+            executor,
+            fee_collector_id: None,
+            escrows: BTreeMap::new(),
+            escrow_account: Entity::new(ESCROW_ACCOUNT_ID.to_string(), "escrow".to_string(), Wallet::new(Amount::from_integer(0))),
+            event_bus,
+            assets: AssetRegistry::new(),
+        }
+    }
+
+    ///
+    /// Set the registered entity credited with the fees of the transactions executed from now on.
+    /// Until a fee collector is set, transactions with a fee are refused.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A String that represents the id of the fee collector entity.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// let mut ledger = Ledger::new(TransferExecutor::new());
+    /// ledger.register_entity(Entity::new("entity_001".to_string(), "bank".to_string(), Wallet::new(Amount::from_integer(0)))).unwrap();
+    /// ledger.set_fee_collector("entity_001".to_string()).unwrap();
+    /// # assert_eq!(ledger.get_fee_collector(), Some("entity_001"))
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An EntityNotFoundError is returned if no entity with the given id is registered.
+    ///
+    pub fn set_fee_collector(
+        self: &mut Ledger,
+        entity_id: String,
+    ) -> Result<(), LedgerError> {
+        self.find_entity(&entity_id)?;
+        self.fee_collector_id = Some(entity_id);
+        Ok(())
+    }
+
+    ///
+    /// Return the id of the entity credited with the fees of the transactions, if any.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let ledger = Ledger::new(TransferExecutor::new());
+    /// let fee_collector_id : Option<&str> = ledger.get_fee_collector();
+    /// # assert!(fee_collector_id.is_none())
+    /// ```
+    ///
+    pub fn get_fee_collector(
+        self: &Ledger,
+    ) -> Option<&str> {
+        self.fee_collector_id.as_deref()
+    }
+
+    ///
+    /// Set the event bus the changes of the ledger are published on.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_bus` - An EventBus that represents the bus the changes are published on.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::event::EventBus;
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// let mut ledger = Ledger::new(TransferExecutor::new());
+    /// let event_bus = EventBus::new();
+    /// let receiver = event_bus.subscribe_channel();
+    /// ledger.set_event_bus(event_bus);
+    /// ledger.register_entity(Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(0)))).unwrap();
+    /// # assert_eq!(receiver.try_recv().unwrap().get_name(), "EntityCreated")
+    /// ```
+    ///
+    pub fn set_event_bus(
+        self: &mut Ledger,
+        event_bus: EventBus,
+    ) {
+        self.event_bus = event_bus;
+    }
+
+    ///
+    /// Return the event bus the changes of the ledger are published on. The returned bus shares its subscribers
+    /// with the bus of the ledger.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let ledger = Ledger::new(TransferExecutor::new());
+    /// let receiver = ledger.get_event_bus().subscribe_channel();
+    /// # assert_eq!(ledger.get_event_bus().get_subscriber_count(), 1)
+    /// ```
+    ///
+    pub fn get_event_bus(
+        self: &Ledger,
+    ) -> EventBus {
+        self.event_bus.clone()
+    }
+
+    ///
+    /// Define an asset in the asset registry of the ledger: the balances of the wallets and the amounts of the transactions
+    /// in the asset can not have more decimal places than the definition. Defining again an asset with the same decimal places
+    /// does nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `definition` - An AssetDefinition that represents the asset.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::AssetDefinition;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// let mut ledger = Ledger::new(TransferExecutor::new());
+    /// let result = ledger.define_asset(AssetDefinition::new("EUR".to_string(), 2).unwrap());
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(ledger.get_asset_registry().get_definition("EUR").get_decimals(), 2);
+    /// # assert!(ledger.define_asset(AssetDefinition::new("EUR".to_string(), 3).unwrap()).is_err())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An AssetDefinitionError is returned if the asset is already defined with other decimal places
+    /// and an AssetPrecisionError if the balance of the asset of a wallet does not fit the decimal places.
+    ///
+    pub fn define_asset(
+        self: &mut Ledger,
+        definition: AssetDefinition,
+    ) -> Result<(), LedgerError> {
+        let mut assets = self.assets.clone();
+        assets.define(definition).map_err(|error| LedgerError::AssetDefinitionError { error })?;
+        for entity in self.entities.values().chain(Some(&self.escrow_account)) {
+            Ledger::check_balance_precision(&assets, entity)?;
+        }
+        self.assets = assets;
+        Ok(())
+    }
+
+    ///
+    /// Return a copy of the asset registry of the ledger.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::{AssetRegistry, DEFAULT_ASSET, DEFAULT_ASSET_DECIMALS};
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let ledger = Ledger::new(TransferExecutor::new());
+    /// let registry : AssetRegistry = ledger.get_asset_registry();
+    /// # assert_eq!(registry.get_definition(DEFAULT_ASSET).get_decimals(), DEFAULT_ASSET_DECIMALS)
+    /// ```
+    ///
+    pub fn get_asset_registry(
+        self: &Ledger,
+    ) -> AssetRegistry {
+        self.assets.clone()
+    }
+
+    ///
+    /// Register an entity in the ledger.
+    /// Every non-zero balance of its wallet is recorded in the journal as an opening balance of its asset
+    /// against the issuance account, tied to the transaction id 'opening_<entity id>'.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity` - An Entity that represents the entity to register.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// let mut ledger = Ledger::new(TransferExecutor::new());
+    /// let result = ledger.register_entity(Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(0))));
+    /// # assert!(result.is_ok());
+    /// # assert!(ledger.register_entity(Entity::new("entity_001".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)))).is_err())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A DuplicateEntityError is returned if an entity with the same id is already registered,
+    /// a ReservedEntityIdError if the id is the one of the issuance account or of the escrow account
+    /// and an AssetPrecisionError if a balance of its wallet does not fit the asset registry of the ledger.
+    ///
+    pub fn register_entity(
+        self: &mut Ledger,
+        entity: Entity,
+    ) -> Result<(), LedgerError> {
+        let entity_id = entity.get_id().to_string();
+        if entity_id == ISSUANCE_ACCOUNT_ID || entity_id == ESCROW_ACCOUNT_ID {
+            return Err(LedgerError::ReservedEntityIdError { entity_id });
+        }
+        if self.entities.contains_key(&entity_id) {
+            return Err(LedgerError::DuplicateEntityError { entity_id });
+        }
+        Ledger::check_balance_precision(&self.assets, &entity)?;
+        let transaction_id = format!("opening_{}", entity_id);
+        for (asset, balance) in entity.get_wallet().get_balances() {
+            let result = if balance.is_zero() {
+                Ok(())
+            } else if !balance.is_negative() {
+                self.journal.record_asset(
+                    transaction_id.clone(), asset, ISSUANCE_ACCOUNT_ID.to_string(), entity_id.clone(), balance)
+            } else {
+                // A negative opening balance is recorded as coins moved from the entity to the issuance account
+                let opposite = balance.checked_neg().unwrap_or(balance);
+                self.journal.record_asset(
+                    transaction_id.clone(), asset, entity_id.clone(), ISSUANCE_ACCOUNT_ID.to_string(), opposite)
+            };
+            result.map_err(|error| LedgerError::JournalOperationError { error })?;
+        }
+        self.event_bus.publish(&entity_created(&entity));
+        self.entities.insert(entity_id, entity);
+        Ok(())
+    }
+
+    ///
+    /// Return a copy of the entity identified by the given id, if it is registered.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A str that represents the id of the entity.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let mut ledger = Ledger::new(TransferExecutor::new());
+    /// # ledger.register_entity(Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(0)))).unwrap();
+    /// let entity : Option<Entity> = ledger.get_entity("entity_001");
+    /// # assert_eq!(entity.unwrap().get_name(), "john")
+    /// ```
+    ///
+    pub fn get_entity(
+        self: &Ledger,
+        entity_id: &str,
+    ) -> Option<Entity> {
+        self.entities.get(entity_id).cloned()
+    }
+
+    ///
+    /// Return a copy of all the registered entities, ordered by id.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::Entity;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let ledger = Ledger::new(TransferExecutor::new());
+    /// let entities : Vec<Entity> = ledger.get_entities();
+    /// # assert!(entities.is_empty())
+    /// ```
+    ///
+    pub fn get_entities(
+        self: &Ledger,
+    ) -> Vec<Entity> {
+        self.entities.values().cloned().collect()
+    }
+
+    ///
+    /// Execute a transaction between two registered entities and append it to the transaction log.
+    /// The transaction is recorded in the log whether it completes or fails;
+    /// it is not recorded if it can not be executed at all, e.g. if it is given in a final state.
+    /// The fee of the transaction, if any, is credited to the fee collector of the ledger.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - A Transaction in the Created state that represents the transfer to execute.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the state changes.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::{Amount, Entity, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// let mut ledger = Ledger::new(TransferExecutor::new());
+    /// ledger.register_entity(Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)))).unwrap();
+    /// ledger.register_entity(Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)))).unwrap();
+    /// let transaction = Transaction::new(
+    ///     "id_0001".to_string(),
+    ///     "entity_001".to_string(),
+    ///     "entity_002".to_string(),
+    ///     Amount::from_integer(100),
+    ///     "Moving 100 coins.".to_string(),
+    ///     Created {timestamp: 1},
+    ///     vec![Created {timestamp: 1}]
+    /// ).unwrap();
+    /// let result = ledger.execute_transaction(transaction, 2);
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(ledger.get_entity("entity_002").unwrap().get_wallet().get_balance(), Amount::from_integer(100));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A LedgerError is returned if the transaction id is already recorded, if its amount or fee does not fit
+    /// the asset registry of the ledger, if one of the entities is not registered or if the transfer fails.
+    ///
+    pub fn execute_transaction(
+        self: &mut Ledger,
+        mut transaction: Transaction,
+        timestamp: u64,
+    ) -> Result<(), LedgerError> {
+        if self.get_transaction(&transaction.get_id()).is_some() || self.escrows.contains_key(&transaction.get_id()) {
+            return Err(LedgerError::DuplicateTransactionError {
+                transaction_id: transaction.get_id(),
+            });
+        }
+        transaction
+            .check_precision(&self.assets)
+            .map_err(|error| LedgerError::InvalidTransactionError { transaction_id: transaction.get_id(), error })?;
+        let mut from_entity = self.find_entity(&transaction.get_from_entity_id())?;
+        let mut to_entity = self.find_entity(&transaction.get_to_entity_id())?;
+        let seen_states = transaction.get_state_history().len();
+
+        let mut fee_collector = match &self.fee_collector_id {
+            Some(fee_collector_id) => Some(self.find_entity(fee_collector_id)?),
+            None => None,
+        };
+        let result = match fee_collector.as_mut() {
+            Some(fee_collector) => self.executor
+                .execute_with_fee(&mut transaction, &mut from_entity, &mut to_entity, fee_collector, timestamp),
+            None => self.executor.execute(&mut transaction, &mut from_entity, &mut to_entity, timestamp),
+        };
+        // Only a transaction moved to its last state by this call is recorded: a transaction given in a final state
+        // is rejected by the executor and must not enter the log as if it had been executed
+        let executed = transaction.get_state_history().len() > seen_states && transaction.get_current_state().is_final();
+        let mut events = Vec::new();
+        if result.is_ok() {
+            // The transfer and the fee are recorded together, so that a rejected posting leaves no half of the transaction
+            let mut changes = vec![(transaction.get_from_entity_id(), transaction.get_to_entity_id(), transaction.get_amount())];
+            let fee = transaction.get_fee().unwrap_or_default();
+            if let Some(fee_collector) = fee_collector.as_ref() {
+                if !fee.is_zero() && fee_collector.get_id() != from_entity.get_id() {
+                    changes.push((transaction.get_from_entity_id(), fee_collector.get_id().to_string(), fee));
+                }
+            }
+            self.journal
+                .record_asset_all(transaction.get_id(), transaction.get_asset(), &changes)
+                .map_err(|error| LedgerError::JournalOperationError { error })?;
+            if let Some(fee_collector) = fee_collector {
+                if fee_collector.get_id() != from_entity.get_id() && fee_collector.get_id() != to_entity.get_id() {
+                    events.extend(self.store_entity(fee_collector, &transaction, timestamp));
+                }
+            }
+            events.extend(self.store_entity(from_entity, &transaction, timestamp));
+            events.extend(self.store_entity(to_entity, &transaction, timestamp));
+        } else if executed {
+            // A failed transaction still consumes the nonce of the sender
+            self.store_entity(from_entity, &transaction, timestamp);
+        }
+        if executed {
+            self.event_bus.publish_all(transaction_changes(&transaction, seen_states, events));
+            self.transactions.push(transaction);
+        }
+        result.map_err(|error| LedgerError::TransferOperationError { error: Box::new(error) })
+    }
+
+    ///
+    /// Execute a transaction between two registered entities as an escrow: its coins leave the sender for the escrow
+    /// account and the transaction stays in the Blocked state until the escrow is released or refunded.
+    /// The coins are recorded in the journal as moved from the sender to the escrow account.
+    /// If the coins can not be removed from the sender, the failed transaction is appended to the transaction log.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - A Transaction in the Created state that represents the transfer to execute.
+    /// * `condition` - A ReleaseCondition that represents what must happen for the coins to be released.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the state changes.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::{Amount, Entity, ReleaseCondition, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// let mut ledger = Ledger::new(TransferExecutor::new());
+    /// ledger.register_entity(Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)))).unwrap();
+    /// ledger.register_entity(Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)))).unwrap();
+    /// let transaction = Transaction::new(
+    ///     "id_0001".to_string(),
+    ///     "entity_001".to_string(),
+    ///     "entity_002".to_string(),
+    ///     Amount::from_integer(100),
+    ///     "Moving 100 coins.".to_string(),
+    ///     Created {timestamp: 1},
+    ///     vec![Created {timestamp: 1}]
+    /// ).unwrap();
+    /// let result = ledger.open_escrow(transaction, ReleaseCondition::Deadline { deadline: 3600 }, 2);
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(ledger.get_entity("entity_001").unwrap().get_wallet().get_balance(), Amount::from_integer(0));
+    /// # assert_eq!(ledger.get_escrows().len(), 1);
+    /// # assert_eq!(ledger.get_escrow_account().get_wallet().get_balance(), Amount::from_integer(100));
+    /// # assert!(ledger.reconcile().is_ok())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A LedgerError is returned if the transaction id is already recorded, if its amount does not fit
+    /// the asset registry of the ledger, if one of the entities is not registered or if the coins can not be moved
+    /// to the escrow account.
+    ///
+    pub fn open_escrow(
+        self: &mut Ledger,
+        mut transaction: Transaction,
+        condition: ReleaseCondition,
+        timestamp: u64,
+    ) -> Result<(), LedgerError> {
+        if self.get_transaction(&transaction.get_id()).is_some() || self.escrows.contains_key(&transaction.get_id()) {
+            return Err(LedgerError::DuplicateTransactionError {
+                transaction_id: transaction.get_id(),
+            });
+        }
+        transaction
+            .check_precision(&self.assets)
+            .map_err(|error| LedgerError::InvalidTransactionError { transaction_id: transaction.get_id(), error })?;
+        let mut from_entity = self.find_entity(&transaction.get_from_entity_id())?;
+        self.find_entity(&transaction.get_to_entity_id())?;
+        let mut escrow_account = self.escrow_account.clone();
+        let seen_states = transaction.get_state_history().len();
+
+        let result = self.executor.execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, condition, timestamp);
+        match result {
+            Ok(escrow) => {
+                self.journal
+                    .record_asset(
+                        transaction.get_id(),
+                        transaction.get_asset(),
+                        transaction.get_from_entity_id(),
+                        ESCROW_ACCOUNT_ID.to_string(),
+                        transaction.get_amount(),
+                    )
+                    .map_err(|error| LedgerError::JournalOperationError { error })?;
+                let mut events = self.store_entity(from_entity, &transaction, timestamp);
+                events.extend(self.store_escrow_account(escrow_account, &transaction, timestamp));
+                self.event_bus.publish_all(transaction_changes(&transaction, seen_states, events));
+                self.escrows.insert(transaction.get_id(), escrow);
+                Ok(())
+            }
+            Err(error) => {
+                // A transaction given in a final state is rejected without being recorded, see 'execute_transaction'
+                if transaction.get_state_history().len() > seen_states && transaction.get_current_state().is_final() {
+                    // A failed transaction still consumes the nonce of the sender
+                    self.store_entity(from_entity, &transaction, timestamp);
+                    self.event_bus.publish_all(transaction_changes(&transaction, seen_states, Vec::new()));
+                    self.transactions.push(transaction);
+                }
+                Err(LedgerError::TransferOperationError { error: Box::new(error) })
+            }
+        }
+    }
+
+    ///
+    /// Record the approval of the release of an open escrow by the given registered entity,
+    /// signed with its registered public key.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_id` - A str that represents the id of the transaction of the escrow.
+    /// * `entity_id` - A str that represents the id of the approving entity.
+    /// * `signature` - A str that represents the signature of the approval by the entity, hex encoded, see 'Escrow::sign_approval'.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the approval.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::crypto::KeyPair;
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::{Amount, Entity, ReleaseCondition, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let mut ledger = Ledger::new(TransferExecutor::new());
+    /// let key_pair = KeyPair::generate();
+    /// let mut entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// entity.set_public_key(key_pair.get_public_key());
+    /// ledger.register_entity(entity).unwrap();
+    /// # ledger.register_entity(Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)))).unwrap();
+    /// # let mut transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// # transaction.sign(&key_pair);
+    /// # ledger.open_escrow(transaction, ReleaseCondition::BothParties, 2).unwrap();
+    /// let signature = ledger.get_escrow("id_0001").unwrap().sign_approval("entity_001", &key_pair);
+    /// let result = ledger.approve_escrow("id_0001", "entity_001", &signature, 3);
+    /// # assert!(result.is_ok());
+    /// # assert!(ledger.approve_escrow("id_0001", "entity_002", &signature, 3).is_err());
+    /// # assert!(ledger.approve_escrow("id_0001", "entity_003", &signature, 3).is_err())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An EscrowNotFoundError is returned if no escrow of the transaction is open, an EntityNotFoundError
+    /// if the entity is not registered and an EscrowOperationError if the entity can not approve the release
+    /// or if the approval is not signed with its registered key.
+    ///
+    pub fn approve_escrow(
+        self: &mut Ledger,
+        transaction_id: &str,
+        entity_id: &str,
+        signature: &str,
+        timestamp: u64,
+    ) -> Result<(), LedgerError> {
+        let escrow = self.escrows
+            .get_mut(transaction_id)
+            .ok_or_else(|| LedgerError::EscrowNotFoundError { transaction_id: transaction_id.to_string() })?;
+        let approver = self.entities
+            .get(entity_id)
+            .ok_or_else(|| LedgerError::EntityNotFoundError { entity_id: entity_id.to_string() })?;
+        escrow
+            .approve(approver, signature, timestamp)
+            .map_err(|error| LedgerError::EscrowOperationError { error })
+    }
+
+    ///
+    /// Release the coins of an open escrow to the receiver of its transaction, once its release condition is met.
+    /// The completed transaction is appended to the transaction log and the escrow is closed.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_id` - A str that represents the id of the transaction of the escrow.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the state change.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::{Amount, Entity, ReleaseCondition, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let mut ledger = Ledger::new(TransferExecutor::new());
+    /// # ledger.register_entity(Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)))).unwrap();
+    /// # ledger.register_entity(Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)))).unwrap();
+    /// # let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// # ledger.open_escrow(transaction, ReleaseCondition::Deadline { deadline: 3600 }, 2).unwrap();
+    /// let result = ledger.release_escrow("id_0001", 3600);
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(ledger.get_entity("entity_002").unwrap().get_wallet().get_balance(), Amount::from_integer(100));
+    /// # assert_eq!(ledger.get_transaction("id_0001").unwrap().get_current_state(), Completed {timestamp: 3600});
+    /// # assert!(ledger.reconcile().is_ok())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An EscrowNotFoundError is returned if no escrow of the transaction is open,
+    /// a TransferOperationError if the release condition is not met or the coins can not be released.
+    ///
+    pub fn release_escrow(
+        self: &mut Ledger,
+        transaction_id: &str,
+        timestamp: u64,
+    ) -> Result<(), LedgerError> {
+        let mut escrow = self.find_escrow(transaction_id)?;
+        let mut escrow_account = self.escrow_account.clone();
+        let mut to_entity = self.find_entity(&escrow.get_transaction().get_to_entity_id())?;
+        let seen_states = escrow.get_transaction().get_state_history().len();
+        self.executor
+            .release_escrow(&mut escrow, &mut escrow_account, &mut to_entity, timestamp)
+            .map_err(|error| LedgerError::TransferOperationError { error: Box::new(error) })?;
+        self.close_escrow(escrow, escrow_account, to_entity, seen_states, timestamp)
+    }
+
+    ///
+    /// Refund the coins of an open escrow to the sender of its transaction, on request of the receiver
+    /// or of the approver of an Approval condition.
+    /// The failed transaction is appended to the transaction log and the escrow is closed.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_id` - A str that represents the id of the transaction of the escrow.
+    /// * `requested_by` - A str that represents the id of the entity requesting the refund.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the state change.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::{Amount, Entity, ReleaseCondition, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let mut ledger = Ledger::new(TransferExecutor::new());
+    /// # ledger.register_entity(Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)))).unwrap();
+    /// # ledger.register_entity(Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)))).unwrap();
+    /// # let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// # let condition = ReleaseCondition::Approval { approver_id: "entity_003".to_string() };
+    /// # ledger.open_escrow(transaction, condition, 2).unwrap();
+    /// let result = ledger.refund_escrow("id_0001", "entity_003", 3);
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(ledger.get_entity("entity_001").unwrap().get_wallet().get_balance(), Amount::from_integer(100));
+    /// # assert_eq!(ledger.get_transaction("id_0001").unwrap().get_current_state(), Failed {timestamp: 3});
+    /// # assert!(ledger.reconcile().is_ok())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An EscrowNotFoundError is returned if no escrow of the transaction is open,
+    /// a TransferOperationError if the entity can not request the refund or the coins can not be refunded.
+    ///
+    pub fn refund_escrow(
+        self: &mut Ledger,
+        transaction_id: &str,
+        requested_by: &str,
+        timestamp: u64,
+    ) -> Result<(), LedgerError> {
+        let mut escrow = self.find_escrow(transaction_id)?;
+        let mut escrow_account = self.escrow_account.clone();
+        let mut from_entity = self.find_entity(&escrow.get_transaction().get_from_entity_id())?;
+        let seen_states = escrow.get_transaction().get_state_history().len();
+        self.executor
+            .refund_escrow(&mut escrow, &mut escrow_account, &mut from_entity, requested_by, timestamp)
+            .map_err(|error| LedgerError::TransferOperationError { error: Box::new(error) })?;
+        self.close_escrow(escrow, escrow_account, from_entity, seen_states, timestamp)
+    }
+
+    ///
+    /// Return a copy of the open escrow of the transaction identified by the given id, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_id` - A str that represents the id of the transaction of the escrow.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::Escrow;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let ledger = Ledger::new(TransferExecutor::new());
+    /// let escrow : Option<Escrow> = ledger.get_escrow("id_0001");
+    /// # assert!(escrow.is_none())
+    /// ```
+    ///
+    pub fn get_escrow(
+        self: &Ledger,
+        transaction_id: &str,
+    ) -> Option<Escrow> {
+        self.escrows.get(transaction_id).cloned()
+    }
+
+    ///
+    /// Return a copy of all the open escrows, ordered by transaction id.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::Escrow;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let ledger = Ledger::new(TransferExecutor::new());
+    /// let escrows : Vec<Escrow> = ledger.get_escrows();
+    /// # assert!(escrows.is_empty())
+    /// ```
+    ///
+    pub fn get_escrows(
+        self: &Ledger,
+    ) -> Vec<Escrow> {
+        self.escrows.values().cloned().collect()
+    }
+
+    ///
+    /// Save the open escrows in the given storage and remove the stored escrows that have been released or refunded since,
+    /// so that the coins held by the escrow account are not lost when the ledger is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - A Storage that represents the backend the escrows are saved in.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::{Amount, Entity, ReleaseCondition, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::storage::{MemoryStorage, Storage};
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let mut ledger = Ledger::new(TransferExecutor::new());
+    /// # ledger.register_entity(Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)))).unwrap();
+    /// # ledger.register_entity(Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)))).unwrap();
+    /// # let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// # ledger.open_escrow(transaction, ReleaseCondition::Deadline { deadline: 3600 }, 2).unwrap();
+    /// let mut storage = MemoryStorage::new();
+    /// let result = ledger.save_escrows(&mut storage);
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(storage.list_escrows().unwrap().len(), 1);
+    /// # ledger.release_escrow("id_0001", 3600).unwrap();
+    /// # ledger.save_escrows(&mut storage).unwrap();
+    /// # assert!(storage.list_escrows().unwrap().is_empty())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A StorageOperationError is returned if an escrow can not be saved or removed.
+    ///
+    pub fn save_escrows<S: Storage>(
+        self: &Ledger,
+        storage: &mut S,
+    ) -> Result<(), LedgerError> {
+        let stored_escrows = storage
+            .list_escrows()
+            .map_err(|error| LedgerError::StorageOperationError { error })?;
+        for stored_escrow in stored_escrows {
+            let transaction_id = stored_escrow.get_transaction().get_id();
+            if !self.escrows.contains_key(&transaction_id) {
+                storage
+                    .remove_escrow(&transaction_id)
+                    .map_err(|error| LedgerError::StorageOperationError { error })?;
+            }
+        }
+        for escrow in self.escrows.values() {
+            storage
+                .save_escrow(escrow)
+                .map_err(|error| LedgerError::StorageOperationError { error })?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Load the escrows saved in the given storage as the open escrows of the ledger, whose entities must already be registered.
+    /// The wallet of the escrow account is rebuilt from the escrows and its balances are recorded in the journal
+    /// as opening balances against the issuance account, tied to the transaction id 'opening_<escrow account id>',
+    /// as the balances of a registered entity are.
+    ///
+    /// # Arguments
+    ///
+    /// * `storage` - A Storage that represents the backend the escrows are loaded from.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::{Amount, Entity, ReleaseCondition, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::storage::MemoryStorage;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let mut ledger = Ledger::new(TransferExecutor::new());
+    /// # ledger.register_entity(Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)))).unwrap();
+    /// # ledger.register_entity(Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)))).unwrap();
+    /// # let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// # ledger.open_escrow(transaction, ReleaseCondition::Deadline { deadline: 3600 }, 2).unwrap();
+    /// # let mut storage = MemoryStorage::new();
+    /// # ledger.save_escrows(&mut storage).unwrap();
+    /// let mut restarted = Ledger::new(TransferExecutor::new());
+    /// # for entity in ledger.get_entities() { restarted.register_entity(entity).unwrap(); }
+    /// let result = restarted.load_escrows(&storage);
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(restarted.get_escrow_account().get_wallet().get_balance(), Amount::from_integer(100));
+    /// # assert!(restarted.reconcile().is_ok());
+    /// # assert!(restarted.load_escrows(&storage).is_err());
+    /// # assert!(restarted.release_escrow("id_0001", 3600).is_ok());
+    /// # assert!(restarted.load_escrows(&storage).is_err())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An EscrowsAlreadyOpenError is returned if escrows are already open in the ledger, a StorageOperationError
+    /// if the escrows can not be loaded, a DuplicateTransactionError if the transaction of an escrow is already recorded,
+    /// an InvalidTransactionError if its amount does not fit the asset registry of the ledger, an EntityNotFoundError
+    /// if one of its entities is not registered and an EscrowAccountOperationError or a JournalOperationError
+    /// if the escrowed coins are out of range. Nothing is loaded if an error is returned.
+    ///
+    pub fn load_escrows<S: Storage>(
+        self: &mut Ledger,
+        storage: &S,
+    ) -> Result<(), LedgerError> {
+        if !self.escrows.is_empty() {
+            return Err(LedgerError::EscrowsAlreadyOpenError { count: self.escrows.len() });
+        }
+        let stored_escrows = storage
+            .list_escrows()
+            .map_err(|error| LedgerError::StorageOperationError { error })?;
+        let mut escrows = BTreeMap::new();
+        let mut escrow_account = self.escrow_account.clone();
+        let mut journal = self.journal.clone();
+        let opening_id = format!("opening_{}", ESCROW_ACCOUNT_ID);
+        for escrow in stored_escrows {
+            let transaction = escrow.get_transaction();
+            if self.get_transaction(&transaction.get_id()).is_some() || escrows.contains_key(&transaction.get_id()) {
+                return Err(LedgerError::DuplicateTransactionError {
+                    transaction_id: transaction.get_id(),
+                });
+            }
+            transaction
+                .check_precision(&self.assets)
+                .map_err(|error| LedgerError::InvalidTransactionError { transaction_id: transaction.get_id(), error })?;
+            self.find_entity(&transaction.get_from_entity_id())?;
+            self.find_entity(&transaction.get_to_entity_id())?;
+            escrow_account
+                .add_asset_coins(&transaction.get_asset(), transaction.get_amount())
+                .map_err(|error| LedgerError::EscrowAccountOperationError { error })?;
+            journal
+                .record_asset(
+                    opening_id.clone(),
+                    transaction.get_asset(),
+                    ISSUANCE_ACCOUNT_ID.to_string(),
+                    ESCROW_ACCOUNT_ID.to_string(),
+                    transaction.get_amount(),
+                )
+                .map_err(|error| LedgerError::JournalOperationError { error })?;
+            escrows.insert(transaction.get_id(), escrow);
+        }
+        self.escrow_account = escrow_account;
+        self.journal = journal;
+        self.escrows = escrows;
+        Ok(())
+    }
+
+    ///
+    /// Return a copy of the escrow account, whose wallet holds the coins of the open escrows.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::{ESCROW_ACCOUNT_ID, Ledger};
+    /// # use banana_coin::model::Entity;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let ledger = Ledger::new(TransferExecutor::new());
+    /// let escrow_account : Entity = ledger.get_escrow_account();
+    /// # assert_eq!(escrow_account.get_id(), ESCROW_ACCOUNT_ID);
+    /// # assert!(escrow_account.get_wallet().get_balance().is_zero())
+    /// ```
+    ///
+    pub fn get_escrow_account(
+        self: &Ledger,
+    ) -> Entity {
+        self.escrow_account.clone()
+    }
+
+    ///
+    /// Return a copy of the transaction identified by the given id, if it is recorded.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_id` - A str that represents the id of the transaction.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::Transaction;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let ledger = Ledger::new(TransferExecutor::new());
+    /// let transaction : Option<Transaction> = ledger.get_transaction("id_0001");
+    /// # assert!(transaction.is_none())
+    /// ```
+    ///
+    pub fn get_transaction(
+        self: &Ledger,
+        transaction_id: &str,
+    ) -> Option<Transaction> {
+        self.transactions
+            .iter()
+            .find(|transaction| transaction.get_id() == transaction_id)
+            .cloned()
+    }
+
+    ///
+    /// Return a copy of the transaction log, in order of execution.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::Transaction;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let ledger = Ledger::new(TransferExecutor::new());
+    /// let transactions : Vec<Transaction> = ledger.get_transactions();
+    /// # assert!(transactions.is_empty())
+    /// ```
+    ///
+    pub fn get_transactions(
+        self: &Ledger,
+    ) -> Vec<Transaction> {
+        self.transactions.clone()
+    }
+
+    ///
+    /// Return a copy of the recorded transactions from or to the given entity, in order of execution.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A str that represents the id of the entity.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::Transaction;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let ledger = Ledger::new(TransferExecutor::new());
+    /// let transactions : Vec<Transaction> = ledger.get_transactions_by_entity("entity_001");
+    /// # assert!(transactions.is_empty())
+    /// ```
+    ///
+    pub fn get_transactions_by_entity(
+        self: &Ledger,
+        entity_id: &str,
+    ) -> Vec<Transaction> {
+        self.filter_transactions(|transaction| {
+            transaction.get_from_entity_id() == entity_id || transaction.get_to_entity_id() == entity_id
+        })
+    }
+
+    ///
+    /// Return a copy of the recorded transactions whose current state is the same kind of the given state,
+    /// in order of execution. The timestamp of the given state is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - A TransactionState that represents the kind of state to look for.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::Transaction;
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let ledger = Ledger::new(TransferExecutor::new());
+    /// let transactions : Vec<Transaction> = ledger.get_transactions_by_state(&Failed {timestamp: 0});
+    /// # assert!(transactions.is_empty())
+    /// ```
+    ///
+    pub fn get_transactions_by_state(
+        self: &Ledger,
+        state: &TransactionState,
+    ) -> Vec<Transaction> {
+        self.filter_transactions(|transaction| transaction.get_current_state().is_same_state(state))
+    }
+
+    ///
+    /// Return a copy of the recorded transactions created between the two given timestamps (both included),
+    /// in order of execution.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_timestamp` - A u64 integer that represents the beginning of the time range.
+    /// * `to_timestamp` - A u64 integer that represents the end of the time range.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::Transaction;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let ledger = Ledger::new(TransferExecutor::new());
+    /// let transactions : Vec<Transaction> = ledger.get_transactions_by_time_range(1, 10);
+    /// # assert!(transactions.is_empty())
+    /// ```
+    ///
+    pub fn get_transactions_by_time_range(
+        self: &Ledger,
+        from_timestamp: u64,
+        to_timestamp: u64,
+    ) -> Vec<Transaction> {
+        self.filter_transactions(|transaction| {
+            let timestamp = transaction.get_creation_timestamp();
+            from_timestamp <= timestamp && timestamp <= to_timestamp
+        })
+    }
+
+    ///
+    /// Return a copy of the postings of the journal, in order of recording.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::{Amount, Entity, Posting, Wallet};
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// let mut ledger = Ledger::new(TransferExecutor::new());
+    /// ledger.register_entity(Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)))).unwrap();
+    /// let postings : Vec<Posting> = ledger.get_postings();
+    /// # assert_eq!(postings.len(), 2)
+    /// ```
+    ///
+    pub fn get_postings(
+        self: &Ledger,
+    ) -> Vec<Posting> {
+        self.journal.get_postings()
+    }
+
+    ///
+    /// Return the balance of the given entity in the default asset derived from the postings of the journal.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A str that represents the id of the entity.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// let mut ledger = Ledger::new(TransferExecutor::new());
+    /// ledger.register_entity(Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)))).unwrap();
+    /// let balance : Amount = ledger.get_derived_balance("entity_001").unwrap();
+    /// # assert_eq!(balance, Amount::from_integer(100))
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A JournalOperationError is returned if the sum of the postings is out of range.
+    ///
+    pub fn get_derived_balance(
+        self: &Ledger,
+        entity_id: &str,
+    ) -> Result<Amount, LedgerError> {
+        self.journal
+            .get_balance(entity_id)
+            .map_err(|error| LedgerError::JournalOperationError { error })
+    }
+
+    ///
+    /// Return the balance of the given entity in the given asset derived from the postings of the journal.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A str that represents the id of the entity.
+    /// * `asset` - A str that represents the code of the asset.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// let mut ledger = Ledger::new(TransferExecutor::new());
+    /// let mut wallet = Wallet::new(Amount::from_integer(0));
+    /// wallet.add_asset_coins("GOLD", Amount::from_integer(5)).unwrap();
+    /// ledger.register_entity(Entity::new("entity_001".to_string(), "john".to_string(), wallet)).unwrap();
+    /// let balance : Amount = ledger.get_derived_asset_balance("entity_001", "GOLD").unwrap();
+    /// # assert_eq!(balance, Amount::from_integer(5))
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A JournalOperationError is returned if the sum of the postings is out of range.
+    ///
+    pub fn get_derived_asset_balance(
+        self: &Ledger,
+        entity_id: &str,
+        asset: &str,
+    ) -> Result<Amount, LedgerError> {
+        self.journal
+            .get_asset_balance(entity_id, asset)
+            .map_err(|error| LedgerError::JournalOperationError { error })
+    }
+
+    ///
+    /// Check that the journal is balanced and that the balance of every asset of every wallet matches the balance
+    /// derived from the postings of the journal, the wallet of the escrow account included.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Ledger;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let ledger = Ledger::new(TransferExecutor::new());
+    /// let result = ledger.reconcile();
+    /// # assert!(result.is_ok())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A JournalOperationError is returned if the journal is not balanced or if a sum of postings is out of range,
+    /// a BalanceMismatchError for the first entity and asset whose wallet balance does not match the derived one.
+    ///
+    pub fn reconcile(
+        self: &Ledger,
+    ) -> Result<(), LedgerError> {
+        self.journal
+            .check_trial_balance()
+            .map_err(|error| LedgerError::JournalOperationError { error })?;
+        for entity in self.entities.values().chain(std::iter::once(&self.escrow_account)) {
+            let wallet = entity.get_wallet();
+            let mut assets: Vec<String> = wallet.get_balances().into_keys().collect();
+            assets.extend(self.journal.get_assets(entity.get_id()));
+            assets.sort();
+            assets.dedup();
+            for asset in assets {
+                let wallet_balance = wallet.get_asset_balance(&asset);
+                let derived_balance = self.get_derived_asset_balance(entity.get_id(), &asset)?;
+                if wallet_balance != derived_balance {
+                    return Err(LedgerError::BalanceMismatchError {
+                        entity_id: entity.get_id().to_string(),
+                        asset,
+                        wallet_balance,
+                        derived_balance,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that every balance of the wallet of an entity fits the decimal places of its asset in the given registry.
+    fn check_balance_precision(
+        assets: &AssetRegistry,
+        entity: &Entity,
+    ) -> Result<(), LedgerError> {
+        for (asset, balance) in entity.get_wallet().get_balances() {
+            let definition = assets.get_definition(&asset);
+            if !definition.fits(balance) {
+                return Err(LedgerError::AssetPrecisionError {
+                    entity_id: entity.get_id().to_string(),
+                    asset,
+                    amount: balance,
+                    decimals: definition.get_decimals(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Return a copy of the registered entity identified by the given id, or an EntityNotFoundError.
+    fn find_entity(
+        self: &Ledger,
+        entity_id: &str,
+    ) -> Result<Entity, LedgerError> {
+        self.get_entity(entity_id)
+            .ok_or_else(|| LedgerError::EntityNotFoundError { entity_id: entity_id.to_string() })
+    }
+
+    /// Return a copy of the open escrow of the given transaction, or an EscrowNotFoundError.
+    fn find_escrow(
+        self: &Ledger,
+        transaction_id: &str,
+    ) -> Result<Escrow, LedgerError> {
+        self.get_escrow(transaction_id)
+            .ok_or_else(|| LedgerError::EscrowNotFoundError { transaction_id: transaction_id.to_string() })
+    }
+
+    /// Store a changed entity and return the events of its balances changed by the given transaction.
+    fn store_entity(
+        self: &mut Ledger,
+        entity: Entity,
+        transaction: &Transaction,
+        timestamp: u64,
+    ) -> Vec<DomainEvent> {
+        let entity_id = entity.get_id().to_string();
+        let events = match self.entities.get(&entity_id) {
+            Some(stored_entity) => balance_changes(
+                &entity_id, &stored_entity.get_wallet(), &entity.get_wallet(), Some(transaction.get_id()), timestamp),
+            None => Vec::new(),
+        };
+        self.entities.insert(entity_id, entity);
+        events
+    }
+
+    /// Store the changed escrow account and return the events of its balances changed by the given transaction.
+    fn store_escrow_account(
+        self: &mut Ledger,
+        escrow_account: Entity,
+        transaction: &Transaction,
+        timestamp: u64,
+    ) -> Vec<DomainEvent> {
+        let events = balance_changes(
+            escrow_account.get_id(), &self.escrow_account.get_wallet(), &escrow_account.get_wallet(), Some(transaction.get_id()), timestamp);
+        self.escrow_account = escrow_account;
+        events
+    }
+
+    /// Record the coins of a settled escrow as moved from the escrow account to the credited entity,
+    /// then close the escrow and append its transaction to the transaction log.
+    fn close_escrow(
+        self: &mut Ledger,
+        escrow: Escrow,
+        escrow_account: Entity,
+        credited_entity: Entity,
+        seen_states: usize,
+        timestamp: u64,
+    ) -> Result<(), LedgerError> {
+        let transaction = escrow.get_transaction();
+        self.journal
+            .record_asset(
+                transaction.get_id(),
+                transaction.get_asset(),
+                ESCROW_ACCOUNT_ID.to_string(),
+                credited_entity.get_id().to_string(),
+                transaction.get_amount(),
+            )
+            .map_err(|error| LedgerError::JournalOperationError { error })?;
+        let mut events = self.store_escrow_account(escrow_account, &transaction, timestamp);
+        events.extend(self.store_entity(credited_entity, &transaction, timestamp));
+        self.event_bus.publish_all(transaction_changes(&transaction, seen_states, events));
+        self.escrows.remove(&transaction.get_id());
+        self.transactions.push(transaction);
+        Ok(())
+    }
+
+    /// Return a copy of the recorded transactions that satisfy the given predicate, in order of execution.
+    fn filter_transactions<P>(
+        self: &Ledger,
+        predicate: P,
+    ) -> Vec<Transaction>
+        where P: Fn(&Transaction) -> bool {
+        self.transactions
+            .iter()
+            .filter(|transaction| predicate(transaction))
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,352 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::JournalError;
+use crate::model::{Amount, Posting, PostingSide, DEFAULT_ASSET};
+
+/// Id of the account that balances the coins entering or leaving the system,
+/// e.g. the opening balances of the wallets.
+pub const ISSUANCE_ACCOUNT_ID: &str = "__issuance__";
+
+/// Id of the account that holds the coins of the open escrows, between the sender and the receiver.
+pub const ESCROW_ACCOUNT_ID: &str = "__escrow__";
+
+/// Journal structure
+/// It is an append-only double-entry journal: every balance change is recorded as a pair of postings,
+/// a Debit and a Credit of the same amount, tied to the id of a transaction.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Default)]
+pub struct Journal {
+    postings: Vec<Posting>,
+}
+
+/// Journal implementation.
+impl Journal {
+    ///
+    /// Return a new empty Journal.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::ledger::Journal;
+    /// let journal = Journal::new();
+    /// # assert!(journal.get_postings().is_empty())
+    /// ```
+    ///
+    pub fn new() -> Journal {
+        Journal {
+            postings: Vec::new(),
+        }
+    }
+
+    ///
+    /// Record a balance change in the default asset as a pair of postings: the amount is withdrawn from the debited entity
+    /// and deposited into the credited entity.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_id` - A String that represents the id of the transaction that generated the change.
+    /// * `debit_entity_id` - A String that represents the id of the entity from which the coins are withdrawn.
+    /// * `credit_entity_id` - A String that represents the id of the entity to which the coins are deposited.
+    /// * `amount` - An Amount that represents the amount of coins moved.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Journal;
+    /// # use banana_coin::model::Amount;
+    /// let mut journal = Journal::new();
+    /// let result = journal.record(
+    ///     "id_0001".to_string(),
+    ///     "entity_001".to_string(),
+    ///     "entity_002".to_string(),
+    ///     Amount::from_integer(100)
+    /// );
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(journal.get_balance("entity_001").unwrap(), Amount::from_integer(-100));
+    /// # assert_eq!(journal.get_balance("entity_002").unwrap(), Amount::from_integer(100))
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A JournalError is returned if the amount is zero or negative or if the two entities are the same.
+    ///
+    pub fn record(
+        self: &mut Journal,
+        transaction_id: String,
+        debit_entity_id: String,
+        credit_entity_id: String,
+        amount: Amount,
+    ) -> Result<(), JournalError> {
+        self.record_asset(transaction_id, DEFAULT_ASSET.to_string(), debit_entity_id, credit_entity_id, amount)
+    }
+
+    ///
+    /// Record a balance change in the given asset as a pair of postings: the amount is withdrawn from the debited entity
+    /// and deposited into the credited entity.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_id` - A String that represents the id of the transaction that generated the change.
+    /// * `asset` - A String that represents the code of the asset of the coins moved.
+    /// * `debit_entity_id` - A String that represents the id of the entity from which the coins are withdrawn.
+    /// * `credit_entity_id` - A String that represents the id of the entity to which the coins are deposited.
+    /// * `amount` - An Amount that represents the amount of coins moved.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Journal;
+    /// # use banana_coin::model::Amount;
+    /// let mut journal = Journal::new();
+    /// let result = journal.record_asset(
+    ///     "id_0001".to_string(),
+    ///     "GOLD".to_string(),
+    ///     "entity_001".to_string(),
+    ///     "entity_002".to_string(),
+    ///     Amount::from_integer(100)
+    /// );
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(journal.get_asset_balance("entity_002", "GOLD").unwrap(), Amount::from_integer(100));
+    /// # assert!(journal.get_balance("entity_002").unwrap().is_zero())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A JournalError is returned if the amount is zero or negative or if the two entities are the same.
+    ///
+    pub fn record_asset(
+        self: &mut Journal,
+        transaction_id: String,
+        asset: String,
+        debit_entity_id: String,
+        credit_entity_id: String,
+        amount: Amount,
+    ) -> Result<(), JournalError> {
+        self.record_asset_all(transaction_id, asset, &[(debit_entity_id, credit_entity_id, amount)])
+    }
+
+    ///
+    /// Record several balance changes of a transaction in the given asset, each as a pair of postings.
+    /// The changes are all checked before any of them is recorded, so either all of them or none are recorded.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_id` - A String that represents the id of the transaction that generated the changes.
+    /// * `asset` - A String that represents the code of the asset of the coins moved.
+    /// * `changes` - A slice of (debited entity id, credited entity id, amount) that represents the changes.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Journal;
+    /// # use banana_coin::model::Amount;
+    /// let mut journal = Journal::new();
+    /// let result = journal.record_asset_all(
+    ///     "id_0001".to_string(),
+    ///     "GOLD".to_string(),
+    ///     &[
+    ///         ("entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100)),
+    ///         ("entity_001".to_string(), "entity_003".to_string(), Amount::from_integer(0)),
+    ///     ]
+    /// );
+    /// # assert!(result.is_err());
+    /// # assert!(journal.get_postings().is_empty())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A JournalError is returned if one of the amounts is zero or negative or if the two entities of a change are the same.
+    ///
+    pub fn record_asset_all(
+        self: &mut Journal,
+        transaction_id: String,
+        asset: String,
+        changes: &[(String, String, Amount)],
+    ) -> Result<(), JournalError> {
+        for (debit_entity_id, credit_entity_id, amount) in changes.iter() {
+            if amount.is_zero() {
+                return Err(JournalError::ZeroAmountPostingError { transaction_id });
+            }
+            if amount.is_negative() {
+                return Err(JournalError::NegativeAmountPostingError { transaction_id, amount: *amount });
+            }
+            if debit_entity_id == credit_entity_id {
+                return Err(JournalError::SameEntityPostingError {
+                    transaction_id,
+                    entity_id: debit_entity_id.clone(),
+                });
+            }
+        }
+        for (debit_entity_id, credit_entity_id, amount) in changes.iter() {
+            self.postings.push(Posting::new_for_asset(
+                transaction_id.clone(), debit_entity_id.clone(), asset.clone(), PostingSide::Debit, *amount));
+            self.postings.push(Posting::new_for_asset(
+                transaction_id.clone(), credit_entity_id.clone(), asset.clone(), PostingSide::Credit, *amount));
+        }
+        Ok(())
+    }
+
+    ///
+    /// Return a copy of all the postings, in order of recording.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Journal;
+    /// # use banana_coin::model::Posting;
+    /// # let journal = Journal::new();
+    /// let postings : Vec<Posting> = journal.get_postings();
+    /// # assert!(postings.is_empty())
+    /// ```
+    ///
+    pub fn get_postings(
+        self: &Journal,
+    ) -> Vec<Posting> {
+        self.postings.clone()
+    }
+
+    ///
+    /// Return a copy of the postings tied to the given transaction, in order of recording.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_id` - A str that represents the id of the transaction.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Journal;
+    /// # use banana_coin::model::Posting;
+    /// # let journal = Journal::new();
+    /// let postings : Vec<Posting> = journal.get_postings_by_transaction("id_0001");
+    /// # assert!(postings.is_empty())
+    /// ```
+    ///
+    pub fn get_postings_by_transaction(
+        self: &Journal,
+        transaction_id: &str,
+    ) -> Vec<Posting> {
+        self.postings
+            .iter()
+            .filter(|posting| posting.get_transaction_id() == transaction_id)
+            .cloned()
+            .collect()
+    }
+
+    ///
+    /// Return the balance of the given entity in the default asset derived from its postings.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A str that represents the id of the entity.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Journal;
+    /// # use banana_coin::model::Amount;
+    /// # let journal = Journal::new();
+    /// let balance : Amount = journal.get_balance("entity_001").unwrap();
+    /// # assert!(balance.is_zero())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A BalanceOverflowError is returned if the sum of the postings is out of range.
+    ///
+    pub fn get_balance(
+        self: &Journal,
+        entity_id: &str,
+    ) -> Result<Amount, JournalError> {
+        self.get_asset_balance(entity_id, DEFAULT_ASSET)
+    }
+
+    ///
+    /// Return the balance of the given entity in the given asset derived from its postings.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A str that represents the id of the entity.
+    /// * `asset` - A str that represents the code of the asset.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Journal;
+    /// # use banana_coin::model::Amount;
+    /// # let journal = Journal::new();
+    /// let balance : Amount = journal.get_asset_balance("entity_001", "GOLD").unwrap();
+    /// # assert!(balance.is_zero())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A BalanceOverflowError is returned if the sum of the postings is out of range.
+    ///
+    pub fn get_asset_balance(
+        self: &Journal,
+        entity_id: &str,
+        asset: &str,
+    ) -> Result<Amount, JournalError> {
+        self.postings
+            .iter()
+            .filter(|posting| posting.get_entity_id() == entity_id && posting.get_asset() == asset)
+            .try_fold(Amount::zero(), |balance, posting| balance.checked_add(posting.get_signed_amount()))
+            .ok_or_else(|| JournalError::BalanceOverflowError { asset: asset.to_string() })
+    }
+
+    ///
+    /// Return the codes of the assets the given entity has postings in.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A str that represents the id of the entity.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Journal;
+    /// # let journal = Journal::new();
+    /// let assets : Vec<String> = journal.get_assets("entity_001");
+    /// # assert!(assets.is_empty())
+    /// ```
+    ///
+    pub fn get_assets(
+        self: &Journal,
+        entity_id: &str,
+    ) -> Vec<String> {
+        let mut assets: Vec<String> = self.postings
+            .iter()
+            .filter(|posting| posting.get_entity_id() == entity_id)
+            .map(|posting| posting.get_asset().to_string())
+            .collect();
+        assets.sort();
+        assets.dedup();
+        assets
+    }
+
+    ///
+    /// Check that the sum of all the postings of every asset is zero.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::Journal;
+    /// # let journal = Journal::new();
+    /// let result = journal.check_trial_balance();
+    /// # assert!(result.is_ok())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An UnbalancedJournalError is returned for the first asset whose postings do not sum to zero
+    /// and a BalanceOverflowError for the first asset whose sum is out of range.
+    ///
+    pub fn check_trial_balance(
+        self: &Journal,
+    ) -> Result<(), JournalError> {
+        let mut totals: BTreeMap<&str, Amount> = BTreeMap::new();
+        for posting in self.postings.iter() {
+            let total = totals.entry(posting.get_asset()).or_default();
+            *total = total
+                .checked_add(posting.get_signed_amount())
+                .ok_or_else(|| JournalError::BalanceOverflowError { asset: posting.get_asset().to_string() })?;
+        }
+        match totals.into_iter().find(|(_, total)| !total.is_zero()) {
+            None => Ok(()),
+            Some((asset, total)) => Err(JournalError::UnbalancedJournalError { asset: asset.to_string(), total }),
+        }
+    }
+}
@@ -0,0 +1,7 @@
+/// Importing entity ledger module
+pub mod entity_ledger;
+pub use entity_ledger::*;
+
+/// Importing journal module
+pub mod journal;
+pub use journal::*;
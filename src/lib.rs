@@ -1,2 +1,14 @@
 pub mod model;
-pub mod error;
\ No newline at end of file
+pub mod error;
+pub mod transfer;
+pub mod ledger;
+pub mod storage;
+pub mod cli;
+pub mod crypto;
+pub mod chain;
+pub mod network;
+pub mod api;
+pub mod event;
+pub mod webhook;
+pub mod audit;
+pub mod util;
@@ -1,48 +1,49 @@
 extern crate banana_coin;
-use banana_coin::model::*;
-use banana_coin::model::TransactionState::*;
 
-fn main() {
-    let mut entity : Entity = Entity::new(
-        "Ciao",
-        "Name",
-        Wallet::new(
-            0
-        )
-    );
+use std::process;
 
-    entity.add_coins(100);
+use banana_coin::audit::{AuditLog, AuditSubscriber};
+use banana_coin::cli::{run_command_with_audit, Invocation};
+use banana_coin::error::CliError;
+use banana_coin::event::EventBus;
+use banana_coin::storage::JsonLinesStorage;
+use banana_coin::util::now;
 
-    let result = serde_json::to_string_pretty(&entity);
-    match result {
-        Ok(e) => {
-            println!("{}", e)
-        }
-        Err(e) => {
-            println!("{}", e)
+fn main() {
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+    let invocation = match Invocation::parse(&arguments) {
+        Ok(invocation) => invocation,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(2)
         }
-    }
-    let mut vec = Vec::new();
-    vec.push(Created {timestamp: 1});
-    vec.push(OnProcess {timestamp: 4});
-    vec.push(Completed {timestamp: 7});
-    let transaction = Transaction::new(
-        "id_0001".to_string(),
-        "entity_001".to_string(),
-        "entity_002".to_string(),
-        100,
-        "Moving 100 coins from entity identified by 'entity_001' to entity identified by 'entity_002'.".to_string(),
-        Completed{timestamp: 7},
-        vec
-    );
-    let result = serde_json::to_string_pretty(&transaction);
+    };
+    let timestamp = now();
+
+    // The changes of the entities are audited once they are stored, on behalf of the given actor, or else of the user running the command.
+    // The data file stays locked by its storage until the command is done, so that no other process changes it meanwhile
+    let actor = invocation.actor.clone()
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_else(|| "unknown".to_string());
+    let result = AuditLog::open(&invocation.audit_log)
+        .map_err(CliError::from)
+        .and_then(|audit_log| {
+            let audit_subscriber = AuditSubscriber::new(audit_log, actor);
+            JsonLinesStorage::open(&invocation.data_file)
+                .map_err(CliError::from)
+                .and_then(|mut storage| run_command_with_audit(
+                    &mut storage, &invocation.command, timestamp, &EventBus::new(), Some(&audit_subscriber)))
+        });
     match result {
-        Ok(e) => {
-            println!("{}", e)
-        }
-        Err(e) => {
-            println!("{}", e)
+        Ok(output) => println!("{}", output.render(invocation.json)),
+        Err(error) => {
+            if invocation.json {
+                let message = serde_json::json!({ "error": error, "message": error.to_string() });
+                eprintln!("{}", serde_json::to_string_pretty(&message).unwrap_or_default());
+            } else {
+                eprintln!("{}", error);
+            }
+            process::exit(1)
         }
     }
-    println!("Hello, world!");
 }
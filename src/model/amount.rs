@@ -0,0 +1,532 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::{self, Visitor};
+
+use crate::error::AmountError;
+
+/// Highest number of decimal places of an amount.
+pub const MAX_DECIMALS: u8 = 18;
+
+/// Amount structure
+/// It represents a fixed-point decimal amount of coins: a signed number of units of 10^-decimals coins.
+/// The number of decimal places is the precision of the asset the amount belongs to, e.g. 2 for an asset
+/// counted in cents. Amounts with different precisions are compared and summed exactly, the arithmetic
+/// never rounds: an operation that can not be carried out exactly fails instead.
+/// It is serialized as a string like "12.345", so that JSON consumers do not lose precision.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Amount {
+    /// Number of units of 10^-decimals coins.
+    units: i128,
+    /// Number of decimal places.
+    decimals: u8,
+}
+
+/// Amount implementation.
+impl Amount {
+    ///
+    /// Return a new Amount made of the given number of units of 10^-decimals coins.
+    ///
+    /// # Arguments
+    ///
+    /// * `units` - A i128 integer that represents the number of units of the amount.
+    /// * `decimals` - A u8 integer that represents the number of decimal places of the amount.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::model::Amount;
+    /// let amount = Amount::new(
+    ///     12345,
+    ///     3
+    /// ).unwrap();
+    /// # assert_eq!(amount.to_string(), "12.345")
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A TooManyDecimalsError is returned if the number of decimal places is higher than MAX_DECIMALS.
+    ///
+    pub fn new(
+        units: i128,
+        decimals: u8,
+    ) -> Result<Amount, AmountError> {
+        if decimals > MAX_DECIMALS {
+            return Err(AmountError::TooManyDecimalsError { decimals, max_decimals: MAX_DECIMALS });
+        }
+        Ok(Amount {
+            units,
+            decimals,
+        })
+    }
+
+    ///
+    /// Return a new Amount made of a whole number of coins, without decimal places.
+    ///
+    /// # Arguments
+    ///
+    /// * `coins` - A i64 integer that represents the number of coins.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::model::Amount;
+    /// let amount = Amount::from_integer(
+    ///     100
+    /// );
+    /// # assert_eq!(amount.to_string(), "100")
+    /// ```
+    ///
+    pub const fn from_integer(
+        coins: i64,
+    ) -> Amount {
+        Amount {
+            units: coins as i128,
+            decimals: 0,
+        }
+    }
+
+    ///
+    /// Return a zero Amount, without decimal places.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::model::Amount;
+    /// let amount = Amount::zero();
+    /// # assert!(amount.is_zero())
+    /// ```
+    ///
+    pub fn zero() -> Amount {
+        Amount::default()
+    }
+
+    ///
+    /// Parse an amount like "12", "-3.5" or "12.345". The amount keeps the decimal places written in the text.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - A str that represents the amount.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::model::Amount;
+    /// let amount = Amount::parse("12.340").unwrap();
+    /// # assert_eq!(amount.get_units(), 12340);
+    /// # assert_eq!(amount.get_decimals(), 3);
+    /// # assert!(Amount::parse("12.").is_err());
+    /// # assert!(Amount::parse("1e3").is_err())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An InvalidFormatError is returned if the text is not made of an optional minus sign, digits and
+    /// optionally a dot followed by digits, a TooManyDecimalsError if it has more than MAX_DECIMALS decimal places
+    /// and an OutOfRangeError if the amount is too large.
+    ///
+    pub fn parse(
+        text: &str,
+    ) -> Result<Amount, AmountError> {
+        let invalid_format = || AmountError::InvalidFormatError { text: text.to_string() };
+        let (negative, unsigned) = match text.strip_prefix('-') {
+            Some(unsigned) => (true, unsigned),
+            None => (false, text),
+        };
+        let (integer, fraction) = match unsigned.split_once('.') {
+            Some((_, "")) => return Err(invalid_format()),
+            Some((integer, fraction)) => (integer, fraction),
+            None => (unsigned, ""),
+        };
+        if integer.is_empty() || !integer.chars().chain(fraction.chars()).all(|digit| digit.is_ascii_digit()) {
+            return Err(invalid_format());
+        }
+        if fraction.len() > usize::from(MAX_DECIMALS) {
+            return Err(AmountError::TooManyDecimalsError {
+                decimals: u8::try_from(fraction.len()).unwrap_or(u8::MAX),
+                max_decimals: MAX_DECIMALS,
+            });
+        }
+        let mut units: i128 = 0;
+        for digit in integer.bytes().chain(fraction.bytes()) {
+            units = units
+                .checked_mul(10)
+                .and_then(|units| units.checked_add(i128::from(digit - b'0')))
+                .ok_or_else(|| AmountError::OutOfRangeError { text: text.to_string() })?;
+        }
+        Amount::new(if negative { -units } else { units }, fraction.len() as u8)
+    }
+
+    ///
+    /// Parse an amount with the given number of decimal places, e.g. the precision of its asset.
+    /// The text can have fewer decimal places, but not more.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - A str that represents the amount.
+    /// * `decimals` - A u8 integer that represents the number of decimal places of the amount.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::model::Amount;
+    /// let amount = Amount::parse_with_decimals("12.3", 2).unwrap();
+    /// # assert_eq!(amount.to_string(), "12.30");
+    /// # assert!(Amount::parse_with_decimals("12.345", 2).is_err())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An AmountError is returned if the text is not a valid amount or if it has more decimal places than the given ones.
+    ///
+    pub fn parse_with_decimals(
+        text: &str,
+        decimals: u8,
+    ) -> Result<Amount, AmountError> {
+        Amount::parse(text)?.rescale(decimals)
+    }
+
+    ///
+    /// Return the number of units of 10^-decimals coins of the amount.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::Amount;
+    /// let amount = Amount::parse("1.50").unwrap();
+    /// let units : i128 = amount.get_units();
+    /// # assert_eq!(units, 150)
+    /// ```
+    ///
+    pub fn get_units(
+        self: &Amount,
+    ) -> i128 {
+        self.units
+    }
+
+    ///
+    /// Return the number of decimal places of the amount.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::Amount;
+    /// let amount = Amount::parse("1.50").unwrap();
+    /// let decimals : u8 = amount.get_decimals();
+    /// # assert_eq!(decimals, 2)
+    /// ```
+    ///
+    pub fn get_decimals(
+        self: &Amount,
+    ) -> u8 {
+        self.decimals
+    }
+
+    ///
+    /// Return whether the amount is zero.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::Amount;
+    /// # assert!(Amount::parse("0.00").unwrap().is_zero())
+    /// ```
+    ///
+    pub fn is_zero(
+        self: &Amount,
+    ) -> bool {
+        self.units == 0
+    }
+
+    ///
+    /// Return whether the amount is lower than zero.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::Amount;
+    /// # assert!(Amount::parse("-0.01").unwrap().is_negative())
+    /// ```
+    ///
+    pub fn is_negative(
+        self: &Amount,
+    ) -> bool {
+        self.units < 0
+    }
+
+    ///
+    /// Return the same amount with the given number of decimal places.
+    ///
+    /// # Arguments
+    ///
+    /// * `decimals` - A u8 integer that represents the new number of decimal places.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::Amount;
+    /// let amount = Amount::parse("1.50").unwrap();
+    /// # assert_eq!(amount.rescale(4).unwrap().to_string(), "1.5000");
+    /// # assert_eq!(amount.rescale(1).unwrap().to_string(), "1.5");
+    /// # assert!(amount.rescale(0).is_err())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A TooManyDecimalsError is returned if the number of decimal places is higher than MAX_DECIMALS,
+    /// a PrecisionLossError if the amount can not be represented exactly with the given decimal places
+    /// and an OutOfRangeError if the rescaled amount is too large.
+    ///
+    pub fn rescale(
+        self: &Amount,
+        decimals: u8,
+    ) -> Result<Amount, AmountError> {
+        if decimals > MAX_DECIMALS {
+            return Err(AmountError::TooManyDecimalsError { decimals, max_decimals: MAX_DECIMALS });
+        }
+        let units = match decimals.cmp(&self.decimals) {
+            Ordering::Equal => Some(self.units),
+            Ordering::Greater => self.units.checked_mul(10i128.pow(u32::from(decimals - self.decimals))),
+            Ordering::Less => {
+                let factor = 10i128.pow(u32::from(self.decimals - decimals));
+                if self.units % factor != 0 {
+                    return Err(AmountError::PrecisionLossError { amount: *self, decimals });
+                }
+                Some(self.units / factor)
+            }
+        };
+        match units {
+            Some(units) => Amount::new(units, decimals),
+            None => Err(AmountError::OutOfRangeError { text: self.to_string() }),
+        }
+    }
+
+    ///
+    /// Return the sum of the two amounts, with the highest number of decimal places of the two,
+    /// or None if the sum is out of range.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - An Amount that represents the amount to add.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::Amount;
+    /// let sum = Amount::parse("1.5").unwrap().checked_add(Amount::parse("0.25").unwrap());
+    /// # assert_eq!(sum.unwrap().to_string(), "1.75")
+    /// ```
+    ///
+    pub fn checked_add(
+        self: &Amount,
+        other: Amount,
+    ) -> Option<Amount> {
+        let (left, right) = Amount::align(*self, other)?;
+        left.units.checked_add(right.units).map(|units| Amount { units, decimals: left.decimals })
+    }
+
+    ///
+    /// Return the difference of the two amounts, with the highest number of decimal places of the two,
+    /// or None if the difference is out of range.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - An Amount that represents the amount to subtract.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::Amount;
+    /// let difference = Amount::parse("1.5").unwrap().checked_sub(Amount::parse("2.25").unwrap());
+    /// # assert_eq!(difference.unwrap().to_string(), "-0.75")
+    /// ```
+    ///
+    pub fn checked_sub(
+        self: &Amount,
+        other: Amount,
+    ) -> Option<Amount> {
+        let (left, right) = Amount::align(*self, other)?;
+        left.units.checked_sub(right.units).map(|units| Amount { units, decimals: left.decimals })
+    }
+
+    ///
+    /// Return the sum of the two amounts, with the highest number of decimal places of the two,
+    /// or the highest or lowest amount with those decimal places if the sum is out of range.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - An Amount that represents the amount to add.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::Amount;
+    /// let sum = Amount::parse("1.5").unwrap().saturating_add(Amount::parse("0.25").unwrap());
+    /// # assert_eq!(sum.to_string(), "1.75")
+    /// ```
+    ///
+    pub fn saturating_add(
+        self: &Amount,
+        other: Amount,
+    ) -> Amount {
+        let decimals = self.decimals.max(other.decimals);
+        self.checked_add(other).unwrap_or(Amount {
+            units: if other.is_negative() { i128::MIN } else { i128::MAX },
+            decimals,
+        })
+    }
+
+    ///
+    /// Return the difference of the two amounts, with the highest number of decimal places of the two,
+    /// or the highest or lowest amount with those decimal places if the difference is out of range.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - An Amount that represents the amount to subtract.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::Amount;
+    /// let difference = Amount::parse("1.5").unwrap().saturating_sub(Amount::parse("0.25").unwrap());
+    /// # assert_eq!(difference.to_string(), "1.25")
+    /// ```
+    ///
+    pub fn saturating_sub(
+        self: &Amount,
+        other: Amount,
+    ) -> Amount {
+        let decimals = self.decimals.max(other.decimals);
+        self.checked_sub(other).unwrap_or(Amount {
+            units: if other.is_negative() { i128::MAX } else { i128::MIN },
+            decimals,
+        })
+    }
+    ///
+    /// Return the amount multiplied by the given integer factor, or None if the product is out of range.
+    ///
+    /// # Arguments
+    ///
+    /// * `factor` - A i128 integer that represents the factor.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::Amount;
+    /// let product = Amount::parse("1.25").unwrap().checked_mul(3);
+    /// # assert_eq!(product.unwrap().to_string(), "3.75")
+    /// ```
+    ///
+    pub fn checked_mul(
+        self: &Amount,
+        factor: i128,
+    ) -> Option<Amount> {
+        self.units.checked_mul(factor).map(|units| Amount { units, decimals: self.decimals })
+    }
+
+    ///
+    /// Return the opposite of the amount, or None if it is out of range.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::Amount;
+    /// let opposite = Amount::parse("1.5").unwrap().checked_neg();
+    /// # assert_eq!(opposite.unwrap().to_string(), "-1.5")
+    /// ```
+    ///
+    pub fn checked_neg(
+        self: &Amount,
+    ) -> Option<Amount> {
+        self.units.checked_neg().map(|units| Amount { units, decimals: self.decimals })
+    }
+
+    /// Return the two amounts with the same number of decimal places, the highest of the two.
+    fn align(
+        left: Amount,
+        right: Amount,
+    ) -> Option<(Amount, Amount)> {
+        let decimals = left.decimals.max(right.decimals);
+        match (left.rescale(decimals), right.rescale(decimals)) {
+            (Ok(left), Ok(right)) => Some((left, right)),
+            _ => None,
+        }
+    }
+
+    /// Return the integer part of the amount and its fractional part in units of 10^-MAX_DECIMALS coins,
+    /// which never overflow and order amounts of any precision.
+    fn split(
+        self: &Amount,
+    ) -> (i128, i128) {
+        let factor = 10i128.pow(u32::from(self.decimals));
+        let fraction = (self.units % factor) * 10i128.pow(u32::from(MAX_DECIMALS - self.decimals));
+        (self.units / factor, fraction)
+    }
+}
+
+impl PartialEq for Amount {
+    fn eq(&self, other: &Amount) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Amount {}
+
+impl PartialOrd for Amount {
+    fn partial_cmp(&self, other: &Amount) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Amount {
+    fn cmp(&self, other: &Amount) -> Ordering {
+        self.split().cmp(&other.split())
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.units < 0 { "-" } else { "" };
+        let magnitude = self.units.unsigned_abs();
+        let factor = 10u128.pow(u32::from(self.decimals));
+        match self.decimals {
+            0 => write!(f, "{}{}", sign, magnitude),
+            decimals => write!(f, "{}{}.{:0width$}", sign, magnitude / factor, magnitude % factor, width = usize::from(decimals)),
+        }
+    }
+}
+
+impl FromStr for Amount {
+    type Err = AmountError;
+
+    fn from_str(text: &str) -> Result<Amount, AmountError> {
+        Amount::parse(text)
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D>(deserializer: D) -> Result<Amount, D::Error>
+        where D: Deserializer<'de> {
+        deserializer.deserialize_any(AmountVisitor)
+    }
+}
+
+/// Visitor reading an amount from a string, or from an integer as stored before the introduction of the amounts.
+struct AmountVisitor;
+
+impl<'de> Visitor<'de> for AmountVisitor {
+    type Value = Amount;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "an amount like \"12.345\" or an integer")
+    }
+
+    fn visit_str<E>(self, text: &str) -> Result<Amount, E>
+        where E: de::Error {
+        Amount::parse(text).map_err(E::custom)
+    }
+
+    fn visit_i64<E>(self, coins: i64) -> Result<Amount, E>
+        where E: de::Error {
+        Ok(Amount::from_integer(coins))
+    }
+
+    fn visit_u64<E>(self, coins: u64) -> Result<Amount, E>
+        where E: de::Error {
+        Ok(Amount { units: i128::from(coins), decimals: 0 })
+    }
+}
@@ -0,0 +1,308 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AmountError;
+use crate::model::{Amount, DEFAULT_ASSET, MAX_DECIMALS};
+
+/// Number of decimal places of the default asset.
+pub const DEFAULT_ASSET_DECIMALS: u8 = 8;
+
+/// Asset definition structure
+/// It represents an asset held by wallets: its code and the number of decimal places of its amounts.
+/// The amounts of the asset can not have more decimal places than that.
+/// The definitions are held by an asset registry; an asset not defined in the registry
+/// has the default definition of its code, see 'AssetDefinition::default_for'.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[serde(try_from = "StoredAssetDefinition")]
+pub struct AssetDefinition {
+    /// Code of the asset.
+    code: String,
+    /// Number of decimal places of the amounts of the asset.
+    decimals: u8,
+}
+
+/// Serialized form of an asset definition, checked like a new definition when it is loaded.
+#[derive(Deserialize)]
+struct StoredAssetDefinition {
+    code: String,
+    decimals: u8,
+}
+
+impl TryFrom<StoredAssetDefinition> for AssetDefinition {
+    type Error = AmountError;
+
+    fn try_from(
+        stored: StoredAssetDefinition,
+    ) -> Result<AssetDefinition, AmountError> {
+        AssetDefinition::new(stored.code, stored.decimals)
+    }
+}
+
+/// Asset definition implementation.
+impl AssetDefinition {
+    ///
+    /// Return a new AssetDefinition.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - A String that represents the code of the asset.
+    /// * `decimals` - A u8 integer that represents the number of decimal places of the asset.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::model::{AssetDefinition, DEFAULT_ASSET, MAX_DECIMALS};
+    /// let definition = AssetDefinition::new("EUR".to_string(), 2).unwrap();
+    /// # assert_eq!(definition.get_decimals(), 2);
+    /// # assert!(AssetDefinition::new("EUR".to_string(), MAX_DECIMALS + 1).is_err());
+    /// # assert!(AssetDefinition::new(DEFAULT_ASSET.to_string(), 2).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A TooManyDecimalsError is returned if the number of decimal places is higher than MAX_DECIMALS
+    /// and an AssetDecimalsConflictError if the asset is the default asset with other decimal places than DEFAULT_ASSET_DECIMALS.
+    ///
+    pub fn new(
+        code: String,
+        decimals: u8,
+    ) -> Result<AssetDefinition, AmountError> {
+        if decimals > MAX_DECIMALS {
+            return Err(AmountError::TooManyDecimalsError { decimals, max_decimals: MAX_DECIMALS });
+        }
+        if code == DEFAULT_ASSET && decimals != DEFAULT_ASSET_DECIMALS {
+            return Err(AmountError::AssetDecimalsConflictError {
+                asset: code,
+                decimals,
+                registered_decimals: DEFAULT_ASSET_DECIMALS,
+            });
+        }
+        Ok(AssetDefinition {
+            code,
+            decimals,
+        })
+    }
+
+    ///
+    /// Return the definition of an asset that is not defined otherwise: DEFAULT_ASSET_DECIMALS decimal places
+    /// for the default asset and MAX_DECIMALS for any other asset.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - A str that represents the code of the asset.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{AssetDefinition, DEFAULT_ASSET, DEFAULT_ASSET_DECIMALS, MAX_DECIMALS};
+    /// let definition = AssetDefinition::default_for(DEFAULT_ASSET);
+    /// # assert_eq!(definition.get_decimals(), DEFAULT_ASSET_DECIMALS);
+    /// # assert_eq!(AssetDefinition::default_for("UNDEFINED").get_decimals(), MAX_DECIMALS);
+    /// ```
+    ///
+    pub fn default_for(
+        code: &str,
+    ) -> AssetDefinition {
+        let decimals = match code == DEFAULT_ASSET {
+            true => DEFAULT_ASSET_DECIMALS,
+            false => MAX_DECIMALS,
+        };
+        AssetDefinition {
+            code: code.to_string(),
+            decimals,
+        }
+    }
+
+    ///
+    /// Return the code of the asset.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::AssetDefinition;
+    /// # let definition = AssetDefinition::new("EUR".to_string(), 2).unwrap();
+    /// let code : &str = definition.get_code();
+    /// # assert_eq!(code, "EUR")
+    /// ```
+    ///
+    pub fn get_code(
+        self: &AssetDefinition,
+    ) -> &str {
+        &self.code
+    }
+
+    ///
+    /// Return the number of decimal places of the asset.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::AssetDefinition;
+    /// # let definition = AssetDefinition::new("EUR".to_string(), 2).unwrap();
+    /// let decimals : u8 = definition.get_decimals();
+    /// # assert_eq!(decimals, 2)
+    /// ```
+    ///
+    pub fn get_decimals(
+        self: &AssetDefinition,
+    ) -> u8 {
+        self.decimals
+    }
+
+    ///
+    /// Return true if the amount can be represented exactly with the decimal places of the asset.
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - An Amount that represents the amount of coins of the asset.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, AssetDefinition, DEFAULT_ASSET};
+    /// let definition = AssetDefinition::default_for(DEFAULT_ASSET);
+    /// let fits = definition.fits(Amount::parse("0.00000001").unwrap());
+    /// # assert!(fits);
+    /// # assert!(!definition.fits(Amount::parse("0.000000001").unwrap()));
+    /// # assert!(definition.fits(Amount::parse("1.000000000").unwrap()));
+    /// ```
+    ///
+    pub fn fits(
+        self: &AssetDefinition,
+        amount: Amount,
+    ) -> bool {
+        amount.get_decimals() <= self.decimals || amount.rescale(self.decimals).is_ok()
+    }
+}
+
+/// Asset registry structure
+/// It represents the definitions of the assets known by a ledger, keyed by asset code.
+/// The amounts of the wallets and of the transactions of the ledger are checked against it,
+/// see 'Transaction::check_precision'. An asset not defined in the registry has the default definition of its code.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[serde(try_from = "Vec<AssetDefinition>", into = "Vec<AssetDefinition>")]
+pub struct AssetRegistry {
+    definitions: BTreeMap<String, AssetDefinition>,
+}
+
+/// A serialized registry is the list of its definitions, so that every key matches the code of its definition.
+impl TryFrom<Vec<AssetDefinition>> for AssetRegistry {
+    type Error = AmountError;
+
+    fn try_from(
+        definitions: Vec<AssetDefinition>,
+    ) -> Result<AssetRegistry, AmountError> {
+        let mut registry = AssetRegistry::new();
+        for definition in definitions {
+            registry.define(definition)?;
+        }
+        Ok(registry)
+    }
+}
+
+impl From<AssetRegistry> for Vec<AssetDefinition> {
+    fn from(
+        registry: AssetRegistry,
+    ) -> Vec<AssetDefinition> {
+        registry.definitions.into_values().collect()
+    }
+}
+
+/// Asset registry implementation.
+impl AssetRegistry {
+    ///
+    /// Return a new AssetRegistry without definitions.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::model::AssetRegistry;
+    /// let registry = AssetRegistry::new();
+    /// # assert!(registry.get_definitions().is_empty())
+    /// ```
+    ///
+    pub fn new() -> AssetRegistry {
+        AssetRegistry {
+            definitions: BTreeMap::new(),
+        }
+    }
+
+    ///
+    /// Define an asset in the registry. Defining again an asset with the same decimal places does nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `definition` - An AssetDefinition that represents the asset.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{AssetDefinition, AssetRegistry};
+    /// let mut registry = AssetRegistry::new();
+    /// let result = registry.define(AssetDefinition::new("EUR".to_string(), 2).unwrap());
+    /// # assert!(result.is_ok());
+    /// # assert!(registry.define(AssetDefinition::new("EUR".to_string(), 2).unwrap()).is_ok());
+    /// # assert!(registry.define(AssetDefinition::new("EUR".to_string(), 3).unwrap()).is_err())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An AssetDecimalsConflictError is returned if the asset is already defined with other decimal places.
+    ///
+    pub fn define(
+        self: &mut AssetRegistry,
+        definition: AssetDefinition,
+    ) -> Result<(), AmountError> {
+        if let Some(defined) = self.definitions.get(definition.get_code()) {
+            if defined.get_decimals() != definition.get_decimals() {
+                return Err(AmountError::AssetDecimalsConflictError {
+                    asset: definition.code,
+                    decimals: definition.decimals,
+                    registered_decimals: defined.get_decimals(),
+                });
+            }
+            return Ok(());
+        }
+        self.definitions.insert(definition.get_code().to_string(), definition);
+        Ok(())
+    }
+
+    ///
+    /// Return the definition of the given asset in the registry, or its default definition if the registry does not define it.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - A str that represents the code of the asset.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{AssetDefinition, AssetRegistry, DEFAULT_ASSET, DEFAULT_ASSET_DECIMALS};
+    /// let registry = AssetRegistry::new();
+    /// let definition : AssetDefinition = registry.get_definition(DEFAULT_ASSET);
+    /// # assert_eq!(definition.get_decimals(), DEFAULT_ASSET_DECIMALS)
+    /// ```
+    ///
+    pub fn get_definition(
+        self: &AssetRegistry,
+        code: &str,
+    ) -> AssetDefinition {
+        self.definitions
+            .get(code)
+            .cloned()
+            .unwrap_or_else(|| AssetDefinition::default_for(code))
+    }
+
+    ///
+    /// Return a copy of the definitions of the registry, in order of asset code.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{AssetDefinition, AssetRegistry};
+    /// # let registry = AssetRegistry::new();
+    /// let definitions : Vec<AssetDefinition> = registry.get_definitions();
+    /// # assert!(definitions.is_empty())
+    /// ```
+    ///
+    pub fn get_definitions(
+        self: &AssetRegistry,
+    ) -> Vec<AssetDefinition> {
+        self.definitions.values().cloned().collect()
+    }
+}
@@ -1,19 +1,26 @@
 use serde::{Deserialize, Serialize};
 
+use crate::crypto::KeyPair;
 use crate::error::EntityError;
-use crate::model::Wallet;
+use crate::model::{Amount, Hold, OverdraftPolicy, Wallet};
 
 /// Entity structure
 /// It represents a person or an organization. It is identified by its id.
 #[derive(Serialize, Deserialize)]
 #[derive(Debug, Clone)]
-pub struct Entity<'a> {
-    id: &'a str,
-    name: &'a str,
+pub struct Entity {
+    id: String,
+    name: String,
     wallet: Wallet,
+    /// Hex encoded Ed25519 public key used to verify the transactions of the entity, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    public_key: Option<String>,
+    /// Nonce the next transaction sent by the entity must carry. It grows by one at every executed transaction.
+    #[serde(default)]
+    nonce: u64,
 }
 
-impl<'a> Entity<'a> {
+impl Entity {
     ///
     /// Return a new Entity.
     ///
@@ -26,42 +33,44 @@ impl<'a> Entity<'a> {
     /// # Examples
     ///
     /// ```
-    /// use banana_coin::model::{Entity, Wallet};
+    /// use banana_coin::model::{Amount, Entity, Wallet};
     /// let entity = Entity::new(
-    ///     "id_0001",
-    ///     "john",
+    ///     "id_0001".to_string(),
+    ///     "john".to_string(),
     ///     Wallet::new(
-    ///         0
+    ///         Amount::from_integer(0)
     ///     )
     /// );
     /// # assert_eq!(entity.get_id(), "id_0001");
     /// # assert_eq!(entity.get_name(), "john");
-    /// # assert_eq!(entity.get_wallet().get_balance(), 0)
+    /// # assert_eq!(entity.get_wallet().get_balance(), Amount::from_integer(0))
     /// ```
     ///
     pub fn new(
-        id: &'a str,
-        name: &'a str,
+        id: String,
+        name: String,
         wallet: Wallet,
-    ) -> Entity<'a> {
+    ) -> Entity {
         Entity {
-            id: id,
-            name: name,
-            wallet: wallet,
+            id,
+            name,
+            wallet,
+            public_key: None,
+            nonce: 0,
         }
     }
 
     ///
-    /// Return a copy of the id string of the entity.
+    /// Return the id string of the entity.
     ///
     /// # Example
     /// ```
-    /// # use banana_coin::model::{Entity, Wallet};
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
     /// let entity = Entity::new(
-    ///     "id_0001",
-    ///     "john",
+    ///     "id_0001".to_string(),
+    ///     "john".to_string(),
     ///     Wallet::new(
-    ///         0
+    ///         Amount::from_integer(0)
     ///     )
     /// );
     /// let entity_id : &str = entity.get_id();
@@ -69,22 +78,22 @@ impl<'a> Entity<'a> {
     /// ```
     ///
     pub fn get_id(
-        self: &Entity <'a>,
+        self: &Entity,
     ) -> &str {
-        self.id.clone()
+        &self.id
     }
 
     ///
-    /// Return a copy of the name string of the entity.
+    /// Return the name string of the entity.
     ///
     /// # Example
     /// ```
-    /// # use banana_coin::model::{Entity, Wallet};
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
     /// let entity = Entity::new(
-    ///     "id_0001",
-    ///     "john",
+    ///     "id_0001".to_string(),
+    ///     "john".to_string(),
     ///     Wallet::new(
-    ///         0
+    ///         Amount::from_integer(0)
     ///     )
     /// );
     /// let entity_name : &str = entity.get_name();
@@ -92,9 +101,9 @@ impl<'a> Entity<'a> {
     /// ```
     ///
     pub fn get_name(
-        self: &Entity <'a>,
+        self: &Entity,
     ) -> &str {
-        self.name.clone()
+        &self.name
     }
 
     ///
@@ -102,26 +111,26 @@ impl<'a> Entity<'a> {
     ///
     /// # Example
     /// ```
-    /// # use banana_coin::model::{Entity, Wallet};
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
     /// let entity = Entity::new(
-    ///     "id_0001",
-    ///     "john",
+    ///     "id_0001".to_string(),
+    ///     "john".to_string(),
     ///     Wallet::new(
-    ///         0
+    ///         Amount::from_integer(0)
     ///     )
     /// );
     ///
     /// //The returned wallet is a copy of the current one
     /// let mut entity_wallet : Wallet = entity.get_wallet();
     ///
-    /// entity_wallet.add_coins(100);
+    /// entity_wallet.add_coins(Amount::from_integer(100));
     ///
-    /// assert_eq!(entity.get_wallet().get_balance(), 0);
-    /// assert_eq!(entity_wallet.get_balance(), 100);
+    /// assert_eq!(entity.get_wallet().get_balance(), Amount::from_integer(0));
+    /// assert_eq!(entity_wallet.get_balance(), Amount::from_integer(100));
     /// ```
     ///
     pub fn get_wallet(
-        self: &Entity <'a>,
+        self: &Entity,
     ) -> Wallet {
         self.wallet.clone()
     }
@@ -131,20 +140,20 @@ impl<'a> Entity<'a> {
     ///
     /// # Arguments
     ///
-    /// * `coins_to_add` - A i64 number that represents the amount of coin to add to the wallet.
+    /// * `coins_to_add` - An Amount that represents the amount of coin to add to the wallet.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use banana_coin::model::{Entity, Wallet};
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
     /// let mut entity = Entity::new(
-    ///     "id_0001",
-    ///     "john",
+    ///     "id_0001".to_string(),
+    ///     "john".to_string(),
     ///     Wallet::new(
-    ///         0
+    ///         Amount::from_integer(0)
     ///     )
     /// );
-    /// let result = entity.add_coins(100);
+    /// let result = entity.add_coins(Amount::from_integer(100));
     /// # match result {
     /// #    Ok(_) => assert!(true),
     /// #    Err(_) => assert!(true)
@@ -152,35 +161,36 @@ impl<'a> Entity<'a> {
     /// ```
     ///
     pub fn add_coins(
-        self: &mut Entity<'a>,
-        coins_to_add: u32,
+        self: &mut Entity,
+        coins_to_add: Amount,
     ) -> Result<(), EntityError> {
         match self.wallet.add_coins(coins_to_add) {
             Ok(_) => Ok(()),
-            Err(error) => Err(EntityError::WalletOperationError { error })
+            Err(error) => Err(EntityError::WalletOperationError { error: Box::new(error) })
         }
     }
 
     ///
-    /// Remove coins to the wallet of the entity.
+    /// Remove coins to the wallet of the entity, within the limit of the overdraft policy of the wallet.
+    /// The expired holds of the wallet are released first.
     ///
     /// # Arguments
     ///
-    /// * `coins_to_remove` - A i64 number that represents the amount of coin to remove from the wallet.
-    /// * `allow_negative_balance` - A boolean that represents if, after the remove operation, a negative wallet balance is allowed.
+    /// * `coins_to_remove` - An Amount that represents the amount of coin to remove from the wallet.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the removal.
     ///
     /// # Example
     ///
     /// ```
-    /// # use banana_coin::model::{Entity, Wallet};
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
     /// let mut entity = Entity::new(
-    ///     "id_0001",
-    ///     "john",
+    ///     "id_0001".to_string(),
+    ///     "john".to_string(),
     ///     Wallet::new(
-    ///         0
+    ///         Amount::from_integer(0)
     ///     )
     /// );
-    /// let result = entity.remove_coins(100, true);
+    /// let result = entity.remove_coins(Amount::from_integer(100), 2);
     /// # match result {
     /// #    Ok(_) => assert!(true),
     /// #    Err(_) => assert!(true)
@@ -188,14 +198,387 @@ impl<'a> Entity<'a> {
     /// ```
     ///
     pub fn remove_coins(
-        self: &mut Entity<'a>,
-        coins_to_add: u32,
-        allow_negative_balance: bool,
+        self: &mut Entity,
+        coins_to_add: Amount,
+        timestamp: u64,
     ) -> Result<(), EntityError> {
-        match self.wallet.remove_coins(coins_to_add, allow_negative_balance) {
+        match self.wallet.remove_coins(coins_to_add, timestamp) {
             Ok(_) => Ok(()),
-            Err(error) => Err(EntityError::WalletOperationError { error })
+            Err(error) => Err(EntityError::WalletOperationError { error: Box::new(error) })
         }
     }
-}
 
+    ///
+    /// Add coins of the given asset to the wallet of the entity.
+    ///
+    /// # Arguments
+    ///
+    /// * `asset` - A str that represents the code of the asset.
+    /// * `coins_to_add` - An Amount that represents the amount of coin to add to the wallet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
+    /// let mut entity = Entity::new(
+    ///     "id_0001".to_string(),
+    ///     "john".to_string(),
+    ///     Wallet::new(
+    ///         Amount::from_integer(0)
+    ///     )
+    /// );
+    /// let result = entity.add_asset_coins("GOLD", Amount::from_integer(100));
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(entity.get_wallet().get_asset_balance("GOLD"), Amount::from_integer(100))
+    /// ```
+    ///
+    pub fn add_asset_coins(
+        self: &mut Entity,
+        asset: &str,
+        coins_to_add: Amount,
+    ) -> Result<(), EntityError> {
+        match self.wallet.add_asset_coins(asset, coins_to_add) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(EntityError::WalletOperationError { error: Box::new(error) })
+        }
+    }
+
+    ///
+    /// Remove coins of the given asset from the wallet of the entity, within the limit of the overdraft policy of the wallet.
+    /// The expired holds of the wallet are released first.
+    ///
+    /// # Arguments
+    ///
+    /// * `asset` - A str that represents the code of the asset.
+    /// * `coins_to_remove` - An Amount that represents the amount of coin to remove from the wallet.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the removal.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
+    /// let mut entity = Entity::new(
+    ///     "id_0001".to_string(),
+    ///     "john".to_string(),
+    ///     Wallet::new(
+    ///         Amount::from_integer(0)
+    ///     )
+    /// );
+    /// let result = entity.remove_asset_coins("GOLD", Amount::from_integer(100), 2);
+    /// # assert!(result.is_err());
+    /// # assert_eq!(entity.get_wallet().get_asset_balance("GOLD"), Amount::from_integer(0))
+    /// ```
+    ///
+    pub fn remove_asset_coins(
+        self: &mut Entity,
+        asset: &str,
+        coins_to_remove: Amount,
+        timestamp: u64,
+    ) -> Result<(), EntityError> {
+        match self.wallet.remove_asset_coins(asset, coins_to_remove, timestamp) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(EntityError::WalletOperationError { error: Box::new(error) })
+        }
+    }
+
+    ///
+    /// Replace the overdraft policy of the wallet of the entity, recording who changed it and when.
+    ///
+    /// # Arguments
+    ///
+    /// * `overdraft_policy` - An OverdraftPolicy that represents the new policy of the wallet.
+    /// * `changed_by` - A String that represents who changes the policy.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the change.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use banana_coin::model::{Amount, Entity, OverdraftPolicy, Wallet};
+    /// let mut entity = Entity::new(
+    ///     "id_0001".to_string(),
+    ///     "john".to_string(),
+    ///     Wallet::new(
+    ///         Amount::from_integer(0)
+    ///     )
+    /// );
+    /// let result = entity.set_overdraft_policy(OverdraftPolicy::Unlimited, "admin".to_string(), 1);
+    /// # assert!(result.is_ok());
+    /// # assert!(entity.remove_coins(Amount::from_integer(100), 2).is_ok());
+    /// # assert_eq!(entity.get_wallet().get_balance(), Amount::from_integer(-100))
+    /// ```
+    ///
+    pub fn set_overdraft_policy(
+        self: &mut Entity,
+        overdraft_policy: OverdraftPolicy,
+        changed_by: String,
+        timestamp: u64,
+    ) -> Result<(), EntityError> {
+        match self.wallet.set_overdraft_policy(overdraft_policy, changed_by, timestamp) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(EntityError::WalletOperationError { error: Box::new(error) })
+        }
+    }
+
+    ///
+    /// Hold coins of the given asset in the wallet of the entity, so that they can not be removed until the hold
+    /// is captured, released or expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `hold_id` - A String that represents the id of the hold.
+    /// * `asset` - A str that represents the code of the asset.
+    /// * `amount` - An Amount that represents the amount of coins to hold.
+    /// * `timestamp` - A u64 integer that represents the timestamp at which the hold is placed.
+    /// * `duration` - A u64 integer that represents how long the hold lasts before it expires.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use banana_coin::model::{Amount, Entity, Wallet, DEFAULT_ASSET};
+    /// let mut entity = Entity::new(
+    ///     "id_0001".to_string(),
+    ///     "john".to_string(),
+    ///     Wallet::new(
+    ///         Amount::from_integer(100)
+    ///     )
+    /// );
+    /// let result = entity.place_asset_hold("hold_0001".to_string(), DEFAULT_ASSET, Amount::from_integer(40), 1, 3600);
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(entity.get_wallet().get_available_balance(2).unwrap(), Amount::from_integer(60))
+    /// ```
+    ///
+    pub fn place_asset_hold(
+        self: &mut Entity,
+        hold_id: String,
+        asset: &str,
+        amount: Amount,
+        timestamp: u64,
+        duration: u64,
+    ) -> Result<(), EntityError> {
+        match self.wallet.place_asset_hold(hold_id, asset, amount, timestamp, duration) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(EntityError::WalletOperationError { error: Box::new(error) })
+        }
+    }
+
+    ///
+    /// Release a hold of the wallet of the entity, so that its coins are available again.
+    ///
+    /// # Arguments
+    ///
+    /// * `hold_id` - A str that represents the id of the hold.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the release.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use banana_coin::model::{Amount, Entity, Wallet, DEFAULT_ASSET};
+    /// # let mut entity = Entity::new("id_0001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// # entity.place_asset_hold("hold_0001".to_string(), DEFAULT_ASSET, Amount::from_integer(40), 1, 3600).unwrap();
+    /// let result = entity.release_hold("hold_0001", 2);
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(entity.get_wallet().get_available_balance(2).unwrap(), Amount::from_integer(100))
+    /// ```
+    ///
+    pub fn release_hold(
+        self: &mut Entity,
+        hold_id: &str,
+        timestamp: u64,
+    ) -> Result<Hold, EntityError> {
+        match self.wallet.release_hold(hold_id, timestamp) {
+            Ok(hold) => Ok(hold),
+            Err(error) => Err(EntityError::WalletOperationError { error: Box::new(error) })
+        }
+    }
+
+    ///
+    /// Capture a hold of the wallet of the entity, removing the given amount of its coins and releasing the rest.
+    ///
+    /// # Arguments
+    ///
+    /// * `hold_id` - A str that represents the id of the hold.
+    /// * `asset` - A str that represents the code of the asset of the coins to remove.
+    /// * `amount` - An Amount that represents the amount of coins to remove, at most the amount of the hold.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the capture.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use banana_coin::model::{Amount, Entity, Wallet, DEFAULT_ASSET};
+    /// # let mut entity = Entity::new("id_0001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// # entity.place_asset_hold("hold_0001".to_string(), DEFAULT_ASSET, Amount::from_integer(40), 1, 3600).unwrap();
+    /// let result = entity.capture_hold("hold_0001", DEFAULT_ASSET, Amount::from_integer(40), 2);
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(entity.get_wallet().get_balance(), Amount::from_integer(60))
+    /// ```
+    ///
+    pub fn capture_hold(
+        self: &mut Entity,
+        hold_id: &str,
+        asset: &str,
+        amount: Amount,
+        timestamp: u64,
+    ) -> Result<Hold, EntityError> {
+        match self.wallet.capture_hold(hold_id, asset, amount, timestamp) {
+            Ok(hold) => Ok(hold),
+            Err(error) => Err(EntityError::WalletOperationError { error: Box::new(error) })
+        }
+    }
+
+    ///
+    /// Release the holds of the wallet of the entity expired at the given timestamp and return them.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - A u64 integer that represents the current timestamp.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use banana_coin::model::{Amount, Entity, Hold, Wallet, DEFAULT_ASSET};
+    /// # let mut entity = Entity::new("id_0001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// # entity.place_asset_hold("hold_0001".to_string(), DEFAULT_ASSET, Amount::from_integer(40), 1, 3600).unwrap();
+    /// let expired : Vec<Hold> = entity.expire_holds(3601);
+    /// # assert_eq!(expired.len(), 1)
+    /// ```
+    ///
+    pub fn expire_holds(
+        self: &mut Entity,
+        timestamp: u64,
+    ) -> Vec<Hold> {
+        self.wallet.expire_holds(timestamp)
+    }
+
+    ///
+    /// Return the public key of the entity, hex encoded, if the entity has one.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
+    /// let entity = Entity::new(
+    ///     "id_0001".to_string(),
+    ///     "john".to_string(),
+    ///     Wallet::new(
+    ///         Amount::from_integer(0)
+    ///     )
+    /// );
+    /// let public_key : Option<&str> = entity.get_public_key();
+    /// # assert!(public_key.is_none())
+    /// ```
+    ///
+    pub fn get_public_key(
+        self: &Entity,
+    ) -> Option<&str> {
+        self.public_key.as_deref()
+    }
+
+    ///
+    /// Set the public key of the entity, replacing the previous one.
+    ///
+    /// # Arguments
+    ///
+    /// * `public_key` - A String that represents the Ed25519 public key, hex encoded.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
+    /// let mut entity = Entity::new(
+    ///     "id_0001".to_string(),
+    ///     "john".to_string(),
+    ///     Wallet::new(
+    ///         Amount::from_integer(0)
+    ///     )
+    /// );
+    /// entity.set_public_key("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a".to_string());
+    /// # assert!(entity.get_public_key().is_some())
+    /// ```
+    ///
+    pub fn set_public_key(
+        self: &mut Entity,
+        public_key: String,
+    ) {
+        self.public_key = Some(public_key);
+    }
+
+    ///
+    /// Generate a new key pair for the entity. The entity keeps the public key,
+    /// the caller must keep the returned key pair to sign the transactions of the entity.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
+    /// let mut entity = Entity::new(
+    ///     "id_0001".to_string(),
+    ///     "john".to_string(),
+    ///     Wallet::new(
+    ///         Amount::from_integer(0)
+    ///     )
+    /// );
+    /// let key_pair = entity.generate_key_pair();
+    /// # assert_eq!(entity.get_public_key(), Some(key_pair.get_public_key().as_str()))
+    /// ```
+    ///
+    pub fn generate_key_pair(
+        self: &mut Entity,
+    ) -> KeyPair {
+        let key_pair = KeyPair::generate();
+        self.public_key = Some(key_pair.get_public_key());
+        key_pair
+    }
+
+    ///
+    /// Return the nonce the next transaction sent by the entity must carry.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
+    /// let entity = Entity::new(
+    ///     "id_0001".to_string(),
+    ///     "john".to_string(),
+    ///     Wallet::new(
+    ///         Amount::from_integer(0)
+    ///     )
+    /// );
+    /// let nonce : u64 = entity.get_nonce();
+    /// # assert_eq!(nonce, 0)
+    /// ```
+    ///
+    pub fn get_nonce(
+        self: &Entity,
+    ) -> u64 {
+        self.nonce
+    }
+
+    ///
+    /// Increment the nonce of the entity by one, after a transaction sent by the entity has been executed.
+    ///
+    /// # Errors
+    ///
+    /// Return a NonceOverflowError if the nonce is already the highest possible one.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
+    /// let mut entity = Entity::new(
+    ///     "id_0001".to_string(),
+    ///     "john".to_string(),
+    ///     Wallet::new(
+    ///         Amount::from_integer(0)
+    ///     )
+    /// );
+    /// entity.increment_nonce().unwrap();
+    /// # assert_eq!(entity.get_nonce(), 1)
+    /// ```
+    ///
+    pub fn increment_nonce(
+        self: &mut Entity,
+    ) -> Result<(), EntityError> {
+        match self.nonce.checked_add(1) {
+            Some(nonce) => {
+                self.nonce = nonce;
+                Ok(())
+            }
+            None => Err(EntityError::NonceOverflowError { nonce: self.nonce })
+        }
+    }
+}
@@ -0,0 +1,449 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{verify_signature, KeyPair};
+use crate::error::EscrowError;
+use crate::model::{Entity, Transaction, TransactionState};
+use crate::model::escrow::ReleaseCondition::*;
+
+/// Release condition enumeration
+/// It represents what must happen for the coins of an escrow to be released to the receiver of its transaction.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReleaseCondition {
+    /// The named entity, e.g. an arbiter of the marketplace, approves the release.
+    Approval { approver_id: String },
+    /// The deadline is reached.
+    Deadline { deadline: u64 },
+    /// Both the sender and the receiver of the transaction approve the release.
+    BothParties,
+}
+
+impl fmt::Display for ReleaseCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Approval { approver_id } => write!(f, "approval by {}", approver_id),
+            Deadline { deadline } => write!(f, "deadline at {}", deadline),
+            BothParties => write!(f, "approval by both parties"),
+        }
+    }
+}
+
+/// Escrow structure
+/// It represents the escrow of a transaction whose coins have left the sender for the escrow account but have not reached the receiver yet.
+/// The transaction stays in the Blocked state until its release condition is met and the coins are released
+/// to the receiver, completing it, or until the coins are refunded to the sender, failing it.
+/// The receiver can always give up the coins and refund the sender, and so can the approver of an Approval condition.
+/// An escrow is created by executing its transaction. It is deserialized only as an open escrow, whose transaction
+/// is valid and Blocked and whose approvals are given by entities allowed to approve it, so that it can be stored
+/// until it is released or refunded; the escrow account holding its coins is stored along with it.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[serde(try_from = "StoredEscrow")]
+pub struct Escrow {
+    transaction: Transaction,
+    condition: ReleaseCondition,
+    /// Ids of the entities that approved the release, with the timestamps of their approvals.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    approvals: BTreeMap<String, u64>,
+}
+
+/// Serialized form of an escrow, checked to be an open escrow when it is loaded.
+#[derive(Deserialize)]
+struct StoredEscrow {
+    transaction: Transaction,
+    condition: ReleaseCondition,
+    #[serde(default)]
+    approvals: BTreeMap<String, u64>,
+}
+
+impl TryFrom<StoredEscrow> for Escrow {
+    type Error = EscrowError;
+
+    fn try_from(
+        stored: StoredEscrow,
+    ) -> Result<Escrow, EscrowError> {
+        let transaction_id = stored.transaction.get_id();
+        stored.transaction
+            .validate()
+            .map_err(|error| EscrowError::InvalidTransactionError { transaction_id: transaction_id.clone(), error: Box::new(error) })?;
+        let current_state = stored.transaction.get_current_state();
+        if !matches!(current_state, TransactionState::Blocked { .. }) {
+            return Err(EscrowError::OpenEscrowStateError { transaction_id, current_state });
+        }
+        let escrow = Escrow::new(stored.transaction, stored.condition);
+        if let Some(entity_id) = stored.approvals.keys().find(|entity_id| !escrow.can_approve(entity_id)) {
+            return Err(EscrowError::UnauthorizedApprovalError {
+                transaction_id,
+                entity_id: entity_id.clone(),
+                condition: escrow.condition,
+            });
+        }
+        Ok(Escrow {
+            approvals: stored.approvals,
+            ..escrow
+        })
+    }
+}
+
+/// Prefix of the signing bytes of an approval, so that a signature can not be reused for other kinds of data.
+const APPROVAL_SIGNING_DOMAIN: &[u8] = b"banana_coin/escrow_approval/v1";
+
+/// Escrow implementation.
+impl Escrow {
+    /// Return a new Escrow on a transaction whose coins have been removed from the sender.
+    pub(crate) fn new(
+        transaction: Transaction,
+        condition: ReleaseCondition,
+    ) -> Escrow {
+        Escrow {
+            transaction,
+            condition,
+            approvals: BTreeMap::new(),
+        }
+    }
+
+    ///
+    /// Return a copy of the transaction of the escrow.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::ESCROW_ACCOUNT_ID;
+    /// # use banana_coin::model::{Amount, Entity, Escrow, ReleaseCondition, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let mut escrow_account = Entity::new(ESCROW_ACCOUNT_ID.to_string(), "escrow".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// # let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// # let mut transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// # let escrow = TransferExecutor::new().execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, ReleaseCondition::BothParties, 2).unwrap();
+    /// let transaction : Transaction = escrow.get_transaction();
+    /// # assert_eq!(transaction.get_current_state(), Blocked {timestamp: 2})
+    /// ```
+    ///
+    pub fn get_transaction(
+        self: &Escrow,
+    ) -> Transaction {
+        self.transaction.clone()
+    }
+
+    ///
+    /// Return the release condition of the escrow.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::ESCROW_ACCOUNT_ID;
+    /// # use banana_coin::model::{Amount, Entity, Escrow, ReleaseCondition, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let mut escrow_account = Entity::new(ESCROW_ACCOUNT_ID.to_string(), "escrow".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// # let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// # let mut transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// # let escrow = TransferExecutor::new().execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, ReleaseCondition::BothParties, 2).unwrap();
+    /// let condition : ReleaseCondition = escrow.get_condition();
+    /// # assert_eq!(condition, ReleaseCondition::BothParties)
+    /// ```
+    ///
+    pub fn get_condition(
+        self: &Escrow,
+    ) -> ReleaseCondition {
+        self.condition.clone()
+    }
+
+    ///
+    /// Return the ids of the entities that approved the release, with the timestamps of their approvals.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::collections::BTreeMap;
+    /// # use banana_coin::ledger::ESCROW_ACCOUNT_ID;
+    /// # use banana_coin::model::{Amount, Entity, Escrow, ReleaseCondition, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let mut escrow_account = Entity::new(ESCROW_ACCOUNT_ID.to_string(), "escrow".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// # let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// # let mut transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// # let escrow = TransferExecutor::new().execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, ReleaseCondition::BothParties, 2).unwrap();
+    /// let approvals : BTreeMap<String, u64> = escrow.get_approvals();
+    /// # assert!(approvals.is_empty())
+    /// ```
+    ///
+    pub fn get_approvals(
+        self: &Escrow,
+    ) -> BTreeMap<String, u64> {
+        self.approvals.clone()
+    }
+
+    ///
+    /// Return the canonical bytes an entity signs to approve the release of the escrow.
+    /// They are made of a domain prefix followed by the id of the transaction and the id of the approving entity,
+    /// each prefixed by its length as a big-endian u32.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A str that represents the id of the approving entity.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::ESCROW_ACCOUNT_ID;
+    /// # use banana_coin::model::{Amount, Entity, Escrow, ReleaseCondition, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let mut escrow_account = Entity::new(ESCROW_ACCOUNT_ID.to_string(), "escrow".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// # let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// # let mut transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// # let escrow = TransferExecutor::new().execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, ReleaseCondition::BothParties, 2).unwrap();
+    /// let signing_bytes : Vec<u8> = escrow.get_approval_signing_bytes("entity_001");
+    /// # assert_ne!(signing_bytes, escrow.get_approval_signing_bytes("entity_002"))
+    /// ```
+    ///
+    pub fn get_approval_signing_bytes(
+        self: &Escrow,
+        entity_id: &str,
+    ) -> Vec<u8> {
+        let mut bytes = APPROVAL_SIGNING_DOMAIN.to_vec();
+        for field in [self.transaction.get_id().as_str(), entity_id].iter() {
+            bytes.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(field.as_bytes());
+        }
+        bytes
+    }
+
+    ///
+    /// Sign the approval of the release of the escrow by the given entity and return the signature, hex encoded.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A str that represents the id of the approving entity.
+    /// * `key_pair` - A KeyPair that represents the key pair of the approving entity.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::crypto::{verify_signature, KeyPair};
+    /// # use banana_coin::ledger::ESCROW_ACCOUNT_ID;
+    /// # use banana_coin::model::{Amount, Entity, Escrow, ReleaseCondition, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let mut escrow_account = Entity::new(ESCROW_ACCOUNT_ID.to_string(), "escrow".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// # let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// # let mut transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// # let escrow = TransferExecutor::new().execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, ReleaseCondition::BothParties, 2).unwrap();
+    /// let key_pair = KeyPair::generate();
+    /// let signature : String = escrow.sign_approval("entity_001", &key_pair);
+    /// # assert!(verify_signature(&key_pair.get_public_key(), &escrow.get_approval_signing_bytes("entity_001"), &signature).is_ok())
+    /// ```
+    ///
+    pub fn sign_approval(
+        self: &Escrow,
+        entity_id: &str,
+        key_pair: &KeyPair,
+    ) -> String {
+        key_pair.sign(&self.get_approval_signing_bytes(entity_id))
+    }
+
+    ///
+    /// Record the approval of the release by the given entity, signed with its registered public key.
+    /// Only the approver of an Approval condition and the two parties of a BothParties condition can approve
+    /// the release; a Deadline condition needs no approval.
+    ///
+    /// # Arguments
+    ///
+    /// * `approver` - An Entity that represents the approving entity.
+    /// * `signature` - A str that represents the signature of the approval by the entity, hex encoded, see 'sign_approval'.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the approval.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::crypto::KeyPair;
+    /// # use banana_coin::ledger::ESCROW_ACCOUNT_ID;
+    /// # use banana_coin::model::{Amount, Entity, Escrow, ReleaseCondition, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let mut escrow_account = Entity::new(ESCROW_ACCOUNT_ID.to_string(), "escrow".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// # let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// # let mut transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// # let mut escrow = TransferExecutor::new().execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, ReleaseCondition::BothParties, 2).unwrap();
+    /// let key_pair = KeyPair::generate();
+    /// from_entity.set_public_key(key_pair.get_public_key());
+    /// let signature = escrow.sign_approval("entity_001", &key_pair);
+    /// let result = escrow.approve(&from_entity, &signature, 3);
+    /// # assert!(result.is_ok());
+    /// # assert!(!escrow.is_releasable(3));
+    /// # let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// # assert!(escrow.approve(&to_entity, &escrow.sign_approval("entity_002", &key_pair), 4).is_err());
+    /// # to_entity.set_public_key(key_pair.get_public_key());
+    /// # assert!(escrow.approve(&to_entity, &signature, 4).is_err());
+    /// # assert!(escrow.approve(&to_entity, &escrow.sign_approval("entity_002", &key_pair), 4).is_ok());
+    /// # assert!(escrow.is_releasable(4))
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A SettledEscrowError is returned if the escrow has already been released or refunded,
+    /// an UnauthorizedApprovalError if the entity can not approve the release, a DuplicateApprovalError
+    /// if the entity has already approved it, a MissingPublicKeyError if the entity has no registered public key
+    /// and a SignatureVerificationError if the signature does not match the approval and the key.
+    ///
+    pub fn approve(
+        self: &mut Escrow,
+        approver: &Entity,
+        signature: &str,
+        timestamp: u64,
+    ) -> Result<(), EscrowError> {
+        self.check_not_settled()?;
+        let entity_id = approver.get_id();
+        if !self.can_approve(entity_id) {
+            return Err(EscrowError::UnauthorizedApprovalError {
+                transaction_id: self.transaction.get_id(),
+                entity_id: entity_id.to_string(),
+                condition: self.condition.clone(),
+            });
+        }
+        if self.approvals.contains_key(entity_id) {
+            return Err(EscrowError::DuplicateApprovalError {
+                transaction_id: self.transaction.get_id(),
+                entity_id: entity_id.to_string(),
+            });
+        }
+        let public_key = approver
+            .get_public_key()
+            .ok_or_else(|| EscrowError::MissingPublicKeyError { entity_id: entity_id.to_string() })?;
+        verify_signature(public_key, &self.get_approval_signing_bytes(entity_id), signature)
+            .map_err(|error| EscrowError::SignatureVerificationError {
+                transaction_id: self.transaction.get_id(),
+                entity_id: entity_id.to_string(),
+                error,
+            })?;
+        self.approvals.insert(entity_id.to_string(), timestamp);
+        Ok(())
+    }
+
+    ///
+    /// Return whether the release condition of the escrow is met at the given timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - A u64 integer that represents the current timestamp.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::ESCROW_ACCOUNT_ID;
+    /// # use banana_coin::model::{Amount, Entity, Escrow, ReleaseCondition, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let mut escrow_account = Entity::new(ESCROW_ACCOUNT_ID.to_string(), "escrow".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// # let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// # let mut transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// # let condition = ReleaseCondition::Deadline { deadline: 3600 };
+    /// # let escrow = TransferExecutor::new().execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, condition, 2).unwrap();
+    /// let releasable : bool = escrow.is_releasable(3600);
+    /// # assert!(releasable);
+    /// # assert!(!escrow.is_releasable(3599))
+    /// ```
+    ///
+    pub fn is_releasable(
+        self: &Escrow,
+        timestamp: u64,
+    ) -> bool {
+        match &self.condition {
+            Approval { approver_id } => self.approvals.contains_key(approver_id),
+            Deadline { deadline } => timestamp >= *deadline,
+            BothParties => self.approvals.contains_key(&self.transaction.get_from_entity_id())
+                && self.approvals.contains_key(&self.transaction.get_to_entity_id()),
+        }
+    }
+
+    ///
+    /// Return whether the given entity can refund the coins of the escrow to the sender:
+    /// the receiver of the transaction and the approver of an Approval condition can.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A str that represents the id of the entity requesting the refund.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::ESCROW_ACCOUNT_ID;
+    /// # use banana_coin::model::{Amount, Entity, Escrow, ReleaseCondition, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let mut escrow_account = Entity::new(ESCROW_ACCOUNT_ID.to_string(), "escrow".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// # let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// # let mut transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// # let condition = ReleaseCondition::Approval { approver_id: "entity_003".to_string() };
+    /// # let escrow = TransferExecutor::new().execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, condition, 2).unwrap();
+    /// let refundable : bool = escrow.can_refund("entity_003");
+    /// # assert!(refundable);
+    /// # assert!(escrow.can_refund("entity_002"));
+    /// # assert!(!escrow.can_refund("entity_001"))
+    /// ```
+    ///
+    pub fn can_refund(
+        self: &Escrow,
+        entity_id: &str,
+    ) -> bool {
+        match &self.condition {
+            Approval { approver_id } if approver_id == entity_id => true,
+            _ => entity_id == self.transaction.get_to_entity_id(),
+        }
+    }
+
+    ///
+    /// Return whether the coins of the escrow have already been released or refunded.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::ESCROW_ACCOUNT_ID;
+    /// # use banana_coin::model::{Amount, Entity, Escrow, ReleaseCondition, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// # let mut escrow_account = Entity::new(ESCROW_ACCOUNT_ID.to_string(), "escrow".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// # let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// # let mut transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// # let escrow = TransferExecutor::new().execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, ReleaseCondition::BothParties, 2).unwrap();
+    /// let settled : bool = escrow.is_settled();
+    /// # assert!(!settled)
+    /// ```
+    ///
+    pub fn is_settled(
+        self: &Escrow,
+    ) -> bool {
+        self.transaction.get_current_state().is_final()
+    }
+
+    /// Return whether the given entity can approve the release of the escrow.
+    fn can_approve(
+        self: &Escrow,
+        entity_id: &str,
+    ) -> bool {
+        match &self.condition {
+            Approval { approver_id } => approver_id == entity_id,
+            Deadline { .. } => false,
+            BothParties => entity_id == self.transaction.get_from_entity_id()
+                || entity_id == self.transaction.get_to_entity_id(),
+        }
+    }
+
+    /// Return a SettledEscrowError if the coins of the escrow have already been released or refunded.
+    pub(crate) fn check_not_settled(
+        self: &Escrow,
+    ) -> Result<(), EscrowError> {
+        if self.is_settled() {
+            return Err(EscrowError::SettledEscrowError {
+                transaction_id: self.transaction.get_id(),
+                current_state: self.transaction.get_current_state(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Replace the transaction of the escrow once it has been released or refunded.
+    pub(crate) fn settle(
+        self: &mut Escrow,
+        transaction: Transaction,
+    ) {
+        self.transaction = transaction;
+    }
+}
@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::Amount;
+
+/// Hold structure
+/// It represents coins of a wallet earmarked for a transfer that has not settled yet, e.g. a card authorization.
+/// The held coins are still part of the balance of the wallet, but they can not be removed until the hold is
+/// captured, released or expired.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hold {
+    id: String,
+    asset: String,
+    amount: Amount,
+    placed_at: u64,
+    expires_at: u64,
+}
+
+/// Hold implementation.
+impl Hold {
+    ///
+    /// Return a new Hold.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - A String that represents the id of the hold.
+    /// * `asset` - A String that represents the code of the asset of the held coins.
+    /// * `amount` - An Amount that represents the amount of held coins.
+    /// * `placed_at` - A u64 integer that represents the timestamp at which the hold is placed.
+    /// * `expires_at` - A u64 integer that represents the timestamp from which the hold is expired.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::model::{Amount, Hold};
+    /// let hold = Hold::new(
+    ///     "hold_0001".to_string(),
+    ///     "BNC".to_string(),
+    ///     Amount::from_integer(10),
+    ///     1,
+    ///     3601
+    /// );
+    /// # assert_eq!(hold.get_id(), "hold_0001")
+    /// ```
+    ///
+    pub fn new(
+        id: String,
+        asset: String,
+        amount: Amount,
+        placed_at: u64,
+        expires_at: u64,
+    ) -> Hold {
+        Hold {
+            id,
+            asset,
+            amount,
+            placed_at,
+            expires_at,
+        }
+    }
+
+    ///
+    /// Return the id of the hold.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Hold};
+    /// # let hold = Hold::new("hold_0001".to_string(), "BNC".to_string(), Amount::from_integer(10), 1, 3601);
+    /// let id : &str = hold.get_id();
+    /// # assert_eq!(id, "hold_0001")
+    /// ```
+    ///
+    pub fn get_id(
+        self: &Hold,
+    ) -> &str {
+        &self.id
+    }
+
+    ///
+    /// Return the code of the asset of the held coins.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Hold};
+    /// # let hold = Hold::new("hold_0001".to_string(), "BNC".to_string(), Amount::from_integer(10), 1, 3601);
+    /// let asset : &str = hold.get_asset();
+    /// # assert_eq!(asset, "BNC")
+    /// ```
+    ///
+    pub fn get_asset(
+        self: &Hold,
+    ) -> &str {
+        &self.asset
+    }
+
+    ///
+    /// Return the amount of held coins.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Hold};
+    /// # let hold = Hold::new("hold_0001".to_string(), "BNC".to_string(), Amount::from_integer(10), 1, 3601);
+    /// let amount : Amount = hold.get_amount();
+    /// # assert_eq!(amount, Amount::from_integer(10))
+    /// ```
+    ///
+    pub fn get_amount(
+        self: &Hold,
+    ) -> Amount {
+        self.amount
+    }
+
+    ///
+    /// Return the timestamp at which the hold was placed.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Hold};
+    /// # let hold = Hold::new("hold_0001".to_string(), "BNC".to_string(), Amount::from_integer(10), 1, 3601);
+    /// let placed_at : u64 = hold.get_placed_at();
+    /// # assert_eq!(placed_at, 1)
+    /// ```
+    ///
+    pub fn get_placed_at(
+        self: &Hold,
+    ) -> u64 {
+        self.placed_at
+    }
+
+    ///
+    /// Return the timestamp from which the hold is expired.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Hold};
+    /// # let hold = Hold::new("hold_0001".to_string(), "BNC".to_string(), Amount::from_integer(10), 1, 3601);
+    /// let expires_at : u64 = hold.get_expires_at();
+    /// # assert_eq!(expires_at, 3601)
+    /// ```
+    ///
+    pub fn get_expires_at(
+        self: &Hold,
+    ) -> u64 {
+        self.expires_at
+    }
+
+    ///
+    /// Return whether the hold is expired at the given timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - A u64 integer that represents the current timestamp.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Hold};
+    /// # let hold = Hold::new("hold_0001".to_string(), "BNC".to_string(), Amount::from_integer(10), 1, 3601);
+    /// let expired : bool = hold.is_expired(3601);
+    /// # assert!(expired);
+    /// # assert!(!hold.is_expired(3600))
+    /// ```
+    ///
+    pub fn is_expired(
+        self: &Hold,
+        timestamp: u64,
+    ) -> bool {
+        timestamp >= self.expires_at
+    }
+}
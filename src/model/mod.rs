@@ -8,8 +8,32 @@ pub use transaction::*;
 
 /// Importing wallet module
 pub mod wallet;
-pub use wallet::Wallet;
+pub use wallet::{Wallet, DEFAULT_ASSET};
 
 /// Importing transaction state module
 pub mod transaction_state;
-pub use transaction_state::*;
\ No newline at end of file
+pub use transaction_state::*;
+
+/// Importing posting module
+pub mod posting;
+pub use posting::*;
+
+/// Importing amount module
+pub mod amount;
+pub use amount::*;
+
+/// Importing asset module
+pub mod asset;
+pub use asset::*;
+
+/// Importing overdraft policy module
+pub mod overdraft_policy;
+pub use overdraft_policy::*;
+
+/// Importing hold module
+pub mod hold;
+pub use hold::*;
+
+/// Importing escrow module
+pub mod escrow;
+pub use escrow::*;
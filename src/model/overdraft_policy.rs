@@ -0,0 +1,202 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::Amount;
+use crate::model::overdraft_policy::OverdraftPolicy::*;
+
+/// Overdraft policy enumeration
+/// It represents how far below zero the balances of a wallet are allowed to go.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverdraftPolicy {
+    /// The balances can not go below zero.
+    #[default]
+    NoOverdraft,
+    /// The balances can go below zero without any limit.
+    Unlimited,
+    /// The balances can go below zero down to the floor, which is zero or negative.
+    Limit { floor: Amount },
+}
+
+/// Overdraft policy implementation.
+impl OverdraftPolicy {
+    ///
+    /// Return the lowest balance allowed by the policy, or None if the balance is unlimited.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::model::{Amount, OverdraftPolicy};
+    /// let policy = OverdraftPolicy::Limit { floor: Amount::from_integer(-50) };
+    /// let floor : Option<Amount> = policy.get_floor();
+    /// # assert_eq!(floor, Some(Amount::from_integer(-50)));
+    /// # assert_eq!(OverdraftPolicy::NoOverdraft.get_floor(), Some(Amount::zero()));
+    /// # assert_eq!(OverdraftPolicy::Unlimited.get_floor(), None)
+    /// ```
+    ///
+    pub fn get_floor(
+        self: &OverdraftPolicy,
+    ) -> Option<Amount> {
+        match self {
+            NoOverdraft => Some(Amount::zero()),
+            Unlimited => None,
+            Limit { floor } => Some(*floor),
+        }
+    }
+
+    ///
+    /// Return whether the policy allows a wallet to hold the given balance.
+    ///
+    /// # Arguments
+    ///
+    /// * `balance` - An Amount that represents the balance to check.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::model::{Amount, OverdraftPolicy};
+    /// let policy = OverdraftPolicy::Limit { floor: Amount::from_integer(-50) };
+    /// let allowed : bool = policy.allows(Amount::from_integer(-50));
+    /// # assert!(allowed);
+    /// # assert!(!policy.allows(Amount::from_integer(-51)));
+    /// # assert!(!OverdraftPolicy::NoOverdraft.allows(Amount::from_integer(-1)));
+    /// # assert!(OverdraftPolicy::Unlimited.allows(Amount::from_integer(-1)))
+    /// ```
+    ///
+    pub fn allows(
+        self: &OverdraftPolicy,
+        balance: Amount,
+    ) -> bool {
+        match self.get_floor() {
+            Some(floor) => balance >= floor,
+            None => true,
+        }
+    }
+}
+
+impl fmt::Display for OverdraftPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NoOverdraft => write!(f, "no overdraft"),
+            Unlimited => write!(f, "unlimited overdraft"),
+            Limit { floor } => write!(f, "overdraft down to {}", floor),
+        }
+    }
+}
+
+/// Overdraft policy change structure
+/// It records a change of the overdraft policy of a wallet: who made it, when, and the policies before and after it.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverdraftPolicyChange {
+    previous_policy: OverdraftPolicy,
+    new_policy: OverdraftPolicy,
+    changed_by: String,
+    timestamp: u64,
+}
+
+/// Overdraft policy change implementation.
+impl OverdraftPolicyChange {
+    ///
+    /// Return a new OverdraftPolicyChange.
+    ///
+    /// # Arguments
+    ///
+    /// * `previous_policy` - An OverdraftPolicy that represents the policy before the change.
+    /// * `new_policy` - An OverdraftPolicy that represents the policy after the change.
+    /// * `changed_by` - A String that represents who changed the policy.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the change.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::model::{OverdraftPolicy, OverdraftPolicyChange};
+    /// let change = OverdraftPolicyChange::new(
+    ///     OverdraftPolicy::NoOverdraft,
+    ///     OverdraftPolicy::Unlimited,
+    ///     "admin".to_string(),
+    ///     1
+    /// );
+    /// # assert_eq!(change.get_new_policy(), OverdraftPolicy::Unlimited)
+    /// ```
+    ///
+    pub fn new(
+        previous_policy: OverdraftPolicy,
+        new_policy: OverdraftPolicy,
+        changed_by: String,
+        timestamp: u64,
+    ) -> OverdraftPolicyChange {
+        OverdraftPolicyChange {
+            previous_policy,
+            new_policy,
+            changed_by,
+            timestamp,
+        }
+    }
+
+    ///
+    /// Return the policy before the change.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{OverdraftPolicy, OverdraftPolicyChange};
+    /// # let change = OverdraftPolicyChange::new(OverdraftPolicy::NoOverdraft, OverdraftPolicy::Unlimited, "admin".to_string(), 1);
+    /// let previous_policy : OverdraftPolicy = change.get_previous_policy();
+    /// # assert_eq!(previous_policy, OverdraftPolicy::NoOverdraft)
+    /// ```
+    ///
+    pub fn get_previous_policy(
+        self: &OverdraftPolicyChange,
+    ) -> OverdraftPolicy {
+        self.previous_policy
+    }
+
+    ///
+    /// Return the policy after the change.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{OverdraftPolicy, OverdraftPolicyChange};
+    /// # let change = OverdraftPolicyChange::new(OverdraftPolicy::NoOverdraft, OverdraftPolicy::Unlimited, "admin".to_string(), 1);
+    /// let new_policy : OverdraftPolicy = change.get_new_policy();
+    /// # assert_eq!(new_policy, OverdraftPolicy::Unlimited)
+    /// ```
+    ///
+    pub fn get_new_policy(
+        self: &OverdraftPolicyChange,
+    ) -> OverdraftPolicy {
+        self.new_policy
+    }
+
+    ///
+    /// Return who changed the policy.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{OverdraftPolicy, OverdraftPolicyChange};
+    /// # let change = OverdraftPolicyChange::new(OverdraftPolicy::NoOverdraft, OverdraftPolicy::Unlimited, "admin".to_string(), 1);
+    /// let changed_by : &str = change.get_changed_by();
+    /// # assert_eq!(changed_by, "admin")
+    /// ```
+    ///
+    pub fn get_changed_by(
+        self: &OverdraftPolicyChange,
+    ) -> &str {
+        &self.changed_by
+    }
+
+    ///
+    /// Return the timestamp of the change.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{OverdraftPolicy, OverdraftPolicyChange};
+    /// # let change = OverdraftPolicyChange::new(OverdraftPolicy::NoOverdraft, OverdraftPolicy::Unlimited, "admin".to_string(), 1);
+    /// let timestamp : u64 = change.get_timestamp();
+    /// # assert_eq!(timestamp, 1)
+    /// ```
+    ///
+    pub fn get_timestamp(
+        self: &OverdraftPolicyChange,
+    ) -> u64 {
+        self.timestamp
+    }
+}
@@ -0,0 +1,218 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::Amount;
+use crate::model::wallet::default_asset;
+
+/// Posting side enumeration
+/// It represents the direction of a posting: a Debit withdraws coins from the wallet of an entity,
+/// a Credit deposits coins into the wallet of an entity.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PostingSide {
+    Debit,
+    Credit,
+}
+
+/// Posting structure
+/// It represents one side of a balance change of the wallet of an entity. It is tied to the transaction that generated it.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Posting {
+    transaction_id: String,
+    entity_id: String,
+    side: PostingSide,
+    amount: Amount,
+    /// Code of the asset of the coins. Postings recorded before the introduction of the assets are in the default asset.
+    #[serde(default = "default_asset")]
+    asset: String,
+}
+
+/// Posting implementation.
+impl Posting {
+    ///
+    /// Return a new Posting in the default asset.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_id` - A String that represents the id of the transaction that generated the posting.
+    /// * `entity_id` - A String that represents the id of the entity whose wallet is changed.
+    /// * `side` - A PostingSide that represents whether the coins are withdrawn or deposited.
+    /// * `amount` - An Amount that represents the amount of coins of the posting.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::model::{Amount, Posting, PostingSide};
+    /// let posting = Posting::new(
+    ///     "id_0001".to_string(),
+    ///     "entity_001".to_string(),
+    ///     PostingSide::Debit,
+    ///     Amount::from_integer(100)
+    /// );
+    /// # assert_eq!(posting.get_transaction_id(), "id_0001");
+    /// # assert_eq!(posting.get_entity_id(), "entity_001");
+    /// # assert_eq!(posting.get_side(), PostingSide::Debit);
+    /// # assert_eq!(posting.get_amount(), Amount::from_integer(100))
+    /// ```
+    ///
+    pub fn new(
+        transaction_id: String,
+        entity_id: String,
+        side: PostingSide,
+        amount: Amount,
+    ) -> Posting {
+        Posting {
+            transaction_id,
+            entity_id,
+            side,
+            amount,
+            asset: default_asset(),
+        }
+    }
+
+    ///
+    /// Return a new Posting in the given asset.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_id` - A String that represents the id of the transaction that generated the posting.
+    /// * `entity_id` - A String that represents the id of the entity whose wallet is changed.
+    /// * `asset` - A String that represents the code of the asset of the coins.
+    /// * `side` - A PostingSide that represents whether the coins are withdrawn or deposited.
+    /// * `amount` - An Amount that represents the amount of coins of the posting.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::model::{Amount, Posting, PostingSide};
+    /// let posting = Posting::new_for_asset(
+    ///     "id_0001".to_string(),
+    ///     "entity_001".to_string(),
+    ///     "GOLD".to_string(),
+    ///     PostingSide::Credit,
+    ///     Amount::from_integer(100)
+    /// );
+    /// # assert_eq!(posting.get_asset(), "GOLD");
+    /// # assert_eq!(posting.get_amount(), Amount::from_integer(100))
+    /// ```
+    ///
+    pub fn new_for_asset(
+        transaction_id: String,
+        entity_id: String,
+        asset: String,
+        side: PostingSide,
+        amount: Amount,
+    ) -> Posting {
+        Posting {
+            transaction_id,
+            entity_id,
+            side,
+            amount,
+            asset,
+        }
+    }
+
+    ///
+    /// Return the id of the transaction that generated the posting.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Posting, PostingSide};
+    /// # let posting = Posting::new("id_0001".to_string(), "entity_001".to_string(), PostingSide::Debit, Amount::from_integer(100));
+    /// let transaction_id : &str = posting.get_transaction_id();
+    /// # assert_eq!(transaction_id, "id_0001")
+    /// ```
+    ///
+    pub fn get_transaction_id(
+        self: &Posting,
+    ) -> &str {
+        &self.transaction_id
+    }
+
+    ///
+    /// Return the id of the entity whose wallet is changed by the posting.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Posting, PostingSide};
+    /// # let posting = Posting::new("id_0001".to_string(), "entity_001".to_string(), PostingSide::Debit, Amount::from_integer(100));
+    /// let entity_id : &str = posting.get_entity_id();
+    /// # assert_eq!(entity_id, "entity_001")
+    /// ```
+    ///
+    pub fn get_entity_id(
+        self: &Posting,
+    ) -> &str {
+        &self.entity_id
+    }
+
+    ///
+    /// Return the side of the posting.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Posting, PostingSide};
+    /// # let posting = Posting::new("id_0001".to_string(), "entity_001".to_string(), PostingSide::Debit, Amount::from_integer(100));
+    /// let side : PostingSide = posting.get_side();
+    /// # assert_eq!(side, PostingSide::Debit)
+    /// ```
+    ///
+    pub fn get_side(
+        self: &Posting,
+    ) -> PostingSide {
+        self.side
+    }
+
+    ///
+    /// Return the amount of coins of the posting.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Posting, PostingSide};
+    /// # let posting = Posting::new("id_0001".to_string(), "entity_001".to_string(), PostingSide::Debit, Amount::from_integer(100));
+    /// let amount : Amount = posting.get_amount();
+    /// # assert_eq!(amount, Amount::from_integer(100))
+    /// ```
+    ///
+    pub fn get_amount(
+        self: &Posting,
+    ) -> Amount {
+        self.amount
+    }
+
+    ///
+    /// Return the code of the asset of the coins of the posting.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Posting, PostingSide, DEFAULT_ASSET};
+    /// # let posting = Posting::new("id_0001".to_string(), "entity_001".to_string(), PostingSide::Debit, Amount::from_integer(100));
+    /// let asset : &str = posting.get_asset();
+    /// # assert_eq!(asset, DEFAULT_ASSET)
+    /// ```
+    ///
+    pub fn get_asset(
+        self: &Posting,
+    ) -> &str {
+        &self.asset
+    }
+
+    ///
+    /// Return the change of balance of the posting: negative for a Debit, positive for a Credit.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Posting, PostingSide};
+    /// let posting = Posting::new("id_0001".to_string(), "entity_001".to_string(), PostingSide::Debit, Amount::from_integer(100));
+    /// let signed_amount : Amount = posting.get_signed_amount();
+    /// # assert_eq!(signed_amount, Amount::from_integer(-100))
+    /// ```
+    ///
+    pub fn get_signed_amount(
+        self: &Posting,
+    ) -> Amount {
+        match self.side {
+            // The amount of a posting is never negative, so its opposite is always in range
+            PostingSide::Debit => self.amount.checked_neg().unwrap_or_default(),
+            PostingSide::Credit => self.amount,
+        }
+    }
+}
@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
+use crate::crypto::{verify_signature, KeyPair};
+use crate::error::{CryptoError, TransactionError};
 use crate::model::transaction_state::TransactionState;
+use crate::model::transaction_state::TransactionState::*;
+use crate::model::{Amount, AssetDefinition, AssetRegistry};
+use crate::model::wallet::default_asset;
 
 /// Transaction structure
 /// It represents a transaction between two entities. It is identified by an identifier 'id'.
@@ -9,23 +14,41 @@ pub struct Transaction {
     id: String,
     from_entity_id: String,
     to_entity_id: String,
-    amount: u32,
+    amount: Amount,
     description: String,
     current_state: TransactionState,
     state_history: Vec<TransactionState>,
+    /// Hex encoded Ed25519 signature of the signing bytes of the transaction, if it is signed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+    /// Nonce of the sender when the transaction was created, so that the transaction can be executed only once.
+    #[serde(default)]
+    nonce: u64,
+    /// Coins paid by the sender, on top of the amount, to the entity collecting the fees, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fee: Option<Amount>,
+    /// Code of the asset moved by the transaction. Transactions stored before the introduction
+    /// of the assets move the default asset.
+    #[serde(default = "default_asset")]
+    asset: String,
 }
 
+/// Prefix of the signing bytes of a transaction, so that a signature can not be reused for other kinds of data.
+const SIGNING_DOMAIN: &[u8] = b"banana_coin/transaction/v5";
+
 /// Transaction implementation.
 impl Transaction {
     ///
-    /// Return a new Transaction.
+    /// Return a new Transaction moving the default asset.
+    /// The state history must start with a Created state, follow the legal transitions between states
+    /// with timestamps that never decrease, and end with the current state.
     ///
     /// # Arguments
     ///
     /// * `id` - A String that represents the id of the transaction.
     /// * `from_entity_id` - A String that represents the id of the entity from which the coins are withdrawn.
     /// * `to_entity_id` - A String that represents the id of the entity to which the coins are deposited.
-    /// * `amount`: A i64 integer that represents the amount of coins transferred from 'from_entity_id' to 'to_entity_id' in the transaction.
+    /// * `amount`: An Amount that represents the amount of coins transferred from 'from_entity_id' to 'to_entity_id' in the transaction.
     /// * `description` A String that represents the textual description of the transaction.
     /// * `current_state` A TransactionState that represents the current state of the transaction.
     /// * `state_history`: A Vec<TransactionState> that represents the history of the states of the transaction.
@@ -40,30 +63,41 @@ impl Transaction {
     ///     "id_0001".to_string(),
     ///     "entity_001".to_string(),
     ///     "entity_002".to_string(),
-    ///     100,
+    ///     Amount::from_integer(100),
     ///     "Moving 100 coins from entity identified by 'entity_001' to entity identified by 'entity_002'.".to_string(),
     ///     Completed{timestamp: 7},
     ///     history
     ///     );
     ///
+    /// # Errors
+    ///
+    /// A TransactionError is returned if the state history is not a valid history for the current state,
+    /// if the amount is negative or if it has more decimal places than the default asset.
+    ///
     pub fn new(
         id: String,
         from_entity_id: String,
         to_entity_id: String,
-        amount: u32,
+        amount: Amount,
         description: String,
         current_state: TransactionState,
         state_history: Vec<TransactionState>,
-    ) -> Transaction {
-        Transaction {
-            id: id,
-            from_entity_id: from_entity_id,
-            to_entity_id: to_entity_id,
-            amount: amount,
-            description: description,
-            current_state: current_state,
-            state_history: state_history,
-        }
+    ) -> Result<Transaction, TransactionError> {
+        let transaction = Transaction {
+            id,
+            from_entity_id,
+            to_entity_id,
+            amount,
+            description,
+            current_state,
+            state_history,
+            signature: None,
+            nonce: 0,
+            fee: None,
+            asset: default_asset(),
+        };
+        transaction.validate()?;
+        Ok(transaction)
     }
 
     ///
@@ -76,7 +110,7 @@ impl Transaction {
     pub fn get_id(
         self: &Transaction
     ) -> String {
-        return self.id.clone();
+        self.id.clone()
     }
 
     ///
@@ -89,7 +123,7 @@ impl Transaction {
     pub fn get_from_entity_id(
         self: &Transaction
     ) -> String {
-        return self.from_entity_id.clone();
+        self.from_entity_id.clone()
     }
 
     ///
@@ -102,7 +136,7 @@ impl Transaction {
     pub fn get_to_entity_id(
         self: &Transaction
     ) -> String {
-        return self.to_entity_id.clone();
+        self.to_entity_id.clone()
     }
 
     ///
@@ -114,8 +148,8 @@ impl Transaction {
     ///
     pub fn get_amount(
         self: &Transaction
-    ) -> u32 {
-        return self.amount.clone();
+    ) -> Amount {
+        self.amount
     }
 
     ///
@@ -128,7 +162,7 @@ impl Transaction {
     pub fn get_description(
         self: &Transaction
     ) -> String {
-        return self.description.clone();
+        self.description.clone()
     }
 
     ///
@@ -141,7 +175,7 @@ impl Transaction {
     pub fn get_current_state(
         self: &Transaction
     ) -> TransactionState {
-        return self.current_state.clone();
+        self.current_state.clone()
     }
 
     ///
@@ -154,7 +188,421 @@ impl Transaction {
     pub fn get_state_history(
         self: &Transaction
     ) -> Vec<TransactionState> {
-        return self.state_history.clone();
+        self.state_history.clone()
+    }
+
+    ///
+    /// Return the timestamp at which the transaction has been created, i.e. the timestamp of the first state of its history.
+    ///
+    /// # Examples
+    ///
+    /// let transaction_creation_timestamp: u64 = transaction.get_creation_timestamp();
+    ///
+    pub fn get_creation_timestamp(
+        self: &Transaction
+    ) -> u64 {
+        self.state_history
+            .first()
+            .unwrap_or(&self.current_state)
+            .get_timestamp()
+    }
+
+    ///
+    /// Return the signature of the transaction, hex encoded, if it is signed.
+    ///
+    /// # Examples
+    ///
+    /// let transaction_signature: Option<String> = transaction.get_signature();
+    ///
+    pub fn get_signature(
+        self: &Transaction
+    ) -> Option<String> {
+        self.signature.clone()
+    }
+
+    ///
+    /// Return the nonce of the sender carried by the transaction.
+    ///
+    /// # Examples
+    ///
+    /// let transaction_nonce: u64 = transaction.get_nonce();
+    ///
+    pub fn get_nonce(
+        self: &Transaction
+    ) -> u64 {
+        self.nonce
+    }
+
+    ///
+    /// Set the nonce of the sender carried by the transaction. It must be set before signing the transaction,
+    /// since the nonce is part of the signed bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `nonce` - A u64 integer that represents the nonce of the sender, see Entity::get_nonce.
+    ///
+    /// # Examples
+    ///
+    /// transaction.set_nonce(entity.get_nonce());
+    ///
+    pub fn set_nonce(
+        self: &mut Transaction,
+        nonce: u64,
+    ) {
+        self.nonce = nonce;
+    }
+
+    ///
+    /// Return the fee of the transaction, if it has one.
+    ///
+    /// # Examples
+    ///
+    /// let transaction_fee: Option<Amount> = transaction.get_fee();
+    ///
+    pub fn get_fee(
+        self: &Transaction
+    ) -> Option<Amount> {
+        self.fee
+    }
+
+    ///
+    /// Set the fee of the transaction. It must be set before signing the transaction,
+    /// since the fee is part of the signed bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `fee` - An Amount that represents the coins paid by the sender on top of the amount.
+    ///
+    /// # Examples
+    ///
+    /// let result: Result<(), TransactionError> = transaction.set_fee(Amount::parse("0.05").unwrap());
+    ///
+    /// # Errors
+    ///
+    /// A NegativeAmountError is returned if the fee is negative, an AssetPrecisionError if it has more decimal places
+    /// than the asset of the transaction and an AmountOverflowError if the amount plus the fee overflows.
+    /// In all cases the transaction is left untouched.
+    ///
+    pub fn set_fee(
+        self: &mut Transaction,
+        fee: Amount,
+    ) -> Result<(), TransactionError> {
+        if fee.is_negative() {
+            return Err(TransactionError::NegativeAmountError { amount: fee });
+        }
+        Transaction::check_amount_precision(&AssetDefinition::default_for(&self.asset), fee)?;
+        match self.amount.checked_add(fee) {
+            Some(_) => {
+                self.fee = Some(fee);
+                Ok(())
+            }
+            None => Err(TransactionError::AmountOverflowError { amount: self.amount, fee })
+        }
+    }
+
+    ///
+    /// Return the code of the asset moved by the transaction.
+    ///
+    /// # Examples
+    ///
+    /// let transaction_asset: String = transaction.get_asset();
+    ///
+    pub fn get_asset(
+        self: &Transaction
+    ) -> String {
+        self.asset.clone()
+    }
+
+    ///
+    /// Set the code of the asset moved by the transaction. It must be set before signing the transaction,
+    /// since the asset is part of the signed bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `asset` - A String that represents the code of the asset.
+    ///
+    /// # Examples
+    ///
+    /// let result: Result<(), TransactionError> = transaction.set_asset("GOLD".to_string());
+    ///
+    /// # Errors
+    ///
+    /// An EmptyAssetError is returned if the asset code is empty and an AssetPrecisionError if the amount
+    /// or the fee has more decimal places than the asset. In both cases the transaction is left untouched.
+    ///
+    pub fn set_asset(
+        self: &mut Transaction,
+        asset: String,
+    ) -> Result<(), TransactionError> {
+        if asset.is_empty() {
+            return Err(TransactionError::EmptyAssetError);
+        }
+        for amount in [Some(self.amount), self.fee].iter().flatten() {
+            Transaction::check_amount_precision(&AssetDefinition::default_for(&asset), *amount)?;
+        }
+        self.asset = asset;
+        Ok(())
+    }
+
+    ///
+    /// Return the coins debited from the sender: the amount plus the fee, if any.
+    ///
+    /// # Examples
+    ///
+    /// let total_amount: Result<Amount, TransactionError> = transaction.get_total_amount();
+    ///
+    /// # Errors
+    ///
+    /// An AmountOverflowError is returned if the amount plus the fee overflows.
+    ///
+    pub fn get_total_amount(
+        self: &Transaction
+    ) -> Result<Amount, TransactionError> {
+        let fee = self.fee.unwrap_or_default();
+        self.amount
+            .checked_add(fee)
+            .ok_or(TransactionError::AmountOverflowError { amount: self.amount, fee })
+    }
+
+    ///
+    /// Return the canonical bytes of the transaction that are signed.
+    /// They are made of a domain prefix followed by the id, the sender id, the receiver id, the description,
+    /// the asset, the amount and the fee, "0" if there is none, each prefixed by its length as a big-endian u32,
+    /// and by the nonce as a big-endian u64. The amounts are written as text, e.g. "12.345".
+    /// The states are not signed, since they change while the transaction is processed.
+    ///
+    /// # Examples
+    ///
+    /// let signing_bytes: Vec<u8> = transaction.get_signing_bytes();
+    ///
+    pub fn get_signing_bytes(
+        self: &Transaction
+    ) -> Vec<u8> {
+        let mut bytes = SIGNING_DOMAIN.to_vec();
+        let amount = self.amount.to_string();
+        let fee = self.fee.unwrap_or_default().to_string();
+        for field in [&self.id, &self.from_entity_id, &self.to_entity_id, &self.description, &self.asset, &amount, &fee].iter() {
+            bytes.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(field.as_bytes());
+        }
+        bytes.extend_from_slice(&self.nonce.to_be_bytes());
+        bytes
+    }
+
+    ///
+    /// Sign the transaction with the key pair of the sender, replacing the previous signature.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_pair` - A KeyPair that represents the key pair of the entity identified by 'from_entity_id'.
+    ///
+    /// # Examples
+    ///
+    /// transaction.sign(&key_pair);
+    ///
+    pub fn sign(
+        self: &mut Transaction,
+        key_pair: &KeyPair,
+    ) {
+        self.signature = Some(key_pair.sign(&self.get_signing_bytes()));
+    }
+
+    ///
+    /// Set the signature of the transaction, replacing the previous one. It is useful for transactions
+    /// signed elsewhere, on the signing bytes of the transaction, see 'get_signing_bytes'.
+    ///
+    /// # Arguments
+    ///
+    /// * `signature` - A String that represents the Ed25519 signature of the sender, hex encoded.
+    ///
+    /// # Examples
+    ///
+    /// transaction.set_signature(signature);
+    ///
+    pub fn set_signature(
+        self: &mut Transaction,
+        signature: String,
+    ) {
+        self.signature = Some(signature);
+    }
+
+    ///
+    /// Verify the signature of the transaction against a public key.
+    ///
+    /// # Arguments
+    ///
+    /// * `public_key` - A str that represents the public key of the sender, hex encoded.
+    ///
+    /// # Examples
+    ///
+    /// let result: Result<(), CryptoError> = transaction.verify_signature(entity.get_public_key().unwrap());
+    ///
+    /// # Errors
+    ///
+    /// A MissingSignatureError is returned if the transaction is not signed,
+    /// a SignatureMismatchError if the signature does not match the transaction and the public key.
+    ///
+    pub fn verify_signature(
+        self: &Transaction,
+        public_key: &str,
+    ) -> Result<(), CryptoError> {
+        match &self.signature {
+            Some(signature) => verify_signature(public_key, &self.get_signing_bytes(), signature),
+            None => Err(CryptoError::MissingSignatureError),
+        }
+    }
+
+    ///
+    /// Check that the state history of the transaction is consistent, that the asset code is not empty,
+    /// that the amount and the fee are not negative, that they fit the decimal places of the default definition of the asset
+    /// and that the amount plus the fee does not overflow.
+    /// It is useful for transactions that have not been built through 'new', e.g. deserialized ones.
+    ///
+    /// # Examples
+    ///
+    /// let result: Result<(), TransactionError> = transaction.validate();
+    ///
+    pub fn validate(
+        self: &Transaction
+    ) -> Result<(), TransactionError> {
+        let first_state = match self.state_history.first() {
+            Some(state) => state,
+            None => return Err(TransactionError::EmptyStateHistoryError),
+        };
+        if !matches!(first_state, Created { .. }) {
+            return Err(TransactionError::InvalidInitialStateError {
+                state: first_state.clone(),
+            });
+        }
+        for states in self.state_history.windows(2) {
+            Transaction::check_transition(&states[0], &states[1])?;
+        }
+        let last_state = &self.state_history[self.state_history.len() - 1];
+        if *last_state != self.current_state {
+            return Err(TransactionError::CurrentStateMismatchError {
+                current_state: self.current_state.clone(),
+                last_state: last_state.clone(),
+            });
+        }
+        if self.asset.is_empty() {
+            return Err(TransactionError::EmptyAssetError);
+        }
+        for amount in [Some(self.amount), self.fee].iter().flatten() {
+            if amount.is_negative() {
+                return Err(TransactionError::NegativeAmountError { amount: *amount });
+            }
+            Transaction::check_amount_precision(&AssetDefinition::default_for(&self.asset), *amount)?;
+        }
+        self.get_total_amount()?;
+        Ok(())
+    }
+
+    ///
+    /// Check that the amount and the fee of the transaction fit the decimal places of its asset in the given registry.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - An AssetRegistry that represents the definitions of the assets.
+    ///
+    /// # Examples
+    ///
+    /// let result: Result<(), TransactionError> = transaction.check_precision(&registry);
+    ///
+    /// # Errors
+    ///
+    /// An AssetPrecisionError is returned if the amount or the fee has more decimal places than the asset.
+    ///
+    pub fn check_precision(
+        self: &Transaction,
+        registry: &AssetRegistry,
+    ) -> Result<(), TransactionError> {
+        let definition = registry.get_definition(&self.asset);
+        for amount in [Some(self.amount), self.fee].iter().flatten() {
+            Transaction::check_amount_precision(&definition, *amount)?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Move the transaction to a new state.
+    /// The new state becomes the current state and it is appended to the history of the states.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - A TransactionState that represents the new state of the transaction.
+    ///
+    /// # Examples
+    ///
+    /// let result: Result<(), TransactionError> = transaction.transition_to(OnProcess {timestamp: 4});
+    ///
+    /// # Errors
+    ///
+    /// A TransactionError is returned, and the transaction is left untouched, if the transition is
+    /// not legal or if the timestamp of the new state is lower than the timestamp of the current state.
+    ///
+    pub fn transition_to(
+        self: &mut Transaction,
+        state: TransactionState,
+    ) -> Result<(), TransactionError> {
+        Transaction::check_transition(&self.current_state, &state)?;
+        self.current_state = state.clone();
+        self.state_history.push(state);
+        Ok(())
+    }
+
+    ///
+    /// Return a copy of the transaction as it was when it reached the state at the given position of its history.
+    /// A position beyond the history gives a copy of the transaction as it is.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - A usize integer that represents the position of the state in the history, starting from 0.
+    ///
+    pub(crate) fn at_state(
+        self: &Transaction,
+        position: usize,
+    ) -> Transaction {
+        let mut transaction = self.clone();
+        transaction.state_history.truncate(position + 1);
+        if let Some(state) = transaction.state_history.last() {
+            transaction.current_state = state.clone();
+        }
+        transaction
+    }
+
+    /// Check that an amount fits the decimal places of the definition of an asset.
+    fn check_amount_precision(
+        definition: &AssetDefinition,
+        amount: Amount,
+    ) -> Result<(), TransactionError> {
+        if !definition.fits(amount) {
+            return Err(TransactionError::AssetPrecisionError {
+                asset: definition.get_code().to_string(),
+                amount,
+                decimals: definition.get_decimals(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Check that a transaction can move from a state to the next one.
+    fn check_transition(
+        from_state: &TransactionState,
+        to_state: &TransactionState,
+    ) -> Result<(), TransactionError> {
+        if !from_state.can_transition_to(to_state) {
+            return Err(TransactionError::IllegalTransitionError {
+                from_state: from_state.clone(),
+                to_state: to_state.clone(),
+            });
+        }
+        if to_state.get_timestamp() < from_state.get_timestamp() {
+            return Err(TransactionError::DecreasingTimestampError {
+                from_state: from_state.clone(),
+                to_state: to_state.clone(),
+            });
+        }
+        Ok(())
     }
 
 }
\ No newline at end of file
@@ -25,8 +25,130 @@ impl PartialEq for TransactionState {
             _ => false,
         }
     }
+}
+
+/// Transaction state implementation.
+impl TransactionState {
+    ///
+    /// Return the timestamp associated to the state.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::model::TransactionState::*;
+    /// let state = OnProcess {timestamp: 4};
+    /// let timestamp : u64 = state.get_timestamp();
+    /// # assert_eq!(timestamp, 4)
+    /// ```
+    ///
+    pub fn get_timestamp(
+        self: &TransactionState,
+    ) -> u64 {
+        match self {
+            Created { timestamp } => *timestamp,
+            OnProcess { timestamp } => *timestamp,
+            Completed { timestamp } => *timestamp,
+            Failed { timestamp } => *timestamp,
+            Blocked { timestamp } => *timestamp,
+        }
+    }
 
-    fn ne(&self, other: &Self) -> bool {
-        !self.eq(other)
+    ///
+    /// Return the name of the state, without its timestamp.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::model::TransactionState::*;
+    /// let name : &str = OnProcess {timestamp: 4}.get_name();
+    /// # assert_eq!(name, "OnProcess")
+    /// ```
+    ///
+    pub fn get_name(
+        self: &TransactionState,
+    ) -> &'static str {
+        match self {
+            Created { .. } => "Created",
+            OnProcess { .. } => "OnProcess",
+            Completed { .. } => "Completed",
+            Failed { .. } => "Failed",
+            Blocked { .. } => "Blocked",
+        }
     }
-}
\ No newline at end of file
+
+    ///
+    /// Return whether no other state can follow this state.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::model::TransactionState::*;
+    /// # assert!(Completed {timestamp: 7}.is_final());
+    /// # assert!(Failed {timestamp: 7}.is_final());
+    /// assert!(!OnProcess {timestamp: 4}.is_final());
+    /// ```
+    ///
+    pub fn is_final(
+        self: &TransactionState,
+    ) -> bool {
+        matches!(self, Completed { .. } | Failed { .. })
+    }
+
+    ///
+    /// Return whether a transaction in this state can move to the given state.
+    /// The timestamps of the states are not taken into account.
+    ///
+    /// The legal transitions are:
+    /// * Created -> OnProcess, Blocked, Failed
+    /// * OnProcess -> Created, Completed, Failed, Blocked
+    /// * Blocked -> OnProcess, Completed, Failed
+    ///
+    /// Completed and Failed are final states.
+    ///
+    /// # Arguments
+    ///
+    /// * `next_state` - A TransactionState that represents the state to move to.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::model::TransactionState::*;
+    /// assert!(Created {timestamp: 1}.can_transition_to(&OnProcess {timestamp: 4}));
+    /// assert!(!Completed {timestamp: 7}.can_transition_to(&OnProcess {timestamp: 8}));
+    /// ```
+    ///
+    pub fn can_transition_to(
+        self: &TransactionState,
+        next_state: &TransactionState,
+    ) -> bool {
+        matches!(
+            (self, next_state),
+            (Created { .. }, OnProcess { .. })
+                | (Created { .. }, Blocked { .. })
+                | (Created { .. }, Failed { .. })
+                | (OnProcess { .. }, Created { .. })
+                | (OnProcess { .. }, Completed { .. })
+                | (OnProcess { .. }, Failed { .. })
+                | (OnProcess { .. }, Blocked { .. })
+                | (Blocked { .. }, OnProcess { .. })
+                | (Blocked { .. }, Completed { .. })
+                | (Blocked { .. }, Failed { .. })
+        )
+    }
+    ///
+    /// Return whether this state and the given state are the same kind of state, regardless of their timestamps.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - A TransactionState that represents the state to compare with.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::model::TransactionState::*;
+    /// assert!(Completed {timestamp: 7}.is_same_state(&Completed {timestamp: 9}));
+    /// assert!(!Completed {timestamp: 7}.is_same_state(&Failed {timestamp: 7}));
+    /// ```
+    ///
+    pub fn is_same_state(
+        self: &TransactionState,
+        other: &TransactionState,
+    ) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
@@ -1,164 +1,869 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 use crate::error::WalletError;
+use crate::model::{Amount, AssetDefinition, Hold, OverdraftPolicy, OverdraftPolicyChange};
+
+/// Code of the asset held by wallets and moved by transactions that do not name one.
+pub const DEFAULT_ASSET: &str = "BNC";
+
+/// Return the code of the default asset, for the records stored before the introduction of the assets.
+pub(crate) fn default_asset() -> String {
+    DEFAULT_ASSET.to_string()
+}
 
 /// Wallet structure
-/// It represents a wallet. It contains coins.
+/// It represents a wallet. It contains coins of one or more assets.
+/// Part of the coins can be held for transfers that have not settled yet: the available balance of an asset
+/// is its total balance minus the held coins, and only the available coins can be removed.
+/// Its overdraft policy decides how far below zero the available balances can go when coins are removed or held.
+/// The amounts of an asset can not have more decimal places than its default definition; the ledger checks them
+/// against its asset registry too.
 #[derive(Serialize, Deserialize)]
 #[derive(Debug, Clone)]
+#[serde(from = "StoredWallet")]
 pub struct Wallet {
-    /// Amount of coins contained in the wallet.
-    /// It is a signed integer 'i64', so it can be positive and negative.
-    balance: i64
+    /// Amount of coins contained in the wallet, keyed by asset code.
+    /// They are signed amounts, so they can be positive and negative.
+    balances: BTreeMap<String, Amount>,
+    /// Overdraft policy enforced on every removal of coins, whatever the asset.
+    overdraft_policy: OverdraftPolicy,
+    /// Changes of the overdraft policy, in order of application.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    overdraft_policy_changes: Vec<OverdraftPolicyChange>,
+    /// Holds placed on the coins of the wallet, keyed by hold id.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    holds: BTreeMap<String, Hold>,
+}
+
+/// Serialized form of a wallet. Wallets stored before the introduction of the assets
+/// only have a single `balance`, which is loaded into the default asset.
+#[derive(Deserialize)]
+struct StoredWallet {
+    #[serde(default)]
+    balance: Option<Amount>,
+    #[serde(default)]
+    balances: BTreeMap<String, Amount>,
+    #[serde(default)]
+    overdraft_policy: OverdraftPolicy,
+    #[serde(default)]
+    overdraft_policy_changes: Vec<OverdraftPolicyChange>,
+    #[serde(default)]
+    holds: BTreeMap<String, Hold>,
+}
+
+impl From<StoredWallet> for Wallet {
+    fn from(
+        stored: StoredWallet,
+    ) -> Wallet {
+        let mut balances = stored.balances;
+        if let Some(balance) = stored.balance {
+            balances.entry(DEFAULT_ASSET.to_string()).or_insert(balance);
+        }
+        Wallet {
+            balances,
+            overdraft_policy: stored.overdraft_policy,
+            overdraft_policy_changes: stored.overdraft_policy_changes,
+            holds: stored.holds,
+        }
+    }
 }
 
 /// Wallet implementation.
 impl Wallet {
     ///
-    /// Return a new Wallet.
+    /// Return a new Wallet holding the given amount of coins of the default asset, which does not allow any overdraft.
     ///
     /// # Arguments
     ///
-    /// * `balance` - A i64 integer that represents the amount of coins in the wallet.
+    /// * `balance` - An Amount that represents the amount of coins of the default asset in the wallet.
     ///
     /// # Example
     /// ```
-    /// use banana_coin::model::Wallet;
+    /// use banana_coin::model::{Amount, Wallet};
     /// let wallet = Wallet::new(
-    ///     100
+    ///     Amount::from_integer(100)
     /// );
-    /// # assert_eq!(wallet.get_balance(), 100)
+    /// # assert_eq!(wallet.get_balance(), Amount::from_integer(100))
     /// ```
     ///
     pub fn new(
-        balance: i64
+        balance: Amount
     ) -> Wallet {
+        let mut balances = BTreeMap::new();
+        balances.insert(DEFAULT_ASSET.to_string(), balance);
         Wallet {
-            balance: balance
+            balances,
+            overdraft_policy: OverdraftPolicy::NoOverdraft,
+            overdraft_policy_changes: Vec::new(),
+            holds: BTreeMap::new(),
         }
     }
 
     ///
-    /// Return a copy of the amount of coins in the wallet.
+    /// Return a copy of the total amount of coins of the default asset in the wallet, held coins included.
     ///
     /// # Example
     /// ```
-    /// use banana_coin::model::Wallet;
+    /// use banana_coin::model::{Amount, Wallet};
     /// let wallet = Wallet::new(
-    ///     100
+    ///     Amount::from_integer(100)
     /// );
-    /// let wallet_balance : i64 = wallet.get_balance();
-    /// # assert_eq!(wallet_balance, 100)
+    /// let wallet_balance : Amount = wallet.get_balance();
+    /// # assert_eq!(wallet_balance, Amount::from_integer(100))
     /// ```
     ///
     pub fn get_balance(
         self: & Wallet,
-    ) -> i64 {
-        self.balance.clone()
+    ) -> Amount {
+        self.get_asset_balance(DEFAULT_ASSET)
+    }
+
+    ///
+    /// Return a copy of the total amount of coins of the given asset in the wallet, held coins included,
+    /// zero if the wallet never held it.
+    ///
+    /// # Arguments
+    ///
+    /// * `asset` - A str that represents the code of the asset.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::model::{Amount, Wallet};
+    /// let mut wallet = Wallet::new(
+    ///     Amount::from_integer(100)
+    /// );
+    /// wallet.add_asset_coins("GOLD", Amount::from_integer(5)).unwrap();
+    /// let wallet_balance : Amount = wallet.get_asset_balance("GOLD");
+    /// # assert_eq!(wallet_balance, Amount::from_integer(5));
+    /// # assert_eq!(wallet.get_asset_balance("SILVER"), Amount::from_integer(0))
+    /// ```
+    ///
+    pub fn get_asset_balance(
+        self: &Wallet,
+        asset: &str,
+    ) -> Amount {
+        self.balances.get(asset).copied().unwrap_or_default()
+    }
+
+    ///
+    /// Return a copy of the amounts of coins in the wallet, keyed by asset code.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use banana_coin::model::{Amount, Wallet, DEFAULT_ASSET};
+    /// let wallet = Wallet::new(
+    ///     Amount::from_integer(100)
+    /// );
+    /// let balances : BTreeMap<String, Amount> = wallet.get_balances();
+    /// # assert_eq!(balances.get(DEFAULT_ASSET), Some(&Amount::from_integer(100)))
+    /// ```
+    ///
+    pub fn get_balances(
+        self: &Wallet,
+    ) -> BTreeMap<String, Amount> {
+        self.balances.clone()
     }
 
     ///
-    /// Add coins to the wallet.
+    /// Add coins of the default asset to the wallet.
     ///
     /// # Arguments
     ///
-    /// * `coins_to_add` - A u32 number that represents the amount of coin to add to the wallet.
+    /// * `coins_to_add` - An Amount that represents the amount of coin to add to the wallet.
     ///
     /// # Example
     ///
     /// ```
-    /// # use banana_coin::model::Wallet;
+    /// # use banana_coin::model::{Amount, Wallet};
     /// let mut wallet = Wallet::new(
-    ///     100
+    ///     Amount::from_integer(100)
     /// );
-    /// # assert_eq!(wallet.get_balance(), 100);
-    /// let result = wallet.add_coins(100);
+    /// # assert_eq!(wallet.get_balance(), Amount::from_integer(100));
+    /// let result = wallet.add_coins(Amount::from_integer(100));
     /// match result {
     ///     Ok(_) => println!("Coins added"),
     ///     Err(e) => println!("{}", e)
     /// }
-    /// # assert_eq!(wallet.get_balance(), 200);
+    /// # assert_eq!(wallet.get_balance(), Amount::from_integer(200));
     /// ```
     ///
     pub fn add_coins(
         self: &mut Wallet,
-        coins_to_add: u32,
+        coins_to_add: Amount,
     ) -> Result<(), WalletError> {
+        self.add_asset_coins(DEFAULT_ASSET, coins_to_add)
+    }
+
+    ///
+    /// Add coins of the given asset to the wallet.
+    ///
+    /// # Arguments
+    ///
+    /// * `asset` - A str that represents the code of the asset.
+    /// * `coins_to_add` - An Amount that represents the amount of coin to add to the wallet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use banana_coin::model::{Amount, Wallet};
+    /// let mut wallet = Wallet::new(
+    ///     Amount::from_integer(100)
+    /// );
+    /// let result = wallet.add_asset_coins("GOLD", Amount::from_integer(10));
+    /// match result {
+    ///     Ok(_) => println!("Coins added"),
+    ///     Err(e) => println!("{}", e)
+    /// }
+    /// # assert_eq!(wallet.get_asset_balance("GOLD"), Amount::from_integer(10));
+    /// # assert_eq!(wallet.get_balance(), Amount::from_integer(100));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An EmptyAssetError is returned if the asset code is empty and an AssetPrecisionError
+    /// if the amount has more decimal places than the asset.
+    ///
+    pub fn add_asset_coins(
+        self: &mut Wallet,
+        asset: &str,
+        coins_to_add: Amount,
+    ) -> Result<(), WalletError> {
+        if asset.is_empty() {
+            return Err(WalletError::EmptyAssetError);
+        }
+        self.check_precision(asset, coins_to_add)?;
+        let current_amount = self.get_asset_balance(asset);
         // Checking for overflow
         match coins_to_add {
-            x if x == 0 => Err(WalletError::AddZeroCoinError),
-            x if x > 0 => {
-                match self.balance.checked_add(coins_to_add as i64) {
+            x if x.is_zero() => Err(WalletError::AddZeroCoinError),
+            x if x.is_negative() => Err(WalletError::AddCoinError { added_amount: coins_to_add }),
+            _ => {
+                match current_amount.checked_add(coins_to_add) {
                     Some(new_balance) => {
-                        self.balance = new_balance;
+                        self.balances.insert(asset.to_string(), new_balance);
                         Ok(())
                     }
                     None => {
                         Err(WalletError::AddCoinOverflowError {
-                            current_amount: self.balance,
+                            asset: asset.to_string(),
+                            current_amount,
                             added_amount: coins_to_add,
                         })
                     }
                 }
             }
-            _ => Err(WalletError::AddCoinError)
         }
     }
 
     ///
-    /// Remove coins to the wallet.
+    /// Remove coins of the default asset from the wallet, within the limit of its overdraft policy.
+    /// The expired holds are released first.
     ///
     /// # Arguments
     ///
-    /// * `coins_to_remove` - A u32 number that represents the amount of coin to remove from the wallet.
-    /// * `allow_negative_balance` - A boolean that represents if, after the remove operation, a negative balance is allowed.
+    /// * `coins_to_remove` - An Amount that represents the amount of coin to remove from the wallet.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the removal.
     ///
     /// # Example
     ///
     /// ```
-    /// # use banana_coin::model::Wallet;
+    /// # use banana_coin::model::{Amount, Wallet};
     /// let mut wallet = Wallet::new(
-    ///     100
+    ///     Amount::from_integer(100)
     /// );
-    /// # assert_eq!(wallet.get_balance(), 100);
-    /// let result = wallet.remove_coins(100, true);
+    /// # assert_eq!(wallet.get_balance(), Amount::from_integer(100));
+    /// let result = wallet.remove_coins(Amount::from_integer(100), 1);
     /// match result {
     ///     Ok(_) => println!("Coins removed"),
     ///     Err(e) => println!("{}", e)
     /// }
-    /// # assert_eq!(wallet.get_balance(), 0);
+    /// # assert_eq!(wallet.get_balance(), Amount::from_integer(0));
     /// ```
     ///
     pub fn remove_coins(
         self: &mut Wallet,
-        coins_to_remove: u32,
-        allow_negative_balance: bool,
+        coins_to_remove: Amount,
+        timestamp: u64,
     ) -> Result<(), WalletError> {
+        self.remove_asset_coins(DEFAULT_ASSET, coins_to_remove, timestamp)
+    }
+
+    ///
+    /// Remove coins of the given asset from the wallet, within the limit of its overdraft policy.
+    /// The expired holds do not count and are released once the coins are removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `asset` - A str that represents the code of the asset.
+    /// * `coins_to_remove` - An Amount that represents the amount of coin to remove from the wallet.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the removal.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use banana_coin::model::{Amount, Wallet};
+    /// let mut wallet = Wallet::new(
+    ///     Amount::from_integer(100)
+    /// );
+    /// let result = wallet.remove_asset_coins("GOLD", Amount::from_integer(10), 1);
+    /// match result {
+    ///     Ok(_) => println!("Coins removed"),
+    ///     Err(e) => println!("{}", e)
+    /// }
+    /// # assert!(wallet.remove_asset_coins("GOLD", Amount::from_integer(10), 1).is_err());
+    /// # assert_eq!(wallet.get_asset_balance("GOLD"), Amount::from_integer(0));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An EmptyAssetError is returned if the asset code is empty, an AssetPrecisionError if the amount has more
+    /// decimal places than the asset and a RemoveCoinNegativeBalanceError if the new available balance is below
+    /// the floor of the overdraft policy.
+    ///
+    pub fn remove_asset_coins(
+        self: &mut Wallet,
+        asset: &str,
+        coins_to_remove: Amount,
+        timestamp: u64,
+    ) -> Result<(), WalletError> {
+        if asset.is_empty() {
+            return Err(WalletError::EmptyAssetError);
+        }
+        self.check_precision(asset, coins_to_remove)?;
+        let current_amount = self.get_asset_balance(asset);
+        let held_amount = self.get_held_asset_amount(asset, timestamp)?;
         // Checking for overflow
         match coins_to_remove {
-            x if x == 0 => Err(WalletError::RemoveZeroCoinError),
-            x if x > 0 => {
-                match self.balance.checked_sub(coins_to_remove as i64) {
+            x if x.is_zero() => Err(WalletError::RemoveZeroCoinError),
+            x if x.is_negative() => Err(WalletError::RemoveCoinError { removed_amount: coins_to_remove }),
+            _ => {
+                match current_amount.checked_sub(coins_to_remove) {
                     Some(total_coins) => {
-                        if !allow_negative_balance && total_coins < 0 {
+                        let available = total_coins.checked_sub(held_amount);
+                        if !available.is_some_and(|available| self.overdraft_policy.allows(available)) {
                             return Err(WalletError::RemoveCoinNegativeBalanceError {
-                                current_amount: self.balance,
+                                asset: asset.to_string(),
+                                current_amount,
+                                held_amount,
                                 removed_amount: coins_to_remove,
-                                negative_balance_allowed: allow_negative_balance,
                             })
                         }
-                        self.balance = total_coins;
+                        self.expire_holds(timestamp);
+                        self.balances.insert(asset.to_string(), total_coins);
                         Ok(())
                     }
                     None => {
                         Err(WalletError::RemoveCoinOverflowError {
-                            current_amount: self.balance,
+                            asset: asset.to_string(),
+                            current_amount,
                             removed_amount: coins_to_remove,
                         })
                     }
                 }
             }
-            _ => Err(WalletError::RemoveCoinError)
         }
     }
-}
\ No newline at end of file
+
+    ///
+    /// Return the overdraft policy of the wallet.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, OverdraftPolicy, Wallet};
+    /// let wallet = Wallet::new(
+    ///     Amount::from_integer(100)
+    /// );
+    /// let overdraft_policy : OverdraftPolicy = wallet.get_overdraft_policy();
+    /// # assert_eq!(overdraft_policy, OverdraftPolicy::NoOverdraft)
+    /// ```
+    ///
+    pub fn get_overdraft_policy(
+        self: &Wallet,
+    ) -> OverdraftPolicy {
+        self.overdraft_policy
+    }
+
+    ///
+    /// Replace the overdraft policy of the wallet, recording who changed it and when.
+    /// The new policy only applies to the next removals of coins: balances already below its floor are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `overdraft_policy` - An OverdraftPolicy that represents the new policy of the wallet.
+    /// * `changed_by` - A String that represents who changes the policy.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the change.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, OverdraftPolicy, Wallet};
+    /// let mut wallet = Wallet::new(
+    ///     Amount::from_integer(100)
+    /// );
+    /// let result = wallet.set_overdraft_policy(
+    ///     OverdraftPolicy::Limit { floor: Amount::from_integer(-50) },
+    ///     "admin".to_string(),
+    ///     1
+    /// );
+    /// # assert!(result.is_ok());
+    /// # assert!(wallet.remove_coins(Amount::from_integer(150), 2).is_ok());
+    /// # assert!(wallet.remove_coins(Amount::from_integer(1), 2).is_err());
+    /// # assert_eq!(wallet.get_overdraft_policy_changes().len(), 1)
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A PositiveOverdraftFloorError is returned if the floor of a Limit policy is positive.
+    ///
+    pub fn set_overdraft_policy(
+        self: &mut Wallet,
+        overdraft_policy: OverdraftPolicy,
+        changed_by: String,
+        timestamp: u64,
+    ) -> Result<(), WalletError> {
+        if let OverdraftPolicy::Limit { floor } = overdraft_policy {
+            if floor > Amount::zero() {
+                return Err(WalletError::PositiveOverdraftFloorError { floor });
+            }
+        }
+        self.overdraft_policy_changes.push(OverdraftPolicyChange::new(
+            self.overdraft_policy, overdraft_policy, changed_by, timestamp));
+        self.overdraft_policy = overdraft_policy;
+        Ok(())
+    }
+
+    ///
+    /// Return a copy of the changes of the overdraft policy of the wallet, in order of application.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, OverdraftPolicyChange, Wallet};
+    /// let wallet = Wallet::new(
+    ///     Amount::from_integer(100)
+    /// );
+    /// let changes : Vec<OverdraftPolicyChange> = wallet.get_overdraft_policy_changes();
+    /// # assert!(changes.is_empty())
+    /// ```
+    ///
+    pub fn get_overdraft_policy_changes(
+        self: &Wallet,
+    ) -> Vec<OverdraftPolicyChange> {
+        self.overdraft_policy_changes.clone()
+    }
+
+    ///
+    /// Return a copy of the available amount of coins of the default asset in the wallet, i.e. its total amount
+    /// minus the coins of the holds not expired at the given timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - A u64 integer that represents the current timestamp.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Wallet};
+    /// let mut wallet = Wallet::new(
+    ///     Amount::from_integer(100)
+    /// );
+    /// wallet.place_hold("hold_0001".to_string(), Amount::from_integer(30), 1, 3600).unwrap();
+    /// let available : Amount = wallet.get_available_balance(2).unwrap();
+    /// # assert_eq!(available, Amount::from_integer(70));
+    /// # assert_eq!(wallet.get_available_balance(3601).unwrap(), Amount::from_integer(100));
+    /// # assert_eq!(wallet.get_balance(), Amount::from_integer(100))
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A HeldAmountOverflowError is returned if the sum of the held coins or the available amount is out of range.
+    ///
+    pub fn get_available_balance(
+        self: &Wallet,
+        timestamp: u64,
+    ) -> Result<Amount, WalletError> {
+        self.get_available_asset_balance(DEFAULT_ASSET, timestamp)
+    }
+
+    ///
+    /// Return a copy of the available amount of coins of the given asset in the wallet, i.e. its total amount
+    /// minus the coins of the holds not expired at the given timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `asset` - A str that represents the code of the asset.
+    /// * `timestamp` - A u64 integer that represents the current timestamp.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Wallet};
+    /// let mut wallet = Wallet::new(
+    ///     Amount::from_integer(100)
+    /// );
+    /// let available : Amount = wallet.get_available_asset_balance("GOLD", 1).unwrap();
+    /// # assert!(available.is_zero())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A HeldAmountOverflowError is returned if the sum of the held coins or the available amount is out of range.
+    ///
+    pub fn get_available_asset_balance(
+        self: &Wallet,
+        asset: &str,
+        timestamp: u64,
+    ) -> Result<Amount, WalletError> {
+        self.get_asset_balance(asset)
+            .checked_sub(self.get_held_asset_amount(asset, timestamp)?)
+            .ok_or_else(|| WalletError::HeldAmountOverflowError { asset: asset.to_string() })
+    }
+
+    ///
+    /// Return the amount of coins of the given asset held by the holds of the wallet not expired at the given timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `asset` - A str that represents the code of the asset.
+    /// * `timestamp` - A u64 integer that represents the current timestamp.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Wallet};
+    /// let mut wallet = Wallet::new(
+    ///     Amount::from_integer(100)
+    /// );
+    /// wallet.place_hold("hold_0001".to_string(), Amount::from_integer(30), 1, 3600).unwrap();
+    /// let held : Amount = wallet.get_held_asset_amount("BNC", 2).unwrap();
+    /// # assert_eq!(held, Amount::from_integer(30));
+    /// # assert!(wallet.get_held_asset_amount("BNC", 3601).unwrap().is_zero())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A HeldAmountOverflowError is returned if the sum of the held coins or the available amount is out of range.
+    ///
+    pub fn get_held_asset_amount(
+        self: &Wallet,
+        asset: &str,
+        timestamp: u64,
+    ) -> Result<Amount, WalletError> {
+        self.holds
+            .values()
+            .filter(|hold| hold.get_asset() == asset && !hold.is_expired(timestamp))
+            .try_fold(Amount::zero(), |held, hold| held.checked_add(hold.get_amount()))
+            .ok_or_else(|| WalletError::HeldAmountOverflowError { asset: asset.to_string() })
+    }
+
+    ///
+    /// Return a copy of the holds of the wallet, in order of hold id.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Hold, Wallet};
+    /// let wallet = Wallet::new(
+    ///     Amount::from_integer(100)
+    /// );
+    /// let holds : Vec<Hold> = wallet.get_holds();
+    /// # assert!(holds.is_empty())
+    /// ```
+    ///
+    pub fn get_holds(
+        self: &Wallet,
+    ) -> Vec<Hold> {
+        self.holds.values().cloned().collect()
+    }
+
+    ///
+    /// Return a copy of the hold identified by the given id, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `hold_id` - A str that represents the id of the hold.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Hold, Wallet};
+    /// let mut wallet = Wallet::new(
+    ///     Amount::from_integer(100)
+    /// );
+    /// wallet.place_hold("hold_0001".to_string(), Amount::from_integer(30), 1, 3600).unwrap();
+    /// let hold : Option<Hold> = wallet.get_hold("hold_0001");
+    /// # assert_eq!(hold.unwrap().get_expires_at(), 3601)
+    /// ```
+    ///
+    pub fn get_hold(
+        self: &Wallet,
+        hold_id: &str,
+    ) -> Option<Hold> {
+        self.holds.get(hold_id).cloned()
+    }
+
+    ///
+    /// Hold coins of the default asset, so that they can not be removed until the hold is captured, released
+    /// or expired. The expired holds are released first.
+    ///
+    /// # Arguments
+    ///
+    /// * `hold_id` - A String that represents the id of the hold.
+    /// * `amount` - An Amount that represents the amount of coins to hold.
+    /// * `timestamp` - A u64 integer that represents the timestamp at which the hold is placed.
+    /// * `duration` - A u64 integer that represents how long the hold lasts before it expires.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Wallet};
+    /// let mut wallet = Wallet::new(
+    ///     Amount::from_integer(100)
+    /// );
+    /// let result = wallet.place_hold("hold_0001".to_string(), Amount::from_integer(30), 1, 3600);
+    /// # assert!(result.is_ok());
+    /// # assert!(wallet.remove_coins(Amount::from_integer(71), 2).is_err());
+    /// # assert!(wallet.remove_coins(Amount::from_integer(70), 2).is_ok())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A WalletError is returned if the hold can not be placed: see 'place_asset_hold'.
+    ///
+    pub fn place_hold(
+        self: &mut Wallet,
+        hold_id: String,
+        amount: Amount,
+        timestamp: u64,
+        duration: u64,
+    ) -> Result<(), WalletError> {
+        self.place_asset_hold(hold_id, DEFAULT_ASSET, amount, timestamp, duration)
+    }
+
+    ///
+    /// Hold coins of the given asset, so that they can not be removed until the hold is captured, released
+    /// or expired. The expired holds do not count and are released once the hold is placed.
+    ///
+    /// # Arguments
+    ///
+    /// * `hold_id` - A String that represents the id of the hold.
+    /// * `asset` - A str that represents the code of the asset.
+    /// * `amount` - An Amount that represents the amount of coins to hold.
+    /// * `timestamp` - A u64 integer that represents the timestamp at which the hold is placed.
+    /// * `duration` - A u64 integer that represents how long the hold lasts before it expires.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Wallet};
+    /// let mut wallet = Wallet::new(
+    ///     Amount::from_integer(100)
+    /// );
+    /// wallet.add_asset_coins("GOLD", Amount::from_integer(10)).unwrap();
+    /// let result = wallet.place_asset_hold("hold_0001".to_string(), "GOLD", Amount::from_integer(10), 1, 3600);
+    /// # assert!(result.is_ok());
+    /// # assert!(wallet.get_available_asset_balance("GOLD", 2).unwrap().is_zero())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An EmptyAssetError is returned if the asset code is empty, an InvalidHoldAmountError if the amount is not positive,
+    /// an AssetPrecisionError if it has more decimal places than the asset, a DuplicateHoldError if the wallet already has a hold with the same id and a HoldNegativeBalanceError
+    /// if the new available balance is below the floor of the overdraft policy.
+    ///
+    pub fn place_asset_hold(
+        self: &mut Wallet,
+        hold_id: String,
+        asset: &str,
+        amount: Amount,
+        timestamp: u64,
+        duration: u64,
+    ) -> Result<(), WalletError> {
+        if asset.is_empty() {
+            return Err(WalletError::EmptyAssetError);
+        }
+        if amount.is_zero() || amount.is_negative() {
+            return Err(WalletError::InvalidHoldAmountError { hold_id, amount });
+        }
+        self.check_precision(asset, amount)?;
+        if self.get_active_hold(&hold_id, timestamp).is_some() {
+            return Err(WalletError::DuplicateHoldError { hold_id });
+        }
+        let available_amount = self.get_available_asset_balance(asset, timestamp)?;
+        let allowed = available_amount
+            .checked_sub(amount)
+            .is_some_and(|available| self.overdraft_policy.allows(available));
+        if !allowed {
+            return Err(WalletError::HoldNegativeBalanceError {
+                hold_id,
+                asset: asset.to_string(),
+                available_amount,
+                held_amount: amount,
+            });
+        }
+        let hold = Hold::new(hold_id.clone(), asset.to_string(), amount, timestamp, timestamp.saturating_add(duration));
+        self.expire_holds(timestamp);
+        self.holds.insert(hold_id, hold);
+        Ok(())
+    }
+
+    ///
+    /// Release a hold, so that its coins are available again. The expired holds are released along with it.
+    ///
+    /// # Arguments
+    ///
+    /// * `hold_id` - A str that represents the id of the hold.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the release.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Wallet};
+    /// let mut wallet = Wallet::new(
+    ///     Amount::from_integer(100)
+    /// );
+    /// wallet.place_hold("hold_0001".to_string(), Amount::from_integer(30), 1, 3600).unwrap();
+    /// let result = wallet.release_hold("hold_0001", 2);
+    /// # assert_eq!(result.unwrap().get_amount(), Amount::from_integer(30));
+    /// # assert_eq!(wallet.get_available_balance(2).unwrap(), Amount::from_integer(100))
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A HoldNotFoundError is returned if the wallet has no hold with the given id, e.g. because it is expired.
+    ///
+    pub fn release_hold(
+        self: &mut Wallet,
+        hold_id: &str,
+        timestamp: u64,
+    ) -> Result<Hold, WalletError> {
+        if self.get_active_hold(hold_id, timestamp).is_none() {
+            return Err(WalletError::HoldNotFoundError { hold_id: hold_id.to_string() });
+        }
+        self.expire_holds(timestamp);
+        self.holds
+            .remove(hold_id)
+            .ok_or_else(|| WalletError::HoldNotFoundError { hold_id: hold_id.to_string() })
+    }
+
+    ///
+    /// Capture a hold, removing the given amount of its coins from the wallet and releasing the rest.
+    /// The expired holds are released along with it.
+    ///
+    /// # Arguments
+    ///
+    /// * `hold_id` - A str that represents the id of the hold.
+    /// * `asset` - A str that represents the code of the asset of the coins to remove.
+    /// * `amount` - An Amount that represents the amount of coins to remove, at most the amount of the hold.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the capture.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Wallet, DEFAULT_ASSET};
+    /// let mut wallet = Wallet::new(
+    ///     Amount::from_integer(100)
+    /// );
+    /// wallet.place_hold("hold_0001".to_string(), Amount::from_integer(30), 1, 3600).unwrap();
+    /// let result = wallet.capture_hold("hold_0001", DEFAULT_ASSET, Amount::from_integer(20), 2);
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(wallet.get_balance(), Amount::from_integer(80));
+    /// # assert_eq!(wallet.get_available_balance(2).unwrap(), Amount::from_integer(80))
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A HoldNotFoundError is returned if the wallet has no hold with the given id, a HoldAssetMismatchError
+    /// if the hold is on another asset and a CaptureExceedsHoldError if the amount is higher than the held one.
+    /// In every case the hold is left untouched.
+    ///
+    pub fn capture_hold(
+        self: &mut Wallet,
+        hold_id: &str,
+        asset: &str,
+        amount: Amount,
+        timestamp: u64,
+    ) -> Result<Hold, WalletError> {
+        let hold = self.get_active_hold(hold_id, timestamp)
+            .cloned()
+            .ok_or_else(|| WalletError::HoldNotFoundError { hold_id: hold_id.to_string() })?;
+        if hold.get_asset() != asset {
+            return Err(WalletError::HoldAssetMismatchError {
+                hold_id: hold_id.to_string(),
+                hold_asset: hold.get_asset().to_string(),
+                asset: asset.to_string(),
+            });
+        }
+        if amount > hold.get_amount() {
+            return Err(WalletError::CaptureExceedsHoldError {
+                hold_id: hold_id.to_string(),
+                held_amount: hold.get_amount(),
+                captured_amount: amount,
+            });
+        }
+        // The held coins are released before the removal, which is then always covered by them.
+        // The removal releases the expired holds once it succeeds
+        self.holds.remove(hold_id);
+        match self.remove_asset_coins(asset, amount, timestamp) {
+            Ok(_) => Ok(hold),
+            Err(error) => {
+                self.holds.insert(hold_id.to_string(), hold);
+                Err(error)
+            }
+        }
+    }
+
+    ///
+    /// Release the holds expired at the given timestamp and return them.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - A u64 integer that represents the current timestamp.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Hold, Wallet};
+    /// let mut wallet = Wallet::new(
+    ///     Amount::from_integer(100)
+    /// );
+    /// wallet.place_hold("hold_0001".to_string(), Amount::from_integer(30), 1, 3600).unwrap();
+    /// let expired : Vec<Hold> = wallet.expire_holds(3601);
+    /// # assert_eq!(expired.len(), 1);
+    /// # assert!(wallet.get_holds().is_empty())
+    /// ```
+    ///
+    pub fn expire_holds(
+        self: &mut Wallet,
+        timestamp: u64,
+    ) -> Vec<Hold> {
+        let expired_ids: Vec<String> = self.holds
+            .values()
+            .filter(|hold| hold.is_expired(timestamp))
+            .map(|hold| hold.get_id().to_string())
+            .collect();
+        expired_ids
+            .iter()
+            .filter_map(|hold_id| self.holds.remove(hold_id))
+            .collect()
+    }
+
+    /// Return the hold with the given id, unless it is expired at the given timestamp.
+    fn get_active_hold(
+        self: &Wallet,
+        hold_id: &str,
+        timestamp: u64,
+    ) -> Option<&Hold> {
+        self.holds
+            .get(hold_id)
+            .filter(|hold| !hold.is_expired(timestamp))
+    }
+
+    /// Check that an amount fits the decimal places of the default definition of an asset.
+    fn check_precision(
+        self: &Wallet,
+        asset: &str,
+        amount: Amount,
+    ) -> Result<(), WalletError> {
+        let definition = AssetDefinition::default_for(asset);
+        if !definition.fits(amount) {
+            return Err(WalletError::AssetPrecisionError {
+                asset: asset.to_string(),
+                amount,
+                decimals: definition.get_decimals(),
+            });
+        }
+        Ok(())
+    }
+}
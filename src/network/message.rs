@@ -0,0 +1,116 @@
+use std::io::{BufRead, Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::chain::Block;
+use crate::error::NetworkError;
+use crate::model::Transaction;
+
+/// Version of the protocol spoken by the nodes. Peers speaking another version are refused.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Maximum length in bytes of a message on the wire, so that a peer can not make a node buffer an endless line.
+pub const MAX_MESSAGE_LENGTH: usize = 32 * 1024 * 1024;
+
+/// Message enum
+/// It represents a message exchanged by two nodes. On the wire every message is a JSON object on its own line.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub enum Message {
+    Handshake { version: u32, height: u64 },
+    Transaction { transaction: Transaction },
+    Block { block: Block },
+    GetBlocks { from_height: u64 },
+    Blocks { blocks: Vec<Block> },
+}
+
+/// Message implementation.
+impl Message {
+    ///
+    /// Return the name string of the message.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::network::Message;
+    /// let message = Message::GetBlocks { from_height: 0 };
+    /// # assert_eq!(message.get_name(), "GetBlocks")
+    /// ```
+    ///
+    pub fn get_name(
+        self: &Message,
+    ) -> &'static str {
+        match self {
+            Message::Handshake { .. } => "Handshake",
+            Message::Transaction { .. } => "Transaction",
+            Message::Block { .. } => "Block",
+            Message::GetBlocks { .. } => "GetBlocks",
+            Message::Blocks { .. } => "Blocks",
+        }
+    }
+
+    ///
+    /// Write the message, followed by a new line, to the given writer.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - A Write that represents the connection to the peer.
+    ///
+    /// # Errors
+    ///
+    /// Return a NetworkError if the message can not be serialized or written.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::network::Message;
+    /// let mut buffer: Vec<u8> = Vec::new();
+    /// Message::GetBlocks { from_height: 0 }.write_to(&mut buffer).unwrap();
+    /// # assert_eq!(buffer, b"{\"GetBlocks\":{\"from_height\":0}}\n")
+    /// ```
+    ///
+    pub fn write_to<W: Write>(
+        self: &Message,
+        writer: &mut W,
+    ) -> Result<(), NetworkError> {
+        let mut line = serde_json::to_vec(self)?;
+        line.push(b'\n');
+        writer.write_all(&line)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    ///
+    /// Read the next message from the given reader. Return None if the peer closed the connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A BufRead that represents the connection to the peer.
+    ///
+    /// # Errors
+    ///
+    /// Return a MessageTooLongError if the line is longer than MAX_MESSAGE_LENGTH bytes
+    /// and a NetworkError if the line can not be read or is not a message.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::network::{Message, MAX_MESSAGE_LENGTH};
+    /// let mut reader: &[u8] = b"{\"GetBlocks\":{\"from_height\":3}}\n";
+    /// let message = Message::read_from(&mut reader).unwrap();
+    /// # assert!(matches!(message, Some(Message::GetBlocks { from_height: 3 })));
+    /// # let long_line = vec![b' '; MAX_MESSAGE_LENGTH + 1];
+    /// # assert!(Message::read_from(&mut long_line.as_slice()).is_err());
+    /// ```
+    ///
+    pub fn read_from<R: BufRead>(
+        reader: &mut R,
+    ) -> Result<Option<Message>, NetworkError> {
+        let mut line = String::new();
+        let length = reader.take(MAX_MESSAGE_LENGTH as u64 + 1).read_line(&mut line)?;
+        if length == 0 {
+            return Ok(None);
+        }
+        if length > MAX_MESSAGE_LENGTH {
+            return Err(NetworkError::MessageTooLongError { max_length: MAX_MESSAGE_LENGTH });
+        }
+        Ok(Some(serde_json::from_str(&line)?))
+    }
+}
@@ -0,0 +1,7 @@
+/// Importing message module
+pub mod message;
+pub use message::*;
+
+/// Importing node module
+pub mod node;
+pub use node::*;
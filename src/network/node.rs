@@ -0,0 +1,743 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::convert::TryFrom;
+use std::io::{BufRead, BufReader, ErrorKind};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::slice;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crate::chain::{Block, Blockchain, ConsensusRules, PendingState};
+use crate::error::NetworkError;
+use crate::event::{balance_changes, block_appended, EventBus};
+use crate::ledger::ISSUANCE_ACCOUNT_ID;
+use crate::model::{Entity, Transaction, TransactionState};
+use crate::network::{Message, PROTOCOL_VERSION};
+
+/// Time a peer has to complete the handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time a peer has to accept a message before it is disconnected.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time a peer has to send the next part of a message it started before it is disconnected.
+/// A peer sending no message at all is kept.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Maximum number of connections of a node, to or from peers, handshakes included.
+pub const MAX_PEERS: usize = 32;
+
+/// Maximum number of transaction ids remembered by a node to stop gossiping the transactions it already heard of.
+/// The oldest ids are forgotten first: the chain still rejects a transaction it already includes.
+const MAX_KNOWN_TRANSACTION_IDS: usize = 100_000;
+
+/// A connected peer, with the stream used to send it messages.
+struct Peer {
+    id: u64,
+    address: String,
+    stream: TcpStream,
+}
+
+/// The data a node shares with the threads serving its peers. The pending state holds the changes
+/// the pending transactions make to the chain, so that a new transaction is checked without replaying them.
+struct NodeState {
+    blockchain: Blockchain,
+    pending_transactions: Vec<Transaction>,
+    pending_state: PendingState,
+    known_transaction_ids: BTreeSet<String>,
+    /// The known transaction ids, in the order they were remembered.
+    known_transaction_order: VecDeque<String>,
+}
+
+/// The state of a node and its peers, shared between the node and its threads.
+/// The consensus rules never change, so that signatures are checked without locking the state.
+struct Shared {
+    rules: ConsensusRules,
+    state: Mutex<NodeState>,
+    peers: Mutex<Vec<Peer>>,
+    event_bus: EventBus,
+    next_peer_id: AtomicU64,
+    connections: AtomicUsize,
+    stopped: AtomicBool,
+}
+
+/// A connection counted against MAX_PEERS, released when dropped.
+struct PeerSlot {
+    shared: Arc<Shared>,
+}
+
+/// The connection is released when its slot is dropped.
+impl Drop for PeerSlot {
+    fn drop(&mut self) {
+        self.shared.connections.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Node structure
+/// It represents a peer of the network: it listens on a TCP port, connects to other nodes,
+/// gossips transactions and blocks to its peers and keeps the longest valid chain it hears of.
+/// Transactions are accepted only if they can follow the chain and the pending transactions of the node,
+/// and the transactions of the blocks dropped when the node switches chain go back to the pending transactions.
+/// The blocks appended to the chain of the node, and the rewards of its miners, are published on its event bus.
+/// Every peer is served by its own thread; the node stops when it is shut down or dropped.
+pub struct Node {
+    address: SocketAddr,
+    shared: Arc<Shared>,
+}
+
+/// Node implementation.
+impl Node {
+    ///
+    /// Return a new Node listening on the given address and starting from the given chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - A str that represents the address to listen on, e.g. "127.0.0.1:0" for any free port.
+    /// * `blockchain` - A Blockchain that represents the chain known by the node when it starts.
+    ///
+    /// # Errors
+    ///
+    /// Return an IoError if the node can not listen on the address.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::chain::Blockchain;
+    /// use banana_coin::network::Node;
+    /// let node = Node::start("127.0.0.1:0", Blockchain::new()).unwrap();
+    /// # assert_eq!(node.get_peer_addresses().len(), 0)
+    /// ```
+    ///
+    pub fn start(
+        address: &str,
+        blockchain: Blockchain,
+    ) -> Result<Node, NetworkError> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let shared = Arc::new(Shared {
+            rules: blockchain.get_rules(),
+            state: Mutex::new(NodeState {
+                pending_state: blockchain.new_pending_state(),
+                blockchain,
+                pending_transactions: Vec::new(),
+                known_transaction_ids: BTreeSet::new(),
+                known_transaction_order: VecDeque::new(),
+            }),
+            peers: Mutex::new(Vec::new()),
+            event_bus: EventBus::new(),
+            next_peer_id: AtomicU64::new(0),
+            connections: AtomicUsize::new(0),
+            stopped: AtomicBool::new(false),
+        });
+        let accepting_shared = Arc::clone(&shared);
+        thread::spawn(move || accept_peers(accepting_shared, listener));
+        Ok(Node {
+            address,
+            shared,
+        })
+    }
+
+    ///
+    /// Return the address the node listens on.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::Blockchain;
+    /// # use banana_coin::network::Node;
+    /// # let node = Node::start("127.0.0.1:0", Blockchain::new()).unwrap();
+    /// let address : String = node.get_address();
+    /// # assert!(address.starts_with("127.0.0.1:"))
+    /// ```
+    ///
+    pub fn get_address(
+        self: &Node,
+    ) -> String {
+        self.address.to_string()
+    }
+
+    ///
+    /// Connect the node to a peer. After the handshake, the node asks the peer for its blocks
+    /// if the peer has a longer chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_address` - A str that represents the address the peer listens on.
+    ///
+    /// # Errors
+    ///
+    /// Return a TooManyPeersError if the node already has MAX_PEERS connections and a NetworkError
+    /// if the peer can not be reached, does not complete the handshake or speaks another version of the protocol.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::Blockchain;
+    /// # use banana_coin::network::Node;
+    /// let node_1 = Node::start("127.0.0.1:0", Blockchain::new()).unwrap();
+    /// let node_2 = Node::start("127.0.0.1:0", Blockchain::new()).unwrap();
+    /// node_2.connect(&node_1.get_address()).unwrap();
+    /// # assert_eq!(node_2.get_peer_addresses(), vec![node_1.get_address()])
+    /// ```
+    ///
+    pub fn connect(
+        self: &Node,
+        peer_address: &str,
+    ) -> Result<(), NetworkError> {
+        let slot = reserve_slot(&self.shared).ok_or(NetworkError::TooManyPeersError { max_peers: MAX_PEERS })?;
+        let stream = TcpStream::connect(peer_address)?;
+        stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        handshake_message(&self.shared).write_to(&mut &stream)?;
+        let peer_height = match Message::read_from(&mut reader)? {
+            Some(Message::Handshake { version, height }) if version == PROTOCOL_VERSION => height,
+            Some(Message::Handshake { version, .. }) => {
+                return Err(NetworkError::IncompatibleVersionError {
+                    expected_version: PROTOCOL_VERSION,
+                    found_version: version,
+                });
+            }
+            Some(message) => return Err(NetworkError::UnexpectedMessageError { message: message.get_name().to_string() }),
+            None => return Err(NetworkError::UnexpectedMessageError { message: "end of stream".to_string() }),
+        };
+        register_peer(&self.shared, stream, reader, peer_address.to_string(), peer_height, slot)
+    }
+
+    ///
+    /// Return the addresses of the connected peers.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::Blockchain;
+    /// # use banana_coin::network::Node;
+    /// # let node = Node::start("127.0.0.1:0", Blockchain::new()).unwrap();
+    /// let peer_addresses : Vec<String> = node.get_peer_addresses();
+    /// # assert!(peer_addresses.is_empty())
+    /// ```
+    ///
+    pub fn get_peer_addresses(
+        self: &Node,
+    ) -> Vec<String> {
+        lock(&self.shared.peers).iter().map(|peer| peer.address.clone()).collect()
+    }
+
+    ///
+    /// Return a copy of the chain of the node.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::Blockchain;
+    /// # use banana_coin::network::Node;
+    /// # let node = Node::start("127.0.0.1:0", Blockchain::new()).unwrap();
+    /// let blockchain : Blockchain = node.get_blockchain();
+    /// # assert_eq!(blockchain.get_height(), 0)
+    /// ```
+    ///
+    pub fn get_blockchain(
+        self: &Node,
+    ) -> Blockchain {
+        lock(&self.shared.state).blockchain.clone()
+    }
+
+    ///
+    /// Return the event bus the blocks appended to the chain of the node are published on. The returned bus
+    /// shares its subscribers with the bus of the node.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::Blockchain;
+    /// # use banana_coin::network::Node;
+    /// # let node = Node::start("127.0.0.1:0", Blockchain::new()).unwrap();
+    /// let receiver = node.get_event_bus().subscribe_channel();
+    /// # assert_eq!(node.get_event_bus().get_subscriber_count(), 1)
+    /// ```
+    ///
+    pub fn get_event_bus(
+        self: &Node,
+    ) -> EventBus {
+        self.shared.event_bus.clone()
+    }
+
+    ///
+    /// Return a copy of the transactions heard by the node and not yet in its chain.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::Blockchain;
+    /// # use banana_coin::model::Transaction;
+    /// # use banana_coin::network::Node;
+    /// # let node = Node::start("127.0.0.1:0", Blockchain::new()).unwrap();
+    /// let transactions : Vec<Transaction> = node.get_pending_transactions();
+    /// # assert!(transactions.is_empty())
+    /// ```
+    ///
+    pub fn get_pending_transactions(
+        self: &Node,
+    ) -> Vec<Transaction> {
+        lock(&self.shared.state).pending_transactions.clone()
+    }
+
+    ///
+    /// Add a transaction to the pending transactions of the node and gossip it to the peers.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - A Transaction that represents the new transaction.
+    ///
+    /// # Errors
+    ///
+    /// Return a DuplicateTransactionError if the node already knows a transaction with the same id,
+    /// an InvalidTransactionError if the transaction is not valid, a ProcessedTransactionError if it is not
+    /// in the Created state, since the block including it executes it, a RejectedTransactionError if it can not follow
+    /// the chain and the pending transactions of the node: not signed with the registered key of its sender,
+    /// not carrying the next nonce of the sender or spending more coins than the sender holds.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::{Blockchain, ConsensusRules};
+    /// # use banana_coin::crypto::KeyPair;
+    /// # use banana_coin::model::{Amount, Entity, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::network::Node;
+    /// # let key_pair = KeyPair::generate();
+    /// # let mut rules = ConsensusRules::default();
+    /// # rules.register_public_key("entity_001".to_string(), key_pair.get_public_key()).unwrap();
+    /// # let node = Node::start("127.0.0.1:0", Blockchain::with_rules(rules)).unwrap();
+    /// # let mut miner = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// # node.mine_block(&mut miner, 1).unwrap();
+    /// # let mut transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(),
+    /// #     Amount::from_integer(10), "".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// # transaction.sign(&key_pair);
+    /// node.submit_transaction(transaction).unwrap();
+    /// # assert_eq!(node.get_pending_transactions().len(), 1)
+    /// ```
+    ///
+    pub fn submit_transaction(
+        self: &Node,
+        transaction: Transaction,
+    ) -> Result<(), NetworkError> {
+        add_transaction(&self.shared, &transaction)?;
+        broadcast(&self.shared, &Message::Transaction { transaction }, None);
+        Ok(())
+    }
+
+    ///
+    /// Mine a block with the pending transactions of the node, append it to the chain of the node
+    /// and gossip it to the peers. The block reward is credited to the miner. The balance change of the miner
+    /// and the new block are published on the event bus of the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `miner` - An Entity that represents the miner of the block.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the block.
+    ///
+    /// # Errors
+    ///
+    /// Return a ChainOperationError if the block can not be mined or appended. The miner is left unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::Blockchain;
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
+    /// # use banana_coin::network::Node;
+    /// # let node = Node::start("127.0.0.1:0", Blockchain::new()).unwrap();
+    /// let mut miner = Entity::new("id_0001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// node.mine_block(&mut miner, 1).unwrap();
+    /// # assert_eq!(node.get_blockchain().get_height(), 1)
+    /// ```
+    ///
+    pub fn mine_block(
+        self: &Node,
+        miner: &mut Entity,
+        timestamp: u64,
+    ) -> Result<Block, NetworkError> {
+        let (mut blockchain, transactions) = {
+            let state = lock(&self.shared.state);
+            (state.blockchain.clone(), state.pending_transactions.clone())
+        };
+        let mut rewarded_miner = miner.clone();
+        let block = blockchain.mine_block(&mut rewarded_miner, transactions, timestamp)?;
+        {
+            let mut state = lock(&self.shared.state);
+            state.blockchain.append_block(block.clone())?;
+            forget_pending_transactions(&mut state, slice::from_ref(&block), Vec::new());
+        }
+        let coinbase_id = block.get_transactions().first().map(|coinbase| coinbase.get_id());
+        let mut events = balance_changes(miner.get_id(), &miner.get_wallet(), &rewarded_miner.get_wallet(), coinbase_id, timestamp);
+        events.push(block_appended(&block));
+        *miner = rewarded_miner;
+        self.shared.event_bus.publish_all(events);
+        broadcast(&self.shared, &Message::Block { block: block.clone() }, None);
+        Ok(block)
+    }
+
+    ///
+    /// Stop the node: it stops listening and disconnects from its peers.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::chain::Blockchain;
+    /// # use banana_coin::network::Node;
+    /// let node = Node::start("127.0.0.1:0", Blockchain::new()).unwrap();
+    /// node.shutdown();
+    /// ```
+    ///
+    pub fn shutdown(
+        self: &Node,
+    ) {
+        if self.shared.stopped.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        for peer in lock(&self.shared.peers).drain(..) {
+            let _ = peer.stream.shutdown(Shutdown::Both);
+        }
+        // Wake up the thread blocked on accepting peers, so that it sees the node is stopped
+        let _ = TcpStream::connect(self.address);
+    }
+}
+
+/// A node stops when it is dropped.
+impl Drop for Node {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Lock a mutex, ignoring the poisoning: the state of the node is always left consistent between two statements.
+fn lock<T>(
+    mutex: &Mutex<T>,
+) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Return the handshake message of the node.
+fn handshake_message(
+    shared: &Shared,
+) -> Message {
+    Message::Handshake { version: PROTOCOL_VERSION, height: lock(&shared.state).blockchain.get_height() }
+}
+
+/// Count a new connection, unless the node already has MAX_PEERS connections.
+fn reserve_slot(
+    shared: &Arc<Shared>,
+) -> Option<PeerSlot> {
+    if shared.connections.fetch_add(1, Ordering::SeqCst) >= MAX_PEERS {
+        shared.connections.fetch_sub(1, Ordering::SeqCst);
+        return None;
+    }
+    Some(PeerSlot { shared: Arc::clone(shared) })
+}
+
+/// Accept the peers connecting to the node until the node is stopped.
+/// The peers connecting while the node has MAX_PEERS connections are disconnected at once.
+fn accept_peers(
+    shared: Arc<Shared>,
+    listener: TcpListener,
+) {
+    for stream in listener.incoming() {
+        if shared.stopped.load(Ordering::SeqCst) {
+            break;
+        }
+        if let Ok(stream) = stream {
+            match reserve_slot(&shared) {
+                Some(slot) => {
+                    let peer_shared = Arc::clone(&shared);
+                    thread::spawn(move || {
+                        let _ = accept_peer(&peer_shared, stream, slot);
+                    });
+                }
+                None => {
+                    let _ = stream.shutdown(Shutdown::Both);
+                }
+            }
+        }
+    }
+}
+
+/// Answer the handshake of a peer that connected to the node and start serving it.
+fn accept_peer(
+    shared: &Arc<Shared>,
+    stream: TcpStream,
+    slot: PeerSlot,
+) -> Result<(), NetworkError> {
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let (version, peer_height) = match Message::read_from(&mut reader)? {
+        Some(Message::Handshake { version, height }) => (version, height),
+        _ => return Ok(()),
+    };
+    // The handshake is answered even to incompatible peers, so that they know which version is spoken
+    handshake_message(shared).write_to(&mut &stream)?;
+    if version != PROTOCOL_VERSION {
+        stream.shutdown(Shutdown::Both)?;
+        return Ok(());
+    }
+    let address = stream.peer_addr()?.to_string();
+    register_peer(shared, stream, reader, address, peer_height, slot)
+}
+
+/// Add a peer that completed the handshake, start its thread and ask it for the blocks after the chain of the node
+/// if its chain is longer.
+fn register_peer(
+    shared: &Arc<Shared>,
+    stream: TcpStream,
+    reader: BufReader<TcpStream>,
+    address: String,
+    peer_height: u64,
+    slot: PeerSlot,
+) -> Result<(), NetworkError> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let id = shared.next_peer_id.fetch_add(1, Ordering::SeqCst);
+    lock(&shared.peers).push(Peer { id, address, stream });
+    let peer_shared = Arc::clone(shared);
+    thread::spawn(move || serve_peer(peer_shared, id, reader, slot));
+    let height = lock(&shared.state).blockchain.get_height();
+    if peer_height > height {
+        send(shared, id, &Message::GetBlocks { from_height: height });
+    }
+    Ok(())
+}
+
+/// Handle the messages of a peer until it disconnects, the node is stopped or the peer stops in the middle of a message.
+/// The connection of the peer is released when its thread ends.
+fn serve_peer(
+    shared: Arc<Shared>,
+    peer_id: u64,
+    mut reader: BufReader<TcpStream>,
+    _slot: PeerSlot,
+) {
+    while !shared.stopped.load(Ordering::SeqCst) {
+        // Waiting for the next message does not time out: only the messages must arrive in time
+        match reader.fill_buf() {
+            Ok([]) => break,
+            Ok(_) => {}
+            Err(error) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+            Err(_) => break,
+        }
+        match Message::read_from(&mut reader) {
+            Ok(Some(message)) => handle_message(&shared, peer_id, message),
+            _ => break,
+        }
+    }
+    remove_peer(&shared, peer_id);
+}
+
+/// Handle a message received from a peer.
+fn handle_message(
+    shared: &Shared,
+    peer_id: u64,
+    message: Message,
+) {
+    match message {
+        Message::Handshake { .. } => {}
+        Message::Transaction { transaction } => {
+            if add_transaction(shared, &transaction).is_ok() {
+                broadcast(shared, &Message::Transaction { transaction }, Some(peer_id));
+            }
+        }
+        Message::Block { block } => {
+            let height = block.get_header().get_height();
+            let mut state = lock(&shared.state);
+            if state.blockchain.append_block(block.clone()).is_ok() {
+                forget_pending_transactions(&mut state, slice::from_ref(&block), Vec::new());
+                drop(state);
+                shared.event_bus.publish(&block_appended(&block));
+                broadcast(shared, &Message::Block { block }, Some(peer_id));
+            } else if height >= state.blockchain.get_height() {
+                // The peer is on a longer chain, possibly a different one
+                let from_height = state.blockchain.get_height();
+                drop(state);
+                send(shared, peer_id, &Message::GetBlocks { from_height });
+            }
+        }
+        Message::GetBlocks { from_height } => {
+            let blocks: Vec<Block> = lock(&shared.state).blockchain.get_blocks().into_iter()
+                .skip(usize::try_from(from_height).unwrap_or(usize::MAX))
+                .collect();
+            send(shared, peer_id, &Message::Blocks { blocks });
+        }
+        Message::Blocks { blocks } => {
+            let first_height = blocks.first().map(|block| block.get_header().get_height());
+            let last_height = blocks.last().map(|block| block.get_header().get_height());
+            if let Some(tip) = adopt_longer_chain(shared, blocks) {
+                broadcast(shared, &Message::Block { block: tip }, Some(peer_id));
+            } else if let (Some(first_height), Some(last_height)) = (first_height, last_height) {
+                // The blocks after the chain of the node do not follow it: the peer is on a different chain,
+                // which is asked for from its start if it is longer
+                if first_height > 0 && last_height >= lock(&shared.state).blockchain.get_height() {
+                    send(shared, peer_id, &Message::GetBlocks { from_height: 0 });
+                }
+            }
+        }
+    }
+}
+
+/// Replace the chain of the node with the chain made of its own blocks up to the height of the first given block
+/// followed by the given blocks, if that chain is valid and longer. The new chain is checked without locking the state
+/// of the node, and it is dropped if the chain of the node changed in the meantime. The transactions of the blocks
+/// left out of the new chain go back to the pending transactions and the blocks of the new chain the node did not have
+/// are published. Return the new last block, if the chain was replaced.
+fn adopt_longer_chain(
+    shared: &Shared,
+    blocks: Vec<Block>,
+) -> Option<Block> {
+    let first_height = blocks.first()?.get_header().get_height();
+    let (current_height, current_tip_hash, mut candidate_blocks) = {
+        let state = lock(&shared.state);
+        let current_height = state.blockchain.get_height();
+        if first_height > current_height {
+            return None;
+        }
+        let candidate_blocks: Vec<Block> = (0..first_height)
+            .filter_map(|height| state.blockchain.get_block(height).cloned())
+            .collect();
+        (current_height, state.blockchain.get_tip_hash(), candidate_blocks)
+    };
+    candidate_blocks.extend(blocks);
+    let candidate = Blockchain::from_blocks(shared.rules.clone(), candidate_blocks).ok()?;
+    if candidate.get_height() <= current_height {
+        return None;
+    }
+    let mut state = lock(&shared.state);
+    if state.blockchain.get_tip_hash() != current_tip_hash {
+        return None;
+    }
+    let old_blocks = state.blockchain.get_blocks();
+    let orphaned_transactions: Vec<Transaction> = old_blocks.iter()
+        .skip(first_height as usize)
+        .flat_map(|block| block.get_transactions())
+        .filter(|transaction| transaction.get_from_entity_id() != ISSUANCE_ACCOUNT_ID)
+        .collect();
+    let new_blocks = candidate.get_blocks();
+    state.blockchain = candidate;
+    forget_pending_transactions(&mut state, &new_blocks, orphaned_transactions);
+    drop(state);
+    // The blocks the node already had are not published again
+    let fork_height = new_blocks.iter()
+        .zip(old_blocks.iter())
+        .take_while(|(new_block, old_block)| new_block.get_hash() == old_block.get_hash())
+        .count();
+    shared.event_bus.publish_all(new_blocks.iter().skip(fork_height).map(block_appended).collect());
+    new_blocks.last().cloned()
+}
+
+/// Add a transaction to the pending transactions of the node, if it is valid, created, not known yet
+/// and it can follow the chain and the pending transactions: signed by its sender, with the next nonce of the sender
+/// and spending only coins the sender holds. The signature is checked without locking the state of the node,
+/// the rest against the pending state of the node, without replaying the chain or the pending transactions.
+fn add_transaction(
+    shared: &Shared,
+    transaction: &Transaction,
+) -> Result<(), NetworkError> {
+    let transaction_id = transaction.get_id();
+    if let Err(error) = transaction.validate() {
+        return Err(NetworkError::InvalidTransactionError { transaction_id, error });
+    }
+    let current_state = transaction.get_current_state();
+    if !matches!(current_state, TransactionState::Created { .. }) {
+        return Err(NetworkError::ProcessedTransactionError { transaction_id, state: current_state });
+    }
+    let height = {
+        let state = lock(&shared.state);
+        if state.known_transaction_ids.contains(&transaction_id) {
+            return Err(NetworkError::DuplicateTransactionError { transaction_id });
+        }
+        state.blockchain.get_height()
+    };
+    if let Err(error) = shared.rules.check_signature(height, transaction) {
+        return Err(NetworkError::RejectedTransactionError { transaction_id, error: Box::new(error) });
+    }
+    let mut state = lock(&shared.state);
+    let state = &mut *state;
+    if state.known_transaction_ids.contains(&transaction_id) {
+        return Err(NetworkError::DuplicateTransactionError { transaction_id });
+    }
+    if let Err(error) = state.blockchain.add_pending_transaction(&mut state.pending_state, transaction) {
+        return Err(NetworkError::RejectedTransactionError { transaction_id, error: Box::new(error) });
+    }
+    remember_transaction_ids(state, vec![transaction_id]);
+    state.pending_transactions.push(transaction.clone());
+    Ok(())
+}
+
+/// Remove the transactions of the given blocks, just appended to the chain, from the pending transactions
+/// and remember them as known. The given returned transactions are put back before the pending transactions;
+/// the ones that can no longer follow the chain are dropped. Every transaction left has had its signature checked
+/// with the rules of the node, when it was added or when its block was appended, so that only its nonce
+/// and the coins it spends are checked again, against a new pending state.
+fn forget_pending_transactions(
+    state: &mut NodeState,
+    blocks: &[Block],
+    returned_transactions: Vec<Transaction>,
+) {
+    let included_ids: BTreeSet<String> = blocks.iter()
+        .flat_map(|block| block.get_transactions())
+        .map(|transaction| transaction.get_id())
+        .collect();
+    let candidates: Vec<Transaction> = returned_transactions.into_iter()
+        .chain(state.pending_transactions.drain(..))
+        .filter(|transaction| !included_ids.contains(&transaction.get_id()))
+        .collect();
+    state.pending_state = state.blockchain.new_pending_state();
+    for transaction in candidates {
+        if state.blockchain.add_pending_transaction(&mut state.pending_state, &transaction).is_ok() {
+            state.pending_transactions.push(transaction);
+        }
+    }
+    remember_transaction_ids(state, included_ids);
+}
+
+/// Remember the given transaction ids as known, forgetting the oldest ones beyond MAX_KNOWN_TRANSACTION_IDS.
+fn remember_transaction_ids<I: IntoIterator<Item = String>>(
+    state: &mut NodeState,
+    transaction_ids: I,
+) {
+    for transaction_id in transaction_ids {
+        if state.known_transaction_ids.insert(transaction_id.clone()) {
+            state.known_transaction_order.push_back(transaction_id);
+        }
+    }
+    while state.known_transaction_order.len() > MAX_KNOWN_TRANSACTION_IDS {
+        if let Some(transaction_id) = state.known_transaction_order.pop_front() {
+            state.known_transaction_ids.remove(&transaction_id);
+        }
+    }
+}
+
+/// Send a message to a peer, disconnecting it if the message can not be written.
+fn send(
+    shared: &Shared,
+    peer_id: u64,
+    message: &Message,
+) {
+    let mut peers = lock(&shared.peers);
+    if let Some(index) = peers.iter().position(|peer| peer.id == peer_id) {
+        if message.write_to(&mut &peers[index].stream).is_err() {
+            let peer = peers.remove(index);
+            let _ = peer.stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+/// Send a message to every peer but the given one, disconnecting the peers the message can not be written to.
+fn broadcast(
+    shared: &Shared,
+    message: &Message,
+    except_peer_id: Option<u64>,
+) {
+    let mut peers = lock(&shared.peers);
+    peers.retain(|peer| {
+        if Some(peer.id) == except_peer_id || message.write_to(&mut &peer.stream).is_ok() {
+            return true;
+        }
+        let _ = peer.stream.shutdown(Shutdown::Both);
+        false
+    });
+}
+
+/// Forget a peer that disconnected.
+fn remove_peer(
+    shared: &Shared,
+    peer_id: u64,
+) {
+    lock(&shared.peers).retain(|peer| peer.id != peer_id);
+}
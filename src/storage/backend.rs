@@ -0,0 +1,186 @@
+use crate::error::StorageError;
+use crate::model::{Entity, Escrow, Transaction};
+use crate::storage::IdempotencyRecord;
+
+/// Storage trait
+/// It represents a persistent backend for the entities, the transactions, the open escrows and the idempotency records.
+/// Entities can be saved many times, the last saved version is the one loaded.
+/// Transactions are append-only: a transaction id can be stored only once.
+/// Escrows are saved while they are open and removed once they are released or refunded.
+/// Idempotency records are kept until they are removed, which their users do once they are expired.
+pub trait Storage {
+    ///
+    /// Save an entity, replacing the previously saved version with the same id.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity` - An Entity that represents the entity to save.
+    ///
+    fn save_entity(
+        &mut self,
+        entity: &Entity,
+    ) -> Result<(), StorageError>;
+
+    ///
+    /// Return the last saved version of the entity identified by the given id, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A str that represents the id of the entity.
+    ///
+    fn load_entity(
+        &self,
+        entity_id: &str,
+    ) -> Result<Option<Entity>, StorageError>;
+
+    ///
+    /// Return the last saved version of all the entities, ordered by id.
+    ///
+    fn list_entities(
+        &self,
+    ) -> Result<Vec<Entity>, StorageError>;
+
+    ///
+    /// Append a transaction to the stored transactions.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - A Transaction that represents the transaction to append.
+    ///
+    /// # Errors
+    ///
+    /// A DuplicateTransactionError is returned if a transaction with the same id is already stored.
+    ///
+    fn append_transaction(
+        &mut self,
+        transaction: &Transaction,
+    ) -> Result<(), StorageError>;
+
+    ///
+    /// Append a transaction to the stored transactions and save the entities it changed, as one operation:
+    /// either the transaction and all the entities are stored, or none of them is.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - A Transaction that represents the transaction to append.
+    /// * `entities` - A slice of Entity that represents the entities changed by the transaction.
+    ///
+    /// # Errors
+    ///
+    /// A DuplicateTransactionError is returned if a transaction with the same id is already stored.
+    ///
+    fn append_transaction_with_entities(
+        &mut self,
+        transaction: &Transaction,
+        entities: &[Entity],
+    ) -> Result<(), StorageError>;
+
+    ///
+    /// Return all the stored transactions, in order of appending.
+    ///
+    fn list_transactions(
+        &self,
+    ) -> Result<Vec<Transaction>, StorageError>;
+
+    ///
+    /// Return the stored transaction identified by the given id, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_id` - A str that represents the id of the transaction.
+    ///
+    fn load_transaction(
+        &self,
+        transaction_id: &str,
+    ) -> Result<Option<Transaction>, StorageError> {
+        Ok(self.list_transactions()?
+            .into_iter()
+            .find(|transaction| transaction.get_id() == transaction_id))
+    }
+
+    ///
+    /// Return the stored transactions from or to the given entity, in order of appending.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A str that represents the id of the entity.
+    ///
+    fn list_transactions_by_entity(
+        &self,
+        entity_id: &str,
+    ) -> Result<Vec<Transaction>, StorageError> {
+        Ok(self.list_transactions()?
+            .into_iter()
+            .filter(|transaction| {
+                transaction.get_from_entity_id() == entity_id || transaction.get_to_entity_id() == entity_id
+            })
+            .collect())
+    }
+
+    ///
+    /// Save an open escrow, replacing the previously saved version of the escrow of the same transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `escrow` - An Escrow that represents the escrow to save.
+    ///
+    fn save_escrow(
+        &mut self,
+        escrow: &Escrow,
+    ) -> Result<(), StorageError>;
+
+    ///
+    /// Remove the saved escrow of the given transaction, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_id` - A str that represents the id of the transaction of the escrow.
+    ///
+    fn remove_escrow(
+        &mut self,
+        transaction_id: &str,
+    ) -> Result<(), StorageError>;
+
+    ///
+    /// Return the last saved version of all the escrows, ordered by transaction id.
+    ///
+    fn list_escrows(
+        &self,
+    ) -> Result<Vec<Escrow>, StorageError>;
+
+    ///
+    /// Save the response given to a request carrying an idempotency key, replacing the record saved with the same key.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - An IdempotencyRecord that represents the request and its response.
+    ///
+    fn save_idempotency_record(
+        &mut self,
+        record: &IdempotencyRecord,
+    ) -> Result<(), StorageError>;
+
+    ///
+    /// Return the record saved for the given idempotency key, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A str that represents the idempotency key.
+    ///
+    fn load_idempotency_record(
+        &self,
+        key: &str,
+    ) -> Result<Option<IdempotencyRecord>, StorageError>;
+
+    ///
+    /// Remove the idempotency records created before the given timestamp and return how many were removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - A u64 integer that represents the creation timestamp from which the records are kept.
+    ///
+    fn remove_idempotency_records_before(
+        &mut self,
+        timestamp: u64,
+    ) -> Result<usize, StorageError>;
+}
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Idempotency record structure
+/// It represents the response given to a request carrying an idempotency key, so that a retry of the request
+/// is answered with the same response instead of being run again, even after a restart.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdempotencyRecord {
+    key: String,
+    request: Value,
+    status: u16,
+    response: Value,
+    created_at: u64,
+}
+
+/// Idempotency record implementation.
+impl IdempotencyRecord {
+    ///
+    /// Return a new IdempotencyRecord.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A String that represents the idempotency key of the request.
+    /// * `request` - A Value that represents the body of the request.
+    /// * `status` - A u16 integer that represents the status of the response.
+    /// * `response` - A Value that represents the body of the response.
+    /// * `created_at` - A u64 integer that represents the timestamp at which the request was answered.
+    ///
+    /// # Example
+    /// ```
+    /// use serde_json::json;
+    /// use banana_coin::storage::IdempotencyRecord;
+    /// let record = IdempotencyRecord::new(
+    ///     "key_0001".to_string(),
+    ///     json!({ "amount": "10" }),
+    ///     201,
+    ///     json!({ "id": "id_0001" }),
+    ///     1
+    /// );
+    /// # assert_eq!(record.get_key(), "key_0001")
+    /// ```
+    ///
+    pub fn new(
+        key: String,
+        request: Value,
+        status: u16,
+        response: Value,
+        created_at: u64,
+    ) -> IdempotencyRecord {
+        IdempotencyRecord {
+            key,
+            request,
+            status,
+            response,
+            created_at,
+        }
+    }
+
+    ///
+    /// Return the idempotency key of the request.
+    ///
+    /// # Example
+    /// ```
+    /// # use serde_json::json;
+    /// # use banana_coin::storage::IdempotencyRecord;
+    /// # let record = IdempotencyRecord::new("key_0001".to_string(), json!({}), 201, json!({}), 1);
+    /// let key : &str = record.get_key();
+    /// # assert_eq!(key, "key_0001")
+    /// ```
+    ///
+    pub fn get_key(
+        self: &IdempotencyRecord,
+    ) -> &str {
+        &self.key
+    }
+
+    ///
+    /// Return the body of the request.
+    ///
+    /// # Example
+    /// ```
+    /// # use serde_json::json;
+    /// # use banana_coin::storage::IdempotencyRecord;
+    /// # let record = IdempotencyRecord::new("key_0001".to_string(), json!({ "amount": "10" }), 201, json!({}), 1);
+    /// let request : &serde_json::Value = record.get_request();
+    /// # assert_eq!(request["amount"], "10")
+    /// ```
+    ///
+    pub fn get_request(
+        self: &IdempotencyRecord,
+    ) -> &Value {
+        &self.request
+    }
+
+    ///
+    /// Return the status of the response.
+    ///
+    /// # Example
+    /// ```
+    /// # use serde_json::json;
+    /// # use banana_coin::storage::IdempotencyRecord;
+    /// # let record = IdempotencyRecord::new("key_0001".to_string(), json!({}), 201, json!({}), 1);
+    /// let status : u16 = record.get_status();
+    /// # assert_eq!(status, 201)
+    /// ```
+    ///
+    pub fn get_status(
+        self: &IdempotencyRecord,
+    ) -> u16 {
+        self.status
+    }
+
+    ///
+    /// Return the body of the response.
+    ///
+    /// # Example
+    /// ```
+    /// # use serde_json::json;
+    /// # use banana_coin::storage::IdempotencyRecord;
+    /// # let record = IdempotencyRecord::new("key_0001".to_string(), json!({}), 201, json!({ "id": "id_0001" }), 1);
+    /// let response : &serde_json::Value = record.get_response();
+    /// # assert_eq!(response["id"], "id_0001")
+    /// ```
+    ///
+    pub fn get_response(
+        self: &IdempotencyRecord,
+    ) -> &Value {
+        &self.response
+    }
+
+    ///
+    /// Return the timestamp at which the request was answered.
+    ///
+    /// # Example
+    /// ```
+    /// # use serde_json::json;
+    /// # use banana_coin::storage::IdempotencyRecord;
+    /// # let record = IdempotencyRecord::new("key_0001".to_string(), json!({}), 201, json!({}), 1);
+    /// let created_at : u64 = record.get_created_at();
+    /// # assert_eq!(created_at, 1)
+    /// ```
+    ///
+    pub fn get_created_at(
+        self: &IdempotencyRecord,
+    ) -> u64 {
+        self.created_at
+    }
+}
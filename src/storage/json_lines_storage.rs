@@ -0,0 +1,259 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::StorageError;
+use crate::model::{Entity, Escrow, Transaction};
+use crate::storage::{IdempotencyRecord, MemoryStorage, Storage};
+
+/// Record enumeration
+/// It represents a line of a JSON-lines storage file.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+enum Record {
+    Entity(Entity),
+    Transaction(Transaction),
+    /// Transaction stored along with the entities it changed, so that they are written at once.
+    TransactionWithEntities { transaction: Transaction, entities: Vec<Entity> },
+    Escrow(Escrow),
+    /// Removal of the escrow of the transaction.
+    EscrowRemoval { transaction_id: String },
+    IdempotencyRecord(IdempotencyRecord),
+    /// Removal of the idempotency records created before the timestamp.
+    IdempotencyRecordsRemoval { before: u64 },
+}
+
+/// JSON-lines storage structure
+/// It stores the entities, the transactions, the escrows and the idempotency records in an append-only file, one JSON record per line.
+/// Saving an entity or an escrow appends its new version; the last version in the file wins when the file is loaded.
+/// A transaction stored with the entities it changed is a single record, so that a failed write can not keep only some of them.
+/// Removing an escrow or idempotency records appends the removal, which is replayed when the file is loaded.
+/// The content of the file is cached in memory when the storage is opened, and the file stays locked while the storage is open,
+/// so that no other storage, in this process or in another one, changes it behind the cache.
+#[derive(Debug)]
+pub struct JsonLinesStorage {
+    /// Path of the storage file.
+    path: PathBuf,
+    /// Storage file, opened in append mode.
+    file: File,
+    /// In-memory copy of the content of the storage file.
+    cache: MemoryStorage,
+}
+
+/// JSON-lines storage implementation.
+impl JsonLinesStorage {
+    ///
+    /// Open the JSON-lines storage file at the given path, creating it if it does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A Path that represents the location of the storage file.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::storage::{JsonLinesStorage, Storage};
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_{}.jsonl", std::process::id()));
+    /// let storage = JsonLinesStorage::open(&path).unwrap();
+    /// # assert!(storage.list_entities().unwrap().is_empty());
+    /// # assert!(JsonLinesStorage::open(&path).is_err());
+    /// # drop(storage);
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A LockedStorageError is returned if the file is already open, in this process or in another one,
+    /// and another StorageError if the file can not be opened or if one of its lines is not a valid record.
+    ///
+    pub fn open(
+        path: &Path,
+    ) -> Result<JsonLinesStorage, StorageError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        // The lock is released when the file is closed, even if the process is killed
+        match file.try_lock() {
+            Ok(_) => {}
+            Err(TryLockError::WouldBlock) => return Err(StorageError::LockedStorageError { path: path.display().to_string() }),
+            Err(TryLockError::Error(error)) => return Err(StorageError::from(error)),
+        }
+        let mut cache = MemoryStorage::new();
+        for (index, line) in BufReader::new(&file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str(&line)
+                .map_err(|error| StorageError::CorruptedRecordError { line: index + 1, message: error.to_string() })?;
+            match record {
+                Record::Entity(entity) => cache.save_entity(&entity)?,
+                Record::Transaction(transaction) => {
+                    transaction
+                        .validate()
+                        .map_err(|error| StorageError::CorruptedRecordError { line: index + 1, message: error.to_string() })?;
+                    cache.append_transaction(&transaction)?
+                }
+                Record::TransactionWithEntities { transaction, entities } => {
+                    transaction
+                        .validate()
+                        .map_err(|error| StorageError::CorruptedRecordError { line: index + 1, message: error.to_string() })?;
+                    cache.append_transaction_with_entities(&transaction, &entities)?
+                }
+                Record::Escrow(escrow) => cache.save_escrow(&escrow)?,
+                Record::EscrowRemoval { transaction_id } => cache.remove_escrow(&transaction_id)?,
+                Record::IdempotencyRecord(record) => cache.save_idempotency_record(&record)?,
+                Record::IdempotencyRecordsRemoval { before } => {
+                    cache.remove_idempotency_records_before(before)?;
+                }
+            }
+        }
+        Ok(JsonLinesStorage {
+            path: path.to_path_buf(),
+            file,
+            cache,
+        })
+    }
+
+    ///
+    /// Return the path of the storage file.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::storage::JsonLinesStorage;
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_path_{}.jsonl", std::process::id()));
+    /// let storage = JsonLinesStorage::open(&path).unwrap();
+    /// assert_eq!(storage.get_path(), path.as_path());
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn get_path(
+        self: &JsonLinesStorage,
+    ) -> &Path {
+        &self.path
+    }
+
+    /// Append a record to the storage file.
+    fn append_record(
+        self: &mut JsonLinesStorage,
+        record: &Record,
+    ) -> Result<(), StorageError> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+impl Storage for JsonLinesStorage {
+    fn save_entity(
+        &mut self,
+        entity: &Entity,
+    ) -> Result<(), StorageError> {
+        self.append_record(&Record::Entity(entity.clone()))?;
+        self.cache.save_entity(entity)
+    }
+
+    fn load_entity(
+        &self,
+        entity_id: &str,
+    ) -> Result<Option<Entity>, StorageError> {
+        self.cache.load_entity(entity_id)
+    }
+
+    fn list_entities(
+        &self,
+    ) -> Result<Vec<Entity>, StorageError> {
+        self.cache.list_entities()
+    }
+
+    fn append_transaction(
+        &mut self,
+        transaction: &Transaction,
+    ) -> Result<(), StorageError> {
+        if self.cache.load_transaction(&transaction.get_id())?.is_some() {
+            return Err(StorageError::DuplicateTransactionError {
+                transaction_id: transaction.get_id(),
+            });
+        }
+        self.append_record(&Record::Transaction(transaction.clone()))?;
+        self.cache.append_transaction(transaction)
+    }
+
+    fn append_transaction_with_entities(
+        &mut self,
+        transaction: &Transaction,
+        entities: &[Entity],
+    ) -> Result<(), StorageError> {
+        if self.cache.load_transaction(&transaction.get_id())?.is_some() {
+            return Err(StorageError::DuplicateTransactionError {
+                transaction_id: transaction.get_id(),
+            });
+        }
+        self.append_record(&Record::TransactionWithEntities { transaction: transaction.clone(), entities: entities.to_vec() })?;
+        self.cache.append_transaction_with_entities(transaction, entities)
+    }
+
+    fn list_transactions(
+        &self,
+    ) -> Result<Vec<Transaction>, StorageError> {
+        self.cache.list_transactions()
+    }
+
+    fn save_escrow(
+        &mut self,
+        escrow: &Escrow,
+    ) -> Result<(), StorageError> {
+        self.append_record(&Record::Escrow(escrow.clone()))?;
+        self.cache.save_escrow(escrow)
+    }
+
+    fn remove_escrow(
+        &mut self,
+        transaction_id: &str,
+    ) -> Result<(), StorageError> {
+        // Like the removal of idempotency records, the removal is appended only if there is an escrow to remove.
+        if self.cache.list_escrows()?.iter().any(|escrow| escrow.get_transaction().get_id() == transaction_id) {
+            self.append_record(&Record::EscrowRemoval { transaction_id: transaction_id.to_string() })?;
+        }
+        self.cache.remove_escrow(transaction_id)
+    }
+
+    fn list_escrows(
+        &self,
+    ) -> Result<Vec<Escrow>, StorageError> {
+        self.cache.list_escrows()
+    }
+
+    fn save_idempotency_record(
+        &mut self,
+        record: &IdempotencyRecord,
+    ) -> Result<(), StorageError> {
+        self.append_record(&Record::IdempotencyRecord(record.clone()))?;
+        self.cache.save_idempotency_record(record)
+    }
+
+    fn load_idempotency_record(
+        &self,
+        key: &str,
+    ) -> Result<Option<IdempotencyRecord>, StorageError> {
+        self.cache.load_idempotency_record(key)
+    }
+
+    fn remove_idempotency_records_before(
+        &mut self,
+        timestamp: u64,
+    ) -> Result<usize, StorageError> {
+        // The removal is appended only if it removes a record, so that the file does not grow for nothing.
+        // If it can not be appended, the records are loaded again with the file and removed by the next removal.
+        let removed = self.cache.remove_idempotency_records_before(timestamp)?;
+        if removed > 0 {
+            self.append_record(&Record::IdempotencyRecordsRemoval { before: timestamp })?;
+        }
+        Ok(removed)
+    }
+}
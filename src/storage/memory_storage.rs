@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+
+use crate::error::StorageError;
+use crate::model::{Entity, Escrow, Transaction};
+use crate::storage::{IdempotencyRecord, Storage};
+
+/// In-memory storage structure
+/// It keeps the entities, the transactions, the escrows and the idempotency records in memory. Nothing survives the end of the process,
+/// so it is meant for tests and as a cache of the other backends.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    /// Last saved version of the entities, indexed by their ids.
+    entities: BTreeMap<String, Entity>,
+    /// Appended transactions, in order of appending.
+    transactions: Vec<Transaction>,
+    /// Last saved version of the escrows, indexed by the ids of their transactions.
+    escrows: BTreeMap<String, Escrow>,
+    /// Saved idempotency records, indexed by their keys.
+    idempotency_records: BTreeMap<String, IdempotencyRecord>,
+}
+
+/// In-memory storage implementation.
+impl MemoryStorage {
+    ///
+    /// Return a new empty MemoryStorage.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::storage::{MemoryStorage, Storage};
+    /// let storage = MemoryStorage::new();
+    /// # assert!(storage.list_entities().unwrap().is_empty())
+    /// ```
+    ///
+    pub fn new() -> MemoryStorage {
+        MemoryStorage {
+            entities: BTreeMap::new(),
+            transactions: Vec::new(),
+            escrows: BTreeMap::new(),
+            idempotency_records: BTreeMap::new(),
+        }
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn save_entity(
+        &mut self,
+        entity: &Entity,
+    ) -> Result<(), StorageError> {
+        self.entities.insert(entity.get_id().to_string(), entity.clone());
+        Ok(())
+    }
+
+    fn load_entity(
+        &self,
+        entity_id: &str,
+    ) -> Result<Option<Entity>, StorageError> {
+        Ok(self.entities.get(entity_id).cloned())
+    }
+
+    fn list_entities(
+        &self,
+    ) -> Result<Vec<Entity>, StorageError> {
+        Ok(self.entities.values().cloned().collect())
+    }
+
+    fn append_transaction(
+        &mut self,
+        transaction: &Transaction,
+    ) -> Result<(), StorageError> {
+        if self.transactions.iter().any(|stored| stored.get_id() == transaction.get_id()) {
+            return Err(StorageError::DuplicateTransactionError {
+                transaction_id: transaction.get_id(),
+            });
+        }
+        self.transactions.push(transaction.clone());
+        Ok(())
+    }
+
+    fn append_transaction_with_entities(
+        &mut self,
+        transaction: &Transaction,
+        entities: &[Entity],
+    ) -> Result<(), StorageError> {
+        self.append_transaction(transaction)?;
+        for entity in entities {
+            self.save_entity(entity)?;
+        }
+        Ok(())
+    }
+
+    fn list_transactions(
+        &self,
+    ) -> Result<Vec<Transaction>, StorageError> {
+        Ok(self.transactions.clone())
+    }
+
+    fn save_escrow(
+        &mut self,
+        escrow: &Escrow,
+    ) -> Result<(), StorageError> {
+        self.escrows.insert(escrow.get_transaction().get_id(), escrow.clone());
+        Ok(())
+    }
+
+    fn remove_escrow(
+        &mut self,
+        transaction_id: &str,
+    ) -> Result<(), StorageError> {
+        self.escrows.remove(transaction_id);
+        Ok(())
+    }
+
+    fn list_escrows(
+        &self,
+    ) -> Result<Vec<Escrow>, StorageError> {
+        Ok(self.escrows.values().cloned().collect())
+    }
+
+    fn save_idempotency_record(
+        &mut self,
+        record: &IdempotencyRecord,
+    ) -> Result<(), StorageError> {
+        self.idempotency_records.insert(record.get_key().to_string(), record.clone());
+        Ok(())
+    }
+
+    fn load_idempotency_record(
+        &self,
+        key: &str,
+    ) -> Result<Option<IdempotencyRecord>, StorageError> {
+        Ok(self.idempotency_records.get(key).cloned())
+    }
+
+    fn remove_idempotency_records_before(
+        &mut self,
+        timestamp: u64,
+    ) -> Result<usize, StorageError> {
+        let count = self.idempotency_records.len();
+        self.idempotency_records.retain(|_, record| record.get_created_at() >= timestamp);
+        Ok(count - self.idempotency_records.len())
+    }
+}
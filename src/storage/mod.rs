@@ -0,0 +1,21 @@
+/// Importing storage backend module
+pub mod backend;
+pub use backend::*;
+
+/// Importing idempotency record module
+pub mod idempotency_record;
+pub use idempotency_record::*;
+
+/// Importing in-memory storage module
+pub mod memory_storage;
+pub use memory_storage::*;
+
+/// Importing JSON-lines file storage module
+pub mod json_lines_storage;
+pub use json_lines_storage::*;
+
+/// Importing SQLite storage module
+#[cfg(feature = "sqlite")]
+pub mod sqlite_storage;
+#[cfg(feature = "sqlite")]
+pub use sqlite_storage::*;
@@ -0,0 +1,307 @@
+use std::convert::TryFrom;
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::error::StorageError;
+use crate::model::{Entity, Escrow, Transaction};
+use crate::storage::{IdempotencyRecord, Storage};
+
+/// SQLite storage structure
+/// It stores the entities, the transactions, the escrows and the idempotency records in an embedded SQLite database.
+/// Each record is kept as its JSON representation, next to the columns used to look it up.
+/// The records are validated when they are read, as the JSON-lines storage does when it is opened.
+/// A transaction stored with the entities it changed is written in a single SQLite transaction.
+#[derive(Debug)]
+pub struct SqliteStorage {
+    connection: Connection,
+}
+
+/// SQLite storage implementation.
+impl SqliteStorage {
+    ///
+    /// Open the SQLite database at the given path, creating it and its tables if they do not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A Path that represents the location of the database file.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::storage::{SqliteStorage, Storage};
+    /// # let path = std::env::temp_dir().join(format!("banana_coin_doc_{}.sqlite", std::process::id()));
+    /// let storage = SqliteStorage::open(&path).unwrap();
+    /// # assert!(storage.list_entities().unwrap().is_empty());
+    /// # std::fs::remove_file(&path).unwrap()
+    /// ```
+    ///
+    pub fn open(
+        path: &Path,
+    ) -> Result<SqliteStorage, StorageError> {
+        SqliteStorage::from_connection(Connection::open(path)?)
+    }
+
+    ///
+    /// Open a new SQLite database that lives in memory.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::storage::{SqliteStorage, Storage};
+    /// let storage = SqliteStorage::open_in_memory().unwrap();
+    /// # assert!(storage.list_transactions().unwrap().is_empty())
+    /// ```
+    ///
+    pub fn open_in_memory() -> Result<SqliteStorage, StorageError> {
+        SqliteStorage::from_connection(Connection::open_in_memory()?)
+    }
+
+    /// Create the tables of the storage, if they do not exist.
+    fn from_connection(
+        connection: Connection,
+    ) -> Result<SqliteStorage, StorageError> {
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS entities (
+                id TEXT PRIMARY KEY NOT NULL,
+                record TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS transactions (
+                sequence INTEGER PRIMARY KEY AUTOINCREMENT,
+                id TEXT UNIQUE NOT NULL,
+                from_entity_id TEXT NOT NULL,
+                to_entity_id TEXT NOT NULL,
+                record TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS transactions_from_entity_id ON transactions (from_entity_id);
+            CREATE INDEX IF NOT EXISTS transactions_to_entity_id ON transactions (to_entity_id);
+            CREATE TABLE IF NOT EXISTS escrows (
+                transaction_id TEXT PRIMARY KEY NOT NULL,
+                record TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS idempotency_records (
+                key TEXT PRIMARY KEY NOT NULL,
+                created_at INTEGER NOT NULL,
+                record TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idempotency_records_created_at ON idempotency_records (created_at);"
+        )?;
+        Ok(SqliteStorage { connection })
+    }
+
+    /// Deserialize and validate the transactions returned by the given query, which selects their sequence and record.
+    fn query_transactions(
+        self: &SqliteStorage,
+        query: &str,
+        parameters: &[&dyn rusqlite::ToSql],
+    ) -> Result<Vec<Transaction>, StorageError> {
+        let mut statement = self.connection.prepare(query)?;
+        let records = statement.query_map(parameters, |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+        let mut transactions = Vec::new();
+        for record in records {
+            let (sequence, record) = record?;
+            let transaction: Transaction = decode_record(sequence, &record)?;
+            transaction
+                .validate()
+                .map_err(|error| corrupted_record(sequence, error.to_string()))?;
+            transactions.push(transaction);
+        }
+        Ok(transactions)
+    }
+}
+
+/// Deserialize a record of the database, which is corrupted if it is not valid JSON of the expected type.
+/// The line of a corrupted record is its row id, the sequence of a transaction.
+fn decode_record<T: serde::de::DeserializeOwned>(
+    row_id: i64,
+    record: &str,
+) -> Result<T, StorageError> {
+    serde_json::from_str(record).map_err(|error| corrupted_record(row_id, error.to_string()))
+}
+
+/// Save an entity through the given connection, replacing the previously saved version with the same id.
+fn insert_entity(
+    connection: &Connection,
+    entity: &Entity,
+) -> Result<(), StorageError> {
+    connection.execute(
+        "INSERT OR REPLACE INTO entities (id, record) VALUES (?1, ?2)",
+        params![entity.get_id(), serde_json::to_string(entity)?],
+    )?;
+    Ok(())
+}
+
+/// Return the error of a corrupted record of the database, identified by its row id.
+fn corrupted_record(
+    row_id: i64,
+    message: String,
+) -> StorageError {
+    StorageError::CorruptedRecordError { line: usize::try_from(row_id).unwrap_or_default(), message }
+}
+
+impl Storage for SqliteStorage {
+    fn save_entity(
+        &mut self,
+        entity: &Entity,
+    ) -> Result<(), StorageError> {
+        insert_entity(&self.connection, entity)
+    }
+
+    fn load_entity(
+        &self,
+        entity_id: &str,
+    ) -> Result<Option<Entity>, StorageError> {
+        let record: Option<(i64, String)> = self.connection
+            .query_row(
+                "SELECT rowid, record FROM entities WHERE id = ?1",
+                params![entity_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        match record {
+            Some((row_id, record)) => Ok(Some(decode_record(row_id, &record)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn list_entities(
+        &self,
+    ) -> Result<Vec<Entity>, StorageError> {
+        let mut statement = self.connection.prepare("SELECT rowid, record FROM entities ORDER BY id")?;
+        let records = statement.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+        let mut entities = Vec::new();
+        for record in records {
+            let (row_id, record) = record?;
+            entities.push(decode_record(row_id, &record)?);
+        }
+        Ok(entities)
+    }
+
+    fn append_transaction(
+        &mut self,
+        transaction: &Transaction,
+    ) -> Result<(), StorageError> {
+        self.append_transaction_with_entities(transaction, &[])
+    }
+
+    fn append_transaction_with_entities(
+        &mut self,
+        transaction: &Transaction,
+        entities: &[Entity],
+    ) -> Result<(), StorageError> {
+        if self.load_transaction(&transaction.get_id())?.is_some() {
+            return Err(StorageError::DuplicateTransactionError {
+                transaction_id: transaction.get_id(),
+            });
+        }
+        // Dropping the SQLite transaction without committing it rolls back what was written
+        let sqlite_transaction = self.connection.transaction()?;
+        sqlite_transaction.execute(
+            "INSERT INTO transactions (id, from_entity_id, to_entity_id, record) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                transaction.get_id(),
+                transaction.get_from_entity_id(),
+                transaction.get_to_entity_id(),
+                serde_json::to_string(transaction)?
+            ],
+        )?;
+        for entity in entities {
+            insert_entity(&sqlite_transaction, entity)?;
+        }
+        sqlite_transaction.commit()?;
+        Ok(())
+    }
+
+    fn list_transactions(
+        &self,
+    ) -> Result<Vec<Transaction>, StorageError> {
+        self.query_transactions("SELECT sequence, record FROM transactions ORDER BY sequence", &[])
+    }
+
+    fn load_transaction(
+        &self,
+        transaction_id: &str,
+    ) -> Result<Option<Transaction>, StorageError> {
+        Ok(self
+            .query_transactions("SELECT sequence, record FROM transactions WHERE id = ?1", &[&transaction_id])?
+            .pop())
+    }
+
+    fn list_transactions_by_entity(
+        &self,
+        entity_id: &str,
+    ) -> Result<Vec<Transaction>, StorageError> {
+        self.query_transactions(
+            "SELECT sequence, record FROM transactions WHERE from_entity_id = ?1 OR to_entity_id = ?1 ORDER BY sequence",
+            &[&entity_id],
+        )
+    }
+
+    fn save_escrow(
+        &mut self,
+        escrow: &Escrow,
+    ) -> Result<(), StorageError> {
+        self.connection.execute(
+            "INSERT OR REPLACE INTO escrows (transaction_id, record) VALUES (?1, ?2)",
+            params![escrow.get_transaction().get_id(), serde_json::to_string(escrow)?],
+        )?;
+        Ok(())
+    }
+
+    fn remove_escrow(
+        &mut self,
+        transaction_id: &str,
+    ) -> Result<(), StorageError> {
+        self.connection.execute("DELETE FROM escrows WHERE transaction_id = ?1", params![transaction_id])?;
+        Ok(())
+    }
+
+    fn list_escrows(
+        &self,
+    ) -> Result<Vec<Escrow>, StorageError> {
+        let mut statement = self.connection.prepare("SELECT rowid, record FROM escrows ORDER BY transaction_id")?;
+        let records = statement.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+        let mut escrows = Vec::new();
+        for record in records {
+            let (row_id, record) = record?;
+            escrows.push(decode_record(row_id, &record)?);
+        }
+        Ok(escrows)
+    }
+
+    fn save_idempotency_record(
+        &mut self,
+        record: &IdempotencyRecord,
+    ) -> Result<(), StorageError> {
+        self.connection.execute(
+            "INSERT OR REPLACE INTO idempotency_records (key, created_at, record) VALUES (?1, ?2, ?3)",
+            params![record.get_key(), i64::try_from(record.get_created_at()).unwrap_or(i64::MAX), serde_json::to_string(record)?],
+        )?;
+        Ok(())
+    }
+
+    fn load_idempotency_record(
+        &self,
+        key: &str,
+    ) -> Result<Option<IdempotencyRecord>, StorageError> {
+        let record: Option<(i64, String)> = self.connection
+            .query_row(
+                "SELECT rowid, record FROM idempotency_records WHERE key = ?1",
+                params![key],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        match record {
+            Some((row_id, record)) => Ok(Some(decode_record(row_id, &record)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn remove_idempotency_records_before(
+        &mut self,
+        timestamp: u64,
+    ) -> Result<usize, StorageError> {
+        Ok(self.connection.execute(
+            "DELETE FROM idempotency_records WHERE created_at < ?1",
+            params![i64::try_from(timestamp).unwrap_or(i64::MAX)],
+        )?)
+    }
+}
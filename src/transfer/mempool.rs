@@ -0,0 +1,662 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::error::MempoolError;
+use crate::event::{transaction_changes, EventBus};
+use crate::model::{Amount, Entity, Transaction, TransactionState, DEFAULT_ASSET};
+use crate::model::TransactionState::*;
+
+/// Mempool structure
+/// It represents the staging area of the transactions waiting to be executed or included in a block.
+/// A transaction is accepted only if the sender can afford it together with its other pending transactions,
+/// within the limit of the overdraft policy of its wallet,
+/// and if it carries the nonce that follows the ones of the other pending transactions of the sender.
+/// Transactions are waiting in the Created state and are moved to the OnProcess state when they are picked in a batch.
+/// The changes of state of the transactions are published on the event bus of the mempool.
+#[derive(Debug, Clone)]
+pub struct Mempool {
+    /// Pending transactions, in order of arrival.
+    transactions: Vec<Transaction>,
+    /// How long a transaction can wait since its creation before it expires.
+    max_age: u64,
+    /// Event bus the changes of state are published on.
+    event_bus: EventBus,
+}
+
+/// Mempool implementation.
+impl Mempool {
+    ///
+    /// Return a new empty Mempool.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_age` - A u64 integer that represents how long a transaction can wait since its creation before it expires.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::transfer::Mempool;
+    /// let mempool = Mempool::new(
+    ///     3600
+    /// );
+    /// # assert_eq!(mempool.get_max_age(), 3600)
+    /// ```
+    ///
+    pub fn new(
+        max_age: u64,
+    ) -> Mempool {
+        Mempool {
+            transactions: Vec::new(),
+            max_age,
+            event_bus: EventBus::new(),
+        }
+    }
+
+    ///
+    /// Set the event bus the changes of state of the transactions are published on.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_bus` - An EventBus that represents the bus the changes are published on.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::event::EventBus;
+    /// # use banana_coin::transfer::Mempool;
+    /// let mut mempool = Mempool::new(3600);
+    /// let event_bus = EventBus::new();
+    /// let receiver = event_bus.subscribe_channel();
+    /// mempool.set_event_bus(event_bus);
+    /// # assert_eq!(mempool.get_event_bus().get_subscriber_count(), 1)
+    /// ```
+    ///
+    pub fn set_event_bus(
+        self: &mut Mempool,
+        event_bus: EventBus,
+    ) {
+        self.event_bus = event_bus;
+    }
+
+    ///
+    /// Return the event bus the changes of state of the transactions are published on. The returned bus shares
+    /// its subscribers with the bus of the mempool.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::transfer::Mempool;
+    /// # let mempool = Mempool::new(3600);
+    /// let receiver = mempool.get_event_bus().subscribe_channel();
+    /// # assert_eq!(mempool.get_event_bus().get_subscriber_count(), 1)
+    /// ```
+    ///
+    pub fn get_event_bus(
+        self: &Mempool,
+    ) -> EventBus {
+        self.event_bus.clone()
+    }
+
+    ///
+    /// Return how long a transaction can wait since its creation before it expires.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::transfer::Mempool;
+    /// # let mempool = Mempool::new(3600);
+    /// let max_age : u64 = mempool.get_max_age();
+    /// # assert_eq!(max_age, 3600)
+    /// ```
+    ///
+    pub fn get_max_age(
+        self: &Mempool,
+    ) -> u64 {
+        self.max_age
+    }
+
+    ///
+    /// Return a copy of the transactions of the mempool, in order of arrival.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::transfer::Mempool;
+    /// # use banana_coin::model::Transaction;
+    /// # let mempool = Mempool::new(3600);
+    /// let transactions : Vec<Transaction> = mempool.get_transactions();
+    /// # assert!(transactions.is_empty())
+    /// ```
+    ///
+    pub fn get_transactions(
+        self: &Mempool,
+    ) -> Vec<Transaction> {
+        self.transactions.clone()
+    }
+
+    ///
+    /// Return a copy of the transaction with the given id, if it is in the mempool.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_id` - A str that represents the id of the transaction.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::transfer::Mempool;
+    /// # let mempool = Mempool::new(3600);
+    /// let transaction = mempool.get_transaction("id_0001");
+    /// # assert!(transaction.is_none())
+    /// ```
+    ///
+    pub fn get_transaction(
+        self: &Mempool,
+        transaction_id: &str,
+    ) -> Option<Transaction> {
+        self.transactions.iter().find(|transaction| transaction.get_id() == transaction_id).cloned()
+    }
+
+    ///
+    /// Return the sum of the amounts and fees of the transactions of the mempool sent by the given entity
+    /// in the default asset.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A str that represents the id of the sender entity.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::Amount;
+    /// # use banana_coin::transfer::Mempool;
+    /// # let mempool = Mempool::new(3600);
+    /// let pending_amount : Amount = mempool.get_pending_amount("entity_001").unwrap();
+    /// # assert!(pending_amount.is_zero())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An AmountOverflowError is returned if the sum of the pending coins is out of range.
+    ///
+    pub fn get_pending_amount(
+        self: &Mempool,
+        entity_id: &str,
+    ) -> Result<Amount, MempoolError> {
+        self.get_pending_asset_amount(entity_id, DEFAULT_ASSET)
+    }
+
+    ///
+    /// Return the sum of the amounts and fees of the transactions of the mempool sent by the given entity
+    /// in the given asset.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A str that represents the id of the sender entity.
+    /// * `asset` - A str that represents the code of the asset.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::Amount;
+    /// # use banana_coin::transfer::Mempool;
+    /// # let mempool = Mempool::new(3600);
+    /// let pending_amount : Amount = mempool.get_pending_asset_amount("entity_001", "GOLD").unwrap();
+    /// # assert!(pending_amount.is_zero())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An AmountOverflowError is returned if the sum of the pending coins is out of range.
+    ///
+    pub fn get_pending_asset_amount(
+        self: &Mempool,
+        entity_id: &str,
+        asset: &str,
+    ) -> Result<Amount, MempoolError> {
+        let overflow_error = || MempoolError::AmountOverflowError { entity_id: entity_id.to_string(), asset: asset.to_string() };
+        self.transactions.iter()
+            .filter(|transaction| transaction.get_from_entity_id() == entity_id && transaction.get_asset() == asset)
+            .try_fold(Amount::zero(), |pending_amount, transaction| {
+                let amount = transaction.get_total_amount().map_err(|_| overflow_error())?;
+                pending_amount.checked_add(amount).ok_or_else(overflow_error)
+            })
+    }
+
+    ///
+    /// Return the nonce the next transaction of the given entity must carry to be accepted:
+    /// the one following the highest nonce of its pending transactions, or the current nonce of the entity
+    /// if it has none.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity` - An Entity that represents the sender, with its current nonce.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
+    /// # use banana_coin::transfer::Mempool;
+    /// # let mempool = Mempool::new(3600);
+    /// # let entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// let nonce : u64 = mempool.get_next_nonce(&entity);
+    /// # assert_eq!(nonce, 0)
+    /// ```
+    ///
+    pub fn get_next_nonce(
+        self: &Mempool,
+        entity: &Entity,
+    ) -> u64 {
+        self.transactions.iter()
+            .filter(|transaction| transaction.get_from_entity_id() == entity.get_id())
+            .map(|transaction| transaction.get_nonce().saturating_add(1))
+            .fold(entity.get_nonce(), u64::max)
+    }
+
+    ///
+    /// Add a transaction to the mempool, after checking it against the current available balance and nonce of the sender
+    /// and the other pending transactions of the sender.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - A Transaction in the Created state that represents the new transaction.
+    /// * `from_entity` - An Entity whose id is the 'from_entity_id' of the transaction, with its current wallet.
+    ///
+    /// # Errors
+    ///
+    /// Return a MempoolError if the transaction is not valid, is not in the Created state, is already in the mempool,
+    /// does not match the given entity, does not carry the next nonce of the sender or can not be afforded by the sender.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Entity, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::transfer::Mempool;
+    /// let mut mempool = Mempool::new(3600);
+    /// let entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(),
+    ///     Amount::from_integer(100), "".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// mempool.submit(transaction, &entity).unwrap();
+    /// # assert_eq!(mempool.get_pending_amount("entity_001").unwrap(), Amount::from_integer(100))
+    /// ```
+    ///
+    pub fn submit(
+        self: &mut Mempool,
+        transaction: Transaction,
+        from_entity: &Entity,
+    ) -> Result<(), MempoolError> {
+        let transaction_id = transaction.get_id();
+        if let Err(error) = transaction.validate() {
+            return Err(MempoolError::TransactionOperationError { transaction_id, error });
+        }
+        if !transaction.get_current_state().is_same_state(&Created { timestamp: 0 }) {
+            return Err(MempoolError::InvalidTransactionStateError {
+                transaction_id,
+                current_state: transaction.get_current_state(),
+            });
+        }
+        if self.get_transaction(&transaction_id).is_some() {
+            return Err(MempoolError::DuplicateTransactionError { transaction_id });
+        }
+        if transaction.get_from_entity_id() != from_entity.get_id() {
+            return Err(MempoolError::EntityMismatchError {
+                expected_entity_id: transaction.get_from_entity_id(),
+                actual_entity_id: from_entity.get_id().to_string(),
+            });
+        }
+        let expected_nonce = self.get_next_nonce(from_entity);
+        if transaction.get_nonce() < expected_nonce {
+            return Err(MempoolError::ReplayedNonceError {
+                entity_id: from_entity.get_id().to_string(),
+                expected_nonce,
+                found_nonce: transaction.get_nonce(),
+            });
+        }
+        if transaction.get_nonce() > expected_nonce {
+            return Err(MempoolError::OutOfOrderNonceError {
+                entity_id: from_entity.get_id().to_string(),
+                expected_nonce,
+                found_nonce: transaction.get_nonce(),
+            });
+        }
+        let asset = transaction.get_asset();
+        // The holds of the sender expired when the transaction was created do not reduce its balance
+        let created_at = transaction.get_current_state().get_timestamp();
+        let balance = from_entity.get_wallet().get_available_asset_balance(&asset, created_at)
+            .map_err(|_| MempoolError::AmountOverflowError { entity_id: from_entity.get_id().to_string(), asset: asset.clone() })?;
+        let pending_amount = self.get_pending_asset_amount(from_entity.get_id(), &asset)?;
+        // The amount plus the fee can not overflow, since the transaction is valid
+        let total_amount = transaction.get_total_amount().unwrap_or_default();
+        let required_amount = pending_amount.checked_add(total_amount)
+            .ok_or_else(|| MempoolError::AmountOverflowError { entity_id: from_entity.get_id().to_string(), asset: asset.clone() })?;
+        let affordable = match balance.checked_sub(required_amount) {
+            Some(remaining) => from_entity.get_wallet().get_overdraft_policy().allows(remaining),
+            None => false,
+        };
+        if !affordable {
+            return Err(MempoolError::InsufficientBalanceError {
+                entity_id: from_entity.get_id().to_string(),
+                asset,
+                required_amount,
+            });
+        }
+        self.transactions.push(transaction);
+        Ok(())
+    }
+
+    ///
+    /// Pick the waiting transactions of the mempool paying the highest fee per byte of their JSON representation,
+    /// up to the given number, and move them to the OnProcess state. The transactions of a sender are always picked
+    /// in order of nonce, and transactions paying the same fee per byte are picked in order of arrival.
+    /// The picked transactions stay in the mempool until their batch is confirmed, requeued or failed.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_size` - A usize integer that represents the maximum number of transactions of the batch.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the state change.
+    ///
+    /// # Errors
+    ///
+    /// Return a TransactionOperationError if a transaction can not be moved to the OnProcess state.
+    /// In that case no transaction is picked.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::Transaction;
+    /// # use banana_coin::transfer::Mempool;
+    /// # let mut mempool = Mempool::new(3600);
+    /// let batch : Vec<Transaction> = mempool.take_batch(10, 2).unwrap();
+    /// # assert!(batch.is_empty())
+    /// ```
+    ///
+    pub fn take_batch(
+        self: &mut Mempool,
+        max_size: usize,
+        timestamp: u64,
+    ) -> Result<Vec<Transaction>, MempoolError> {
+        let mut picked: Vec<(usize, Transaction)> = Vec::new();
+        while picked.len() < max_size {
+            // The next candidate of each sender is its oldest waiting transaction that has not been picked yet
+            let mut candidates: BTreeMap<String, usize> = BTreeMap::new();
+            for (index, transaction) in self.transactions.iter().enumerate() {
+                if transaction.get_current_state().is_same_state(&Created { timestamp: 0 })
+                    && !picked.iter().any(|(picked_index, _)| *picked_index == index) {
+                    candidates.entry(transaction.get_from_entity_id()).or_insert(index);
+                }
+            }
+            let mut best_index: Option<usize> = None;
+            for index in candidates.values().copied() {
+                let preferred = match best_index {
+                    Some(best) => {
+                        let transaction = &self.transactions[index];
+                        let best_transaction = &self.transactions[best];
+                        Mempool::pays_more_per_byte(transaction, best_transaction)
+                            || (index < best && !Mempool::pays_more_per_byte(best_transaction, transaction))
+                    }
+                    None => true,
+                };
+                if preferred {
+                    best_index = Some(index);
+                }
+            }
+            let index = match best_index {
+                Some(index) => index,
+                None => break,
+            };
+            let mut transaction = self.transactions[index].clone();
+            Mempool::transition(&mut transaction, OnProcess { timestamp })?;
+            picked.push((index, transaction));
+        }
+        for (index, transaction) in picked.iter() {
+            self.transactions[*index] = transaction.clone();
+        }
+        let batch: Vec<Transaction> = picked.into_iter().map(|(_, transaction)| transaction).collect();
+        self.publish_last_states(&batch);
+        Ok(batch)
+    }
+
+    ///
+    /// Remove the transactions of an accepted batch from the mempool and return them.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_ids` - A slice of String that represents the ids of the transactions of the batch.
+    ///
+    /// # Errors
+    ///
+    /// Return a MempoolError if a transaction is not in the mempool or is not in the OnProcess state.
+    /// In that case the mempool is left unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::transfer::Mempool;
+    /// # let mut mempool = Mempool::new(3600);
+    /// let result = mempool.confirm_batch(&["id_0001".to_string()]);
+    /// # assert!(result.is_err())
+    /// ```
+    ///
+    pub fn confirm_batch(
+        self: &mut Mempool,
+        transaction_ids: &[String],
+    ) -> Result<Vec<Transaction>, MempoolError> {
+        self.find_batch(transaction_ids)?;
+        Ok(self.remove_batch(transaction_ids))
+    }
+
+    ///
+    /// Move the transactions of a rejected batch back to the Created state, so that they can be picked again.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_ids` - A slice of String that represents the ids of the transactions of the batch.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the state change.
+    ///
+    /// # Errors
+    ///
+    /// Return a MempoolError if a transaction is not in the mempool, is not in the OnProcess state
+    /// or can not be moved to the Created state. In that case the mempool is left unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::transfer::Mempool;
+    /// # let mut mempool = Mempool::new(3600);
+    /// let result = mempool.requeue_batch(&["id_0001".to_string()], 3);
+    /// # assert!(result.is_err())
+    /// ```
+    ///
+    pub fn requeue_batch(
+        self: &mut Mempool,
+        transaction_ids: &[String],
+        timestamp: u64,
+    ) -> Result<(), MempoolError> {
+        let batch = self.transition_batch(transaction_ids, Created { timestamp })?;
+        for (index, transaction) in batch {
+            self.transactions[index] = transaction;
+        }
+        let requeued: Vec<Transaction> = transaction_ids.iter()
+            .filter_map(|transaction_id| self.get_transaction(transaction_id))
+            .collect();
+        self.publish_last_states(&requeued);
+        Ok(())
+    }
+
+    ///
+    /// Move the transactions of a rejected batch to the Failed state, remove them from the mempool and return them.
+    /// The waiting transactions of the same senders carrying a higher nonce can no longer be executed,
+    /// so they are failed and returned as well, after the ones of the batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction_ids` - A slice of String that represents the ids of the transactions of the batch.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the state change.
+    ///
+    /// # Errors
+    ///
+    /// Return a MempoolError if a transaction is not in the mempool, is not in the OnProcess state
+    /// or can not be moved to the Failed state. In that case the mempool is left unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::transfer::Mempool;
+    /// # let mut mempool = Mempool::new(3600);
+    /// let result = mempool.fail_batch(&["id_0001".to_string()], 3);
+    /// # assert!(result.is_err())
+    /// ```
+    ///
+    pub fn fail_batch(
+        self: &mut Mempool,
+        transaction_ids: &[String],
+        timestamp: u64,
+    ) -> Result<Vec<Transaction>, MempoolError> {
+        let batch: Vec<Transaction> = self.transition_batch(transaction_ids, Failed { timestamp })?.into_iter()
+            .map(|(_, transaction)| transaction)
+            .collect();
+        self.fail_with_successors(batch, timestamp)
+    }
+
+    ///
+    /// Move the waiting transactions created more than the maximum age before the given timestamp
+    /// to the Failed state, remove them from the mempool and return them.
+    /// Transactions in the OnProcess state do not expire. The waiting transactions of the same senders
+    /// carrying a higher nonce can no longer be executed, so they are failed and returned as well, after the expired ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - A u64 integer that represents the current timestamp.
+    ///
+    /// # Errors
+    ///
+    /// Return a TransactionOperationError if a transaction can not be moved to the Failed state.
+    /// In that case the mempool is left unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::Transaction;
+    /// # use banana_coin::transfer::Mempool;
+    /// # let mut mempool = Mempool::new(3600);
+    /// let expired : Vec<Transaction> = mempool.expire(7200).unwrap();
+    /// # assert!(expired.is_empty())
+    /// ```
+    ///
+    pub fn expire(
+        self: &mut Mempool,
+        timestamp: u64,
+    ) -> Result<Vec<Transaction>, MempoolError> {
+        let expired_ids: Vec<String> = self.transactions.iter()
+            .filter(|transaction| transaction.get_current_state().is_same_state(&Created { timestamp: 0 }))
+            .filter(|transaction| transaction.get_creation_timestamp().saturating_add(self.max_age) < timestamp)
+            .map(|transaction| transaction.get_id())
+            .collect();
+        let mut expired = Vec::new();
+        for transaction_id in expired_ids.iter() {
+            let mut transaction = self.get_transaction(transaction_id)
+                .ok_or_else(|| MempoolError::TransactionNotFoundError { transaction_id: transaction_id.clone() })?;
+            Mempool::transition(&mut transaction, Failed { timestamp })?;
+            expired.push(transaction);
+        }
+        self.fail_with_successors(expired, timestamp)
+    }
+
+    /// Publish the last change of state of the given transactions, in order.
+    fn publish_last_states(
+        self: &Mempool,
+        transactions: &[Transaction],
+    ) {
+        for transaction in transactions {
+            let seen_states = transaction.get_state_history().len().saturating_sub(1);
+            self.event_bus.publish_all(transaction_changes(transaction, seen_states, Vec::new()));
+        }
+    }
+
+    /// Remove the given failed transactions from the mempool together with the waiting transactions of the same senders
+    /// carrying a higher nonce, moved to the Failed state, and return them all. The mempool is left unchanged on error.
+    fn fail_with_successors(
+        self: &mut Mempool,
+        failed: Vec<Transaction>,
+        timestamp: u64,
+    ) -> Result<Vec<Transaction>, MempoolError> {
+        let failed_ids: BTreeSet<String> = failed.iter().map(|transaction| transaction.get_id()).collect();
+        let mut lowest_nonces: BTreeMap<String, u64> = BTreeMap::new();
+        for transaction in failed.iter() {
+            let nonce = lowest_nonces.entry(transaction.get_from_entity_id()).or_insert(u64::MAX);
+            *nonce = (*nonce).min(transaction.get_nonce());
+        }
+        let mut removed = failed;
+        for transaction in self.transactions.iter() {
+            let is_successor = lowest_nonces.get(&transaction.get_from_entity_id())
+                .is_some_and(|lowest_nonce| transaction.get_nonce() > *lowest_nonce);
+            if is_successor && !failed_ids.contains(&transaction.get_id())
+                && transaction.get_current_state().is_same_state(&Created { timestamp: 0 }) {
+                let mut successor = transaction.clone();
+                Mempool::transition(&mut successor, Failed { timestamp })?;
+                removed.push(successor);
+            }
+        }
+        let removed_ids: Vec<String> = removed.iter().map(|transaction| transaction.get_id()).collect();
+        self.remove_batch(&removed_ids);
+        self.publish_last_states(&removed);
+        Ok(removed)
+    }
+
+    /// Return the positions and copies of the transactions of a batch, checking that they are all in the OnProcess state.
+    fn find_batch(
+        self: &Mempool,
+        transaction_ids: &[String],
+    ) -> Result<Vec<(usize, Transaction)>, MempoolError> {
+        let mut batch = Vec::new();
+        for transaction_id in transaction_ids.iter() {
+            let index = self.transactions.iter()
+                .position(|transaction| &transaction.get_id() == transaction_id)
+                .ok_or_else(|| MempoolError::TransactionNotFoundError { transaction_id: transaction_id.clone() })?;
+            let transaction = self.transactions[index].clone();
+            if !transaction.get_current_state().is_same_state(&OnProcess { timestamp: 0 }) {
+                return Err(MempoolError::InvalidTransactionStateError {
+                    transaction_id: transaction_id.clone(),
+                    current_state: transaction.get_current_state(),
+                });
+            }
+            batch.push((index, transaction));
+        }
+        Ok(batch)
+    }
+
+    /// Return the positions and copies of the transactions of a batch moved to the given state, without changing the mempool.
+    fn transition_batch(
+        self: &Mempool,
+        transaction_ids: &[String],
+        state: TransactionState,
+    ) -> Result<Vec<(usize, Transaction)>, MempoolError> {
+        let mut batch = self.find_batch(transaction_ids)?;
+        for (_, transaction) in batch.iter_mut() {
+            Mempool::transition(transaction, state.clone())?;
+        }
+        Ok(batch)
+    }
+
+    /// Remove the transactions with the given ids from the mempool and return them.
+    fn remove_batch(
+        self: &mut Mempool,
+        transaction_ids: &[String],
+    ) -> Vec<Transaction> {
+        let ids: BTreeSet<&String> = transaction_ids.iter().collect();
+        let (removed, kept) = self.transactions.drain(..)
+            .partition(|transaction| ids.contains(&transaction.get_id()));
+        self.transactions = kept;
+        removed
+    }
+
+    /// Return true if the first transaction pays a strictly higher fee per byte of its JSON representation than the second one.
+    fn pays_more_per_byte(
+        transaction: &Transaction,
+        other_transaction: &Transaction,
+    ) -> bool {
+        let size = |transaction: &Transaction| serde_json::to_vec(transaction).map(|bytes| bytes.len()).unwrap_or(1) as i128;
+        let fee = |transaction: &Transaction| transaction.get_fee().unwrap_or_default();
+        match (fee(transaction).checked_mul(size(other_transaction)), fee(other_transaction).checked_mul(size(transaction))) {
+            (Some(fee_per_byte), Some(other_fee_per_byte)) => fee_per_byte > other_fee_per_byte,
+            (None, Some(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// Move a transaction to a new state, wrapping the error.
+    fn transition(
+        transaction: &mut Transaction,
+        state: TransactionState,
+    ) -> Result<(), MempoolError> {
+        transaction.transition_to(state).map_err(|error| MempoolError::TransactionOperationError {
+            transaction_id: transaction.get_id(),
+            error,
+        })
+    }
+}
@@ -0,0 +1,7 @@
+/// Importing transfer executor module
+pub mod transfer_executor;
+pub use transfer_executor::*;
+
+/// Importing mempool module
+pub mod mempool;
+pub use mempool::*;
@@ -0,0 +1,804 @@
+use crate::error::{EscrowError, TransferError};
+use crate::event::{balance_changes, transaction_changes, EventBus};
+use crate::ledger::ESCROW_ACCOUNT_ID;
+use crate::model::{Entity, Escrow, ReleaseCondition, Transaction, TransactionState, Wallet};
+use crate::model::TransactionState::*;
+
+/// Transfer executor structure
+/// It moves the coins of a transaction, in the asset named by the transaction, from the wallet of an entity to the wallet of another entity.
+/// The transfer is atomic: either both wallets are updated or none of them is.
+/// A transaction whose sender has a public key is executed only if it is signed with the key pair of the sender.
+/// The wallet of the sender can go below zero only as far as its overdraft policy allows.
+/// The coins of an escrow transaction are held in the wallet of the escrow account until the escrow is released or refunded.
+/// The changes of the transactions and of the balances are published on the event bus of the executor, if it has one.
+#[derive(Debug, Clone, Default)]
+pub struct TransferExecutor {
+    /// Whether every sender must have a public key, so that every executed transaction is signed.
+    require_signature: bool,
+    /// Event bus the changes are published on, if any.
+    event_bus: Option<EventBus>,
+}
+
+/// Transfer executor implementation.
+impl TransferExecutor {
+    ///
+    /// Return a new TransferExecutor that executes the transactions of senders without a public key.
+    /// See 'with_required_signature' to refuse them.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::transfer::TransferExecutor;
+    /// let executor = TransferExecutor::new();
+    /// # assert!(!executor.is_signature_required())
+    /// ```
+    ///
+    pub fn new() -> TransferExecutor {
+        TransferExecutor {
+            require_signature: false,
+            event_bus: None,
+        }
+    }
+
+    ///
+    /// Return the executor set to refuse, or not, the transactions from senders without a public key.
+    ///
+    /// # Arguments
+    ///
+    /// * `require_signature` - A boolean that represents if transactions from senders without a public key are refused.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::transfer::TransferExecutor;
+    /// let executor = TransferExecutor::new().with_required_signature(true);
+    /// # assert!(executor.is_signature_required())
+    /// ```
+    ///
+    pub fn with_required_signature(
+        mut self: TransferExecutor,
+        require_signature: bool,
+    ) -> TransferExecutor {
+        self.require_signature = require_signature;
+        self
+    }
+
+    ///
+    /// Return whether the executor refuses the transactions from senders without a public key.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// let executor = TransferExecutor::new();
+    /// let required : bool = executor.is_signature_required();
+    /// # assert!(!required)
+    /// ```
+    ///
+    pub fn is_signature_required(
+        self: &TransferExecutor,
+    ) -> bool {
+        self.require_signature
+    }
+
+    ///
+    /// Return the executor publishing the changes of the transactions it executes, and of the balances of their entities,
+    /// on the given event bus. A ledger built with the executor takes over its event bus and publishes the changes
+    /// of its transfers itself, so that they are published only once, see 'Ledger::new'.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_bus` - An EventBus that represents the bus the changes are published on.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::event::EventBus;
+    /// use banana_coin::transfer::TransferExecutor;
+    /// let event_bus = EventBus::new();
+    /// let receiver = event_bus.subscribe_channel();
+    /// let executor = TransferExecutor::new().with_event_bus(event_bus);
+    /// # assert!(executor.get_event_bus().is_some())
+    /// ```
+    ///
+    pub fn with_event_bus(
+        mut self: TransferExecutor,
+        event_bus: EventBus,
+    ) -> TransferExecutor {
+        self.event_bus = Some(event_bus);
+        self
+    }
+
+    ///
+    /// Return the event bus the changes are published on, if any. The returned bus shares its subscribers
+    /// with the bus of the executor.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::transfer::TransferExecutor;
+    /// let executor = TransferExecutor::new();
+    /// # assert!(executor.get_event_bus().is_none())
+    /// ```
+    ///
+    pub fn get_event_bus(
+        self: &TransferExecutor,
+    ) -> Option<EventBus> {
+        self.event_bus.clone()
+    }
+
+    /// Remove the event bus of the executor and return it, so that its owner publishes the changes in its place.
+    pub(crate) fn take_event_bus(
+        self: &mut TransferExecutor,
+    ) -> Option<EventBus> {
+        self.event_bus.take()
+    }
+
+    ///
+    /// Execute a transaction, moving its amount of coins from the sender entity to the receiver entity.
+    /// The transaction must be in the Created state, or in the OnProcess state if it has already been picked
+    /// for processing, e.g. from a mempool. It is moved to the OnProcess state and then
+    /// to the Completed state if both wallet operations succeed, otherwise to the Failed state.
+    /// Each step is appended to the state history of the transaction.
+    /// If a wallet operation fails, no wallet is modified.
+    /// The transaction must carry the current nonce of the sender, which is incremented once the transaction
+    /// is completed or failed, so that the same transaction can not be executed twice.
+    /// A transaction that can not be executed, e.g. because its signature does not match the public key
+    /// of the sender, is refused and left in the Created state.
+    /// A transaction with a fee is refused, since there is no entity to credit the fee to: see 'execute_with_fee'.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - A Transaction that represents the transfer to execute.
+    /// * `from_entity` - An Entity whose id is the 'from_entity_id' of the transaction.
+    /// * `to_entity` - An Entity whose id is the 'to_entity_id' of the transaction.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the state changes.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Entity, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// use banana_coin::transfer::TransferExecutor;
+    /// let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// let mut transaction = Transaction::new(
+    ///     "id_0001".to_string(),
+    ///     "entity_001".to_string(),
+    ///     "entity_002".to_string(),
+    ///     Amount::from_integer(100),
+    ///     "Moving 100 coins.".to_string(),
+    ///     Created {timestamp: 1},
+    ///     vec![Created {timestamp: 1}]
+    /// ).unwrap();
+    /// let executor = TransferExecutor::new();
+    /// let result = executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 2);
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(0));
+    /// # assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(100));
+    /// # assert_eq!(transaction.get_current_state(), Completed {timestamp: 2});
+    /// ```
+    ///
+    pub fn execute(
+        self: &TransferExecutor,
+        transaction: &mut Transaction,
+        from_entity: &mut Entity,
+        to_entity: &mut Entity,
+        timestamp: u64,
+    ) -> Result<(), TransferError> {
+        self.execute_transfer(transaction, from_entity, to_entity, None, None, timestamp)
+    }
+
+    ///
+    /// Execute a transaction like 'execute', also moving its fee from the sender entity to the fee collector entity.
+    /// The sender is debited of the amount plus the fee. If the fee collector is the sender or the receiver
+    /// of the transaction, the fee is credited to that entity and the given fee collector is left untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - A Transaction that represents the transfer to execute.
+    /// * `from_entity` - An Entity whose id is the 'from_entity_id' of the transaction.
+    /// * `to_entity` - An Entity whose id is the 'to_entity_id' of the transaction.
+    /// * `fee_collector` - An Entity that represents the entity collecting the fee, e.g. the operator of the ledger.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the state changes.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Entity, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// use banana_coin::transfer::TransferExecutor;
+    /// let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// let mut fee_collector = Entity::new("entity_003".to_string(), "bank".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// let mut transaction = Transaction::new(
+    ///     "id_0001".to_string(),
+    ///     "entity_001".to_string(),
+    ///     "entity_002".to_string(),
+    ///     Amount::from_integer(90),
+    ///     "Moving 90 coins.".to_string(),
+    ///     Created {timestamp: 1},
+    ///     vec![Created {timestamp: 1}]
+    /// ).unwrap();
+    /// transaction.set_fee(Amount::from_integer(10)).unwrap();
+    /// let executor = TransferExecutor::new();
+    /// let result = executor.execute_with_fee(&mut transaction, &mut from_entity, &mut to_entity, &mut fee_collector, 2);
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(0));
+    /// # assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(90));
+    /// # assert_eq!(fee_collector.get_wallet().get_balance(), Amount::from_integer(10));
+    /// ```
+    ///
+    pub fn execute_with_fee(
+        self: &TransferExecutor,
+        transaction: &mut Transaction,
+        from_entity: &mut Entity,
+        to_entity: &mut Entity,
+        fee_collector: &mut Entity,
+        timestamp: u64,
+    ) -> Result<(), TransferError> {
+        self.execute_transfer(transaction, from_entity, to_entity, Some(fee_collector), None, timestamp)
+    }
+
+    ///
+    /// Execute a transaction like 'execute', capturing a hold of the sender entity instead of removing available coins.
+    /// The amount of the transaction is removed from the held coins and the rest of the hold is released.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - A Transaction that represents the transfer to execute.
+    /// * `from_entity` - An Entity whose id is the 'from_entity_id' of the transaction.
+    /// * `to_entity` - An Entity whose id is the 'to_entity_id' of the transaction.
+    /// * `hold_id` - A str that represents the id of the hold of the sender entity to capture.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the state changes.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Entity, Transaction, Wallet, DEFAULT_ASSET};
+    /// # use banana_coin::model::TransactionState::*;
+    /// use banana_coin::transfer::TransferExecutor;
+    /// let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// from_entity.place_asset_hold("hold_0001".to_string(), DEFAULT_ASSET, Amount::from_integer(100), 1, 3600).unwrap();
+    /// let mut transaction = Transaction::new(
+    ///     "id_0001".to_string(),
+    ///     "entity_001".to_string(),
+    ///     "entity_002".to_string(),
+    ///     Amount::from_integer(80),
+    ///     "Moving 80 coins.".to_string(),
+    ///     Created {timestamp: 2},
+    ///     vec![Created {timestamp: 2}]
+    /// ).unwrap();
+    /// let executor = TransferExecutor::new();
+    /// let result = executor.execute_hold(&mut transaction, &mut from_entity, &mut to_entity, "hold_0001", 3);
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(from_entity.get_wallet().get_available_balance(2).unwrap(), Amount::from_integer(20));
+    /// # assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(80));
+    /// ```
+    ///
+    pub fn execute_hold(
+        self: &TransferExecutor,
+        transaction: &mut Transaction,
+        from_entity: &mut Entity,
+        to_entity: &mut Entity,
+        hold_id: &str,
+        timestamp: u64,
+    ) -> Result<(), TransferError> {
+        self.execute_transfer(transaction, from_entity, to_entity, None, Some(hold_id), timestamp)
+    }
+
+    ///
+    /// Execute a transaction as an escrow: its amount of coins is moved from the sender entity to the escrow account
+    /// and held there until the returned Escrow is released to the receiver or refunded to the sender.
+    /// The transaction is checked like in 'execute', moved to the OnProcess state and then to the Blocked state
+    /// if the coins can be removed from the sender, otherwise to the Failed state.
+    /// The nonce of the sender is consumed either way.
+    /// A transaction with a fee is refused, since there is no entity to credit the fee to.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - A Transaction that represents the transfer to execute.
+    /// * `from_entity` - An Entity whose id is the 'from_entity_id' of the transaction.
+    /// * `escrow_account` - An Entity whose id is ESCROW_ACCOUNT_ID that represents the account holding the coins.
+    /// * `condition` - A ReleaseCondition that represents what must happen for the coins to be released.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the state changes.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::ESCROW_ACCOUNT_ID;
+    /// # use banana_coin::model::{Amount, Entity, Escrow, ReleaseCondition, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// use banana_coin::transfer::TransferExecutor;
+    /// let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// let mut escrow_account = Entity::new(ESCROW_ACCOUNT_ID.to_string(), "escrow".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// let mut transaction = Transaction::new(
+    ///     "id_0001".to_string(),
+    ///     "entity_001".to_string(),
+    ///     "entity_002".to_string(),
+    ///     Amount::from_integer(100),
+    ///     "Moving 100 coins.".to_string(),
+    ///     Created {timestamp: 1},
+    ///     vec![Created {timestamp: 1}]
+    /// ).unwrap();
+    /// let executor = TransferExecutor::new();
+    /// let condition = ReleaseCondition::Approval { approver_id: "entity_003".to_string() };
+    /// let result = executor.execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, condition, 2);
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(0));
+    /// # assert_eq!(escrow_account.get_wallet().get_balance(), Amount::from_integer(100));
+    /// # assert_eq!(transaction.get_current_state(), Blocked {timestamp: 2});
+    /// ```
+    ///
+    pub fn execute_escrow(
+        self: &TransferExecutor,
+        transaction: &mut Transaction,
+        from_entity: &mut Entity,
+        escrow_account: &mut Entity,
+        condition: ReleaseCondition,
+        timestamp: u64,
+    ) -> Result<Escrow, TransferError> {
+        let seen_states = transaction.get_state_history().len();
+        let from_wallet = from_entity.get_wallet();
+        let escrow_wallet = escrow_account.get_wallet();
+        let result = self.run_escrow(transaction, from_entity, escrow_account, condition, timestamp);
+        self.publish_changes(transaction, seen_states, &[(&from_wallet, from_entity), (&escrow_wallet, escrow_account)], timestamp);
+        result
+    }
+
+    ///
+    /// Release the coins of an escrow from the escrow account to the receiver entity of its transaction,
+    /// moving the transaction to the Completed state.
+    /// If the release condition is not met or the coins can not be moved to the receiver, nothing is modified.
+    ///
+    /// # Arguments
+    ///
+    /// * `escrow` - An Escrow that represents the escrow to release.
+    /// * `escrow_account` - An Entity whose id is ESCROW_ACCOUNT_ID that represents the account holding the coins.
+    /// * `to_entity` - An Entity whose id is the 'to_entity_id' of the transaction of the escrow.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the state change.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::crypto::KeyPair;
+    /// # use banana_coin::ledger::ESCROW_ACCOUNT_ID;
+    /// # use banana_coin::model::{Amount, Entity, Escrow, ReleaseCondition, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// use banana_coin::transfer::TransferExecutor;
+    /// # let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// let mut escrow_account = Entity::new(ESCROW_ACCOUNT_ID.to_string(), "escrow".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// # let mut transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// let executor = TransferExecutor::new();
+    /// let condition = ReleaseCondition::Approval { approver_id: "entity_003".to_string() };
+    /// let mut escrow = executor.execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, condition, 2).unwrap();
+    /// # let key_pair = KeyPair::generate();
+    /// # let mut arbiter = Entity::new("entity_003".to_string(), "arbiter".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// # arbiter.set_public_key(key_pair.get_public_key());
+    /// escrow.approve(&arbiter, &escrow.sign_approval("entity_003", &key_pair), 3).unwrap();
+    /// let result = executor.release_escrow(&mut escrow, &mut escrow_account, &mut to_entity, 4);
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(100));
+    /// # assert!(escrow_account.get_wallet().get_balance().is_zero());
+    /// # assert_eq!(escrow.get_transaction().get_current_state(), Completed {timestamp: 4});
+    /// ```
+    ///
+    pub fn release_escrow(
+        self: &TransferExecutor,
+        escrow: &mut Escrow,
+        escrow_account: &mut Entity,
+        to_entity: &mut Entity,
+        timestamp: u64,
+    ) -> Result<(), TransferError> {
+        let mut transaction = escrow.get_transaction();
+        escrow
+            .check_not_settled()
+            .map_err(|error| TransferError::EscrowOperationError {
+                transaction_id: transaction.get_id(),
+                error,
+            })?;
+        if !escrow.is_releasable(timestamp) {
+            return Err(TransferError::EscrowOperationError {
+                transaction_id: transaction.get_id(),
+                error: EscrowError::ReleaseConditionNotMetError {
+                    transaction_id: transaction.get_id(),
+                    condition: escrow.get_condition(),
+                },
+            });
+        }
+        self.settle(escrow, &mut transaction, escrow_account, to_entity, Completed { timestamp })
+    }
+
+    ///
+    /// Refund the coins of an escrow from the escrow account to the sender entity of its transaction,
+    /// moving the transaction to the Failed state.
+    /// Only the receiver of the transaction and the approver of an Approval condition can request the refund.
+    /// If the refund can not be made, nothing is modified.
+    ///
+    /// # Arguments
+    ///
+    /// * `escrow` - An Escrow that represents the escrow to refund.
+    /// * `escrow_account` - An Entity whose id is ESCROW_ACCOUNT_ID that represents the account holding the coins.
+    /// * `from_entity` - An Entity whose id is the 'from_entity_id' of the transaction of the escrow.
+    /// * `requested_by` - A str that represents the id of the entity requesting the refund.
+    /// * `timestamp` - A u64 integer that represents the timestamp of the state change.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::ledger::ESCROW_ACCOUNT_ID;
+    /// # use banana_coin::model::{Amount, Entity, Escrow, ReleaseCondition, Transaction, Wallet};
+    /// # use banana_coin::model::TransactionState::*;
+    /// use banana_coin::transfer::TransferExecutor;
+    /// let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    /// let mut escrow_account = Entity::new(ESCROW_ACCOUNT_ID.to_string(), "escrow".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// # let mut transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Created {timestamp: 1}, vec![Created {timestamp: 1}]).unwrap();
+    /// let executor = TransferExecutor::new();
+    /// let mut escrow = executor.execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, ReleaseCondition::BothParties, 2).unwrap();
+    /// let result = executor.refund_escrow(&mut escrow, &mut escrow_account, &mut from_entity, "entity_002", 3);
+    /// # assert!(result.is_ok());
+    /// # assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(100));
+    /// # assert_eq!(escrow.get_transaction().get_current_state(), Failed {timestamp: 3});
+    /// ```
+    ///
+    pub fn refund_escrow(
+        self: &TransferExecutor,
+        escrow: &mut Escrow,
+        escrow_account: &mut Entity,
+        from_entity: &mut Entity,
+        requested_by: &str,
+        timestamp: u64,
+    ) -> Result<(), TransferError> {
+        let mut transaction = escrow.get_transaction();
+        escrow
+            .check_not_settled()
+            .map_err(|error| TransferError::EscrowOperationError {
+                transaction_id: transaction.get_id(),
+                error,
+            })?;
+        if !escrow.can_refund(requested_by) {
+            return Err(TransferError::EscrowOperationError {
+                transaction_id: transaction.get_id(),
+                error: EscrowError::UnauthorizedRefundError {
+                    transaction_id: transaction.get_id(),
+                    entity_id: requested_by.to_string(),
+                },
+            });
+        }
+        self.settle(escrow, &mut transaction, escrow_account, from_entity, Failed { timestamp })
+    }
+
+    /// Execute a transaction, crediting its fee, if any, to the given fee collector
+    /// and removing the coins from the given hold of the sender, if any, then publish its changes.
+    fn execute_transfer(
+        self: &TransferExecutor,
+        transaction: &mut Transaction,
+        from_entity: &mut Entity,
+        to_entity: &mut Entity,
+        fee_collector: Option<&mut Entity>,
+        hold_id: Option<&str>,
+        timestamp: u64,
+    ) -> Result<(), TransferError> {
+        let seen_states = transaction.get_state_history().len();
+        let from_wallet = from_entity.get_wallet();
+        let to_wallet = to_entity.get_wallet();
+        let mut fee_collector = fee_collector.map(|fee_collector| (fee_collector.get_wallet(), fee_collector));
+        let result = self.run_transfer(
+            transaction,
+            from_entity,
+            to_entity,
+            fee_collector.as_mut().map(|(_, fee_collector)| &mut **fee_collector),
+            hold_id,
+            timestamp,
+        );
+        let mut changes: Vec<(&Wallet, &Entity)> = vec![(&from_wallet, from_entity), (&to_wallet, to_entity)];
+        // A fee collector that is the sender or the receiver is left untouched, so it has no change of its own
+        changes.extend(fee_collector.as_ref().map(|(fee_collector_wallet, fee_collector)| (fee_collector_wallet, &**fee_collector)));
+        self.publish_changes(transaction, seen_states, &changes, timestamp);
+        result
+    }
+
+    /// Execute a transaction like 'execute_transfer', without publishing its changes.
+    fn run_transfer(
+        self: &TransferExecutor,
+        transaction: &mut Transaction,
+        from_entity: &mut Entity,
+        to_entity: &mut Entity,
+        fee_collector: Option<&mut Entity>,
+        hold_id: Option<&str>,
+        timestamp: u64,
+    ) -> Result<(), TransferError> {
+        let picked = self.check_transfer(transaction, from_entity, Some(to_entity))?;
+
+        let total_amount = transaction
+            .get_total_amount()
+            .map_err(|error| TransferError::TransactionOperationError {
+                transaction_id: transaction.get_id(),
+                error,
+            })?;
+        let fee = transaction.get_fee().unwrap_or_default();
+        if !fee.is_zero() && fee_collector.is_none() {
+            return Err(TransferError::MissingFeeCollectorError { transaction_id: transaction.get_id() });
+        }
+
+        // The nonce is consumed by the execution, whether the transaction completes or fails,
+        // and the expired holds of the sender are released before its coins are removed
+        let mut nonce_consumed_from_entity = from_entity.clone();
+        nonce_consumed_from_entity.expire_holds(timestamp);
+        nonce_consumed_from_entity
+            .increment_nonce()
+            .map_err(|error| TransferError::EntityOperationError {
+                entity_id: transaction.get_from_entity_id(),
+                error,
+            })?;
+
+        if !picked {
+            Self::transition(transaction, OnProcess { timestamp })?;
+        }
+
+        // Working on copies, so that the entities are left untouched if any operation fails
+        let mut new_from_entity = nonce_consumed_from_entity.clone();
+        let mut new_to_entity = to_entity.clone();
+        let mut new_fee_collector = fee_collector.as_ref().map(|fee_collector| (*fee_collector).clone());
+        let asset = transaction.get_asset();
+        let debit = match hold_id {
+            Some(hold_id) => new_from_entity.capture_hold(hold_id, &asset, total_amount, timestamp).map(|_| ()),
+            None => new_from_entity.remove_asset_coins(&asset, total_amount, timestamp),
+        };
+        let result = debit
+            .map_err(|error| TransferError::EntityOperationError {
+                entity_id: transaction.get_from_entity_id(),
+                error,
+            })
+            .and_then(|_| new_to_entity
+                .add_asset_coins(&asset, transaction.get_amount())
+                .map_err(|error| TransferError::EntityOperationError {
+                    entity_id: transaction.get_to_entity_id(),
+                    error,
+                }))
+            .and_then(|_| {
+                if fee.is_zero() {
+                    return Ok(());
+                }
+                let collecting_entity = match new_fee_collector.as_mut() {
+                    Some(collector) if collector.get_id() == new_from_entity.get_id() => &mut new_from_entity,
+                    Some(collector) if collector.get_id() == new_to_entity.get_id() => &mut new_to_entity,
+                    Some(collector) => collector,
+                    None => return Ok(()),
+                };
+                collecting_entity
+                    .add_asset_coins(&asset, fee)
+                    .map_err(|error| TransferError::EntityOperationError {
+                        entity_id: collecting_entity.get_id().to_string(),
+                        error,
+                    })
+            });
+
+        match result {
+            Ok(_) => {
+                Self::transition(transaction, Completed { timestamp })?;
+                if let (Some(fee_collector), Some(new_fee_collector)) = (fee_collector, new_fee_collector) {
+                    if new_fee_collector.get_id() != new_from_entity.get_id()
+                        && new_fee_collector.get_id() != new_to_entity.get_id() {
+                        *fee_collector = new_fee_collector;
+                    }
+                }
+                *from_entity = new_from_entity;
+                *to_entity = new_to_entity;
+                Ok(())
+            }
+            Err(error) => {
+                Self::transition(transaction, Failed { timestamp })?;
+                *from_entity = nonce_consumed_from_entity;
+                Err(error)
+            }
+        }
+    }
+
+    /// Execute a transaction as an escrow, see 'execute_escrow', without publishing its changes.
+    fn run_escrow(
+        self: &TransferExecutor,
+        transaction: &mut Transaction,
+        from_entity: &mut Entity,
+        escrow_account: &mut Entity,
+        condition: ReleaseCondition,
+        timestamp: u64,
+    ) -> Result<Escrow, TransferError> {
+        Self::check_escrow_account(escrow_account)?;
+        let picked = self.check_transfer(transaction, from_entity, None)?;
+        let fee = transaction.get_fee().unwrap_or_default();
+        if !fee.is_zero() {
+            return Err(TransferError::MissingFeeCollectorError { transaction_id: transaction.get_id() });
+        }
+
+        // The nonce is consumed by the execution, whether the coins reach the escrow or not
+        let mut nonce_consumed_from_entity = from_entity.clone();
+        nonce_consumed_from_entity.expire_holds(timestamp);
+        nonce_consumed_from_entity
+            .increment_nonce()
+            .map_err(|error| TransferError::EntityOperationError {
+                entity_id: transaction.get_from_entity_id(),
+                error,
+            })?;
+
+        if !picked {
+            Self::transition(transaction, OnProcess { timestamp })?;
+        }
+
+        let mut new_from_entity = nonce_consumed_from_entity.clone();
+        let mut new_escrow_account = escrow_account.clone();
+        let result = Self::move_coins(transaction, &mut new_from_entity, &mut new_escrow_account, timestamp);
+
+        match result {
+            Ok(_) => {
+                Self::transition(transaction, Blocked { timestamp })?;
+                *from_entity = new_from_entity;
+                *escrow_account = new_escrow_account;
+                Ok(Escrow::new(transaction.clone(), condition))
+            }
+            Err(error) => {
+                Self::transition(transaction, Failed { timestamp })?;
+                *from_entity = nonce_consumed_from_entity;
+                Err(error)
+            }
+        }
+    }
+
+    /// Check that a transaction can be executed against the given entities: the entity ids, the state,
+    /// the nonce and the signature of the transaction are checked.
+    /// Return whether the transaction has already been picked for processing, i.e. it is in the OnProcess state.
+    fn check_transfer(
+        self: &TransferExecutor,
+        transaction: &Transaction,
+        from_entity: &Entity,
+        to_entity: Option<&Entity>,
+    ) -> Result<bool, TransferError> {
+        // Checking that the transaction can be executed against the given entities
+        if transaction.get_from_entity_id() != from_entity.get_id() {
+            return Err(TransferError::EntityMismatchError {
+                expected_entity_id: transaction.get_from_entity_id(),
+                actual_entity_id: from_entity.get_id().to_string(),
+            });
+        }
+        if let Some(to_entity) = to_entity {
+            if transaction.get_to_entity_id() != to_entity.get_id() {
+                return Err(TransferError::EntityMismatchError {
+                    expected_entity_id: transaction.get_to_entity_id(),
+                    actual_entity_id: to_entity.get_id().to_string(),
+                });
+            }
+        }
+        if transaction.get_from_entity_id() == transaction.get_to_entity_id() {
+            return Err(TransferError::SelfTransferError {
+                entity_id: transaction.get_from_entity_id(),
+            });
+        }
+        let picked = match transaction.get_current_state() {
+            Created { .. } => false,
+            OnProcess { .. } => true,
+            current_state => return Err(TransferError::InvalidTransactionStateError { current_state }),
+        };
+        let expected_nonce = from_entity.get_nonce();
+        if transaction.get_nonce() < expected_nonce {
+            return Err(TransferError::ReplayedNonceError {
+                entity_id: from_entity.get_id().to_string(),
+                expected_nonce,
+                found_nonce: transaction.get_nonce(),
+            });
+        }
+        if transaction.get_nonce() > expected_nonce {
+            return Err(TransferError::OutOfOrderNonceError {
+                entity_id: from_entity.get_id().to_string(),
+                expected_nonce,
+                found_nonce: transaction.get_nonce(),
+            });
+        }
+        match from_entity.get_public_key() {
+            Some(public_key) => transaction
+                .verify_signature(public_key)
+                .map_err(|error| TransferError::SignatureVerificationError {
+                    transaction_id: transaction.get_id(),
+                    error,
+                })?,
+            None if self.require_signature => return Err(TransferError::MissingPublicKeyError {
+                entity_id: from_entity.get_id().to_string(),
+            }),
+            None => {}
+        }
+        Ok(picked)
+    }
+
+    /// Move the transaction of an escrow to its final state and move the coins of the escrow from the escrow account
+    /// to the given entity, which is the receiver of the transaction for a release and the sender for a refund.
+    fn settle(
+        self: &TransferExecutor,
+        escrow: &mut Escrow,
+        transaction: &mut Transaction,
+        escrow_account: &mut Entity,
+        entity: &mut Entity,
+        state: TransactionState,
+    ) -> Result<(), TransferError> {
+        Self::check_escrow_account(escrow_account)?;
+        let expected_entity_id = match state {
+            Completed { .. } => transaction.get_to_entity_id(),
+            _ => transaction.get_from_entity_id(),
+        };
+        if expected_entity_id != entity.get_id() {
+            return Err(TransferError::EntityMismatchError {
+                expected_entity_id,
+                actual_entity_id: entity.get_id().to_string(),
+            });
+        }
+        let mut new_escrow_account = escrow_account.clone();
+        let mut new_entity = entity.clone();
+        Self::move_coins(transaction, &mut new_escrow_account, &mut new_entity, state.get_timestamp())?;
+        let seen_states = transaction.get_state_history().len();
+        Self::transition(transaction, state)?;
+        escrow.settle(transaction.clone());
+        let escrow_wallet = escrow_account.get_wallet();
+        let wallet = entity.get_wallet();
+        *escrow_account = new_escrow_account;
+        *entity = new_entity;
+        self.publish_changes(transaction, seen_states, &[(&escrow_wallet, escrow_account), (&wallet, entity)],
+                             transaction.get_current_state().get_timestamp());
+        Ok(())
+    }
+
+    /// Return an EntityMismatchError if the given entity is not the escrow account.
+    fn check_escrow_account(
+        escrow_account: &Entity,
+    ) -> Result<(), TransferError> {
+        if escrow_account.get_id() != ESCROW_ACCOUNT_ID {
+            return Err(TransferError::EntityMismatchError {
+                expected_entity_id: ESCROW_ACCOUNT_ID.to_string(),
+                actual_entity_id: escrow_account.get_id().to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Move the amount of coins of a transaction, in its asset, from an entity to another one at the given timestamp.
+    fn move_coins(
+        transaction: &Transaction,
+        from_entity: &mut Entity,
+        to_entity: &mut Entity,
+        timestamp: u64,
+    ) -> Result<(), TransferError> {
+        from_entity
+            .remove_asset_coins(&transaction.get_asset(), transaction.get_amount(), timestamp)
+            .map_err(|error| TransferError::EntityOperationError {
+                entity_id: from_entity.get_id().to_string(),
+                error,
+            })?;
+        to_entity
+            .add_asset_coins(&transaction.get_asset(), transaction.get_amount())
+            .map_err(|error| TransferError::EntityOperationError {
+                entity_id: to_entity.get_id().to_string(),
+                error,
+            })
+    }
+
+    /// Publish the changes of an operation on a transaction on the event bus of the executor, if any: the states reached
+    /// since the given position of its history, with the changes of the balances of the given wallets before the last one.
+    fn publish_changes(
+        self: &TransferExecutor,
+        transaction: &Transaction,
+        seen_states: usize,
+        changes: &[(&Wallet, &Entity)],
+        timestamp: u64,
+    ) {
+        if let Some(event_bus) = &self.event_bus {
+            let events = changes
+                .iter()
+                .flat_map(|(before, entity)| {
+                    balance_changes(entity.get_id(), before, &entity.get_wallet(), Some(transaction.get_id()), timestamp)
+                })
+                .collect();
+            event_bus.publish_all(transaction_changes(transaction, seen_states, events));
+        }
+    }
+
+    /// Move the transaction to a new state, wrapping the error into a TransferError.
+    fn transition(
+        transaction: &mut Transaction,
+        state: TransactionState,
+    ) -> Result<(), TransferError> {
+        transaction
+            .transition_to(state)
+            .map_err(|error| TransferError::TransactionOperationError {
+                transaction_id: transaction.get_id(),
+                error,
+            })
+    }
+}
@@ -0,0 +1,18 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+///
+/// Return the current timestamp, in seconds since the epoch, or 0 if the system clock is set before the epoch.
+///
+/// # Example
+/// ```
+/// use banana_coin::util::now;
+/// let timestamp : u64 = now();
+/// # assert!(timestamp > 0)
+/// ```
+///
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,3 @@
+/// Importing clock module
+pub mod clock;
+pub use clock::*;
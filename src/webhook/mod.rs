@@ -0,0 +1,11 @@
+/// Importing webhook endpoint module
+pub mod webhook_endpoint;
+pub use webhook_endpoint::*;
+
+/// Importing webhook delivery module
+pub mod webhook_delivery;
+pub use webhook_delivery::*;
+
+/// Importing webhook dispatcher module
+pub mod webhook_dispatcher;
+pub use webhook_dispatcher::*;
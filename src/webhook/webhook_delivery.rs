@@ -0,0 +1,524 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::WebhookError;
+
+/// Retry policy structure
+/// It represents how many times a webhook delivery is attempted and how long to wait between the attempts:
+/// the wait doubles at every failed attempt, from the initial backoff up to the max backoff.
+/// A delivery failing all its attempts is dead-lettered.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: u64,
+    max_backoff: u64,
+}
+
+/// Default retry policy: 6 attempts over about half an hour.
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy::new(6, 60, 3600)
+    }
+}
+
+/// Retry policy implementation.
+impl RetryPolicy {
+    ///
+    /// Return a new RetryPolicy.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_attempts` - A u32 integer that represents the number of attempts of a delivery, at least 1.
+    /// * `initial_backoff` - A u64 integer that represents the wait after the first failed attempt, in seconds.
+    /// * `max_backoff` - A u64 integer that represents the longest wait between two attempts, in seconds.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::webhook::RetryPolicy;
+    /// let retry_policy = RetryPolicy::new(
+    ///     5,
+    ///     10,
+    ///     60
+    /// );
+    /// # assert_eq!(retry_policy.get_max_attempts(), 5)
+    /// ```
+    ///
+    pub fn new(
+        max_attempts: u32,
+        initial_backoff: u64,
+        max_backoff: u64,
+    ) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            initial_backoff,
+            max_backoff,
+        }
+    }
+
+    ///
+    /// Return the number of attempts of a delivery.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::webhook::RetryPolicy;
+    /// # let retry_policy = RetryPolicy::new(5, 10, 60);
+    /// let max_attempts : u32 = retry_policy.get_max_attempts();
+    /// # assert_eq!(max_attempts, 5)
+    /// ```
+    ///
+    pub fn get_max_attempts(
+        self: &RetryPolicy,
+    ) -> u32 {
+        self.max_attempts
+    }
+
+    ///
+    /// Return the wait after the given failed attempt, in seconds.
+    ///
+    /// # Arguments
+    ///
+    /// * `attempt` - A u32 integer that represents the number of the failed attempt, starting from 1.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::webhook::RetryPolicy;
+    /// # let retry_policy = RetryPolicy::new(5, 10, 60);
+    /// let backoff : u64 = retry_policy.get_backoff(3);
+    /// # assert_eq!(backoff, 40);
+    /// # assert_eq!(retry_policy.get_backoff(1), 10);
+    /// # assert_eq!(retry_policy.get_backoff(4), 60)
+    /// ```
+    ///
+    pub fn get_backoff(
+        self: &RetryPolicy,
+        attempt: u32,
+    ) -> u64 {
+        let factor = 1u64.checked_shl(attempt.saturating_sub(1)).unwrap_or(u64::MAX);
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+/// Webhook delivery structure
+/// It represents the notification of a transaction to the endpoint of an entity: the payload, which is the JSON
+/// representation of the transaction, and the attempts made so far to deliver it.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookDelivery {
+    id: String,
+    entity_id: String,
+    transaction_id: String,
+    payload: String,
+    attempts: u32,
+    next_attempt_at: u64,
+}
+
+/// Webhook delivery implementation.
+impl WebhookDelivery {
+    /// Return a new WebhookDelivery, to be attempted at the given timestamp.
+    pub(crate) fn new(
+        id: String,
+        entity_id: String,
+        transaction_id: String,
+        payload: String,
+        timestamp: u64,
+    ) -> WebhookDelivery {
+        WebhookDelivery {
+            id,
+            entity_id,
+            transaction_id,
+            payload,
+            attempts: 0,
+            next_attempt_at: timestamp,
+        }
+    }
+
+    ///
+    /// Return the id of the delivery.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Transaction};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::webhook::*;
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// # dispatcher.register_endpoint("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// # let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Failed {timestamp: 2}, vec![Created {timestamp: 1}, OnProcess {timestamp: 2}, Failed {timestamp: 2}]).unwrap();
+    /// # dispatcher.enqueue(&transaction);
+    /// # let delivery = dispatcher.get_pending_deliveries().remove(0);
+    /// let id : &str = delivery.get_id();
+    /// # assert_eq!(id, "dlv_000001")
+    /// ```
+    ///
+    pub fn get_id(
+        self: &WebhookDelivery,
+    ) -> &str {
+        &self.id
+    }
+
+    ///
+    /// Return the id of the notified entity.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Transaction};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::webhook::*;
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// # dispatcher.register_endpoint("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// # let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Failed {timestamp: 2}, vec![Created {timestamp: 1}, OnProcess {timestamp: 2}, Failed {timestamp: 2}]).unwrap();
+    /// # dispatcher.enqueue(&transaction);
+    /// # let delivery = dispatcher.get_pending_deliveries().remove(0);
+    /// let entity_id : &str = delivery.get_entity_id();
+    /// # assert_eq!(entity_id, "entity_001")
+    /// ```
+    ///
+    pub fn get_entity_id(
+        self: &WebhookDelivery,
+    ) -> &str {
+        &self.entity_id
+    }
+
+    ///
+    /// Return the id of the notified transaction.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Transaction};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::webhook::*;
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// # dispatcher.register_endpoint("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// # let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Failed {timestamp: 2}, vec![Created {timestamp: 1}, OnProcess {timestamp: 2}, Failed {timestamp: 2}]).unwrap();
+    /// # dispatcher.enqueue(&transaction);
+    /// # let delivery = dispatcher.get_pending_deliveries().remove(0);
+    /// let transaction_id : &str = delivery.get_transaction_id();
+    /// # assert_eq!(transaction_id, "id_0001")
+    /// ```
+    ///
+    pub fn get_transaction_id(
+        self: &WebhookDelivery,
+    ) -> &str {
+        &self.transaction_id
+    }
+
+    ///
+    /// Return the payload of the delivery: the JSON representation of the transaction.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Transaction};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::webhook::*;
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// # dispatcher.register_endpoint("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// # let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Failed {timestamp: 2}, vec![Created {timestamp: 1}, OnProcess {timestamp: 2}, Failed {timestamp: 2}]).unwrap();
+    /// # dispatcher.enqueue(&transaction);
+    /// # let delivery = dispatcher.get_pending_deliveries().remove(0);
+    /// let payload : &str = delivery.get_payload();
+    /// # assert!(payload.contains("\"id\":\"id_0001\""))
+    /// ```
+    ///
+    pub fn get_payload(
+        self: &WebhookDelivery,
+    ) -> &str {
+        &self.payload
+    }
+
+    ///
+    /// Return the number of attempts made so far.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Transaction};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::webhook::*;
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// # dispatcher.register_endpoint("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// # let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Failed {timestamp: 2}, vec![Created {timestamp: 1}, OnProcess {timestamp: 2}, Failed {timestamp: 2}]).unwrap();
+    /// # dispatcher.enqueue(&transaction);
+    /// # let delivery = dispatcher.get_pending_deliveries().remove(0);
+    /// let attempts : u32 = delivery.get_attempts();
+    /// # assert_eq!(attempts, 0)
+    /// ```
+    ///
+    pub fn get_attempts(
+        self: &WebhookDelivery,
+    ) -> u32 {
+        self.attempts
+    }
+
+    ///
+    /// Return the timestamp of the next attempt.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Transaction};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::webhook::*;
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// # dispatcher.register_endpoint("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// # let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Failed {timestamp: 2}, vec![Created {timestamp: 1}, OnProcess {timestamp: 2}, Failed {timestamp: 2}]).unwrap();
+    /// # dispatcher.enqueue(&transaction);
+    /// # let delivery = dispatcher.get_pending_deliveries().remove(0);
+    /// let next_attempt_at : u64 = delivery.get_next_attempt_at();
+    /// # assert_eq!(next_attempt_at, 2)
+    /// ```
+    ///
+    pub fn get_next_attempt_at(
+        self: &WebhookDelivery,
+    ) -> u64 {
+        self.next_attempt_at
+    }
+
+    /// Record a failed attempt and return whether another attempt is allowed by the retry policy.
+    pub(crate) fn fail(
+        self: &mut WebhookDelivery,
+        retry_policy: &RetryPolicy,
+        timestamp: u64,
+    ) -> bool {
+        self.attempts += 1;
+        self.next_attempt_at = timestamp.saturating_add(retry_policy.get_backoff(self.attempts));
+        self.attempts < retry_policy.get_max_attempts()
+    }
+
+    /// Make the delivery due again at the given timestamp, with all the attempts of the retry policy.
+    pub(crate) fn reset(
+        self: &mut WebhookDelivery,
+        timestamp: u64,
+    ) {
+        self.attempts = 0;
+        self.next_attempt_at = timestamp;
+    }
+}
+
+/// Delivery attempt structure
+/// It represents an entry of the delivery log: an attempt to deliver a webhook, with the status answered
+/// by the endpoint or the error met.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub struct DeliveryAttempt {
+    delivery_id: String,
+    entity_id: String,
+    url: Option<String>,
+    attempt: u32,
+    timestamp: u64,
+    status: Option<u16>,
+    error: Option<WebhookError>,
+}
+
+/// Delivery attempt implementation.
+impl DeliveryAttempt {
+    /// Return a new DeliveryAttempt of the given delivery, from the result of its request.
+    pub(crate) fn new(
+        delivery: &WebhookDelivery,
+        url: Option<String>,
+        timestamp: u64,
+        result: &Result<u16, WebhookError>,
+    ) -> DeliveryAttempt {
+        let status = match result {
+            Ok(status) | Err(WebhookError::UnexpectedStatusError { status }) => Some(*status),
+            Err(_) => None,
+        };
+        DeliveryAttempt {
+            delivery_id: delivery.id.clone(),
+            entity_id: delivery.entity_id.clone(),
+            url,
+            attempt: delivery.attempts + 1,
+            timestamp,
+            status,
+            error: result.as_ref().err().cloned(),
+        }
+    }
+
+    ///
+    /// Return the id of the attempted delivery.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Transaction};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::webhook::*;
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// # dispatcher.register_endpoint("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// # let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Failed {timestamp: 2}, vec![Created {timestamp: 1}, OnProcess {timestamp: 2}, Failed {timestamp: 2}]).unwrap();
+    /// # dispatcher.enqueue(&transaction);
+    /// # dispatcher.unregister_endpoint("entity_001").unwrap();
+    /// # let attempt = dispatcher.process(2).remove(0);
+    /// let delivery_id : &str = attempt.get_delivery_id();
+    /// # assert_eq!(delivery_id, "dlv_000001")
+    /// ```
+    ///
+    pub fn get_delivery_id(
+        self: &DeliveryAttempt,
+    ) -> &str {
+        &self.delivery_id
+    }
+
+    ///
+    /// Return the id of the notified entity.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Transaction};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::webhook::*;
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// # dispatcher.register_endpoint("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// # let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Failed {timestamp: 2}, vec![Created {timestamp: 1}, OnProcess {timestamp: 2}, Failed {timestamp: 2}]).unwrap();
+    /// # dispatcher.enqueue(&transaction);
+    /// # dispatcher.unregister_endpoint("entity_001").unwrap();
+    /// # let attempt = dispatcher.process(2).remove(0);
+    /// let entity_id : &str = attempt.get_entity_id();
+    /// # assert_eq!(entity_id, "entity_001")
+    /// ```
+    ///
+    pub fn get_entity_id(
+        self: &DeliveryAttempt,
+    ) -> &str {
+        &self.entity_id
+    }
+
+    ///
+    /// Return the url the delivery was attempted at, if the entity had an endpoint.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Transaction};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::webhook::*;
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// # dispatcher.register_endpoint("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// # let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Failed {timestamp: 2}, vec![Created {timestamp: 1}, OnProcess {timestamp: 2}, Failed {timestamp: 2}]).unwrap();
+    /// # dispatcher.enqueue(&transaction);
+    /// # dispatcher.unregister_endpoint("entity_001").unwrap();
+    /// # let attempt = dispatcher.process(2).remove(0);
+    /// let url : Option<&str> = attempt.get_url();
+    /// # assert!(url.is_none())
+    /// ```
+    ///
+    pub fn get_url(
+        self: &DeliveryAttempt,
+    ) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    ///
+    /// Return the number of the attempt, starting from 1.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Transaction};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::webhook::*;
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// # dispatcher.register_endpoint("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// # let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Failed {timestamp: 2}, vec![Created {timestamp: 1}, OnProcess {timestamp: 2}, Failed {timestamp: 2}]).unwrap();
+    /// # dispatcher.enqueue(&transaction);
+    /// # dispatcher.unregister_endpoint("entity_001").unwrap();
+    /// # let attempt = dispatcher.process(2).remove(0);
+    /// let number : u32 = attempt.get_attempt();
+    /// # assert_eq!(number, 1)
+    /// ```
+    ///
+    pub fn get_attempt(
+        self: &DeliveryAttempt,
+    ) -> u32 {
+        self.attempt
+    }
+
+    ///
+    /// Return the timestamp of the attempt.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Transaction};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::webhook::*;
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// # dispatcher.register_endpoint("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// # let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Failed {timestamp: 2}, vec![Created {timestamp: 1}, OnProcess {timestamp: 2}, Failed {timestamp: 2}]).unwrap();
+    /// # dispatcher.enqueue(&transaction);
+    /// # dispatcher.unregister_endpoint("entity_001").unwrap();
+    /// # let attempt = dispatcher.process(2).remove(0);
+    /// let timestamp : u64 = attempt.get_timestamp();
+    /// # assert_eq!(timestamp, 2)
+    /// ```
+    ///
+    pub fn get_timestamp(
+        self: &DeliveryAttempt,
+    ) -> u64 {
+        self.timestamp
+    }
+
+    ///
+    /// Return the status answered by the endpoint, if it answered.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Transaction};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::webhook::*;
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// # dispatcher.register_endpoint("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// # let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Failed {timestamp: 2}, vec![Created {timestamp: 1}, OnProcess {timestamp: 2}, Failed {timestamp: 2}]).unwrap();
+    /// # dispatcher.enqueue(&transaction);
+    /// # dispatcher.unregister_endpoint("entity_001").unwrap();
+    /// # let attempt = dispatcher.process(2).remove(0);
+    /// let status : Option<u16> = attempt.get_status();
+    /// # assert!(status.is_none())
+    /// ```
+    ///
+    pub fn get_status(
+        self: &DeliveryAttempt,
+    ) -> Option<u16> {
+        self.status
+    }
+
+    ///
+    /// Return the error met by the attempt, if it failed.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Transaction};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::webhook::*;
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// # dispatcher.register_endpoint("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// # let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Failed {timestamp: 2}, vec![Created {timestamp: 1}, OnProcess {timestamp: 2}, Failed {timestamp: 2}]).unwrap();
+    /// # dispatcher.enqueue(&transaction);
+    /// # dispatcher.unregister_endpoint("entity_001").unwrap();
+    /// # let attempt = dispatcher.process(2).remove(0);
+    /// let error : Option<banana_coin::error::WebhookError> = attempt.get_error();
+    /// # assert!(error.is_some())
+    /// ```
+    ///
+    pub fn get_error(
+        self: &DeliveryAttempt,
+    ) -> Option<WebhookError> {
+        self.error.clone()
+    }
+
+    ///
+    /// Return whether the webhook was delivered.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Transaction};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::webhook::*;
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// # dispatcher.register_endpoint("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// # let transaction = Transaction::new("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100), "Moving 100 coins.".to_string(), Failed {timestamp: 2}, vec![Created {timestamp: 1}, OnProcess {timestamp: 2}, Failed {timestamp: 2}]).unwrap();
+    /// # dispatcher.enqueue(&transaction);
+    /// # dispatcher.unregister_endpoint("entity_001").unwrap();
+    /// # let attempt = dispatcher.process(2).remove(0);
+    /// let successful : bool = attempt.is_successful();
+    /// # assert!(!successful)
+    /// ```
+    ///
+    pub fn is_successful(
+        self: &DeliveryAttempt,
+    ) -> bool {
+        self.error.is_none()
+    }
+}
@@ -0,0 +1,720 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::crypto::verify_signature;
+use crate::error::WebhookError;
+use crate::event::{DomainEvent, Subscriber};
+use crate::model::{Entity, Transaction, TransactionState};
+use crate::util::now;
+use crate::webhook::{registration_signing_bytes, DeliveryAttempt, RetryPolicy, WebhookDelivery, WebhookEndpoint, DELIVERY_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+
+/// Time an endpoint has to accept a connection and to answer a delivery.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Number of attempts kept in the delivery log, when the dispatcher is not given another limit.
+pub const DEFAULT_MAX_DELIVERY_LOG_LENGTH: usize = 10_000;
+
+/// Number of dead letters kept for every entity, when the dispatcher is not given another limit.
+pub const DEFAULT_MAX_DEAD_LETTERS: usize = 1_000;
+
+/// Due delivery, with its position among the due deliveries and the endpoint it is sent to.
+type DueDelivery = (usize, WebhookDelivery, Option<WebhookEndpoint>);
+
+/// State shared by the copies of a dispatcher.
+#[derive(Debug, Default)]
+struct DispatcherState {
+    /// Registered endpoints, indexed by the ids of their entities.
+    endpoints: BTreeMap<String, WebhookEndpoint>,
+    /// Deliveries waiting for their next attempt.
+    pending: Vec<WebhookDelivery>,
+    /// Deliveries that failed all their attempts, the most recent ones of every entity.
+    dead_letters: Vec<WebhookDelivery>,
+    /// Most recent attempts made, in order.
+    delivery_log: VecDeque<DeliveryAttempt>,
+    /// Number of deliveries created so far, used to identify them.
+    delivery_count: u64,
+}
+
+/// Webhook dispatcher structure
+/// It notifies the entities with a registered endpoint of their transactions reaching the Completed, Failed or Blocked
+/// state, as a subscriber of an event bus. Every notification is an HTTP POST of the JSON representation of the
+/// transaction, signed with the secret of the endpoint, see 'sign_payload'.
+/// The deliveries are attempted when the dispatcher is processed, see 'process': a failed attempt is retried
+/// following the retry policy, and a delivery failing all its attempts is moved to the dead letters.
+/// Every attempt is recorded in the delivery log. The delivery log and the dead letters of every entity are bounded:
+/// the oldest entries are dropped beyond their limit. The copies of a dispatcher share their endpoints and deliveries.
+#[derive(Debug, Clone)]
+pub struct WebhookDispatcher {
+    state: Arc<Mutex<DispatcherState>>,
+    retry_policy: RetryPolicy,
+    /// Number of attempts kept in the delivery log.
+    max_delivery_log_length: usize,
+    /// Number of dead letters kept for every entity.
+    max_dead_letters: usize,
+}
+
+/// Webhook dispatcher implementation.
+impl WebhookDispatcher {
+    ///
+    /// Return a new WebhookDispatcher without endpoints, keeping DEFAULT_MAX_DELIVERY_LOG_LENGTH attempts
+    /// in its delivery log and DEFAULT_MAX_DEAD_LETTERS dead letters for every entity.
+    ///
+    /// # Arguments
+    ///
+    /// * `retry_policy` - A RetryPolicy that represents how the failed deliveries are retried.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::webhook::{RetryPolicy, WebhookDispatcher};
+    /// let dispatcher = WebhookDispatcher::new(
+    ///     RetryPolicy::default()
+    /// );
+    /// # assert!(dispatcher.get_pending_deliveries().is_empty())
+    /// ```
+    ///
+    pub fn new(
+        retry_policy: RetryPolicy,
+    ) -> WebhookDispatcher {
+        WebhookDispatcher {
+            state: Arc::new(Mutex::new(DispatcherState::default())),
+            retry_policy,
+            max_delivery_log_length: DEFAULT_MAX_DELIVERY_LOG_LENGTH,
+            max_dead_letters: DEFAULT_MAX_DEAD_LETTERS,
+        }
+    }
+
+    ///
+    /// Return the WebhookDispatcher keeping the given number of attempts in its delivery log.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_delivery_log_length` - A usize integer that represents the number of attempts kept in the delivery log.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::webhook::{RetryPolicy, WebhookDispatcher};
+    /// let dispatcher = WebhookDispatcher::new(RetryPolicy::default()).with_max_delivery_log_length(100);
+    /// # assert_eq!(dispatcher.get_max_delivery_log_length(), 100)
+    /// ```
+    ///
+    pub fn with_max_delivery_log_length(
+        mut self: WebhookDispatcher,
+        max_delivery_log_length: usize,
+    ) -> WebhookDispatcher {
+        self.max_delivery_log_length = max_delivery_log_length;
+        self
+    }
+
+    ///
+    /// Return the number of attempts kept in the delivery log.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::webhook::{RetryPolicy, WebhookDispatcher, DEFAULT_MAX_DELIVERY_LOG_LENGTH};
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// let max_delivery_log_length : usize = dispatcher.get_max_delivery_log_length();
+    /// # assert_eq!(max_delivery_log_length, DEFAULT_MAX_DELIVERY_LOG_LENGTH)
+    /// ```
+    ///
+    pub fn get_max_delivery_log_length(
+        self: &WebhookDispatcher,
+    ) -> usize {
+        self.max_delivery_log_length
+    }
+
+    ///
+    /// Return the WebhookDispatcher keeping the given number of dead letters for every entity.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_dead_letters` - A usize integer that represents the number of dead letters kept for every entity.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::webhook::{RetryPolicy, WebhookDispatcher};
+    /// let dispatcher = WebhookDispatcher::new(RetryPolicy::default()).with_max_dead_letters(10);
+    /// # assert_eq!(dispatcher.get_max_dead_letters(), 10)
+    /// ```
+    ///
+    pub fn with_max_dead_letters(
+        mut self: WebhookDispatcher,
+        max_dead_letters: usize,
+    ) -> WebhookDispatcher {
+        self.max_dead_letters = max_dead_letters;
+        self
+    }
+
+    ///
+    /// Return the number of dead letters kept for every entity.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::webhook::{RetryPolicy, WebhookDispatcher, DEFAULT_MAX_DEAD_LETTERS};
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// let max_dead_letters : usize = dispatcher.get_max_dead_letters();
+    /// # assert_eq!(max_dead_letters, DEFAULT_MAX_DEAD_LETTERS)
+    /// ```
+    ///
+    pub fn get_max_dead_letters(
+        self: &WebhookDispatcher,
+    ) -> usize {
+        self.max_dead_letters
+    }
+
+    ///
+    /// Register the endpoint of an entity, replacing its previous endpoint if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A String that represents the id of the entity.
+    /// * `url` - A String that represents the url of the endpoint, e.g. "http://localhost:8080/webhooks".
+    /// * `secret` - A String that represents the secret shared with the entity, used to sign the payloads.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::webhook::{RetryPolicy, WebhookDispatcher};
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// let result = dispatcher.register_endpoint("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string());
+    /// # assert!(result.is_ok());
+    /// # assert!(dispatcher.get_endpoint("entity_001").is_some())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An InvalidUrlError is returned if the url is not an http url with a host.
+    ///
+    pub fn register_endpoint(
+        self: &WebhookDispatcher,
+        entity_id: String,
+        url: String,
+        secret: String,
+    ) -> Result<(), WebhookError> {
+        let endpoint = WebhookEndpoint::new(entity_id.clone(), url, secret)?;
+        self.lock().endpoints.insert(entity_id, endpoint);
+        Ok(())
+    }
+
+    ///
+    /// Remove the endpoint of an entity. The pending deliveries to the entity are dead-lettered at their next attempt.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A str that represents the id of the entity.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::webhook::{RetryPolicy, WebhookDispatcher};
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// # dispatcher.register_endpoint("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// let result = dispatcher.unregister_endpoint("entity_001");
+    /// # assert!(result.is_ok());
+    /// # assert!(dispatcher.unregister_endpoint("entity_001").is_err())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An EndpointNotFoundError is returned if the entity has no endpoint.
+    ///
+    pub fn unregister_endpoint(
+        self: &WebhookDispatcher,
+        entity_id: &str,
+    ) -> Result<(), WebhookError> {
+        match self.lock().endpoints.remove(entity_id) {
+            Some(_) => Ok(()),
+            None => Err(WebhookError::EndpointNotFoundError { entity_id: entity_id.to_string() }),
+        }
+    }
+
+    ///
+    /// Register the endpoint of an entity on its behalf, like 'register_endpoint', once the registration is checked
+    /// to be signed with the registered public key of the entity, see 'sign_registration'.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity` - An Entity that represents the entity, with its public key.
+    /// * `url` - A String that represents the url of the endpoint, e.g. "http://localhost:8080/webhooks".
+    /// * `secret` - A String that represents the secret shared with the entity, used to sign the payloads.
+    /// * `signature` - A str that represents the signature of the registration by the entity, hex encoded.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::crypto::KeyPair;
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
+    /// # use banana_coin::webhook::{sign_registration, RetryPolicy, WebhookDispatcher};
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// let key_pair = KeyPair::generate();
+    /// let mut entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// entity.set_public_key(key_pair.get_public_key());
+    /// let url = "http://localhost:8080/webhooks";
+    /// let signature = sign_registration("entity_001", Some(url), &key_pair);
+    /// let result = dispatcher.register_signed_endpoint(&entity, url.to_string(), "secret".to_string(), &signature);
+    /// # assert!(result.is_ok());
+    /// # assert!(dispatcher.register_signed_endpoint(&entity, "http://localhost:9090/".to_string(), "secret".to_string(), &signature).is_err())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A MissingPublicKeyError is returned if the entity has no public key, a SignatureVerificationError
+    /// if the signature does not match the registration and the public key and an InvalidUrlError
+    /// if the url is not an http url with a host.
+    ///
+    pub fn register_signed_endpoint(
+        self: &WebhookDispatcher,
+        entity: &Entity,
+        url: String,
+        secret: String,
+        signature: &str,
+    ) -> Result<(), WebhookError> {
+        check_registration_signature(entity, Some(&url), signature)?;
+        self.register_endpoint(entity.get_id().to_string(), url, secret)
+    }
+
+    ///
+    /// Remove the endpoint of an entity on its behalf, like 'unregister_endpoint', once the removal is checked
+    /// to be signed with the registered public key of the entity, see 'sign_registration'.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity` - An Entity that represents the entity, with its public key.
+    /// * `signature` - A str that represents the signature of the removal by the entity, hex encoded.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::crypto::KeyPair;
+    /// # use banana_coin::model::{Amount, Entity, Wallet};
+    /// # use banana_coin::webhook::{sign_registration, RetryPolicy, WebhookDispatcher};
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// # let key_pair = KeyPair::generate();
+    /// # let mut entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(0)));
+    /// # entity.set_public_key(key_pair.get_public_key());
+    /// # dispatcher.register_endpoint("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// let signature = sign_registration("entity_001", None, &key_pair);
+    /// let result = dispatcher.unregister_signed_endpoint(&entity, &signature);
+    /// # assert!(result.is_ok());
+    /// # assert!(dispatcher.get_endpoint("entity_001").is_none())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A MissingPublicKeyError is returned if the entity has no public key, a SignatureVerificationError
+    /// if the signature does not match the removal and the public key and an EndpointNotFoundError
+    /// if the entity has no endpoint.
+    ///
+    pub fn unregister_signed_endpoint(
+        self: &WebhookDispatcher,
+        entity: &Entity,
+        signature: &str,
+    ) -> Result<(), WebhookError> {
+        check_registration_signature(entity, None, signature)?;
+        self.unregister_endpoint(entity.get_id())
+    }
+
+    ///
+    /// Return a copy of the endpoint of an entity, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A str that represents the id of the entity.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::webhook::{RetryPolicy, WebhookDispatcher, WebhookEndpoint};
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// # dispatcher.register_endpoint("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// let endpoint : Option<WebhookEndpoint> = dispatcher.get_endpoint("entity_001");
+    /// # assert_eq!(endpoint.unwrap().get_url(), "http://localhost:8080/webhooks")
+    /// ```
+    ///
+    pub fn get_endpoint(
+        self: &WebhookDispatcher,
+        entity_id: &str,
+    ) -> Option<WebhookEndpoint> {
+        self.lock().endpoints.get(entity_id).cloned()
+    }
+
+    ///
+    /// Queue the notification of a transaction to the endpoints of its sender and of its receiver,
+    /// if its current state is Completed, Failed or Blocked. The deliveries are due at the timestamp of the state.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - A Transaction that represents the transaction to notify.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::model::{Amount, Transaction};
+    /// # use banana_coin::model::TransactionState::*;
+    /// # use banana_coin::webhook::{RetryPolicy, WebhookDispatcher};
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// # dispatcher.register_endpoint("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// let transaction = Transaction::new(
+    ///     "id_0001".to_string(),
+    ///     "entity_001".to_string(),
+    ///     "entity_002".to_string(),
+    ///     Amount::from_integer(100),
+    ///     "Moving 100 coins.".to_string(),
+    ///     Failed {timestamp: 2},
+    ///     vec![Created {timestamp: 1}, OnProcess {timestamp: 2}, Failed {timestamp: 2}]
+    /// ).unwrap();
+    /// dispatcher.enqueue(&transaction);
+    /// # assert_eq!(dispatcher.get_pending_deliveries().len(), 1)
+    /// ```
+    ///
+    pub fn enqueue(
+        self: &WebhookDispatcher,
+        transaction: &Transaction,
+    ) {
+        let state = transaction.get_current_state();
+        if !matches!(state, TransactionState::Completed { .. } | TransactionState::Failed { .. } | TransactionState::Blocked { .. }) {
+            return;
+        }
+        let payload = match serde_json::to_string(transaction) {
+            Ok(payload) => payload,
+            Err(_) => return,
+        };
+        let mut entity_ids = vec![transaction.get_from_entity_id(), transaction.get_to_entity_id()];
+        entity_ids.dedup();
+        let mut dispatcher = self.lock();
+        for entity_id in entity_ids {
+            if !dispatcher.endpoints.contains_key(&entity_id) {
+                continue;
+            }
+            dispatcher.delivery_count += 1;
+            let delivery = WebhookDelivery::new(
+                format!("dlv_{:06}", dispatcher.delivery_count),
+                entity_id,
+                transaction.get_id(),
+                payload.clone(),
+                state.get_timestamp(),
+            );
+            dispatcher.pending.push(delivery);
+        }
+    }
+
+    ///
+    /// Attempt the deliveries due at the given timestamp and return the attempts made, in the order the deliveries were queued.
+    /// The endpoints are called in parallel, each by its own thread, so that a slow or unreachable endpoint only delays
+    /// its own deliveries. Once an endpoint can not be reached, its other due deliveries wait for the next processing.
+    /// A failed delivery is attempted again after the backoff of the retry policy, or dead-lettered after its
+    /// last attempt; a delivery to an entity without endpoint is dead-lettered at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - A u64 integer that represents the current timestamp, also sent with the signed payloads.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::webhook::{DeliveryAttempt, RetryPolicy, WebhookDispatcher};
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// let attempts : Vec<DeliveryAttempt> = dispatcher.process(10);
+    /// # assert!(attempts.is_empty())
+    /// ```
+    ///
+    pub fn process(
+        self: &WebhookDispatcher,
+        timestamp: u64,
+    ) -> Vec<DeliveryAttempt> {
+        // The due deliveries are taken out of the queue, so that the endpoints are called without holding the lock
+        let mut due: BTreeMap<String, Vec<DueDelivery>> = BTreeMap::new();
+        {
+            let mut dispatcher = self.lock();
+            let (due_deliveries, pending) = dispatcher.pending
+                .drain(..)
+                .partition(|delivery: &WebhookDelivery| delivery.get_next_attempt_at() <= timestamp);
+            dispatcher.pending = pending;
+            for (position, delivery) in due_deliveries.into_iter().enumerate() {
+                let endpoint = dispatcher.endpoints.get(delivery.get_entity_id()).cloned();
+                due.entry(delivery.get_entity_id().to_string()).or_default().push((position, delivery, endpoint));
+            }
+        }
+
+        let mut attempts: Vec<(usize, DeliveryAttempt)> = thread::scope(|scope| {
+            let workers: Vec<_> = due
+                .into_values()
+                .map(|deliveries| scope.spawn(move || self.deliver(deliveries, timestamp)))
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap_or_default())
+                .collect()
+        });
+        attempts.sort_by_key(|(position, _)| *position);
+        let attempts: Vec<DeliveryAttempt> = attempts.into_iter().map(|(_, attempt)| attempt).collect();
+
+        let mut dispatcher = self.lock();
+        dispatcher.delivery_log.extend(attempts.iter().cloned());
+        let overflow = dispatcher.delivery_log.len().saturating_sub(self.max_delivery_log_length);
+        dispatcher.delivery_log.drain(..overflow);
+        attempts
+    }
+
+    ///
+    /// Move a dead-lettered delivery back to the queue, due at the given timestamp with all the attempts of the retry policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `delivery_id` - A str that represents the id of the delivery.
+    /// * `timestamp` - A u64 integer that represents the timestamp the delivery is due at.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::webhook::{RetryPolicy, WebhookDispatcher};
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// let result = dispatcher.retry_dead_letter("dlv_000001", 10);
+    /// # assert!(result.is_err())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// A DeadLetterNotFoundError is returned if no dead-lettered delivery has the given id.
+    ///
+    pub fn retry_dead_letter(
+        self: &WebhookDispatcher,
+        delivery_id: &str,
+        timestamp: u64,
+    ) -> Result<(), WebhookError> {
+        let mut dispatcher = self.lock();
+        let position = dispatcher.dead_letters
+            .iter()
+            .position(|delivery| delivery.get_id() == delivery_id)
+            .ok_or_else(|| WebhookError::DeadLetterNotFoundError { delivery_id: delivery_id.to_string() })?;
+        let mut delivery = dispatcher.dead_letters.remove(position);
+        delivery.reset(timestamp);
+        dispatcher.pending.push(delivery);
+        Ok(())
+    }
+
+    ///
+    /// Return a copy of the deliveries waiting for their next attempt.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::webhook::{RetryPolicy, WebhookDelivery, WebhookDispatcher};
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// let pending_deliveries : Vec<WebhookDelivery> = dispatcher.get_pending_deliveries();
+    /// # assert!(pending_deliveries.is_empty())
+    /// ```
+    ///
+    pub fn get_pending_deliveries(
+        self: &WebhookDispatcher,
+    ) -> Vec<WebhookDelivery> {
+        self.lock().pending.clone()
+    }
+
+    ///
+    /// Return a copy of the deliveries that failed all their attempts, the most recent ones of every entity.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::webhook::{RetryPolicy, WebhookDelivery, WebhookDispatcher};
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// let dead_letters : Vec<WebhookDelivery> = dispatcher.get_dead_letters();
+    /// # assert!(dead_letters.is_empty())
+    /// ```
+    ///
+    pub fn get_dead_letters(
+        self: &WebhookDispatcher,
+    ) -> Vec<WebhookDelivery> {
+        self.lock().dead_letters.clone()
+    }
+
+    ///
+    /// Return a copy of the delivery log: the most recent attempts made, in order.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::webhook::{DeliveryAttempt, RetryPolicy, WebhookDispatcher};
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// let delivery_log : Vec<DeliveryAttempt> = dispatcher.get_delivery_log();
+    /// # assert!(delivery_log.is_empty())
+    /// ```
+    ///
+    pub fn get_delivery_log(
+        self: &WebhookDispatcher,
+    ) -> Vec<DeliveryAttempt> {
+        self.lock().delivery_log.iter().cloned().collect()
+    }
+
+    ///
+    /// Start a thread processing the dispatcher at the given interval, with the current time as timestamp.
+    /// The thread stops when the returned worker is shut down or dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - A Duration that represents the time between two processings.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::time::Duration;
+    /// # use banana_coin::webhook::{RetryPolicy, WebhookDispatcher};
+    /// # let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    /// let worker = dispatcher.start_worker(Duration::from_secs(1));
+    /// worker.shutdown();
+    /// ```
+    ///
+    pub fn start_worker(
+        self: &WebhookDispatcher,
+        interval: Duration,
+    ) -> WebhookWorker {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let worker_stopped = Arc::clone(&stopped);
+        let dispatcher = self.clone();
+        let thread = thread::spawn(move || {
+            while !worker_stopped.load(Ordering::SeqCst) {
+                dispatcher.process(now());
+                thread::park_timeout(interval);
+            }
+        });
+        WebhookWorker {
+            stopped,
+            thread: Mutex::new(Some(thread)),
+        }
+    }
+
+    /// Attempt the due deliveries of a single entity, in order, and return the attempts made with the positions of their deliveries.
+    fn deliver(
+        self: &WebhookDispatcher,
+        deliveries: Vec<DueDelivery>,
+        timestamp: u64,
+    ) -> Vec<(usize, DeliveryAttempt)> {
+        let mut attempts = Vec::new();
+        let mut unreachable = false;
+        for (position, mut delivery, endpoint) in deliveries {
+            if unreachable {
+                self.lock().pending.push(delivery);
+                continue;
+            }
+            let result = match &endpoint {
+                Some(endpoint) => {
+                    let headers = [
+                        (DELIVERY_HEADER, delivery.get_id().to_string()),
+                        (TIMESTAMP_HEADER, timestamp.to_string()),
+                        (SIGNATURE_HEADER, endpoint.sign(timestamp, delivery.get_payload())),
+                    ];
+                    endpoint.post(&headers, delivery.get_payload(), DELIVERY_TIMEOUT)
+                }
+                None => Err(WebhookError::EndpointNotFoundError { entity_id: delivery.get_entity_id().to_string() }),
+            };
+            let url = endpoint.as_ref().map(|endpoint| endpoint.get_url().to_string());
+            let attempt = DeliveryAttempt::new(&delivery, url, timestamp, &result);
+
+            let mut dispatcher = self.lock();
+            match result {
+                Ok(_) => {}
+                Err(WebhookError::EndpointNotFoundError { .. }) => {
+                    delivery.fail(&self.retry_policy, timestamp);
+                    dispatcher.push_dead_letter(delivery, self.max_dead_letters);
+                }
+                Err(error) => {
+                    // An endpoint that can not be reached would make every other delivery wait for the timeout again
+                    unreachable = matches!(error, WebhookError::IoError { .. });
+                    if delivery.fail(&self.retry_policy, timestamp) {
+                        dispatcher.pending.push(delivery);
+                    } else {
+                        dispatcher.push_dead_letter(delivery, self.max_dead_letters);
+                    }
+                }
+            }
+            attempts.push((position, attempt));
+        }
+        attempts
+    }
+
+    /// Lock the shared state, which stays usable even if a thread panicked while it was locked.
+    fn lock(
+        self: &WebhookDispatcher,
+    ) -> MutexGuard<'_, DispatcherState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Check that the registration of the endpoint of an entity at the given url, or its removal if no url is given,
+/// is signed with the public key of the entity.
+fn check_registration_signature(
+    entity: &Entity,
+    url: Option<&str>,
+    signature: &str,
+) -> Result<(), WebhookError> {
+    let entity_id = entity.get_id();
+    let public_key = entity
+        .get_public_key()
+        .ok_or_else(|| WebhookError::MissingPublicKeyError { entity_id: entity_id.to_string() })?;
+    verify_signature(public_key, &registration_signing_bytes(entity_id, url), signature)
+        .map_err(|error| WebhookError::SignatureVerificationError { entity_id: entity_id.to_string(), error })
+}
+
+/// Dispatcher state implementation.
+impl DispatcherState {
+    /// Add a dead letter, dropping the oldest dead letter of the same entity if it has more than the given number.
+    fn push_dead_letter(
+        self: &mut DispatcherState,
+        delivery: WebhookDelivery,
+        max_dead_letters: usize,
+    ) {
+        let entity_id = delivery.get_entity_id().to_string();
+        self.dead_letters.push(delivery);
+        let dead_letter_count = self.dead_letters.iter().filter(|delivery| delivery.get_entity_id() == entity_id).count();
+        if dead_letter_count > max_dead_letters {
+            if let Some(position) = self.dead_letters.iter().position(|delivery| delivery.get_entity_id() == entity_id) {
+                self.dead_letters.remove(position);
+            }
+        }
+    }
+}
+
+/// A dispatcher queues the notifications of the transactions whose state changes.
+impl Subscriber for WebhookDispatcher {
+    fn notify(
+        &self,
+        event: &DomainEvent,
+    ) {
+        if let DomainEvent::TransactionStateChanged { transaction, .. } = event {
+            self.enqueue(transaction);
+        }
+    }
+}
+
+/// Webhook worker structure
+/// It represents the thread processing a dispatcher at a regular interval, see 'WebhookDispatcher::start_worker'.
+/// The thread stops when the worker is shut down or dropped.
+#[derive(Debug)]
+pub struct WebhookWorker {
+    stopped: Arc<AtomicBool>,
+    thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+/// Webhook worker implementation.
+impl WebhookWorker {
+    ///
+    /// Stop the thread, once the processing in progress, if any, is completed.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::time::Duration;
+    /// # use banana_coin::webhook::{RetryPolicy, WebhookDispatcher};
+    /// let worker = WebhookDispatcher::new(RetryPolicy::default()).start_worker(Duration::from_secs(1));
+    /// worker.shutdown();
+    /// ```
+    ///
+    pub fn shutdown(
+        self: &WebhookWorker,
+    ) {
+        self.stopped.store(true, Ordering::SeqCst);
+        let thread = self.thread.lock().unwrap_or_else(PoisonError::into_inner).take();
+        if let Some(thread) = thread {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+/// A worker stops when it is dropped.
+impl Drop for WebhookWorker {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
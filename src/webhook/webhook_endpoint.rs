@@ -0,0 +1,351 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::crypto::KeyPair;
+use crate::error::WebhookError;
+
+/// Header carrying the signature of a webhook payload.
+pub const SIGNATURE_HEADER: &str = "X-Banana-Signature";
+
+/// Header carrying the timestamp a webhook payload was signed at.
+pub const TIMESTAMP_HEADER: &str = "X-Banana-Timestamp";
+
+/// Header carrying the id of a webhook delivery, which is the same for all its attempts.
+pub const DELIVERY_HEADER: &str = "X-Banana-Delivery";
+
+/// Prefix of a signature, naming the algorithm it is computed with.
+const SIGNATURE_PREFIX: &str = "sha256=";
+
+/// Prefix of the bytes an entity signs to register or remove its endpoint, so that the signature can not be used for anything else.
+const REGISTRATION_SIGNING_DOMAIN: &[u8] = b"banana_coin/webhook_registration/v1";
+
+/// Maximum length of the status line of a response, in bytes, so that an endpoint can not make the dispatcher
+/// read without end.
+const MAX_STATUS_LINE_LENGTH: usize = 8 * 1024;
+
+/// Webhook endpoint structure
+/// It represents the HTTP endpoint of an entity, notified of the transactions of the entity, and the secret
+/// the payloads sent to it are signed with. Only plain HTTP endpoints are supported.
+#[derive(Clone, PartialEq)]
+pub struct WebhookEndpoint {
+    entity_id: String,
+    url: String,
+    secret: String,
+    /// Host and port the endpoint listens on.
+    authority: String,
+    /// Path and query of the endpoint.
+    path: String,
+}
+
+/// Webhook endpoint implementation.
+impl WebhookEndpoint {
+    ///
+    /// Return a new WebhookEndpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id` - A String that represents the id of the entity notified through the endpoint.
+    /// * `url` - A String that represents the url of the endpoint, e.g. "http://localhost:8080/webhooks".
+    /// * `secret` - A String that represents the secret shared with the entity, used to sign the payloads.
+    ///
+    /// # Example
+    /// ```
+    /// use banana_coin::webhook::WebhookEndpoint;
+    /// let endpoint = WebhookEndpoint::new(
+    ///     "entity_001".to_string(),
+    ///     "http://localhost:8080/webhooks".to_string(),
+    ///     "secret".to_string()
+    /// );
+    /// # assert!(endpoint.is_ok());
+    /// # assert!(WebhookEndpoint::new("entity_001".to_string(), "ftp://localhost/webhooks".to_string(), "secret".to_string()).is_err())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An InvalidUrlError is returned if the url is not an http url with a host, or if it contains user information,
+    /// whitespace or control characters.
+    ///
+    pub fn new(
+        entity_id: String,
+        url: String,
+        secret: String,
+    ) -> Result<WebhookEndpoint, WebhookError> {
+        let invalid = |message: &str| WebhookError::InvalidUrlError { url: url.clone(), message: message.to_string() };
+        let rest = url.strip_prefix("http://").ok_or_else(|| invalid("only http urls are supported"))?;
+        let (authority, path) = match rest.find(['/', '?']) {
+            Some(index) => (&rest[..index], rest[index..].to_string()),
+            None => (rest, "/".to_string()),
+        };
+        let path = if path.starts_with('?') { format!("/{}", path) } else { path };
+        // The authority and the path are written in the request line and in the Host header
+        let unsafe_char = |c: char| c.is_whitespace() || c.is_control();
+        if authority.is_empty() || authority.contains('@') || authority.contains(unsafe_char) || path.contains(unsafe_char) {
+            return Err(invalid("the url must have a host and no user information, spaces or control characters"));
+        }
+        let authority = if authority.rsplit_once(':').is_some_and(|(_, port)| port.parse::<u16>().is_ok()) {
+            authority.to_string()
+        } else {
+            format!("{}:80", authority)
+        };
+        Ok(WebhookEndpoint {
+            entity_id,
+            url,
+            secret,
+            authority,
+            path,
+        })
+    }
+
+    ///
+    /// Return the id of the entity notified through the endpoint.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::webhook::WebhookEndpoint;
+    /// # let endpoint = WebhookEndpoint::new("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// let entity_id : &str = endpoint.get_entity_id();
+    /// # assert_eq!(entity_id, "entity_001")
+    /// ```
+    ///
+    pub fn get_entity_id(
+        self: &WebhookEndpoint,
+    ) -> &str {
+        &self.entity_id
+    }
+
+    ///
+    /// Return the url of the endpoint.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::webhook::WebhookEndpoint;
+    /// # let endpoint = WebhookEndpoint::new("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// let url : &str = endpoint.get_url();
+    /// # assert_eq!(url, "http://localhost:8080/webhooks")
+    /// ```
+    ///
+    pub fn get_url(
+        self: &WebhookEndpoint,
+    ) -> &str {
+        &self.url
+    }
+
+    ///
+    /// Return the signature of a payload sent at the given timestamp, see 'sign_payload'.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - A u64 integer that represents the timestamp the payload is sent at.
+    /// * `payload` - A str that represents the payload.
+    ///
+    /// # Example
+    /// ```
+    /// # use banana_coin::webhook::{verify_payload, WebhookEndpoint};
+    /// # let endpoint = WebhookEndpoint::new("entity_001".to_string(), "http://localhost:8080/webhooks".to_string(), "secret".to_string()).unwrap();
+    /// let signature : String = endpoint.sign(1, "{}");
+    /// # assert!(verify_payload("secret", 1, "{}", &signature))
+    /// ```
+    ///
+    pub fn sign(
+        self: &WebhookEndpoint,
+        timestamp: u64,
+        payload: &str,
+    ) -> String {
+        sign_payload(&self.secret, timestamp, payload)
+    }
+
+    /// Send a payload to the endpoint with an HTTP POST request and return the status of the successful response.
+    pub(crate) fn post(
+        self: &WebhookEndpoint,
+        headers: &[(&str, String)],
+        payload: &str,
+        timeout: Duration,
+    ) -> Result<u16, WebhookError> {
+        let address = self.authority
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| WebhookError::IoError { message: format!("{} does not resolve to any address", self.authority) })?;
+        let mut stream = TcpStream::connect_timeout(&address, timeout)?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+
+        let mut request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+            self.path, self.authority, payload.len());
+        for (name, value) in headers {
+            request.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        request.push_str("\r\n");
+        request.push_str(payload);
+        stream.write_all(request.as_bytes())?;
+
+        let mut status_line = Vec::new();
+        BufReader::new(stream).take(MAX_STATUS_LINE_LENGTH as u64 + 1).read_until(b'\n', &mut status_line)?;
+        if status_line.len() > MAX_STATUS_LINE_LENGTH {
+            return Err(WebhookError::MalformedResponseError {
+                message: format!("status line longer than {} bytes", MAX_STATUS_LINE_LENGTH),
+            });
+        }
+        let status_line = String::from_utf8_lossy(&status_line);
+        let status = match status_line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+            [version, status, ..] if version.starts_with("HTTP/1.") => status.parse::<u16>().ok(),
+            _ => None,
+        }.ok_or_else(|| WebhookError::MalformedResponseError {
+            message: format!("invalid status line '{}'", status_line.trim_end()),
+        })?;
+        if (200..300).contains(&status) {
+            Ok(status)
+        } else {
+            Err(WebhookError::UnexpectedStatusError { status })
+        }
+    }
+}
+
+/// The secret of an endpoint is never printed.
+impl fmt::Debug for WebhookEndpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebhookEndpoint")
+            .field("entity_id", &self.entity_id)
+            .field("url", &self.url)
+            .finish()
+    }
+}
+
+///
+/// Return the signature of a webhook payload: "sha256=" followed by the hex encoded HMAC-SHA256, keyed with the secret,
+/// of the timestamp, a dot and the payload. The timestamp is signed too, so that a payload can not be replayed later on.
+///
+/// # Arguments
+///
+/// * `secret` - A str that represents the secret of the endpoint.
+/// * `timestamp` - A u64 integer that represents the timestamp the payload is sent at.
+/// * `payload` - A str that represents the payload.
+///
+/// # Example
+/// ```
+/// use banana_coin::webhook::sign_payload;
+/// let signature = sign_payload("secret", 1, "{}");
+/// # assert!(signature.starts_with("sha256="));
+/// # assert_eq!(signature.len(), 7 + 64)
+/// ```
+///
+pub fn sign_payload(
+    secret: &str,
+    timestamp: u64,
+    payload: &str,
+) -> String {
+    payload_mac(secret, timestamp, payload)
+        .map(|mac| format!("{}{}", SIGNATURE_PREFIX, hex::encode(mac.finalize().into_bytes())))
+        .unwrap_or_default()
+}
+
+///
+/// Return whether a signature is the signature of a webhook payload, see 'sign_payload'.
+/// The signatures are compared in constant time.
+///
+/// # Arguments
+///
+/// * `secret` - A str that represents the secret of the endpoint.
+/// * `timestamp` - A u64 integer that represents the timestamp sent with the payload.
+/// * `payload` - A str that represents the payload.
+/// * `signature` - A str that represents the signature sent with the payload.
+///
+/// # Example
+/// ```
+/// use banana_coin::webhook::{sign_payload, verify_payload};
+/// let signature = sign_payload("secret", 1, "{}");
+/// let valid : bool = verify_payload("secret", 1, "{}", &signature);
+/// # assert!(valid);
+/// # assert!(!verify_payload("secret", 2, "{}", &signature));
+/// # assert!(!verify_payload("other secret", 1, "{}", &signature))
+/// ```
+///
+pub fn verify_payload(
+    secret: &str,
+    timestamp: u64,
+    payload: &str,
+    signature: &str,
+) -> bool {
+    signature
+        .strip_prefix(SIGNATURE_PREFIX)
+        .and_then(|signature| hex::decode(signature).ok())
+        .zip(payload_mac(secret, timestamp, payload))
+        .is_some_and(|(signature, mac)| mac.verify_slice(&signature).is_ok())
+}
+
+///
+/// Return the canonical bytes an entity signs to register the endpoint at the given url, or to remove its endpoint
+/// if no url is given. They are made of a domain prefix followed by the id of the entity and the url, empty for a removal,
+/// each prefixed by its length as a big-endian u32.
+///
+/// # Arguments
+///
+/// * `entity_id` - A str that represents the id of the entity.
+/// * `url` - An optional str that represents the url of the registered endpoint, None for the removal of the endpoint.
+///
+/// # Example
+/// ```
+/// use banana_coin::webhook::registration_signing_bytes;
+/// let signing_bytes : Vec<u8> = registration_signing_bytes("entity_001", Some("http://localhost:8080/webhooks"));
+/// # assert_ne!(signing_bytes, registration_signing_bytes("entity_001", None));
+/// # assert_ne!(signing_bytes, registration_signing_bytes("entity_002", Some("http://localhost:8080/webhooks")))
+/// ```
+///
+pub fn registration_signing_bytes(
+    entity_id: &str,
+    url: Option<&str>,
+) -> Vec<u8> {
+    let mut bytes = REGISTRATION_SIGNING_DOMAIN.to_vec();
+    for field in [entity_id, url.unwrap_or_default()].iter() {
+        bytes.extend_from_slice(&(field.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(field.as_bytes());
+    }
+    bytes
+}
+
+///
+/// Sign the registration of the endpoint of an entity at the given url, or the removal of its endpoint if no url is given,
+/// and return the signature, hex encoded, see 'registration_signing_bytes'.
+///
+/// # Arguments
+///
+/// * `entity_id` - A str that represents the id of the entity.
+/// * `url` - An optional str that represents the url of the registered endpoint, None for the removal of the endpoint.
+/// * `key_pair` - A KeyPair that represents the key pair of the entity.
+///
+/// # Example
+/// ```
+/// use banana_coin::crypto::{verify_signature, KeyPair};
+/// use banana_coin::webhook::{registration_signing_bytes, sign_registration};
+/// let key_pair = KeyPair::generate();
+/// let signature = sign_registration("entity_001", Some("http://localhost:8080/webhooks"), &key_pair);
+/// # let signing_bytes = registration_signing_bytes("entity_001", Some("http://localhost:8080/webhooks"));
+/// # assert!(verify_signature(&key_pair.get_public_key(), &signing_bytes, &signature).is_ok())
+/// ```
+///
+pub fn sign_registration(
+    entity_id: &str,
+    url: Option<&str>,
+    key_pair: &KeyPair,
+) -> String {
+    key_pair.sign(&registration_signing_bytes(entity_id, url))
+}
+
+/// Return the HMAC-SHA256 of the timestamp and the payload, keyed with the secret. HMAC accepts keys of any length.
+fn payload_mac(
+    secret: &str,
+    timestamp: u64,
+    payload: &str,
+) -> Option<Hmac<Sha256>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).ok()?;
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(payload.as_bytes());
+    Some(mac)
+}
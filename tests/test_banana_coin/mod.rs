@@ -1,4 +1,25 @@
+pub mod test_amount;
+pub mod test_asset;
+pub mod test_audit;
+pub mod test_blockchain;
+pub mod test_cli;
+pub mod test_crypto;
 pub mod test_entity;
+pub mod test_escrow;
+pub mod test_event;
+pub mod test_journal;
+pub mod test_ledger;
+pub mod test_mempool;
+pub mod test_merkle;
+pub mod test_miner;
+pub mod test_network;
+pub mod test_rest;
+pub mod test_rpc;
+pub mod test_storage;
+// The baseline transaction test builds its state history push by push
+#[allow(clippy::vec_init_then_push)]
 pub mod test_transaction;
 pub mod test_transaction_state;
-pub mod test_wallet;
\ No newline at end of file
+pub mod test_transfer_executor;
+pub mod test_wallet;
+pub mod test_webhook;
@@ -0,0 +1,100 @@
+extern crate banana_coin;
+use banana_coin::model::*;
+use banana_coin::error::AmountError;
+
+#[test]
+fn parse_successful() {
+    let amount = Amount::parse("12.345").unwrap();
+    assert_eq!(amount.get_units(), 12345);
+    assert_eq!(amount.get_decimals(), 3);
+    let amount = Amount::parse("-0.05").unwrap();
+    assert_eq!(amount.get_units(), -5);
+    assert!(amount.is_negative());
+    assert_eq!("7".parse::<Amount>().unwrap(), Amount::from_integer(7));
+}
+
+#[test]
+fn parse_unsuccessful_invalid_format() {
+    for text in ["", "-", "12.", ".5", "1e3", "1,5", "+1", "1.2.3", " 1"].iter() {
+        assert!(matches!(Amount::parse(text), Err(AmountError::InvalidFormatError { .. })));
+    }
+}
+
+#[test]
+fn parse_unsuccessful_too_many_decimals() {
+    assert!(matches!(Amount::parse("0.0000000000000000001"), Err(AmountError::TooManyDecimalsError { decimals: 19, max_decimals: MAX_DECIMALS })));
+}
+
+#[test]
+fn parse_unsuccessful_out_of_range() {
+    assert!(matches!(Amount::parse("170141183460469231731687303715884105728"), Err(AmountError::OutOfRangeError { .. })));
+}
+
+#[test]
+fn format_amount() {
+    assert_eq!(Amount::parse("12.340").unwrap().to_string(), "12.340");
+    assert_eq!(Amount::parse("-0.05").unwrap().to_string(), "-0.05");
+    assert_eq!(Amount::from_integer(-12).to_string(), "-12");
+    assert_eq!(Amount::zero().to_string(), "0");
+}
+
+#[test]
+fn rescale_successful() {
+    let amount = Amount::parse("1.5").unwrap().rescale(3).unwrap();
+    assert_eq!(amount.get_units(), 1500);
+    assert_eq!(amount.to_string(), "1.500");
+    assert_eq!(amount.rescale(1).unwrap().to_string(), "1.5");
+}
+
+#[test]
+fn rescale_unsuccessful_precision_loss() {
+    match Amount::parse("1.25").unwrap().rescale(1) {
+        Ok(_) => panic!("expected an error"),
+        Err(AmountError::PrecisionLossError { amount, decimals: 1 }) => assert_eq!(amount.to_string(), "1.25"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+}
+
+#[test]
+fn checked_arithmetic() {
+    let amount = Amount::parse("1.5").unwrap();
+    let other = Amount::parse("0.25").unwrap();
+    assert_eq!(amount.checked_add(other).unwrap().to_string(), "1.75");
+    assert_eq!(other.checked_sub(amount).unwrap().to_string(), "-1.25");
+    assert_eq!(amount.checked_mul(3).unwrap().to_string(), "4.5");
+    assert_eq!(amount.checked_neg().unwrap().to_string(), "-1.5");
+    let max = Amount::new(i128::MAX, 0).unwrap();
+    assert!(max.checked_add(Amount::from_integer(1)).is_none());
+    assert!(max.checked_add(other).is_none());
+    assert!(Amount::new(i128::MIN, 0).unwrap().checked_neg().is_none());
+    assert_eq!(max.saturating_add(Amount::from_integer(1)), max);
+}
+
+#[test]
+fn compare_across_decimals() {
+    assert_eq!(Amount::parse("1.50").unwrap(), Amount::parse("1.5").unwrap());
+    assert!(Amount::parse("1.05").unwrap() < Amount::parse("1.5").unwrap());
+    assert!(Amount::parse("-2.5").unwrap() < Amount::from_integer(-2));
+    assert!(Amount::zero() < Amount::parse("0.000000000000000001").unwrap());
+    assert!(!Amount::parse("0.1").unwrap().is_zero());
+    assert!(Amount::parse("0.00").unwrap().is_zero());
+}
+
+#[test]
+fn serialize_as_string() {
+    let amount = Amount::parse("12.340").unwrap();
+    assert_eq!(serde_json::to_string(&amount).unwrap(), "\"12.340\"");
+    let deserialized: Amount = serde_json::from_str("\"12.340\"").unwrap();
+    assert_eq!(deserialized.get_decimals(), 3);
+    assert_eq!(deserialized, amount);
+}
+
+#[test]
+fn deserialize_legacy_integer() {
+    let amount: Amount = serde_json::from_str("100").unwrap();
+    assert_eq!(amount, Amount::from_integer(100));
+    let amount: Amount = serde_json::from_str("-7").unwrap();
+    assert_eq!(amount, Amount::from_integer(-7));
+    assert!(serde_json::from_str::<Amount>("\"1e3\"").is_err());
+    assert!(serde_json::from_str::<Amount>("1.5").is_err());
+}
@@ -0,0 +1,70 @@
+extern crate banana_coin;
+use banana_coin::model::*;
+use banana_coin::error::AmountError;
+
+#[test]
+fn asset_definition_successful() {
+    let definition = AssetDefinition::new("EUR".to_string(), 3).unwrap();
+    assert_eq!(definition.get_code(), "EUR");
+    assert_eq!(definition.get_decimals(), 3);
+    assert!(definition.fits(Amount::parse("1.234").unwrap()));
+    assert!(definition.fits(Amount::parse("1.23400").unwrap()));
+    assert!(!definition.fits(Amount::parse("1.2345").unwrap()));
+    assert_eq!(AssetDefinition::new(DEFAULT_ASSET.to_string(), DEFAULT_ASSET_DECIMALS).unwrap(), AssetDefinition::default_for(DEFAULT_ASSET));
+    assert_eq!(AssetDefinition::default_for("EUR").get_decimals(), MAX_DECIMALS);
+}
+
+#[test]
+fn asset_definition_unsuccessful_default_asset_conflict() {
+    match AssetDefinition::new(DEFAULT_ASSET.to_string(), 2) {
+        Ok(_) => panic!("expected an error"),
+        Err(AmountError::AssetDecimalsConflictError { decimals, registered_decimals, .. }) => {
+            assert_eq!(decimals, 2);
+            assert_eq!(registered_decimals, DEFAULT_ASSET_DECIMALS);
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+}
+
+#[test]
+fn asset_definition_unsuccessful_too_many_decimals() {
+    assert!(matches!(AssetDefinition::new("EUR".to_string(), MAX_DECIMALS + 1), Err(AmountError::TooManyDecimalsError { .. })));
+}
+
+#[test]
+fn asset_definition_deserialize_validated() {
+    let definition: AssetDefinition = serde_json::from_str(r#"{"code":"EUR","decimals":2}"#).unwrap();
+    assert_eq!(definition, AssetDefinition::new("EUR".to_string(), 2).unwrap());
+    assert!(serde_json::from_str::<AssetDefinition>(&format!(r#"{{"code":"EUR","decimals":{}}}"#, MAX_DECIMALS + 1)).is_err());
+    assert!(serde_json::from_str::<AssetDefinition>(&format!(r#"{{"code":"{}","decimals":2}}"#, DEFAULT_ASSET)).is_err());
+}
+
+#[test]
+fn asset_registry_successful() {
+    let mut registry = AssetRegistry::new();
+    assert_eq!(registry.get_definition("EUR"), AssetDefinition::default_for("EUR"));
+    assert!(registry.define(AssetDefinition::new("EUR".to_string(), 2).unwrap()).is_ok());
+    assert!(registry.define(AssetDefinition::new("EUR".to_string(), 2).unwrap()).is_ok());
+    assert_eq!(registry.get_definition("EUR").get_decimals(), 2);
+    assert_eq!(registry.get_definition(DEFAULT_ASSET).get_decimals(), DEFAULT_ASSET_DECIMALS);
+    let json = serde_json::to_string(&registry).unwrap();
+    let deserialized: AssetRegistry = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, registry);
+}
+
+#[test]
+fn asset_registry_unsuccessful_conflict() {
+    let mut registry = AssetRegistry::new();
+    assert!(registry.define(AssetDefinition::new("EUR".to_string(), 2).unwrap()).is_ok());
+    match registry.define(AssetDefinition::new("EUR".to_string(), 4).unwrap()) {
+        Ok(_) => panic!("expected an error"),
+        Err(AmountError::AssetDecimalsConflictError { decimals, registered_decimals, .. }) => {
+            assert_eq!(decimals, 4);
+            assert_eq!(registered_decimals, 2);
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(registry.get_definitions(), vec![AssetDefinition::new("EUR".to_string(), 2).unwrap()]);
+    let conflicting = r#"[{"code":"EUR","decimals":2},{"code":"EUR","decimals":4}]"#;
+    assert!(serde_json::from_str::<AssetRegistry>(conflicting).is_err());
+}
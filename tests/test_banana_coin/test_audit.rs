@@ -0,0 +1,424 @@
+extern crate banana_coin;
+use std::path::{Path, PathBuf};
+use std::process::Command as Process;
+
+use banana_coin::audit::*;
+use banana_coin::cli::{run_command_with_audit, Command, TransferSignature};
+use banana_coin::crypto::KeyPair;
+use banana_coin::error::{AuditError, CliError, StorageError};
+use banana_coin::event::{DomainEvent, EventBus};
+use banana_coin::ledger::Ledger;
+use banana_coin::model::*;
+use banana_coin::model::TransactionState::*;
+use banana_coin::storage::{IdempotencyRecord, MemoryStorage, Storage};
+use banana_coin::transfer::TransferExecutor;
+
+fn audit_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("banana_coin_{}_{}_audit.jsonl", std::process::id(), name));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn new_transaction(id: &str, amount: i64) -> Transaction {
+    Transaction::new(
+        id.to_string(),
+        "entity_001".to_string(),
+        "entity_002".to_string(),
+        Amount::from_integer(amount),
+        "Moving coins.".to_string(),
+        Created {timestamp: 1},
+        vec![Created {timestamp: 1}],
+    ).unwrap()
+}
+
+/// Register two entities and transfer 30 coins between them, recording 4 balance changes.
+fn audited_ledger(path: &Path, actor: &str) -> (Ledger, AuditSubscriber) {
+    let subscriber = AuditSubscriber::new(AuditLog::open(path).unwrap(), actor.to_string());
+    let mut ledger = Ledger::new(TransferExecutor::new());
+    ledger.get_event_bus().subscribe(subscriber.clone());
+    ledger.register_entity(Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)))).unwrap();
+    ledger.register_entity(Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(5)))).unwrap();
+    ledger.execute_transaction(new_transaction("id_0001", 30), 2).unwrap();
+    (ledger, subscriber)
+}
+
+/// Storage remembering, at every change of an entity, how many records the audit log had, and failing the changes if asked to.
+struct AuditCheckingStorage {
+    storage: MemoryStorage,
+    audit_subscriber: AuditSubscriber,
+    record_counts: Vec<u64>,
+    failing: bool,
+}
+
+impl AuditCheckingStorage {
+    fn check_failing(&self) -> Result<(), StorageError> {
+        match self.failing {
+            true => Err(StorageError::IoError { message: "disk full".to_string() }),
+            false => Ok(()),
+        }
+    }
+}
+
+impl Storage for AuditCheckingStorage {
+    fn save_entity(&mut self, entity: &Entity) -> Result<(), StorageError> {
+        self.record_counts.push(self.audit_subscriber.get_record_count());
+        self.check_failing()?;
+        self.storage.save_entity(entity)
+    }
+
+    fn load_entity(&self, entity_id: &str) -> Result<Option<Entity>, StorageError> {
+        self.storage.load_entity(entity_id)
+    }
+
+    fn list_entities(&self) -> Result<Vec<Entity>, StorageError> {
+        self.storage.list_entities()
+    }
+
+    fn append_transaction(&mut self, transaction: &Transaction) -> Result<(), StorageError> {
+        self.check_failing()?;
+        self.storage.append_transaction(transaction)
+    }
+
+    fn append_transaction_with_entities(&mut self, transaction: &Transaction, entities: &[Entity]) -> Result<(), StorageError> {
+        for _ in entities {
+            self.record_counts.push(self.audit_subscriber.get_record_count());
+        }
+        self.check_failing()?;
+        self.storage.append_transaction_with_entities(transaction, entities)
+    }
+
+    fn list_transactions(&self) -> Result<Vec<Transaction>, StorageError> {
+        self.storage.list_transactions()
+    }
+
+    fn save_escrow(&mut self, escrow: &Escrow) -> Result<(), StorageError> {
+        self.storage.save_escrow(escrow)
+    }
+
+    fn remove_escrow(&mut self, transaction_id: &str) -> Result<(), StorageError> {
+        self.storage.remove_escrow(transaction_id)
+    }
+
+    fn list_escrows(&self) -> Result<Vec<Escrow>, StorageError> {
+        self.storage.list_escrows()
+    }
+
+    fn save_idempotency_record(&mut self, record: &IdempotencyRecord) -> Result<(), StorageError> {
+        self.storage.save_idempotency_record(record)
+    }
+
+    fn load_idempotency_record(&self, key: &str) -> Result<Option<IdempotencyRecord>, StorageError> {
+        self.storage.load_idempotency_record(key)
+    }
+
+    fn remove_idempotency_records_before(&mut self, timestamp: u64) -> Result<usize, StorageError> {
+        self.storage.remove_idempotency_records_before(timestamp)
+    }
+}
+
+fn read_lines(path: &Path) -> Vec<serde_json::Value> {
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn write_lines(path: &Path, lines: &[serde_json::Value]) {
+    let content: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+    std::fs::write(path, content).unwrap();
+}
+
+fn assert_tampered(path: &Path, expected_line: usize) {
+    match AuditLog::verify(path) {
+        Ok(_) => panic!("expected an error"),
+        Err(AuditError::TamperedRecordError { line, .. }) => assert_eq!(line, expected_line),
+        Err(error) => panic!("unexpected error: {:?}", error),
+    }
+}
+
+#[test]
+fn audit_log_is_locked_while_open() {
+    let path = audit_path("locked");
+    let audit_log = AuditLog::open(&path).unwrap();
+    assert!(matches!(AuditLog::open(&path), Err(AuditError::LockedLogError { .. })));
+    drop(audit_log);
+    assert!(AuditLog::open(&path).is_ok());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn run_command_audits_changes_once_stored() {
+    let path = audit_path("once_stored");
+    let audit_subscriber = AuditSubscriber::new(AuditLog::open(&path).unwrap(), "operator".to_string());
+    let mut storage = AuditCheckingStorage {
+        storage: MemoryStorage::new(),
+        audit_subscriber: audit_subscriber.clone(),
+        record_counts: Vec::new(),
+        failing: false,
+    };
+    let event_bus = EventBus::new();
+    let key_pair = KeyPair::generate();
+    let commands = vec![
+        Command::EntityCreate {
+            entity_id: "entity_001".to_string(),
+            name: "john".to_string(),
+            balance: Amount::from_integer(100),
+            public_key: Some(key_pair.get_public_key()),
+        },
+        Command::EntityCreate { entity_id: "entity_002".to_string(), name: "jane".to_string(), balance: Amount::from_integer(5), public_key: None },
+        Command::Transfer {
+            from_entity_id: "entity_001".to_string(),
+            to_entity_id: "entity_002".to_string(),
+            amount: Amount::from_integer(30),
+            transaction_id: None,
+            description: None,
+            nonce: None,
+            signature: TransferSignature::SecretKey(key_pair.get_secret_key()),
+        },
+    ];
+    for command in &commands {
+        run_command_with_audit(&mut storage, command, 2, &event_bus, Some(&audit_subscriber)).unwrap();
+    }
+    // The changes are audited only once both entities of the transfer are stored, the public key along with the creation
+    assert_eq!(storage.record_counts, vec![0, 2, 3, 3]);
+    assert_eq!(audit_subscriber.get_record_count(), 5);
+
+    // A change that can not be stored is not audited
+    storage.failing = true;
+    let command = Command::WalletDeposit { entity_id: "entity_002".to_string(), amount: Amount::from_integer(10), transaction_id: None, description: None };
+    match run_command_with_audit(&mut storage, &command, 3, &event_bus, Some(&audit_subscriber)) {
+        Ok(_) => panic!("expected an error"),
+        Err(CliError::StorageOperationError { .. }) => {}
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(audit_subscriber.get_record_count(), 5);
+    assert_eq!(AuditLog::verify(&path).unwrap().unwrap().get_sequence(), 5);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn audit_log_appends_events_at_once() {
+    let path = audit_path("at_once");
+    let mut audit_log = AuditLog::open(&path).unwrap();
+    let added = DomainEvent::CoinsAdded {
+        entity_id: "entity_001".to_string(),
+        asset: DEFAULT_ASSET.to_string(),
+        amount: Amount::from_integer(10),
+        balance: Amount::from_integer(10),
+        transaction_id: None,
+        timestamp: 1,
+    };
+    let max = Amount::new(i128::MAX, 0).unwrap();
+    let removed = DomainEvent::CoinsRemoved {
+        entity_id: "entity_001".to_string(),
+        asset: DEFAULT_ASSET.to_string(),
+        amount: max,
+        balance: max,
+        transaction_id: None,
+        timestamp: 1,
+    };
+    // No record is appended if one of the events can not be recorded
+    assert!(matches!(audit_log.append_all("operator", &[added.clone(), removed]), Err(AuditError::InvalidEventError { .. })));
+    assert_eq!(audit_log.get_record_count(), 0);
+    assert_eq!(AuditLog::verify(&path).unwrap(), None);
+    assert_eq!(audit_log.append_all("operator", &[added.clone(), added]).unwrap().len(), 2);
+    assert_eq!(AuditLog::verify(&path).unwrap().unwrap().get_sequence(), 2);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn audit_log_records_balance_changes() {
+    let path = audit_path("records");
+    let (_, subscriber) = audited_ledger(&path, "operator");
+    assert!(subscriber.get_errors().is_empty());
+    assert_eq!(subscriber.get_record_count(), 4);
+
+    let records: Vec<AuditRecord> = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let summary: Vec<(&str, &str, String, String, Option<&str>)> = records
+        .iter()
+        .map(|record| (
+            record.get_entity_id(),
+            record.get_operation(),
+            record.get_before().unwrap().to_string(),
+            record.get_after().unwrap().to_string(),
+            record.get_transaction_id(),
+        ))
+        .collect();
+    assert_eq!(summary, vec![
+        ("entity_001", "EntityCreated", "0".to_string(), "100".to_string(), None),
+        ("entity_002", "EntityCreated", "0".to_string(), "5".to_string(), None),
+        ("entity_001", "CoinsRemoved", "100".to_string(), "70".to_string(), Some("id_0001")),
+        ("entity_002", "CoinsAdded", "5".to_string(), "35".to_string(), Some("id_0001")),
+    ]);
+    assert!(records.iter().all(|record| record.get_actor() == "operator" && record.get_asset() == Some("BNC")));
+    assert_eq!(records[2].get_timestamp(), 2);
+    assert_eq!(records[0].get_previous_hash(), GENESIS_HASH);
+    for pair in records.windows(2) {
+        assert_eq!(pair[1].get_previous_hash(), pair[0].get_hash());
+        assert_eq!(pair[1].get_sequence(), pair[0].get_sequence() + 1);
+    }
+    assert_eq!(AuditLog::verify(&path).unwrap(), Some(records[3].clone()));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn run_command_audits_setting_changes() {
+    let path = audit_path("settings");
+    let audit_subscriber = AuditSubscriber::new(AuditLog::open(&path).unwrap(), "operator".to_string());
+    let mut storage = MemoryStorage::new();
+    let event_bus = EventBus::new();
+    let public_key = KeyPair::generate().get_public_key();
+    let commands = vec![
+        Command::EntityCreate { entity_id: "entity_001".to_string(), name: "john".to_string(), balance: Amount::zero(), public_key: None },
+        Command::EntityKey { entity_id: "entity_001".to_string(), public_key: public_key.clone() },
+        Command::WalletOverdraft {
+            entity_id: "entity_001".to_string(),
+            overdraft_policy: OverdraftPolicy::Limit { floor: Amount::from_integer(-50) },
+            changed_by: "admin".to_string(),
+        },
+    ];
+    for command in &commands {
+        run_command_with_audit(&mut storage, command, 2, &event_bus, Some(&audit_subscriber)).unwrap();
+    }
+    let records: Vec<AuditRecord> = read_lines(&path)
+        .into_iter()
+        .map(|line| serde_json::from_value(line).unwrap())
+        .collect();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[1].get_operation(), "PublicKeySet");
+    assert_eq!(records[1].get_setting(), Some("public_key"));
+    assert_eq!(records[1].get_previous_value(), None);
+    assert_eq!(records[1].get_value(), Some(public_key.as_str()));
+    assert_eq!(records[2].get_operation(), "OverdraftPolicyChanged");
+    assert_eq!(records[2].get_setting(), Some("overdraft_policy"));
+    assert_eq!(records[2].get_previous_value(), Some(OverdraftPolicy::NoOverdraft.to_string().as_str()));
+    assert_eq!(records[2].get_value(), Some(OverdraftPolicy::Limit { floor: Amount::from_integer(-50) }.to_string().as_str()));
+    assert!(records[1..].iter().all(|record| record.get_asset().is_none() && record.get_before().is_none() && record.get_timestamp() == 2));
+
+    // The setting records are chained like the balance records
+    assert_eq!(AuditLog::verify(&path).unwrap().unwrap().get_sequence(), 3);
+    let mut lines = read_lines(&path);
+    lines[2]["value"] = serde_json::json!("unlimited overdraft");
+    write_lines(&path, &lines);
+    assert_tampered(&path, 3);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn audit_log_continues_chain_when_reopened() {
+    let path = audit_path("reopened");
+    let (_, subscriber) = audited_ledger(&path, "operator");
+    drop(subscriber);
+    let last_record = AuditLog::verify(&path).unwrap().unwrap();
+
+    let audit_log = AuditLog::open(&path).unwrap();
+    assert_eq!(audit_log.get_record_count(), 4);
+    assert_eq!(audit_log.get_last_hash(), last_record.get_hash());
+    let subscriber = AuditSubscriber::new(audit_log, "auditor".to_string());
+    let mut ledger = Ledger::new(TransferExecutor::new());
+    ledger.get_event_bus().subscribe(subscriber.clone());
+    ledger.register_entity(Entity::new("entity_003".to_string(), "jack".to_string(), Wallet::new(Amount::from_integer(1)))).unwrap();
+
+    let record = AuditLog::verify(&path).unwrap().unwrap();
+    assert_eq!(record.get_sequence(), 5);
+    assert_eq!(record.get_actor(), "auditor");
+    assert_eq!(record.get_previous_hash(), last_record.get_hash());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn audit_log_pinpoints_first_tampered_record() {
+    let path = audit_path("tampered");
+    audited_ledger(&path, "operator");
+    let original = read_lines(&path);
+
+    // Changing a balance breaks the hash of the record
+    let mut lines = original.clone();
+    lines[2]["after"] = serde_json::json!("700");
+    write_lines(&path, &lines);
+    assert_tampered(&path, 3);
+    match AuditLog::open(&path) {
+        Ok(_) => panic!("expected an error"),
+        Err(AuditError::TamperedRecordError { line, .. }) => assert_eq!(line, 3),
+        Err(error) => panic!("unexpected error: {:?}", error),
+    }
+
+    // Recomputing the hash of the changed record breaks the chain at the next record
+    let record: AuditRecord = serde_json::from_value(lines[2].clone()).unwrap();
+    lines[2]["hash"] = serde_json::json!(record.compute_hash().unwrap());
+    write_lines(&path, &lines);
+    assert_tampered(&path, 4);
+
+    // Removing or reordering records breaks the sequence
+    let mut lines = original.clone();
+    lines.remove(1);
+    write_lines(&path, &lines);
+    assert_tampered(&path, 2);
+    let mut lines = original.clone();
+    lines.swap(0, 1);
+    write_lines(&path, &lines);
+    assert_tampered(&path, 1);
+
+    // A line that is not a record is tampered too
+    let mut content: String = original.iter().map(|line| format!("{}\n", line)).collect();
+    content.push_str("not a record\n");
+    std::fs::write(&path, content).unwrap();
+    assert_tampered(&path, 5);
+
+    write_lines(&path, &original);
+    assert_eq!(AuditLog::verify(&path).unwrap().unwrap().get_sequence(), 4);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn binary_audits_balance_changes() {
+    let data_path = audit_path("binary_data");
+    let path = audit_path("binary");
+    let binary = env!("CARGO_BIN_EXE_banana_coin");
+    let invoke = |line: &str| {
+        Process::new(binary)
+            .arg("--data-file")
+            .arg(&data_path)
+            .arg("--audit-log")
+            .arg(&path)
+            .arg("--actor")
+            .arg("alice")
+            .args(line.split_whitespace())
+            .output()
+            .unwrap()
+    };
+    assert!(invoke("entity create entity_001 john --balance 100").status.success());
+    assert!(invoke("wallet withdraw entity_001 40").status.success());
+    assert!(!invoke("wallet withdraw entity_001 400").status.success());
+
+    // A command can not run while another process holds the audit log
+    let audit_log = AuditLog::open(&path).unwrap();
+    let output = invoke("wallet withdraw entity_001 10");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already open"));
+    drop(audit_log);
+
+    let verify = |line: &str| Process::new(binary).arg("--data-file").arg(&data_path).args(line.split_whitespace()).arg(&path).output().unwrap();
+    let output = verify("--json audit verify");
+    assert!(output.status.success());
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(summary["record_count"], 2);
+    let records = read_lines(&path);
+    assert_eq!(summary["last_hash"], records[1]["hash"]);
+    assert_eq!(records[1]["actor"], "alice");
+    assert_eq!(records[1]["before"], "100");
+    assert_eq!(records[1]["after"], "60");
+
+    let mut lines = records.clone();
+    lines[0]["actor"] = serde_json::json!("mallory");
+    write_lines(&path, &lines);
+    assert!(!verify("audit verify").status.success());
+    assert!(!invoke("entity show entity_001").status.success());
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&data_path).unwrap();
+}
@@ -0,0 +1,229 @@
+extern crate banana_coin;
+use banana_coin::chain::*;
+use banana_coin::crypto::KeyPair;
+use banana_coin::error::{ChainError, CryptoError};
+use banana_coin::ledger::ISSUANCE_ACCOUNT_ID;
+use banana_coin::model::*;
+use banana_coin::model::TransactionState::*;
+
+fn key_pair(entity_id: &str) -> KeyPair {
+    KeyPair::from_secret_key(&format!("{:0>64}", &entity_id[entity_id.len() - 3..])).unwrap()
+}
+
+fn signed_transaction(id: &str, from_entity_id: &str, to_entity_id: &str, amount: i64, nonce: u64, state: TransactionState) -> Transaction {
+    let mut transaction = Transaction::new(id.to_string(), from_entity_id.to_string(), to_entity_id.to_string(), Amount::from_integer(amount),
+                                           "".to_string(), state.clone(), vec![Created { timestamp: 1 }, OnProcess { timestamp: 2 }, state]).unwrap();
+    transaction.set_nonce(nonce);
+    transaction.sign(&key_pair(from_entity_id));
+    transaction
+}
+
+fn completed_transaction(id: &str, from_entity_id: &str, to_entity_id: &str, amount: i64, nonce: u64) -> Transaction {
+    signed_transaction(id, from_entity_id, to_entity_id, amount, nonce, Completed { timestamp: 3 })
+}
+
+fn failed_transaction(id: &str, from_entity_id: &str, to_entity_id: &str, amount: i64, nonce: u64) -> Transaction {
+    signed_transaction(id, from_entity_id, to_entity_id, amount, nonce, Failed { timestamp: 3 })
+}
+
+fn new_blockchain() -> Blockchain {
+    let mut rules = ConsensusRules::default();
+    for entity_id in ["entity_001", "entity_002", "entity_003"].iter() {
+        rules.register_public_key(entity_id.to_string(), key_pair(entity_id).get_public_key()).unwrap();
+    }
+    Blockchain::with_rules(rules)
+}
+
+fn coinbase(height: u64, to_entity_id: &str) -> Transaction {
+    create_coinbase_transaction(height, to_entity_id.to_string(), Amount::from_integer(50), 5).unwrap()
+}
+
+fn sample_blockchain() -> Blockchain {
+    let mut blockchain = new_blockchain();
+    let block = blockchain.create_block(vec![
+        coinbase(0, "entity_001"),
+        completed_transaction("tx_0001", "entity_001", "entity_002", 40, 0),
+        completed_transaction("tx_0002", "entity_002", "entity_003", 30, 0),
+    ], 10);
+    blockchain.append_block(block).unwrap();
+    let block = blockchain.create_block(vec![
+        failed_transaction("tx_0003", "entity_003", "entity_001", 5, 0),
+        completed_transaction("tx_0004", "entity_003", "entity_001", 10, 1),
+    ], 20);
+    blockchain.append_block(block).unwrap();
+    blockchain
+}
+
+#[test]
+fn append_successful() {
+    let blockchain = sample_blockchain();
+    assert_eq!(blockchain.get_height(), 2);
+    let blocks = blockchain.get_blocks();
+    assert_eq!(blocks[0].get_header().get_previous_hash(), GENESIS_PREVIOUS_HASH);
+    assert_eq!(blocks[1].get_header().get_previous_hash(), blocks[0].get_hash());
+    assert_eq!(blockchain.get_tip_hash(), blocks[1].get_hash());
+    assert!(blockchain.validate().is_ok());
+}
+
+#[test]
+fn append_unsuccessful_wrong_height() {
+    let mut blockchain = sample_blockchain();
+    let block = Block::new(5, blockchain.get_tip_hash(), 30, 0, Vec::new());
+    assert!(matches!(blockchain.append_block(block), Err(ChainError::InvalidHeightError { expected_height: 2, found_height: 5 })));
+    assert_eq!(blockchain.get_height(), 2);
+}
+
+#[test]
+fn append_unsuccessful_wrong_previous_hash() {
+    let mut blockchain = sample_blockchain();
+    let block = Block::new(2, GENESIS_PREVIOUS_HASH.to_string(), 30, 0, Vec::new());
+    assert!(matches!(blockchain.append_block(block), Err(ChainError::InvalidPreviousHashError { height: 2, .. })));
+}
+
+#[test]
+fn append_unsuccessful_decreasing_timestamp() {
+    let mut blockchain = sample_blockchain();
+    let block = blockchain.create_block(Vec::new(), 5);
+    assert!(matches!(blockchain.append_block(block), Err(ChainError::DecreasingTimestampError { height: 2, previous_timestamp: 20, timestamp: 5 })));
+}
+
+#[test]
+fn balances_replay() {
+    let balances = sample_blockchain().get_balances().unwrap();
+    assert_eq!(balances.get("entity_001"), Some(&Amount::from_integer(25)));
+    assert_eq!(balances.get("entity_002"), Some(&Amount::from_integer(10)));
+    assert_eq!(balances.get("entity_003"), Some(&Amount::from_integer(15)));
+    assert!(balances.values().fold(Amount::zero(), |total, balance| total.saturating_add(*balance)).is_zero());
+}
+
+#[test]
+fn validate_unsuccessful_tampered_transaction() {
+    let json = serde_json::to_string(&sample_blockchain()).unwrap();
+    let tampered_json = json.replace("\"amount\":\"40\"", "\"amount\":\"400\"");
+    assert_ne!(json, tampered_json);
+    let blockchain: Blockchain = serde_json::from_str(&tampered_json).unwrap();
+    assert!(matches!(blockchain.validate(), Err(ChainError::InvalidMerkleRootError { height: 0, .. })));
+    assert!(blockchain.get_balances().is_err());
+}
+
+#[test]
+fn validate_unsuccessful_tampered_header() {
+    let json = serde_json::to_string(&sample_blockchain()).unwrap();
+    let tampered_json = json.replace("\"timestamp\":10", "\"timestamp\":11");
+    let blockchain: Blockchain = serde_json::from_str(&tampered_json).unwrap();
+    assert!(matches!(blockchain.validate(), Err(ChainError::InvalidPreviousHashError { height: 1, .. })));
+}
+
+#[test]
+fn append_unsuccessful_overspend() {
+    let mut blockchain = sample_blockchain();
+    let block = blockchain.create_block(vec![completed_transaction("tx_0005", "entity_002", "entity_001", 11, 1)], 30);
+    match blockchain.append_block(block) {
+        Ok(_) => panic!("expected an error"),
+        Err(ChainError::InsufficientBalanceError { height: 2, transaction_id, entity_id }) => {
+            assert_eq!(transaction_id, "tx_0005");
+            assert_eq!(entity_id, "entity_002");
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(blockchain.get_height(), 2);
+    let balances = blockchain.get_balances().unwrap();
+    assert!(balances.iter().all(|(entity_id, balance)| entity_id == ISSUANCE_ACCOUNT_ID || !balance.is_negative()));
+}
+
+#[test]
+fn append_unsuccessful_duplicate_transaction_id() {
+    let mut blockchain = sample_blockchain();
+    let block = blockchain.create_block(vec![completed_transaction("tx_0001", "entity_001", "entity_002", 1, 1)], 30);
+    match blockchain.append_block(block) {
+        Ok(_) => panic!("expected an error"),
+        Err(ChainError::DuplicateTransactionIdError { height: 2, transaction_id }) => assert_eq!(transaction_id, "tx_0001"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+}
+
+#[test]
+fn append_unsuccessful_replayed_nonce() {
+    let mut blockchain = sample_blockchain();
+    let block = blockchain.create_block(vec![completed_transaction("tx_0005", "entity_001", "entity_002", 1, 0)], 30);
+    assert!(matches!(blockchain.append_block(block), Err(ChainError::InvalidNonceError { height: 2, expected_nonce: 1, found_nonce: 0, .. })));
+}
+
+#[test]
+fn append_unsuccessful_unknown_sender() {
+    let mut blockchain = sample_blockchain();
+    let block = blockchain.create_block(vec![completed_transaction("tx_0005", "entity_004", "entity_001", 1, 0)], 30);
+    match blockchain.append_block(block) {
+        Ok(_) => panic!("expected an error"),
+        Err(ChainError::UnknownSenderError { height: 2, entity_id, .. }) => assert_eq!(entity_id, "entity_004"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+}
+
+#[test]
+fn append_unsuccessful_forged_signature() {
+    let mut blockchain = sample_blockchain();
+    let mut transaction = completed_transaction("tx_0005", "entity_001", "entity_002", 1, 1);
+    transaction.sign(&key_pair("entity_002"));
+    let unsigned = Transaction::new("tx_0006".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(1),
+                                    "".to_string(), Created { timestamp: 1 }, vec![Created { timestamp: 1 }]).unwrap();
+    for (transaction, missing) in [(transaction, false), (unsigned, true)] {
+        let block = blockchain.create_block(vec![transaction], 30);
+        match blockchain.append_block(block) {
+            Ok(_) => panic!("expected an error"),
+            Err(ChainError::InvalidSignatureError { height: 2, error: CryptoError::SignatureMismatchError, .. }) => assert!(!missing),
+            Err(ChainError::InvalidSignatureError { height: 2, error: CryptoError::MissingSignatureError, .. }) => assert!(missing),
+            Err(error) => panic!("unexpected error: {:?}", error)
+        }
+    }
+}
+
+#[test]
+fn register_public_key_unsuccessful_replacement() {
+    let mut rules = new_blockchain().get_rules();
+    assert!(rules.register_public_key("entity_001".to_string(), key_pair("entity_001").get_public_key()).is_ok());
+    match rules.register_public_key("entity_001".to_string(), key_pair("entity_002").get_public_key()) {
+        Ok(_) => panic!("expected an error"),
+        Err(ChainError::PublicKeyReplacementError { entity_id }) => assert_eq!(entity_id, "entity_001"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(rules.get_public_key("entity_001"), Some(key_pair("entity_001").get_public_key()));
+    // The blocks signed with the registered key are still valid under the rules
+    assert!(Blockchain::from_blocks(rules, sample_blockchain().get_blocks()).is_ok());
+}
+
+#[test]
+fn append_ignores_transaction_state() {
+    let mut created = Transaction::new("tx_0005".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(20),
+                                       "".to_string(), Created { timestamp: 1 }, vec![Created { timestamp: 1 }]).unwrap();
+    created.set_nonce(1);
+    created.sign(&key_pair("entity_001"));
+    let mut relayed = created.clone();
+    for state in [OnProcess { timestamp: 2 }, Completed { timestamp: 3 }] {
+        assert!(relayed.transition_to(state).is_ok());
+    }
+    assert!(relayed.verify_signature(&key_pair("entity_001").get_public_key()).is_ok());
+    let mut balances = Vec::new();
+    for transaction in [created, relayed] {
+        let mut blockchain = sample_blockchain();
+        let block = blockchain.create_block(vec![transaction], 30);
+        blockchain.append_block(block).unwrap();
+        balances.push(blockchain.get_balances().unwrap());
+    }
+    assert_eq!(balances[0], balances[1]);
+    assert_eq!(balances[0].get("entity_001"), Some(&Amount::from_integer(5)));
+    assert_eq!(balances[0].get("entity_002"), Some(&Amount::from_integer(30)));
+}
+
+#[test]
+fn append_after_deserialization() {
+    let json = serde_json::to_string(&sample_blockchain()).unwrap();
+    let mut blockchain: Blockchain = serde_json::from_str(&json).unwrap();
+    let block = blockchain.create_block(vec![completed_transaction("tx_0005", "entity_002", "entity_001", 10, 1)], 30);
+    blockchain.append_block(block).unwrap();
+    let balances = blockchain.get_balances().unwrap();
+    assert_eq!(balances.get("entity_001"), Some(&Amount::from_integer(35)));
+    assert_eq!(balances.get("entity_002"), Some(&Amount::zero()));
+    assert!(blockchain.check_transactions(&[completed_transaction("tx_0006", "entity_002", "entity_001", 1, 2)]).is_err());
+    assert!(blockchain.check_transactions(&[completed_transaction("tx_0006", "entity_001", "entity_002", 1, 1)]).is_ok());
+}
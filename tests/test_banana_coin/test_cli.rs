@@ -0,0 +1,232 @@
+extern crate banana_coin;
+use std::process::Command as Process;
+
+use banana_coin::audit::AuditLog;
+use banana_coin::cli::*;
+use banana_coin::crypto::KeyPair;
+use banana_coin::error::{CliError, TransferError};
+use banana_coin::ledger::{ESCROW_ACCOUNT_ID, ISSUANCE_ACCOUNT_ID};
+use banana_coin::model::{Amount, OverdraftPolicy, Transaction};
+use banana_coin::model::TransactionState::*;
+use banana_coin::storage::{MemoryStorage, Storage};
+
+const SECRET_KEY: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+const PUBLIC_KEY: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+
+fn arguments(line: &str) -> Vec<String> {
+    line.split_whitespace().map(String::from).collect()
+}
+
+fn run(storage: &mut MemoryStorage, line: &str) -> Result<CommandOutput, CliError> {
+    let invocation = Invocation::parse(&arguments(line)).unwrap();
+    run_command(storage, &invocation.command, 1)
+}
+
+#[test]
+fn parse_successful() {
+    let invocation = Invocation::parse(&arguments(
+        &format!("--data-file data.jsonl transfer entity_001 entity_002 30 --id id_0001 --secret-key {}", SECRET_KEY))).unwrap();
+    assert_eq!(invocation.data_file, std::path::PathBuf::from("data.jsonl"));
+    assert_eq!(invocation.audit_log, std::path::PathBuf::from("data.audit.jsonl"));
+    assert!(!invocation.json);
+    assert_eq!(invocation.command, Command::Transfer {
+        from_entity_id: "entity_001".to_string(),
+        to_entity_id: "entity_002".to_string(),
+        amount: Amount::from_integer(30),
+        transaction_id: Some("id_0001".to_string()),
+        description: None,
+        nonce: None,
+        signature: TransferSignature::SecretKey(SECRET_KEY.to_string()),
+    });
+    assert_eq!(Invocation::parse(&arguments("tx history")).unwrap().command,
+               Command::TransactionHistory { entity_id: None });
+    assert_eq!(Invocation::parse(&arguments("entity list")).unwrap().data_file,
+               std::path::PathBuf::from(DEFAULT_DATA_FILE));
+    assert_eq!(Invocation::parse(&arguments("--audit-log audit.jsonl entity list")).unwrap().audit_log,
+               std::path::PathBuf::from("audit.jsonl"));
+}
+
+#[test]
+fn parse_unsuccessful() {
+    match Invocation::parse(&arguments("wallet deposit entity_001 ten")) {
+        Ok(_) => panic!("expected an error"),
+        Err(CliError::UsageError { message }) => assert!(message.starts_with("Invalid number ten")),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(matches!(Invocation::parse(&arguments("entity remove entity_001")), Err(CliError::UsageError { .. })));
+    match Invocation::parse(&arguments("entity list --data-file")) {
+        Ok(_) => panic!("expected an error"),
+        Err(CliError::UsageError { message }) => assert!(message.starts_with("Missing value for --data-file")),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    match Invocation::parse(&arguments("transfer entity_001 entity_002 30")) {
+        Ok(_) => panic!("expected an error"),
+        Err(CliError::UsageError { message }) => assert!(message.starts_with("Missing --secret-key or --signature")),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+}
+
+#[test]
+fn run_wallet_commands() {
+    let mut storage = MemoryStorage::new();
+    assert!(run(&mut storage, "entity create entity_001 john --balance 100").is_ok());
+    assert!(run(&mut storage, "wallet deposit entity_001 50").is_ok());
+    assert!(run(&mut storage, "wallet withdraw entity_001 20").is_ok());
+    assert!(matches!(run(&mut storage, "wallet withdraw entity_001 200"), Err(CliError::EntityOperationError { .. })));
+    match run(&mut storage, "wallet balance entity_001") {
+        Ok(CommandOutput::Balance { balance, .. }) => assert_eq!(balance, Amount::from_integer(130)),
+        _ => panic!("unexpected result")
+    }
+    let states: Vec<String> = storage.list_transactions().unwrap().iter()
+        .map(|transaction| transaction.get_current_state().get_name().to_string())
+        .collect();
+    assert_eq!(states, vec!["Completed", "Completed", "Failed"]);
+    let mut ids: Vec<String> = storage.list_transactions().unwrap().iter().map(Transaction::get_id).collect();
+    assert!(ids.iter().all(|id| id.starts_with("tx_") && id.len() == 35));
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 3);
+}
+
+#[test]
+fn run_wallet_overdraft_command() {
+    let mut storage = MemoryStorage::new();
+    assert!(run(&mut storage, "entity create entity_001 john --balance 10").is_ok());
+    match Invocation::parse(&arguments("wallet overdraft entity_001 unlimited")) {
+        Ok(_) => panic!("expected an error"),
+        Err(CliError::UsageError { message }) => assert!(message.starts_with("Missing --changed-by")),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(run(&mut storage, "wallet overdraft entity_001 -50 --changed-by admin").is_ok());
+    assert!(run(&mut storage, "wallet withdraw entity_001 60").is_ok());
+    assert!(matches!(run(&mut storage, "wallet withdraw entity_001 1"), Err(CliError::EntityOperationError { .. })));
+    let wallet = storage.load_entity("entity_001").unwrap().unwrap().get_wallet();
+    assert_eq!(wallet.get_balance(), Amount::from_integer(-50));
+    assert_eq!(wallet.get_overdraft_policy(), OverdraftPolicy::Limit { floor: Amount::from_integer(-50) });
+    assert_eq!(wallet.get_overdraft_policy_changes()[0].get_changed_by(), "admin");
+}
+
+#[test]
+fn run_transfer_commands() {
+    let mut storage = MemoryStorage::new();
+    assert!(run(&mut storage, &format!("entity create entity_001 john --balance 100 --public-key {}", PUBLIC_KEY)).is_ok());
+    assert!(run(&mut storage, "entity create entity_002 jane").is_ok());
+    assert!(matches!(run(&mut storage, "entity create entity_002 jack"), Err(CliError::DuplicateEntityError { .. })));
+    for reserved_id in [ISSUANCE_ACCOUNT_ID, ESCROW_ACCOUNT_ID].iter() {
+        match run(&mut storage, &format!("entity create {} jack", reserved_id)) {
+            Ok(_) => panic!("expected an error"),
+            Err(CliError::ReservedEntityIdError { entity_id }) => assert_eq!(entity_id, *reserved_id),
+            Err(error) => panic!("unexpected error: {:?}", error)
+        }
+        assert!(storage.load_entity(reserved_id).unwrap().is_none());
+    }
+    match run(&mut storage, &format!("transfer entity_001 entity_002 30 --id id_0001 --secret-key {}", SECRET_KEY)) {
+        Ok(CommandOutput::Transaction(transaction)) =>
+            assert_eq!(transaction.get_current_state(), Completed { timestamp: 1 }),
+        _ => panic!("unexpected result")
+    }
+    let key_pair = KeyPair::generate();
+    assert!(run(&mut storage, &format!("entity key entity_002 {}", key_pair.get_public_key())).is_ok());
+    match run(&mut storage, &format!("transfer entity_002 entity_001 300 --secret-key {}", key_pair.get_secret_key())) {
+        Ok(_) => panic!("expected an error"),
+        Err(CliError::TransferOperationError { error }) => assert!(matches!(*error, TransferError::EntityOperationError { .. })),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(matches!(run(&mut storage, &format!("transfer entity_001 entity_002 10 --id id_0001 --secret-key {}", SECRET_KEY)), Err(CliError::StorageOperationError { .. })));
+    match run(&mut storage, "entity list") {
+        Ok(output) => assert_eq!(output.render(false),
+                                 "ID          NAME  BALANCE  OVERDRAFT\n\
+                                  entity_001  john  70       no overdraft\n\
+                                  entity_002  jane  30       no overdraft"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    match run(&mut storage, "tx history entity_002") {
+        Ok(CommandOutput::Transactions(transactions)) => assert_eq!(transactions.len(), 2),
+        _ => panic!("unexpected result")
+    }
+    assert!(matches!(run(&mut storage, "tx show id_0002"), Err(CliError::TransactionNotFoundError { .. })));
+}
+
+#[test]
+fn run_transfer_commands_unsuccessful_signature() {
+    let mut storage = MemoryStorage::new();
+    assert!(run(&mut storage, "entity create entity_001 john --balance 100").is_ok());
+    assert!(run(&mut storage, "entity create entity_002 jane").is_ok());
+    // The sender has no public key to check the signature against
+    match run(&mut storage, &format!("transfer entity_001 entity_002 30 --secret-key {}", SECRET_KEY)) {
+        Ok(_) => panic!("expected an error"),
+        Err(CliError::TransferOperationError { error }) => assert!(matches!(*error, TransferError::MissingPublicKeyError { .. })),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(run(&mut storage, &format!("entity key entity_001 {}", PUBLIC_KEY)).is_ok());
+    match run(&mut storage, &format!("entity key entity_001 {}", KeyPair::generate().get_public_key())) {
+        Ok(_) => panic!("expected an error"),
+        Err(CliError::PublicKeyReplacementError { entity_id }) => assert_eq!(entity_id, "entity_001"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    match run(&mut storage, &format!("transfer entity_001 entity_002 30 --secret-key {}", KeyPair::generate().get_secret_key())) {
+        Ok(_) => panic!("expected an error"),
+        Err(CliError::TransferOperationError { error }) => assert!(matches!(*error, TransferError::SignatureVerificationError { .. })),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(matches!(run(&mut storage, &format!("transfer entity_001 entity_002 30 --signature {}", "00".repeat(64))), Err(CliError::UsageError { .. })));
+    // A refused transfer is not recorded and leaves the wallets untouched
+    assert!(storage.list_transactions().unwrap().is_empty());
+    assert_eq!(storage.load_entity("entity_001").unwrap().unwrap().get_wallet().get_balance(), Amount::from_integer(100));
+
+    // A transaction signed elsewhere is run once its signature is given along with its id
+    let mut transaction = Transaction::new(
+        "id_0001".to_string(),
+        "entity_001".to_string(),
+        "entity_002".to_string(),
+        Amount::from_integer(30),
+        "rent".to_string(),
+        Created { timestamp: 1 },
+        vec![Created { timestamp: 1 }],
+    ).unwrap();
+    transaction.sign(&KeyPair::from_secret_key(SECRET_KEY).unwrap());
+    let line = format!("transfer entity_001 entity_002 30 --id id_0001 --description rent --nonce 0 --signature {}", transaction.get_signature().unwrap());
+    match run(&mut storage, &line) {
+        Ok(CommandOutput::Transaction(transaction)) => assert_eq!(transaction.get_current_state(), Completed { timestamp: 1 }),
+        _ => panic!("unexpected result")
+    }
+    assert_eq!(storage.load_entity("entity_002").unwrap().unwrap().get_wallet().get_balance(), Amount::from_integer(30));
+}
+
+#[test]
+fn binary_persists_data_file() {
+    let path = std::env::temp_dir().join(format!("banana_coin_{}_cli.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(default_audit_log(&path));
+    let binary = env!("CARGO_BIN_EXE_banana_coin");
+    let invoke = |line: &str| {
+        Process::new(binary)
+            .arg("--data-file")
+            .arg(&path)
+            .args(arguments(line))
+            .output()
+            .unwrap()
+    };
+    let output = invoke("--json key generate");
+    assert!(output.status.success());
+    let key_pair: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(invoke(&format!("entity create entity_001 john --balance 100 --public-key {}", key_pair["public_key"].as_str().unwrap())).status.success());
+    assert!(invoke("entity create entity_002 jane").status.success());
+    assert!(invoke(&format!("transfer entity_001 entity_002 40 --secret-key {}", key_pair["secret_key"].as_str().unwrap())).status.success());
+    assert!(!invoke(&format!("transfer entity_001 entity_002 400 --secret-key {}", key_pair["secret_key"].as_str().unwrap())).status.success());
+
+    let output = invoke("--json wallet balance entity_002");
+    assert!(output.status.success());
+    let balance: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(balance["balance"], "40");
+
+    let output = invoke("--json tx history");
+    let history: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(history.as_array().unwrap().len(), 2);
+
+    // The changes are audited by default, in the audit log next to the data file
+    let audit_log = default_audit_log(&path);
+    assert_eq!(AuditLog::verify(&audit_log).unwrap().unwrap().get_sequence(), 5);
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&audit_log).unwrap();
+}
@@ -0,0 +1,121 @@
+extern crate banana_coin;
+use banana_coin::crypto::*;
+use banana_coin::error::{CryptoError, TransferError};
+use banana_coin::model::*;
+use banana_coin::model::TransactionState::*;
+use banana_coin::transfer::TransferExecutor;
+
+// RFC 8032, section 7.1, test 1
+const SECRET_KEY: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+const PUBLIC_KEY: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+const EMPTY_MESSAGE_SIGNATURE: &str = "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b";
+
+// Transaction built by `new_transaction` and signed with SECRET_KEY
+const SIGNING_BYTES: &str = "62616e616e615f636f696e2f7472616e73616374696f6e2f76350000000769645f303030310000000a656e746974795f3030310000000a656e746974795f303032000000114d6f76696e672031303020636f696e732e00000003424e430000000331303000000001300000000000000000";
+const TRANSACTION_SIGNATURE: &str = "7013a4ea733fb988b813e130fe4d2c99617736a279b221e7ffe74e7b2b524e061661622e1e11172a6172f6c84331889ce9c3785a467c876024b56a408fb59b04";
+
+fn new_transaction() -> Transaction {
+    Transaction::new(
+        "id_0001".to_string(),
+        "entity_001".to_string(),
+        "entity_002".to_string(),
+        Amount::from_integer(100),
+        "Moving 100 coins.".to_string(),
+        Created { timestamp: 1 },
+        vec![Created { timestamp: 1 }],
+    ).unwrap()
+}
+
+fn new_entities() -> (Entity, Entity) {
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    from_entity.set_public_key(PUBLIC_KEY.to_string());
+    let to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    (from_entity, to_entity)
+}
+
+#[test]
+fn key_pair_test_vector() {
+    let key_pair = KeyPair::from_secret_key(SECRET_KEY).unwrap();
+    assert_eq!(key_pair.get_public_key(), PUBLIC_KEY);
+    assert_eq!(key_pair.get_secret_key(), SECRET_KEY);
+    assert_eq!(key_pair.sign(b""), EMPTY_MESSAGE_SIGNATURE);
+    assert!(verify_signature(PUBLIC_KEY, b"", EMPTY_MESSAGE_SIGNATURE).is_ok());
+}
+
+#[test]
+fn key_pair_unsuccessful_invalid_secret_key() {
+    assert!(matches!(KeyPair::from_secret_key("9d61b1"), Err(CryptoError::InvalidKeyError { .. })));
+    assert!(matches!(KeyPair::from_secret_key("not hex"), Err(CryptoError::InvalidKeyError { .. })));
+}
+
+#[test]
+fn transaction_signature_test_vector() {
+    let mut transaction = new_transaction();
+    assert_eq!(hex::encode(transaction.get_signing_bytes()), SIGNING_BYTES);
+    transaction.sign(&KeyPair::from_secret_key(SECRET_KEY).unwrap());
+    assert_eq!(transaction.get_signature(), Some(TRANSACTION_SIGNATURE.to_string()));
+    assert!(transaction.verify_signature(PUBLIC_KEY).is_ok());
+}
+
+#[test]
+fn transaction_signature_survives_state_changes_and_serialization() {
+    let mut transaction = new_transaction();
+    transaction.sign(&KeyPair::from_secret_key(SECRET_KEY).unwrap());
+    assert!(transaction.transition_to(OnProcess { timestamp: 2 }).is_ok());
+    let json = serde_json::to_string(&transaction).unwrap();
+    let deserialized: Transaction = serde_json::from_str(&json).unwrap();
+    assert!(deserialized.verify_signature(PUBLIC_KEY).is_ok());
+}
+
+#[test]
+fn transaction_signature_unsuccessful_tampered_amount() {
+    let mut transaction = new_transaction();
+    transaction.sign(&KeyPair::from_secret_key(SECRET_KEY).unwrap());
+    let json = serde_json::to_string(&transaction).unwrap().replace("\"amount\":\"100\"", "\"amount\":\"900\"");
+    let tampered: Transaction = serde_json::from_str(&json).unwrap();
+    assert!(matches!(tampered.verify_signature(PUBLIC_KEY), Err(CryptoError::SignatureMismatchError)));
+}
+
+#[test]
+fn execute_signed_transaction_successful() {
+    let (mut from_entity, mut to_entity) = new_entities();
+    let mut transaction = new_transaction();
+    transaction.sign(&KeyPair::from_secret_key(SECRET_KEY).unwrap());
+    let executor = TransferExecutor::new().with_required_signature(true);
+    assert!(executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 2).is_ok());
+    assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(100));
+}
+
+#[test]
+fn execute_unsuccessful_missing_signature() {
+    let (mut from_entity, mut to_entity) = new_entities();
+    let mut transaction = new_transaction();
+    let executor = TransferExecutor::new();
+    assert!(matches!(executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 2), Err(TransferError::SignatureVerificationError { error: CryptoError::MissingSignatureError, .. })));
+    assert_eq!(transaction.get_current_state(), Created { timestamp: 1 });
+    assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(100));
+}
+
+#[test]
+fn execute_unsuccessful_signed_by_another_entity() {
+    let (mut from_entity, mut to_entity) = new_entities();
+    let mut transaction = new_transaction();
+    transaction.sign(&to_entity.generate_key_pair());
+    let executor = TransferExecutor::new();
+    assert!(matches!(executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 2), Err(TransferError::SignatureVerificationError { error: CryptoError::SignatureMismatchError, .. })));
+    assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(100));
+    assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(0));
+}
+
+#[test]
+fn execute_unsuccessful_missing_public_key() {
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    let mut transaction = new_transaction();
+    let executor = TransferExecutor::new().with_required_signature(true);
+    match executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 2) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransferError::MissingPublicKeyError { entity_id }) => assert_eq!(entity_id, "entity_001"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+}
@@ -1,2 +1,47 @@
 extern crate banana_coin;
 use banana_coin::model::*;
+
+#[test]
+fn new_entity() {
+    let entity = Entity::new(
+        "id_0001".to_string(),
+        "john".to_string(),
+        Wallet::new(
+            Amount::from_integer(10)
+        )
+    );
+    assert_eq!(entity.get_id(), "id_0001");
+    assert_eq!(entity.get_name(), "john");
+    assert_eq!(entity.get_wallet().get_balance(), Amount::from_integer(10));
+}
+
+#[test]
+fn deserialize_from_dropped_buffer() {
+    let entity: Entity = {
+        let json = String::from(r#"{"id":"id_0001","name":"john","wallet":{"balance":10}}"#);
+        serde_json::from_str(&json).unwrap()
+    };
+    assert_eq!(entity.get_id(), "id_0001");
+    assert_eq!(entity.get_name(), "john");
+    assert_eq!(entity.get_wallet().get_balance(), Amount::from_integer(10));
+}
+
+#[test]
+fn serialization_round_trip() {
+    let mut entity = Entity::new("id_0001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(0)));
+    assert!(entity.add_coins(Amount::from_integer(100)).is_ok());
+    let json = serde_json::to_string(&entity).unwrap();
+    let deserialized: Entity = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized.get_id(), entity.get_id());
+    assert_eq!(deserialized.get_name(), entity.get_name());
+    assert_eq!(deserialized.get_wallet().get_balance(), Amount::from_integer(100));
+}
+
+#[test]
+fn send_across_threads() {
+    let entity = Entity::new("id_0001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(10)));
+    let handle = std::thread::spawn(move || {
+        entity.get_wallet().get_balance()
+    });
+    assert_eq!(handle.join().unwrap(), Amount::from_integer(10));
+}
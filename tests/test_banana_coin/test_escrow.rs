@@ -0,0 +1,387 @@
+extern crate banana_coin;
+use banana_coin::crypto::KeyPair;
+use banana_coin::model::*;
+use banana_coin::model::TransactionState::*;
+use banana_coin::error::{EntityError, EscrowError, LedgerError, TransferError, WalletError};
+use banana_coin::ledger::{ESCROW_ACCOUNT_ID, Ledger};
+use banana_coin::storage::{MemoryStorage, Storage};
+use banana_coin::transfer::TransferExecutor;
+
+fn key_pair(entity_id: &str) -> KeyPair {
+    let seed = entity_id.bytes().last().unwrap();
+    KeyPair::from_secret_key(&format!("{:02x}", seed).repeat(32)).unwrap()
+}
+
+fn new_transaction(id: &str, amount: i64) -> Transaction {
+    let mut transaction = Transaction::new(
+        id.to_string(),
+        "entity_001".to_string(),
+        "entity_002".to_string(),
+        Amount::from_integer(amount),
+        format!("Moving {} coins.", amount),
+        Created { timestamp: 1 },
+        vec![Created { timestamp: 1 }],
+    ).unwrap();
+    transaction.sign(&key_pair("entity_001"));
+    transaction
+}
+
+fn new_entity(id: &str, name: &str, balance: i64) -> Entity {
+    let mut entity = Entity::new(id.to_string(), name.to_string(), Wallet::new(Amount::from_integer(balance)));
+    entity.set_public_key(key_pair(id).get_public_key());
+    entity
+}
+
+fn new_entities() -> (Entity, Entity) {
+    (new_entity("entity_001", "john", 100), new_entity("entity_002", "jane", 0))
+}
+
+fn new_arbiter() -> Entity {
+    new_entity("entity_003", "arbiter", 0)
+}
+
+fn approve(escrow: &mut Escrow, entity: &Entity, timestamp: u64) -> Result<(), EscrowError> {
+    let signature = escrow.sign_approval(entity.get_id(), &key_pair(entity.get_id()));
+    escrow.approve(entity, &signature, timestamp)
+}
+
+fn approval_signature(ledger: &Ledger, transaction_id: &str, entity_id: &str) -> String {
+    ledger.get_escrow(transaction_id).unwrap().sign_approval(entity_id, &key_pair(entity_id))
+}
+
+fn new_escrow_account() -> Entity {
+    Entity::new(ESCROW_ACCOUNT_ID.to_string(), "escrow".to_string(), Wallet::new(Amount::from_integer(0)))
+}
+
+fn new_ledger() -> Ledger {
+    let mut ledger = Ledger::new(TransferExecutor::new());
+    let (from_entity, to_entity) = new_entities();
+    ledger.register_entity(from_entity).unwrap();
+    ledger.register_entity(to_entity).unwrap();
+    ledger.register_entity(new_arbiter()).unwrap();
+    ledger
+}
+
+fn arbiter_condition() -> ReleaseCondition {
+    ReleaseCondition::Approval { approver_id: "entity_003".to_string() }
+}
+
+#[test]
+fn execute_escrow_successful() {
+    let (mut from_entity, mut to_entity) = new_entities();
+    let mut transaction = new_transaction("id_0001", 60);
+    let mut escrow_account = new_escrow_account();
+    let executor = TransferExecutor::new();
+    let mut escrow = executor.execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, arbiter_condition(), 2).unwrap();
+    assert_eq!(transaction.get_current_state(), Blocked { timestamp: 2 });
+    assert_eq!(transaction.get_state_history(), vec![Created { timestamp: 1 }, OnProcess { timestamp: 2 }, Blocked { timestamp: 2 }]);
+    assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(40));
+    assert_eq!(from_entity.get_nonce(), 1);
+    assert!(!escrow.is_settled());
+    // The coins stay in the escrow until the approver releases them
+    match executor.release_escrow(&mut escrow, &mut escrow_account, &mut to_entity, 3) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransferError::EscrowOperationError { error: EscrowError::ReleaseConditionNotMetError { condition, .. }, .. }) =>
+            assert_eq!(condition, arbiter_condition()),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(approve(&mut escrow, &new_arbiter(), 4).is_ok());
+    assert!(executor.release_escrow(&mut escrow, &mut escrow_account, &mut to_entity, 5).is_ok());
+    assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(60));
+    assert_eq!(escrow.get_transaction().get_current_state(), Completed { timestamp: 5 });
+    assert!(escrow.is_settled());
+    assert!(matches!(executor.release_escrow(&mut escrow, &mut escrow_account, &mut to_entity, 6), Err(TransferError::EscrowOperationError { error: EscrowError::SettledEscrowError { .. }, .. })));
+    assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(60));
+}
+
+#[test]
+fn execute_escrow_unsuccessful_negative_balance() {
+    let (mut from_entity, _) = new_entities();
+    let mut transaction = new_transaction("id_0001", 101);
+    let mut escrow_account = new_escrow_account();
+    let executor = TransferExecutor::new();
+    match executor.execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, arbiter_condition(), 2) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransferError::EntityOperationError { error: EntityError::WalletOperationError { error }, .. }) =>
+            assert!(matches!(*error, WalletError::RemoveCoinNegativeBalanceError { .. })),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(transaction.get_current_state(), Failed { timestamp: 2 });
+    assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(100));
+    assert_eq!(from_entity.get_nonce(), 1);
+}
+
+#[test]
+fn execute_escrow_unsuccessful_with_fee() {
+    let (mut from_entity, _) = new_entities();
+    let mut transaction = new_transaction("id_0001", 50);
+    transaction.set_fee(Amount::from_integer(1)).unwrap();
+    transaction.sign(&key_pair("entity_001"));
+    let mut escrow_account = new_escrow_account();
+    let executor = TransferExecutor::new();
+    assert!(matches!(executor.execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, arbiter_condition(), 2), Err(TransferError::MissingFeeCollectorError { .. })));
+    assert_eq!(transaction.get_current_state(), Created { timestamp: 1 });
+    assert_eq!(from_entity.get_nonce(), 0);
+}
+
+#[test]
+fn escrow_released_at_deadline() {
+    let (mut from_entity, mut to_entity) = new_entities();
+    let mut transaction = new_transaction("id_0001", 60);
+    let mut escrow_account = new_escrow_account();
+    let executor = TransferExecutor::new();
+    let condition = ReleaseCondition::Deadline { deadline: 100 };
+    let mut escrow = executor.execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, condition, 2).unwrap();
+    match approve(&mut escrow, &to_entity, 3) {
+        Ok(_) => panic!("expected an error"),
+        Err(EscrowError::UnauthorizedApprovalError { entity_id, .. }) => assert_eq!(entity_id, "entity_002"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(executor.release_escrow(&mut escrow, &mut escrow_account, &mut to_entity, 99).is_err());
+    assert!(executor.release_escrow(&mut escrow, &mut escrow_account, &mut to_entity, 100).is_ok());
+    assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(60));
+}
+
+#[test]
+fn escrow_released_by_both_parties() {
+    let (mut from_entity, mut to_entity) = new_entities();
+    let mut transaction = new_transaction("id_0001", 60);
+    let mut escrow_account = new_escrow_account();
+    let executor = TransferExecutor::new();
+    let mut escrow = executor.execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, ReleaseCondition::BothParties, 2).unwrap();
+    assert!(approve(&mut escrow, &new_arbiter(), 3).is_err());
+    assert!(approve(&mut escrow, &to_entity, 3).is_ok());
+    assert!(matches!(approve(&mut escrow, &to_entity, 4), Err(EscrowError::DuplicateApprovalError { .. })));
+    assert!(!escrow.is_releasable(4));
+    assert!(executor.release_escrow(&mut escrow, &mut escrow_account, &mut to_entity, 4).is_err());
+    assert!(approve(&mut escrow, &from_entity, 5).is_ok());
+    assert_eq!(escrow.get_approvals().len(), 2);
+    // The coins can only be released to the receiver of the transaction
+    match executor.release_escrow(&mut escrow, &mut escrow_account, &mut from_entity, 6) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransferError::EntityMismatchError { expected_entity_id, .. }) => assert_eq!(expected_entity_id, "entity_002"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(executor.release_escrow(&mut escrow, &mut escrow_account, &mut to_entity, 6).is_ok());
+    assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(60));
+    assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(40));
+}
+
+#[test]
+fn escrow_approval_unsuccessful_signature() {
+    let (mut from_entity, _) = new_entities();
+    let mut transaction = new_transaction("id_0001", 60);
+    let mut escrow_account = new_escrow_account();
+    let mut escrow = TransferExecutor::new().execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, arbiter_condition(), 2).unwrap();
+    // The approval must be signed with the registered key of the approver, for this escrow and this approver
+    let arbiter = new_arbiter();
+    let unsigned_arbiter = Entity::new("entity_003".to_string(), "arbiter".to_string(), Wallet::new(Amount::from_integer(0)));
+    let signature = escrow.sign_approval("entity_003", &key_pair("entity_003"));
+    match escrow.approve(&unsigned_arbiter, &signature, 3) {
+        Ok(_) => panic!("expected an error"),
+        Err(EscrowError::MissingPublicKeyError { entity_id }) => assert_eq!(entity_id, "entity_003"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    let forged_signature = escrow.sign_approval("entity_003", &KeyPair::generate());
+    match escrow.approve(&arbiter, &forged_signature, 3) {
+        Ok(_) => panic!("expected an error"),
+        Err(EscrowError::SignatureVerificationError { entity_id, .. }) => assert_eq!(entity_id, "entity_003"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    let mut other_transaction = new_transaction("id_0002", 10);
+    other_transaction.set_nonce(1);
+    other_transaction.sign(&key_pair("entity_001"));
+    let other_escrow = TransferExecutor::new().execute_escrow(&mut other_transaction, &mut from_entity, &mut escrow_account, arbiter_condition(), 2).unwrap();
+    let other_signature = other_escrow.sign_approval("entity_003", &key_pair("entity_003"));
+    assert!(matches!(escrow.approve(&arbiter, &other_signature, 3), Err(EscrowError::SignatureVerificationError { .. })));
+    assert!(escrow.get_approvals().is_empty());
+    assert!(escrow.approve(&arbiter, &signature, 3).is_ok());
+    assert!(escrow.is_releasable(3));
+
+    let mut ledger = new_ledger();
+    assert!(ledger.open_escrow(new_transaction("id_0001", 60), arbiter_condition(), 2).is_ok());
+    assert!(matches!(ledger.approve_escrow("id_0001", "entity_003", &forged_signature, 3),
+                     Err(LedgerError::EscrowOperationError { error: EscrowError::SignatureVerificationError { .. } })));
+    assert!(matches!(ledger.approve_escrow("id_0001", "entity_004", &signature, 3), Err(LedgerError::EntityNotFoundError { .. })));
+    assert!(ledger.release_escrow("id_0001", 4).is_err());
+}
+
+#[test]
+fn escrow_refunded() {
+    let (mut from_entity, mut to_entity) = new_entities();
+    let mut transaction = new_transaction("id_0001", 60);
+    let mut escrow_account = new_escrow_account();
+    let executor = TransferExecutor::new();
+    let mut escrow = executor.execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, arbiter_condition(), 2).unwrap();
+    // The sender can not take the coins back on its own
+    match executor.refund_escrow(&mut escrow, &mut escrow_account, &mut from_entity, "entity_001", 3) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransferError::EscrowOperationError { error: EscrowError::UnauthorizedRefundError { entity_id, .. }, .. }) =>
+            assert_eq!(entity_id, "entity_001"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(executor.refund_escrow(&mut escrow, &mut escrow_account, &mut from_entity, "entity_003", 3).is_ok());
+    assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(100));
+    assert_eq!(escrow.get_transaction().get_current_state(), Failed { timestamp: 3 });
+    assert!(approve(&mut escrow, &new_arbiter(), 4).is_err());
+    assert!(executor.release_escrow(&mut escrow, &mut escrow_account, &mut to_entity, 4).is_err());
+    assert!(to_entity.get_wallet().get_balance().is_zero());
+}
+
+#[test]
+fn escrow_serialization() {
+    let (mut from_entity, _) = new_entities();
+    let mut transaction = new_transaction("id_0001", 60);
+    let mut escrow_account = new_escrow_account();
+    let executor = TransferExecutor::new();
+    let mut escrow = executor.execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, ReleaseCondition::BothParties, 2).unwrap();
+    approve(&mut escrow, &from_entity, 3).unwrap();
+    let json = serde_json::to_value(&escrow).unwrap();
+    assert_eq!(json["condition"], "BothParties");
+    assert_eq!(json["approvals"]["entity_001"], 3);
+    assert_eq!(json["transaction"]["id"], "id_0001");
+    let deserialized: Escrow = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(deserialized.get_approvals(), escrow.get_approvals());
+    assert_eq!(deserialized.get_transaction().get_current_state(), Blocked { timestamp: 2 });
+}
+
+#[test]
+fn escrow_deserialization_unsuccessful() {
+    let (mut from_entity, mut to_entity) = new_entities();
+    let mut transaction = new_transaction("id_0001", 60);
+    let mut escrow_account = new_escrow_account();
+    let executor = TransferExecutor::new();
+    let mut escrow = executor.execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, ReleaseCondition::BothParties, 2).unwrap();
+    let json = serde_json::to_value(&escrow).unwrap();
+    // Only the parties can approve an escrow released by both parties
+    let mut unauthorized = json.clone();
+    unauthorized["approvals"] = serde_json::json!({ "entity_003": 3 });
+    assert!(serde_json::from_value::<Escrow>(unauthorized).is_err());
+    // The transaction must be valid
+    let mut invalid = json;
+    invalid["transaction"]["state_history"] = serde_json::json!([{ "Created": { "timestamp": 1 } }]);
+    assert!(serde_json::from_value::<Escrow>(invalid).is_err());
+    // A settled escrow is not open any more
+    approve(&mut escrow, &from_entity, 3).unwrap();
+    approve(&mut escrow, &to_entity, 3).unwrap();
+    executor.release_escrow(&mut escrow, &mut escrow_account, &mut to_entity, 4).unwrap();
+    assert!(serde_json::from_value::<Escrow>(serde_json::to_value(&escrow).unwrap()).is_err());
+}
+
+#[test]
+fn escrow_coins_held_in_escrow_account() {
+    let (mut from_entity, mut to_entity) = new_entities();
+    let mut transaction = new_transaction("id_0001", 60);
+    let mut escrow_account = new_escrow_account();
+    let executor = TransferExecutor::new();
+    let mut escrow = executor.execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, ReleaseCondition::Deadline { deadline: 10 }, 2).unwrap();
+    assert_eq!(escrow_account.get_wallet().get_balance(), Amount::from_integer(60));
+    // The coins can only be released from an escrow account holding them
+    let mut empty_escrow_account = new_escrow_account();
+    match executor.release_escrow(&mut escrow, &mut empty_escrow_account, &mut to_entity, 10) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransferError::EntityOperationError { entity_id, error: EntityError::WalletOperationError { error } }) => {
+            assert_eq!(entity_id, ESCROW_ACCOUNT_ID);
+            assert!(matches!(*error, WalletError::RemoveCoinNegativeBalanceError { .. }));
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    let mut other_entity = Entity::new("entity_003".to_string(), "arbiter".to_string(), Wallet::new(Amount::from_integer(100)));
+    match executor.release_escrow(&mut escrow, &mut other_entity, &mut to_entity, 10) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransferError::EntityMismatchError { expected_entity_id, .. }) => assert_eq!(expected_entity_id, ESCROW_ACCOUNT_ID),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(!escrow.is_settled());
+    assert!(to_entity.get_wallet().get_balance().is_zero());
+    assert_eq!(other_entity.get_wallet().get_balance(), Amount::from_integer(100));
+
+    assert!(executor.release_escrow(&mut escrow, &mut escrow_account, &mut to_entity, 10).is_ok());
+    assert!(escrow_account.get_wallet().get_balance().is_zero());
+    assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(60));
+}
+
+#[test]
+fn ledger_escrow_released() {
+    let mut ledger = new_ledger();
+    assert!(ledger.open_escrow(new_transaction("id_0001", 60), arbiter_condition(), 2).is_ok());
+    assert_eq!(ledger.get_derived_balance(ESCROW_ACCOUNT_ID).unwrap(), Amount::from_integer(60));
+    assert_eq!(ledger.get_escrow_account().get_wallet().get_balance(), Amount::from_integer(60));
+    assert!(ledger.reconcile().is_ok());
+    assert!(ledger.get_transaction("id_0001").is_none());
+    assert!(matches!(ledger.open_escrow(new_transaction("id_0001", 10), arbiter_condition(), 2), Err(LedgerError::DuplicateTransactionError { .. })));
+    assert!(ledger.release_escrow("id_0001", 3).is_err());
+    assert!(ledger.approve_escrow("id_0001", "entity_003", &approval_signature(&ledger, "id_0001", "entity_003"), 3).is_ok());
+    assert!(ledger.release_escrow("id_0001", 4).is_ok());
+    assert_eq!(ledger.get_entity("entity_001").unwrap().get_wallet().get_balance(), Amount::from_integer(40));
+    assert_eq!(ledger.get_entity("entity_002").unwrap().get_wallet().get_balance(), Amount::from_integer(60));
+    assert!(ledger.get_derived_balance(ESCROW_ACCOUNT_ID).unwrap().is_zero());
+    assert!(ledger.get_escrow_account().get_wallet().get_balance().is_zero());
+    assert_eq!(ledger.get_transaction("id_0001").unwrap().get_current_state(), Completed { timestamp: 4 });
+    assert!(ledger.get_escrows().is_empty());
+    assert!(ledger.reconcile().is_ok());
+    match ledger.release_escrow("id_0001", 5) {
+        Ok(_) => panic!("expected an error"),
+        Err(LedgerError::EscrowNotFoundError { transaction_id }) => assert_eq!(transaction_id, "id_0001"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+}
+
+#[test]
+fn ledger_escrow_refunded() {
+    let mut ledger = new_ledger();
+    assert!(ledger.open_escrow(new_transaction("id_0001", 60), ReleaseCondition::BothParties, 2).is_ok());
+    assert!(matches!(ledger.approve_escrow("id_0001", "entity_003", &approval_signature(&ledger, "id_0001", "entity_003"), 3), Err(LedgerError::EscrowOperationError { error: EscrowError::UnauthorizedApprovalError { .. } })));
+    assert!(ledger.refund_escrow("id_0001", "entity_001", 3).is_err());
+    assert!(ledger.refund_escrow("id_0001", "entity_002", 3).is_ok());
+    assert_eq!(ledger.get_entity("entity_001").unwrap().get_wallet().get_balance(), Amount::from_integer(100));
+    assert_eq!(ledger.get_transaction("id_0001").unwrap().get_current_state(), Failed { timestamp: 3 });
+    assert!(ledger.reconcile().is_ok());
+}
+
+#[test]
+fn ledger_escrow_unsuccessful() {
+    let mut ledger = new_ledger();
+    assert!(matches!(ledger.open_escrow(new_transaction("id_0001", 101), arbiter_condition(), 2), Err(LedgerError::TransferOperationError { .. })));
+    assert_eq!(ledger.get_transaction("id_0001").unwrap().get_current_state(), Failed { timestamp: 2 });
+    assert_eq!(ledger.get_entity("entity_001").unwrap().get_nonce(), 1);
+    assert!(ledger.get_escrows().is_empty());
+    assert!(matches!(ledger.register_entity(Entity::new(ESCROW_ACCOUNT_ID.to_string(), "escrow".to_string(), Wallet::new(Amount::from_integer(0)))), Err(LedgerError::ReservedEntityIdError { .. })));
+    assert!(ledger.reconcile().is_ok());
+}
+
+#[test]
+fn ledger_escrows_saved_and_loaded() {
+    let mut ledger = new_ledger();
+    let mut storage = MemoryStorage::new();
+    assert!(ledger.open_escrow(new_transaction("id_0001", 60), arbiter_condition(), 2).is_ok());
+    let mut second_transaction = new_transaction("id_0002", 30);
+    second_transaction.set_nonce(1);
+    second_transaction.sign(&key_pair("entity_001"));
+    assert!(ledger.open_escrow(second_transaction, ReleaseCondition::Deadline { deadline: 10 }, 2).is_ok());
+    assert!(ledger.approve_escrow("id_0001", "entity_003", &approval_signature(&ledger, "id_0001", "entity_003"), 3).is_ok());
+    assert!(ledger.save_escrows(&mut storage).is_ok());
+    assert!(ledger.release_escrow("id_0002", 10).is_ok());
+    assert!(ledger.save_escrows(&mut storage).is_ok());
+    assert_eq!(storage.list_escrows().unwrap().len(), 1);
+
+    // A restarted ledger gets back the open escrow and the coins held for it
+    let mut restarted = Ledger::new(TransferExecutor::new());
+    match restarted.load_escrows(&storage) {
+        Ok(_) => panic!("expected an error"),
+        Err(LedgerError::EntityNotFoundError { entity_id }) => assert_eq!(entity_id, "entity_001"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    for entity in ledger.get_entities() {
+        restarted.register_entity(entity).unwrap();
+    }
+    assert!(restarted.load_escrows(&storage).is_ok());
+    assert_eq!(restarted.get_escrow_account().get_wallet().get_balance(), Amount::from_integer(60));
+    assert_eq!(restarted.get_escrow("id_0001").unwrap().get_approvals().len(), 1);
+    assert!(restarted.reconcile().is_ok());
+    assert!(matches!(restarted.load_escrows(&storage), Err(LedgerError::EscrowsAlreadyOpenError { count: 1 })));
+    assert!(restarted.release_escrow("id_0001", 4).is_ok());
+    assert_eq!(restarted.get_entity("entity_002").unwrap().get_wallet().get_balance(), Amount::from_integer(90));
+    assert!(restarted.get_escrow_account().get_wallet().get_balance().is_zero());
+    assert!(restarted.reconcile().is_ok());
+}
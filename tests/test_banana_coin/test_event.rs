@@ -0,0 +1,259 @@
+extern crate banana_coin;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::Receiver;
+
+use banana_coin::cli::{run_command_with_events, Command};
+use banana_coin::event::*;
+use banana_coin::event::DomainEvent::*;
+use banana_coin::ledger::{ESCROW_ACCOUNT_ID, Ledger};
+use banana_coin::model::*;
+use banana_coin::model::TransactionState::*;
+use banana_coin::storage::MemoryStorage;
+use banana_coin::transfer::{Mempool, TransferExecutor};
+
+fn new_transaction(id: &str, amount: i64) -> Transaction {
+    Transaction::new(
+        id.to_string(),
+        "entity_001".to_string(),
+        "entity_002".to_string(),
+        Amount::from_integer(amount),
+        "Moving coins.".to_string(),
+        Created {timestamp: 1},
+        vec![Created {timestamp: 1}],
+    ).unwrap()
+}
+
+fn new_ledger() -> (Ledger, Receiver<DomainEvent>) {
+    let mut ledger = Ledger::new(TransferExecutor::new());
+    let receiver = ledger.get_event_bus().subscribe_channel();
+    ledger.register_entity(Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)))).unwrap();
+    ledger.register_entity(Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)))).unwrap();
+    (ledger, receiver)
+}
+
+fn names(receiver: &Receiver<DomainEvent>) -> Vec<&'static str> {
+    receiver.try_iter().map(|event| event.get_name()).collect()
+}
+
+#[test]
+fn ledger_publishes_transfer_events_in_order() {
+    let (mut ledger, receiver) = new_ledger();
+    match receiver.try_recv() {
+        Ok(EntityCreated { entity_id, name, balances }) => {
+            assert_eq!(entity_id, "entity_001");
+            assert_eq!(name, "john");
+            assert_eq!(balances.get(DEFAULT_ASSET), Some(&Amount::from_integer(100)));
+        }
+        _ => panic!("unexpected result"),
+    }
+    assert_eq!(names(&receiver), vec!["EntityCreated"]);
+
+    ledger.execute_transaction(new_transaction("id_0001", 30), 2).unwrap();
+    let events: Vec<DomainEvent> = receiver.try_iter().collect();
+    assert_eq!(events.len(), 4);
+    match &events[0] {
+        TransactionStateChanged { transaction, previous_state } => {
+            assert_eq!(transaction.get_id(), "id_0001");
+            assert_eq!(transaction.get_current_state(), OnProcess {timestamp: 2});
+            assert_eq!(previous_state, &Some(Created {timestamp: 1}));
+        }
+        _ => panic!("unexpected result"),
+    }
+    match &events[1] {
+        CoinsRemoved { entity_id, asset, amount, balance, transaction_id, timestamp } => {
+            assert_eq!(entity_id, "entity_001");
+            assert_eq!(asset, DEFAULT_ASSET);
+            assert_eq!(*amount, Amount::from_integer(30));
+            assert_eq!(*balance, Amount::from_integer(70));
+            assert_eq!(transaction_id.as_deref(), Some("id_0001"));
+            assert_eq!(*timestamp, 2);
+        }
+        _ => panic!("unexpected result"),
+    }
+    match &events[2] {
+        CoinsAdded { entity_id, amount, balance, .. } => {
+            assert_eq!(entity_id, "entity_002");
+            assert_eq!(*amount, Amount::from_integer(30));
+            assert_eq!(*balance, Amount::from_integer(30));
+        }
+        _ => panic!("unexpected result"),
+    }
+    match &events[3] {
+        TransactionStateChanged { transaction, previous_state } => {
+            assert_eq!(transaction.get_current_state(), Completed {timestamp: 2});
+            assert_eq!(previous_state, &Some(OnProcess {timestamp: 2}));
+        }
+        _ => panic!("unexpected result"),
+    }
+    assert_eq!(events[3].get_entity_ids(), vec!["entity_001", "entity_002"]);
+}
+
+#[test]
+fn ledger_publishes_failed_transaction_without_balance_events() {
+    let (mut ledger, receiver) = new_ledger();
+    names(&receiver);
+    assert!(ledger.execute_transaction(new_transaction("id_0001", 150), 2).is_err());
+    assert_eq!(names(&receiver), vec!["TransactionStateChanged", "TransactionStateChanged"]);
+    // A transaction that is not recorded publishes nothing
+    assert!(ledger.execute_transaction(new_transaction("id_0001", 10), 3).is_err());
+    assert!(names(&receiver).is_empty());
+}
+
+#[test]
+fn ledger_publishes_escrow_events() {
+    let (mut ledger, receiver) = new_ledger();
+    names(&receiver);
+    ledger.open_escrow(new_transaction("id_0001", 40), ReleaseCondition::Deadline { deadline: 10 }, 2).unwrap();
+    let events: Vec<DomainEvent> = receiver.try_iter().collect();
+    assert_eq!(events.iter().map(DomainEvent::get_name).collect::<Vec<_>>(),
+               vec!["TransactionStateChanged", "CoinsRemoved", "CoinsAdded", "TransactionStateChanged"]);
+    match &events[2] {
+        CoinsAdded { entity_id, balance, .. } => {
+            assert_eq!(entity_id, ESCROW_ACCOUNT_ID);
+            assert_eq!(*balance, Amount::from_integer(40));
+        }
+        _ => panic!("unexpected result"),
+    }
+    match &events[3] {
+        TransactionStateChanged { transaction, .. } => assert_eq!(transaction.get_current_state(), Blocked {timestamp: 2}),
+        _ => panic!("unexpected result"),
+    }
+    ledger.release_escrow("id_0001", 10).unwrap();
+    let events: Vec<DomainEvent> = receiver.try_iter().collect();
+    assert_eq!(events.iter().map(DomainEvent::get_name).collect::<Vec<_>>(), vec!["CoinsRemoved", "CoinsAdded", "TransactionStateChanged"]);
+    match &events[1] {
+        CoinsAdded { entity_id, balance, .. } => {
+            assert_eq!(entity_id, "entity_002");
+            assert_eq!(*balance, Amount::from_integer(40));
+        }
+        _ => panic!("unexpected result"),
+    }
+}
+
+#[test]
+fn event_bus_dispatches_to_every_subscriber() {
+    let event_bus = EventBus::new();
+    let published = Arc::new(Mutex::new(Vec::new()));
+    let subscriber_published = Arc::clone(&published);
+    event_bus.subscribe(move |event: &DomainEvent| subscriber_published.lock().unwrap().push(event.get_name()));
+    let dropped_receiver = event_bus.subscribe_channel();
+    drop(dropped_receiver);
+    let receiver = event_bus.clone().subscribe_channel();
+    assert_eq!(event_bus.get_subscriber_count(), 3);
+
+    let mut storage = MemoryStorage::new();
+    let command = Command::EntityCreate { entity_id: "entity_001".to_string(), name: "john".to_string(), balance: Amount::from_integer(0), public_key: None };
+    run_command_with_events(&mut storage, &command, 1, &event_bus).unwrap();
+    let command = Command::WalletDeposit { entity_id: "entity_001".to_string(), amount: Amount::from_integer(25), transaction_id: None, description: None };
+    run_command_with_events(&mut storage, &command, 2, &event_bus).unwrap();
+    let command = Command::WalletWithdraw { entity_id: "entity_001".to_string(), amount: Amount::from_integer(50), transaction_id: None, description: None };
+    assert!(run_command_with_events(&mut storage, &command, 3, &event_bus).is_err());
+
+    let expected = vec!["EntityCreated", "TransactionStateChanged", "CoinsAdded", "TransactionStateChanged",
+                        "TransactionStateChanged", "TransactionStateChanged"];
+    assert_eq!(*published.lock().unwrap(), expected);
+    assert_eq!(names(&receiver), expected);
+}
+
+#[test]
+fn executor_publishes_transfer_events() {
+    let event_bus = EventBus::new();
+    let receiver = event_bus.subscribe_channel();
+    let executor = TransferExecutor::new().with_event_bus(event_bus);
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    let mut transaction = new_transaction("id_0001", 60);
+    executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 2).unwrap();
+
+    let events: Vec<DomainEvent> = receiver.try_iter().collect();
+    let event_names: Vec<&str> = events.iter().map(|event| event.get_name()).collect();
+    assert_eq!(event_names, vec!["TransactionStateChanged", "CoinsRemoved", "CoinsAdded", "TransactionStateChanged"]);
+    match &events[1] {
+        CoinsRemoved { entity_id, balance, transaction_id, .. } => {
+            assert_eq!(entity_id, "entity_001");
+            assert_eq!(*balance, Amount::from_integer(40));
+            assert_eq!(*transaction_id, Some("id_0001".to_string()));
+        }
+        _ => panic!("unexpected result"),
+    }
+    match &events[3] {
+        TransactionStateChanged { transaction, .. } => assert_eq!(transaction.get_current_state(), Completed { timestamp: 2 }),
+        _ => panic!("unexpected result"),
+    }
+
+    // A transaction that is refused before it is run changes nothing, so it publishes nothing
+    let mut transaction = new_transaction("id_0002", 60);
+    assert!(executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 3).is_err());
+    assert!(names(&receiver).is_empty());
+}
+
+#[test]
+fn ledger_takes_over_executor_event_bus() {
+    let event_bus = EventBus::new();
+    let receiver = event_bus.subscribe_channel();
+    let mut ledger = Ledger::new(TransferExecutor::new().with_event_bus(event_bus));
+    ledger.register_entity(Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)))).unwrap();
+    ledger.register_entity(Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)))).unwrap();
+    ledger.execute_transaction(new_transaction("id_0001", 30), 2).unwrap();
+    // Every change is published once, by the ledger, and not a second time by its executor
+    assert_eq!(names(&receiver), vec!["EntityCreated", "EntityCreated", "TransactionStateChanged", "CoinsRemoved",
+                                       "CoinsAdded", "TransactionStateChanged"]);
+    assert_eq!(ledger.get_event_bus().get_subscriber_count(), 1);
+}
+
+#[test]
+fn mempool_publishes_state_changes() {
+    let mut mempool = Mempool::new(100);
+    let receiver = mempool.get_event_bus().subscribe_channel();
+    let sender = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    for id in ["id_0001", "id_0002"] {
+        let mut transaction = new_transaction(id, 10);
+        transaction.set_nonce(mempool.get_next_nonce(&sender));
+        mempool.submit(transaction, &sender).unwrap();
+    }
+    assert!(names(&receiver).is_empty());
+
+    mempool.take_batch(1, 2).unwrap();
+    let events: Vec<DomainEvent> = receiver.try_iter().collect();
+    assert_eq!(events.len(), 1);
+    match &events[0] {
+        TransactionStateChanged { transaction, previous_state } => {
+            assert_eq!(transaction.get_current_state(), OnProcess { timestamp: 2 });
+            assert_eq!(*previous_state, Some(Created { timestamp: 1 }));
+        }
+        _ => panic!("unexpected result"),
+    }
+
+    // The waiting successor of a failed transaction is failed with it
+    mempool.fail_batch(&["id_0001".to_string()], 3).unwrap();
+    let states: Vec<TransactionState> = receiver.try_iter()
+        .map(|event| match event {
+            TransactionStateChanged { transaction, .. } => transaction.get_current_state(),
+            _ => Created { timestamp: 0 },
+        })
+        .collect();
+    assert_eq!(states, vec![Failed { timestamp: 3 }, Failed { timestamp: 3 }]);
+}
+
+#[test]
+fn domain_event_serialization() {
+    let event = CoinsAdded {
+        entity_id: "entity_001".to_string(),
+        asset: DEFAULT_ASSET.to_string(),
+        amount: Amount::parse("2.5").unwrap(),
+        balance: Amount::parse("102.5").unwrap(),
+        transaction_id: Some("id_0001".to_string()),
+        timestamp: 7,
+    };
+    let json = serde_json::to_value(&event).unwrap();
+    assert_eq!(json["CoinsAdded"]["amount"], "2.5");
+    assert_eq!(json["CoinsAdded"]["transaction_id"], "id_0001");
+    let deserialized: DomainEvent = serde_json::from_value(json).unwrap();
+    match deserialized {
+        CoinsAdded { balance, timestamp, .. } => {
+            assert_eq!(balance, Amount::parse("102.5").unwrap());
+            assert_eq!(timestamp, 7);
+        }
+        _ => panic!("unexpected result"),
+    }
+}
@@ -0,0 +1,110 @@
+extern crate banana_coin;
+use banana_coin::model::*;
+use banana_coin::error::JournalError;
+use banana_coin::ledger::Journal;
+
+#[test]
+fn record_successful() {
+    let mut journal = Journal::new();
+    match journal.record("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100)) {
+        Ok(_) => {
+            assert_eq!(journal.get_postings(), vec![
+                Posting::new("id_0001".to_string(), "entity_001".to_string(), PostingSide::Debit, Amount::from_integer(100)),
+                Posting::new("id_0001".to_string(), "entity_002".to_string(), PostingSide::Credit, Amount::from_integer(100)),
+            ]);
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+}
+
+#[test]
+fn record_unsuccessful_zero_amount() {
+    let mut journal = Journal::new();
+    assert!(matches!(journal.record("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(0)), Err(JournalError::ZeroAmountPostingError { .. })));
+    assert!(journal.get_postings().is_empty());
+}
+
+#[test]
+fn record_unsuccessful_negative_amount() {
+    let mut journal = Journal::new();
+    match journal.record("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(-10)) {
+        Ok(_) => panic!("expected an error"),
+        Err(JournalError::NegativeAmountPostingError { amount, .. }) => assert_eq!(amount, Amount::from_integer(-10)),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(journal.get_postings().is_empty());
+}
+
+#[test]
+fn record_asset_all_unsuccessful_records_no_change() {
+    let mut journal = Journal::new();
+    let changes = vec![
+        ("entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100)),
+        ("entity_001".to_string(), "entity_001".to_string(), Amount::from_integer(5)),
+    ];
+    assert!(matches!(journal.record_asset_all("id_0001".to_string(), DEFAULT_ASSET.to_string(), &changes),
+                     Err(JournalError::SameEntityPostingError { .. })));
+    assert!(journal.get_postings().is_empty());
+    assert!(journal.record_asset_all("id_0001".to_string(), DEFAULT_ASSET.to_string(), &changes[..1]).is_ok());
+    assert_eq!(journal.get_postings().len(), 2);
+}
+
+#[test]
+fn record_unsuccessful_same_entity() {
+    let mut journal = Journal::new();
+    assert!(matches!(journal.record("id_0001".to_string(), "entity_001".to_string(), "entity_001".to_string(), Amount::from_integer(10)), Err(JournalError::SameEntityPostingError { .. })));
+}
+
+#[test]
+fn derived_balances_and_trial_balance() {
+    let mut journal = Journal::new();
+    assert!(journal.record("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(100)).is_ok());
+    assert!(journal.record("id_0002".to_string(), "entity_002".to_string(), "entity_003".to_string(), Amount::from_integer(30)).is_ok());
+    assert!(journal.record("id_0003".to_string(), "entity_003".to_string(), "entity_001".to_string(), Amount::from_integer(10)).is_ok());
+    assert_eq!(journal.get_balance("entity_001").unwrap(), Amount::from_integer(-90));
+    assert_eq!(journal.get_balance("entity_002").unwrap(), Amount::from_integer(70));
+    assert_eq!(journal.get_balance("entity_003").unwrap(), Amount::from_integer(20));
+    assert_eq!(journal.get_postings_by_transaction("id_0002").len(), 2);
+    assert!(journal.check_trial_balance().is_ok());
+}
+
+#[test]
+fn trial_balance_unsuccessful_tampered_journal() {
+    let json = r#"{"postings":[
+        {"transaction_id":"id_0001","entity_id":"entity_001","side":"Debit","amount":100},
+        {"transaction_id":"id_0001","entity_id":"entity_002","side":"Credit","amount":90}]}"#;
+    let journal: Journal = serde_json::from_str(json).unwrap();
+    match journal.check_trial_balance() {
+        Ok(_) => panic!("expected an error"),
+        Err(JournalError::UnbalancedJournalError { asset, total }) => {
+            assert_eq!(asset, DEFAULT_ASSET);
+            assert_eq!(total, Amount::from_integer(-10));
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+}
+
+#[test]
+fn derived_balances_unsuccessful_overflow() {
+    let max = Amount::new(i128::MAX, 0).unwrap();
+    let mut journal = Journal::new();
+    assert!(journal.record("id_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), max).is_ok());
+    assert!(journal.record("id_0002".to_string(), "entity_003".to_string(), "entity_002".to_string(), max).is_ok());
+    match journal.get_balance("entity_002") {
+        Ok(_) => panic!("expected an error"),
+        Err(JournalError::BalanceOverflowError { asset }) => assert_eq!(asset, DEFAULT_ASSET),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+
+    // Two credits out of range must not clamp into a balanced trial balance
+    let json = format!(r#"{{"postings":[
+        {{"transaction_id":"id_0001","entity_id":"entity_001","side":"Credit","amount":"{max}"}},
+        {{"transaction_id":"id_0001","entity_id":"entity_002","side":"Credit","amount":"{max}"}},
+        {{"transaction_id":"id_0001","entity_id":"entity_003","side":"Debit","amount":"{max}"}}]}}"#);
+    let journal: Journal = serde_json::from_str(&json).unwrap();
+    match journal.check_trial_balance() {
+        Ok(_) => panic!("expected an error"),
+        Err(JournalError::BalanceOverflowError { asset }) => assert_eq!(asset, DEFAULT_ASSET),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+}
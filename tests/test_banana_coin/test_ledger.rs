@@ -0,0 +1,224 @@
+extern crate banana_coin;
+use banana_coin::model::*;
+use banana_coin::model::TransactionState::*;
+use banana_coin::error::{LedgerError, TransactionError, TransferError};
+use banana_coin::ledger::{ISSUANCE_ACCOUNT_ID, Ledger};
+use banana_coin::transfer::TransferExecutor;
+
+fn new_transaction(id: &str, from_entity_id: &str, to_entity_id: &str, amount: i64, timestamp: u64) -> Transaction {
+    Transaction::new(
+        id.to_string(),
+        from_entity_id.to_string(),
+        to_entity_id.to_string(),
+        Amount::from_integer(amount),
+        format!("Moving {} coins from entity identified by '{}' to entity identified by '{}'.", amount, from_entity_id, to_entity_id),
+        Created { timestamp },
+        vec![Created { timestamp }],
+    ).unwrap()
+}
+
+fn new_ledger() -> Ledger {
+    let mut ledger = Ledger::new(TransferExecutor::new());
+    ledger.register_entity(Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)))).unwrap();
+    ledger.register_entity(Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(50)))).unwrap();
+    ledger.register_entity(Entity::new("entity_003".to_string(), "jack".to_string(), Wallet::new(Amount::from_integer(0)))).unwrap();
+    ledger
+}
+
+#[test]
+fn register_entity_unsuccessful_duplicate_id() {
+    let mut ledger = new_ledger();
+    match ledger.register_entity(Entity::new("entity_001".to_string(), "jill".to_string(), Wallet::new(Amount::from_integer(10)))) {
+        Ok(_) => panic!("expected an error"),
+        Err(LedgerError::DuplicateEntityError { entity_id }) => assert_eq!(entity_id, "entity_001"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(ledger.get_entity("entity_001").unwrap().get_name(), "john");
+    assert_eq!(ledger.get_entities().len(), 3);
+}
+
+#[test]
+fn execute_transaction_successful() {
+    let mut ledger = new_ledger();
+    match ledger.execute_transaction(new_transaction("id_0001", "entity_001", "entity_003", 30, 1), 2) {
+        Ok(_) => {
+            assert_eq!(ledger.get_entity("entity_001").unwrap().get_wallet().get_balance(), Amount::from_integer(70));
+            assert_eq!(ledger.get_entity("entity_003").unwrap().get_wallet().get_balance(), Amount::from_integer(30));
+            assert_eq!(ledger.get_transaction("id_0001").unwrap().get_current_state(), Completed { timestamp: 2 });
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+}
+
+#[test]
+fn execute_transaction_unsuccessful_unknown_entity() {
+    let mut ledger = new_ledger();
+    match ledger.execute_transaction(new_transaction("id_0001", "entity_001", "entity_004", 30, 1), 2) {
+        Ok(_) => panic!("expected an error"),
+        Err(LedgerError::EntityNotFoundError { entity_id }) => assert_eq!(entity_id, "entity_004"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(ledger.get_transactions().is_empty());
+    assert_eq!(ledger.get_entity("entity_001").unwrap().get_wallet().get_balance(), Amount::from_integer(100));
+}
+
+#[test]
+fn execute_transaction_unsuccessful_logs_failed_transaction() {
+    let mut ledger = new_ledger();
+    match ledger.execute_transaction(new_transaction("id_0001", "entity_003", "entity_001", 30, 1), 2) {
+        Ok(_) => panic!("expected an error"),
+        Err(LedgerError::TransferOperationError { error }) => assert!(matches!(*error, TransferError::EntityOperationError { .. })),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(ledger.get_transaction("id_0001").unwrap().get_current_state(), Failed { timestamp: 2 });
+    assert_eq!(ledger.get_entity("entity_003").unwrap().get_wallet().get_balance(), Amount::from_integer(0));
+    assert_eq!(ledger.get_entity("entity_001").unwrap().get_wallet().get_balance(), Amount::from_integer(100));
+}
+
+#[test]
+fn execute_transaction_unsuccessful_duplicate_id() {
+    let mut ledger = new_ledger();
+    assert!(ledger.execute_transaction(new_transaction("id_0001", "entity_001", "entity_003", 30, 1), 2).is_ok());
+    match ledger.execute_transaction(new_transaction("id_0001", "entity_001", "entity_003", 30, 3), 4) {
+        Ok(_) => panic!("expected an error"),
+        Err(LedgerError::DuplicateTransactionError { transaction_id }) => assert_eq!(transaction_id, "id_0001"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(ledger.get_entity("entity_001").unwrap().get_wallet().get_balance(), Amount::from_integer(70));
+    assert_eq!(ledger.get_transactions().len(), 1);
+}
+
+#[test]
+fn query_transactions() {
+    let mut ledger = new_ledger();
+    assert!(ledger.execute_transaction(new_transaction("id_0001", "entity_001", "entity_003", 30, 1), 2).is_ok());
+    assert!(ledger.execute_transaction(new_transaction("id_0002", "entity_002", "entity_001", 20, 5), 6).is_ok());
+    assert!(ledger.execute_transaction(new_transaction("id_0003", "entity_003", "entity_002", 90, 9), 10).is_err());
+
+    let ids = |transactions: Vec<Transaction>| -> Vec<String> {
+        transactions.iter().map(|transaction| transaction.get_id()).collect()
+    };
+    assert_eq!(ids(ledger.get_transactions_by_entity("entity_001")), vec!["id_0001", "id_0002"]);
+    assert_eq!(ids(ledger.get_transactions_by_entity("entity_003")), vec!["id_0001", "id_0003"]);
+    assert_eq!(ids(ledger.get_transactions_by_state(&Completed { timestamp: 0 })), vec!["id_0001", "id_0002"]);
+    assert_eq!(ids(ledger.get_transactions_by_state(&Failed { timestamp: 0 })), vec!["id_0003"]);
+    assert_eq!(ids(ledger.get_transactions_by_time_range(2, 9)), vec!["id_0002", "id_0003"]);
+    assert_eq!(ids(ledger.get_transactions_by_time_range(10, 20)), Vec::<String>::new());
+}
+
+#[test]
+fn reconcile_successful() {
+    let mut ledger = new_ledger();
+    assert!(ledger.register_entity(Entity::new("entity_004".to_string(), "jill".to_string(), Wallet::new(Amount::from_integer(-20)))).is_ok());
+    assert!(ledger.execute_transaction(new_transaction("id_0001", "entity_001", "entity_003", 30, 1), 2).is_ok());
+    assert!(ledger.execute_transaction(new_transaction("id_0002", "entity_002", "entity_001", 20, 5), 6).is_ok());
+    assert!(ledger.execute_transaction(new_transaction("id_0003", "entity_003", "entity_002", 90, 9), 10).is_err());
+    for entity in ledger.get_entities() {
+        assert_eq!(ledger.get_derived_balance(entity.get_id()).unwrap(), entity.get_wallet().get_balance());
+    }
+    assert_eq!(ledger.get_derived_balance("entity_001").unwrap(), Amount::from_integer(90));
+    assert_eq!(ledger.get_derived_balance("entity_004").unwrap(), Amount::from_integer(-20));
+    assert!(ledger.reconcile().is_ok());
+}
+
+#[test]
+fn register_entity_unsuccessful_reserved_id() {
+    let mut ledger = new_ledger();
+    assert!(matches!(ledger.register_entity(Entity::new(ISSUANCE_ACCOUNT_ID.to_string(), "issuance".to_string(), Wallet::new(Amount::from_integer(0)))), Err(LedgerError::ReservedEntityIdError { .. })));
+}
+
+#[test]
+fn execute_transaction_with_fee_collector() {
+    let mut ledger = new_ledger();
+    assert!(matches!(ledger.set_fee_collector("entity_004".to_string()), Err(LedgerError::EntityNotFoundError { .. })));
+    assert!(ledger.set_fee_collector("entity_003".to_string()).is_ok());
+    let mut transaction = new_transaction("id_0001", "entity_001", "entity_002", 60, 1);
+    assert!(transaction.set_fee(Amount::from_integer(4)).is_ok());
+    assert!(ledger.execute_transaction(transaction, 2).is_ok());
+    assert_eq!(ledger.get_entity("entity_001").unwrap().get_wallet().get_balance(), Amount::from_integer(36));
+    assert_eq!(ledger.get_entity("entity_002").unwrap().get_wallet().get_balance(), Amount::from_integer(110));
+    assert_eq!(ledger.get_entity("entity_003").unwrap().get_wallet().get_balance(), Amount::from_integer(4));
+    assert_eq!(ledger.get_derived_balance("entity_003").unwrap(), Amount::from_integer(4));
+    assert!(ledger.reconcile().is_ok());
+}
+
+#[test]
+fn reconcile_successful_multiple_assets() {
+    let mut ledger = new_ledger();
+    let mut wallet = Wallet::new(Amount::from_integer(10));
+    assert!(wallet.add_asset_coins("GOLD", Amount::from_integer(40)).is_ok());
+    assert!(ledger.register_entity(Entity::new("entity_004".to_string(), "jill".to_string(), wallet)).is_ok());
+    let mut transaction = new_transaction("id_0001", "entity_004", "entity_001", 25, 1);
+    assert!(transaction.set_asset("GOLD".to_string()).is_ok());
+    assert!(ledger.execute_transaction(transaction, 2).is_ok());
+    let mut transaction = new_transaction("id_0002", "entity_002", "entity_001", 5, 3);
+    assert!(transaction.set_asset("GOLD".to_string()).is_ok());
+    assert!(ledger.execute_transaction(transaction, 4).is_err());
+    assert_eq!(ledger.get_derived_asset_balance("entity_004", "GOLD").unwrap(), Amount::from_integer(15));
+    assert_eq!(ledger.get_derived_asset_balance("entity_001", "GOLD").unwrap(), Amount::from_integer(25));
+    assert_eq!(ledger.get_derived_balance("entity_001").unwrap(), Amount::from_integer(100));
+    assert_eq!(ledger.get_entity("entity_001").unwrap().get_wallet().get_asset_balance("GOLD"), Amount::from_integer(25));
+    assert!(ledger.reconcile().is_ok());
+}
+
+#[test]
+fn execute_transaction_unsuccessful_does_not_log_final_transaction() {
+    let mut ledger = new_ledger();
+    let receiver = ledger.get_event_bus().subscribe_channel();
+    let forged = |id: &str| Transaction::new(
+        id.to_string(),
+        "entity_001".to_string(),
+        "entity_003".to_string(),
+        Amount::from_integer(1000),
+        "Forged transfer.".to_string(),
+        Completed { timestamp: 3 },
+        vec![Created { timestamp: 1 }, OnProcess { timestamp: 2 }, Completed { timestamp: 3 }],
+    ).unwrap();
+    assert!(ledger.execute_transaction(forged("id_0001"), 4).is_err());
+    assert!(ledger.open_escrow(forged("id_0002"), ReleaseCondition::Deadline { deadline: 10 }, 4).is_err());
+    assert!(ledger.get_transaction("id_0001").is_none());
+    assert!(ledger.get_transaction("id_0002").is_none());
+    assert!(ledger.get_transactions().is_empty());
+    assert_eq!(receiver.try_iter().count(), 0);
+    assert_eq!(ledger.get_entity("entity_001").unwrap().get_wallet().get_balance(), Amount::from_integer(100));
+    assert_eq!(ledger.get_entity("entity_003").unwrap().get_wallet().get_balance(), Amount::from_integer(0));
+}
+
+#[test]
+fn asset_registry_enforced() {
+    let mut ledger = new_ledger();
+    let mut wallet = Wallet::new(Amount::from_integer(10));
+    assert!(wallet.add_asset_coins("EUR", Amount::parse("20.005").unwrap()).is_ok());
+    assert!(ledger.register_entity(Entity::new("entity_004".to_string(), "jill".to_string(), wallet.clone())).is_ok());
+    // A definition the balances of the wallets do not fit is refused
+    match ledger.define_asset(AssetDefinition::new("EUR".to_string(), 2).unwrap()) {
+        Ok(_) => panic!("expected an error"),
+        Err(LedgerError::AssetPrecisionError { entity_id, decimals, .. }) => {
+            assert_eq!(entity_id, "entity_004");
+            assert_eq!(decimals, 2);
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(ledger.define_asset(AssetDefinition::new("EUR".to_string(), 3).unwrap()).is_ok());
+    assert!(matches!(ledger.define_asset(AssetDefinition::new("EUR".to_string(), 4).unwrap()),
+                     Err(LedgerError::AssetDefinitionError { .. })));
+    assert!(wallet.add_asset_coins("EUR", Amount::parse("0.0001").unwrap()).is_ok());
+    assert!(matches!(ledger.register_entity(Entity::new("entity_005".to_string(), "jim".to_string(), wallet)),
+                     Err(LedgerError::AssetPrecisionError { .. })));
+
+    // The transactions are checked against the registry before the coins are moved
+    let mut transaction = Transaction::new("id_0001".to_string(), "entity_004".to_string(), "entity_001".to_string(),
+                                           Amount::parse("1.0005").unwrap(), "".to_string(), Created { timestamp: 1 }, vec![Created { timestamp: 1 }]).unwrap();
+    assert!(transaction.set_asset("EUR".to_string()).is_ok());
+    match ledger.execute_transaction(transaction, 2) {
+        Ok(_) => panic!("expected an error"),
+        Err(LedgerError::InvalidTransactionError { error, .. }) => assert!(matches!(error, TransactionError::AssetPrecisionError { decimals: 3, .. })),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(ledger.get_transactions().is_empty());
+    assert_eq!(ledger.get_entity("entity_004").unwrap().get_wallet().get_asset_balance("EUR"), Amount::parse("20.005").unwrap());
+    let mut transaction = new_transaction("id_0002", "entity_004", "entity_001", 1, 1);
+    assert!(transaction.set_asset("EUR".to_string()).is_ok());
+    assert!(ledger.execute_transaction(transaction, 2).is_ok());
+    assert!(ledger.reconcile().is_ok());
+}
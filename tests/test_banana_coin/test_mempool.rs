@@ -0,0 +1,243 @@
+extern crate banana_coin;
+use banana_coin::error::MempoolError;
+use banana_coin::model::*;
+use banana_coin::model::TransactionState::*;
+use banana_coin::transfer::{Mempool, TransferExecutor};
+
+fn entity(id: &str, balance: i64) -> Entity {
+    Entity::new(id.to_string(), "john".to_string(), Wallet::new(Amount::from_integer(balance)))
+}
+
+fn transaction(id: &str, amount: i64, timestamp: u64) -> Transaction {
+    Transaction::new(id.to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(amount), "".to_string(),
+                     Created { timestamp }, vec![Created { timestamp }]).unwrap()
+}
+
+fn submit(mempool: &mut Mempool, mut transaction: Transaction, sender: &Entity) -> Result<(), MempoolError> {
+    transaction.set_nonce(mempool.get_next_nonce(sender));
+    mempool.submit(transaction, sender)
+}
+
+fn ids(transactions: &[Transaction]) -> Vec<String> {
+    transactions.iter().map(|transaction| transaction.get_id()).collect()
+}
+
+#[test]
+fn submit_successful() {
+    let mut mempool = Mempool::new(100);
+    let sender = entity("entity_001", 100);
+    assert!(submit(&mut mempool, transaction("tx_0001", 60, 1), &sender).is_ok());
+    assert!(submit(&mut mempool, transaction("tx_0002", 40, 1), &sender).is_ok());
+    assert_eq!(mempool.get_pending_amount("entity_001").unwrap(), Amount::from_integer(100));
+    assert_eq!(ids(&mempool.get_transactions()), vec!["tx_0001", "tx_0002"]);
+}
+
+#[test]
+fn submit_unsuccessful_double_spend() {
+    let mut mempool = Mempool::new(100);
+    let sender = entity("entity_001", 100);
+    assert!(submit(&mut mempool, transaction("tx_0001", 60, 1), &sender).is_ok());
+    match submit(&mut mempool, transaction("tx_0002", 60, 1), &sender) {
+        Ok(_) => panic!("expected an error"),
+        Err(MempoolError::InsufficientBalanceError { required_amount, .. }) => {
+            assert_eq!(required_amount, Amount::from_integer(120));
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(mempool.get_transactions().len(), 1);
+}
+
+#[test]
+fn submit_successful_within_overdraft_policy() {
+    let mut mempool = Mempool::new(100);
+    let mut sender = entity("entity_001", 0);
+    let policy = OverdraftPolicy::Limit { floor: Amount::from_integer(-100) };
+    assert!(sender.set_overdraft_policy(policy, "admin".to_string(), 1).is_ok());
+    assert!(submit(&mut mempool, transaction("tx_0001", 60, 1), &sender).is_ok());
+    assert!(submit(&mut mempool, transaction("tx_0002", 40, 1), &sender).is_ok());
+    assert!(matches!(submit(&mut mempool, transaction("tx_0003", 1, 1), &sender), Err(MempoolError::InsufficientBalanceError { .. })));
+}
+
+#[test]
+fn submit_unsuccessful_duplicate() {
+    let mut mempool = Mempool::new(100);
+    let sender = entity("entity_001", 100);
+    assert!(submit(&mut mempool, transaction("tx_0001", 10, 1), &sender).is_ok());
+    assert!(matches!(submit(&mut mempool, transaction("tx_0001", 10, 1), &sender), Err(MempoolError::DuplicateTransactionError { .. })));
+}
+
+#[test]
+fn submit_unsuccessful_wrong_state_or_entity() {
+    let mut mempool = Mempool::new(100);
+    let completed = Transaction::new("tx_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(10),
+                                     "".to_string(), Completed { timestamp: 2 },
+                                     vec![Created { timestamp: 1 }, OnProcess { timestamp: 2 }, Completed { timestamp: 2 }]).unwrap();
+    assert!(matches!(submit(&mut mempool, completed, &entity("entity_001", 100)), Err(MempoolError::InvalidTransactionStateError { .. })));
+    assert!(matches!(submit(&mut mempool, transaction("tx_0002", 10, 1), &entity("entity_003", 100)), Err(MempoolError::EntityMismatchError { .. })));
+}
+
+#[test]
+fn take_and_confirm_batch() {
+    let mut mempool = Mempool::new(100);
+    let mut sender = entity("entity_001", 100);
+    let mut receiver = entity("entity_002", 0);
+    for index in 1..4 {
+        assert!(submit(&mut mempool, transaction(&format!("tx_000{}", index), 10, 1), &sender).is_ok());
+    }
+    let batch = mempool.take_batch(2, 2).unwrap();
+    assert_eq!(ids(&batch), vec!["tx_0001", "tx_0002"]);
+    assert!(batch.iter().all(|transaction| transaction.get_current_state() == OnProcess { timestamp: 2 }));
+    assert_eq!(mempool.get_pending_amount("entity_001").unwrap(), Amount::from_integer(30));
+
+    // The picked transactions are executed from the OnProcess state
+    let executor = TransferExecutor::new();
+    for transaction in batch.clone().iter_mut() {
+        assert!(executor.execute(transaction, &mut sender, &mut receiver, 3).is_ok());
+        assert_eq!(transaction.get_state_history().len(), 3);
+    }
+    assert_eq!(ids(&mempool.confirm_batch(&ids(&batch)).unwrap()), vec!["tx_0001", "tx_0002"]);
+    assert_eq!(ids(&mempool.get_transactions()), vec!["tx_0003"]);
+    assert_eq!(ids(&mempool.take_batch(10, 4).unwrap()), vec!["tx_0003"]);
+}
+
+#[test]
+fn requeue_batch() {
+    let mut mempool = Mempool::new(100);
+    let sender = entity("entity_001", 100);
+    assert!(submit(&mut mempool, transaction("tx_0001", 10, 1), &sender).is_ok());
+    let batch = mempool.take_batch(10, 2).unwrap();
+    assert!(mempool.take_batch(10, 2).unwrap().is_empty());
+    assert!(mempool.requeue_batch(&ids(&batch), 3).is_ok());
+    let transaction = mempool.get_transaction("tx_0001").unwrap();
+    assert_eq!(transaction.get_current_state(), Created { timestamp: 3 });
+    assert_eq!(ids(&mempool.take_batch(10, 4).unwrap()), vec!["tx_0001"]);
+}
+
+#[test]
+fn fail_batch() {
+    let mut mempool = Mempool::new(100);
+    let sender = entity("entity_001", 100);
+    assert!(submit(&mut mempool, transaction("tx_0001", 10, 1), &sender).is_ok());
+    assert!(submit(&mut mempool, transaction("tx_0002", 10, 1), &sender).is_ok());
+    assert!(matches!(mempool.fail_batch(&["tx_0001".to_string()], 3), Err(MempoolError::InvalidTransactionStateError { .. })));
+    let batch = mempool.take_batch(1, 2).unwrap();
+    let failed = mempool.fail_batch(&ids(&batch), 3).unwrap();
+    // The following transaction of the sender can no longer be executed, so it fails with the batch
+    assert_eq!(ids(&failed), vec!["tx_0001", "tx_0002"]);
+    assert!(failed.iter().all(|transaction| transaction.get_current_state() == Failed { timestamp: 3 }));
+    assert!(mempool.get_transactions().is_empty());
+    assert!(mempool.get_pending_amount("entity_001").unwrap().is_zero());
+    assert_eq!(mempool.get_next_nonce(&sender), 0);
+}
+
+#[test]
+fn expire_drops_successors_and_frees_nonce() {
+    let mut mempool = Mempool::new(10);
+    let sender = entity("entity_001", 100);
+    assert!(submit(&mut mempool, transaction("tx_0001", 10, 5), &sender).is_ok());
+    assert!(submit(&mut mempool, transaction("tx_0002", 10, 1), &sender).is_ok());
+    assert!(submit(&mut mempool, transaction("tx_0003", 10, 5), &sender).is_ok());
+    let expired = mempool.expire(12).unwrap();
+    assert_eq!(ids(&expired), vec!["tx_0002", "tx_0003"]);
+    assert_eq!(ids(&mempool.get_transactions()), vec!["tx_0001"]);
+    // The next transaction takes the nonce of the expired one
+    assert_eq!(mempool.get_next_nonce(&sender), 1);
+    assert!(submit(&mut mempool, transaction("tx_0004", 10, 12), &sender).is_ok());
+    assert_eq!(mempool.get_transaction("tx_0004").unwrap().get_nonce(), 1);
+    let batch = mempool.take_batch(10, 13).unwrap();
+    assert_eq!(ids(&batch), vec!["tx_0001", "tx_0004"]);
+}
+
+#[test]
+fn expire_stale_transactions() {
+    let mut mempool = Mempool::new(10);
+    let sender = entity("entity_001", 100);
+    assert!(submit(&mut mempool, transaction("tx_0001", 10, 1), &sender).is_ok());
+    assert!(submit(&mut mempool, transaction("tx_0002", 10, 5), &sender).is_ok());
+    assert!(submit(&mut mempool, transaction("tx_0003", 10, 1), &sender).is_ok());
+    assert!(mempool.expire(11).unwrap().is_empty());
+    // A picked transaction does not expire
+    assert!(mempool.take_batch(1, 11).is_ok());
+    let expired = mempool.expire(12).unwrap();
+    assert_eq!(ids(&expired), vec!["tx_0003"]);
+    assert_eq!(expired[0].get_current_state(), Failed { timestamp: 12 });
+    assert_eq!(ids(&mempool.get_transactions()), vec!["tx_0001", "tx_0002"]);
+}
+
+#[test]
+fn submit_unsuccessful_wrong_nonce() {
+    let mut mempool = Mempool::new(100);
+    let mut sender = entity("entity_001", 100);
+    assert!(sender.increment_nonce().is_ok());
+    assert!(submit(&mut mempool, transaction("tx_0001", 10, 1), &sender).is_ok());
+    let mut replayed = transaction("tx_0002", 10, 1);
+    replayed.set_nonce(1);
+    assert!(matches!(mempool.submit(replayed, &sender), Err(MempoolError::ReplayedNonceError { expected_nonce: 2, found_nonce: 1, .. })));
+    let mut ahead = transaction("tx_0003", 10, 1);
+    ahead.set_nonce(3);
+    assert!(matches!(mempool.submit(ahead, &sender), Err(MempoolError::OutOfOrderNonceError { expected_nonce: 2, found_nonce: 3, .. })));
+}
+
+#[test]
+fn submit_unsuccessful_fee_over_balance() {
+    let mut mempool = Mempool::new(100);
+    let sender = entity("entity_001", 100);
+    let mut with_fee = transaction("tx_0001", 95, 1);
+    assert!(with_fee.set_fee(Amount::from_integer(5)).is_ok());
+    assert!(submit(&mut mempool, with_fee, &sender).is_ok());
+    assert_eq!(mempool.get_pending_amount("entity_001").unwrap(), Amount::from_integer(100));
+    let mut with_fee = transaction("tx_0002", 1, 1);
+    assert!(with_fee.set_fee(Amount::from_integer(1)).is_ok());
+    match submit(&mut mempool, with_fee, &sender) {
+        Ok(_) => panic!("expected an error"),
+        Err(MempoolError::InsufficientBalanceError { required_amount, .. }) => {
+            assert_eq!(required_amount, Amount::from_integer(102));
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+}
+
+#[test]
+fn take_batch_by_fee_per_byte() {
+    let mut mempool = Mempool::new(100);
+    let sender_1 = entity("entity_001", 100);
+    let sender_2 = Entity::new("entity_003".to_string(), "jack".to_string(), Wallet::new(Amount::from_integer(100)));
+    let new_transaction = |id: &str, from_entity_id: &str, fee: i64| {
+        let mut transaction = Transaction::new(id.to_string(), from_entity_id.to_string(), "entity_002".to_string(), Amount::from_integer(10),
+                                               "".to_string(), Created { timestamp: 1 }, vec![Created { timestamp: 1 }]).unwrap();
+        assert!(transaction.set_fee(Amount::from_integer(fee)).is_ok());
+        transaction
+    };
+    assert!(submit(&mut mempool, new_transaction("tx_0001", "entity_001", 1), &sender_1).is_ok());
+    assert!(submit(&mut mempool, new_transaction("tx_0002", "entity_001", 9), &sender_1).is_ok());
+    assert!(submit(&mut mempool, new_transaction("tx_0003", "entity_003", 5), &sender_2).is_ok());
+    assert!(submit(&mut mempool, new_transaction("tx_0004", "entity_003", 5), &sender_2).is_ok());
+    // The transactions of a sender keep the order of their nonces, whatever their fees
+    let batch = mempool.take_batch(3, 2).unwrap();
+    assert_eq!(ids(&batch), vec!["tx_0003", "tx_0004", "tx_0001"]);
+    assert_eq!(ids(&mempool.take_batch(3, 2).unwrap()), vec!["tx_0002"]);
+}
+
+#[test]
+fn submit_pending_amounts_per_asset() {
+    let mut mempool = Mempool::new(100);
+    let mut wallet = Wallet::new(Amount::from_integer(100));
+    assert!(wallet.add_asset_coins("GOLD", Amount::from_integer(10)).is_ok());
+    let sender = Entity::new("entity_001".to_string(), "john".to_string(), wallet);
+    let mut gold_transaction = transaction("tx_0001", 10, 1);
+    assert!(gold_transaction.set_asset("GOLD".to_string()).is_ok());
+    assert!(submit(&mut mempool, gold_transaction, &sender).is_ok());
+    assert!(submit(&mut mempool, transaction("tx_0002", 100, 1), &sender).is_ok());
+    assert_eq!(mempool.get_pending_amount("entity_001").unwrap(), Amount::from_integer(100));
+    assert_eq!(mempool.get_pending_asset_amount("entity_001", "GOLD").unwrap(), Amount::from_integer(10));
+    let mut gold_transaction = transaction("tx_0003", 1, 1);
+    assert!(gold_transaction.set_asset("GOLD".to_string()).is_ok());
+    match submit(&mut mempool, gold_transaction, &sender) {
+        Ok(_) => panic!("expected an error"),
+        Err(MempoolError::InsufficientBalanceError { asset, required_amount, .. }) => {
+            assert_eq!(asset, "GOLD");
+            assert_eq!(required_amount, Amount::from_integer(11));
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+}
@@ -0,0 +1,106 @@
+extern crate banana_coin;
+use banana_coin::chain::*;
+use banana_coin::error::ChainError;
+use banana_coin::model::*;
+use banana_coin::model::TransactionState::*;
+
+fn transactions(count: u32) -> Vec<Transaction> {
+    (0..count).map(|index| {
+        Transaction::new(format!("tx_{:04}", index), "entity_001".to_string(), "entity_002".to_string(),
+                         Amount::from_integer(i64::from(index) + 1), "".to_string(), Created { timestamp: 1 }, vec![Created { timestamp: 1 }]).unwrap()
+    }).collect()
+}
+
+#[test]
+fn root_matches_leaf_hashes() {
+    let batch = transactions(5);
+    let leaves: Vec<String> = batch.iter().map(hash_transaction).collect();
+    assert_eq!(MerkleTree::new(&batch).get_root(), compute_merkle_root(&leaves).unwrap());
+}
+
+#[test]
+fn proof_verify_successful() {
+    for count in 1..9 {
+        let batch = transactions(count);
+        let tree = MerkleTree::new(&batch);
+        for transaction in batch.iter() {
+            let proof = tree.get_proof(&transaction.get_id()).unwrap();
+            assert_eq!(proof.get_transaction_id(), transaction.get_id());
+            assert!(proof.verify(&tree.get_root()));
+            assert!(proof.verify_transaction(transaction, &tree.get_root()));
+        }
+    }
+}
+
+#[test]
+fn proof_missing_transaction() {
+    let tree = MerkleTree::new(&transactions(3));
+    assert!(tree.get_proof("tx_9999").is_none());
+}
+
+#[test]
+fn proof_verify_unsuccessful_other_root() {
+    let batch = transactions(4);
+    let proof = MerkleTree::new(&batch).get_proof("tx_0002").unwrap();
+    let other_root = MerkleTree::new(&transactions(5)).get_root();
+    assert!(!proof.verify(&other_root));
+}
+
+#[test]
+fn proof_verify_unsuccessful_other_transaction() {
+    let batch = transactions(4);
+    let tree = MerkleTree::new(&batch);
+    let proof = tree.get_proof("tx_0002").unwrap();
+    assert!(!proof.verify_transaction(&batch[1], &tree.get_root()));
+    let tampered = Transaction::new("tx_0002".to_string(), "entity_001".to_string(), "entity_002".to_string(),
+                                    Amount::from_integer(1000), "".to_string(), Created { timestamp: 1 }, vec![Created { timestamp: 1 }]).unwrap();
+    assert!(!proof.verify_transaction(&tampered, &tree.get_root()));
+}
+
+#[test]
+fn proof_serde_round_trip() {
+    let batch = transactions(6);
+    let tree = MerkleTree::new(&batch);
+    let proof = tree.get_proof("tx_0005").unwrap();
+    let json = serde_json::to_string(&proof).unwrap();
+    let decoded: MerkleProof = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, proof);
+    assert!(decoded.verify(&tree.get_root()));
+}
+
+#[test]
+fn block_proof_verifies_against_header() {
+    let block = Block::new(0, GENESIS_PREVIOUS_HASH.to_string(), 1, 0, transactions(3));
+    let proof = block.get_merkle_proof("tx_0001").unwrap();
+    assert!(proof.verify(block.get_header().get_merkle_root()));
+}
+
+#[test]
+fn root_unsuccessful_invalid_hash() {
+    assert!(matches!(compute_merkle_root(&["not hex".to_string()]), Err(ChainError::InvalidHashError { .. })));
+    let proof = MerkleProof::new("tx_0001".to_string(), sha256(b"leaf"),
+                                 vec![MerkleProofStep::new("not hex".to_string(), MerkleSide::Right)]);
+    assert!(proof.compute_root().is_err());
+    assert!(!proof.verify(&sha256(&[])));
+}
+
+#[test]
+fn root_differs_with_duplicated_last_leaf() {
+    let leaves: Vec<String> = transactions(3).iter().map(hash_transaction).collect();
+    let mut duplicated = leaves.clone();
+    duplicated.push(leaves[2].clone());
+    assert_ne!(compute_merkle_root(&leaves).unwrap(), compute_merkle_root(&duplicated).unwrap());
+}
+
+#[test]
+fn proof_verify_unsuccessful_inner_node_as_leaf() {
+    let batch = transactions(4);
+    let tree = MerkleTree::new(&batch);
+    let proof = tree.get_proof("tx_0000").unwrap();
+    // The node over the first two leaves, given as a leaf with the sibling of its level, does not lead to the root
+    let leaves: Vec<String> = batch[..2].iter().map(hash_transaction).collect();
+    let inner_node = compute_merkle_root(&leaves).unwrap();
+    let inner_proof = MerkleProof::new("tx_0000".to_string(), inner_node, proof.get_steps()[1..].to_vec());
+    assert!(proof.verify(&tree.get_root()));
+    assert!(!inner_proof.verify(&tree.get_root()));
+}
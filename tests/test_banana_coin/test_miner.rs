@@ -0,0 +1,166 @@
+extern crate banana_coin;
+use banana_coin::chain::*;
+use banana_coin::crypto::KeyPair;
+use banana_coin::error::ChainError;
+use banana_coin::ledger::ISSUANCE_ACCOUNT_ID;
+use banana_coin::model::*;
+use banana_coin::model::TransactionState::*;
+
+fn miner() -> Entity {
+    Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(0)))
+}
+
+fn key_pair() -> KeyPair {
+    KeyPair::from_secret_key("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60").unwrap()
+}
+
+fn signing_rules(rules: ConsensusRules) -> ConsensusRules {
+    let mut rules = rules;
+    rules.register_public_key("entity_001".to_string(), key_pair().get_public_key()).unwrap();
+    rules
+}
+
+fn completed_transaction(id: &str, from_entity_id: &str, to_entity_id: &str, amount: i64) -> Transaction {
+    let mut transaction = Transaction::new(id.to_string(), from_entity_id.to_string(), to_entity_id.to_string(), Amount::from_integer(amount),
+                                           "".to_string(), Completed { timestamp: 3 },
+                                           vec![Created { timestamp: 1 }, OnProcess { timestamp: 2 }, Completed { timestamp: 3 }]).unwrap();
+    transaction.sign(&key_pair());
+    transaction
+}
+
+#[test]
+fn mine_block_successful() {
+    let mut blockchain = Blockchain::with_rules(signing_rules(ConsensusRules::new(8, 8, 10, 5, Amount::from_integer(25))));
+    let mut miner = miner();
+    let block = blockchain.mine_block(&mut miner, vec![completed_transaction("tx_0001", "entity_001", "entity_002", 10)], 1).unwrap();
+    assert_eq!(block.get_header().get_difficulty(), 8);
+    assert!(block.get_hash().starts_with("00"));
+    let transactions = block.get_transactions();
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(transactions[0].get_from_entity_id(), ISSUANCE_ACCOUNT_ID);
+    assert_eq!(transactions[0].get_to_entity_id(), "entity_001");
+    assert_eq!(miner.get_wallet().get_balance(), Amount::from_integer(25));
+    let balances = blockchain.get_balances().unwrap();
+    assert_eq!(balances.get("entity_001"), Some(&Amount::from_integer(15)));
+    assert_eq!(balances.get(ISSUANCE_ACCOUNT_ID), Some(&Amount::from_integer(-25)));
+}
+
+#[test]
+fn mine_block_unsuccessful_leaves_chain_and_miner_unchanged() {
+    let mut blockchain = Blockchain::new();
+    let mut miner = miner();
+    blockchain.mine_block(&mut miner, Vec::new(), 10).unwrap();
+    assert!(matches!(blockchain.mine_block(&mut miner, Vec::new(), 5), Err(ChainError::DecreasingTimestampError { .. })));
+    assert_eq!(blockchain.get_height(), 1);
+    assert_eq!(miner.get_wallet().get_balance(), Amount::from_integer(50));
+}
+
+#[test]
+fn mine_block_fractional_reward() {
+    let mut blockchain = Blockchain::with_rules(ConsensusRules::new(0, 0, 10, 2, Amount::parse("12.5").unwrap()));
+    let mut miner = miner();
+    blockchain.mine_block(&mut miner, Vec::new(), 1).unwrap();
+    assert_eq!(miner.get_wallet().get_balance(), Amount::parse("12.5").unwrap());
+    assert!(blockchain.validate().is_ok());
+    // Rules stored with a whole block reward are still loaded
+    let rules: ConsensusRules = serde_json::from_str(r#"{"initial_difficulty":0,"max_difficulty":0,"target_block_interval":1,"retarget_window":2,"block_reward":50}"#).unwrap();
+    assert_eq!(rules.get_block_reward(), DEFAULT_BLOCK_REWARD);
+}
+
+#[test]
+fn difficulty_retarget() {
+    let mut blockchain = Blockchain::with_rules(ConsensusRules::new(0, 4, 10, 2, Amount::from_integer(50)));
+    let mut miner = miner();
+    blockchain.mine_block(&mut miner, Vec::new(), 1).unwrap();
+    assert_eq!(blockchain.get_next_difficulty(), 0);
+    blockchain.mine_block(&mut miner, Vec::new(), 2).unwrap();
+    // Two blocks in one time unit instead of ten
+    assert_eq!(blockchain.get_next_difficulty(), 1);
+    blockchain.mine_block(&mut miner, Vec::new(), 100).unwrap();
+    assert_eq!(blockchain.get_next_difficulty(), 1);
+    blockchain.mine_block(&mut miner, Vec::new(), 200).unwrap();
+    // Two blocks in one hundred time units instead of ten
+    assert_eq!(blockchain.get_next_difficulty(), 0);
+    assert!(blockchain.validate().is_ok());
+}
+
+#[test]
+fn difficulty_retarget_capped() {
+    let rules = ConsensusRules::new(2, 2, 10, 2, Amount::from_integer(50));
+    let mut blockchain = Blockchain::with_rules(rules);
+    let mut miner = miner();
+    for timestamp in 1..5 {
+        blockchain.mine_block(&mut miner, Vec::new(), timestamp).unwrap();
+    }
+    assert_eq!(blockchain.get_next_difficulty(), 2);
+}
+
+#[test]
+fn append_unsuccessful_wrong_difficulty() {
+    let mut blockchain = Blockchain::with_rules(ConsensusRules::new(4, 4, 10, 5, Amount::from_integer(50)));
+    let mut block = Block::new(0, GENESIS_PREVIOUS_HASH.to_string(), 1, 1, Vec::new());
+    mine_block(&mut block).unwrap();
+    assert!(matches!(blockchain.append_block(block), Err(ChainError::InvalidDifficultyError { height: 0, expected_difficulty: 4, found_difficulty: 1 })));
+}
+
+#[test]
+fn append_unsuccessful_invalid_proof_of_work() {
+    let mut blockchain = Blockchain::with_rules(ConsensusRules::new(8, 8, 10, 5, Amount::from_integer(50)));
+    let mut block = blockchain.create_block(Vec::new(), 1);
+    let nonce = (0..).find(|nonce| {
+        block.set_nonce(*nonce);
+        !block.get_header().meets_difficulty()
+    }).unwrap();
+    block.set_nonce(nonce);
+    assert!(matches!(blockchain.append_block(block), Err(ChainError::InvalidProofOfWorkError { height: 0, difficulty: 8, .. })));
+}
+
+#[test]
+fn append_unsuccessful_wrong_block_reward() {
+    let mut blockchain = Blockchain::new();
+    let coinbase = create_coinbase_transaction(0, "entity_001".to_string(), Amount::from_integer(1000), 1).unwrap();
+    let block = blockchain.create_block(vec![coinbase], 1);
+    match blockchain.append_block(block) {
+        Ok(_) => panic!("expected an error"),
+        Err(ChainError::InvalidBlockRewardError { height: 0, expected_reward, found_reward }) => {
+            assert_eq!(expected_reward, Amount::from_integer(50));
+            assert_eq!(found_reward, Amount::from_integer(1000));
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+}
+
+#[test]
+fn append_unsuccessful_misplaced_coinbase() {
+    let mut blockchain = Blockchain::new();
+    let coinbase = create_coinbase_transaction(0, "entity_001".to_string(), Amount::from_integer(50), 1).unwrap();
+    let block = blockchain.create_block(vec![completed_transaction("tx_0001", "entity_001", "entity_002", 10), coinbase], 1);
+    assert!(matches!(blockchain.append_block(block), Err(ChainError::MisplacedCoinbaseError { height: 0, .. })));
+}
+
+#[test]
+fn serde_round_trip_keeps_rules() {
+    let mut blockchain = Blockchain::with_rules(ConsensusRules::new(4, 4, 10, 5, Amount::from_integer(50)));
+    blockchain.mine_block(&mut miner(), Vec::new(), 1).unwrap();
+    let json = serde_json::to_string(&blockchain).unwrap();
+    let decoded: Blockchain = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded.get_rules(), blockchain.get_rules());
+    assert!(decoded.validate().is_ok());
+}
+
+#[test]
+fn mine_block_collects_fees() {
+    let mut blockchain = Blockchain::with_rules(signing_rules(ConsensusRules::default()));
+    blockchain.mine_block(&mut miner(), Vec::new(), 1).unwrap();
+    let mut miner = Entity::new("entity_003".to_string(), "miner".to_string(), Wallet::new(Amount::from_integer(0)));
+    let mut transaction = completed_transaction("tx_0001", "entity_001", "entity_002", 10);
+    assert!(transaction.set_fee(Amount::from_integer(3)).is_ok());
+    transaction.sign(&key_pair());
+    blockchain.mine_block(&mut miner, vec![transaction], 2).unwrap();
+    assert_eq!(miner.get_wallet().get_balance(), Amount::from_integer(53));
+    let balances = blockchain.get_balances().unwrap();
+    assert_eq!(balances.get("entity_001"), Some(&Amount::from_integer(37)));
+    assert_eq!(balances.get("entity_002"), Some(&Amount::from_integer(10)));
+    assert_eq!(balances.get("entity_003"), Some(&Amount::from_integer(53)));
+    assert!(balances.values().fold(Amount::zero(), |total, balance| total.saturating_add(*balance)).is_zero());
+}
@@ -0,0 +1,300 @@
+extern crate banana_coin;
+use std::io::BufReader;
+use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use banana_coin::chain::*;
+use banana_coin::crypto::KeyPair;
+use banana_coin::error::{ChainError, NetworkError};
+use banana_coin::model::*;
+use banana_coin::model::TransactionState::*;
+use banana_coin::network::*;
+
+fn wait_until<F: Fn() -> bool>(condition: F) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        if condition() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+fn key_pair() -> KeyPair {
+    KeyPair::from_secret_key("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60").unwrap()
+}
+
+fn start_node() -> Node {
+    let mut rules = ConsensusRules::default();
+    rules.register_public_key("entity_001".to_string(), key_pair().get_public_key()).unwrap();
+    Node::start("127.0.0.1:0", Blockchain::with_rules(rules)).unwrap()
+}
+
+fn miner(id: &str) -> Entity {
+    Entity::new(id.to_string(), "miner".to_string(), Wallet::new(Amount::from_integer(0)))
+}
+
+fn transaction(id: &str) -> Transaction {
+    let mut transaction = Transaction::new(id.to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(10),
+                                           "".to_string(), Created { timestamp: 1 }, vec![Created { timestamp: 1 }]).unwrap();
+    transaction.sign(&key_pair());
+    transaction
+}
+
+#[test]
+fn nodes_sync_and_gossip() {
+    let node_1 = start_node();
+    let mut miner_1 = miner("entity_001");
+    node_1.mine_block(&mut miner_1, 1).unwrap();
+    node_1.mine_block(&mut miner_1, 2).unwrap();
+
+    // A node joining the network downloads the missing blocks
+    let node_2 = start_node();
+    node_2.connect(&node_1.get_address()).unwrap();
+    assert!(wait_until(|| node_2.get_blockchain().get_height() == 2));
+    assert_eq!(node_2.get_blockchain().get_tip_hash(), node_1.get_blockchain().get_tip_hash());
+
+    let node_3 = start_node();
+    node_3.connect(&node_2.get_address()).unwrap();
+    assert!(wait_until(|| node_3.get_blockchain().get_height() == 2));
+
+    // Transactions reach every node, even the ones not directly connected
+    node_1.submit_transaction(transaction("tx_0001")).unwrap();
+    assert!(wait_until(|| node_3.get_pending_transactions().len() == 1));
+    assert_eq!(node_2.get_pending_transactions().len(), 1);
+
+    // Blocks reach every node and clear the pending transactions they contain
+    let mut miner_3 = miner("entity_003");
+    let block = node_3.mine_block(&mut miner_3, 3).unwrap();
+    assert_eq!(block.get_transactions().len(), 2);
+    assert_eq!(miner_3.get_wallet().get_balance(), Amount::from_integer(50));
+    assert!(wait_until(|| node_1.get_blockchain().get_tip_hash() == block.get_hash()));
+    assert!(wait_until(|| node_2.get_blockchain().get_tip_hash() == block.get_hash()));
+    assert!(node_1.get_pending_transactions().is_empty());
+    assert!(node_2.get_pending_transactions().is_empty());
+    assert!(node_3.get_pending_transactions().is_empty());
+
+    // Known transactions are not accepted twice
+    assert!(matches!(node_2.submit_transaction(transaction("tx_0001")), Err(NetworkError::DuplicateTransactionError { .. })));
+}
+
+#[test]
+fn nodes_publish_appended_blocks() {
+    let node_1 = start_node();
+    let receiver_1 = node_1.get_event_bus().subscribe_channel();
+    let mut miner_1 = miner("entity_001");
+    let block = node_1.mine_block(&mut miner_1, 1).unwrap();
+    let names: Vec<&str> = receiver_1.try_iter().map(|event| event.get_name()).collect();
+    assert_eq!(names, vec!["CoinsAdded", "BlockAppended"]);
+
+    // A block received from a peer is published by the receiving node
+    let node_2 = start_node();
+    let receiver_2 = node_2.get_event_bus().subscribe_channel();
+    node_2.connect(&node_1.get_address()).unwrap();
+    assert!(wait_until(|| node_2.get_blockchain().get_height() == 1));
+    match receiver_2.recv_timeout(Duration::from_secs(5)) {
+        Ok(banana_coin::event::DomainEvent::BlockAppended { height, hash, .. }) => {
+            assert_eq!(height, 0);
+            assert_eq!(hash, block.get_hash());
+        }
+        _ => panic!("unexpected result"),
+    }
+    assert!(receiver_2.try_recv().is_err());
+}
+
+#[test]
+fn nodes_apply_longest_chain() {
+    let node_1 = start_node();
+    let node_2 = start_node();
+    let mut miner_1 = miner("entity_001");
+    let mut miner_2 = miner("entity_002");
+    node_1.mine_block(&mut miner_1, 1).unwrap();
+    for timestamp in 1..4 {
+        node_2.mine_block(&mut miner_2, timestamp).unwrap();
+    }
+    assert_ne!(node_1.get_blockchain().get_blocks()[0].get_hash(), node_2.get_blockchain().get_blocks()[0].get_hash());
+
+    node_1.connect(&node_2.get_address()).unwrap();
+    assert!(wait_until(|| node_1.get_blockchain().get_height() == 3));
+    assert_eq!(node_1.get_blockchain().get_tip_hash(), node_2.get_blockchain().get_tip_hash());
+
+    // The shorter chain is not applied by the node with the longer one
+    let node_3 = start_node();
+    node_3.mine_block(&mut miner("entity_003"), 1).unwrap();
+    node_3.connect(&node_2.get_address()).unwrap();
+    assert!(wait_until(|| node_3.get_blockchain().get_height() == 3));
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(node_2.get_blockchain().get_tip_hash(), node_1.get_blockchain().get_tip_hash());
+}
+
+#[test]
+fn nodes_return_orphaned_transactions_to_pending() {
+    let node_1 = start_node();
+    let mut miner_1 = miner("entity_001");
+    node_1.mine_block(&mut miner_1, 1).unwrap();
+    // Both chains share the first block, which funds the sender of the orphaned transaction
+    let node_2 = Node::start("127.0.0.1:0", node_1.get_blockchain()).unwrap();
+    node_1.submit_transaction(transaction("tx_0001")).unwrap();
+    node_1.mine_block(&mut miner_1, 2).unwrap();
+    assert!(node_1.get_pending_transactions().is_empty());
+    let mut miner_2 = miner("entity_002");
+    for timestamp in 2..5 {
+        node_2.mine_block(&mut miner_2, timestamp).unwrap();
+    }
+
+    node_1.connect(&node_2.get_address()).unwrap();
+    assert!(wait_until(|| node_1.get_blockchain().get_height() == 4));
+    let pending_transactions = node_1.get_pending_transactions();
+    assert_eq!(pending_transactions.len(), 1);
+    assert_eq!(pending_transactions[0].get_id(), "tx_0001");
+}
+
+#[test]
+fn submit_transaction_unsuccessful_forged() {
+    let node = start_node();
+    node.mine_block(&mut miner("entity_001"), 1).unwrap();
+    let unsigned = Transaction::new("tx_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(10),
+                                    "".to_string(), Created { timestamp: 1 }, vec![Created { timestamp: 1 }]).unwrap();
+    let mut overspending = Transaction::new("tx_0002".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(1000),
+                                            "".to_string(), Created { timestamp: 1 }, vec![Created { timestamp: 1 }]).unwrap();
+    overspending.sign(&key_pair());
+    let mut skipped_nonce = transaction("tx_0003");
+    skipped_nonce.set_nonce(1);
+    skipped_nonce.sign(&key_pair());
+    for forged in [unsigned, overspending, skipped_nonce] {
+        match node.submit_transaction(forged) {
+            Ok(_) => panic!("expected an error"),
+            Err(NetworkError::RejectedTransactionError { error, transaction_id }) => match *error {
+                ChainError::InvalidSignatureError { .. } => assert_eq!(transaction_id, "tx_0001"),
+                ChainError::InsufficientBalanceError { .. } => assert_eq!(transaction_id, "tx_0002"),
+                ChainError::InvalidNonceError { .. } => assert_eq!(transaction_id, "tx_0003"),
+                _ => panic!("unexpected result")
+            },
+            Err(error) => panic!("unexpected error: {:?}", error)
+        }
+    }
+    assert!(node.get_pending_transactions().is_empty());
+
+    // The nonces of the pending transactions follow each other
+    node.submit_transaction(transaction("tx_0004")).unwrap();
+    let mut next = transaction("tx_0005");
+    next.set_nonce(1);
+    next.sign(&key_pair());
+    node.submit_transaction(next).unwrap();
+    assert_eq!(node.get_pending_transactions().len(), 2);
+}
+
+#[test]
+fn handshake_unsuccessful_incompatible_version() {
+    let node = start_node();
+    let stream = TcpStream::connect(node.get_address()).unwrap();
+    Message::Handshake { version: PROTOCOL_VERSION + 1, height: 0 }.write_to(&mut &stream).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    match Message::read_from(&mut reader) {
+        Ok(Some(Message::Handshake { version, .. })) => assert_eq!(version, PROTOCOL_VERSION),
+        _ => panic!("unexpected result")
+    }
+    // The node closes the connection and does not keep the peer
+    match Message::read_from(&mut reader) {
+        Ok(None) | Err(_) => {},
+        Ok(Some(_)) => panic!("expected no message")
+    }
+    assert!(node.get_peer_addresses().is_empty());
+}
+
+#[test]
+fn submit_transaction_unsuccessful_processed() {
+    let node = start_node();
+    node.mine_block(&mut miner("entity_001"), 1).unwrap();
+    let mut completed = Transaction::new("tx_0001".to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(10),
+                                         "".to_string(), Completed { timestamp: 3 },
+                                         vec![Created { timestamp: 1 }, OnProcess { timestamp: 2 }, Completed { timestamp: 3 }]).unwrap();
+    completed.sign(&key_pair());
+    match node.submit_transaction(completed) {
+        Ok(_) => panic!("expected an error"),
+        Err(NetworkError::ProcessedTransactionError { transaction_id, state: Completed { .. } }) => assert_eq!(transaction_id, "tx_0001"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(node.get_pending_transactions().is_empty());
+}
+
+#[test]
+fn mined_transaction_moves_coins() {
+    let node = start_node();
+    let mut miner_1 = miner("entity_001");
+    node.mine_block(&mut miner_1, 1).unwrap();
+    let mut transaction = transaction("tx_0001");
+    assert!(transaction.set_fee(Amount::from_integer(2)).is_ok());
+    transaction.sign(&key_pair());
+    node.submit_transaction(transaction).unwrap();
+    node.mine_block(&mut miner("entity_003"), 2).unwrap();
+    let balances = node.get_blockchain().get_balances().unwrap();
+    assert_eq!(balances.get("entity_001"), Some(&Amount::from_integer(38)));
+    assert_eq!(balances.get("entity_002"), Some(&Amount::from_integer(10)));
+    assert_eq!(balances.get("entity_003"), Some(&Amount::from_integer(52)));
+}
+
+#[test]
+fn pending_transactions_spend_only_held_coins() {
+    let node = start_node();
+    node.mine_block(&mut miner("entity_001"), 1).unwrap();
+    let mut transactions = Vec::new();
+    for (nonce, id) in ["tx_0001", "tx_0002"].iter().enumerate() {
+        let mut transaction = Transaction::new(id.to_string(), "entity_001".to_string(), "entity_002".to_string(), Amount::from_integer(30),
+                                               "".to_string(), Created { timestamp: 1 }, vec![Created { timestamp: 1 }]).unwrap();
+        transaction.set_nonce(nonce as u64);
+        transaction.sign(&key_pair());
+        transactions.push(transaction);
+    }
+    node.submit_transaction(transactions[0].clone()).unwrap();
+    match node.submit_transaction(transactions[1].clone()) {
+        Ok(_) => panic!("expected an error"),
+        Err(NetworkError::RejectedTransactionError { error, .. }) => match *error {
+            ChainError::InsufficientBalanceError { height: 1, transaction_id, .. } => assert_eq!(transaction_id, "tx_0002"),
+            _ => panic!("unexpected result")
+        },
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+
+    // Once the first transaction is in a block, the pending state starts again from the new chain
+    node.mine_block(&mut miner("entity_001"), 2).unwrap();
+    assert!(node.get_pending_transactions().is_empty());
+    node.submit_transaction(transactions[1].clone()).unwrap();
+    assert_eq!(node.get_pending_transactions().len(), 1);
+}
+
+#[test]
+fn peers_are_capped() {
+    let node = start_node();
+    let handshake = |stream: &TcpStream| {
+        Message::Handshake { version: PROTOCOL_VERSION, height: 0 }.write_to(&mut &*stream).unwrap();
+        Message::read_from(&mut BufReader::new(stream.try_clone().unwrap()))
+    };
+    let streams: Vec<TcpStream> = (0..MAX_PEERS).map(|_| TcpStream::connect(node.get_address()).unwrap()).collect();
+    for stream in streams.iter() {
+        assert!(matches!(handshake(stream), Ok(Some(Message::Handshake { .. }))));
+    }
+    assert!(wait_until(|| node.get_peer_addresses().len() == MAX_PEERS));
+    // The node does not answer the handshake of one peer too many
+    let refused = TcpStream::connect(node.get_address()).unwrap();
+    assert!(!matches!(handshake(&refused), Ok(Some(_))));
+    let other_node = start_node();
+    assert!(matches!(node.connect(&other_node.get_address()), Err(NetworkError::TooManyPeersError { .. })));
+    // A disconnected peer releases its connection
+    streams[0].shutdown(std::net::Shutdown::Both).unwrap();
+    assert!(wait_until(|| node.get_peer_addresses().len() == MAX_PEERS - 1));
+    assert!(node.connect(&other_node.get_address()).is_ok());
+}
+
+#[test]
+fn nodes_ask_for_blocks_after_their_chain() {
+    let node = start_node();
+    node.mine_block(&mut miner("entity_001"), 1).unwrap();
+    let stream = TcpStream::connect(node.get_address()).unwrap();
+    Message::Handshake { version: PROTOCOL_VERSION, height: 5 }.write_to(&mut &stream).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    assert!(matches!(Message::read_from(&mut reader), Ok(Some(Message::Handshake { height: 1, .. }))));
+    assert!(matches!(Message::read_from(&mut reader), Ok(Some(Message::GetBlocks { from_height: 1 }))));
+}
@@ -0,0 +1,352 @@
+extern crate banana_coin;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use banana_coin::api::*;
+use banana_coin::audit::{AuditLog, AuditSubscriber};
+use banana_coin::cli::{run_command_with_audit, Command};
+use banana_coin::crypto::KeyPair;
+use banana_coin::event::EventBus;
+use banana_coin::model::{Amount, Entity, Transaction, Wallet};
+use banana_coin::model::TransactionState::*;
+use banana_coin::storage::{JsonLinesStorage, MemoryStorage, Storage};
+
+const JOHN_SECRET_KEY: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+const JANE_SECRET_KEY: &str = "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb";
+
+fn http_request(address: &str, method: &str, target: &str, headers: &[(&str, &str)], body: &str) -> (u16, Vec<(String, String)>, Value) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    let mut request = format!("{} {} HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\n", method, target, address, body.len());
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let mut lines = head.split("\r\n");
+    let status = lines.next().unwrap().split_whitespace().nth(1).unwrap().parse().unwrap();
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+    let body = if body.is_empty() { Value::Null } else { serde_json::from_str(body).unwrap() };
+    (status, headers, body)
+}
+
+fn get(address: &str, target: &str) -> (u16, Value) {
+    let (status, _, body) = http_request(address, "GET", target, &[], "");
+    (status, body)
+}
+
+fn post_transfer(address: &str, idempotency_key: &str, body: Value) -> (u16, Vec<(String, String)>, Value) {
+    http_request(address, "POST", "/transfers", &[("Idempotency-Key", idempotency_key), ("Content-Type", "application/json")], &body.to_string())
+}
+
+fn public_key(secret_key: &str) -> String {
+    KeyPair::from_secret_key(secret_key).unwrap().get_public_key()
+}
+
+/// Return the body of a POST /transfers request signed with the given secret key.
+fn transfer_body(transaction_id: &str, from_entity_id: &str, to_entity_id: &str, amount: &str, nonce: u64, secret_key: &str) -> Value {
+    let mut transaction = Transaction::new(
+        transaction_id.to_string(),
+        from_entity_id.to_string(),
+        to_entity_id.to_string(),
+        amount.parse().unwrap(),
+        "Moving coins.".to_string(),
+        Created { timestamp: 1 },
+        vec![Created { timestamp: 1 }],
+    ).unwrap();
+    transaction.set_nonce(nonce);
+    transaction.sign(&KeyPair::from_secret_key(secret_key).unwrap());
+    json!({
+        "from_entity_id": from_entity_id,
+        "to_entity_id": to_entity_id,
+        "amount": amount,
+        "transaction_id": transaction_id,
+        "description": "Moving coins.",
+        "nonce": nonce,
+        "signature": transaction.get_signature().unwrap(),
+    })
+}
+
+/// Deposit coins to an entity as the operator does, with the command-line tool.
+fn deposit<S: Storage>(storage: &Arc<Mutex<S>>, entity_id: &str, amount: i64, audit_subscriber: Option<&AuditSubscriber>) {
+    let command = Command::WalletDeposit {
+        entity_id: entity_id.to_string(),
+        amount: Amount::from_integer(amount),
+        transaction_id: None,
+        description: None,
+    };
+    run_command_with_audit(&mut *storage.lock().unwrap(), &command, 1, &EventBus::new(), audit_subscriber).unwrap();
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers.iter().find(|(header, _)| header == name).map(|(_, value)| value.as_str())
+}
+
+fn start_servers() -> (RpcServer, RestServer) {
+    let storage = Arc::new(Mutex::new(MemoryStorage::new()));
+    let rpc_server = RpcServer::start("127.0.0.1:0", Arc::clone(&storage), ServerOptions::new()).unwrap();
+    let rest_server = RestServer::start("127.0.0.1:0", Arc::clone(&storage), ServerOptions::new()).unwrap();
+    let requests = json!([
+        { "jsonrpc": "2.0", "method": "entity_create", "params": { "entity_id": "entity_001", "name": "john", "public_key": public_key(JOHN_SECRET_KEY) }, "id": 1 },
+        { "jsonrpc": "2.0", "method": "entity_create", "params": { "entity_id": "entity_002", "name": "jane", "public_key": public_key(JANE_SECRET_KEY) }, "id": 2 }
+    ]);
+    let (status, _, _) = http_request(&rpc_server.get_address(), "POST", "/", &[], &requests.to_string());
+    assert_eq!(status, 200);
+    deposit(&storage, "entity_001", 100, None);
+    (rpc_server, rest_server)
+}
+
+#[test]
+fn rest_get_entity() {
+    let (_rpc_server, rest_server) = start_servers();
+    let address = rest_server.get_address();
+    let (status, entity) = get(&address, "/entities/entity_001");
+    assert_eq!(status, 200);
+    assert_eq!(entity["id"], "entity_001");
+    assert_eq!(entity["name"], "john");
+    assert_eq!(entity["wallet"]["balances"]["BNC"], "100");
+
+    let (status, error) = get(&address, "/entities/entity_003");
+    assert_eq!(status, 404);
+    assert_eq!(error["error"]["EntityNotFoundError"]["entity_id"], "entity_003");
+    assert!(error["message"].as_str().unwrap().contains("entity_003"));
+
+    let (status, error) = get(&address, "/wallets/entity_001");
+    assert_eq!(status, 404);
+    assert!(error["error"]["RouteNotFoundError"].is_object());
+    let (status, headers, _) = http_request(&address, "DELETE", "/entities/entity_001", &[], "");
+    assert_eq!(status, 405);
+    assert_eq!(header(&headers, "allow"), Some("GET"));
+}
+
+#[test]
+fn rest_post_transfer_idempotent() {
+    let (_rpc_server, rest_server) = start_servers();
+    let address = rest_server.get_address();
+    let transfer = transfer_body("id_0001", "entity_001", "entity_002", "30.5", 0, JOHN_SECRET_KEY);
+    let (status, headers, transaction) = post_transfer(&address, "key_0001", transfer.clone());
+    assert_eq!(status, 201);
+    assert_eq!(header(&headers, "idempotent-replayed"), None);
+    assert_eq!(transaction["amount"], "30.5");
+    assert!(transaction["current_state"]["Completed"].is_object());
+
+    // The retry is answered with the first transaction and does not move the coins again
+    let (status, headers, replayed) = post_transfer(&address, "key_0001", transfer.clone());
+    assert_eq!(status, 201);
+    assert_eq!(header(&headers, "idempotent-replayed"), Some("true"));
+    assert_eq!(replayed, transaction);
+    assert_eq!(get(&address, "/entities/entity_001").1["wallet"]["balances"]["BNC"], "69.5");
+
+    let (status, _, error) = post_transfer(&address, "key_0001", transfer_body("id_0002", "entity_001", "entity_002", "1", 1, JOHN_SECRET_KEY));
+    assert_eq!(status, 422);
+    assert_eq!(error["error"]["IdempotencyKeyReuseError"]["idempotency_key"], "key_0001");
+
+    // The same transfer under another key is refused, since its transaction is already stored
+    assert_eq!(post_transfer(&address, "key_0002", transfer).0, 409);
+    let (status, _, transaction) = post_transfer(&address, "key_0003", transfer_body("id_0002", "entity_001", "entity_002", "30.5", 1, JOHN_SECRET_KEY));
+    assert_eq!(status, 201);
+    assert!(transaction["current_state"]["Completed"].is_object());
+    assert_eq!(get(&address, "/entities/entity_002").1["wallet"]["balances"]["BNC"], "61.0");
+}
+
+#[test]
+fn servers_publish_transfer_events() {
+    let storage = Arc::new(Mutex::new(MemoryStorage::new()));
+    let event_bus = EventBus::new();
+    let receiver = event_bus.subscribe_channel();
+    let options = ServerOptions::new().with_event_bus(event_bus);
+    let rpc_server = RpcServer::start("127.0.0.1:0", Arc::clone(&storage), options.clone()).unwrap();
+    let rest_server = RestServer::start("127.0.0.1:0", Arc::clone(&storage), options).unwrap();
+    let requests = json!([
+        { "jsonrpc": "2.0", "method": "entity_create", "params": { "entity_id": "entity_001", "name": "john", "public_key": public_key(JOHN_SECRET_KEY) }, "id": 1 },
+        { "jsonrpc": "2.0", "method": "entity_create", "params": { "entity_id": "entity_002", "name": "jane", "public_key": public_key(JANE_SECRET_KEY) }, "id": 2 }
+    ]);
+    http_request(&rpc_server.get_address(), "POST", "/", &[], &requests.to_string());
+    deposit(&storage, "entity_001", 100, None);
+    let transfer = transfer_body("id_0001", "entity_001", "entity_002", "30", 0, JOHN_SECRET_KEY);
+    assert_eq!(post_transfer(&rest_server.get_address(), "key_0001", transfer.clone()).0, 201);
+
+    // A replayed transfer is not run again, so it publishes nothing
+    assert_eq!(post_transfer(&rest_server.get_address(), "key_0001", transfer).0, 201);
+    let names: Vec<&str> = receiver.try_iter().map(|event| event.get_name()).collect();
+    assert_eq!(names, vec!["EntityCreated", "PublicKeySet", "EntityCreated", "PublicKeySet", "TransactionStateChanged",
+                           "CoinsRemoved", "CoinsAdded", "TransactionStateChanged"]);
+}
+
+#[test]
+fn servers_audit_changes() {
+    let path = std::env::temp_dir().join(format!("banana_coin_{}_servers_audit.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let storage = Arc::new(Mutex::new(MemoryStorage::new()));
+    let audit_subscriber = AuditSubscriber::new(AuditLog::open(&path).unwrap(), "operator".to_string());
+    let options = ServerOptions::new().with_audit_subscriber(audit_subscriber.clone());
+    let rpc_server = RpcServer::start("127.0.0.1:0", Arc::clone(&storage), options.clone()).unwrap();
+    let rest_server = RestServer::start("127.0.0.1:0", Arc::clone(&storage), options).unwrap();
+    let requests = json!([
+        { "jsonrpc": "2.0", "method": "entity_create", "params": { "entity_id": "entity_001", "name": "john", "public_key": public_key(JOHN_SECRET_KEY) }, "id": 1 },
+        { "jsonrpc": "2.0", "method": "entity_create", "params": { "entity_id": "entity_002", "name": "jane" }, "id": 2 }
+    ]);
+    http_request(&rpc_server.get_address(), "POST", "/", &[], &requests.to_string());
+    // The public key of the first entity is audited along with its creation
+    assert_eq!(audit_subscriber.get_record_count(), 3);
+    deposit(&storage, "entity_001", 100, Some(&audit_subscriber));
+    deposit(&storage, "entity_002", 5, Some(&audit_subscriber));
+    assert_eq!(audit_subscriber.get_record_count(), 5);
+    let transfer = transfer_body("id_0001", "entity_001", "entity_002", "30", 0, JOHN_SECRET_KEY);
+    assert_eq!(post_transfer(&rest_server.get_address(), "key_0001", transfer).0, 201);
+    assert_eq!(audit_subscriber.get_record_count(), 7);
+
+    let last_record = AuditLog::verify(&path).unwrap().unwrap();
+    assert_eq!(last_record.get_actor(), "operator");
+    assert_eq!(last_record.get_entity_id(), "entity_002");
+    assert_eq!(last_record.get_after(), Some(Amount::from_integer(35)));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn rest_post_transfer_unsuccessful() {
+    let (_rpc_server, rest_server) = start_servers();
+    let address = rest_server.get_address();
+    let transfer = transfer_body("id_0001", "entity_001", "entity_002", "150", 0, JOHN_SECRET_KEY);
+    let (status, _, error) = http_request(&address, "POST", "/transfers", &[], &transfer.to_string());
+    assert_eq!(status, 400);
+    assert_eq!(error["error"], "MissingIdempotencyKeyError");
+
+    let (status, _, error) = post_transfer(&address, "key_0001", transfer.clone());
+    assert_eq!(status, 422);
+    let error = &error["error"]["TransferOperationError"]["error"]["EntityOperationError"];
+    assert_eq!(error["entity_id"], "entity_001");
+    // The failure is replayed as well, the failed transaction is recorded once
+    let (status, headers, _) = post_transfer(&address, "key_0001", transfer);
+    assert_eq!(status, 422);
+    assert_eq!(header(&headers, "idempotent-replayed"), Some("true"));
+    let (_, page) = get(&address, "/entities/entity_001/transactions?state=Failed");
+    assert_eq!(page["transactions"].as_array().unwrap().len(), 1);
+    assert_eq!(page["transactions"][0]["id"], "id_0001");
+
+    let (status, _, error) = post_transfer(&address, "key_0002", json!({ "from_entity_id": "entity_001", "to_entity_id": "entity_002", "amount": "ten" }));
+    assert_eq!(status, 400);
+    assert!(error["error"]["InvalidBodyError"].is_object());
+    let (status, _, _) = post_transfer(&address, "key_0003", transfer_body("id_0002", "entity_001", "entity_003", "1", 1, JOHN_SECRET_KEY));
+    assert_eq!(status, 404);
+    let (status, _, _) = post_transfer(&address, "key_0004", transfer_body("id_0001", "entity_002", "entity_001", "1", 0, JANE_SECRET_KEY));
+    assert_eq!(status, 409);
+    // The transfers must be signed by the sender
+    let (status, _, error) = post_transfer(&address, "key_0005", json!({ "from_entity_id": "entity_001", "to_entity_id": "entity_002", "amount": "1" }));
+    assert_eq!(status, 400);
+    assert!(error["error"]["InvalidBodyError"].is_object());
+    let (status, _, error) = post_transfer(&address, "key_0006", transfer_body("id_0002", "entity_001", "entity_002", "1", 1, JANE_SECRET_KEY));
+    assert_eq!(status, 422);
+    assert!(error["error"]["TransferOperationError"]["error"]["SignatureVerificationError"].is_object());
+}
+
+#[test]
+fn rest_list_transactions_paginated() {
+    let (_rpc_server, rest_server) = start_servers();
+    let address = rest_server.get_address();
+    for (index, amount) in ["10", "20", "500", "30", "40"].iter().enumerate() {
+        // A failed transfer consumes the nonce of the sender too
+        let transfer = transfer_body(&format!("id_000{}", index), "entity_001", "entity_002", amount, index as u64, JOHN_SECRET_KEY);
+        post_transfer(&address, &format!("key_000{}", index), transfer);
+    }
+
+    let (status, page) = get(&address, "/entities/entity_002/transactions?state=Completed&limit=3");
+    assert_eq!(status, 200);
+    let ids: Vec<&str> = page["transactions"].as_array().unwrap().iter().map(|transaction| transaction["id"].as_str().unwrap()).collect();
+    assert_eq!(ids, vec!["id_0000", "id_0001", "id_0003"]);
+    let cursor = page["next_cursor"].as_str().unwrap().to_string();
+    assert_eq!(cursor, "id_0004");
+    let (_, page) = get(&address, &format!("/entities/entity_002/transactions?state=Completed&limit=3&cursor={}", cursor));
+    assert_eq!(page["transactions"].as_array().unwrap().len(), 1);
+    assert_eq!(page["transactions"][0]["id"], "id_0004");
+    assert_eq!(page["next_cursor"], Value::Null);
+
+    let (_, page) = get(&address, "/entities/entity_001/transactions?state=Failed");
+    assert_eq!(page["transactions"][0]["id"], "id_0002");
+    let (_, page) = get(&address, "/entities/entity_001/transactions?from=2&to=3");
+    assert!(page["transactions"].as_array().unwrap().is_empty());
+
+    assert_eq!(get(&address, "/entities/entity_001/transactions?state=Done").0, 400);
+    assert_eq!(get(&address, "/entities/entity_001/transactions?limit=0").0, 400);
+    assert_eq!(get(&address, "/entities/entity_001/transactions?cursor=abc").0, 400);
+    // The cursor must be a transaction of the entity
+    assert_eq!(get(&address, "/entities/entity_002/transactions?cursor=id_0002").0, 200);
+    assert_eq!(get(&address, "/entities/entity_003/transactions?cursor=id_0002").0, 404);
+    let (status, error) = get(&address, "/entities/entity_001/transactions?sort=asc");
+    assert_eq!(status, 400);
+    assert_eq!(error["error"]["InvalidParameterError"]["parameter"], "sort");
+    assert_eq!(get(&address, "/entities/entity_003/transactions").0, 404);
+}
+
+fn post_request(idempotency_key: &str, body: Value) -> HttpRequest {
+    let request = format!("POST /transfers HTTP/1.1\r\nIdempotency-Key: {}\r\nContent-Length: {}\r\n\r\n{}", idempotency_key, body.to_string().len(), body);
+    HttpRequest::read_from(&mut request.as_bytes()).unwrap()
+}
+
+fn fill_storage<S: Storage>(storage: &mut S) {
+    let mut entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    entity.set_public_key(public_key(JOHN_SECRET_KEY));
+    storage.save_entity(&entity).unwrap();
+    storage.save_entity(&Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)))).unwrap();
+}
+
+#[test]
+fn rest_idempotency_keys_survive_restart() {
+    let path = std::env::temp_dir().join(format!("banana_coin_{}_rest_restart.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let transfer = transfer_body("id_0001", "entity_001", "entity_002", "10", 0, JOHN_SECRET_KEY);
+    let transaction = {
+        let mut storage = JsonLinesStorage::open(&path).unwrap();
+        fill_storage(&mut storage);
+        let storage = Arc::new(Mutex::new(storage));
+        let server = RestServer::start("127.0.0.1:0", Arc::clone(&storage), ServerOptions::new()).unwrap();
+        let (status, _, transaction) = post_transfer(&server.get_address(), "key_0001", transfer.clone());
+        assert_eq!(status, 201);
+        server.shutdown();
+        // The storage file stays locked until the threads of the server are done with the storage
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Arc::strong_count(&storage) > 1 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        transaction
+    };
+    // The retry reaches a new server over the same storage file and does not move the coins again
+    let api = RestApi::new();
+    let mut storage = JsonLinesStorage::open(&path).unwrap();
+    let response = api.handle(&mut storage, &post_request("key_0001", transfer), 2);
+    assert_eq!(response.get_status(), 201);
+    assert_eq!(response.get_header(IDEMPOTENT_REPLAYED_HEADER), Some("true"));
+    assert_eq!(serde_json::from_slice::<Value>(response.get_body()).unwrap(), transaction);
+    assert_eq!(storage.load_entity("entity_001").unwrap().unwrap().get_wallet().get_balance(), Amount::from_integer(90));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn rest_idempotency_keys_expire() {
+    let api = RestApi::new().with_idempotency_key_ttl(10);
+    let mut storage = MemoryStorage::new();
+    fill_storage(&mut storage);
+    let transfer = transfer_body("id_0001", "entity_001", "entity_002", "10", 0, JOHN_SECRET_KEY);
+    assert_eq!(api.handle(&mut storage, &post_request("key_0001", transfer.clone()), 100).get_status(), 201);
+    let response = api.handle(&mut storage, &post_request("key_0001", transfer.clone()), 110);
+    assert_eq!(response.get_header(IDEMPOTENT_REPLAYED_HEADER), Some("true"));
+    let other_transfer = transfer_body("id_0002", "entity_001", "entity_002", "10", 1, JOHN_SECRET_KEY);
+    assert_eq!(api.handle(&mut storage, &post_request("key_0002", other_transfer), 111).get_status(), 201);
+    // Once expired, the key is forgotten and the request is run again, the signed transaction is not moved twice though
+    assert!(storage.load_idempotency_record("key_0001").unwrap().is_none());
+    let response = api.handle(&mut storage, &post_request("key_0001", transfer), 111);
+    assert_eq!(response.get_status(), 409);
+    assert_eq!(response.get_header(IDEMPOTENT_REPLAYED_HEADER), None);
+    assert_eq!(storage.load_entity("entity_001").unwrap().unwrap().get_wallet().get_balance(), Amount::from_integer(80));
+}
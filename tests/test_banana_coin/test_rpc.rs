@@ -0,0 +1,324 @@
+extern crate banana_coin;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use banana_coin::api::*;
+use banana_coin::cli::{run_command, Command};
+use banana_coin::crypto::KeyPair;
+use banana_coin::error::ApiError;
+use banana_coin::model::{Amount, Transaction};
+use banana_coin::model::TransactionState::*;
+use banana_coin::storage::MemoryStorage;
+use banana_coin::webhook::{sign_registration, RetryPolicy, WebhookDispatcher};
+
+const JOHN_SECRET_KEY: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+const JANE_SECRET_KEY: &str = "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb";
+
+fn http_request(address: &str, method: &str, body: &str) -> (u16, String) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(stream, "{} / HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+           method, address, body.len(), body).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, body.to_string())
+}
+
+fn call(address: &str, method: &str, params: Value) -> Value {
+    let request = json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": 1 });
+    let (status, body) = http_request(address, "POST", &request.to_string());
+    assert_eq!(status, 200);
+    let response: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(response["jsonrpc"], "2.0");
+    assert_eq!(response["id"], 1);
+    response
+}
+
+fn public_key(secret_key: &str) -> String {
+    KeyPair::from_secret_key(secret_key).unwrap().get_public_key()
+}
+
+/// Return the params of a transfer_submit call signed with the given secret key.
+fn transfer_params(transaction_id: &str, from_entity_id: &str, to_entity_id: &str, amount: &str, nonce: u64, secret_key: &str) -> Value {
+    let mut transaction = Transaction::new(
+        transaction_id.to_string(),
+        from_entity_id.to_string(),
+        to_entity_id.to_string(),
+        amount.parse().unwrap(),
+        "Moving coins.".to_string(),
+        Created { timestamp: 1 },
+        vec![Created { timestamp: 1 }],
+    ).unwrap();
+    transaction.set_nonce(nonce);
+    transaction.sign(&KeyPair::from_secret_key(secret_key).unwrap());
+    json!({
+        "from_entity_id": from_entity_id,
+        "to_entity_id": to_entity_id,
+        "amount": amount,
+        "transaction_id": transaction_id,
+        "description": "Moving coins.",
+        "nonce": nonce,
+        "signature": transaction.get_signature().unwrap(),
+    })
+}
+
+fn start_server() -> RpcServer {
+    start_server_with_options(ServerOptions::new())
+}
+
+fn start_server_with_options(options: ServerOptions) -> RpcServer {
+    // The coins are deposited by the operator, with the command-line tool
+    let mut storage = MemoryStorage::new();
+    let command = Command::EntityCreate {
+        entity_id: "entity_001".to_string(),
+        name: "john".to_string(),
+        balance: Amount::from_integer(100),
+        public_key: Some(public_key(JOHN_SECRET_KEY)),
+    };
+    run_command(&mut storage, &command, 1).unwrap();
+    let server = RpcServer::start("127.0.0.1:0", Arc::new(Mutex::new(storage)), options).unwrap();
+    let address = server.get_address();
+    call(&address, "entity_create", json!({ "entity_id": "entity_002", "name": "jane", "public_key": public_key(JANE_SECRET_KEY) }));
+    server
+}
+
+#[test]
+fn rpc_transfer_successful() {
+    let server = start_server();
+    let address = server.get_address();
+    let response = call(&address, "wallet_getBalance", json!({ "entity_id": "entity_001" }));
+    assert_eq!(response["result"], json!({ "entity_id": "entity_001", "balance": "100" }));
+
+    let response = call(&address, "transfer_submit", transfer_params("id_0001", "entity_001", "entity_002", "30.5", 0, JOHN_SECRET_KEY));
+    assert_eq!(response["result"]["id"], "id_0001");
+    assert_eq!(response["result"]["amount"], "30.5");
+    assert!(response["result"]["current_state"]["Completed"].is_object());
+    assert_eq!(call(&address, "wallet_getBalance", json!({ "entity_id": "entity_002" }))["result"]["balance"], "30.5");
+
+    let response = call(&address, "tx_get", json!({ "transaction_id": "id_0001" }));
+    assert_eq!(response["result"]["from_entity_id"], "entity_001");
+    let response = call(&address, "tx_history", json!({ "entity_id": "entity_002" }));
+    assert_eq!(response["result"].as_array().unwrap().len(), 1);
+    let response = call(&address, "tx_history", json!({}));
+    assert_eq!(response["result"][0]["id"], "id_0001");
+}
+
+#[test]
+fn rpc_transfer_unsuccessful_keeps_error_fields() {
+    let server = start_server();
+    let address = server.get_address();
+    let response = call(&address, "transfer_submit", transfer_params("id_0001", "entity_001", "entity_002", "150", 0, JOHN_SECRET_KEY));
+    assert_eq!(response["error"]["code"], TRANSFER_FAILED);
+    let error = &response["error"]["data"]["TransferOperationError"]["error"]["EntityOperationError"];
+    assert_eq!(error["entity_id"], "entity_001");
+    let wallet_error = &error["error"]["WalletOperationError"]["error"]["RemoveCoinNegativeBalanceError"];
+    assert_eq!(wallet_error["current_amount"], "100");
+    assert_eq!(wallet_error["removed_amount"], "150");
+    assert_eq!(wallet_error["asset"], "BNC");
+    // The failed transaction is recorded
+    assert!(call(&address, "tx_get", json!({ "transaction_id": "id_0001" }))["result"]["current_state"]["Failed"].is_object());
+
+    // A transfer signed by someone else than the sender is refused and not recorded
+    let response = call(&address, "transfer_submit", transfer_params("id_0002", "entity_001", "entity_002", "10", 1, JANE_SECRET_KEY));
+    assert_eq!(response["error"]["code"], TRANSFER_FAILED);
+    assert!(response["error"]["data"]["TransferOperationError"]["error"]["SignatureVerificationError"].is_object());
+    let response = call(&address, "transfer_submit", transfer_params("id_0002", "entity_001", "entity_002", "10", 0, JOHN_SECRET_KEY));
+    assert!(response["error"]["data"]["TransferOperationError"]["error"]["ReplayedNonceError"].is_object());
+    assert_eq!(call(&address, "tx_get", json!({ "transaction_id": "id_0002" }))["error"]["code"], TRANSACTION_NOT_FOUND);
+
+    let response = call(&address, "wallet_getBalance", json!({ "entity_id": "entity_003" }));
+    assert_eq!(response["error"]["code"], ENTITY_NOT_FOUND);
+    assert_eq!(response["error"]["data"]["EntityNotFoundError"]["entity_id"], "entity_003");
+    let response = call(&address, "entity_create", json!({ "entity_id": "entity_001", "name": "jill" }));
+    assert_eq!(response["error"]["code"], DUPLICATE_ENTITY);
+    // The clients can not create coins
+    let response = call(&address, "entity_create", json!({ "entity_id": "entity_003", "name": "jack", "balance": "1000" }));
+    assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    let response = call(&address, "entity_create", json!({ "entity_id": "entity_003", "name": "jack" }));
+    assert_eq!(response["result"]["wallet"]["balances"], json!({ "BNC": "0" }));
+    let response = call(&address, "tx_get", json!({ "transaction_id": "id_0002" }));
+    assert_eq!(response["error"]["code"], TRANSACTION_NOT_FOUND);
+}
+
+#[test]
+fn rpc_unsuccessful_invalid_requests() {
+    let server = start_server();
+    let address = server.get_address();
+    assert_eq!(call(&address, "wallet_burn", json!({}))["error"]["code"], METHOD_NOT_FOUND);
+    // The webhook methods are found only if the server has a webhook dispatcher
+    assert_eq!(call(&address, "webhook_unregister", json!({ "entity_id": "entity_001", "signature": "" }))["error"]["code"], METHOD_NOT_FOUND);
+    assert_eq!(call(&address, "wallet_getBalance", json!({ "entity": "entity_001" }))["error"]["code"], INVALID_PARAMS);
+    assert_eq!(call(&address, "transfer_submit", json!({ "from_entity_id": "entity_001", "to_entity_id": "entity_002", "amount": "ten" }))["error"]["code"], INVALID_PARAMS);
+    // The transfers must be signed by the sender
+    assert_eq!(call(&address, "transfer_submit", json!({ "from_entity_id": "entity_001", "to_entity_id": "entity_002", "amount": "10" }))["error"]["code"], INVALID_PARAMS);
+    let mut params = transfer_params("id_0001", "entity_001", "entity_002", "10", 0, JOHN_SECRET_KEY);
+    params.as_object_mut().unwrap().remove("transaction_id");
+    assert_eq!(call(&address, "transfer_submit", params)["error"]["code"], INVALID_PARAMS);
+
+    let (status, body) = http_request(&address, "POST", "{\"jsonrpc\": \"2.0\", \"method\"");
+    assert_eq!(status, 200);
+    let response: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(response["error"]["code"], PARSE_ERROR);
+    assert_eq!(response["id"], Value::Null);
+
+    let (_, body) = http_request(&address, "POST", "{\"jsonrpc\": \"1.0\", \"method\": \"tx_history\", \"id\": 7}");
+    let response: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(response["error"]["code"], INVALID_REQUEST);
+    assert_eq!(response["id"], 7);
+
+    let (status, _) = http_request(&address, "GET", "");
+    assert_eq!(status, 405);
+}
+
+#[test]
+fn rpc_webhook_registration() {
+    let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    let server = start_server_with_options(ServerOptions::new().with_webhook_dispatcher(dispatcher.clone()));
+    let address = server.get_address();
+    let url = "http://localhost:8080/webhooks";
+    let register_params = |entity_id: &str, url: &str, secret_key: &str| json!({
+        "entity_id": entity_id,
+        "url": url,
+        "secret": "john_secret",
+        "signature": sign_registration(entity_id, Some(url), &KeyPair::from_secret_key(secret_key).unwrap()),
+    });
+
+    // Only the entity can register its endpoint
+    let response = call(&address, "webhook_register", register_params("entity_001", url, JANE_SECRET_KEY));
+    assert_eq!(response["error"]["code"], WEBHOOK_OPERATION_FAILED);
+    assert!(response["error"]["data"]["SignatureVerificationError"].is_object());
+    assert_eq!(call(&address, "webhook_register", register_params("entity_003", url, JOHN_SECRET_KEY))["error"]["code"], ENTITY_NOT_FOUND);
+    assert_eq!(call(&address, "webhook_register", register_params("entity_001", "ftp://localhost/", JOHN_SECRET_KEY))["error"]["code"], INVALID_PARAMS);
+    assert!(dispatcher.get_endpoint("entity_001").is_none());
+
+    let response = call(&address, "webhook_register", register_params("entity_001", url, JOHN_SECRET_KEY));
+    assert_eq!(response["result"], json!({ "entity_id": "entity_001", "url": url }));
+    assert_eq!(dispatcher.get_endpoint("entity_001").unwrap().get_url(), url);
+    // The transfers run by the server are queued for delivery
+    call(&address, "transfer_submit", transfer_params("id_0001", "entity_001", "entity_002", "10", 0, JOHN_SECRET_KEY));
+    let pending = dispatcher.get_pending_deliveries();
+    assert!(pending.iter().any(|delivery| delivery.get_entity_id() == "entity_001" && delivery.get_transaction_id() == "id_0001"));
+
+    // The signature of a registration can not remove the endpoint
+    let signature = sign_registration("entity_001", Some(url), &KeyPair::from_secret_key(JOHN_SECRET_KEY).unwrap());
+    let response = call(&address, "webhook_unregister", json!({ "entity_id": "entity_001", "signature": signature }));
+    assert_eq!(response["error"]["code"], WEBHOOK_OPERATION_FAILED);
+    let signature = sign_registration("entity_001", None, &KeyPair::from_secret_key(JOHN_SECRET_KEY).unwrap());
+    let response = call(&address, "webhook_unregister", json!({ "entity_id": "entity_001", "signature": signature }));
+    assert_eq!(response["result"], json!({ "entity_id": "entity_001" }));
+    assert!(dispatcher.get_endpoint("entity_001").is_none());
+}
+
+#[test]
+fn rpc_batch_and_notifications() {
+    let server = start_server();
+    let address = server.get_address();
+    let batch = json!([
+        { "jsonrpc": "2.0", "method": "wallet_getBalance", "params": { "entity_id": "entity_001" }, "id": "a" },
+        { "jsonrpc": "2.0", "method": "transfer_submit", "params": transfer_params("id_0001", "entity_001", "entity_002", "10", 0, JOHN_SECRET_KEY) },
+        { "jsonrpc": "2.0", "method": "wallet_getBalance", "params": { "entity_id": "entity_002" }, "id": "b" }
+    ]);
+    let (status, body) = http_request(&address, "POST", &batch.to_string());
+    assert_eq!(status, 200);
+    let responses: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(responses.as_array().unwrap().len(), 2);
+    assert_eq!(responses[0]["result"]["balance"], "100");
+    assert_eq!(responses[1]["id"], "b");
+    assert_eq!(responses[1]["result"]["balance"], "10");
+
+    let notification = json!({ "jsonrpc": "2.0", "method": "tx_history" });
+    let (status, body) = http_request(&address, "POST", &notification.to_string());
+    assert_eq!(status, 204);
+    assert!(body.is_empty());
+}
+
+#[test]
+fn http_request_parsing() {
+    let mut reader: &[u8] = b"POST /rpc?x=1 HTTP/1.1\r\ncontent-length: 4\r\nX-Custom:  value \r\n\r\nbody";
+    let request = HttpRequest::read_from(&mut reader).unwrap();
+    assert_eq!(request.get_path(), "/rpc");
+    assert_eq!(request.get_query(), Some("x=1"));
+    assert_eq!(request.get_header("x-custom"), Some("value"));
+    assert_eq!(request.get_body(), b"body");
+
+    let mut reader: &[u8] = b"POST / HTTP/1.1\r\nContent-Length: 9999999999\r\n\r\n";
+    match HttpRequest::read_from(&mut reader) {
+        Ok(_) => panic!("expected an error"),
+        Err(ApiError::PayloadTooLargeError { limit, .. }) => assert_eq!(limit, MAX_BODY_LENGTH),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    let mut reader: &[u8] = b"HELLO\r\n\r\n";
+    assert!(matches!(HttpRequest::read_from(&mut reader), Err(ApiError::MalformedRequestError { .. })));
+}
+
+/// Return the status of a tx_history call, or 0 when the connection failed.
+fn try_http_status(address: &str) -> u16 {
+    let body = json!({ "jsonrpc": "2.0", "method": "tx_history", "id": 1 }).to_string();
+    let mut response = String::new();
+    let sent = TcpStream::connect(address).and_then(|mut stream| {
+        write!(stream, "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}", body.len(), body)?;
+        stream.read_to_string(&mut response)
+    });
+    match (sent, response.split_whitespace().nth(1)) {
+        (Ok(_), Some(status)) => status.parse().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+#[test]
+fn http_connections_are_capped() {
+    let server = start_server();
+    let address = server.get_address();
+    let idle_streams: Vec<TcpStream> = (0..MAX_CONNECTIONS).map(|_| TcpStream::connect(&address).unwrap()).collect();
+    // The accepting thread counts the idle connections in the order they were accepted
+    let mut status = 0;
+    for _ in 0..50 {
+        status = try_http_status(&address);
+        if status == 503 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(status, 503);
+
+    drop(idle_streams);
+    let mut status = 0;
+    for _ in 0..50 {
+        status = try_http_status(&address);
+        if status == 200 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(status, 200);
+}
+
+#[test]
+fn http_request_has_a_deadline() {
+    let server = start_server();
+    let address = server.get_address();
+    let mut stream = TcpStream::connect(&address).unwrap();
+    let started = Instant::now();
+    // A client sending its request a byte at a time is answered once the deadline of the whole request is passed
+    let mut response = String::new();
+    for byte in b"POST / HTTP/1.1\r\nContent-Length: 1000\r\n\r\n".iter().cycle() {
+        if stream.write_all(&[*byte]).is_err() || started.elapsed() > REQUEST_TIMEOUT * 3 {
+            break;
+        }
+        stream.set_read_timeout(Some(Duration::from_millis(500))).unwrap();
+        let mut buffer = [0; 1024];
+        if let Ok(length) = stream.read(&mut buffer) {
+            response = String::from_utf8_lossy(&buffer[..length]).to_string();
+            break;
+        }
+    }
+    assert!(response.starts_with("HTTP/1.1 400"));
+    assert!(started.elapsed() >= REQUEST_TIMEOUT);
+    assert!(started.elapsed() < REQUEST_TIMEOUT * 2);
+}
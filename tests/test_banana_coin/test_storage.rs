@@ -0,0 +1,299 @@
+extern crate banana_coin;
+use std::path::PathBuf;
+
+use serde_json::json;
+
+use banana_coin::model::*;
+use banana_coin::model::TransactionState::*;
+use banana_coin::error::StorageError;
+use banana_coin::ledger::ESCROW_ACCOUNT_ID;
+use banana_coin::storage::*;
+use banana_coin::transfer::TransferExecutor;
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("banana_coin_{}_{}", std::process::id(), name));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn new_transaction(id: &str, from_entity_id: &str, to_entity_id: &str) -> Transaction {
+    Transaction::new(
+        id.to_string(),
+        from_entity_id.to_string(),
+        to_entity_id.to_string(),
+        Amount::from_integer(10),
+        "".to_string(),
+        Completed { timestamp: 2 },
+        vec![Created { timestamp: 1 }, OnProcess { timestamp: 2 }, Completed { timestamp: 2 }],
+    ).unwrap()
+}
+
+fn fill_storage<S: Storage>(storage: &mut S) {
+    assert!(storage.save_entity(&Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)))).is_ok());
+    assert!(storage.save_entity(&Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)))).is_ok());
+    assert!(storage.save_entity(&Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(90)))).is_ok());
+    assert!(storage.append_transaction(&new_transaction("id_0001", "entity_001", "entity_002")).is_ok());
+    assert!(storage.append_transaction(&new_transaction("id_0002", "entity_002", "entity_003")).is_ok());
+    assert!(storage.save_idempotency_record(&IdempotencyRecord::new("key_0001".to_string(), json!({}), 201, json!({}), 1)).is_ok());
+    assert!(storage.save_idempotency_record(&IdempotencyRecord::new("key_0002".to_string(), json!({ "amount": "10" }), 422, json!({}), 5)).is_ok());
+    assert_eq!(storage.remove_idempotency_records_before(3).unwrap(), 1);
+    assert_eq!(storage.remove_idempotency_records_before(3).unwrap(), 0);
+}
+
+fn check_storage<S: Storage>(storage: &mut S) {
+    assert_eq!(storage.load_entity("entity_001").unwrap().unwrap().get_wallet().get_balance(), Amount::from_integer(90));
+    assert!(storage.load_entity("entity_003").unwrap().is_none());
+    let entity_ids: Vec<String> = storage.list_entities().unwrap().iter().map(|entity| entity.get_id().to_string()).collect();
+    assert_eq!(entity_ids, vec!["entity_001", "entity_002"]);
+
+    let transaction_ids = |transactions: Vec<Transaction>| -> Vec<String> {
+        transactions.iter().map(|transaction| transaction.get_id()).collect()
+    };
+    assert_eq!(transaction_ids(storage.list_transactions().unwrap()), vec!["id_0001", "id_0002"]);
+    assert_eq!(transaction_ids(storage.list_transactions_by_entity("entity_001").unwrap()), vec!["id_0001"]);
+    assert_eq!(transaction_ids(storage.list_transactions_by_entity("entity_002").unwrap()), vec!["id_0001", "id_0002"]);
+    assert_eq!(storage.load_transaction("id_0002").unwrap().unwrap().get_to_entity_id(), "entity_003");
+    assert!(storage.load_transaction("id_0003").unwrap().is_none());
+
+    match storage.append_transaction(&new_transaction("id_0001", "entity_001", "entity_002")) {
+        Ok(_) => panic!("expected an error"),
+        Err(StorageError::DuplicateTransactionError { transaction_id }) => assert_eq!(transaction_id, "id_0001"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(storage.list_transactions().unwrap().len(), 2);
+
+    assert!(storage.load_idempotency_record("key_0001").unwrap().is_none());
+    let record = storage.load_idempotency_record("key_0002").unwrap().unwrap();
+    assert_eq!(record.get_status(), 422);
+    assert_eq!(record.get_request()["amount"], "10");
+    assert_eq!(record.get_created_at(), 5);
+}
+
+#[test]
+fn memory_storage() {
+    let mut storage = MemoryStorage::new();
+    fill_storage(&mut storage);
+    check_storage(&mut storage);
+}
+
+#[test]
+fn json_lines_storage_persists_across_reopening() {
+    let path = temp_path("persist.jsonl");
+    {
+        let mut storage = JsonLinesStorage::open(&path).unwrap();
+        fill_storage(&mut storage);
+        check_storage(&mut storage);
+    }
+    let mut storage = JsonLinesStorage::open(&path).unwrap();
+    check_storage(&mut storage);
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(content.lines().count(), 8);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn json_lines_storage_is_locked_while_open() {
+    let path = temp_path("locked.jsonl");
+    let storage = JsonLinesStorage::open(&path).unwrap();
+    assert!(matches!(JsonLinesStorage::open(&path), Err(StorageError::LockedStorageError { .. })));
+    drop(storage);
+    assert!(JsonLinesStorage::open(&path).is_ok());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn json_lines_storage_unsuccessful_corrupted_record() {
+    let path = temp_path("corrupted.jsonl");
+    {
+        let mut storage = JsonLinesStorage::open(&path).unwrap();
+        fill_storage(&mut storage);
+    }
+    let mut content = std::fs::read_to_string(&path).unwrap();
+    content.push_str("{\"Entity\": \n");
+    std::fs::write(&path, content).unwrap();
+    match JsonLinesStorage::open(&path) {
+        Ok(_) => panic!("expected an error"),
+        Err(StorageError::CorruptedRecordError { line, .. }) => assert_eq!(line, 9),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_storage_persists_across_reopening() {
+    let path = temp_path("persist.sqlite");
+    {
+        let mut storage = SqliteStorage::open(&path).unwrap();
+        fill_storage(&mut storage);
+        check_storage(&mut storage);
+    }
+    let mut storage = SqliteStorage::open(&path).unwrap();
+    check_storage(&mut storage);
+    drop(storage);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_storage_in_memory() {
+    let mut storage = SqliteStorage::open_in_memory().unwrap();
+    fill_storage(&mut storage);
+    check_storage(&mut storage);
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_storage_unsuccessful_corrupted_record() {
+    let path = temp_path("corrupted.sqlite");
+    {
+        let mut storage = SqliteStorage::open(&path).unwrap();
+        fill_storage(&mut storage);
+    }
+    // A transaction whose current state is not the last state of its history does not pass validation
+    let connection = rusqlite::Connection::open(&path).unwrap();
+    let record: String = connection.query_row("SELECT record FROM transactions WHERE id = 'id_0002'", [], |row| row.get(0)).unwrap();
+    let mut record: serde_json::Value = serde_json::from_str(&record).unwrap();
+    record["state_history"] = serde_json::json!([{ "Created": { "timestamp": 1 } }]);
+    connection.execute("UPDATE transactions SET record = ?1 WHERE id = 'id_0002'", [record.to_string()]).unwrap();
+    connection.execute("UPDATE entities SET record = '{\"id\": ' WHERE id = 'entity_002'", []).unwrap();
+    drop(connection);
+
+    let storage = SqliteStorage::open(&path).unwrap();
+    assert!(storage.load_transaction("id_0001").unwrap().is_some());
+    match storage.list_transactions() {
+        Ok(_) => panic!("expected an error"),
+        Err(StorageError::CorruptedRecordError { line, .. }) => assert_eq!(line, 2),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(matches!(storage.load_entity("entity_002"), Err(StorageError::CorruptedRecordError { .. })));
+    drop(storage);
+    std::fs::remove_file(&path).unwrap();
+}
+
+fn check_transaction_with_entities<S: Storage>(storage: &mut S) {
+    let entities = vec![
+        Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(80))),
+        Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(20))),
+    ];
+    assert!(storage.append_transaction_with_entities(&new_transaction("id_0003", "entity_001", "entity_002"), &entities).is_ok());
+    assert!(storage.load_transaction("id_0003").unwrap().is_some());
+    assert_eq!(storage.load_entity("entity_001").unwrap().unwrap().get_wallet().get_balance(), Amount::from_integer(80));
+    assert_eq!(storage.load_entity("entity_002").unwrap().unwrap().get_wallet().get_balance(), Amount::from_integer(20));
+
+    // Nothing is stored when the transaction is a duplicate
+    let entities = vec![Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(0)))];
+    match storage.append_transaction_with_entities(&new_transaction("id_0003", "entity_001", "entity_002"), &entities) {
+        Ok(_) => panic!("expected an error"),
+        Err(StorageError::DuplicateTransactionError { transaction_id }) => assert_eq!(transaction_id, "id_0003"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(storage.load_entity("entity_001").unwrap().unwrap().get_wallet().get_balance(), Amount::from_integer(80));
+    assert_eq!(storage.list_transactions().unwrap().len(), 3);
+}
+
+#[test]
+fn memory_storage_transaction_with_entities() {
+    let mut storage = MemoryStorage::new();
+    fill_storage(&mut storage);
+    check_transaction_with_entities(&mut storage);
+}
+
+#[test]
+fn json_lines_storage_transaction_with_entities() {
+    let path = temp_path("transaction_with_entities.jsonl");
+    {
+        let mut storage = JsonLinesStorage::open(&path).unwrap();
+        fill_storage(&mut storage);
+        check_transaction_with_entities(&mut storage);
+    }
+    let storage = JsonLinesStorage::open(&path).unwrap();
+    assert_eq!(storage.load_entity("entity_002").unwrap().unwrap().get_wallet().get_balance(), Amount::from_integer(20));
+    assert!(storage.load_transaction("id_0003").unwrap().is_some());
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(content.lines().count(), 9);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_storage_transaction_with_entities() {
+    let path = temp_path("transaction_with_entities.sqlite");
+    {
+        let mut storage = SqliteStorage::open(&path).unwrap();
+        fill_storage(&mut storage);
+        check_transaction_with_entities(&mut storage);
+    }
+    let storage = SqliteStorage::open(&path).unwrap();
+    assert_eq!(storage.load_entity("entity_002").unwrap().unwrap().get_wallet().get_balance(), Amount::from_integer(20));
+    assert!(storage.load_transaction("id_0003").unwrap().is_some());
+    drop(storage);
+    std::fs::remove_file(&path).unwrap();
+}
+
+fn new_escrow(id: &str) -> Escrow {
+    let mut transaction = Transaction::new(
+        id.to_string(),
+        "entity_001".to_string(),
+        "entity_002".to_string(),
+        Amount::from_integer(10),
+        "".to_string(),
+        Created { timestamp: 1 },
+        vec![Created { timestamp: 1 }],
+    ).unwrap();
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    let mut escrow_account = Entity::new(ESCROW_ACCOUNT_ID.to_string(), "escrow".to_string(), Wallet::new(Amount::from_integer(0)));
+    TransferExecutor::new()
+        .execute_escrow(&mut transaction, &mut from_entity, &mut escrow_account, ReleaseCondition::Deadline { deadline: 10 }, 2)
+        .unwrap()
+}
+
+fn check_escrows<S: Storage>(storage: &mut S) {
+    assert!(storage.save_escrow(&new_escrow("id_0002")).is_ok());
+    assert!(storage.save_escrow(&new_escrow("id_0001")).is_ok());
+    assert!(storage.save_escrow(&new_escrow("id_0001")).is_ok());
+    assert!(storage.save_escrow(&new_escrow("id_0003")).is_ok());
+    assert!(storage.remove_escrow("id_0003").is_ok());
+    assert!(storage.remove_escrow("id_0004").is_ok());
+}
+
+fn escrow_ids<S: Storage>(storage: &S) -> Vec<String> {
+    storage.list_escrows().unwrap().iter().map(|escrow| escrow.get_transaction().get_id()).collect()
+}
+
+#[test]
+fn memory_storage_escrows() {
+    let mut storage = MemoryStorage::new();
+    check_escrows(&mut storage);
+    assert_eq!(escrow_ids(&storage), vec!["id_0001", "id_0002"]);
+}
+
+#[test]
+fn json_lines_storage_escrows_persist_across_reopening() {
+    let path = temp_path("escrows.jsonl");
+    {
+        let mut storage = JsonLinesStorage::open(&path).unwrap();
+        check_escrows(&mut storage);
+    }
+    let storage = JsonLinesStorage::open(&path).unwrap();
+    assert_eq!(escrow_ids(&storage), vec!["id_0001", "id_0002"]);
+    assert_eq!(storage.list_escrows().unwrap()[0].get_transaction().get_current_state(), Blocked { timestamp: 2 });
+    // Removing an escrow that is not stored appends nothing
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(content.lines().count(), 5);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_storage_escrows_persist_across_reopening() {
+    let path = temp_path("escrows.sqlite");
+    {
+        let mut storage = SqliteStorage::open(&path).unwrap();
+        check_escrows(&mut storage);
+    }
+    let storage = SqliteStorage::open(&path).unwrap();
+    assert_eq!(escrow_ids(&storage), vec!["id_0001", "id_0002"]);
+    drop(storage);
+    std::fs::remove_file(&path).unwrap();
+}
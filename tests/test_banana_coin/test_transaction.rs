@@ -1,6 +1,7 @@
 extern crate banana_coin;
 use banana_coin::model::*;
 use banana_coin::model::transaction_state::{TransactionState, TransactionState::*};
+use banana_coin::error::TransactionError;
 
 #[test]
 fn new_transaction() {
@@ -12,15 +13,15 @@ fn new_transaction() {
         "id_0001".to_string(),
         "entity_001".to_string(),
         "entity_002".to_string(),
-        100,
+        Amount::from_integer(100),
         "Moving 100 coins from entity identified by 'entity_001' to entity identified by 'entity_002'.".to_string(),
         Completed{timestamp: 7},
         history
-    );
+    ).unwrap();
     assert_eq!(transaction.get_id(), "id_0001".to_string());
     assert_eq!(transaction.get_from_entity_id(), "entity_001".to_string());
     assert_eq!(transaction.get_to_entity_id(), "entity_002".to_string());
-    assert_eq!(transaction.get_amount(), 100);
+    assert_eq!(transaction.get_amount(), Amount::from_integer(100));
     assert_eq!(transaction.get_description(),
                "Moving 100 coins from entity identified by 'entity_001' to entity identified by 'entity_002'.".to_string());
     assert_eq!(transaction.get_current_state(), Completed { timestamp: 7 });
@@ -29,4 +30,239 @@ fn new_transaction() {
     assert!(transaction.get_state_history().contains(&Completed {timestamp: 7}));
 
 
-}
\ No newline at end of file
+}
+
+fn new_transaction_in(
+    current_state: TransactionState,
+    state_history: Vec<TransactionState>,
+) -> Result<Transaction, TransactionError> {
+    Transaction::new(
+        "id_0001".to_string(),
+        "entity_001".to_string(),
+        "entity_002".to_string(),
+        Amount::from_integer(100),
+        "Moving 100 coins from entity identified by 'entity_001' to entity identified by 'entity_002'.".to_string(),
+        current_state,
+        state_history
+    )
+}
+
+fn new_created_transaction() -> Transaction {
+    new_transaction_in(Created{timestamp: 1}, vec![Created {timestamp: 1}]).unwrap()
+}
+
+#[test]
+fn new_transaction_unsuccessful_empty_history() {
+    assert!(matches!(new_transaction_in(Created{timestamp: 1}, Vec::new()), Err(TransactionError::EmptyStateHistoryError)));
+}
+
+#[test]
+fn new_transaction_unsuccessful_completed_before_created() {
+    assert!(matches!(new_transaction_in(Created{timestamp: 7}, vec![Completed {timestamp: 1}, Created {timestamp: 7}]), Err(TransactionError::InvalidInitialStateError {..})));
+}
+
+#[test]
+fn new_transaction_unsuccessful_decreasing_timestamp() {
+    assert!(matches!(new_transaction_in(Completed{timestamp: 7}, vec![Created {timestamp: 1}, OnProcess {timestamp: 8}, Completed {timestamp: 7}]), Err(TransactionError::DecreasingTimestampError {..})));
+}
+
+#[test]
+fn new_transaction_unsuccessful_current_state_mismatch() {
+    assert!(matches!(new_transaction_in(Completed{timestamp: 7}, vec![Created {timestamp: 1}, OnProcess {timestamp: 4}]), Err(TransactionError::CurrentStateMismatchError {..})));
+}
+
+#[test]
+fn transition_to_successful() {
+    let mut transaction = new_created_transaction();
+    match transaction.transition_to(OnProcess {timestamp: 4}) {
+        Ok(_) => assert_eq!(transaction.get_current_state(), OnProcess {timestamp: 4}),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    match transaction.transition_to(Completed {timestamp: 4}) {
+        Ok(_) => assert_eq!(transaction.get_current_state(), Completed {timestamp: 4}),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(transaction.get_state_history(),
+               vec![Created {timestamp: 1}, OnProcess {timestamp: 4}, Completed {timestamp: 4}]);
+}
+
+#[test]
+fn transition_to_unsuccessful_illegal_transition() {
+    let mut transaction = new_created_transaction();
+    assert!(transaction.transition_to(OnProcess {timestamp: 4}).is_ok());
+    assert!(transaction.transition_to(Completed {timestamp: 7}).is_ok());
+    assert!(matches!(transaction.transition_to(OnProcess {timestamp: 8}), Err(TransactionError::IllegalTransitionError {..})));
+    assert_eq!(transaction.get_current_state(), Completed {timestamp: 7});
+    assert_eq!(transaction.get_state_history().len(), 3);
+}
+
+#[test]
+fn transition_to_unsuccessful_out_of_failed() {
+    let mut transaction = new_created_transaction();
+    assert!(transaction.transition_to(Failed {timestamp: 4}).is_ok());
+    assert!(matches!(transaction.transition_to(Created {timestamp: 8}), Err(TransactionError::IllegalTransitionError {..})));
+}
+
+#[test]
+fn transition_to_unsuccessful_decreasing_timestamp() {
+    let mut transaction = new_created_transaction();
+    assert!(transaction.transition_to(OnProcess {timestamp: 4}).is_ok());
+    assert!(matches!(transaction.transition_to(Completed {timestamp: 3}), Err(TransactionError::DecreasingTimestampError {..})));
+    assert_eq!(transaction.get_current_state(), OnProcess {timestamp: 4});
+}
+
+#[test]
+fn validate_deserialized_transaction() {
+    let json = r#"{"id":"id_0001","from_entity_id":"entity_001","to_entity_id":"entity_002","amount":100,
+        "description":"","current_state":{"OnProcess":{"timestamp":4}},
+        "state_history":[{"Completed":{"timestamp":1}},{"OnProcess":{"timestamp":4}}]}"#;
+    let transaction: Transaction = serde_json::from_str(json).unwrap();
+    assert!(matches!(transaction.validate(), Err(TransactionError::InvalidInitialStateError {..})));
+}
+
+#[test]
+fn set_fee_successful() {
+    let mut transaction = new_created_transaction();
+    assert_eq!(transaction.get_fee(), None);
+    assert!(transaction.set_fee(Amount::from_integer(5)).is_ok());
+    assert_eq!(transaction.get_fee(), Some(Amount::from_integer(5)));
+    assert_eq!(transaction.get_total_amount().unwrap(), Amount::from_integer(105));
+}
+
+#[test]
+fn set_fee_unsuccessful_negative_fee() {
+    let mut transaction = new_created_transaction();
+    match transaction.set_fee(Amount::from_integer(-5)) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransactionError::NegativeAmountError { amount }) => assert_eq!(amount, Amount::from_integer(-5)),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(transaction.get_fee(), None);
+}
+
+#[test]
+fn new_transaction_unsuccessful_negative_amount() {
+    match Transaction::new(
+        "id_0001".to_string(),
+        "entity_001".to_string(),
+        "entity_002".to_string(),
+        Amount::parse("-0.5").unwrap(),
+        "".to_string(),
+        Created{timestamp: 1},
+        vec![Created {timestamp: 1}]
+    ) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransactionError::NegativeAmountError { amount }) => assert_eq!(amount.to_string(), "-0.5"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+}
+
+#[test]
+fn deserialize_fractional_and_legacy_amount() {
+    let mut json = serde_json::to_value(new_created_transaction()).unwrap();
+    assert_eq!(json["amount"], "100");
+    json["amount"] = serde_json::json!("12.25");
+    let transaction: Transaction = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(transaction.get_amount(), Amount::parse("12.25").unwrap());
+    json["amount"] = serde_json::json!(100);
+    let transaction: Transaction = serde_json::from_value(json).unwrap();
+    assert_eq!(transaction.get_amount(), Amount::from_integer(100));
+}
+
+#[test]
+fn set_fee_unsuccessful_overflow() {
+    let mut transaction = new_created_transaction();
+    let fee = Amount::new(i128::MAX, 0).unwrap();
+    match transaction.set_fee(fee) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransactionError::AmountOverflowError { fee: found_fee, .. }) => assert_eq!(found_fee, fee),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(transaction.get_fee(), None);
+}
+
+#[test]
+fn validate_unsuccessful_deserialized_fee_overflow() {
+    let mut json = serde_json::to_value(new_created_transaction()).unwrap();
+    json["fee"] = serde_json::json!(i128::MAX.to_string());
+    let transaction: Transaction = serde_json::from_value(json).unwrap();
+    assert!(matches!(transaction.validate(), Err(TransactionError::AmountOverflowError { .. })));
+}
+
+#[test]
+fn set_asset_successful() {
+    let mut transaction = new_created_transaction();
+    assert_eq!(transaction.get_asset(), DEFAULT_ASSET);
+    let signing_bytes = transaction.get_signing_bytes();
+    assert!(transaction.set_asset("GOLD".to_string()).is_ok());
+    assert_eq!(transaction.get_asset(), "GOLD");
+    assert_ne!(transaction.get_signing_bytes(), signing_bytes);
+}
+
+#[test]
+fn set_asset_unsuccessful_empty() {
+    let mut transaction = new_created_transaction();
+    assert!(matches!(transaction.set_asset("".to_string()), Err(TransactionError::EmptyAssetError)));
+    assert_eq!(transaction.get_asset(), DEFAULT_ASSET);
+}
+
+#[test]
+fn deserialize_transaction_without_asset() {
+    let json = r#"{"id":"id_0001","from_entity_id":"entity_001","to_entity_id":"entity_002","amount":100,
+        "description":"","current_state":{"Created":{"timestamp":1}},
+        "state_history":[{"Created":{"timestamp":1}}]}"#;
+    let transaction: Transaction = serde_json::from_str(json).unwrap();
+    assert!(transaction.validate().is_ok());
+    assert_eq!(transaction.get_asset(), DEFAULT_ASSET);
+}
+
+#[test]
+fn asset_precision_enforced() {
+    let mut transaction = new_created_transaction();
+    assert!(transaction.set_fee(Amount::parse("0.001").unwrap()).is_ok());
+    match transaction.set_fee(Amount::parse("0.000000001").unwrap()) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransactionError::AssetPrecisionError { asset, decimals, .. }) => {
+            assert_eq!(asset, DEFAULT_ASSET);
+            assert_eq!(decimals, DEFAULT_ASSET_DECIMALS);
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    // The decimal places of the other assets are defined by an asset registry
+    assert!(transaction.set_asset("TRANSACTION_CENTS".to_string()).is_ok());
+    assert!(transaction.set_fee(Amount::parse("0.000000001").unwrap()).is_ok());
+    let mut registry = AssetRegistry::new();
+    assert!(transaction.check_precision(&registry).is_ok());
+    assert!(registry.define(AssetDefinition::new("TRANSACTION_CENTS".to_string(), 2).unwrap()).is_ok());
+    match transaction.check_precision(&registry) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransactionError::AssetPrecisionError { asset, decimals, .. }) => {
+            assert_eq!(asset, "TRANSACTION_CENTS");
+            assert_eq!(decimals, 2);
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(matches!(transaction.set_asset(DEFAULT_ASSET.to_string()), Err(TransactionError::AssetPrecisionError { .. })));
+    assert_eq!(transaction.get_asset(), "TRANSACTION_CENTS");
+    let mut json = serde_json::to_value(&transaction).unwrap();
+    json["asset"] = serde_json::json!(DEFAULT_ASSET);
+    let transaction: Transaction = serde_json::from_value(json).unwrap();
+    assert!(transaction.validate().is_err());
+}
+
+#[test]
+fn new_transaction_unsuccessful_default_asset_precision() {
+    match Transaction::new(
+        "id_0001".to_string(),
+        "entity_001".to_string(),
+        "entity_002".to_string(),
+        Amount::parse("0.000000001").unwrap(),
+        "".to_string(),
+        Created{timestamp: 1},
+        vec![Created {timestamp: 1}]
+    ) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransactionError::AssetPrecisionError { decimals, .. }) => assert_eq!(decimals, DEFAULT_ASSET_DECIMALS),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+}
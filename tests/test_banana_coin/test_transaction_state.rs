@@ -1,6 +1,8 @@
 extern crate banana_coin;
 
+#[allow(unused_imports)]
 use banana_coin::model::*;
+#[allow(unused_imports)]
 use banana_coin::model::transaction_state::{TransactionState, TransactionState::*};
 
 #[test]
@@ -28,4 +30,33 @@ fn inequality_timestamp() {
     assert!(Completed { timestamp: 3 } != Completed { timestamp: 4 });
     assert!(Failed { timestamp: 4 } != Failed { timestamp: 5 });
     assert!(Blocked { timestamp: 5 } != Blocked { timestamp: 6 });
-}
\ No newline at end of file
+}
+
+#[test]
+fn timestamp() {
+    assert_eq!(Created { timestamp: 1 }.get_timestamp(), 1);
+    assert_eq!(OnProcess { timestamp: 2 }.get_timestamp(), 2);
+    assert_eq!(Completed { timestamp: 3 }.get_timestamp(), 3);
+    assert_eq!(Failed { timestamp: 4 }.get_timestamp(), 4);
+    assert_eq!(Blocked { timestamp: 5 }.get_timestamp(), 5);
+}
+
+#[test]
+fn legal_transitions() {
+    assert!(Created { timestamp: 1 }.can_transition_to(&OnProcess { timestamp: 2 }));
+    assert!(Created { timestamp: 1 }.can_transition_to(&Blocked { timestamp: 2 }));
+    assert!(OnProcess { timestamp: 2 }.can_transition_to(&Completed { timestamp: 3 }));
+    assert!(OnProcess { timestamp: 2 }.can_transition_to(&Failed { timestamp: 3 }));
+    assert!(OnProcess { timestamp: 2 }.can_transition_to(&Created { timestamp: 3 }));
+    assert!(Blocked { timestamp: 2 }.can_transition_to(&Completed { timestamp: 3 }));
+}
+
+#[test]
+fn illegal_transitions() {
+    assert!(!Created { timestamp: 1 }.can_transition_to(&Created { timestamp: 2 }));
+    assert!(!Created { timestamp: 1 }.can_transition_to(&Completed { timestamp: 2 }));
+    assert!(!Completed { timestamp: 3 }.can_transition_to(&OnProcess { timestamp: 4 }));
+    assert!(!Failed { timestamp: 3 }.can_transition_to(&Created { timestamp: 4 }));
+    assert!(!Failed { timestamp: 3 }.can_transition_to(&OnProcess { timestamp: 4 }));
+    assert!(!Failed { timestamp: 3 }.can_transition_to(&Completed { timestamp: 4 }));
+}
@@ -0,0 +1,308 @@
+extern crate banana_coin;
+use banana_coin::model::*;
+use banana_coin::model::TransactionState::*;
+use banana_coin::error::{EntityError, TransferError, WalletError};
+use banana_coin::transfer::TransferExecutor;
+
+fn new_transaction(amount: i64) -> Transaction {
+    Transaction::new(
+        "id_0001".to_string(),
+        "entity_001".to_string(),
+        "entity_002".to_string(),
+        Amount::from_integer(amount),
+        "Moving coins from entity identified by 'entity_001' to entity identified by 'entity_002'.".to_string(),
+        Created { timestamp: 1 },
+        vec![Created { timestamp: 1 }],
+    ).unwrap()
+}
+
+#[test]
+fn execute_successful() {
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(10)));
+    let mut transaction = new_transaction(60);
+    let executor = TransferExecutor::new();
+    match executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 2) {
+        Ok(_) => {
+            assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(40));
+            assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(70));
+            assert_eq!(transaction.get_current_state(), Completed { timestamp: 2 });
+            assert_eq!(transaction.get_state_history(),
+                       vec![Created { timestamp: 1 }, OnProcess { timestamp: 2 }, Completed { timestamp: 2 }]);
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+}
+
+#[test]
+fn execute_unsuccessful_negative_balance() {
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(50)));
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(10)));
+    let mut transaction = new_transaction(60);
+    let executor = TransferExecutor::new();
+    match executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 2) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransferError::EntityOperationError { entity_id, error: EntityError::WalletOperationError { error } }) => {
+            assert_eq!(entity_id, "entity_001");
+            assert!(matches!(*error, WalletError::RemoveCoinNegativeBalanceError { .. }));
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(50));
+    assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(10));
+    assert_eq!(transaction.get_current_state(), Failed { timestamp: 2 });
+    assert_eq!(transaction.get_state_history(),
+               vec![Created { timestamp: 1 }, OnProcess { timestamp: 2 }, Failed { timestamp: 2 }]);
+}
+
+#[test]
+fn execute_unsuccessful_rollback_on_credit_overflow() {
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::new(i128::MAX, 0).unwrap()));
+    let mut transaction = new_transaction(60);
+    let executor = TransferExecutor::new();
+    match executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 2) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransferError::EntityOperationError { entity_id, error: EntityError::WalletOperationError { error } }) => {
+            assert_eq!(entity_id, "entity_002");
+            assert!(matches!(*error, WalletError::AddCoinOverflowError { .. }));
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(100));
+    assert_eq!(to_entity.get_wallet().get_balance(), Amount::new(i128::MAX, 0).unwrap());
+    assert_eq!(transaction.get_current_state(), Failed { timestamp: 2 });
+}
+
+#[test]
+fn execute_unsuccessful_entity_mismatch() {
+    let mut from_entity = Entity::new("entity_003".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    let mut transaction = new_transaction(60);
+    let executor = TransferExecutor::new();
+    match executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 2) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransferError::EntityMismatchError { expected_entity_id, actual_entity_id }) => {
+            assert_eq!(expected_entity_id, "entity_001");
+            assert_eq!(actual_entity_id, "entity_003");
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(transaction.get_current_state(), Created { timestamp: 1 });
+    assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(100));
+}
+
+#[test]
+fn execute_unsuccessful_already_executed() {
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    let mut transaction = new_transaction(60);
+    let executor = TransferExecutor::new();
+    assert!(executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 2).is_ok());
+    match executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 3) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransferError::InvalidTransactionStateError { current_state }) =>
+            assert_eq!(current_state, Completed { timestamp: 2 }),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(40));
+    assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(60));
+}
+
+#[test]
+fn execute_unsuccessful_replayed_nonce() {
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    let executor = TransferExecutor::new();
+    let mut transaction = new_transaction(10);
+    // A copy of the transaction taken before its execution
+    let mut replayed = transaction.clone();
+    assert!(executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 2).is_ok());
+    assert_eq!(from_entity.get_nonce(), 1);
+    match executor.execute(&mut replayed, &mut from_entity, &mut to_entity, 3) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransferError::ReplayedNonceError { entity_id, expected_nonce: 1, found_nonce: 0 }) =>
+            assert_eq!(entity_id, "entity_001"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(replayed.get_current_state(), Created { timestamp: 1 });
+    assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(90));
+    assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(10));
+}
+
+#[test]
+fn execute_unsuccessful_out_of_order_nonce() {
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    let executor = TransferExecutor::new();
+    let mut transaction = new_transaction(10);
+    transaction.set_nonce(1);
+    assert!(matches!(executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 2), Err(TransferError::OutOfOrderNonceError { expected_nonce: 0, found_nonce: 1, .. })));
+    assert_eq!(from_entity.get_nonce(), 0);
+    assert_eq!(transaction.get_current_state(), Created { timestamp: 1 });
+}
+
+#[test]
+fn execute_failed_transaction_consumes_nonce() {
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(50)));
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    let executor = TransferExecutor::new();
+    let mut transaction = new_transaction(60);
+    assert!(executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 2).is_err());
+    assert_eq!(transaction.get_current_state(), Failed { timestamp: 2 });
+    assert_eq!(from_entity.get_nonce(), 1);
+    assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(50));
+}
+
+#[test]
+fn execute_with_fee_successful() {
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    let mut fee_collector = Entity::new("entity_003".to_string(), "bank".to_string(), Wallet::new(Amount::from_integer(0)));
+    let mut transaction = new_transaction(60);
+    assert!(transaction.set_fee(Amount::from_integer(5)).is_ok());
+    let executor = TransferExecutor::new();
+    assert!(executor.execute_with_fee(&mut transaction, &mut from_entity, &mut to_entity, &mut fee_collector, 2).is_ok());
+    assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(35));
+    assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(60));
+    assert_eq!(fee_collector.get_wallet().get_balance(), Amount::from_integer(5));
+}
+
+#[test]
+fn execute_with_fee_collected_by_receiver() {
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    let mut fee_collector = to_entity.clone();
+    let mut transaction = new_transaction(60);
+    assert!(transaction.set_fee(Amount::from_integer(5)).is_ok());
+    let executor = TransferExecutor::new();
+    assert!(executor.execute_with_fee(&mut transaction, &mut from_entity, &mut to_entity, &mut fee_collector, 2).is_ok());
+    assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(35));
+    assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(65));
+}
+
+#[test]
+fn execute_with_fee_unsuccessful_amount_plus_fee_over_balance() {
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    let mut fee_collector = Entity::new("entity_003".to_string(), "bank".to_string(), Wallet::new(Amount::from_integer(0)));
+    let mut transaction = new_transaction(100);
+    assert!(transaction.set_fee(Amount::from_integer(1)).is_ok());
+    let executor = TransferExecutor::new();
+    match executor.execute_with_fee(&mut transaction, &mut from_entity, &mut to_entity, &mut fee_collector, 2) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransferError::EntityOperationError { entity_id, .. }) => assert_eq!(entity_id, "entity_001"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(transaction.get_current_state(), Failed { timestamp: 2 });
+    assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(100));
+    assert_eq!(fee_collector.get_wallet().get_balance(), Amount::from_integer(0));
+}
+
+#[test]
+fn execute_unsuccessful_fee_without_collector() {
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    let mut transaction = new_transaction(60);
+    assert!(transaction.set_fee(Amount::from_integer(5)).is_ok());
+    let executor = TransferExecutor::new();
+    assert!(matches!(executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 2), Err(TransferError::MissingFeeCollectorError { .. })));
+    assert_eq!(transaction.get_current_state(), Created { timestamp: 1 });
+    assert_eq!(from_entity.get_nonce(), 0);
+}
+
+#[test]
+fn execute_with_fee_successful_without_fee() {
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    let mut fee_collector = Entity::new("entity_003".to_string(), "bank".to_string(), Wallet::new(Amount::from_integer(0)));
+    let mut transaction = new_transaction(60);
+    let executor = TransferExecutor::new();
+    assert!(executor.execute_with_fee(&mut transaction, &mut from_entity, &mut to_entity, &mut fee_collector, 2).is_ok());
+    assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(40));
+    assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(60));
+    assert_eq!(fee_collector.get_wallet().get_balance(), Amount::from_integer(0));
+}
+
+#[test]
+fn execute_successful_other_asset() {
+    let mut from_wallet = Wallet::new(Amount::from_integer(100));
+    assert!(from_wallet.add_asset_coins("GOLD", Amount::from_integer(20)).is_ok());
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), from_wallet);
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    let mut transaction = new_transaction(15);
+    assert!(transaction.set_asset("GOLD".to_string()).is_ok());
+    let executor = TransferExecutor::new();
+    assert!(executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 2).is_ok());
+    assert_eq!(from_entity.get_wallet().get_asset_balance("GOLD"), Amount::from_integer(5));
+    assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(100));
+    assert_eq!(to_entity.get_wallet().get_asset_balance("GOLD"), Amount::from_integer(15));
+    assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(0));
+}
+
+#[test]
+fn execute_unsuccessful_other_asset_negative_balance() {
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    let mut transaction = new_transaction(15);
+    assert!(transaction.set_asset("GOLD".to_string()).is_ok());
+    let executor = TransferExecutor::new();
+    match executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 2) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransferError::EntityOperationError { error: EntityError::WalletOperationError { error }, .. }) => match *error {
+            WalletError::RemoveCoinNegativeBalanceError { asset, .. } => assert_eq!(asset, "GOLD"),
+            _ => panic!("unexpected result")
+        },
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(100));
+}
+
+#[test]
+fn execute_hold_successful() {
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    assert!(from_entity.place_asset_hold("hold_0001".to_string(), DEFAULT_ASSET, Amount::from_integer(80), 1, 10).is_ok());
+    let executor = TransferExecutor::new();
+    // The held coins can not be spent by another transfer
+    let mut transaction = new_transaction(30);
+    assert!(executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 2).is_err());
+    let mut transaction = new_transaction(60);
+    transaction.set_nonce(from_entity.get_nonce());
+    assert!(executor.execute_hold(&mut transaction, &mut from_entity, &mut to_entity, "hold_0001", 3).is_ok());
+    assert_eq!(transaction.get_current_state(), Completed { timestamp: 3 });
+    assert_eq!(from_entity.get_wallet().get_balance(), Amount::from_integer(40));
+    assert_eq!(from_entity.get_wallet().get_available_balance(3).unwrap(), Amount::from_integer(40));
+    assert!(from_entity.get_wallet().get_holds().is_empty());
+    assert_eq!(to_entity.get_wallet().get_balance(), Amount::from_integer(60));
+}
+
+#[test]
+fn execute_hold_unsuccessful_exceeding_hold() {
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    assert!(from_entity.place_asset_hold("hold_0001".to_string(), DEFAULT_ASSET, Amount::from_integer(50), 1, 10).is_ok());
+    let mut transaction = new_transaction(60);
+    let executor = TransferExecutor::new();
+    match executor.execute_hold(&mut transaction, &mut from_entity, &mut to_entity, "hold_0001", 2) {
+        Ok(_) => panic!("expected an error"),
+        Err(TransferError::EntityOperationError { error: EntityError::WalletOperationError { error }, .. }) =>
+            assert!(matches!(*error, WalletError::CaptureExceedsHoldError { .. })),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert_eq!(transaction.get_current_state(), Failed { timestamp: 2 });
+    assert_eq!(from_entity.get_wallet().get_available_balance(2).unwrap(), Amount::from_integer(50));
+    assert_eq!(from_entity.get_wallet().get_holds().len(), 1);
+}
+
+#[test]
+fn execute_successful_after_hold_expiry() {
+    let mut from_entity = Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)));
+    let mut to_entity = Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)));
+    assert!(from_entity.place_asset_hold("hold_0001".to_string(), DEFAULT_ASSET, Amount::from_integer(80), 1, 10).is_ok());
+    let mut transaction = new_transaction(100);
+    let executor = TransferExecutor::new();
+    assert!(executor.execute(&mut transaction, &mut from_entity, &mut to_entity, 11).is_ok());
+    assert!(from_entity.get_wallet().get_balance().is_zero());
+    assert!(from_entity.get_wallet().get_holds().is_empty());
+}
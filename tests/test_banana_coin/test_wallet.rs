@@ -5,106 +5,367 @@ use self::banana_coin::error::WalletError;
 #[test]
 fn add_coins_successful_from_negative() {
     let mut wallet = Wallet::new(
-        -5
+        Amount::from_integer(-5)
     );
-    match wallet.add_coins(100) {
-        Ok(_) => assert_eq!(wallet.get_balance(), 95),
-        Err(_) => assert!(false)
+    match wallet.add_coins(Amount::from_integer(100)) {
+        Ok(_) => assert_eq!(wallet.get_balance(), Amount::from_integer(95)),
+        Err(error) => panic!("unexpected error: {:?}", error)
     }
 }
 
 #[test]
 fn add_coins_unsuccessful_zero_amount() {
     let mut wallet: Wallet = Wallet::new(
-        0
+        Amount::from_integer(0)
     );
-    match wallet.add_coins(0) {
-        Ok(_) => assert!(false),
-        Err(WalletError::AddZeroCoinError) => assert!(true),
-        Err(_) => assert!(false)
-    }
+    assert!(matches!(wallet.add_coins(Amount::from_integer(0)), Err(WalletError::AddZeroCoinError)));
 }
 
 #[test]
 fn add_coins_unsuccessful_overflow_max() {
     let mut wallet: Wallet = Wallet::new(
-        i64::max_value()
+        Amount::new(i128::MAX, 0).unwrap()
     );
-    match wallet.add_coins(10) {
-        Ok(_) => assert!(false),
-        Err(WalletError::AddCoinOverflowError {..}) => assert!(true),
-        Err(_) => assert!(false)
-    }
+    assert!(matches!(wallet.add_coins(Amount::from_integer(10)), Err(WalletError::AddCoinOverflowError {..})));
 }
 
 #[test]
 fn remove_coins_successful() {
     let mut wallet: Wallet = Wallet::new(
-        100
+        Amount::from_integer(100)
     );
-    match wallet.remove_coins(50, false) {
-        Ok(_) => assert_eq!(wallet.get_balance(), 50),
-        Err(_) => assert!(false)
+    match wallet.remove_coins(Amount::from_integer(50), 2) {
+        Ok(_) => assert_eq!(wallet.get_balance(), Amount::from_integer(50)),
+        Err(error) => panic!("unexpected error: {:?}", error)
     }
-    match wallet.remove_coins(50, true) {
-        Ok(_) => assert_eq!(wallet.get_balance(), 0),
-        Err(_) => assert!(false)
+    match wallet.remove_coins(Amount::from_integer(50), 2) {
+        Ok(_) => assert_eq!(wallet.get_balance(), Amount::from_integer(0)),
+        Err(error) => panic!("unexpected error: {:?}", error)
     }
 }
 
 #[test]
 fn remove_coins_successful_from_negative() {
     let mut wallet: Wallet = Wallet::new(
-        -5
+        Amount::from_integer(-5)
     );
-    match wallet.remove_coins(50, true) {
-        Ok(_) => assert_eq!(wallet.get_balance(), -55),
-        Err(_) => assert!(false)
+    assert!(wallet.set_overdraft_policy(OverdraftPolicy::Unlimited, "admin".to_string(), 1).is_ok());
+    match wallet.remove_coins(Amount::from_integer(50), 2) {
+        Ok(_) => assert_eq!(wallet.get_balance(), Amount::from_integer(-55)),
+        Err(error) => panic!("unexpected error: {:?}", error)
     }
 }
 
 #[test]
 fn remove_coins_unsuccessful_zero_amount() {
     let mut wallet: Wallet = Wallet::new(
-        100
+        Amount::from_integer(100)
+    );
+    assert!(matches!(wallet.remove_coins(Amount::from_integer(0), 2), Err(WalletError::RemoveZeroCoinError)));
+}
+
+#[test]
+fn remove_coins_unsuccessful_overflow_max() {
+    let mut wallet: Wallet = Wallet::new(
+        Amount::new(i128::MIN, 0).unwrap()
     );
-    match wallet.remove_coins(0, false) {
-        Ok(_) => assert!(false),
-        Err(WalletError::RemoveZeroCoinError) => assert!(true),
-        Err(_) => assert!(false)
+    assert!(wallet.set_overdraft_policy(OverdraftPolicy::Unlimited, "admin".to_string(), 1).is_ok());
+    assert!(matches!(wallet.remove_coins(Amount::from_integer(10), 2), Err(WalletError::RemoveCoinOverflowError {..})));
+}
+
+#[test]
+fn remove_coins_unsuccessful_negative_balance() {
+    let mut wallet: Wallet = Wallet::new(
+        Amount::from_integer(0)
+    );
+    match wallet.remove_coins(Amount::from_integer(10), 2) {
+        Ok(_) => panic!("expected an error"),
+        Err(WalletError::RemoveCoinNegativeBalanceError { current_amount, removed_amount, .. }) => {
+            assert!(current_amount.is_zero());
+            assert_eq!(removed_amount, Amount::from_integer(10));
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
     }
-    match wallet.remove_coins(0, true) {
-        Ok(_) => assert!(false),
-        Err(WalletError::RemoveZeroCoinError) => assert!(true),
-        Err(_) => assert!(false)
+}
+
+#[test]
+fn remove_coins_successful_down_to_overdraft_floor() {
+    let mut wallet: Wallet = Wallet::new(
+        Amount::from_integer(10)
+    );
+    let policy = OverdraftPolicy::Limit { floor: Amount::parse("-50.5").unwrap() };
+    assert!(wallet.set_overdraft_policy(policy, "admin".to_string(), 1).is_ok());
+    assert!(wallet.remove_coins(Amount::parse("60.5").unwrap(), 2).is_ok());
+    assert_eq!(wallet.get_balance(), Amount::parse("-50.5").unwrap());
+    match wallet.remove_coins(Amount::parse("0.1").unwrap(), 2) {
+        Ok(_) => panic!("expected an error"),
+        Err(WalletError::RemoveCoinNegativeBalanceError { current_amount, .. }) => {
+            assert_eq!(current_amount, Amount::parse("-50.5").unwrap());
+            assert_eq!(wallet.get_overdraft_policy(), policy);
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
     }
+    assert_eq!(wallet.get_balance(), Amount::parse("-50.5").unwrap());
 }
 
 #[test]
-fn remove_coins_unsuccessful_overflow_max() {
+fn set_overdraft_policy_records_changes() {
+    let mut wallet: Wallet = Wallet::new(
+        Amount::from_integer(0)
+    );
+    assert_eq!(wallet.get_overdraft_policy(), OverdraftPolicy::NoOverdraft);
+    assert!(wallet.set_overdraft_policy(OverdraftPolicy::Unlimited, "admin".to_string(), 1).is_ok());
+    assert!(wallet.remove_coins(Amount::from_integer(100), 2).is_ok());
+    assert!(wallet.set_overdraft_policy(OverdraftPolicy::NoOverdraft, "auditor".to_string(), 2).is_ok());
+    // The balance already below the floor is kept, but it can not go further down
+    assert!(wallet.remove_coins(Amount::from_integer(1), 2).is_err());
+    assert_eq!(wallet.get_balance(), Amount::from_integer(-100));
+    assert_eq!(wallet.get_overdraft_policy_changes(), vec![
+        OverdraftPolicyChange::new(OverdraftPolicy::NoOverdraft, OverdraftPolicy::Unlimited, "admin".to_string(), 1),
+        OverdraftPolicyChange::new(OverdraftPolicy::Unlimited, OverdraftPolicy::NoOverdraft, "auditor".to_string(), 2),
+    ]);
+    let json = serde_json::to_string(&wallet).unwrap();
+    let deserialized: Wallet = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized.get_overdraft_policy(), OverdraftPolicy::NoOverdraft);
+    assert_eq!(deserialized.get_overdraft_policy_changes(), wallet.get_overdraft_policy_changes());
+}
+
+#[test]
+fn set_overdraft_policy_unsuccessful_positive_floor() {
     let mut wallet: Wallet = Wallet::new(
-        i64::min_value()
+        Amount::from_integer(100)
     );
-    match wallet.remove_coins(10, true) {
-        Ok(_) => assert!(false),
-        Err(WalletError::RemoveCoinOverflowError {..}) => assert!(true),
-        Err(_) => assert!(false)
+    match wallet.set_overdraft_policy(OverdraftPolicy::Limit { floor: Amount::from_integer(10) }, "admin".to_string(), 1) {
+        Ok(_) => panic!("expected an error"),
+        Err(WalletError::PositiveOverdraftFloorError { floor }) => assert_eq!(floor, Amount::from_integer(10)),
+        Err(error) => panic!("unexpected error: {:?}", error)
     }
-    match wallet.remove_coins(10, false) {
-        Ok(_) => assert!(false),
-        Err(WalletError::RemoveCoinOverflowError {..}) => assert!(true),
-        Err(_) => assert!(false)
+    assert_eq!(wallet.get_overdraft_policy(), OverdraftPolicy::NoOverdraft);
+    assert!(wallet.get_overdraft_policy_changes().is_empty());
+}
+#[test]
+fn asset_coins_successful_kept_apart() {
+    let mut wallet: Wallet = Wallet::new(
+        Amount::from_integer(100)
+    );
+    assert!(wallet.set_overdraft_policy(OverdraftPolicy::Unlimited, "admin".to_string(), 1).is_ok());
+    assert!(wallet.add_asset_coins("GOLD", Amount::from_integer(10)).is_ok());
+    assert!(wallet.remove_asset_coins("SILVER", Amount::from_integer(5), 2).is_ok());
+    assert_eq!(wallet.get_balance(), Amount::from_integer(100));
+    assert_eq!(wallet.get_asset_balance(DEFAULT_ASSET), Amount::from_integer(100));
+    assert_eq!(wallet.get_asset_balance("GOLD"), Amount::from_integer(10));
+    assert_eq!(wallet.get_asset_balance("SILVER"), Amount::from_integer(-5));
+    assert_eq!(wallet.get_balances().len(), 3);
+}
+
+#[test]
+fn remove_asset_coins_unsuccessful_negative_balance() {
+    let mut wallet: Wallet = Wallet::new(
+        Amount::from_integer(100)
+    );
+    match wallet.remove_asset_coins("GOLD", Amount::from_integer(10), 2) {
+        Ok(_) => panic!("expected an error"),
+        Err(WalletError::RemoveCoinNegativeBalanceError { asset, current_amount, .. }) => {
+            assert_eq!(asset, "GOLD");
+            assert_eq!(current_amount, Amount::from_integer(0));
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
     }
 }
 
 #[test]
-fn remove_coins_unsuccessful_negative_balance() {
+fn add_asset_coins_unsuccessful_overflow_reports_asset() {
+    let mut wallet: Wallet = serde_json::from_str(r#"{"balances":{"GOLD":"170141183460469231731687303715884105727"}}"#).unwrap();
+    match wallet.add_asset_coins("GOLD", Amount::from_integer(1)) {
+        Ok(_) => panic!("expected an error"),
+        Err(WalletError::AddCoinOverflowError { asset, .. }) => assert_eq!(asset, "GOLD"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+}
+
+#[test]
+fn asset_coins_unsuccessful_empty_asset() {
     let mut wallet: Wallet = Wallet::new(
-        0
+        Amount::from_integer(0)
     );
-    match wallet.remove_coins(10, false) {
-        Ok(_) => assert!(false),
-        Err(WalletError::RemoveCoinNegativeBalanceError {..}) => assert!(true),
-        Err(_) => assert!(false)
+    assert!(matches!(wallet.add_asset_coins("", Amount::from_integer(10)), Err(WalletError::EmptyAssetError)));
+}
+
+#[test]
+fn deserialize_single_asset_wallet() {
+    let wallet: Wallet = serde_json::from_str(r#"{"balance":100}"#).unwrap();
+    assert_eq!(wallet.get_balance(), Amount::from_integer(100));
+    assert_eq!(wallet.get_balances().len(), 1);
+    let json = serde_json::to_string(&wallet).unwrap();
+    assert_eq!(json, r#"{"balances":{"BNC":"100"},"overdraft_policy":"NoOverdraft"}"#);
+    let wallet: Wallet = serde_json::from_str(&json).unwrap();
+    assert_eq!(wallet.get_asset_balance(DEFAULT_ASSET), Amount::from_integer(100));
+}
+
+#[test]
+fn place_hold_successful() {
+    let mut wallet: Wallet = Wallet::new(
+        Amount::from_integer(100)
+    );
+    assert!(wallet.place_hold("hold_0001".to_string(), Amount::from_integer(30), 1, 10).is_ok());
+    assert_eq!(wallet.get_balance(), Amount::from_integer(100));
+    assert_eq!(wallet.get_available_balance(2).unwrap(), Amount::from_integer(70));
+    assert_eq!(wallet.get_hold("hold_0001"),
+               Some(Hold::new("hold_0001".to_string(), DEFAULT_ASSET.to_string(), Amount::from_integer(30), 1, 11)));
+    match wallet.remove_coins(Amount::from_integer(71), 2) {
+        Ok(_) => panic!("expected an error"),
+        Err(WalletError::RemoveCoinNegativeBalanceError { current_amount, held_amount, .. }) => {
+            assert_eq!(current_amount, Amount::from_integer(100));
+            assert_eq!(held_amount, Amount::from_integer(30));
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(wallet.remove_coins(Amount::from_integer(70), 2).is_ok());
+    assert!(wallet.get_available_balance(2).unwrap().is_zero());
+}
+
+#[test]
+fn place_hold_unsuccessful() {
+    let mut wallet: Wallet = Wallet::new(
+        Amount::from_integer(100)
+    );
+    assert!(matches!(wallet.place_hold("hold_0001".to_string(), Amount::from_integer(0), 1, 10), Err(WalletError::InvalidHoldAmountError { .. })));
+    match wallet.place_hold("hold_0001".to_string(), Amount::from_integer(101), 1, 10) {
+        Ok(_) => panic!("expected an error"),
+        Err(WalletError::HoldNegativeBalanceError { available_amount, held_amount, .. }) => {
+            assert_eq!(available_amount, Amount::from_integer(100));
+            assert_eq!(held_amount, Amount::from_integer(101));
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(wallet.place_hold("hold_0001".to_string(), Amount::from_integer(60), 1, 10).is_ok());
+    match wallet.place_hold("hold_0001".to_string(), Amount::from_integer(10), 2, 10) {
+        Ok(_) => panic!("expected an error"),
+        Err(WalletError::DuplicateHoldError { hold_id }) => assert_eq!(hold_id, "hold_0001"),
+        Err(error) => panic!("unexpected error: {:?}", error)
     }
-}
\ No newline at end of file
+    assert!(wallet.place_hold("hold_0002".to_string(), Amount::from_integer(41), 2, 10).is_err());
+    assert_eq!(wallet.get_available_balance(2).unwrap(), Amount::from_integer(40));
+}
+
+#[test]
+fn capture_and_release_hold() {
+    let mut wallet: Wallet = Wallet::new(
+        Amount::from_integer(100)
+    );
+    assert!(wallet.place_hold("hold_0001".to_string(), Amount::from_integer(30), 1, 10).is_ok());
+    assert!(wallet.place_hold("hold_0002".to_string(), Amount::from_integer(20), 1, 10).is_ok());
+    match wallet.capture_hold("hold_0001", "GOLD", Amount::from_integer(10), 2) {
+        Ok(_) => panic!("expected an error"),
+        Err(WalletError::HoldAssetMismatchError { hold_asset, .. }) => assert_eq!(hold_asset, DEFAULT_ASSET),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    match wallet.capture_hold("hold_0001", DEFAULT_ASSET, Amount::from_integer(31), 2) {
+        Ok(_) => panic!("expected an error"),
+        Err(WalletError::CaptureExceedsHoldError { held_amount, .. }) => assert_eq!(held_amount, Amount::from_integer(30)),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(wallet.capture_hold("hold_0001", DEFAULT_ASSET, Amount::from_integer(25), 2).is_ok());
+    assert_eq!(wallet.get_balance(), Amount::from_integer(75));
+    assert_eq!(wallet.get_available_balance(2).unwrap(), Amount::from_integer(55));
+    assert_eq!(wallet.release_hold("hold_0002", 3).unwrap().get_amount(), Amount::from_integer(20));
+    assert_eq!(wallet.get_available_balance(3).unwrap(), Amount::from_integer(75));
+    match wallet.release_hold("hold_0002", 3) {
+        Ok(_) => panic!("expected an error"),
+        Err(WalletError::HoldNotFoundError { hold_id }) => assert_eq!(hold_id, "hold_0002"),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+}
+
+#[test]
+fn holds_expire() {
+    let mut wallet: Wallet = Wallet::new(
+        Amount::from_integer(100)
+    );
+    assert!(wallet.place_hold("hold_0001".to_string(), Amount::from_integer(100), 1, 10).is_ok());
+    // An expired hold is released before a new hold is placed
+    assert!(wallet.place_hold("hold_0002".to_string(), Amount::from_integer(100), 11, 10).is_ok());
+    assert!(wallet.get_hold("hold_0001").is_none());
+    assert!(matches!(wallet.capture_hold("hold_0002", DEFAULT_ASSET, Amount::from_integer(10), 21), Err(WalletError::HoldNotFoundError { .. })));
+    assert_eq!(wallet.get_available_balance(21).unwrap(), Amount::from_integer(100));
+    assert!(wallet.place_hold("hold_0003".to_string(), Amount::from_integer(10), 30, 10).is_ok());
+    let json = serde_json::to_string(&wallet).unwrap();
+    let mut deserialized: Wallet = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized.get_holds(), wallet.get_holds());
+    assert_eq!(deserialized.expire_holds(40).len(), 1);
+    assert_eq!(deserialized.get_available_balance(40).unwrap(), Amount::from_integer(100));
+}
+
+#[test]
+fn remove_coins_successful_after_hold_expired() {
+    let mut wallet: Wallet = Wallet::new(
+        Amount::from_integer(100)
+    );
+    assert!(wallet.place_hold("hold_0001".to_string(), Amount::from_integer(80), 1, 10).is_ok());
+    match wallet.remove_coins(Amount::from_integer(30), 10) {
+        Ok(_) => panic!("expected an error"),
+        Err(WalletError::RemoveCoinNegativeBalanceError { held_amount, .. }) => assert_eq!(held_amount, Amount::from_integer(80)),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    // The expired hold no longer reduces the available balance, without being released beforehand
+    assert_eq!(wallet.get_held_asset_amount(DEFAULT_ASSET, 11).unwrap(), Amount::from_integer(0));
+    assert_eq!(wallet.get_available_balance(11).unwrap(), Amount::from_integer(100));
+    assert!(wallet.remove_coins(Amount::from_integer(30), 11).is_ok());
+    assert_eq!(wallet.get_balance(), Amount::from_integer(70));
+    assert!(wallet.get_holds().is_empty());
+}
+
+#[test]
+fn unsuccessful_operations_keep_expired_holds() {
+    let mut wallet: Wallet = Wallet::new(
+        Amount::from_integer(100)
+    );
+    assert!(wallet.place_hold("hold_0001".to_string(), Amount::from_integer(80), 1, 10).is_ok());
+    assert!(matches!(wallet.remove_coins(Amount::from_integer(101), 11), Err(WalletError::RemoveCoinNegativeBalanceError { .. })));
+    assert!(matches!(wallet.remove_coins(Amount::parse("0.000000001").unwrap(), 11), Err(WalletError::AssetPrecisionError { .. })));
+    assert!(matches!(wallet.place_hold("hold_0002".to_string(), Amount::from_integer(101), 11, 10), Err(WalletError::HoldNegativeBalanceError { .. })));
+    assert!(matches!(wallet.release_hold("hold_0002", 11), Err(WalletError::HoldNotFoundError { .. })));
+    assert!(matches!(wallet.capture_hold("hold_0001", DEFAULT_ASSET, Amount::from_integer(10), 11), Err(WalletError::HoldNotFoundError { .. })));
+    assert!(wallet.get_hold("hold_0001").is_some());
+    // An expired hold does not prevent a new hold with the same id
+    assert!(wallet.place_hold("hold_0001".to_string(), Amount::from_integer(20), 11, 10).is_ok());
+    assert_eq!(wallet.get_hold("hold_0001").unwrap().get_amount(), Amount::from_integer(20));
+    assert_eq!(wallet.get_holds().len(), 1);
+}
+
+#[test]
+fn held_amount_overflow() {
+    let mut wallet: Wallet = Wallet::new(
+        Amount::zero()
+    );
+    assert!(wallet.set_overdraft_policy(OverdraftPolicy::Unlimited, "admin".to_string(), 1).is_ok());
+    assert!(wallet.place_hold("hold_0001".to_string(), Amount::new(i128::MAX, 0).unwrap(), 1, 10).is_ok());
+    assert!(wallet.place_hold("hold_0002".to_string(), Amount::from_integer(1), 1, 10).is_ok());
+    match wallet.get_available_balance(2) {
+        Ok(_) => panic!("expected an error"),
+        Err(WalletError::HeldAmountOverflowError { asset }) => assert_eq!(asset, DEFAULT_ASSET),
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(wallet.remove_coins(Amount::from_integer(1), 2).is_err());
+}
+
+#[test]
+fn asset_precision_enforced() {
+    let mut wallet: Wallet = Wallet::new(
+        Amount::zero()
+    );
+    assert!(wallet.add_coins(Amount::parse("0.00000001").unwrap()).is_ok());
+    match wallet.add_coins(Amount::parse("0.000000001").unwrap()) {
+        Ok(_) => panic!("expected an error"),
+        Err(WalletError::AssetPrecisionError { asset, decimals, .. }) => {
+            assert_eq!(asset, DEFAULT_ASSET);
+            assert_eq!(decimals, DEFAULT_ASSET_DECIMALS);
+        }
+        Err(error) => panic!("unexpected error: {:?}", error)
+    }
+    assert!(wallet.remove_coins(Amount::parse("0.000000005").unwrap(), 2).is_err());
+    assert!(wallet.place_hold("hold_0001".to_string(), Amount::parse("0.000000005").unwrap(), 1, 10).is_err());
+    assert_eq!(wallet.get_balance(), Amount::parse("0.00000001").unwrap());
+    // The other assets have the default definition of their code: the ledger checks them against its asset registry
+    assert!(wallet.add_asset_coins("WALLET_CENTS", Amount::parse("0.000000001").unwrap()).is_ok());
+}
@@ -0,0 +1,299 @@
+extern crate banana_coin;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use banana_coin::api::{HttpRequest, HttpResponse, HttpServer};
+use banana_coin::error::WebhookError;
+use banana_coin::ledger::Ledger;
+use banana_coin::model::*;
+use banana_coin::model::TransactionState::*;
+use banana_coin::transfer::TransferExecutor;
+use banana_coin::webhook::*;
+
+struct StubServer {
+    server: HttpServer,
+    requests: Arc<Mutex<Vec<HttpRequest>>>,
+    status: Arc<AtomicU16>,
+}
+
+impl StubServer {
+    fn start(status: u16) -> StubServer {
+        StubServer::start_with_delay(status, Duration::from_millis(0))
+    }
+
+    fn start_with_delay(status: u16, delay: Duration) -> StubServer {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let status = Arc::new(AtomicU16::new(status));
+        let (server_requests, server_status) = (Arc::clone(&requests), Arc::clone(&status));
+        let server = HttpServer::start("127.0.0.1:0", move |request| {
+            server_requests.lock().unwrap().push(request.unwrap());
+            thread::sleep(delay);
+            HttpResponse::empty(server_status.load(Ordering::SeqCst))
+        }).unwrap();
+        StubServer { server, requests, status }
+    }
+
+    fn url(&self) -> String {
+        format!("http://{}/webhooks?partner=1", self.server.get_address())
+    }
+
+    fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn new_transaction(id: &str, amount: i64) -> Transaction {
+    Transaction::new(
+        id.to_string(),
+        "entity_001".to_string(),
+        "entity_002".to_string(),
+        Amount::from_integer(amount),
+        "Moving coins.".to_string(),
+        Created {timestamp: 1},
+        vec![Created {timestamp: 1}],
+    ).unwrap()
+}
+
+fn new_ledger(dispatcher: &WebhookDispatcher) -> Ledger {
+    let mut ledger = Ledger::new(TransferExecutor::new());
+    ledger.get_event_bus().subscribe(dispatcher.clone());
+    ledger.register_entity(Entity::new("entity_001".to_string(), "john".to_string(), Wallet::new(Amount::from_integer(100)))).unwrap();
+    ledger.register_entity(Entity::new("entity_002".to_string(), "jane".to_string(), Wallet::new(Amount::from_integer(0)))).unwrap();
+    ledger
+}
+
+#[test]
+fn webhook_delivers_signed_transaction() {
+    let stub = StubServer::start(200);
+    let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    dispatcher.register_endpoint("entity_002".to_string(), stub.url(), "jane_secret".to_string()).unwrap();
+    let mut ledger = new_ledger(&dispatcher);
+    ledger.execute_transaction(new_transaction("id_0001", 30), 2).unwrap();
+    // Only the entity with an endpoint is notified, and only of the final state
+    assert_eq!(dispatcher.get_pending_deliveries().len(), 1);
+
+    let attempts = dispatcher.process(5);
+    assert_eq!(attempts.len(), 1);
+    assert!(attempts[0].is_successful());
+    assert_eq!(attempts[0].get_status(), Some(200));
+    assert_eq!(attempts[0].get_url(), Some(stub.url().as_str()));
+    assert!(dispatcher.get_pending_deliveries().is_empty());
+
+    let requests = stub.requests();
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    assert_eq!(request.get_method(), "POST");
+    assert_eq!(request.get_path(), "/webhooks");
+    assert_eq!(request.get_query(), Some("partner=1"));
+    assert_eq!(request.get_header(DELIVERY_HEADER), Some(attempts[0].get_delivery_id()));
+    assert_eq!(request.get_header(TIMESTAMP_HEADER), Some("5"));
+    let payload = std::str::from_utf8(request.get_body()).unwrap();
+    let signature = request.get_header(SIGNATURE_HEADER).unwrap();
+    assert!(verify_payload("jane_secret", 5, payload, signature));
+    assert!(!verify_payload("john_secret", 5, payload, signature));
+    let transaction: Transaction = serde_json::from_str(payload).unwrap();
+    assert_eq!(transaction.get_id(), "id_0001");
+    assert_eq!(transaction.get_current_state(), Completed {timestamp: 2});
+}
+
+#[test]
+fn webhook_retries_with_backoff_then_dead_letters() {
+    let stub = StubServer::start(503);
+    let dispatcher = WebhookDispatcher::new(RetryPolicy::new(3, 10, 15));
+    dispatcher.register_endpoint("entity_001".to_string(), stub.url(), "john_secret".to_string()).unwrap();
+    let mut ledger = new_ledger(&dispatcher);
+    assert!(ledger.execute_transaction(new_transaction("id_0001", 150), 2).is_err());
+
+    let attempts = dispatcher.process(2);
+    assert_eq!(attempts.len(), 1);
+    match attempts[0].get_error() {
+        Some(WebhookError::UnexpectedStatusError { status }) => assert_eq!(status, 503),
+        _ => panic!("unexpected result"),
+    }
+    assert_eq!(dispatcher.get_pending_deliveries()[0].get_next_attempt_at(), 12);
+    assert!(dispatcher.process(11).is_empty());
+    assert_eq!(dispatcher.process(12).len(), 1);
+    // The backoff doubles up to the max backoff
+    assert_eq!(dispatcher.get_pending_deliveries()[0].get_next_attempt_at(), 27);
+    assert_eq!(dispatcher.process(27)[0].get_attempt(), 3);
+    assert!(dispatcher.get_pending_deliveries().is_empty());
+    let dead_letters = dispatcher.get_dead_letters();
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0].get_transaction_id(), "id_0001");
+    assert_eq!(dead_letters[0].get_attempts(), 3);
+    assert_eq!(stub.requests().len(), 3);
+
+    stub.status.store(204, Ordering::SeqCst);
+    dispatcher.retry_dead_letter(dead_letters[0].get_id(), 100).unwrap();
+    assert!(dispatcher.get_dead_letters().is_empty());
+    let attempts = dispatcher.process(100);
+    assert!(attempts[0].is_successful());
+    assert_eq!(attempts[0].get_attempt(), 1);
+    let log = dispatcher.get_delivery_log();
+    assert_eq!(log.len(), 4);
+    assert!(log.iter().all(|attempt| attempt.get_delivery_id() == dead_letters[0].get_id()));
+    assert!(matches!(dispatcher.retry_dead_letter(dead_letters[0].get_id(), 100), Err(WebhookError::DeadLetterNotFoundError { .. })));
+}
+
+#[test]
+fn webhook_unsuccessful_deliveries() {
+    let closed_address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let dispatcher = WebhookDispatcher::new(RetryPolicy::new(2, 1, 1));
+    dispatcher.register_endpoint("entity_001".to_string(), format!("http://{}/", closed_address), "secret".to_string()).unwrap();
+    dispatcher.register_endpoint("entity_002".to_string(), format!("http://{}/", closed_address), "secret".to_string()).unwrap();
+    let mut ledger = new_ledger(&dispatcher);
+    ledger.execute_transaction(new_transaction("id_0001", 10), 2).unwrap();
+    assert_eq!(dispatcher.get_pending_deliveries().len(), 2);
+
+    // A delivery to an entity without endpoint is dead-lettered at once
+    dispatcher.unregister_endpoint("entity_002").unwrap();
+    let attempts = dispatcher.process(2);
+    match attempts[0].get_error() {
+        Some(WebhookError::IoError { .. }) => {},
+        _ => panic!("unexpected result"),
+    }
+    assert_eq!(attempts[0].get_status(), None);
+    match attempts[1].get_error() {
+        Some(WebhookError::EndpointNotFoundError { entity_id }) => assert_eq!(entity_id, "entity_002"),
+        _ => panic!("unexpected result"),
+    }
+    assert_eq!(dispatcher.get_dead_letters().len(), 1);
+    dispatcher.process(3);
+    assert_eq!(dispatcher.get_dead_letters().len(), 2);
+
+    for url in ["https://example.com/webhooks", "http://example.com\r\nX-Injected: 1/webhooks", "http://exa mple.com/webhooks"] {
+        assert!(matches!(dispatcher.register_endpoint("entity_001".to_string(), url.to_string(), "secret".to_string()), Err(WebhookError::InvalidUrlError { .. })));
+    }
+    assert!(matches!(dispatcher.unregister_endpoint("entity_002"), Err(WebhookError::EndpointNotFoundError { .. })));
+}
+
+#[test]
+fn webhook_unsuccessful_endless_status_line() {
+    // An endpoint answering with a status line that never ends is not read without end
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0; 1024];
+        let _ = stream.read(&mut request);
+        let _ = stream.write_all(&vec![b'A'; 64 * 1024]);
+        thread::sleep(Duration::from_millis(200));
+    });
+    let dispatcher = WebhookDispatcher::new(RetryPolicy::new(1, 1, 1));
+    dispatcher.register_endpoint("entity_001".to_string(), format!("http://{}/", address), "secret".to_string()).unwrap();
+    let mut transaction = new_transaction("id_0001", 10);
+    transaction.transition_to(Failed {timestamp: 1}).unwrap();
+    dispatcher.enqueue(&transaction);
+    let attempts = dispatcher.process(2);
+    match attempts[0].get_error() {
+        Some(WebhookError::MalformedResponseError { message }) => assert!(message.contains("status line longer")),
+        _ => panic!("unexpected result"),
+    }
+    server.join().unwrap();
+}
+
+#[test]
+fn webhook_notifies_blocked_and_released_escrow() {
+    let stub = StubServer::start(200);
+    let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    dispatcher.register_endpoint("entity_002".to_string(), stub.url(), "jane_secret".to_string()).unwrap();
+    let mut ledger = new_ledger(&dispatcher);
+    ledger.open_escrow(new_transaction("id_0001", 40), ReleaseCondition::Deadline { deadline: 10 }, 2).unwrap();
+    ledger.release_escrow("id_0001", 10).unwrap();
+    assert_eq!(dispatcher.process(10).len(), 2);
+    let states: Vec<String> = stub.requests()
+        .iter()
+        .map(|request| serde_json::from_slice::<Transaction>(request.get_body()).unwrap().get_current_state().get_name().to_string())
+        .collect();
+    assert_eq!(states, vec!["Blocked", "Completed"]);
+}
+
+#[test]
+fn webhook_worker_processes_deliveries() {
+    let stub = StubServer::start(200);
+    let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    dispatcher.register_endpoint("entity_001".to_string(), stub.url(), "secret".to_string()).unwrap();
+    let worker = dispatcher.start_worker(Duration::from_millis(20));
+    let mut transaction = new_transaction("id_0001", 10);
+    transaction.transition_to(Failed {timestamp: 1}).unwrap();
+    dispatcher.enqueue(&transaction);
+
+    let start = Instant::now();
+    while stub.requests().is_empty() && start.elapsed() < Duration::from_secs(5) {
+        thread::sleep(Duration::from_millis(10));
+    }
+    worker.shutdown();
+    assert_eq!(stub.requests().len(), 1);
+    assert_eq!(dispatcher.get_delivery_log().len(), 1);
+}
+
+#[test]
+fn webhook_delivers_to_endpoints_in_parallel() {
+    let slow_stub = StubServer::start_with_delay(200, Duration::from_millis(800));
+    let other_slow_stub = StubServer::start_with_delay(200, Duration::from_millis(800));
+    let dispatcher = WebhookDispatcher::new(RetryPolicy::default());
+    dispatcher.register_endpoint("entity_001".to_string(), slow_stub.url(), "john_secret".to_string()).unwrap();
+    dispatcher.register_endpoint("entity_002".to_string(), other_slow_stub.url(), "jane_secret".to_string()).unwrap();
+    let mut ledger = new_ledger(&dispatcher);
+    ledger.execute_transaction(new_transaction("id_0001", 10), 2).unwrap();
+
+    let start = Instant::now();
+    let attempts = dispatcher.process(2);
+    assert!(start.elapsed() < Duration::from_millis(1500));
+    // The attempts are returned in the order the deliveries were queued
+    let entity_ids: Vec<&str> = attempts.iter().map(|attempt| attempt.get_entity_id()).collect();
+    assert_eq!(entity_ids, vec!["entity_001", "entity_002"]);
+    assert!(attempts.iter().all(|attempt| attempt.is_successful()));
+}
+
+#[test]
+fn webhook_unreachable_endpoint_postpones_its_deliveries() {
+    let closed_address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let dispatcher = WebhookDispatcher::new(RetryPolicy::new(3, 1, 1));
+    dispatcher.register_endpoint("entity_001".to_string(), format!("http://{}/", closed_address), "secret".to_string()).unwrap();
+    for id in ["id_0001", "id_0002", "id_0003"] {
+        let mut transaction = new_transaction(id, 10);
+        transaction.transition_to(Failed {timestamp: 1}).unwrap();
+        dispatcher.enqueue(&transaction);
+    }
+
+    let attempts = dispatcher.process(2);
+    assert_eq!(attempts.len(), 1);
+    assert_eq!(attempts[0].get_delivery_id(), "dlv_000001");
+    let pending: Vec<u32> = dispatcher.get_pending_deliveries().iter().map(|delivery| delivery.get_attempts()).collect();
+    assert_eq!(pending.len(), 3);
+    assert_eq!(pending.iter().filter(|attempts| **attempts == 0).count(), 2);
+}
+
+#[test]
+fn webhook_bounds_delivery_log_and_dead_letters() {
+    let stub = StubServer::start(503);
+    let dispatcher = WebhookDispatcher::new(RetryPolicy::new(1, 1, 1))
+        .with_max_delivery_log_length(2)
+        .with_max_dead_letters(1);
+    dispatcher.register_endpoint("entity_001".to_string(), stub.url(), "john_secret".to_string()).unwrap();
+    dispatcher.register_endpoint("entity_002".to_string(), stub.url(), "jane_secret".to_string()).unwrap();
+    for id in ["id_0001", "id_0002", "id_0003"] {
+        let mut transaction = new_transaction(id, 10);
+        transaction.transition_to(Failed {timestamp: 1}).unwrap();
+        dispatcher.enqueue(&transaction);
+    }
+
+    assert_eq!(dispatcher.process(2).len(), 6);
+    let delivery_log = dispatcher.get_delivery_log();
+    assert_eq!(delivery_log.len(), 2);
+    let delivery_ids: Vec<&str> = delivery_log.iter().map(|attempt| attempt.get_delivery_id()).collect();
+    assert_eq!(delivery_ids, vec!["dlv_000005", "dlv_000006"]);
+    // Only the most recent dead letter of every entity is kept
+    let dead_letters: Vec<(String, String)> = dispatcher.get_dead_letters()
+        .iter()
+        .map(|delivery| (delivery.get_entity_id().to_string(), delivery.get_transaction_id().to_string()))
+        .collect();
+    assert_eq!(dead_letters.len(), 2);
+    assert!(dead_letters.contains(&("entity_001".to_string(), "id_0003".to_string())));
+    assert!(dead_letters.contains(&("entity_002".to_string(), "id_0003".to_string())));
+}